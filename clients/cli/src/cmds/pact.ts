@@ -0,0 +1,97 @@
+import fs from 'node:fs';
+import { Command } from 'commander';
+import * as ed from '@noble/ed25519';
+import { canonicalize } from '../utils/canon.js';
+import { blake3hex } from '../utils/hash.js';
+
+// Formatos de ubl-pact/src/offline.rs
+const REQUEST_FORMAT = 'ubl.pact.signing_request.v1';
+const SIGNATURE_FORMAT = 'ubl.pact.signature.v1';
+
+// Mesmos bytes de ubl_pact::PactProof::signing_bytes:
+// "ubl:pact:v1\n" || u32be(len) || pact_id || u32be(len) || link_hash || u64be(nonce)
+function signingBytes(linkHash: string, pactId: string, nonce: bigint): Uint8Array {
+  const field = (s: string) => {
+    const bytes = Buffer.from(s, 'utf8');
+    const len = Buffer.alloc(4);
+    len.writeUInt32BE(bytes.length);
+    return Buffer.concat([len, bytes]);
+  };
+  const n = Buffer.alloc(8);
+  n.writeBigUInt64BE(nonce);
+  return Buffer.concat([Buffer.from('ubl:pact:v1\n', 'utf8'), field(pactId), field(linkHash.toLowerCase()), n]);
+}
+
+// Recalcula o hash do pedido e confere os campos; devolve a lista de erros
+function checkRequest(file: any, now: number): string[] {
+  const errors: string[] = [];
+  const req = file?.request;
+  if (!req || typeof file.request_hash !== 'string') return ['arquivo sem request/request_hash'];
+  if (req.format !== REQUEST_FORMAT) errors.push(`formato nao suportado: ${req.format}`);
+  const computed = blake3hex(Buffer.from(canonicalize(req), 'utf8'));
+  if (computed !== file.request_hash) errors.push(`request_hash divergente: declarado ${file.request_hash}, calculado ${computed}`);
+  if (!/^[0-9a-f]{64}$/.test(String(req.link_hash))) errors.push('link_hash deve ser hex32 minusculo');
+  if (now > Number(req.not_after)) errors.push(`pedido expirado (not_after=${req.not_after})`);
+  return errors;
+}
+
+export function pactCommands(){
+  const cmd = new Command('pact').description('Cerimônias de pacto offline (signatários air-gapped)');
+
+  cmd.command('offline:verify')
+    .requiredOption('--request <file>', 'pedido exportado (signing request)')
+    .action((opts)=>{
+      const file = JSON.parse(fs.readFileSync(opts.request, 'utf8'));
+      const errors = checkRequest(file, Math.floor(Date.now()/1000));
+      const req = file.request ?? {};
+      console.log(JSON.stringify({
+        ok: errors.length === 0,
+        errors,
+        request_hash: file.request_hash,
+        pact_id: req.pact_id,
+        container_id: req.container_id,
+        link_hash: req.link_hash,
+        nonce: req.nonce ?? 0,
+        intent_class: req.intent_class,
+        risk_level: req.risk_level,
+        threshold: req.threshold,
+        signers: req.signers,
+        weights: req.weights,
+        groups: req.groups,
+      }, null, 2));
+      if (errors.length) process.exitCode = 1;
+    });
+
+  cmd.command('offline:sign')
+    .requiredOption('--request <file>', 'pedido exportado (signing request)')
+    .requiredOption('--priv <hex>', 'chave privada Ed25519 do signatário')
+    .option('--out <file>', 'arquivo de assinatura (default: stdout)')
+    .action(async (opts)=>{
+      const file = JSON.parse(fs.readFileSync(opts.request, 'utf8'));
+      const errors = checkRequest(file, Math.floor(Date.now()/1000));
+      const priv = Buffer.from(opts.priv.replace(/^0x/,''), 'hex');
+      const pubkey = Buffer.from(await ed.getPublicKeyAsync(priv)).toString('hex');
+      if (!Array.isArray(file.request?.signers) || !file.request.signers.includes(pubkey)) {
+        errors.push(`chave ${pubkey} nao e signataria do pacto`);
+      }
+      if (errors.length) {
+        console.error(JSON.stringify({ ok: false, errors }, null, 2));
+        process.exitCode = 1;
+        return;
+      }
+      const req = file.request;
+      const sig = await ed.signAsync(signingBytes(req.link_hash, req.pact_id, BigInt(req.nonce ?? 0)), priv);
+      const out = JSON.stringify({
+        format: SIGNATURE_FORMAT,
+        request_hash: file.request_hash,
+        pact_id: req.pact_id,
+        link_hash: req.link_hash,
+        nonce: req.nonce ?? 0,
+        pubkey,
+        signature: Buffer.from(sig).toString('hex'),
+      }, null, 2);
+      if (opts.out) fs.writeFileSync(opts.out, out); else console.log(out);
+    });
+
+  return cmd;
+}
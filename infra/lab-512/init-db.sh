@@ -29,7 +29,7 @@ psql -v ON_ERROR_STOP=1 --username "$POSTGRES_USER" --dbname "$POSTGRES_DB" <<-E
 EOSQL
 
 # Run migration files
-for sql_file in /sql/00*.sql; do
+for sql_file in /sql/00*.sql /sql/02*.sql; do
     if [ -f "$sql_file" ]; then
         echo "📄 Running $(basename $sql_file)..."
         psql -v ON_ERROR_STOP=1 --username "$POSTGRES_USER" --dbname "$POSTGRES_DB" -f "$sql_file"
@@ -0,0 +1,157 @@
+//! # UBL Errors Derive
+//!
+//! `#[derive(ErrorCatalog)]` for the error enums listed by `GET /errors`.
+//! Use it through `ubl-errors`, which documents the `#[catalog(...)]`
+//! attributes; the generated code names `::ubl_errors`.
+
+#![deny(unsafe_code)]
+#![warn(missing_docs)]
+
+use std::collections::HashSet;
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::parse::ParseStream;
+use syn::{parse_macro_input, Attribute, Data, DeriveInput, Expr, ExprLit, Fields, Lit, LitInt, LitStr, Meta};
+
+/// Implement `ubl_errors::ErrorCatalog` for an enum
+#[proc_macro_derive(ErrorCatalog, attributes(catalog))]
+pub fn derive_error_catalog(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input).unwrap_or_else(syn::Error::into_compile_error).into()
+}
+
+/// `#[catalog(...)]` on the enum or on one variant
+#[derive(Default)]
+struct Settings {
+    source: Option<LitStr>,
+    code: Option<LitStr>,
+    status: Option<LitInt>,
+}
+
+fn settings(attrs: &[Attribute]) -> syn::Result<Settings> {
+    let mut settings = Settings::default();
+    for attr in attrs.iter().filter(|a| a.path().is_ident("catalog")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("source") {
+                settings.source = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("code") {
+                settings.code = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("status") {
+                let status: LitInt = meta.value()?.parse()?;
+                if !(100..=599).contains(&status.base10_parse::<u16>()?) {
+                    return Err(syn::Error::new_spanned(&status, "not an HTTP status"));
+                }
+                settings.status = Some(status);
+            } else {
+                return Err(meta.error("expected `source`, `code` or `status`"));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(settings)
+}
+
+/// First line of the doc comment
+fn description(attrs: &[Attribute]) -> String {
+    attrs
+        .iter()
+        .filter(|a| a.path().is_ident("doc"))
+        .find_map(|a| match &a.meta {
+            Meta::NameValue(nv) => match &nv.value {
+                Expr::Lit(ExprLit { lit: Lit::Str(s), .. }) => Some(s.value().trim().to_string()),
+                _ => None,
+            },
+            _ => None,
+        })
+        .unwrap_or_default()
+}
+
+/// Template of a thiserror `#[error("...", args)]`, if the variant has one
+fn message(attrs: &[Attribute]) -> Option<String> {
+    let attr = attrs.iter().find(|a| a.path().is_ident("error"))?;
+    attr.parse_args_with(|input: ParseStream| {
+        let template: LitStr = input.parse()?;
+        input.parse::<TokenStream2>()?;
+        Ok(template.value())
+    })
+    .ok()
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let Data::Enum(data) = &input.data else {
+        return Err(syn::Error::new_spanned(&input.ident, "ErrorCatalog is derived for enums only"));
+    };
+    let top = settings(&input.attrs)?;
+    let source = top
+        .source
+        .ok_or_else(|| syn::Error::new_spanned(&input.ident, "missing #[catalog(source = \"...\")]"))?;
+
+    let mut seen = HashSet::new();
+    let mut codes = Vec::new();
+    let mut code_arms = Vec::new();
+    let mut status_arms = Vec::new();
+    for variant in &data.variants {
+        let ident = &variant.ident;
+        let own = settings(&variant.attrs)?;
+        let code = own.code.unwrap_or_else(|| LitStr::new(&ident.to_string(), ident.span()));
+        if !seen.insert(code.value()) {
+            return Err(syn::Error::new_spanned(&code, format!("code {} is used twice", code.value())));
+        }
+        let status = own.status.or_else(|| top.status.clone()).ok_or_else(|| {
+            syn::Error::new_spanned(ident, "missing #[catalog(status = ...)] on the variant or the enum")
+        })?;
+        let (params, pattern): (Vec<String>, _) = match &variant.fields {
+            Fields::Named(f) => (
+                f.named.iter().filter_map(|f| f.ident.as_ref()).map(|i| i.to_string()).collect(),
+                quote!(Self::#ident { .. }),
+            ),
+            Fields::Unnamed(f) => ((0..f.unnamed.len()).map(|i| i.to_string()).collect(), quote!(Self::#ident(..))),
+            Fields::Unit => (Vec::new(), quote!(Self::#ident)),
+        };
+        let name = ident.to_string();
+        let description = description(&variant.attrs);
+        let message = match message(&variant.attrs) {
+            Some(m) => quote!(::core::option::Option::Some(#m)),
+            None => quote!(::core::option::Option::None),
+        };
+        codes.push(quote! {
+            ::ubl_errors::ErrorCode {
+                code: #code,
+                name: #name,
+                status: #status,
+                params: &[#(#params),*],
+                description: #description,
+                message: #message,
+            }
+        });
+        code_arms.push(quote!(#pattern => #code));
+        status_arms.push(quote!(#pattern => #status));
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::ubl_errors::ErrorCatalog for #ident #ty_generics #where_clause {
+            const SOURCE: &'static str = #source;
+
+            fn catalog() -> &'static [::ubl_errors::ErrorCode] {
+                const CODES: &[::ubl_errors::ErrorCode] = &[#(#codes),*];
+                CODES
+            }
+
+            fn code(&self) -> &'static str {
+                match self {
+                    #(#code_arms,)*
+                }
+            }
+
+            fn status(&self) -> u16 {
+                match self {
+                    #(#status_arms,)*
+                }
+            }
+        }
+    })
+}
@@ -0,0 +1,139 @@
+//! # UBL Errors
+//!
+//! The catalog of error codes a deployment can emit, generated from the
+//! error enums that emit them. `#[derive(ErrorCatalog)]` lists each variant
+//! with its code, HTTP status, parameters, doc comment and message, and
+//! gives every value its [`ErrorCatalog::code`] and [`ErrorCatalog::status`].
+//! The server answers with those and serves all families at `GET /errors`,
+//! so a variant added to an enum is in the catalog by construction and SDKs
+//! or docs generated from it cannot drift from the code.
+//!
+//! ```ignore
+//! #[derive(Debug, ErrorCatalog)]
+//! #[catalog(source = "tangency", status = 409)]
+//! pub enum TangencyError {
+//!     /// `previous_hash` is not the container's head
+//!     RealityDrift,
+//!     /// This region is not the primary
+//!     #[catalog(status = 503)]
+//!     Fenced,
+//! }
+//! ```
+//!
+//! ## Attributes
+//! - `source` (enum, required): name of the family, e.g. `membrane`
+//! - `status` (enum default, or per variant): HTTP status the error is answered with
+//! - `code` (variant; default: the variant name): the stable code clients branch on
+//!
+//! Codes are unique within a family; the derive refuses duplicates.
+
+#![deny(unsafe_code)]
+#![warn(missing_docs)]
+
+use serde::Serialize;
+
+pub use ubl_errors_derive::ErrorCatalog;
+
+// The derive names `::ubl_errors`, also in this crate's own tests
+#[cfg(test)]
+extern crate self as ubl_errors;
+
+/// Format tag of the catalog served at `GET /errors`
+pub const CATALOG_FORMAT: &str = "ubl.errors.v1";
+
+/// One error code of a family
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+pub struct ErrorCode {
+    /// Stable code clients branch on (`V4`, `PactExpired`, `amount_exceeded`)
+    pub code: &'static str,
+
+    /// Enum variant it comes from
+    pub name: &'static str,
+
+    /// HTTP status it is answered with
+    pub status: u16,
+
+    /// Fields the error carries, in order (`0`, `1`… for tuple variants)
+    pub params: &'static [&'static str],
+
+    /// First line of the variant's doc comment
+    pub description: &'static str,
+
+    /// Display template (`#[error("...")]`), with the params in braces
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<&'static str>,
+}
+
+/// Every code of one error enum
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct ErrorFamily {
+    /// Family name (`membrane`, `pact`, …)
+    pub source: &'static str,
+
+    /// Codes in declaration order
+    pub codes: &'static [ErrorCode],
+}
+
+/// An error enum listed in the catalog; derive it with `#[derive(ErrorCatalog)]`
+pub trait ErrorCatalog {
+    /// Family name
+    const SOURCE: &'static str;
+
+    /// Every code the enum can emit, in declaration order
+    fn catalog() -> &'static [ErrorCode];
+
+    /// This error's code
+    fn code(&self) -> &'static str;
+
+    /// HTTP status this error is answered with
+    fn status(&self) -> u16;
+
+    /// The enum's family for the catalog
+    fn family() -> ErrorFamily
+    where
+        Self: Sized,
+    {
+        ErrorFamily {
+            source: Self::SOURCE,
+            codes: Self::catalog(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Sample family
+    #[derive(Debug, ErrorCatalog)]
+    #[catalog(source = "sample", status = 422)]
+    #[allow(dead_code)]
+    enum SampleError {
+        /// Nothing to do
+        ///
+        /// Second paragraph, left out.
+        Idle,
+
+        /// Over the limit
+        #[catalog(code = "S2", status = 409)]
+        Over { got: usize, need: usize },
+
+        /// Unknown key
+        Unknown(String),
+    }
+
+    #[test]
+    fn test_derived_catalog() {
+        let family = SampleError::family();
+        assert_eq!(family.source, "sample");
+        let codes: Vec<_> = family.codes.iter().map(|c| (c.code, c.name, c.status)).collect();
+        assert_eq!(codes, [("Idle", "Idle", 422), ("S2", "Over", 409), ("Unknown", "Unknown", 422)]);
+        assert_eq!(family.codes[0].description, "Nothing to do");
+        assert_eq!(family.codes[1].params, ["got", "need"]);
+        assert_eq!(family.codes[2].params, ["0"]);
+
+        let over = SampleError::Over { got: 1, need: 2 };
+        assert_eq!((over.code(), over.status()), ("S2", 409));
+        assert_eq!(SampleError::Unknown("k".to_string()).code(), "Unknown");
+    }
+}
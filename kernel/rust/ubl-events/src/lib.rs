@@ -0,0 +1,238 @@
+//! # UBL Events
+//!
+//! Serde types for everything `ubl-server` sends to consumers, so services
+//! downstream of a ledger deserialize into these instead of keeping their
+//! own copies:
+//!
+//! | Where                                  | Type                          |
+//! |----------------------------------------|-------------------------------|
+//! | SSE `GET /ledger/:container_id/tail`   | [`LedgerRow`] (`ledger_entry`) |
+//! | SSE `GET /ledger/heads/tail`           | [`HeadEvent`] (`head`)         |
+//! | `POST /link/commit` accepted           | [`CommitReceipt`]             |
+//! | `POST /link/commit` rejected (JSON)    | [`CommitRejection`]           |
+//! | `GET /alerts/notifications`            | [`AlertNotification`]         |
+//!
+//! [`StreamEvent::parse`] turns an SSE frame (event name and data) into the
+//! matching type. The server has no webhook or message-broker output today;
+//! when it gains one, its payloads land here first.
+//!
+//! Types are versioned by [`CONTRACT_VERSION`]. Within a version fields are
+//! only added, as `Option` or `#[serde(default)]`, so older consumers keep
+//! parsing; removing or retyping a field is a new version. The server's
+//! contract tests (`ubl-server/src/event_contracts.rs`) serialize its own
+//! response types and require them to round-trip through these exactly.
+
+#![deny(unsafe_code)]
+#![warn(missing_docs)]
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use thiserror::Error;
+
+/// Version of the contract these types describe
+pub const CONTRACT_VERSION: &str = "ubl-events/1";
+
+/// SSE event name of [`LedgerRow`]
+pub const EVENT_LEDGER_ENTRY: &str = "ledger_entry";
+/// SSE event name of [`HeadEvent`]
+pub const EVENT_HEAD: &str = "head";
+
+/// Response header carrying a read-your-writes token (`<container_id>@<sequence>`)
+pub const CONSISTENCY_HEADER: &str = "x-ubl-consistency";
+/// Response header naming the database that answered a read (`primary` or `replica`)
+pub const SERVED_BY_HEADER: &str = "x-ubl-served-by";
+/// Request and response header carrying the region fencing epoch
+pub const FENCE_HEADER: &str = "x-ubl-fence";
+
+/// Errors from reading an event
+#[derive(Error, Debug)]
+pub enum EventError {
+    /// SSE event name this contract does not know
+    #[error("unknown event: {0}")]
+    UnknownEvent(String),
+
+    /// Payload does not match the event's type
+    #[error("malformed {event} payload: {source}")]
+    Malformed {
+        /// Event name
+        event: String,
+        /// Deserialization error
+        source: serde_json::Error,
+    },
+}
+
+/// A ledger entry row as the container tail streams it
+/// (`row_to_json` of `ledger_entry`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LedgerRow {
+    /// Feed cursor; absent on deployments without the `id` column
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<i64>,
+    /// Container
+    pub container_id: String,
+    /// Position in the container's chain, from 1
+    pub sequence: i64,
+    /// Atom hash the link committed
+    pub link_hash: String,
+    /// Entry hash of the previous entry (`0x00` at genesis)
+    pub previous_hash: String,
+    /// This entry's hash
+    pub entry_hash: String,
+    /// Append time, unix milliseconds
+    pub ts_unix_ms: i64,
+    /// Server-set metadata (e.g. `policy.decided_by`)
+    #[serde(default)]
+    pub metadata: Value,
+    /// Intent class name
+    #[serde(default)]
+    pub intent_class: Option<String>,
+    /// Physics delta as the link carried it (an i128 string)
+    #[serde(default)]
+    pub physics_delta: Option<Value>,
+    /// Insert time (Postgres timestamp text)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<String>,
+}
+
+/// One accepted commit in the deployment-wide head feed; the SSE event id
+/// is `id`, to resume with `Last-Event-ID`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HeadEvent {
+    /// Feed cursor
+    pub id: i64,
+    /// Container
+    pub container_id: String,
+    /// New head sequence
+    pub sequence: i64,
+    /// New head hash
+    pub entry_hash: String,
+}
+
+/// A ledger entry as commit answers and `GET /state` serve it, hashes in
+/// the ledger's read format
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Entry {
+    /// Container
+    pub container_id: String,
+    /// Position in the chain
+    pub sequence: i64,
+    /// Atom hash the link committed
+    pub link_hash: String,
+    /// Previous entry hash
+    pub previous_hash: String,
+    /// This entry's hash
+    pub entry_hash: String,
+    /// Append time, unix milliseconds
+    pub ts_unix_ms: i64,
+}
+
+/// Outcome of one commit pipeline stage
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StageOutcome {
+    /// Ran and passed
+    Pass,
+    /// Ran and rejected the commit
+    Fail,
+    /// Not executed for this commit
+    Skip,
+}
+
+/// One stage of a `?debug=true` commit trace
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Stage {
+    /// Stage name (e.g. `v4_causality`)
+    pub stage: String,
+    /// What happened
+    pub outcome: StageOutcome,
+    /// Why it failed or was skipped
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    /// Time spent, microseconds
+    pub duration_us: u64,
+}
+
+/// Receipt of an accepted commit
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommitReceipt {
+    /// Always true
+    pub ok: bool,
+    /// The appended entry
+    pub entry: Entry,
+    /// Send back in [`CONSISTENCY_HEADER`] to read this entry from a replica
+    pub consistency_token: String,
+    /// Pipeline stages, with `?debug=true`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace: Option<Vec<Stage>>,
+}
+
+/// JSON body of a rejected commit (policy denials, and every rejection with
+/// `?debug=true`; other rejections answer plain text)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommitRejection {
+    /// Always false
+    pub ok: bool,
+    /// What went wrong
+    pub error: String,
+    /// Canonical deny code (snake_case) when a policy denied the commit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deny_code: Option<String>,
+    /// Pipeline stages, with `?debug=true`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace: Option<Vec<Stage>>,
+}
+
+/// An alert that fired for its owner
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AlertNotification {
+    /// Notification id
+    pub id: i64,
+    /// Rule that fired
+    pub alert_id: String,
+    /// Container watched
+    pub container_id: String,
+    /// Rule kind
+    pub kind: String,
+    /// Human-readable message
+    pub message: String,
+    /// Kind-specific detail
+    #[serde(default)]
+    pub detail: Value,
+    /// RFC 3339
+    pub created_at: String,
+    /// RFC 3339, once marked read
+    #[serde(default)]
+    pub read_at: Option<String>,
+}
+
+/// An SSE frame, by event name
+#[derive(Debug, Clone, PartialEq)]
+pub enum StreamEvent {
+    /// [`EVENT_LEDGER_ENTRY`]
+    LedgerEntry(LedgerRow),
+    /// [`EVENT_HEAD`]
+    Head(HeadEvent),
+}
+
+impl StreamEvent {
+    /// Read an SSE frame's `event` and `data`
+    pub fn parse(event: &str, data: &str) -> Result<Self, EventError> {
+        let malformed = |source| EventError::Malformed {
+            event: event.to_string(),
+            source,
+        };
+        match event {
+            EVENT_LEDGER_ENTRY => serde_json::from_str(data).map(StreamEvent::LedgerEntry).map_err(malformed),
+            EVENT_HEAD => serde_json::from_str(data).map(StreamEvent::Head).map_err(malformed),
+            other => Err(EventError::UnknownEvent(other.to_string())),
+        }
+    }
+
+    /// SSE event name
+    pub fn name(&self) -> &'static str {
+        match self {
+            StreamEvent::LedgerEntry(_) => EVENT_LEDGER_ENTRY,
+            StreamEvent::Head(_) => EVENT_HEAD,
+        }
+    }
+}
@@ -0,0 +1,36 @@
+//! Fixtures in `tests/fixtures` are frames as the server streams them: the
+//! ledger entry is `row_to_json` of a `ledger_entry` row (sql/000_unified.sql),
+//! the head is `ubl-server`'s `db::HeadEvent`. Each must parse and serialize
+//! back to the same JSON.
+
+use serde_json::Value;
+use ubl_events::{EventError, StreamEvent, EVENT_HEAD, EVENT_LEDGER_ENTRY};
+
+fn fixture(name: &str) -> String {
+    std::fs::read_to_string(format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name)).unwrap()
+}
+
+fn round_trip(event: &str, data: &str) {
+    let parsed = StreamEvent::parse(event, data).unwrap();
+    assert_eq!(parsed.name(), event);
+    let back = match &parsed {
+        StreamEvent::LedgerEntry(row) => serde_json::to_value(row).unwrap(),
+        StreamEvent::Head(head) => serde_json::to_value(head).unwrap(),
+    };
+    assert_eq!(back, serde_json::from_str::<Value>(data).unwrap());
+}
+
+#[test]
+fn ubl_events_stream_fixtures_round_trip() {
+    round_trip(EVENT_LEDGER_ENTRY, &fixture("ledger_entry.json"));
+    round_trip(EVENT_HEAD, &fixture("head.json"));
+}
+
+#[test]
+fn ubl_events_rejects_unknown_and_malformed() {
+    assert!(matches!(StreamEvent::parse("tick", "{}"), Err(EventError::UnknownEvent(_))));
+    assert!(matches!(
+        StreamEvent::parse(EVENT_HEAD, r#"{"id":"42","container_id":"C.Bank"}"#),
+        Err(EventError::Malformed { .. })
+    ));
+}
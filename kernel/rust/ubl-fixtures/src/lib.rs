@@ -0,0 +1,527 @@
+//! # UBL Fixtures
+//!
+//! Deterministic test data from a seed: keypairs, container ids, signed
+//! entry chains, pacts with their proofs, and ASCs. The same seed gives the
+//! same bytes on every machine and every release of this crate, so a bug
+//! report can name its data ("seed 42, chain wallet, entry 1337") and
+//! anyone can regenerate it:
+//!
+//! ```
+//! use ubl_fixtures::Fixtures;
+//!
+//! let chain = Fixtures::new(42).chain("wallet", 1337);
+//! let entry = chain.entry(1337).unwrap();
+//! assert_eq!(chain.locate(1337).to_string(), "seed 42, chain wallet, entry 1337");
+//! assert_eq!(entry.link.expected_sequence, 1337);
+//! ```
+//!
+//! Every value is drawn from its own BLAKE3 XOF stream, keyed by the seed
+//! and a label (`key/<name>`, `chain/<name>`, …): adding a keypair to a
+//! test does not shift the chain it already generated.
+//!
+//! Chains are valid under the membrane in strict mode. The first link mints
+//! a balance (Entropy); later ones mix transfers (Conservation, never below
+//! zero), notes (Observation) and further mints. Links are signed by the
+//! chain owner (`<name>/owner`) and entry hashes are `hash_link` of the
+//! signing bytes.
+
+#![deny(unsafe_code)]
+#![warn(missing_docs)]
+
+use std::fmt;
+
+use ed25519_dalek::SigningKey;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use ubl_link::{IntentClass, LinkCommit};
+use ubl_pact::{Pact, PactProof, PactScope, PactSignature, RiskLevel, TimeWindow};
+
+/// BLAKE3 derive-key context of every stream; changing it changes all fixtures
+pub const STREAM_CONTEXT: &str = "ubl-fixtures v1 stream";
+
+/// Unix time fixtures are dated from (2026-01-01T00:00:00Z)
+pub const EPOCH: i64 = 1_767_225_600;
+
+/// Seconds between consecutive entries of a chain
+pub const ENTRY_INTERVAL_SECS: i64 = 60;
+
+/// Validity of generated pacts and ASCs, from [`EPOCH`]
+pub const VALIDITY_SECS: i64 = 365 * 24 * 3600;
+
+/// Pact for tests to fill in with the terms they exercise, e.g.
+/// `Pact { max_uses: Some(1), ..ubl_fixtures::pact() }`: `pact_test`,
+/// global, threshold 1 of no signers yet, L2, always in force, every
+/// optional term unset
+pub fn pact() -> Pact {
+    Pact {
+        pact_id: "pact_test".to_string(),
+        version: 1,
+        scope: PactScope::Global,
+        threshold: 1,
+        signers: Default::default(),
+        window: TimeWindow {
+            not_before: 0,
+            not_after: i64::MAX,
+        },
+        risk_level: RiskLevel::L2,
+        container_id: None,
+        weights: None,
+        groups: Vec::new(),
+        veto_signers: Default::default(),
+        predecessor: None,
+        namespace: None,
+        max_uses: None,
+        frost_group_key: None,
+        signature_scheme: Default::default(),
+        risk_thresholds: None,
+        parent: None,
+        issues_pacts: false,
+        emergency: false,
+    }
+}
+
+/// Deterministic byte stream for one label
+pub struct Stream(blake3::OutputReader);
+
+impl Stream {
+    fn new(seed: u64, label: &str) -> Self {
+        let mut hasher = blake3::Hasher::new_derive_key(STREAM_CONTEXT);
+        hasher.update(&seed.to_be_bytes());
+        hasher.update(label.as_bytes());
+        Stream(hasher.finalize_xof())
+    }
+
+    /// Next 32 bytes
+    pub fn bytes32(&mut self) -> [u8; 32] {
+        let mut out = [0u8; 32];
+        self.0.fill(&mut out);
+        out
+    }
+
+    /// Next u64
+    pub fn next_u64(&mut self) -> u64 {
+        let mut out = [0u8; 8];
+        self.0.fill(&mut out);
+        u64::from_be_bytes(out)
+    }
+
+    /// Uniform in `0..n` (`n > 0`)
+    pub fn below(&mut self, n: u64) -> u64 {
+        // Rejection sampling keeps the draw unbiased
+        let zone = u64::MAX - u64::MAX % n;
+        loop {
+            let x = self.next_u64();
+            if x < zone {
+                return x % n;
+            }
+        }
+    }
+
+    /// Uniform in `lo..=hi`
+    pub fn range(&mut self, lo: i64, hi: i64) -> i64 {
+        lo + self.below((hi - lo) as u64 + 1) as i64
+    }
+}
+
+/// A named Ed25519 keypair
+#[derive(Clone)]
+pub struct Keypair {
+    /// Name it was generated under
+    pub name: String,
+    /// Signing key
+    pub signing_key: SigningKey,
+    /// Public key (hex)
+    pub pubkey: String,
+}
+
+impl Keypair {
+    /// Ed25519 signature over `message` (hex)
+    pub fn sign(&self, message: &[u8]) -> String {
+        ubl_kernel::sign(&self.signing_key, message)
+    }
+}
+
+impl fmt::Debug for Keypair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Keypair")
+            .field("name", &self.name)
+            .field("pubkey", &self.pubkey)
+            .finish_non_exhaustive()
+    }
+}
+
+/// One entry of a generated chain
+#[derive(Debug, Clone)]
+pub struct ChainEntry {
+    /// The atom the link commits to
+    pub atom: Value,
+    /// Signed link
+    pub link: LinkCommit,
+    /// `hash_link` of the link's signing bytes
+    pub entry_hash: String,
+}
+
+/// Where a generated entry comes from, for bug reports
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Locator {
+    /// Fixture seed
+    pub seed: u64,
+    /// Chain name
+    pub chain: String,
+    /// Entry sequence (1-indexed)
+    pub sequence: u64,
+}
+
+impl fmt::Display for Locator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "seed {}, chain {}, entry {}", self.seed, self.chain, self.sequence)
+    }
+}
+
+/// A generated entry chain for one container
+#[derive(Debug, Clone)]
+pub struct Chain {
+    /// Fixture seed
+    pub seed: u64,
+    /// Chain name
+    pub name: String,
+    /// Container id (Hash32 hex)
+    pub container_id: String,
+    /// Author of every link
+    pub owner: Keypair,
+    /// Entries, sequence 1 first
+    pub entries: Vec<ChainEntry>,
+}
+
+impl Chain {
+    /// Entry at `sequence` (1-indexed)
+    pub fn entry(&self, sequence: u64) -> Option<&ChainEntry> {
+        let index = sequence.checked_sub(1)?;
+        self.entries.get(index as usize)
+    }
+
+    /// Hash of the last entry (genesis hash when empty)
+    pub fn head(&self) -> String {
+        self.entries
+            .last()
+            .map(|e| e.entry_hash.clone())
+            .unwrap_or_else(|| ubl_kernel::GENESIS_HASH.to_string())
+    }
+
+    /// Sum of all deltas
+    pub fn balance(&self) -> i128 {
+        self.entries.iter().map(|e| e.link.physics_delta).sum()
+    }
+
+    /// Locator of the entry at `sequence`
+    pub fn locate(&self, sequence: u64) -> Locator {
+        Locator {
+            seed: self.seed,
+            chain: self.name.clone(),
+            sequence,
+        }
+    }
+
+    /// Replay into an in-memory ledger (timestamps are the replay's)
+    pub fn ledger(&self) -> ubl_ledger::Ledger {
+        let mut ledger = ubl_ledger::Ledger::new(self.container_id.clone());
+        for entry in &self.entries {
+            ledger.append(entry.link.clone(), entry.entry_hash.clone());
+        }
+        ledger
+    }
+
+    /// Membrane state of the container before the first entry: genesis
+    /// hash, sequence 1, no balances, no time. Tests override what they
+    /// exercise, e.g. `LedgerState { physical_balance: 10, ..chain.genesis_state() }`
+    pub fn genesis_state(&self) -> ubl_membrane::LedgerState {
+        ubl_membrane::LedgerState {
+            container_id: self.container_id.clone(),
+            last_hash: ubl_kernel::GENESIS_HASH.to_string(),
+            next_sequence: 1,
+            physical_balance: 0,
+            asset_balances: Default::default(),
+            last_timestamp: None,
+        }
+    }
+}
+
+/// Agent Signing Certificate, as issued by `POST /id/agents/:sid/asc`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Asc {
+    /// Certificate id (hex)
+    pub asc_id: String,
+    /// Agent SID: `ubl:sid:` + blake3(pubkey hex ‖ kind)
+    pub sid: String,
+    /// Agent public key (hex)
+    pub public_key: String,
+    /// `{"containers": [...], "intent_classes": [...], "max_delta": ...}`
+    pub scopes: Value,
+    /// Unix seconds
+    pub not_before: i64,
+    /// Unix seconds
+    pub not_after: i64,
+    /// Issuer (`asc-issuer` keypair) signature over the canonical JSON of
+    /// the other fields (hex)
+    pub signature: String,
+}
+
+/// Fixture generator for one seed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fixtures {
+    seed: u64,
+}
+
+impl Fixtures {
+    /// Generator for `seed`
+    pub fn new(seed: u64) -> Self {
+        Fixtures { seed }
+    }
+
+    /// The seed
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Stream for `label`
+    pub fn stream(&self, label: &str) -> Stream {
+        Stream::new(self.seed, label)
+    }
+
+    /// Hash32 hex drawn from the stream for `label`
+    pub fn hash32(&self, label: &str) -> String {
+        hex::encode(self.stream(label).bytes32())
+    }
+
+    /// Keypair named `name`
+    pub fn keypair(&self, name: &str) -> Keypair {
+        let signing_key = SigningKey::from_bytes(&self.stream(&format!("key/{}", name)).bytes32());
+        Keypair {
+            name: name.to_string(),
+            pubkey: ubl_kernel::pubkey_from_signing_key(&signing_key),
+            signing_key,
+        }
+    }
+
+    /// Container id (Hash32 hex) named `name`
+    pub fn container_id(&self, name: &str) -> String {
+        self.hash32(&format!("container/{}", name))
+    }
+
+    /// Chain of `len` entries for the container named `name`
+    pub fn chain(&self, name: &str, len: u64) -> Chain {
+        let container_id = self.container_id(name);
+        let owner = self.keypair(&format!("{}/owner", name));
+        let mut stream = self.stream(&format!("chain/{}", name));
+        let mut entries: Vec<ChainEntry> = Vec::with_capacity(len as usize);
+        let mut balance: i64 = 0;
+        for sequence in 1..=len {
+            let roll = stream.below(10);
+            let (kind, intent_class, delta) = if sequence == 1 || roll == 9 {
+                ("mint", IntentClass::Entropy, stream.range(1_000, 100_000))
+            } else if roll >= 6 {
+                ("note", IntentClass::Observation, 0)
+            } else if balance > 0 && stream.below(2) == 0 {
+                ("transfer_out", IntentClass::Conservation, -stream.range(1, balance))
+            } else {
+                ("transfer_in", IntentClass::Conservation, stream.range(1, 10_000))
+            };
+            balance += delta;
+            let atom = json!({
+                "type": kind,
+                "container_id": container_id,
+                "sequence": sequence,
+                "amount": delta,
+                "memo": format!("{} #{}", name, sequence),
+                "ts": EPOCH + sequence as i64 * ENTRY_INTERVAL_SECS,
+            });
+            let canonical = ubl_atom::canonicalize(&atom).expect("fixture atom canonicalizes");
+            let mut link = LinkCommit {
+                version: 1,
+                container_id: container_id.clone(),
+                expected_sequence: sequence,
+                previous_hash: entries
+                    .last()
+                    .map(|e| e.entry_hash.clone())
+                    .unwrap_or_else(|| ubl_kernel::GENESIS_HASH.to_string()),
+                atom_hash: ubl_kernel::hash_atom(&canonical),
+                intent_class,
+                physics_delta: delta as i128,
+                asset_deltas: Default::default(),
+                timestamp: None,
+                pact: None,
+                author_pubkey: owner.pubkey.clone(),
+                signature: String::new(),
+            };
+            let signing_bytes = link.signing_bytes();
+            link.signature = owner.sign(&signing_bytes);
+            entries.push(ChainEntry {
+                atom,
+                entry_hash: ubl_kernel::hash_link(&signing_bytes),
+                link,
+            });
+        }
+        Chain {
+            seed: self.seed,
+            name: name.to_string(),
+            container_id,
+            owner,
+            entries,
+        }
+    }
+
+    /// Pact named `name` over `signers`, valid for [`VALIDITY_SECS`] from
+    /// [`EPOCH`]; container-scoped when `container_id` is given, global otherwise
+    pub fn pact(
+        &self,
+        name: &str,
+        container_id: Option<&str>,
+        threshold: usize,
+        signers: &[&Keypair],
+        risk_level: RiskLevel,
+    ) -> Pact {
+        Pact {
+            pact_id: self.hash32(&format!("pact/{}", name)),
+            scope: if container_id.is_some() {
+                PactScope::Container
+            } else {
+                PactScope::Global
+            },
+            threshold,
+            signers: signers.iter().map(|k| k.pubkey.clone()).collect(),
+            window: TimeWindow {
+                not_before: EPOCH,
+                not_after: EPOCH + VALIDITY_SECS,
+            },
+            risk_level,
+            container_id: container_id.map(str::to_string),
+            ..pact()
+        }
+    }
+
+    /// Proof for `link` under `pact`, signed by `signers` over
+    /// `PactProof::signing_bytes(hash_link(signing bytes), pact_id, nonce)`
+    pub fn pact_proof(pact: &Pact, signers: &[&Keypair], link: &LinkCommit, nonce: u64) -> PactProof {
+        let link_hash = ubl_kernel::hash_link(&link.signing_bytes());
+        let message = PactProof::signing_bytes(&link_hash, &pact.pact_id, nonce);
+        PactProof {
+            pact_id: pact.pact_id.clone(),
+            signatures: signers
+                .iter()
+                .map(|k| PactSignature {
+                    pubkey: k.pubkey.clone(),
+                    signature: k.sign(&message),
+                })
+                .collect(),
+            nonce,
+            delegations: Vec::new(),
+            frost_signature: None,
+            bls_aggregate: None,
+        }
+    }
+
+    /// ASC for `agent` (of `kind`, e.g. `llm`) scoped to `containers` and
+    /// `intent_classes`, valid for [`VALIDITY_SECS`] from [`EPOCH`]
+    pub fn asc(
+        &self,
+        agent: &Keypair,
+        kind: &str,
+        containers: &[String],
+        intent_classes: &[IntentClass],
+        max_delta: Option<i64>,
+    ) -> Asc {
+        let mut sid = blake3::Hasher::new();
+        sid.update(agent.pubkey.as_bytes());
+        sid.update(kind.as_bytes());
+        let mut asc = Asc {
+            asc_id: self.hash32(&format!("asc/{}/{}", agent.name, kind))[..32].to_string(),
+            sid: format!("ubl:sid:{}", hex::encode(sid.finalize().as_bytes())),
+            public_key: agent.pubkey.clone(),
+            scopes: json!({
+                "containers": containers,
+                "intent_classes": intent_classes.iter().map(|c| c.as_str()).collect::<Vec<_>>(),
+                "max_delta": max_delta,
+            }),
+            not_before: EPOCH,
+            not_after: EPOCH + VALIDITY_SECS,
+            signature: String::new(),
+        };
+        let body = serde_json::to_value(&asc).expect("asc serializes");
+        let canonical = ubl_atom::canonicalize(&body).expect("asc canonicalizes");
+        asc.signature = self.keypair("asc-issuer").sign(&canonical);
+        asc
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_seed_same_fixtures() {
+        let a = Fixtures::new(42).chain("wallet", 50);
+        let b = Fixtures::new(42).chain("wallet", 50);
+        assert_eq!(a.head(), b.head());
+        assert_eq!(a.owner.pubkey, b.owner.pubkey);
+        assert_ne!(a.head(), Fixtures::new(43).chain("wallet", 50).head());
+        assert_ne!(a.container_id, Fixtures::new(42).container_id("vault"));
+    }
+
+    #[test]
+    fn test_chain_links_and_signatures() {
+        let chain = Fixtures::new(7).chain("wallet", 200);
+        let mut previous = ubl_kernel::GENESIS_HASH.to_string();
+        let mut balance = 0i128;
+        for (i, entry) in chain.entries.iter().enumerate() {
+            let link = &entry.link;
+            assert_eq!(link.expected_sequence, i as u64 + 1);
+            assert_eq!(link.previous_hash, previous);
+            let canonical = ubl_atom::canonicalize(&entry.atom).unwrap();
+            assert_eq!(link.atom_hash, ubl_kernel::hash_atom(&canonical));
+            ubl_kernel::verify(&link.author_pubkey, &link.signing_bytes(), &link.signature).unwrap();
+            balance += link.physics_delta;
+            assert!(balance >= 0);
+            previous = entry.entry_hash.clone();
+        }
+        assert_eq!(chain.entries[0].link.intent_class, IntentClass::Entropy);
+        assert_eq!(chain.balance(), balance);
+        assert_eq!(chain.ledger().last_hash(), chain.head());
+    }
+
+    #[test]
+    fn test_prefix_is_stable() {
+        let short = Fixtures::new(42).chain("wallet", 10);
+        let long = Fixtures::new(42).chain("wallet", 100);
+        assert_eq!(short.head(), long.entry(10).unwrap().entry_hash);
+        assert!(long.entry(0).is_none() && long.entry(101).is_none());
+    }
+
+    #[test]
+    fn test_pact_proof_validates() {
+        let fixtures = Fixtures::new(1);
+        let (a, b) = (fixtures.keypair("alice"), fixtures.keypair("bob"));
+        let chain = fixtures.chain("fund", 1);
+        let pact = fixtures.pact("fund", Some(chain.container_id.as_str()), 2, &[&a, &b], RiskLevel::L4);
+        let link = &chain.entries[0].link;
+        let proof = Fixtures::pact_proof(&pact, &[&a, &b], link, 1);
+        let mut registry = ubl_pact::PactRegistry::new();
+        registry.register(pact);
+        let link_hash = ubl_kernel::hash_link(&link.signing_bytes());
+        let class = link.intent_class.as_byte();
+        registry.validate(&proof, &chain.container_id, &link_hash, class, EPOCH).unwrap();
+        let stale = registry.validate(&proof, &chain.container_id, ubl_kernel::GENESIS_HASH, class, EPOCH);
+        assert_eq!(stale, Err(ubl_pact::PactError::InvalidSignature(a.pubkey.clone())));
+    }
+
+    #[test]
+    fn test_asc_is_signed_by_issuer() {
+        let fixtures = Fixtures::new(9);
+        let agent = fixtures.keypair("agent");
+        let mut asc = fixtures.asc(&agent, "llm", &["C.Wallet".to_string()], &[IntentClass::Observation], None);
+        assert!(asc.sid.starts_with("ubl:sid:"));
+        assert_eq!(asc.scopes["intent_classes"][0], "Observation");
+        let signature = std::mem::take(&mut asc.signature);
+        let canonical = ubl_atom::canonicalize(&serde_json::to_value(&asc).unwrap()).unwrap();
+        ubl_kernel::verify(&fixtures.keypair("asc-issuer").pubkey, &canonical, &signature).unwrap();
+    }
+}
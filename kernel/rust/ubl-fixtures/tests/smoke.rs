@@ -0,0 +1,5 @@
+#[test]
+fn ubl_fixtures_smoke() {
+    let fixtures = ubl_fixtures::Fixtures::new(42);
+    assert_eq!(fixtures.chain("wallet", 3).entries.len(), 3);
+}
@@ -0,0 +1,398 @@
+//! Dormancy attestations (SPEC-UBL-LEDGER v1.0 §10.1)
+//!
+//! A [`DormancyAttestation`] states that a container's head stayed at one
+//! sequence over a time range: nothing was appended to it. The statement is
+//! built from two checkpoints of every container's head, one written at or
+//! before the range opens and one after it closes, and binds each by its
+//! [`checkpoint_root`] together with the container's Merkle path to it. An
+//! auditor holding the attestation and the attesting key checks it with
+//! [`DormancyAttestation::verify`], with no access to the ledger or the
+//! service that issued it; anyone holding the checkpoints can recompute the
+//! roots.
+
+use ed25519_dalek::SigningKey;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// `format` of a dormancy attestation
+pub const ATTESTATION_FORMAT: &str = "ubl.ledger.dormancy.v1";
+
+/// Domain of the bytes an attestation signs
+pub const DORMANCY_DOMAIN: &[u8] = b"ubl:ledger-dormant:v1\n";
+
+/// Domain of a checkpoint head's Merkle leaf
+pub const HEAD_DOMAIN: &[u8] = b"ubl:checkpoint-head:v1\n";
+
+/// Why an attestation does not hold
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum AttestationError {
+    /// Unknown `format`
+    #[error("Unsupported attestation format: {0}")]
+    UnsupportedFormat(String),
+
+    /// The head moved between the checkpoints
+    #[error("Sequence moved from {from_seq} to {to_seq}")]
+    SequenceMoved {
+        /// Sequence at the opening checkpoint
+        from_seq: u64,
+        /// Sequence at the closing checkpoint
+        to_seq: u64,
+    },
+
+    /// The checkpoints do not enclose the range
+    #[error("Checkpoints at {opening} and {closing} do not enclose {from}..={to}")]
+    RangeNotCovered {
+        /// Start of the range
+        from: i64,
+        /// End of the range
+        to: i64,
+        /// Time of the opening checkpoint
+        opening: i64,
+        /// Time of the closing checkpoint
+        closing: i64,
+    },
+
+    /// The head's path does not lead to the checkpoint root
+    #[error("Head not included in the {0} checkpoint")]
+    NotIncluded(&'static str),
+
+    /// Signed by another key than the one trusted
+    #[error("Attestation signed by {0}, not the trusted key")]
+    UntrustedKey(String),
+
+    /// Signature does not verify
+    #[error("Invalid attestation signature")]
+    InvalidSignature,
+}
+
+/// One container's head in a checkpoint
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CheckpointHead {
+    /// Container identifier
+    pub container_id: String,
+    /// Sequence of the container's last entry
+    pub sequence: u64,
+    /// Hash of that entry (hex)
+    pub entry_hash: String,
+}
+
+impl CheckpointHead {
+    /// Merkle leaf: BLAKE3 of [`HEAD_DOMAIN`] and the length-prefixed fields
+    pub fn leaf(&self) -> Vec<u8> {
+        let mut hasher = blake3::Hasher::new();
+        hasher.update(HEAD_DOMAIN);
+        field(&mut hasher, &self.container_id);
+        hasher.update(&self.sequence.to_be_bytes());
+        field(&mut hasher, &self.entry_hash.to_ascii_lowercase());
+        hasher.finalize().as_bytes().to_vec()
+    }
+}
+
+fn field(hasher: &mut blake3::Hasher, s: &str) {
+    hasher.update(&(s.len() as u32).to_be_bytes());
+    hasher.update(s.as_bytes());
+}
+
+/// One step of a Merkle path: the sibling and which side it is on
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PathStep {
+    /// Sibling hash (hex)
+    pub hash: String,
+    /// Whether the sibling is the left operand
+    pub left: bool,
+}
+
+/// Leaves of a checkpoint, ordered by container id
+fn leaves(heads: &[CheckpointHead]) -> Vec<(&str, Vec<u8>)> {
+    let mut leaves: Vec<(&str, Vec<u8>)> = heads.iter().map(|h| (h.container_id.as_str(), h.leaf())).collect();
+    leaves.sort_by(|a, b| a.0.cmp(b.0));
+    leaves
+}
+
+/// Root and path of leaf `index`: pairs are hashed with
+/// `ubl_kernel::hash_merkle`, and an odd node is carried up unchanged
+fn tree(mut level: Vec<Vec<u8>>, mut index: usize) -> (Vec<u8>, Vec<PathStep>) {
+    let mut path = Vec::new();
+    while level.len() > 1 {
+        let sibling = index ^ 1;
+        if sibling < level.len() {
+            path.push(PathStep {
+                hash: hex::encode(&level[sibling]),
+                left: sibling < index,
+            });
+        }
+        level = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [l, r] => ubl_kernel::hash_merkle(l, r),
+                [single] => single.clone(),
+                _ => unreachable!(),
+            })
+            .collect();
+        index /= 2;
+    }
+    (level.pop().unwrap_or_else(|| vec![0; 32]), path)
+}
+
+/// Merkle root of a checkpoint's heads (32 zero bytes when it has none)
+pub fn checkpoint_root(heads: &[CheckpointHead]) -> String {
+    let leaves = leaves(heads).into_iter().map(|(_, l)| l).collect();
+    hex::encode(tree(leaves, 0).0)
+}
+
+/// Path from `container_id`'s head to the checkpoint root, if it has one
+pub fn inclusion_path(heads: &[CheckpointHead], container_id: &str) -> Option<Vec<PathStep>> {
+    let leaves = leaves(heads);
+    let index = leaves.iter().position(|(c, _)| *c == container_id)?;
+    Some(tree(leaves.into_iter().map(|(_, l)| l).collect(), index).1)
+}
+
+/// Whether `path` leads from `head` to `root`
+pub fn verify_inclusion(head: &CheckpointHead, path: &[PathStep], root: &str) -> bool {
+    let mut node = head.leaf();
+    for step in path {
+        let Ok(sibling) = hex::decode(&step.hash) else {
+            return false;
+        };
+        node = if step.left {
+            ubl_kernel::hash_merkle(&sibling, &node)
+        } else {
+            ubl_kernel::hash_merkle(&node, &sibling)
+        };
+    }
+    hex::encode(node).eq_ignore_ascii_case(root)
+}
+
+/// A checkpoint as an attestation binds it
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CheckpointRef {
+    /// Epoch the checkpoint was written in
+    pub epoch: i64,
+    /// Ledger cursor the heads were read at
+    pub cursor_id: i64,
+    /// Unix seconds the checkpoint was written
+    pub at: i64,
+    /// [`checkpoint_root`] of its heads
+    pub root: String,
+    /// The container's path to `root`
+    pub path: Vec<PathStep>,
+}
+
+/// What a dormancy attestation states
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DormancyStatement {
+    /// Always [`ATTESTATION_FORMAT`]
+    pub format: String,
+    /// Container attested
+    pub container_id: String,
+    /// Head sequence at the opening checkpoint
+    pub from_seq: u64,
+    /// Head sequence at the closing checkpoint; equal to `from_seq`
+    pub to_seq: u64,
+    /// Hash of the head entry at both
+    pub entry_hash: String,
+    /// Start of the dormant range (unix seconds)
+    pub from: i64,
+    /// End of the dormant range (unix seconds)
+    pub to: i64,
+    /// Checkpoint at or before `from`
+    pub opening: CheckpointRef,
+    /// Checkpoint after `to`
+    pub closing: CheckpointRef,
+    /// Unix seconds the attestation was issued
+    pub issued_at: i64,
+}
+
+impl DormancyStatement {
+    /// Canonical bytes to sign: [`DORMANCY_DOMAIN`], then each field with
+    /// strings length-prefixed (u32be) and integers big-endian. Paths are
+    /// not signed; they are checked against the signed roots.
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        let mut bytes = DORMANCY_DOMAIN.to_vec();
+        let push_str = |bytes: &mut Vec<u8>, s: &str| {
+            bytes.extend((s.len() as u32).to_be_bytes());
+            bytes.extend(s.as_bytes());
+        };
+        push_str(&mut bytes, &self.container_id);
+        bytes.extend(self.from_seq.to_be_bytes());
+        bytes.extend(self.to_seq.to_be_bytes());
+        push_str(&mut bytes, &self.entry_hash.to_ascii_lowercase());
+        bytes.extend(self.from.to_be_bytes());
+        bytes.extend(self.to.to_be_bytes());
+        for checkpoint in [&self.opening, &self.closing] {
+            bytes.extend(checkpoint.epoch.to_be_bytes());
+            bytes.extend(checkpoint.cursor_id.to_be_bytes());
+            bytes.extend(checkpoint.at.to_be_bytes());
+            push_str(&mut bytes, &checkpoint.root.to_ascii_lowercase());
+        }
+        bytes.extend(self.issued_at.to_be_bytes());
+        bytes
+    }
+
+    /// Check that the statement proves dormancy: one sequence at both
+    /// checkpoints, checkpoints enclosing the range, and the head included
+    /// in both roots
+    pub fn check(&self) -> Result<(), AttestationError> {
+        if self.format != ATTESTATION_FORMAT {
+            return Err(AttestationError::UnsupportedFormat(self.format.clone()));
+        }
+        if self.from_seq != self.to_seq {
+            return Err(AttestationError::SequenceMoved {
+                from_seq: self.from_seq,
+                to_seq: self.to_seq,
+            });
+        }
+        if self.from > self.to || self.opening.at > self.from || self.closing.at <= self.to {
+            return Err(AttestationError::RangeNotCovered {
+                from: self.from,
+                to: self.to,
+                opening: self.opening.at,
+                closing: self.closing.at,
+            });
+        }
+        let head = CheckpointHead {
+            container_id: self.container_id.clone(),
+            sequence: self.from_seq,
+            entry_hash: self.entry_hash.clone(),
+        };
+        for (name, checkpoint) in [("opening", &self.opening), ("closing", &self.closing)] {
+            if !verify_inclusion(&head, &checkpoint.path, &checkpoint.root) {
+                return Err(AttestationError::NotIncluded(name));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A dormancy statement signed by the attesting key
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DormancyAttestation {
+    /// What is attested
+    #[serde(flatten)]
+    pub statement: DormancyStatement,
+    /// Attesting key (hex)
+    pub pubkey: String,
+    /// Ed25519 signature over [`DormancyStatement::signing_bytes`] (hex)
+    pub signature: String,
+}
+
+impl DormancyAttestation {
+    /// Check and sign `statement` with `key`
+    pub fn sign(statement: DormancyStatement, key: &SigningKey) -> Result<Self, AttestationError> {
+        statement.check()?;
+        Ok(Self {
+            signature: ubl_kernel::sign(key, &statement.signing_bytes()),
+            pubkey: ubl_kernel::pubkey_from_signing_key(key),
+            statement,
+        })
+    }
+
+    /// Verify offline against the key the auditor trusts
+    pub fn verify(&self, trusted_pubkey: &str) -> Result<(), AttestationError> {
+        if !self.pubkey.eq_ignore_ascii_case(trusted_pubkey) {
+            return Err(AttestationError::UntrustedKey(self.pubkey.clone()));
+        }
+        self.statement.check()?;
+        ubl_kernel::verify(
+            &trusted_pubkey.to_ascii_lowercase(),
+            &self.statement.signing_bytes(),
+            &self.signature,
+        )
+        .map_err(|_| AttestationError::InvalidSignature)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn heads(n: u64) -> Vec<CheckpointHead> {
+        (0..n)
+            .map(|i| CheckpointHead {
+                container_id: format!("C.{}", i),
+                sequence: i + 1,
+                entry_hash: format!("{:064x}", i),
+            })
+            .collect()
+    }
+
+    fn checkpoint(heads: &[CheckpointHead], container_id: &str, at: i64) -> CheckpointRef {
+        CheckpointRef {
+            epoch: 0,
+            cursor_id: at,
+            at,
+            root: checkpoint_root(heads),
+            path: inclusion_path(heads, container_id).unwrap(),
+        }
+    }
+
+    fn statement(opening: &[CheckpointHead], closing: &[CheckpointHead]) -> DormancyStatement {
+        DormancyStatement {
+            format: ATTESTATION_FORMAT.to_string(),
+            container_id: "C.2".to_string(),
+            from_seq: 3,
+            to_seq: 3,
+            entry_hash: format!("{:064x}", 2),
+            from: 1000,
+            to: 2000,
+            opening: checkpoint(opening, "C.2", 990),
+            closing: checkpoint(closing, "C.2", 2010),
+            issued_at: 3000,
+        }
+    }
+
+    #[test]
+    fn test_inclusion_paths() {
+        for n in 1..=9 {
+            let heads = heads(n);
+            let root = checkpoint_root(&heads);
+            for head in &heads {
+                let path = inclusion_path(&heads, &head.container_id).unwrap();
+                assert!(verify_inclusion(head, &path, &root), "{} of {}", head.container_id, n);
+                let mut moved = head.clone();
+                moved.sequence += 1;
+                assert!(!verify_inclusion(&moved, &path, &root));
+            }
+        }
+        assert!(inclusion_path(&heads(3), "C.9").is_none());
+    }
+
+    #[test]
+    fn test_attestation_verifies_offline() {
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let pubkey = ubl_kernel::pubkey_from_signing_key(&key);
+        // Other containers moved between the checkpoints; C.2 did not
+        let opening = heads(5);
+        let mut closing = heads(6);
+        closing[0].sequence = 40;
+        let attestation = DormancyAttestation::sign(statement(&opening, &closing), &key).unwrap();
+        assert_eq!(attestation.verify(&pubkey), Ok(()));
+
+        let json = serde_json::to_string(&attestation).unwrap();
+        let parsed: DormancyAttestation = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.verify(&pubkey), Ok(()));
+
+        let other = ubl_kernel::pubkey_from_signing_key(&SigningKey::from_bytes(&[8; 32]));
+        assert!(matches!(attestation.verify(&other), Err(AttestationError::UntrustedKey(_))));
+
+        let mut widened = attestation.clone();
+        widened.statement.to = 2005;
+        assert_eq!(widened.verify(&pubkey), Err(AttestationError::InvalidSignature));
+        widened.statement.to = 2010;
+        assert!(matches!(widened.verify(&pubkey), Err(AttestationError::RangeNotCovered { .. })));
+    }
+
+    #[test]
+    fn test_moved_head_cannot_be_attested() {
+        let key = SigningKey::from_bytes(&[7; 32]);
+        let opening = heads(5);
+        let mut closing = heads(5);
+        closing[2].sequence = 4;
+        let result = DormancyAttestation::sign(statement(&opening, &closing), &key);
+        assert_eq!(result, Err(AttestationError::NotIncluded("closing")));
+
+        let mut moved = statement(&opening, &opening);
+        moved.to_seq = 4;
+        assert!(matches!(moved.check(), Err(AttestationError::SequenceMoved { .. })));
+    }
+}
@@ -8,14 +8,21 @@
 //! - Hash chain (each entry links to previous)
 //! - State is always a projection of history
 //! - Merkle root for daily anchoring
+//! - Dormancy attestations from checkpoints ([`attestation`])
 
 #![deny(unsafe_code)]
 #![warn(missing_docs)]
 
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use ubl_link::{IntentClass, LinkCommit, LinkReceipt};
 
+pub mod attestation;
+
+pub use attestation::{AttestationError, CheckpointHead, DormancyAttestation, DormancyStatement};
+
 /// Errors from ledger operations
 #[derive(Error, Debug)]
 pub enum LedgerError {
@@ -94,6 +101,15 @@ impl Ledger {
         self.chain.iter().map(|e| e.link.physics_delta).sum()
     }
 
+    /// Balances of the container's other assets (sums of their deltas)
+    pub fn asset_balances(&self) -> BTreeMap<String, i128> {
+        let mut balances = BTreeMap::new();
+        for (asset, delta) in self.chain.iter().flat_map(|e| &e.link.asset_deltas) {
+            *balances.entry(asset.clone()).or_insert(0) += delta;
+        }
+        balances
+    }
+
     /// Append a validated commit to the ledger
     /// NOTE: Validation should be done by the membrane before calling this
     pub fn append(&mut self, link: LinkCommit, entry_hash: String) -> LinkReceipt {
@@ -154,6 +170,9 @@ pub struct LedgerState {
     pub last_hash: String,
     /// Physical balance
     pub physical_balance: i128,
+    /// Balances of the other assets
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub asset_balances: BTreeMap<String, i128>,
     /// Merkle root
     pub merkle_root: String,
 }
@@ -165,6 +184,7 @@ impl From<&Ledger> for LedgerState {
             sequence: ledger.current_sequence(),
             last_hash: ledger.last_hash(),
             physical_balance: ledger.physical_balance(),
+            asset_balances: ledger.asset_balances(),
             merkle_root: ledger.merkle_root_hex(),
         }
     }
@@ -183,6 +203,8 @@ mod tests {
             atom_hash: "atom".to_string(),
             intent_class: IntentClass::Conservation,
             physics_delta: delta,
+            asset_deltas: BTreeMap::new(),
+            timestamp: None,
             pact: None,
             author_pubkey: "pk".to_string(),
             signature: "sig".to_string(),
@@ -229,11 +251,18 @@ mod tests {
         let mut ledger = Ledger::new("wallet".to_string());
         let commit = make_commit(1, GENESIS_HASH, 50);
         ledger.append(commit, "hash1".to_string());
+        let mut commit = make_commit(2, "hash1", 0);
+        commit.asset_deltas.insert("EUR".to_string(), 30);
+        ledger.append(commit, "hash2".to_string());
+        let mut commit = make_commit(3, "hash2", -5);
+        commit.asset_deltas.insert("EUR".to_string(), -10);
+        ledger.append(commit, "hash3".to_string());
 
         let state: LedgerState = (&ledger).into();
 
         assert_eq!(state.container_id, "wallet");
-        assert_eq!(state.sequence, 1);
-        assert_eq!(state.physical_balance, 50);
+        assert_eq!(state.sequence, 3);
+        assert_eq!(state.physical_balance, 45);
+        assert_eq!(state.asset_balances["EUR"], 20);
     }
 }
\ No newline at end of file
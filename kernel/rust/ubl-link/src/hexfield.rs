@@ -0,0 +1,132 @@
+//! Typed hex fields of a link (SPEC-UBL-LINK v1.0 §3.2)
+//!
+//! Each newtype only holds a value of the exact width, in lowercase hex (as
+//! `hex::encode` writes it), so a value that parses is canonical and
+//! compares byte-for-byte. They deserialize through the same check, and
+//! strict validation uses them at the membrane and the server.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use thiserror::Error;
+
+/// A hex field of the wrong width or alphabet
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("{field} must be {len} lowercase hex characters")]
+pub struct HexFieldError {
+    /// Field kind (`atom_hash`, `entry_hash`, `pubkey`, `signature`)
+    pub field: &'static str,
+    /// Required number of hex characters
+    pub len: usize,
+}
+
+fn check(s: &str, field: &'static str, len: usize) -> Result<(), HexFieldError> {
+    if s.len() == len && s.bytes().all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b)) {
+        Ok(())
+    } else {
+        Err(HexFieldError { field, len })
+    }
+}
+
+macro_rules! hex_field {
+    ($(#[$doc:meta])* $name:ident, $field:literal, $len:literal) => {
+        $(#[$doc])*
+        #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+        #[serde(try_from = "String", into = "String")]
+        pub struct $name(String);
+
+        impl $name {
+            /// Number of hex characters
+            pub const LEN: usize = $len;
+
+            /// Check and wrap `s`
+            pub fn parse(s: &str) -> Result<Self, HexFieldError> {
+                check(s, $field, $len)?;
+                Ok(Self(s.to_string()))
+            }
+
+            /// The hex string
+            pub fn as_str(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl TryFrom<String> for $name {
+            type Error = HexFieldError;
+
+            fn try_from(s: String) -> Result<Self, Self::Error> {
+                check(&s, $field, $len)?;
+                Ok(Self(s))
+            }
+        }
+
+        impl std::str::FromStr for $name {
+            type Err = HexFieldError;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                Self::parse(s)
+            }
+        }
+
+        impl From<$name> for String {
+            fn from(v: $name) -> String {
+                v.0
+            }
+        }
+
+        impl AsRef<str> for $name {
+            fn as_ref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(&self.0)
+            }
+        }
+    };
+}
+
+hex_field!(
+    /// BLAKE3 hash of an atom: 64 hex characters
+    AtomHash, "atom_hash", 64
+);
+hex_field!(
+    /// Ledger entry hash (`previous_hash`, `entry_hash`): 64 hex characters
+    EntryHash, "entry_hash", 64
+);
+hex_field!(
+    /// Ed25519 public key: 64 hex characters
+    PubKeyHex, "pubkey", 64
+);
+hex_field!(
+    /// Ed25519 signature: 128 hex characters
+    SignatureHex, "signature", 128
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exact_width_lowercase_only() {
+        let h = "ab".repeat(32);
+        assert_eq!(AtomHash::parse(&h).unwrap().as_str(), h);
+        assert!(AtomHash::parse(&h[..62]).is_err());
+        assert!(AtomHash::parse(&h.to_uppercase()).is_err());
+        assert!(AtomHash::parse(&"zz".repeat(32)).is_err());
+        assert_eq!(
+            SignatureHex::parse(&h).unwrap_err(),
+            HexFieldError { field: "signature", len: 128 }
+        );
+        assert!(SignatureHex::parse(&h.repeat(2)).is_ok());
+    }
+
+    #[test]
+    fn test_deserialization_checks_width() {
+        let ok: PubKeyHex = serde_json::from_str(&format!("\"{}\"", "0".repeat(64))).unwrap();
+        assert_eq!(serde_json::to_string(&ok).unwrap(), format!("\"{}\"", "0".repeat(64)));
+        let err = serde_json::from_str::<PubKeyHex>("\"abcd\"").unwrap_err();
+        assert!(err.to_string().contains("pubkey must be 64 lowercase hex characters"));
+    }
+}
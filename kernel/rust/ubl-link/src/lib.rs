@@ -17,14 +17,26 @@
 //! - Causal control (sequence, previous hash)
 //! - Atom hash (the semantic content, hashed)
 //! - Physical class (Observation, Conservation, Entropy, Evolution)
-//! - Physics delta (the physical change)
+//! - Physics delta (the physical change), and per-asset deltas for
+//!   containers holding more than one asset
+//! - Declared time (optional)
 //! - Authority (signature)
 
 #![deny(unsafe_code)]
 #![warn(missing_docs)]
 
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
+pub mod hexfield;
+pub mod pact;
+pub mod spec;
+
+pub use hexfield::{AtomHash, EntryHash, HexFieldError, PubKeyHex, SignatureHex};
+pub use pact::PactProof;
+pub use spec::{SpecVersion, UnknownSpecVersion};
+
 /// SPEC 4: Intent Class
 /// The physical classification of an intent.
 /// SPEC-UBL-LINK v1.0 §4
@@ -45,15 +57,31 @@ impl IntentClass {
     pub fn as_byte(&self) -> u8 {
         *self as u8
     }
+
+    /// Canonical name, as used on the wire
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IntentClass::Observation => "Observation",
+            IntentClass::Conservation => "Conservation",
+            IntentClass::Entropy => "Entropy",
+            IntentClass::Evolution => "Evolution",
+        }
+    }
 }
 
-/// Pact proof structure (SPEC-UBL-PACT v1.0 §8)
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct PactProof {
-    /// Pact identifier
-    pub pact_id: String,
-    /// Signatures from authorized signers
-    pub signatures: Vec<String>,
+impl std::str::FromStr for IntentClass {
+    type Err = String;
+
+    /// Parse the canonical name ("Observation", "Conservation", "Entropy", "Evolution")
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Observation" => Ok(IntentClass::Observation),
+            "Conservation" => Ok(IntentClass::Conservation),
+            "Entropy" => Ok(IntentClass::Entropy),
+            "Evolution" => Ok(IntentClass::Evolution),
+            other => Err(format!("unknown intent class: {}", other)),
+        }
+    }
 }
 
 /// SPEC 3: The Link Commit Structure
@@ -79,8 +107,17 @@ pub struct LinkCommit {
     /// SPEC 3.2: Physical class of the intent
     pub intent_class: IntentClass,
     
-    /// SPEC 3.2: Physical delta (change in value) - i128 per spec
+    /// SPEC 3.2: Physical delta (change in value) - i128 per spec; the
+    /// container's primary asset
     pub physics_delta: i128,
+
+    /// SPEC 3.2: Deltas of the container's other assets, by asset id
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub asset_deltas: BTreeMap<String, i128>,
+
+    /// SPEC 3.2: Declared time of the link (Unix seconds), optional
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<i64>,
     
     /// SPEC 3.2: Pact proof (optional)
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -119,6 +156,19 @@ impl LinkCommit {
         
         // Physics delta (16 bytes, big-endian for i128)
         bytes.extend_from_slice(&self.physics_delta.to_be_bytes());
+
+        // Asset deltas, by asset id, only when present: single-asset links
+        // sign the same bytes as before
+        for (asset, delta) in &self.asset_deltas {
+            bytes.extend_from_slice(&(asset.len() as u32).to_be_bytes());
+            bytes.extend_from_slice(asset.as_bytes());
+            bytes.extend_from_slice(&delta.to_be_bytes());
+        }
+
+        // Declared time (8 bytes, big-endian), only when present
+        if let Some(timestamp) = self.timestamp {
+            bytes.extend_from_slice(&timestamp.to_be_bytes());
+        }
         
         // STOP HERE - do NOT include pact, author_pubkey, or signature
         bytes
@@ -153,6 +203,19 @@ mod tests {
         assert_eq!(IntentClass::Evolution.as_byte(), 0x03);
     }
 
+    #[test]
+    fn test_intent_class_from_str_roundtrip() {
+        for class in [
+            IntentClass::Observation,
+            IntentClass::Conservation,
+            IntentClass::Entropy,
+            IntentClass::Evolution,
+        ] {
+            assert_eq!(class.as_str().parse::<IntentClass>().unwrap(), class);
+        }
+        assert!("observation".parse::<IntentClass>().is_err());
+    }
+
     #[test]
     fn test_signing_bytes_deterministic() {
         let commit = LinkCommit {
@@ -163,6 +226,8 @@ mod tests {
             atom_hash: "def456".to_string(),
             intent_class: IntentClass::Conservation,
             physics_delta: -100,
+            asset_deltas: BTreeMap::new(),
+            timestamp: None,
             author_pubkey: "pubkey".to_string(),
             signature: "sig".to_string(),
             pact: None,
@@ -184,6 +249,8 @@ mod tests {
             atom_hash: "abcd".to_string(),
             intent_class: IntentClass::Conservation,
             physics_delta: -50,
+            asset_deltas: BTreeMap::new(),
+            timestamp: None,
             author_pubkey: "pk".to_string(),
             signature: "sig".to_string(),
             pact: None,
@@ -194,5 +261,41 @@ mod tests {
         
         assert_eq!(parsed.container_id, commit.container_id);
         assert_eq!(parsed.physics_delta, commit.physics_delta);
+        assert!(!json.contains("asset_deltas"));
+    }
+
+    #[test]
+    fn test_optional_fields_are_signed() {
+        let mut commit = LinkCommit {
+            version: 1,
+            container_id: "vault".to_string(),
+            expected_sequence: 3,
+            previous_hash: "0000".to_string(),
+            atom_hash: "abcd".to_string(),
+            intent_class: IntentClass::Conservation,
+            physics_delta: 0,
+            asset_deltas: BTreeMap::new(),
+            timestamp: None,
+            author_pubkey: "pk".to_string(),
+            signature: "sig".to_string(),
+            pact: None,
+        };
+        let single = commit.signing_bytes();
+        commit.asset_deltas.insert("EUR".to_string(), -25);
+        let multi = commit.signing_bytes();
+        assert_eq!(multi[..single.len()], single[..]);
+        assert_eq!(multi.len(), single.len() + 4 + 3 + 16);
+        commit.asset_deltas.insert("EUR".to_string(), 25);
+        assert_ne!(commit.signing_bytes(), multi);
+
+        let json = serde_json::to_string(&commit).unwrap();
+        let parsed: LinkCommit = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.asset_deltas, commit.asset_deltas);
+
+        // So is a declared time
+        commit.timestamp = Some(1_767_225_600);
+        assert_eq!(commit.signing_bytes().len(), multi.len() + 8);
+        let json = serde_json::to_string(&commit).unwrap();
+        assert_eq!(serde_json::from_str::<LinkCommit>(&json).unwrap().timestamp, commit.timestamp);
     }
 }
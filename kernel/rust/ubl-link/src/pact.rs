@@ -0,0 +1,152 @@
+//! Pact proofs as links carry them (SPEC-UBL-PACT v1.0 §8)
+//!
+//! [`LinkCommit::pact`](crate::LinkCommit::pact) holds a [`PactProof`], so
+//! its types, and the canonical bytes its signers sign, are part of the
+//! wire format and live here, free of crypto. `ubl-pact` re-exports them
+//! and verifies proofs against registered pacts.
+
+use serde::{Deserialize, Serialize};
+
+/// Time window for pact validity (SPEC-UBL-PACT v1.0 §7)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimeWindow {
+    /// Unix timestamp - not valid before this
+    pub not_before: i64,
+    /// Unix timestamp - not valid after this
+    pub not_after: i64,
+}
+
+impl TimeWindow {
+    /// Check if current time is within window
+    pub fn is_valid(&self, now: i64) -> bool {
+        now >= self.not_before && now <= self.not_after
+    }
+}
+
+/// Pact proof attached to a link (SPEC-UBL-PACT v1.0 §8)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PactProof {
+    /// Reference to the pact
+    pub pact_id: String,
+    
+    /// Signatures from authorized signers
+    pub signatures: Vec<PactSignature>,
+
+    /// Nonce every signature in the proof commits to
+    #[serde(default)]
+    pub nonce: u64,
+
+    /// Optional: delegations from pact signers to the sub-keys that signed
+    /// in their place ([`SignerDelegation`])
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub delegations: Vec<SignerDelegation>,
+
+    /// Optional: FROST threshold signature (hex) over the same bytes under
+    /// the pact's `frost_group_key`, replacing `signatures`: 64 bytes however
+    /// large the quorum, and it does not reveal who signed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub frost_signature: Option<String>,
+
+    /// Optional: BLS12-381 aggregate signature over the same bytes, under a
+    /// pact whose `signature_scheme` is BLS12-381, replacing
+    /// `signatures`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bls_aggregate: Option<BlsAggregate>,
+}
+
+/// One BLS12-381 signature aggregated from every signer's signature over
+/// the proof's signing bytes: it verifies in two pairings however many
+/// signed (SPEC-UBL-PACT v1.0 §8.6)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlsAggregate {
+    /// Public keys (hex, 48-byte compressed G1) of the signers aggregated
+    pub signers: Vec<String>,
+
+    /// Aggregate signature (hex, 96-byte compressed G2)
+    pub signature: String,
+}
+
+/// Domain tag of the bytes a pact signer signs
+pub const SIGNING_DOMAIN: &[u8] = b"ubl:pact:v1\n";
+
+impl PactProof {
+    /// Canonical bytes a pact signer signs (SPEC-UBL-PACT v1.0 §8.2):
+    ///
+    /// ```text
+    /// "ubl:pact:v1\n" || u32be(len pact_id) || pact_id
+    ///                 || u32be(len link_hash) || lowercase(link_hash)
+    ///                 || u64be(nonce)
+    /// ```
+    ///
+    /// `link_hash` is `hash_link` of the authorized link's signing bytes.
+    pub fn signing_bytes(link_hash: &str, pact_id: &str, nonce: u64) -> Vec<u8> {
+        let link_hash = link_hash.to_ascii_lowercase();
+        let mut bytes = Vec::with_capacity(SIGNING_DOMAIN.len() + 16 + pact_id.len() + link_hash.len());
+        bytes.extend_from_slice(SIGNING_DOMAIN);
+        for field in [pact_id.as_bytes(), link_hash.as_bytes()] {
+            bytes.extend_from_slice(&(field.len() as u32).to_be_bytes());
+            bytes.extend_from_slice(field);
+        }
+        bytes.extend_from_slice(&nonce.to_be_bytes());
+        bytes
+    }
+}
+
+/// A single signature in a pact proof
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PactSignature {
+    /// Signer's public key (hex)
+    pub pubkey: String,
+    
+    /// Signature (hex)
+    pub signature: String,
+}
+
+/// Domain tag of the bytes a delegating key signs
+pub const DELEGATION_DOMAIN: &[u8] = b"ubl:pact-delegate:v1\n";
+
+/// A key's grant of its signing capability under a pact to a sub-key for a
+/// bounded window (SPEC-UBL-PACT v1.0 §8.4), signed by the parent key. The
+/// parent is a signer of the pact or, down a chain, a delegate of one.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignerDelegation {
+    /// Pact the capability is delegated under
+    pub pact_id: String,
+
+    /// Delegating key (hex)
+    pub parent: String,
+
+    /// Sub-key that may sign in the parent's place (hex)
+    pub delegate: String,
+
+    /// When the delegate may sign
+    pub window: TimeWindow,
+
+    /// Parent's Ed25519 signature over [`SignerDelegation::signing_bytes`] (hex)
+    pub signature: String,
+}
+
+impl SignerDelegation {
+    /// Canonical bytes the parent key signs:
+    ///
+    /// ```text
+    /// "ubl:pact-delegate:v1\n" || u32be(len pact_id) || pact_id
+    ///                          || u32be(len parent) || lowercase(parent)
+    ///                          || u32be(len delegate) || lowercase(delegate)
+    ///                          || i64be(not_before) || i64be(not_after)
+    /// ```
+    pub fn signing_bytes(pact_id: &str, parent: &str, delegate: &str, window: &TimeWindow) -> Vec<u8> {
+        let parent = parent.to_ascii_lowercase();
+        let delegate = delegate.to_ascii_lowercase();
+        let mut bytes =
+            Vec::with_capacity(DELEGATION_DOMAIN.len() + 28 + pact_id.len() + parent.len() + delegate.len());
+        bytes.extend_from_slice(DELEGATION_DOMAIN);
+        for field in [pact_id.as_bytes(), parent.as_bytes(), delegate.as_bytes()] {
+            bytes.extend_from_slice(&(field.len() as u32).to_be_bytes());
+            bytes.extend_from_slice(field);
+        }
+        bytes.extend_from_slice(&window.not_before.to_be_bytes());
+        bytes.extend_from_slice(&window.not_after.to_be_bytes());
+        bytes
+    }
+}
@@ -0,0 +1,81 @@
+//! Spec versions
+//!
+//! The SPEC-UBL-* documents are frozen per version. A container declares the
+//! version it is committed under in the manifest on its first link, and
+//! every later link, replayed or new, is validated under that version's
+//! rules. The membrane, pact and policy crates take a [`SpecVersion`] and
+//! `match` on it wherever a rule is versioned, so a new version is an added
+//! variant every one of those matches must answer for. It lives here, with
+//! the link types, so crates without crypto can name it.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// A frozen SPEC-UBL-* version
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum SpecVersion {
+    /// SPEC-UBL-* v1.0, frozen 2025-12-25; containers without a declared
+    /// version were committed under it
+    #[default]
+    #[serde(rename = "1.0")]
+    V1_0,
+}
+
+/// A spec version this build does not know
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("Unknown spec version: {0}")]
+pub struct UnknownSpecVersion(pub String);
+
+impl SpecVersion {
+    /// Every version this build can validate, oldest first
+    pub const ALL: &'static [SpecVersion] = &[SpecVersion::V1_0];
+
+    /// Version new containers are committed under unless they declare one
+    pub const LATEST: SpecVersion = SpecVersion::V1_0;
+
+    /// Version string as declared in manifests (`"1.0"`)
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SpecVersion::V1_0 => "1.0",
+        }
+    }
+}
+
+impl fmt::Display for SpecVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for SpecVersion {
+    type Err = UnknownSpecVersion;
+
+    /// Accepts `"1.0"` and `"v1.0"`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let version = s.strip_prefix('v').unwrap_or(s);
+        SpecVersion::ALL
+            .iter()
+            .copied()
+            .find(|v| v.as_str() == version)
+            .ok_or_else(|| UnknownSpecVersion(s.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spec_version_round_trip() {
+        for v in SpecVersion::ALL {
+            assert_eq!(v.as_str().parse::<SpecVersion>().unwrap(), *v);
+            assert_eq!(serde_json::to_string(v).unwrap(), format!("\"{}\"", v));
+        }
+        assert_eq!("v1.0".parse::<SpecVersion>().unwrap(), SpecVersion::V1_0);
+        assert!("1.1".parse::<SpecVersion>().is_err());
+        assert!(serde_json::from_str::<SpecVersion>("\"2.0\"").is_err());
+    }
+}
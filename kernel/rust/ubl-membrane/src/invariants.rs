@@ -0,0 +1,130 @@
+//! Container physics invariants
+//!
+//! Beyond the normative physics (an Observation moves nothing, a
+//! Conservation overdraws nothing), a container may hold its links to rules
+//! of its own: a balance cap, deltas in whole units, … Each is an
+//! [`Invariant`], and the container's set ([`Invariants`]) travels in
+//! [`ValidationOptions::invariants`](crate::ValidationOptions::invariants),
+//! configured per container like its profile. The physics validator
+//! ([`Physics`](crate::pipeline::Physics)) checks them, in order, after the
+//! built-in rules; a broken invariant is a `PhysicsViolation` naming it.
+
+use std::fmt;
+use std::sync::Arc;
+
+use ubl_link::LinkCommit;
+
+use crate::{LedgerState, MembraneError, Result};
+
+/// A physics rule a container adds to the normative ones
+pub trait Invariant: Send + Sync {
+    /// Name reported when the invariant is broken
+    fn name(&self) -> &str;
+
+    /// Whether `link` keeps the invariant over `state`; the reason if not
+    fn check(&self, link: &LinkCommit, state: &LedgerState) -> std::result::Result<(), String>;
+}
+
+/// A container's invariants, checked in order
+#[derive(Clone, Default)]
+pub struct Invariants(Vec<Arc<dyn Invariant>>);
+
+impl Invariants {
+    /// No invariants beyond the normative physics
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// These invariants, then `invariant`
+    pub fn with(mut self, invariant: impl Invariant + 'static) -> Self {
+        self.0.push(Arc::new(invariant));
+        self
+    }
+
+    /// Invariant names, in order
+    pub fn names(&self) -> Vec<&str> {
+        self.0.iter().map(|i| i.name()).collect()
+    }
+
+    /// Check every invariant; the first broken one is a `PhysicsViolation`
+    pub fn check(&self, link: &LinkCommit, state: &LedgerState) -> Result<()> {
+        for invariant in &self.0 {
+            invariant
+                .check(link, state)
+                .map_err(|reason| MembraneError::PhysicsViolation {
+                    reason: format!("{}: {}", invariant.name(), reason),
+                })?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Invariants {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.names()).finish()
+    }
+}
+
+/// The delta `link` carries for `asset` (the primary asset when `None`)
+fn delta(link: &LinkCommit, asset: Option<&str>) -> i128 {
+    match asset {
+        None => link.physics_delta,
+        Some(asset) => link.asset_deltas.get(asset).copied().unwrap_or(0),
+    }
+}
+
+/// The balance `state` holds of `asset` (the primary asset when `None`)
+fn balance(state: &LedgerState, asset: Option<&str>) -> i128 {
+    match asset {
+        None => state.physical_balance,
+        Some(asset) => state.asset_balances.get(asset).copied().unwrap_or(0),
+    }
+}
+
+/// The balance of an asset never exceeds `cap`
+#[derive(Debug, Clone)]
+pub struct BalanceCap {
+    /// Asset id; the primary asset when `None`
+    pub asset: Option<String>,
+    /// Highest balance allowed
+    pub cap: i128,
+}
+
+impl Invariant for BalanceCap {
+    fn name(&self) -> &str {
+        "balance_cap"
+    }
+
+    fn check(&self, link: &LinkCommit, state: &LedgerState) -> std::result::Result<(), String> {
+        let asset = self.asset.as_deref();
+        let resulting = balance(state, asset).saturating_add(delta(link, asset));
+        if resulting > self.cap {
+            return Err(format!("balance would be {}, above the cap of {}", resulting, self.cap));
+        }
+        Ok(())
+    }
+}
+
+/// Every delta of an asset is a multiple of `unit` (e.g. whole units of
+/// 100 cents)
+#[derive(Debug, Clone)]
+pub struct DeltaMultiple {
+    /// Asset id; the primary asset when `None`
+    pub asset: Option<String>,
+    /// Smallest movable amount; positive
+    pub unit: i128,
+}
+
+impl Invariant for DeltaMultiple {
+    fn name(&self) -> &str {
+        "delta_multiple"
+    }
+
+    fn check(&self, link: &LinkCommit, _state: &LedgerState) -> std::result::Result<(), String> {
+        let delta = delta(link, self.asset.as_deref());
+        if self.unit <= 0 || delta % self.unit != 0 {
+            return Err(format!("delta {} is not a multiple of {}", delta, self.unit));
+        }
+        Ok(())
+    }
+}
@@ -11,13 +11,84 @@
 //! Validation is deterministic, fast (<1ms), and semantically blind.
 //!
 //! ## Validations
+//! Each validation reports the `MembraneError` of the same code.
 //! - V1: Version check
-//! - V2: Container ID match
-//! - V3: Signature verification
+//! - V2: Signature integrity: field formats, and the signature itself
+//!   ([`ValidationOptions::verify_signature`], feature `verify`)
+//! - V3: Container ID match
 //! - V4: Reality drift (previous hash)
 //! - V5: Sequence continuity
-//! - V6: Atom hash format
-//! - V7: Physics invariants (conservation, entropy)
+//! - V6: Physics (class rules, conservation, container invariants)
+//! - V7: Pact standing and pact proofs ([`validate_with_pacts`])
+//! - V8: Evolution authorization ([`validate_with_pacts`])
+//! - V9: Declared time ([`ValidationOptions::clock`])
+//!
+//! V1–V7 and V9 run as a [`Pipeline`] of [`Validator`]s, pact proofs aside
+//! (V7 and V8 need a `PactRegistry`, see [`check_pact`]); deployments add their
+//! own checks to it (see [`pipeline`]). Bulk importers validate a whole
+//! batch against the evolving state with [`validate_batch`]; clients
+//! debugging rejections get every failing check from [`validate_full`].
+//!
+//! ## Profiles
+//! Governance containers (`gov://…`) record policy activations, pact
+//! registrations and freezes. Their profile only admits Observation and
+//! Evolution links and rejects any nonzero delta. A container whose
+//! operating pact has expired or been revoked is held to the restricted
+//! profile, Observation only, until the pact is renewed (V7).
+//!
+//! ## Assets
+//! A link's `physics_delta` moves the container's primary asset
+//! ([`LedgerState::physical_balance`]); its `asset_deltas` move the others
+//! ([`LedgerState::asset_balances`]). Physics holds per asset: an
+//! Observation moves none, Conservation keeps every balance it touches
+//! non-negative, and profile rules apply to each delta. Containers add
+//! invariants of their own ([`invariants`]).
+//!
+//! ## Spec versions
+//! Rules are applied as of the spec version the container is committed
+//! under ([`ValidationOptions::spec`]), so historical entries replay under
+//! the rules in force when they landed. Only v1.0 exists today.
+//!
+//! ## Pacts
+//! [`validate_with_pacts`] also checks the link's pact proof against a
+//! `PactRegistry` ([`check_pact`]): Entropy needs one, and any proof
+//! present must validate under SPEC-UBL-PACT for the link's hash (V7).
+//! Pact errors surface as `PactViolation`, or `UnauthorizedEvolution` for
+//! Evolution links; the registry's validation sink records the underlying
+//! `PactError`.
+//!
+//! This is library API only: ubl-server's commit path does not call it.
+//! Its drafts carry no pact proof, and it enforces authority pacts and
+//! freezes itself (`pact_routes::admit`).
+//!
+//! ## Evolution
+//! An Evolution link changes the rules themselves (V8). It passes only
+//! with a proof under an L5 pact and an allowing decision from the
+//! caller's [`EvolutionPolicy`] (e.g. a policy VM evaluation), both
+//! checked by [`check_pact`]; anything less is `UnauthorizedEvolution`.
+//! [`validate`] and [`validate_with`] have neither, so they hold Evolution
+//! links to physics alone.
+//!
+//! ## Time
+//! A link may declare when it was made (`timestamp`). A declared time
+//! must not precede the previous entry's ([`LedgerState::last_timestamp`])
+//! nor, given a [`LedgerClock`], stray from ledger time by more than its
+//! skew; either is `TimestampSkew` (V9). The clock is an input like the
+//! ledger state, never read by the membrane, so validation stays
+//! reproducible. Links declaring no time pass.
+//!
+//! ## Signatures
+//! V2 checks the author's Ed25519 signature over
+//! [`LinkCommit::signing_bytes`] only when asked to
+//! ([`ValidationOptions::verify_signature`]); callers that verify
+//! signatures upstream skip it.
+//!
+//! ## Crypto
+//! Everything that hashes or verifies is behind the `verify` feature (on by
+//! default): the signature check, pact proofs ([`validate_with_pacts`],
+//! [`check_pact`]) and [`validate_batch`]'s link hashing. Without it the
+//! membrane depends on `ubl-link` alone, for embedded builds without
+//! crypto; they chain batches with [`validate_batch_with`].
 //!
 //! ## Performance Target
 //! All validations must complete in < 1ms
@@ -25,44 +96,75 @@
 #![deny(unsafe_code)]
 #![warn(missing_docs)]
 
+use std::collections::BTreeMap;
+
 use thiserror::Error;
+use ubl_errors::ErrorCatalog;
 use ubl_link::{IntentClass, LinkCommit};
+#[cfg(feature = "verify")]
+use ubl_pact::{PactRegistry, RiskLevel};
+
+pub mod invariants;
+pub mod pipeline;
+pub mod report;
+
+pub use invariants::{Invariant, Invariants};
+pub use pipeline::{Pipeline, Validator};
+pub use report::{CheckReport, ValidationReport};
+pub use ubl_link::SpecVersion;
 
 /// Errors that can occur during membrane validation
-/// SPEC-UBL-MEMBRANE v1.0: Canonical error names (8 total)
-#[derive(Error, Debug, Clone)]
+/// SPEC-UBL-MEMBRANE v1.0: Canonical error names (9 total)
+#[derive(Error, Debug, Clone, ErrorCatalog)]
+#[catalog(source = "membrane")]
 pub enum MembraneError {
     /// V1: Invalid protocol version
     #[error("V1: Invalid version")]
+    #[catalog(code = "V1", status = 400)]
     InvalidVersion,
 
     /// V2: Invalid signature
     #[error("V2: Invalid signature")]
+    #[catalog(code = "V2", status = 422)]
     InvalidSignature,
 
     /// V3: Invalid target (container mismatch)
     #[error("V3: Invalid target")]
+    #[catalog(code = "V3", status = 400)]
     InvalidTarget,
 
     /// V4: Reality drift (previous hash mismatch)
     #[error("V4: Reality drift")]
+    #[catalog(code = "V4", status = 409)]
     RealityDrift,
 
     /// V5: Sequence mismatch
     #[error("V5: Sequence mismatch")]
+    #[catalog(code = "V5", status = 409)]
     SequenceMismatch,
 
     /// V6: Physics violation (includes conservation, observation, etc.)
     #[error("V6: Physics violation: {reason}")]
+    #[catalog(code = "V6", status = 422)]
     PhysicsViolation { reason: String },
 
     /// V7: Pact violation
     #[error("V7: Pact violation")]
+    #[catalog(code = "V7", status = 403)]
     PactViolation,
 
     /// V8: Unauthorized evolution
     #[error("V8: Unauthorized evolution")]
+    #[catalog(code = "V8", status = 403)]
     UnauthorizedEvolution,
+
+    /// V9: Declared time out of skew, or before the previous entry
+    #[error("V9: Timestamp skew: {reason}")]
+    #[catalog(code = "V9", status = 422)]
+    TimestampSkew {
+        /// Which time bound was crossed
+        reason: String,
+    },
 }
 
 /// Result type for membrane validation
@@ -85,6 +187,7 @@ impl Decision {
 }
 
 /// Ledger state needed for validation
+#[derive(Debug, Clone)]
 pub struct LedgerState {
     /// Container ID
     pub container_id: String,
@@ -94,73 +197,252 @@ pub struct LedgerState {
     pub next_sequence: u64,
     /// Current physical balance
     pub physical_balance: i128,
+    /// Current balances of the other assets, by asset id (absent is 0)
+    pub asset_balances: BTreeMap<String, i128>,
+    /// Time of the last entry (Unix seconds), if known
+    pub last_timestamp: Option<i64>,
 }
 
-/// Validate a link commit (SPEC-UBL-MEMBRANE v1.0 §6)
-/// This version does not perform signature validation - that must be done separately
-pub fn validate(link: &LinkCommit, state: &LedgerState) -> Result<()> {
-    // V1 - Version check
-    if link.version != 1 {
-        return Err(MembraneError::InvalidVersion);
-    }
-
-    // V2 - Container ID match (InvalidTarget)
-    if link.container_id != state.container_id {
-        return Err(MembraneError::InvalidTarget);
-    }
-
-    // V4 - Reality drift (causal chain)
-    if link.previous_hash != state.last_hash {
-        return Err(MembraneError::RealityDrift);
-    }
+/// Container id prefix of the governance archetype
+pub const GOVERNANCE_PREFIX: &str = "gov://";
+
+/// Membrane profile applied on top of the normative validations
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ContainerProfile {
+    /// Operational container: normative rules only
+    #[default]
+    Standard,
+    /// Governance container: Observation/Evolution only, delta must be 0
+    Governance,
+    /// Container whose operating pact is not in force: Observation only
+    Restricted,
+}
 
-    // V5 - Sequence continuity
-    if link.expected_sequence != state.next_sequence {
-        return Err(MembraneError::SequenceMismatch);
+impl ContainerProfile {
+    /// Profile implied by the container id
+    pub fn for_container(container_id: &str) -> Self {
+        if container_id.starts_with(GOVERNANCE_PREFIX) {
+            ContainerProfile::Governance
+        } else {
+            ContainerProfile::Standard
+        }
     }
 
-    // V6 - Atom hash format (should be 64 hex chars = 32 bytes)
-    if link.atom_hash.len() != 64 || hex::decode(&link.atom_hash).is_err() {
-        // Allow shorter hashes for testing
-        if link.atom_hash.len() < 4 {
-            return Err(MembraneError::InvalidSignature);
-        }
+    /// Rules specific to this profile: [`ContainerProfile::check_physics`]
+    /// and [`ContainerProfile::check_standing`]
+    pub fn check(&self, intent_class: IntentClass, physics_delta: i128) -> Result<()> {
+        self.check_physics(intent_class, physics_delta)?;
+        self.check_standing(intent_class)
     }
 
-    // V6 - Physics invariants
-    match link.intent_class {
-        IntentClass::Observation => {
-            // Observations must have zero delta
-            if link.physics_delta != 0 {
+    /// Physics rules specific to this profile (part of V6)
+    pub fn check_physics(&self, intent_class: IntentClass, physics_delta: i128) -> Result<()> {
+        if *self == ContainerProfile::Governance {
+            if !matches!(intent_class, IntentClass::Observation | IntentClass::Evolution) {
                 return Err(MembraneError::PhysicsViolation {
-                    reason: format!("Observation must have delta=0, got {}", link.physics_delta)
+                    reason: format!("Governance container rejects {:?}", intent_class),
                 });
             }
-        }
-        IntentClass::Conservation => {
-            // Conservation: balance must remain >= 0
-            let resulting_balance = state.physical_balance + link.physics_delta;
-            if resulting_balance < 0 {
+            if physics_delta != 0 {
                 return Err(MembraneError::PhysicsViolation {
-                    reason: format!("Conservation requires balance >= 0, would be {}", resulting_balance)
+                    reason: format!("Governance container requires delta=0, got {}", physics_delta),
                 });
             }
         }
-        IntentClass::Entropy => {
-            // Entropy allows creation/destruction - no additional checks
-        }
-        IntentClass::Evolution => {
-            // Evolution is for rule changes - would need additional policy checks
-            // For now, allow it
+        Ok(())
+    }
+
+    /// Pact standing specific to this profile (part of V7)
+    pub fn check_standing(&self, intent_class: IntentClass) -> Result<()> {
+        if *self == ContainerProfile::Restricted && intent_class != IntentClass::Observation {
+            return Err(MembraneError::PactViolation);
         }
+        Ok(())
+    }
+}
+
+/// Options for `validate_with`
+#[derive(Debug, Clone, Default)]
+pub struct ValidationOptions {
+    /// Profile of the target container
+    pub profile: ContainerProfile,
+    /// Strict field formats: 64-hex atom and previous hashes and public
+    /// key, 128-hex signature ([`ubl_link::hexfield`]); without it, atom
+    /// hashes of 4+ characters pass for test fixtures
+    pub strict: bool,
+    /// Spec version the target container is committed under
+    pub spec: SpecVersion,
+    /// The target container's own physics invariants, checked after the
+    /// built-in rules
+    pub invariants: Invariants,
+    /// V9: ledger time, and how far a link's declared time may stray
+    /// from it; without it, declared times are only held to the previous
+    /// entry's
+    pub clock: Option<LedgerClock>,
+    /// V2: verify the author's signature over the link's canonical bytes
+    /// ([`verify_signature`])
+    #[cfg(feature = "verify")]
+    pub verify_signature: bool,
+}
+
+/// Ledger time a link's declared time is checked against (V9)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LedgerClock {
+    /// Ledger time (Unix seconds)
+    pub now: i64,
+    /// Largest distance, in seconds, a declared time may have from `now`
+    pub max_skew: i64,
+}
+
+/// Link format version a spec version admits (V1)
+pub fn link_version(spec: SpecVersion) -> u8 {
+    match spec {
+        SpecVersion::V1_0 => 1,
+    }
+}
+
+/// V2: the author's Ed25519 signature over [`LinkCommit::signing_bytes`]
+#[cfg(feature = "verify")]
+pub fn verify_signature(link: &LinkCommit) -> Result<()> {
+    ubl_kernel::verify(&link.author_pubkey, &link.signing_bytes(), &link.signature)
+        .map_err(|_| MembraneError::InvalidSignature)
+}
+
+/// Validate a link commit (SPEC-UBL-MEMBRANE v1.0 §6)
+/// The signature is not verified here: use [`validate_with`] and
+/// [`ValidationOptions::verify_signature`], or verify it separately
+pub fn validate(link: &LinkCommit, state: &LedgerState) -> Result<()> {
+    validate_with(link, state, &ValidationOptions::default())
+}
+
+/// Validate a link commit under explicit options (container profile, …):
+/// the normative [`Pipeline`]
+pub fn validate_with(link: &LinkCommit, state: &LedgerState, options: &ValidationOptions) -> Result<()> {
+    Pipeline::normative().validate(link, state, options)
+}
+
+/// Run every check on a link, past failures, for diagnostics: each one's
+/// result and timing ([`report`]). The commit path stays fail-fast
+/// ([`validate`], [`decide`])
+pub fn validate_full(link: &LinkCommit, state: &LedgerState) -> ValidationReport {
+    validate_full_with(link, state, &ValidationOptions::default())
+}
+
+/// [`validate_full`] under explicit options
+pub fn validate_full_with(link: &LinkCommit, state: &LedgerState, options: &ValidationOptions) -> ValidationReport {
+    Pipeline::normative().validate_full(link, state, options)
+}
+
+/// Validate a causally ordered batch of links against `state`, as one
+/// [`validate`] per link with each accepted link's sequence, delta and
+/// entry hash applied before the next; one result per link, in order.
+///
+/// A rejected link is not applied, so the links after it fail V5 until
+/// the gap is filled. Each link must name the entry hash of the one
+/// before it (V4); here that is [`ubl_kernel::hash_link`] of its signing
+/// bytes. Ledgers that hash entries otherwise use [`validate_batch_with`].
+#[cfg(feature = "verify")]
+pub fn validate_batch(links: &[LinkCommit], state: &LedgerState) -> Vec<Result<()>> {
+    validate_batch_with(links, state, &ValidationOptions::default(), |link| {
+        ubl_kernel::hash_link(&link.signing_bytes())
+    })
+}
+
+/// [`validate_batch`] under explicit options, with `entry_hash` giving the
+/// hash each accepted link is appended under
+pub fn validate_batch_with(
+    links: &[LinkCommit],
+    state: &LedgerState,
+    options: &ValidationOptions,
+    entry_hash: impl Fn(&LinkCommit) -> String,
+) -> Vec<Result<()>> {
+    Pipeline::normative().validate_batch(links, state, options, entry_hash)
+}
+
+/// Policy decision on an Evolution link (V8)
+pub trait EvolutionPolicy {
+    /// Whether the rule change `link` carries is allowed
+    fn allows(&self, link: &LinkCommit) -> bool;
+}
+
+impl<F: Fn(&LinkCommit) -> bool> EvolutionPolicy for F {
+    fn allows(&self, link: &LinkCommit) -> bool {
+        self(link)
+    }
+}
+
+/// [`EvolutionPolicy`] allowing no Evolution link, for callers without one
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoEvolution;
+
+impl EvolutionPolicy for NoEvolution {
+    fn allows(&self, _link: &LinkCommit) -> bool {
+        false
     }
+}
 
+/// [`validate_with`], then the link's pact proof against `pacts` at `now`
+/// under `options.spec`, and `evolution`'s decision on an Evolution link
+/// ([`check_pact`])
+#[cfg(feature = "verify")]
+pub fn validate_with_pacts(
+    link: &LinkCommit,
+    state: &LedgerState,
+    options: &ValidationOptions,
+    pacts: &PactRegistry,
+    evolution: &dyn EvolutionPolicy,
+    now: i64,
+) -> Result<()> {
+    validate_with(link, state, options)?;
+    check_pact(link, pacts, evolution, options.spec, now)
+}
+
+/// V7–V8: Entropy links carry a pact proof, Evolution links one under an
+/// L5 pact and `evolution`'s leave, and every proof carried validates
+/// (`PactRegistry::validate_under`) over the link's hash
+/// ([`ubl_kernel::hash_link`] of its signing bytes)
+#[cfg(feature = "verify")]
+pub fn check_pact(
+    link: &LinkCommit,
+    pacts: &PactRegistry,
+    evolution: &dyn EvolutionPolicy,
+    spec: SpecVersion,
+    now: i64,
+) -> Result<()> {
+    let Some(proof) = &link.pact else {
+        return match link.intent_class {
+            IntentClass::Entropy => Err(MembraneError::PactViolation),
+            IntentClass::Evolution => Err(MembraneError::UnauthorizedEvolution),
+            _ => Ok(()),
+        };
+    };
+    let risk_level = pacts.get(&proof.pact_id).map(|p| p.risk_level);
+    if link.intent_class == IntentClass::Evolution && risk_level != Some(RiskLevel::L5) {
+        return Err(MembraneError::UnauthorizedEvolution);
+    }
+    let link_hash = ubl_kernel::hash_link(&link.signing_bytes());
+    pacts
+        .validate_under(spec, proof, &link.container_id, &link_hash, link.intent_class.as_byte(), now)
+        .map_err(|_| match link.intent_class {
+            IntentClass::Evolution => MembraneError::UnauthorizedEvolution,
+            _ => MembraneError::PactViolation,
+        })?;
+
+    // V8 - Evolution: the policy has the last word
+    if link.intent_class == IntentClass::Evolution && !evolution.allows(link) {
+        return Err(MembraneError::UnauthorizedEvolution);
+    }
     Ok(())
 }
 
 /// Quick decide function that returns Decision enum
 pub fn decide(link: &LinkCommit, state: &LedgerState) -> Decision {
-    match validate(link, state) {
+    decide_with(link, state, &ValidationOptions::default())
+}
+
+/// [`decide`] under explicit options
+pub fn decide_with(link: &LinkCommit, state: &LedgerState, options: &ValidationOptions) -> Decision {
+    match validate_with(link, state, options) {
         Ok(()) => Decision::Accept,
         Err(e) => Decision::Reject(e),
     }
@@ -179,6 +461,8 @@ mod tests {
             atom_hash: "a".repeat(64),
             intent_class: class,
             physics_delta: delta,
+            asset_deltas: BTreeMap::new(),
+            timestamp: None,
             pact: None,
             author_pubkey: "pk".to_string(),
             signature: "mock".to_string(),
@@ -191,6 +475,8 @@ mod tests {
             last_hash: hash.to_string(),
             next_sequence: seq,
             physical_balance: balance,
+            asset_balances: BTreeMap::new(),
+            last_timestamp: None,
         }
     }
 
@@ -266,6 +552,61 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_conservation_per_asset() {
+        let mut state = make_state(1, "genesis", 100);
+        state.asset_balances.insert("EUR".to_string(), 40);
+        let mut link = make_commit(1, "genesis", -100, IntentClass::Conservation);
+        link.asset_deltas.insert("EUR".to_string(), -40);
+        assert!(validate(&link, &state).is_ok());
+
+        // Each asset is held to its own balance, absent ones to 0
+        link.asset_deltas.insert("EUR".to_string(), -41);
+        assert!(matches!(validate(&link, &state), Err(MembraneError::PhysicsViolation { .. })));
+        link.asset_deltas.clear();
+        link.asset_deltas.insert("USD".to_string(), -1);
+        assert!(matches!(validate(&link, &state), Err(MembraneError::PhysicsViolation { .. })));
+
+        let mut observe = make_commit(1, "genesis", 0, IntentClass::Observation);
+        observe.asset_deltas.insert("EUR".to_string(), 1);
+        assert!(matches!(validate(&observe, &state), Err(MembraneError::PhysicsViolation { .. })));
+        let governance = ValidationOptions {
+            profile: ContainerProfile::Governance,
+            ..Default::default()
+        };
+        observe.intent_class = IntentClass::Evolution;
+        assert!(matches!(
+            validate_with(&observe, &state, &governance),
+            Err(MembraneError::PhysicsViolation { .. })
+        ));
+    }
+
+    #[test]
+    fn test_balance_overflow_is_a_physics_violation() {
+        let overflow = |result: Result<()>| {
+            matches!(result, Err(MembraneError::PhysicsViolation { reason }) if reason == "overflow")
+        };
+        let full = make_state(1, "genesis", i128::MAX);
+        let mint = make_commit(1, "genesis", 1, IntentClass::Entropy);
+        assert!(overflow(validate(&mint, &full)));
+        assert!(validate(&make_commit(1, "genesis", 0, IntentClass::Entropy), &full).is_ok());
+        assert!(validate(&make_commit(1, "genesis", -i128::MAX, IntentClass::Conservation), &full).is_ok());
+
+        let burn = make_commit(1, "genesis", i128::MIN, IntentClass::Entropy);
+        assert!(overflow(validate(&burn, &make_state(1, "genesis", -1))));
+        assert!(validate(&burn, &make_state(1, "genesis", 0)).is_ok());
+
+        // Every asset is held to it
+        let mut state = make_state(1, "genesis", 0);
+        state.asset_balances.insert("EUR".to_string(), i128::MAX);
+        let mut link = make_commit(1, "genesis", 0, IntentClass::Conservation);
+        link.asset_deltas.insert("EUR".to_string(), 1);
+        assert!(overflow(validate(&link, &state)));
+        link.asset_deltas.insert("EUR".to_string(), i128::MIN);
+        assert!(!overflow(validate(&link, &state)));
+        assert!(validate(&link, &state).is_err());
+    }
+
     #[test]
     fn test_entropy_allows_creation() {
         let state = make_state(1, "genesis", 0);
@@ -275,6 +616,57 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_full_report_lists_every_failure() {
+        let state = make_state(1, "genesis", 0);
+        let mut commit = make_commit(5, "wrong_hash", -10, IntentClass::Conservation);
+        commit.version = 2;
+
+        let report = validate_full(&commit, &state);
+        assert_eq!(report.checks.len(), 8);
+        let failed: Vec<&str> = report.failures().map(|c| c.id.as_str()).collect();
+        assert_eq!(failed, ["V1", "V4", "V5", "V6"]);
+        assert!(!report.is_accept());
+        assert!(report.elapsed() >= report.checks[0].elapsed);
+
+        // Same decision as the fail-fast path
+        assert!(matches!(report.first_error(), Some(MembraneError::InvalidVersion)));
+        assert!(matches!(decide(&commit, &state), Decision::Reject(MembraneError::InvalidVersion)));
+        assert!(matches!(report.decision(), Decision::Reject(MembraneError::InvalidVersion)));
+
+        let valid = make_commit(1, "genesis", 0, IntentClass::Observation);
+        let report = validate_full(&valid, &state);
+        assert!(report.is_accept() && report.decision().is_accept());
+    }
+
+    #[test]
+    fn test_declared_time_within_skew() {
+        let mut state = make_state(1, "genesis", 0);
+        let clocked = ValidationOptions {
+            clock: Some(LedgerClock {
+                now: 1_000_000,
+                max_skew: 300,
+            }),
+            ..Default::default()
+        };
+        let mut link = make_commit(1, "genesis", 0, IntentClass::Observation);
+        assert!(validate_with(&link, &state, &clocked).is_ok());
+        link.timestamp = Some(1_000_300);
+        assert!(validate_with(&link, &state, &clocked).is_ok());
+        link.timestamp = Some(999_699);
+        assert!(matches!(
+            validate_with(&link, &state, &clocked),
+            Err(MembraneError::TimestampSkew { .. })
+        ));
+
+        // Never before the previous entry, clock or not
+        link.timestamp = Some(1_000_000);
+        state.last_timestamp = Some(1_000_001);
+        assert!(matches!(validate(&link, &state), Err(MembraneError::TimestampSkew { .. })));
+        state.last_timestamp = Some(1_000_000);
+        assert!(validate(&link, &state).is_ok());
+    }
+
     #[test]
     fn test_decide_accept() {
         let state = make_state(1, "genesis", 0);
@@ -283,4 +675,127 @@ mod tests {
         let decision = decide(&commit, &state);
         assert!(decision.is_accept());
     }
+
+    #[test]
+    fn test_governance_profile_rejects_nonzero_delta() {
+        let state = LedgerState {
+            container_id: "gov://policies".to_string(),
+            ..make_state(1, "genesis", 0)
+        };
+        let mut link = make_commit(1, "genesis", 0, IntentClass::Evolution);
+        link.container_id = "gov://policies".to_string();
+        let opts = ValidationOptions {
+            profile: ContainerProfile::for_container(&link.container_id),
+            ..Default::default()
+        };
+        assert_eq!(opts.profile, ContainerProfile::Governance);
+        assert!(validate_with(&link, &state, &opts).is_ok());
+
+        link.physics_delta = 1;
+        link.intent_class = IntentClass::Entropy;
+        assert!(matches!(
+            validate_with(&link, &state, &opts),
+            Err(MembraneError::PhysicsViolation { .. })
+        ));
+    }
+
+    #[test]
+    fn test_governance_profile_rejects_conservation() {
+        let state = make_state(1, "genesis", 100);
+        let link = make_commit(1, "genesis", 0, IntentClass::Conservation);
+        let opts = ValidationOptions {
+            profile: ContainerProfile::Governance,
+            ..Default::default()
+        };
+        assert!(validate(&link, &state).is_ok());
+        assert!(validate_with(&link, &state, &opts).is_err());
+    }
+
+    #[test]
+    fn test_restricted_profile_admits_only_observation() {
+        let state = make_state(1, "genesis", 100);
+        let opts = ValidationOptions {
+            profile: ContainerProfile::Restricted,
+            ..Default::default()
+        };
+        let observe = make_commit(1, "genesis", 0, IntentClass::Observation);
+        assert!(validate_with(&observe, &state, &opts).is_ok());
+        let spend = make_commit(1, "genesis", -10, IntentClass::Conservation);
+        assert!(validate(&spend, &state).is_ok());
+        assert!(matches!(validate_with(&spend, &state, &opts), Err(MembraneError::PactViolation)));
+    }
+
+    #[test]
+    fn test_spec_version_selects_link_version() {
+        let state = make_state(1, "genesis", 0);
+        let mut link = make_commit(1, "genesis", 0, IntentClass::Observation);
+        assert_eq!(ValidationOptions::default().spec, SpecVersion::V1_0);
+        for spec in SpecVersion::ALL {
+            let opts = ValidationOptions {
+                spec: *spec,
+                ..Default::default()
+            };
+            assert!(validate_with(&link, &state, &opts).is_ok());
+        }
+        link.version = 2;
+        assert!(matches!(validate(&link, &state), Err(MembraneError::InvalidVersion)));
+    }
+
+    #[test]
+    fn test_strict_mode_requires_full_width_hex() {
+        let strict = ValidationOptions {
+            strict: true,
+            ..Default::default()
+        };
+        let head = "0".repeat(64);
+        let state = make_state(1, &head, 0);
+        let mut link = make_commit(1, &head, 0, IntentClass::Observation);
+        link.author_pubkey = "1f".repeat(32);
+        link.signature = "2e".repeat(64);
+        assert!(validate_with(&link, &state, &strict).is_ok());
+
+        // Fixture-width values still pass outside strict mode
+        let mut short = link.clone();
+        short.atom_hash = "abcd".to_string();
+        short.signature = "mock".to_string();
+        assert!(validate(&short, &state).is_ok());
+        assert!(matches!(validate_with(&short, &state, &strict), Err(MembraneError::InvalidSignature)));
+
+        let mut upper = link.clone();
+        upper.author_pubkey = upper.author_pubkey.to_uppercase();
+        assert!(matches!(validate_with(&upper, &state, &strict), Err(MembraneError::InvalidSignature)));
+
+        let genesis = make_state(1, "genesis", 0);
+        let mut named = link;
+        named.previous_hash = "genesis".to_string();
+        assert!(validate(&named, &genesis).is_ok());
+        assert!(matches!(validate_with(&named, &genesis, &strict), Err(MembraneError::RealityDrift)));
+    }
+
+    #[cfg(feature = "verify")]
+    #[test]
+    fn test_signature_verified_on_request() {
+        let verify = ValidationOptions {
+            verify_signature: true,
+            ..Default::default()
+        };
+        let state = make_state(1, "genesis", 100);
+        let (pubkey, key) = ubl_kernel::generate_keypair();
+        let mut link = make_commit(1, "genesis", 0, IntentClass::Observation);
+        link.author_pubkey = pubkey;
+        link.signature = ubl_kernel::sign(&key, &link.signing_bytes());
+        assert!(decide_with(&link, &state, &verify).is_accept());
+
+        // Any signed field altered, or a mock signature, fails V2
+        let mut altered = link.clone();
+        altered.physics_delta = 5;
+        altered.intent_class = IntentClass::Entropy;
+        assert!(matches!(validate_with(&altered, &state, &verify), Err(MembraneError::InvalidSignature)));
+        assert!(decide(&altered, &state).is_accept());
+        let mock = make_commit(1, "genesis", 0, IntentClass::Observation);
+        assert!(matches!(
+            decide_with(&mock, &state, &verify),
+            Decision::Reject(MembraneError::InvalidSignature)
+        ));
+    }
 }
@@ -0,0 +1,370 @@
+//! Validator pipeline
+//!
+//! [`validate_with`](crate::validate_with) runs the normative validations
+//! (V1–V7 and V9; pact proofs and V8 need a registry, see
+//! [`check_pact`](crate::check_pact)) as an ordered [`Pipeline`] of
+//! [`Validator`]s, stopping at the first error. A deployment with checks of
+//! its own (jurisdiction rules, amount limits, …) starts from
+//! [`Pipeline::normative`] and adds them, anywhere in the order, without
+//! forking the crate. Custom validators report through the canonical
+//! [`MembraneError`]s, so a rejection means the same to every client. The
+//! normative set stays the default: removing or replacing a normative
+//! validator is not offered.
+
+use std::time::Instant;
+
+use ubl_link::{AtomHash, EntryHash, IntentClass, LinkCommit, PubKeyHex, SignatureHex};
+
+use crate::report::{CheckReport, ValidationReport};
+use crate::{link_version, Decision, LedgerState, MembraneError, Result, ValidationOptions};
+
+/// One check a link goes through before it enters the ledger
+pub trait Validator: Send + Sync {
+    /// Name in the pipeline: `V1`–`V7` and `V9` for the normative
+    /// validations
+    fn id(&self) -> &str;
+
+    /// Check `link` against `state` under `options`
+    fn check(&self, link: &LinkCommit, state: &LedgerState, options: &ValidationOptions) -> Result<()>;
+}
+
+/// Ordered validators, run in turn until one fails
+pub struct Pipeline {
+    validators: Vec<Box<dyn Validator>>,
+}
+
+impl Pipeline {
+    /// The normative validations, V1–V7 and V9 in spec order
+    pub fn normative() -> Self {
+        Pipeline {
+            validators: vec![
+                Box::new(Version),
+                Box::new(Signature),
+                Box::new(Target),
+                Box::new(Drift),
+                Box::new(Sequence),
+                Box::new(Physics),
+                Box::new(Standing),
+                Box::new(Timestamp),
+            ],
+        }
+    }
+
+    /// Append `validator`, to run after the ones already in place
+    pub fn push(&mut self, validator: impl Validator + 'static) -> &mut Self {
+        self.validators.push(Box::new(validator));
+        self
+    }
+
+    /// Insert `validator` right before the one named `id`; false (and
+    /// nothing inserted) if there is none
+    pub fn insert_before(&mut self, id: &str, validator: impl Validator + 'static) -> bool {
+        let Some(at) = self.validators.iter().position(|v| v.id() == id) else {
+            return false;
+        };
+        self.validators.insert(at, Box::new(validator));
+        true
+    }
+
+    /// Validator names, in order
+    pub fn ids(&self) -> Vec<&str> {
+        self.validators.iter().map(|v| v.id()).collect()
+    }
+
+    /// Run every validator in order; the first error rejects the link
+    pub fn validate(&self, link: &LinkCommit, state: &LedgerState, options: &ValidationOptions) -> Result<()> {
+        self.validators.iter().try_for_each(|v| v.check(link, state, options))
+    }
+
+    /// Run every validator, past failures, and report each one's result
+    /// and timing ([`ValidationReport`])
+    pub fn validate_full(
+        &self,
+        link: &LinkCommit,
+        state: &LedgerState,
+        options: &ValidationOptions,
+    ) -> ValidationReport {
+        let checks = self
+            .validators
+            .iter()
+            .map(|v| {
+                let started = Instant::now();
+                let result = v.check(link, state, options);
+                CheckReport {
+                    id: v.id().to_string(),
+                    result,
+                    elapsed: started.elapsed(),
+                }
+            })
+            .collect();
+        ValidationReport { checks }
+    }
+
+    /// Validate a causally ordered batch against `state`, applying each
+    /// accepted link's sequence, delta and entry hash (`entry_hash`) before
+    /// the next (see [`validate_batch`](crate::validate_batch)); one result
+    /// per link
+    pub fn validate_batch(
+        &self,
+        links: &[LinkCommit],
+        state: &LedgerState,
+        options: &ValidationOptions,
+        entry_hash: impl Fn(&LinkCommit) -> String,
+    ) -> Vec<Result<()>> {
+        let mut state = state.clone();
+        let mut results = Vec::with_capacity(links.len());
+        for link in links {
+            let result = self.validate(link, &state, options);
+            if result.is_ok() {
+                // V6 checked these additions
+                state.next_sequence += 1;
+                state.physical_balance += link.physics_delta;
+                for (asset, delta) in &link.asset_deltas {
+                    *state.asset_balances.entry(asset.clone()).or_insert(0) += delta;
+                }
+                if link.timestamp.is_some() {
+                    state.last_timestamp = link.timestamp;
+                }
+                state.last_hash = entry_hash(link);
+            }
+            results.push(result);
+        }
+        results
+    }
+
+    /// [`Pipeline::validate`] as a [`Decision`]
+    pub fn decide(&self, link: &LinkCommit, state: &LedgerState, options: &ValidationOptions) -> Decision {
+        match self.validate(link, state, options) {
+            Ok(()) => Decision::Accept,
+            Err(e) => Decision::Reject(e),
+        }
+    }
+}
+
+impl Default for Pipeline {
+    fn default() -> Self {
+        Pipeline::normative()
+    }
+}
+
+/// V1 - Version check
+pub struct Version;
+
+impl Validator for Version {
+    fn id(&self) -> &str {
+        "V1"
+    }
+
+    fn check(&self, link: &LinkCommit, _state: &LedgerState, options: &ValidationOptions) -> Result<()> {
+        if link.version != link_version(options.spec) {
+            return Err(MembraneError::InvalidVersion);
+        }
+        Ok(())
+    }
+}
+
+/// V2 - Signature integrity: field formats (atom hash of 64 hex chars =
+/// 32 bytes), then the signature itself when
+/// [`ValidationOptions::verify_signature`] asks for it (not checked without
+/// the `verify` feature)
+pub struct Signature;
+
+impl Validator for Signature {
+    fn id(&self) -> &str {
+        "V2"
+    }
+
+    fn check(&self, link: &LinkCommit, _state: &LedgerState, options: &ValidationOptions) -> Result<()> {
+        if options.strict {
+            if AtomHash::parse(&link.atom_hash).is_err()
+                || PubKeyHex::parse(&link.author_pubkey).is_err()
+                || SignatureHex::parse(&link.signature).is_err()
+            {
+                return Err(MembraneError::InvalidSignature);
+            }
+        } else if (link.atom_hash.len() != 64 || hex::decode(&link.atom_hash).is_err()) && link.atom_hash.len() < 4 {
+            // Shorter hashes pass outside strict mode, for test fixtures
+            return Err(MembraneError::InvalidSignature);
+        }
+        #[cfg(feature = "verify")]
+        if options.verify_signature {
+            crate::verify_signature(link)?;
+        }
+        Ok(())
+    }
+}
+
+/// V3 - Container ID match (InvalidTarget)
+pub struct Target;
+
+impl Validator for Target {
+    fn id(&self) -> &str {
+        "V3"
+    }
+
+    fn check(&self, link: &LinkCommit, state: &LedgerState, _options: &ValidationOptions) -> Result<()> {
+        if link.container_id != state.container_id {
+            return Err(MembraneError::InvalidTarget);
+        }
+        Ok(())
+    }
+}
+
+/// V4 - Reality drift (causal chain); no malformed hash is a chain head
+pub struct Drift;
+
+impl Validator for Drift {
+    fn id(&self) -> &str {
+        "V4"
+    }
+
+    fn check(&self, link: &LinkCommit, state: &LedgerState, options: &ValidationOptions) -> Result<()> {
+        if link.previous_hash != state.last_hash || (options.strict && EntryHash::parse(&link.previous_hash).is_err()) {
+            return Err(MembraneError::RealityDrift);
+        }
+        Ok(())
+    }
+}
+
+/// V5 - Sequence continuity
+pub struct Sequence;
+
+impl Validator for Sequence {
+    fn id(&self) -> &str {
+        "V5"
+    }
+
+    fn check(&self, link: &LinkCommit, state: &LedgerState, _options: &ValidationOptions) -> Result<()> {
+        if link.expected_sequence != state.next_sequence {
+            return Err(MembraneError::SequenceMismatch);
+        }
+        Ok(())
+    }
+}
+
+/// V6 - Profile rules, then physics invariants, per asset, then the
+/// container's own ([`ValidationOptions::invariants`])
+pub struct Physics;
+
+impl Validator for Physics {
+    fn id(&self) -> &str {
+        "V6"
+    }
+
+    fn check(&self, link: &LinkCommit, state: &LedgerState, options: &ValidationOptions) -> Result<()> {
+        options.profile.check_physics(link.intent_class, link.physics_delta)?;
+        for delta in link.asset_deltas.values() {
+            options.profile.check_physics(link.intent_class, *delta)?;
+        }
+
+        // Balances after the link, in every asset: none may overflow
+        let resulting_balance = add_delta(state.physical_balance, link.physics_delta)?;
+        let resulting_assets = link
+            .asset_deltas
+            .iter()
+            .map(|(asset, delta)| {
+                let balance = state.asset_balances.get(asset).copied().unwrap_or(0);
+                Ok((asset, add_delta(balance, *delta)?))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        match link.intent_class {
+            IntentClass::Observation => {
+                // Observations must have zero delta, in every asset
+                if link.physics_delta != 0 {
+                    return Err(MembraneError::PhysicsViolation {
+                        reason: format!("Observation must have delta=0, got {}", link.physics_delta),
+                    });
+                }
+                if let Some((asset, delta)) = link.asset_deltas.iter().find(|(_, delta)| **delta != 0) {
+                    return Err(MembraneError::PhysicsViolation {
+                        reason: format!("Observation must have delta=0, got {} {}", delta, asset),
+                    });
+                }
+            }
+            IntentClass::Conservation => {
+                // Conservation: balance must remain >= 0, in every asset
+                if resulting_balance < 0 {
+                    return Err(MembraneError::PhysicsViolation {
+                        reason: format!("Conservation requires balance >= 0, would be {}", resulting_balance),
+                    });
+                }
+                for (asset, resulting_balance) in resulting_assets {
+                    if resulting_balance < 0 {
+                        return Err(MembraneError::PhysicsViolation {
+                            reason: format!(
+                                "Conservation requires {} balance >= 0, would be {}",
+                                asset, resulting_balance
+                            ),
+                        });
+                    }
+                }
+            }
+            IntentClass::Entropy => {
+                // Entropy allows creation/destruction - no additional checks
+            }
+            IntentClass::Evolution => {
+                // Evolution is for rule changes: authorized by V8 (check_pact),
+                // which needs the pacts and the policy decision
+            }
+        }
+        options.invariants.check(link, state)
+    }
+}
+
+/// `balance + delta`, or a physics violation where an i128 cannot hold it
+fn add_delta(balance: i128, delta: i128) -> Result<i128> {
+    balance
+        .checked_add(delta)
+        .ok_or_else(|| MembraneError::PhysicsViolation {
+            reason: "overflow".to_string(),
+        })
+}
+
+/// V7 - Pact standing: a container whose operating pact is not in force
+/// admits Observation only
+/// ([`Restricted`](crate::ContainerProfile::Restricted)). Pact proofs
+/// are V7 too, checked with the registry by [`check_pact`](crate::check_pact)
+pub struct Standing;
+
+impl Validator for Standing {
+    fn id(&self) -> &str {
+        "V7"
+    }
+
+    fn check(&self, link: &LinkCommit, _state: &LedgerState, options: &ValidationOptions) -> Result<()> {
+        options.profile.check_standing(link.intent_class)
+    }
+}
+
+/// V9 - Declared time: not before the previous entry's, and within the
+/// clock's skew of ledger time ([`ValidationOptions::clock`])
+pub struct Timestamp;
+
+impl Validator for Timestamp {
+    fn id(&self) -> &str {
+        "V9"
+    }
+
+    fn check(&self, link: &LinkCommit, state: &LedgerState, options: &ValidationOptions) -> Result<()> {
+        let Some(timestamp) = link.timestamp else {
+            return Ok(());
+        };
+        if let Some(last) = state.last_timestamp.filter(|last| timestamp < *last) {
+            return Err(MembraneError::TimestampSkew {
+                reason: format!("declared {}, before the previous entry's {}", timestamp, last),
+            });
+        }
+        if let Some(clock) = options.clock {
+            if timestamp.abs_diff(clock.now) > clock.max_skew.unsigned_abs() {
+                return Err(MembraneError::TimestampSkew {
+                    reason: format!(
+                        "declared {}, more than {}s from ledger time {}",
+                        timestamp, clock.max_skew, clock.now
+                    ),
+                });
+            }
+        }
+        Ok(())
+    }
+}
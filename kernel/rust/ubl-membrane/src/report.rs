@@ -0,0 +1,68 @@
+//! Validation reports
+//!
+//! [`validate`](crate::validate) and [`decide`](crate::decide) stop at the
+//! first failure: that is all the commit path needs. A client chasing why
+//! its links are rejected wants every failure at once, so
+//! [`validate_full`](crate::validate_full) runs every check in the
+//! pipeline regardless and reports each one: its result and how long it
+//! took. The report's [`decision`](ValidationReport::decision) is the one
+//! the fail-fast path would reach.
+
+use std::time::Duration;
+
+use crate::{Decision, MembraneError, Result};
+
+/// One check as run for a report
+#[derive(Debug, Clone)]
+pub struct CheckReport {
+    /// Validator name (`V1`–`V9`, or a custom validator's)
+    pub id: String,
+    /// Pass, or the error the check failed with
+    pub result: Result<()>,
+    /// Time the check took
+    pub elapsed: Duration,
+}
+
+impl CheckReport {
+    /// Whether the check passed
+    pub fn passed(&self) -> bool {
+        self.result.is_ok()
+    }
+}
+
+/// Every check of a pipeline run over one link, in pipeline order
+#[derive(Debug, Clone, Default)]
+pub struct ValidationReport {
+    /// The checks, in the order they ran
+    pub checks: Vec<CheckReport>,
+}
+
+impl ValidationReport {
+    /// Whether every check passed
+    pub fn is_accept(&self) -> bool {
+        self.checks.iter().all(CheckReport::passed)
+    }
+
+    /// The failed checks, in order
+    pub fn failures(&self) -> impl Iterator<Item = &CheckReport> {
+        self.checks.iter().filter(|c| !c.passed())
+    }
+
+    /// The first failure: the error fail-fast validation returns
+    pub fn first_error(&self) -> Option<&MembraneError> {
+        self.checks.iter().find_map(|c| c.result.as_ref().err())
+    }
+
+    /// Total time across the checks
+    pub fn elapsed(&self) -> Duration {
+        self.checks.iter().map(|c| c.elapsed).sum()
+    }
+
+    /// The decision fail-fast validation reaches
+    pub fn decision(&self) -> Decision {
+        match self.first_error() {
+            None => Decision::Accept,
+            Some(e) => Decision::Reject(e.clone()),
+        }
+    }
+}
@@ -0,0 +1,44 @@
+//! Batches validated against the evolving ledger state
+
+#![cfg(feature = "verify")]
+
+use ubl_fixtures::Fixtures;
+use ubl_link::{IntentClass, LinkCommit};
+use ubl_membrane::{validate_batch, LedgerState, MembraneError};
+
+#[test]
+fn batch_applies_each_accepted_link() {
+    let chain = Fixtures::new(7).chain("import", 20);
+    let state = chain.genesis_state();
+    let mut links: Vec<LinkCommit> = chain.entries.iter().map(|e| e.link.clone()).collect();
+    assert!(validate_batch(&links, &state).iter().all(|r| r.is_ok()));
+
+    // An overdraft mid-batch is rejected and not applied: the rest fail V5
+    links[9].intent_class = IntentClass::Conservation;
+    links[9].physics_delta = -i128::MAX;
+    let results = validate_batch(&links, &state);
+    assert!(results[..9].iter().all(|r| r.is_ok()));
+    assert!(matches!(results[9], Err(MembraneError::PhysicsViolation { .. })));
+    assert!(results[10..]
+        .iter()
+        .all(|r| matches!(r, Err(MembraneError::SequenceMismatch))));
+
+    // The first link is held to the state's head
+    let drifted = LedgerState {
+        last_hash: chain.entries[0].entry_hash.clone(),
+        ..state.clone()
+    };
+    assert!(matches!(
+        validate_batch(&links[..1], &drifted)[0],
+        Err(MembraneError::RealityDrift)
+    ));
+    assert!(validate_batch(&[], &drifted).is_empty());
+
+    // Later links are held to the hash of the link before them, not to
+    // the one they name
+    let mut forged: Vec<LinkCommit> = chain.entries.iter().map(|e| e.link.clone()).collect();
+    forged[5].previous_hash = "ab".repeat(32);
+    let results = validate_batch(&forged, &state);
+    assert!(results[..5].iter().all(|r| r.is_ok()));
+    assert!(matches!(results[5], Err(MembraneError::RealityDrift)));
+}
@@ -0,0 +1,36 @@
+//! Property: every generated fixture chain passes strict validation, its
+//! signatures included
+
+use quickcheck::quickcheck;
+use ubl_fixtures::Fixtures;
+use ubl_membrane::{validate_with, ValidationOptions};
+
+fn chain_validates(seed: u64, len: u64) -> Result<(), String> {
+    let chain = Fixtures::new(seed).chain("wallet", len);
+    let options = ValidationOptions {
+        strict: true,
+        #[cfg(feature = "verify")]
+        verify_signature: true,
+        ..Default::default()
+    };
+    let mut state = chain.genesis_state();
+    for entry in &chain.entries {
+        validate_with(&entry.link, &state, &options)
+            .map_err(|e| format!("{}: {}", chain.locate(state.next_sequence), e))?;
+        state.last_hash = entry.entry_hash.clone();
+        state.next_sequence += 1;
+        state.physical_balance += entry.link.physics_delta;
+    }
+    Ok(())
+}
+
+#[test]
+fn fixture_chain_validates() {
+    chain_validates(42, 500).unwrap();
+}
+
+quickcheck! {
+    fn prop_fixture_chains_validate(seed: u64) -> bool {
+        chain_validates(seed, 64).is_ok()
+    }
+}
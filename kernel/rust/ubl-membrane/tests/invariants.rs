@@ -0,0 +1,73 @@
+//! Container physics invariants checked alongside the built-in rules
+
+use ubl_fixtures::Fixtures;
+use ubl_link::{IntentClass, LinkCommit};
+use ubl_membrane::invariants::{BalanceCap, DeltaMultiple};
+use ubl_membrane::{validate_with, Invariant, Invariants, LedgerState, MembraneError, ValidationOptions};
+
+/// No Entropy link may mint more than `limit` at once
+struct MintLimit {
+    limit: i128,
+}
+
+impl Invariant for MintLimit {
+    fn name(&self) -> &str {
+        "mint_limit"
+    }
+
+    fn check(&self, link: &LinkCommit, _state: &LedgerState) -> Result<(), String> {
+        if link.intent_class == IntentClass::Entropy && link.physics_delta > self.limit {
+            return Err(format!("mints {} at once", link.physics_delta));
+        }
+        Ok(())
+    }
+}
+
+fn reason(result: Result<(), MembraneError>) -> String {
+    match result {
+        Err(MembraneError::PhysicsViolation { reason }) => reason,
+        other => panic!("expected a physics violation, got {:?}", other),
+    }
+}
+
+#[test]
+fn container_invariants_apply_in_order() {
+    let chain = Fixtures::new(7).chain("capped", 1);
+    let mut link = chain.entries[0].link.clone();
+    link.physics_delta = 1_000;
+    link.asset_deltas.insert("EUR".to_string(), 250);
+    let mut state = chain.genesis_state();
+    let capped = ValidationOptions {
+        invariants: Invariants::new()
+            .with(BalanceCap {
+                asset: None,
+                cap: 1_000,
+            })
+            .with(DeltaMultiple {
+                asset: Some("EUR".to_string()),
+                unit: 100,
+            })
+            .with(MintLimit { limit: 500 }),
+        ..Default::default()
+    };
+    assert_eq!(
+        capped.invariants.names(),
+        ["balance_cap", "delta_multiple", "mint_limit"]
+    );
+    validate_with(&link, &state, &ValidationOptions::default()).unwrap();
+
+    assert!(reason(validate_with(&link, &state, &capped)).starts_with("delta_multiple: "));
+    link.asset_deltas.insert("EUR".to_string(), 300);
+    assert_eq!(
+        reason(validate_with(&link, &state, &capped)),
+        "mint_limit: mints 1000 at once"
+    );
+    link.physics_delta = 500;
+    validate_with(&link, &state, &capped).unwrap();
+    state.physical_balance = 501;
+    assert!(reason(validate_with(&link, &state, &capped)).starts_with("balance_cap: "));
+
+    // Built-in rules come first
+    link.intent_class = IntentClass::Observation;
+    assert!(reason(validate_with(&link, &state, &capped)).starts_with("Observation"));
+}
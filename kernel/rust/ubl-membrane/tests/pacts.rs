@@ -0,0 +1,84 @@
+//! V7–V8: pact proofs and Evolution authorization checked by the membrane
+
+#![cfg(feature = "verify")]
+
+use ubl_fixtures::{Fixtures, EPOCH};
+use ubl_link::{IntentClass, LinkCommit};
+use ubl_membrane::{validate_with_pacts, MembraneError, NoEvolution, ValidationOptions};
+use ubl_pact::{PactRegistry, RiskLevel};
+
+#[test]
+fn entropy_needs_a_valid_proof() {
+    let fixtures = Fixtures::new(7);
+    let (a, b) = (fixtures.keypair("alice"), fixtures.keypair("bob"));
+    let chain = fixtures.chain("fund", 1);
+    let pact = fixtures.pact("fund", Some(chain.container_id.as_str()), 2, &[&a, &b], RiskLevel::L4);
+    let state = chain.genesis_state();
+    let mut link = chain.entries[0].link.clone();
+    assert_eq!(link.intent_class, IntentClass::Entropy);
+    let options = ValidationOptions::default();
+    let mut registry = PactRegistry::new();
+    registry.register(pact.clone());
+
+    assert!(matches!(
+        validate_with_pacts(&link, &state, &options, &registry, &NoEvolution, EPOCH),
+        Err(MembraneError::PactViolation)
+    ));
+    link.pact = Some(Fixtures::pact_proof(&pact, &[&a], &link, 1));
+    assert!(matches!(
+        validate_with_pacts(&link, &state, &options, &registry, &NoEvolution, EPOCH),
+        Err(MembraneError::PactViolation)
+    ));
+    link.pact = Some(Fixtures::pact_proof(&pact, &[&a, &b], &link, 1));
+    validate_with_pacts(&link, &state, &options, &registry, &NoEvolution, EPOCH).unwrap();
+
+    // A proof for other link bytes does not carry over
+    let mut other = link.clone();
+    other.physics_delta += 1;
+    assert!(matches!(
+        validate_with_pacts(&other, &state, &options, &registry, &NoEvolution, EPOCH),
+        Err(MembraneError::PactViolation)
+    ));
+}
+
+#[test]
+fn evolution_needs_an_l5_pact_and_the_policy() {
+    let fixtures = Fixtures::new(7);
+    let (a, b) = (fixtures.keypair("alice"), fixtures.keypair("bob"));
+    let chain = fixtures.chain("fund", 1);
+    let container_id = Some(chain.container_id.as_str());
+    let systemic = fixtures.pact("systemic", container_id, 2, &[&a, &b], RiskLevel::L4);
+    let sovereign = fixtures.pact("sovereign", container_id, 2, &[&a, &b], RiskLevel::L5);
+    let state = chain.genesis_state();
+    let mut link = chain.entries[0].link.clone();
+    link.intent_class = IntentClass::Evolution;
+    link.physics_delta = 0;
+    let options = ValidationOptions::default();
+    let mut registry = PactRegistry::new();
+    registry.register(systemic.clone());
+    registry.register(sovereign.clone());
+    let allow = |_: &LinkCommit| true;
+
+    assert!(matches!(
+        validate_with_pacts(&link, &state, &options, &registry, &allow, EPOCH),
+        Err(MembraneError::UnauthorizedEvolution)
+    ));
+    link.pact = Some(Fixtures::pact_proof(&systemic, &[&a, &b], &link, 1));
+    assert!(matches!(
+        validate_with_pacts(&link, &state, &options, &registry, &allow, EPOCH),
+        Err(MembraneError::UnauthorizedEvolution)
+    ));
+    link.pact = Some(Fixtures::pact_proof(&sovereign, &[&a, &b], &link, 1));
+    validate_with_pacts(&link, &state, &options, &registry, &allow, EPOCH).unwrap();
+
+    // The policy has the last word, even on a valid L5 proof
+    assert!(matches!(
+        validate_with_pacts(&link, &state, &options, &registry, &NoEvolution, EPOCH),
+        Err(MembraneError::UnauthorizedEvolution)
+    ));
+
+    // Observations need no proof
+    link.intent_class = IntentClass::Observation;
+    link.pact = None;
+    validate_with_pacts(&link, &state, &options, &registry, &NoEvolution, EPOCH).unwrap();
+}
@@ -0,0 +1,64 @@
+//! Custom validators in the membrane pipeline
+
+use ubl_fixtures::Fixtures;
+use ubl_link::{IntentClass, LinkCommit};
+use ubl_membrane::{LedgerState, MembraneError, Pipeline, Result, ValidationOptions, Validator};
+
+/// Jurisdiction rule: no Entropy in a sanctioned container
+struct Jurisdiction {
+    sanctioned: String,
+}
+
+impl Validator for Jurisdiction {
+    fn id(&self) -> &str {
+        "jurisdiction"
+    }
+
+    fn check(&self, link: &LinkCommit, _state: &LedgerState, _options: &ValidationOptions) -> Result<()> {
+        if link.container_id == self.sanctioned && link.intent_class == IntentClass::Entropy {
+            return Err(MembraneError::PactViolation);
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn custom_validators_run_in_order() {
+    let chain = Fixtures::new(7).chain("sanctioned", 1);
+    let mut link = chain.entries[0].link.clone();
+    assert_eq!(link.intent_class, IntentClass::Entropy);
+    let state = chain.genesis_state();
+    let options = ValidationOptions::default();
+    let jurisdiction = || Jurisdiction {
+        sanctioned: chain.container_id.clone(),
+    };
+
+    let normative = Pipeline::normative();
+    assert_eq!(normative.ids(), ["V1", "V2", "V3", "V4", "V5", "V6", "V7", "V9"]);
+    normative.validate(&link, &state, &options).unwrap();
+
+    let mut pipeline = Pipeline::normative();
+    pipeline.push(jurisdiction());
+    assert!(matches!(
+        pipeline.validate(&link, &state, &options),
+        Err(MembraneError::PactViolation)
+    ));
+
+    // Ahead of V5, the custom check wins over a sequence mismatch
+    link.expected_sequence = 9;
+    assert!(matches!(
+        pipeline.validate(&link, &state, &options),
+        Err(MembraneError::SequenceMismatch)
+    ));
+    let mut early = Pipeline::normative();
+    assert!(early.insert_before("V5", jurisdiction()));
+    assert!(!early.insert_before("V42", jurisdiction()));
+    assert_eq!(
+        early.ids(),
+        ["V1", "V2", "V3", "V4", "jurisdiction", "V5", "V6", "V7", "V9"]
+    );
+    assert!(matches!(
+        early.validate(&link, &state, &options),
+        Err(MembraneError::PactViolation)
+    ));
+}
@@ -0,0 +1,158 @@
+//! ubl-pact-sign — offline pact signer
+//!
+//! ```text
+//! ubl-pact-sign --pact-id <id> --link-hash <hex> [--nonce <n>] [--key <file>]
+//! ubl-pact-sign --pact-id <id> --link-hash <hex> [--nonce <n>] --bytes
+//! ```
+//!
+//! Signs `PactProof::signing_bytes(link_hash, pact_id, nonce)` with a local
+//! Ed25519 key and prints the `PactSignature` (`{pubkey, signature}`) to
+//! stdout, ready for `POST /pacts/proofs/:link_hash/signatures` or a proof
+//! assembled by hand. Nothing touches the network, so the signer can stay
+//! air-gapped; only the link hash, pact id and nonce have to reach it.
+//!
+//! The key file holds the 32-byte Ed25519 secret, raw or as hex. Without
+//! `--key` the hex secret is read from stdin (prompted for on a terminal).
+//! `--bytes` prints the signing bytes as hex instead of signing, for
+//! signers that sign elsewhere (an HSM, a hardware wallet).
+//!
+//! Exit codes: 0 signed, 1 failed, 2 bad usage.
+
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::process::ExitCode;
+
+use ed25519_dalek::SigningKey;
+use ubl_pact::{PactProof, PactSignature};
+
+const USAGE: &str = "usage: ubl-pact-sign --pact-id <id> --link-hash <hex> [--nonce <n>] [--key <file> | --bytes]";
+
+#[derive(Debug, Default, PartialEq)]
+struct Args {
+    pact_id: String,
+    link_hash: String,
+    nonce: u64,
+    key: Option<String>,
+    bytes: bool,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+    let mut parsed = Args::default();
+    while let Some(flag) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("{} needs a value", flag));
+        match flag.as_str() {
+            "--pact-id" => parsed.pact_id = value()?,
+            "--link-hash" => parsed.link_hash = value()?.to_ascii_lowercase(),
+            "--nonce" => parsed.nonce = value()?.parse().map_err(|e| format!("--nonce: {}", e))?,
+            "--key" => parsed.key = Some(value()?),
+            "--bytes" => parsed.bytes = true,
+            other => return Err(format!("unknown argument {}", other)),
+        }
+    }
+    if parsed.pact_id.is_empty() || parsed.link_hash.is_empty() {
+        return Err("--pact-id and --link-hash are required".to_string());
+    }
+    if parsed.bytes && parsed.key.is_some() {
+        return Err("--bytes signs nothing; drop --key".to_string());
+    }
+    Ok(parsed)
+}
+
+/// Ed25519 secret from a key file's contents: 32 raw bytes or 64 hex digits
+fn parse_key(contents: &[u8]) -> Result<SigningKey, String> {
+    if let Ok(raw) = <[u8; 32]>::try_from(contents) {
+        return Ok(SigningKey::from_bytes(&raw));
+    }
+    let text = std::str::from_utf8(contents).map_err(|_| "key is neither 32 raw bytes nor hex".to_string())?;
+    let text = text.trim();
+    let text = text.strip_prefix("0x").unwrap_or(text);
+    let bytes = hex::decode(text).map_err(|e| format!("key is not hex: {}", e))?;
+    let raw = <[u8; 32]>::try_from(bytes.as_slice()).map_err(|_| format!("key is {} bytes, not 32", bytes.len()))?;
+    Ok(SigningKey::from_bytes(&raw))
+}
+
+fn read_key(path: Option<&str>) -> Result<SigningKey, String> {
+    if let Some(path) = path {
+        let contents = std::fs::read(path).map_err(|e| format!("{}: {}", path, e))?;
+        return parse_key(&contents);
+    }
+    let stdin = io::stdin();
+    if stdin.is_terminal() {
+        eprint!("Ed25519 secret key (hex): ");
+        io::stderr().flush().ok();
+    }
+    let mut line = String::new();
+    stdin.lock().read_line(&mut line).map_err(|e| format!("stdin: {}", e))?;
+    parse_key(line.as_bytes())
+}
+
+fn sign(args: &Args, key: &SigningKey) -> PactSignature {
+    let message = PactProof::signing_bytes(&args.link_hash, &args.pact_id, args.nonce);
+    PactSignature {
+        pubkey: ubl_kernel::pubkey_from_signing_key(key),
+        signature: ubl_kernel::sign(key, &message),
+    }
+}
+
+fn run(args: Args) -> Result<String, String> {
+    if args.bytes {
+        return Ok(hex::encode(PactProof::signing_bytes(&args.link_hash, &args.pact_id, args.nonce)));
+    }
+    let key = read_key(args.key.as_deref())?;
+    serde_json::to_string_pretty(&sign(&args, &key)).map_err(|e| e.to_string())
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{}\n{}", e, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+    match run(args) {
+        Ok(out) => {
+            println!("{}", out);
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("ubl-pact-sign: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Result<Args, String> {
+        parse_args(list.iter().map(|s| s.to_string()))
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let parsed = args(&["--pact-id", "pact_fund", "--link-hash", "0xABC", "--nonce", "7"]).unwrap();
+        assert_eq!((parsed.link_hash.as_str(), parsed.nonce, parsed.key), ("0xabc", 7, None));
+        assert!(args(&["--pact-id", "pact_fund"]).is_err());
+        assert!(args(&["--pact-id", "pact_fund", "--link-hash", "0xabc", "--nonce"]).is_err());
+        assert!(args(&["--pact-id", "p", "--link-hash", "0xabc", "--bytes", "--key", "k"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_key() {
+        let hex_key = format!("0x{}\n", "07".repeat(32));
+        let key = parse_key(hex_key.as_bytes()).unwrap();
+        assert_eq!(key.to_bytes(), [7; 32]);
+        assert_eq!(parse_key(&[7; 32]).unwrap().to_bytes(), [7; 32]);
+        assert!(parse_key(b"0707").is_err());
+    }
+
+    #[test]
+    fn test_signature_verifies_under_pact_bytes() {
+        let args = args(&["--pact-id", "pact_fund", "--link-hash", "0xABC", "--nonce", "3"]).unwrap();
+        let key = SigningKey::from_bytes(&[9; 32]);
+        let signature = sign(&args, &key);
+        let message = PactProof::signing_bytes("0xabc", "pact_fund", 3);
+        assert!(ubl_kernel::verify(&signature.pubkey, &message, &signature.signature).is_ok());
+    }
+}
@@ -0,0 +1,138 @@
+//! BLS12-381 aggregate proofs (feature `bls`)
+//!
+//! A pact whose [`Pact::signature_scheme`](crate::Pact::signature_scheme)
+//! is BLS12-381 lists its signers' BLS public keys, and a proof under it
+//! carries one [`BlsAggregate`](crate::BlsAggregate): every signer signs
+//! the same [`PactProof::signing_bytes`](crate::PactProof::signing_bytes)
+//! and the signatures are added into one. Verification is two pairings
+//! however many signed, where an Ed25519 proof checks each signature.
+//!
+//! Keys are in G1 (48 bytes compressed) and signatures in G2 (96 bytes),
+//! the "min-pk" variant, under the proof-of-possession ciphersuite [`DST`].
+//! Aggregating over one message is only sound against keys whose holders
+//! proved possession of the secret: otherwise a rogue key chosen from the
+//! others' cancels them out. Check [`verify_possession`] for every signer
+//! before registering a BLS12-381 pact.
+
+use blst::min_pk::{PublicKey, Signature};
+use blst::BLST_ERROR;
+
+use crate::{PactError, Result};
+
+/// Ciphersuite of the signatures over pact signing bytes
+pub const DST: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+
+/// Ciphersuite of proofs of possession, each over its own public key
+pub const POP_DST: &[u8] = b"BLS_POP_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+
+/// Public key from hex, checked to be a valid, non-identity G1 point
+fn public_key(pubkey: &str) -> Result<PublicKey> {
+    let bytes = hex::decode(pubkey).map_err(|_| PactError::InvalidSignature(pubkey.to_string()))?;
+    PublicKey::key_validate(&bytes).map_err(|_| PactError::InvalidSignature(pubkey.to_string()))
+}
+
+/// Signature from hex, checked to be a valid G2 point
+fn signature(signature: &str, signer: &str) -> Result<Signature> {
+    let bytes = hex::decode(signature).map_err(|_| PactError::InvalidSignature(signer.to_string()))?;
+    Signature::sig_validate(&bytes, true).map_err(|_| PactError::InvalidSignature(signer.to_string()))
+}
+
+/// Check that `aggregate` (hex) is the sum of each of `pubkeys` signing
+/// `message`. The caller has checked the keys are distinct signers.
+pub fn verify_aggregate(pubkeys: &[&str], message: &[u8], aggregate: &str) -> Result<()> {
+    let keys = pubkeys.iter().map(|k| public_key(k)).collect::<Result<Vec<_>>>()?;
+    let keys: Vec<&PublicKey> = keys.iter().collect();
+    let aggregate = signature(aggregate, "aggregate")?;
+    match aggregate.fast_aggregate_verify(false, message, DST, &keys) {
+        BLST_ERROR::BLST_SUCCESS => Ok(()),
+        _ => Err(PactError::InvalidSignature("aggregate".to_string())),
+    }
+}
+
+/// Check `proof` (hex), the key holder's signature over the key's own
+/// compressed bytes under [`POP_DST`]
+pub fn verify_possession(pubkey: &str, proof: &str) -> Result<()> {
+    let key = public_key(pubkey)?;
+    let proof = signature(proof, pubkey)?;
+    match proof.verify(false, &key.to_bytes(), POP_DST, &[], &key, false) {
+        BLST_ERROR::BLST_SUCCESS => Ok(()),
+        _ => Err(PactError::InvalidSignature(pubkey.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BlsAggregate, Pact, PactProof, PactRegistry, RiskLevel, SignatureScheme, TimeWindow};
+    use blst::min_pk::{AggregateSignature, SecretKey};
+
+    fn key(seed: u8) -> SecretKey {
+        SecretKey::key_gen(&[seed; 32], &[]).unwrap()
+    }
+
+    fn pubkey(sk: &SecretKey) -> String {
+        hex::encode(sk.sk_to_pk().to_bytes())
+    }
+
+    fn aggregate(keys: &[&SecretKey], message: &[u8]) -> String {
+        let sigs: Vec<Signature> = keys.iter().map(|k| k.sign(message, DST, &[])).collect();
+        let sigs: Vec<&Signature> = sigs.iter().collect();
+        hex::encode(AggregateSignature::aggregate(&sigs, false).unwrap().to_signature().to_bytes())
+    }
+
+    #[test]
+    fn test_aggregate_proof() {
+        let keys: Vec<SecretKey> = (1..=5).map(key).collect();
+        let mut registry = PactRegistry::new();
+        let window = TimeWindow { not_before: 0, not_after: 1000 };
+        registry.register(Pact {
+            signers: keys.iter().map(pubkey).collect(),
+            signature_scheme: SignatureScheme::Bls12381,
+            ..crate::templates::base("pact_bls", "C.Test", &[], 3, window, RiskLevel::L2)
+        });
+
+        let link_hash = "0xabc";
+        let message = PactProof::signing_bytes(link_hash, "pact_bls", 0);
+        let proof = |signers: &[&SecretKey], message: &[u8]| PactProof {
+            pact_id: "pact_bls".to_string(),
+            signatures: Vec::new(),
+            nonce: 0,
+            delegations: Vec::new(),
+            frost_signature: None,
+            bls_aggregate: Some(BlsAggregate {
+                signers: signers.iter().map(|k| pubkey(k)).collect(),
+                signature: aggregate(signers, message),
+            }),
+        };
+        let validate = |p: &PactProof| registry.validate(p, "C.Test", link_hash, 0x00, 500);
+
+        assert!(validate(&proof(&[&keys[0], &keys[1], &keys[2]], &message)).is_ok());
+        assert!(matches!(
+            validate(&proof(&[&keys[0], &keys[1]], &message)),
+            Err(PactError::InsufficientSignatures { got: 2, need: 3 })
+        ));
+        assert!(matches!(
+            validate(&proof(&[&keys[0], &keys[1], &keys[2]], b"other")),
+            Err(PactError::InvalidSignature(_))
+        ));
+
+        // A signer listed twice, or claimed without signing, fails
+        let mut twice = proof(&[&keys[0], &keys[1], &keys[2]], &message);
+        twice.bls_aggregate.as_mut().unwrap().signers[2] = pubkey(&keys[0]);
+        assert!(matches!(validate(&twice), Err(PactError::DuplicateSigner(_))));
+        let mut claimed = proof(&[&keys[0], &keys[1]], &message);
+        claimed.bls_aggregate.as_mut().unwrap().signers.push(pubkey(&keys[3]));
+        assert!(matches!(validate(&claimed), Err(PactError::InvalidSignature(_))));
+    }
+
+    #[test]
+    fn test_proof_of_possession() {
+        let sk = key(7);
+        let pk = sk.sk_to_pk();
+        let pop = hex::encode(sk.sign(&pk.to_bytes(), POP_DST, &[]).to_bytes());
+        assert!(verify_possession(&pubkey(&sk), &pop).is_ok());
+        assert!(verify_possession(&pubkey(&key(8)), &pop).is_err());
+        let wrong_suite = hex::encode(sk.sign(&pk.to_bytes(), DST, &[]).to_bytes());
+        assert!(verify_possession(&pubkey(&sk), &wrong_suite).is_err());
+    }
+}
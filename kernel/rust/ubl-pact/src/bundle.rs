@@ -0,0 +1,188 @@
+//! Signed pact bundles
+//!
+//! A [`PactBundle`] moves a set of pacts between environments (staging to
+//! production). It carries the pacts, where and when they were exported,
+//! the BLAKE3 hash of all that as canonical JSON, and Ed25519 signatures
+//! over [`PactBundle::signing_bytes`] of the hash. The importing side
+//! recomputes the hash and only accepts the pacts once `threshold` of the
+//! keys it trusts have signed it ([`PactBundle::verify`]), so no pact can
+//! be added, dropped or altered in transit.
+
+use std::collections::BTreeSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::signer::PactSigner;
+use crate::{Pact, PactError, PactSignature, Result};
+
+/// `format` of a pact bundle
+pub const BUNDLE_FORMAT: &str = "ubl.pact.bundle.v1";
+
+/// Domain tag of the bytes a bundle signer signs
+pub const BUNDLE_DOMAIN: &[u8] = b"ubl:pact-bundle:v1\n";
+
+/// Pacts exported together, with signatures over their hash
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PactBundle {
+    /// Always [`BUNDLE_FORMAT`]
+    pub format: String,
+    /// Environment the pacts were exported from
+    pub source: String,
+    /// Unix timestamp of the export
+    pub exported_at: i64,
+    /// The pacts, ordered by `pact_id`
+    pub pacts: Vec<Pact>,
+    /// [`PactBundle::hash`] at export time
+    pub bundle_hash: String,
+    /// Signatures over [`PactBundle::signing_bytes`]
+    #[serde(default)]
+    pub signatures: Vec<PactSignature>,
+}
+
+impl PactBundle {
+    /// Bundle `pacts`, unsigned
+    pub fn new(source: &str, exported_at: i64, mut pacts: Vec<Pact>) -> Self {
+        pacts.sort_by(|a, b| a.pact_id.cmp(&b.pact_id));
+        let mut bundle = PactBundle {
+            format: BUNDLE_FORMAT.to_string(),
+            source: source.to_string(),
+            exported_at,
+            pacts,
+            bundle_hash: String::new(),
+            signatures: Vec::new(),
+        };
+        bundle.bundle_hash = bundle.hash();
+        bundle
+    }
+
+    /// BLAKE3 over the canonical JSON of format, source, export time and pacts (hex)
+    pub fn hash(&self) -> String {
+        let content = serde_json::json!({
+            "format": self.format,
+            "source": self.source,
+            "exported_at": self.exported_at,
+            "pacts": self.pacts,
+        });
+        let canonical = ubl_atom::canonicalize(&content).expect("pact bundle is finite JSON");
+        ubl_kernel::hash_atom(&canonical)
+    }
+
+    /// [`BUNDLE_DOMAIN`] followed by the bundle hash
+    pub fn signing_bytes(bundle_hash: &str) -> Vec<u8> {
+        [BUNDLE_DOMAIN, bundle_hash.to_ascii_lowercase().as_bytes()].concat()
+    }
+
+    /// Add `signer`'s signature
+    pub fn sign(&mut self, signer: &dyn PactSigner) -> Result<()> {
+        let signature = signer.sign(&Self::signing_bytes(&self.bundle_hash))?;
+        self.signatures.push(PactSignature {
+            pubkey: signer.pubkey(),
+            signature,
+        });
+        Ok(())
+    }
+
+    /// Check the bundle is intact and signed by `threshold` distinct keys of
+    /// `trusted`. Signatures from other keys are ignored; a trusted key
+    /// whose signature does not verify rejects the bundle outright.
+    pub fn verify(&self, trusted: &BTreeSet<String>, threshold: usize) -> Result<()> {
+        if self.format != BUNDLE_FORMAT {
+            return Err(PactError::InvalidBundle(format!("unsupported format {}", self.format)));
+        }
+        let computed = self.hash();
+        if !computed.eq_ignore_ascii_case(&self.bundle_hash) {
+            return Err(PactError::InvalidBundle(format!(
+                "hash mismatch: declared {}, computed {}",
+                self.bundle_hash, computed
+            )));
+        }
+        let mut ids = BTreeSet::new();
+        if let Some(pact) = self.pacts.iter().find(|p| !ids.insert(&p.pact_id)) {
+            return Err(PactError::InvalidBundle(format!("pact {} appears twice", pact.pact_id)));
+        }
+        let message = Self::signing_bytes(&computed);
+        let mut signed = BTreeSet::new();
+        for sig in &self.signatures {
+            let pubkey = sig.pubkey.to_ascii_lowercase();
+            if !trusted.contains(&pubkey) {
+                continue;
+            }
+            ubl_kernel::verify(&pubkey, &message, &sig.signature)
+                .map_err(|_| PactError::InvalidSignature(pubkey.clone()))?;
+            signed.insert(pubkey);
+        }
+        let need = threshold.max(1);
+        if signed.len() < need {
+            return Err(PactError::InsufficientSignatures { got: signed.len(), need });
+        }
+        Ok(())
+    }
+
+    /// The pacts, once [`PactBundle::verify`] passes
+    pub fn into_pacts(self, trusted: &BTreeSet<String>, threshold: usize) -> Result<Vec<Pact>> {
+        self.verify(trusted, threshold)?;
+        Ok(self.pacts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::templates;
+    use crate::{RiskLevel, TimeWindow};
+    use ed25519_dalek::SigningKey;
+
+    fn pact(pact_id: &str) -> Pact {
+        let window = TimeWindow {
+            not_before: 0,
+            not_after: 1000,
+        };
+        templates::two_of_three(pact_id, "C.Test", ["aa", "bb", "cc"], window, RiskLevel::L2)
+    }
+
+    fn trusted(keys: &[&SigningKey]) -> BTreeSet<String> {
+        keys.iter().map(|k| ubl_kernel::pubkey_from_signing_key(k)).collect()
+    }
+
+    #[test]
+    fn test_signed_bundle_round_trip() {
+        let staging = SigningKey::from_bytes(&[1; 32]);
+        let other = SigningKey::from_bytes(&[2; 32]);
+        let mut bundle = PactBundle::new("staging", 500, vec![pact("pact_b"), pact("pact_a")]);
+        bundle.sign(&staging).unwrap();
+
+        let json = serde_json::to_string(&bundle).unwrap();
+        let imported: PactBundle = serde_json::from_str(&json).unwrap();
+        let pacts = imported.clone().into_pacts(&trusted(&[&staging]), 1).unwrap();
+        let ids: Vec<&str> = pacts.iter().map(|p| p.pact_id.as_str()).collect();
+        assert_eq!(ids, ["pact_a", "pact_b"]);
+
+        assert!(matches!(
+            imported.verify(&trusted(&[&other]), 1),
+            Err(PactError::InsufficientSignatures { got: 0, need: 1 })
+        ));
+        assert!(matches!(
+            imported.verify(&trusted(&[&staging, &other]), 2),
+            Err(PactError::InsufficientSignatures { got: 1, need: 2 })
+        ));
+    }
+
+    #[test]
+    fn test_tampered_bundle_is_refused() {
+        let key = SigningKey::from_bytes(&[1; 32]);
+        let keys = trusted(&[&key]);
+        let mut bundle = PactBundle::new("staging", 500, vec![pact("pact_a"), pact("pact_b")]);
+        bundle.sign(&key).unwrap();
+
+        let mut altered = bundle.clone();
+        altered.pacts[0].threshold = 1;
+        assert!(matches!(altered.verify(&keys, 1), Err(PactError::InvalidBundle(_))));
+        let mut dropped = bundle.clone();
+        dropped.pacts.pop();
+        assert!(matches!(dropped.verify(&keys, 1), Err(PactError::InvalidBundle(_))));
+
+        // Rehashed after the change: the signature no longer matches
+        altered.bundle_hash = altered.hash();
+        assert!(matches!(altered.verify(&keys, 1), Err(PactError::InvalidSignature(_))));
+    }
+}
@@ -0,0 +1,109 @@
+//! Emergency freeze
+//!
+//! An emergency pact ([`Pact::emergency`], Global scope) is the commit
+//! path's kill-switch (SPEC-UBL-PACT v1.0 §9.7). A proof under it over
+//! [`FreezeAction::Freeze`]'s [`subject`](FreezeAction::subject) freezes
+//! the commit path: from then on every link but an Observation is refused,
+//! whatever its container or proof ([`PactError::Frozen`]), until a proof
+//! over [`FreezeAction::Lift`]'s subject lifts it. Lifting takes a quorum
+//! of its own: a freeze proof signs a different subject, and no proof acts
+//! twice.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Pact, PactError, PactProof, PactRegistry, PactScope, Result, SignatureTally};
+
+/// Domain tag of the subject an emergency proof signs
+pub const FREEZE_DOMAIN: &str = "ubl:pact-freeze:v1\n";
+
+/// What an emergency proof does to the commit path
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FreezeAction {
+    /// Refuse every link but Observations
+    Freeze,
+    /// Resume normal operation
+    Lift,
+}
+
+impl FreezeAction {
+    /// `freeze` or `lift`
+    pub fn as_str(self) -> &'static str {
+        match self {
+            FreezeAction::Freeze => "freeze",
+            FreezeAction::Lift => "lift",
+        }
+    }
+
+    /// BLAKE3 of [`FREEZE_DOMAIN`] and the action (hex): what an emergency
+    /// proof signs in place of a link hash, as
+    /// [`PactProof::signing_bytes`]`(subject, pact_id, nonce)`
+    pub fn subject(self) -> String {
+        ubl_kernel::hash_atom(format!("{}{}", FREEZE_DOMAIN, self.as_str()).as_bytes())
+    }
+}
+
+/// The commit path's freeze, while in force
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Freeze {
+    /// Emergency pact the freeze proof was under
+    pub pact_id: String,
+    /// Unix timestamp of the freeze
+    pub since: i64,
+}
+
+/// Whether a link of `intent_class` may be committed while frozen:
+/// Observations (0x00) only
+pub fn admits(intent_class: u8) -> bool {
+    intent_class == 0x00
+}
+
+/// Whether `pact` may freeze or lift the commit path
+pub fn is_emergency(pact: &Pact) -> bool {
+    pact.emergency && pact.scope == PactScope::Global
+}
+
+impl PactRegistry {
+    /// Whether `proof` may take `action` at `now`: its pact is a Global
+    /// emergency pact in standing, and the proof meets the pact's quorum
+    /// at its own risk level over the action's subject
+    pub fn check_freeze(&self, proof: &PactProof, action: FreezeAction, now: i64) -> Result<()> {
+        let pact = self
+            .get(&proof.pact_id)
+            .ok_or_else(|| PactError::UnknownPact(proof.pact_id.clone()))?;
+        if !is_emergency(pact) {
+            return Err(PactError::NotEmergencyPact(pact.pact_id.clone()));
+        }
+        let subject = action.subject();
+        self.check_standing(pact, proof, &subject, now)?;
+        let message = PactProof::signing_bytes(&subject, &pact.pact_id, proof.nonce);
+        self.check_signatures(
+            pact,
+            pact.risk_level,
+            &message,
+            proof,
+            now,
+            &mut SignatureTally::default(),
+        )
+    }
+
+    /// Freeze or lift the commit path on `proof`, as
+    /// [`PactRegistry::check_freeze`] allows. The proof is consumed.
+    pub fn apply_freeze(&mut self, proof: &PactProof, action: FreezeAction, now: i64) -> Result<()> {
+        self.check_freeze(proof, action, now)?;
+        self.consume(proof, &action.subject())?;
+        self.frozen = match action {
+            FreezeAction::Freeze => Some(Freeze {
+                pact_id: proof.pact_id.clone(),
+                since: now,
+            }),
+            FreezeAction::Lift => None,
+        };
+        Ok(())
+    }
+
+    /// The commit path's freeze, if frozen
+    pub fn frozen(&self) -> Option<&Freeze> {
+        self.frozen.as_ref()
+    }
+}
@@ -14,11 +14,29 @@
 #![warn(missing_docs)]
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use thiserror::Error;
+use ubl_errors::ErrorCatalog;
+
+#[cfg(feature = "bls")]
+pub mod bls;
+pub mod bundle;
+pub mod freeze;
+pub mod offline;
+#[cfg(feature = "pkcs11")]
+pub mod pkcs11;
+pub mod signer;
+pub mod templates;
+
+pub use ubl_link::SpecVersion;
+pub use ubl_link::pact::{
+    BlsAggregate, PactProof, PactSignature, SignerDelegation, TimeWindow, DELEGATION_DOMAIN, SIGNING_DOMAIN,
+};
 
 /// Errors from pact validation
-#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[derive(Error, Debug, Clone, PartialEq, Eq, ErrorCatalog)]
+#[catalog(source = "pact", status = 422)]
 pub enum PactError {
     /// Unknown pact ID
     #[error("Unknown pact: {0}")]
@@ -28,17 +46,161 @@ pub enum PactError {
     #[error("Pact expired")]
     PactExpired,
 
+    /// Pact authorized as many proofs as its `max_uses` allows
+    #[error("Pact uses exhausted: used {used} of {max}")]
+    UsesExhausted {
+        /// Proofs already accepted under the pact
+        used: u64,
+        /// The pact's `max_uses`
+        max: u64,
+    },
+
+    /// Proof for this link and nonce was already consumed
+    #[error("Proof replayed: link {link_hash}, nonce {nonce}")]
+    #[catalog(status = 409)]
+    Replayed {
+        /// Link the proof authorizes
+        link_hash: String,
+        /// Nonce its signatures commit to
+        nonce: u64,
+    },
+
+    /// Pact was revoked by a quorum of its signers
+    #[error("Pact revoked")]
+    Revoked,
+
+    /// A veto signer vetoed the pact, or the link under validation
+    #[error("Vetoed by {0}")]
+    Vetoed(String),
+
     /// Insufficient signatures
     #[error("Insufficient signatures: got {got}, need {need}")]
     InsufficientSignatures { got: usize, need: usize },
 
+    /// A signer group's own threshold was not met
+    #[error("Group {group} quorum not met: got {got}, need {need}")]
+    GroupQuorumNotMet {
+        /// Name of the group
+        group: String,
+        /// Weight of the valid signatures from its members
+        got: usize,
+        /// Weight the group requires
+        need: usize,
+    },
+
     /// Unauthorized signer
     #[error("Unauthorized signer: {0}")]
+    #[catalog(status = 403)]
     UnauthorizedSigner(String),
 
     /// Risk level mismatch
     #[error("Risk mismatch: intent={intent:?}, pact={pact:?}")]
     RiskMismatch { intent: RiskLevel, pact: RiskLevel },
+
+    /// Signer appears twice in a proof
+    #[error("Duplicate signer: {0}")]
+    #[catalog(status = 409)]
+    DuplicateSigner(String),
+
+    /// Signature does not verify against the signer's key
+    #[error("Invalid signature from {0}")]
+    InvalidSignature(String),
+
+    /// The target container is outside the pact's scope
+    #[error("Scope mismatch: {container_id} is outside the pact's {scope:?} scope")]
+    ScopeMismatch {
+        /// Container the proof was presented for
+        container_id: String,
+        /// Scope of the pact
+        scope: PactScope,
+    },
+
+    /// Successor pact that cannot renew its predecessor
+    #[error("Invalid renewal: {0}")]
+    InvalidRenewal(String),
+
+    /// Threshold proof under a pact that has no FROST group key, or that
+    /// also carries individual signatures
+    #[error("Invalid threshold proof: {0}")]
+    InvalidThresholdProof(String),
+
+    /// Proof in a signature scheme other than the pact's
+    /// ([`Pact::signature_scheme`])
+    #[error("Scheme mismatch: pact signs with {pact:?}, proof with {proof:?}")]
+    SchemeMismatch {
+        /// Scheme the pact declares
+        pact: SignatureScheme,
+        /// Scheme the proof is in
+        proof: SignatureScheme,
+    },
+
+    /// Signature scheme this build cannot verify (BLS12-381 without the
+    /// `bls` feature)
+    #[error("Unsupported signature scheme: {0:?}")]
+    #[catalog(status = 501)]
+    UnsupportedScheme(SignatureScheme),
+
+    /// Delegation that does not let its key sign: out of its window, for
+    /// another pact, or past [`MAX_DELEGATION_DEPTH`]
+    #[error("Invalid delegation: {0}")]
+    InvalidDelegation(String),
+
+    /// Signature for a pact at or above [`signer::HARDWARE_RISK`] asked of
+    /// a signer whose key is not held in hardware
+    #[error("Hardware signer required for {0:?} pacts")]
+    #[catalog(status = 403)]
+    HardwareSignerRequired(RiskLevel),
+
+    /// Signer could not produce a signature (token missing, locked, key
+    /// not found…)
+    #[error("Signer failed: {0}")]
+    #[catalog(status = 502)]
+    SignerFailed(String),
+
+    /// Pact bundle that is malformed or was altered after export
+    #[error("Invalid pact bundle: {0}")]
+    InvalidBundle(String),
+
+    /// Child pact its parent cannot authorize: the parent is no meta-pact,
+    /// or the child is not named for it or reaches outside its scope
+    #[error("Invalid child pact: {0}")]
+    #[catalog(status = 403)]
+    InvalidChildPact(String),
+
+    /// Freeze or lift under a pact that is not a Global emergency pact
+    #[error("Not an emergency pact: {0}")]
+    #[catalog(status = 403)]
+    NotEmergencyPact(String),
+
+    /// Link other than an Observation while the commit path is frozen by
+    /// the emergency pact named
+    #[error("Commit path frozen by emergency pact {0}: Observation links only")]
+    #[catalog(status = 403)]
+    Frozen(String),
+
+    /// Malformed signing request or signature file
+    #[error("Invalid signing request: {0}")]
+    InvalidRequest(String),
+
+    /// Signing request was altered after export
+    #[error("Request hash mismatch: declared {declared}, computed {computed}")]
+    RequestHashMismatch {
+        /// Hash carried by the file
+        declared: String,
+        /// Hash of the request as read
+        computed: String,
+    },
+
+    /// Signature file answers a different request
+    #[error("Binding mismatch on {field}: expected {expected}, got {got}")]
+    BindingMismatch {
+        /// Field that differs
+        field: String,
+        /// Value in the pending proof
+        expected: String,
+        /// Value in the signature file
+        got: String,
+    },
 }
 
 /// Result type for pact operations
@@ -55,6 +217,24 @@ pub enum PactScope {
     Global = 2,
 }
 
+/// Signature scheme of a pact's signers (SPEC-UBL-PACT v1.0 §8.6)
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum SignatureScheme {
+    /// Ed25519 keys, one signature each ([`PactProof::signatures`])
+    #[default]
+    Ed25519 = 0,
+    /// BLS12-381 keys (min-pk), one aggregate signature for the whole
+    /// quorum ([`PactProof::bls_aggregate`])
+    Bls12381 = 1,
+}
+
+impl SignatureScheme {
+    /// Whether this is the default scheme, left out of serialized pacts
+    pub fn is_ed25519(&self) -> bool {
+        *self == SignatureScheme::Ed25519
+    }
+}
+
 /// Risk level (SPEC-UBL-PACT v1.0 §6)
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
 pub enum RiskLevel {
@@ -85,22 +265,6 @@ impl RiskLevel {
     }
 }
 
-/// Time window for pact validity (SPEC-UBL-PACT v1.0 §7)
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct TimeWindow {
-    /// Unix timestamp - not valid before this
-    pub not_before: i64,
-    /// Unix timestamp - not valid after this
-    pub not_after: i64,
-}
-
-impl TimeWindow {
-    /// Check if current time is within window
-    pub fn is_valid(&self, now: i64) -> bool {
-        now >= self.not_before && now <= self.not_after
-    }
-}
-
 /// Pact definition (SPEC-UBL-PACT v1.0 §4)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Pact {
@@ -113,11 +277,30 @@ pub struct Pact {
     /// Scope of application
     pub scope: PactScope,
     
-    /// Minimum threshold of signatures required
+    /// Minimum total weight of valid signatures ([`Pact::weight`]); a
+    /// signature count when no weights are set. [`Pact::risk_thresholds`]
+    /// may ask for another per risk level.
     pub threshold: usize,
     
-    /// Authorized signers (public keys in hex)
+    /// Authorized signers: public keys in hex, or UBL ID SIDs
+    /// ([`SID_PREFIX`]) that sign with the key they hold at validation time
     pub signers: HashSet<String>,
+
+    /// Optional: weight of each signer towards `threshold`; signers not
+    /// listed weigh 1
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weights: Option<HashMap<String, usize>>,
+
+    /// Optional: signer groups (roles), each with a threshold of its own
+    /// that must be met besides `threshold`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub groups: Vec<SignerGroup>,
+
+    /// Optional: keys (e.g. a compliance officer's) whose [`PactVeto`]
+    /// fails validation however many signers approved; they need not be
+    /// signers themselves
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    pub veto_signers: BTreeSet<String>,
     
     /// Time window
     pub window: TimeWindow,
@@ -127,230 +310,2027 @@ pub struct Pact {
     
     /// Optional: container ID if scope is Container
     pub container_id: Option<String>,
-}
 
-/// Pact proof attached to a link (SPEC-UBL-PACT v1.0 §8)
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct PactProof {
-    /// Reference to the pact
-    pub pact_id: String,
-    
-    /// Signatures from authorized signers
-    pub signatures: Vec<PactSignature>,
-}
+    /// Optional: namespace if scope is Namespace; it covers the containers
+    /// placed in it ([`PactRegistry::set_namespace`])
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub namespace: Option<String>,
 
-/// A single signature in a pact proof
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct PactSignature {
-    /// Signer's public key (hex)
-    pub pubkey: String,
-    
-    /// Signature (hex)
-    pub signature: String,
-}
+    /// Optional: `pact_id` of the pact this one renews (SPEC-UBL-PACT v1.0
+    /// §9.3); [`PactRegistry::lineage`] walks the chain
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub predecessor: Option<String>,
 
-/// Pact registry for validation
-pub struct PactRegistry {
-    pacts: std::collections::HashMap<String, Pact>,
+    /// Optional: how many proofs the pact authorizes in all, e.g. 1 for a
+    /// single-use emergency authorization; [`PactRegistry::record_use`]
+    /// counts them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_uses: Option<u64>,
+
+    /// Optional: FROST(Ed25519, SHA-512) group verifying key (hex) the
+    /// signers generated for `threshold`, accepting one threshold signature
+    /// in place of individual ones ([`PactProof::frost_signature`])
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub frost_group_key: Option<String>,
+
+    /// Scheme the signers' keys are in; BLS12-381 signers prove with one
+    /// aggregate signature ([`PactProof::bls_aggregate`])
+    #[serde(default, skip_serializing_if = "SignatureScheme::is_ed25519")]
+    pub signature_scheme: SignatureScheme,
+
+    /// Optional: threshold by the risk level an intent requires, in place
+    /// of `threshold`, so one signer set can ask fewer signatures of L2
+    /// links than of L4 ones ([`Pact::threshold_for`])
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub risk_thresholds: Option<BTreeMap<RiskLevel, usize>>,
+
+    /// Optional: the meta-pact whose proof authorized registering this
+    /// pact ([`PactRegistry::register_child`])
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
+
+    /// Whether this is a meta-pact: a proof under it authorizes
+    /// registering a child pact within its scope and risk level
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub issues_pacts: bool,
+
+    /// Whether this is an emergency pact: a proof under it freezes or
+    /// lifts the freeze of the commit path ([`freeze`]); Global scope only
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub emergency: bool,
 }
 
-impl PactRegistry {
-    /// Create a new empty registry
-    pub fn new() -> Self {
-        Self {
-            pacts: std::collections::HashMap::new(),
+impl Pact {
+    /// Whether `container_id`, in `namespace` if it was placed in one, is
+    /// within this pact's scope (SPEC-UBL-PACT v1.0 §5)
+    pub fn covers(&self, container_id: &str, namespace: Option<&str>) -> bool {
+        match self.scope {
+            PactScope::Container => self.container_id.as_deref() == Some(container_id),
+            PactScope::Namespace => self.namespace.is_some() && self.namespace.as_deref() == namespace,
+            PactScope::Global => true,
         }
     }
 
-    /// Register a pact
-    pub fn register(&mut self, pact: Pact) {
-        self.pacts.insert(pact.pact_id.clone(), pact);
+    /// Whether a further proof is allowed after `used` were accepted
+    pub fn check_uses(&self, used: u64) -> Result<()> {
+        match self.max_uses {
+            Some(max) if used >= max => Err(PactError::UsesExhausted { used, max }),
+            _ => Ok(()),
+        }
     }
 
-    /// Get a pact by ID
-    pub fn get(&self, pact_id: &str) -> Option<&Pact> {
-        self.pacts.get(pact_id)
+    /// Weight of `pubkey`'s signature towards the threshold
+    pub fn weight(&self, pubkey: &str) -> usize {
+        self.weights
+            .as_ref()
+            .and_then(|w| w.get(pubkey))
+            .copied()
+            .unwrap_or(1)
     }
 
-    /// Validate a pact proof (SPEC-UBL-PACT v1.0 §9)
-    pub fn validate(
-        &self,
-        proof: &PactProof,
-        intent_class: u8,
-        now: i64,
-    ) -> Result<()> {
-        // Get the pact
-        let pact = self
-            .get(&proof.pact_id)
-            .ok_or_else(|| PactError::UnknownPact(proof.pact_id.clone()))?;
+    /// Threshold for a link requiring `risk`: the entry of
+    /// [`Pact::risk_thresholds`] at the lowest level from `risk` up, or
+    /// `threshold` if there is none. A level never takes the quorum of a
+    /// lower one
+    pub fn threshold_for(&self, risk: RiskLevel) -> usize {
+        self.risk_thresholds
+            .as_ref()
+            .and_then(|t| t.range(risk..).next())
+            .map_or(self.threshold, |(_, threshold)| *threshold)
+    }
 
-        // Check time window
-        if !pact.window.is_valid(now) {
-            return Err(PactError::PactExpired);
+    /// The pact with `threshold` set to [`Pact::threshold_for`] `risk`
+    pub fn at_risk(&self, risk: RiskLevel) -> Cow<'_, Pact> {
+        let threshold = self.threshold_for(risk);
+        if threshold == self.threshold {
+            return Cow::Borrowed(self);
         }
+        Cow::Owned(Pact {
+            threshold,
+            ..self.clone()
+        })
+    }
 
-        // Check risk level
-        let required_risk = RiskLevel::from_intent_class(intent_class);
-        if pact.risk_level < required_risk {
-            return Err(PactError::RiskMismatch {
-                intent: required_risk,
-                pact: pact.risk_level,
-            });
+    /// Weight of every signer together, the most a proof can collect
+    pub fn total_weight(&self) -> usize {
+        self.signers.iter().map(|s| self.weight(s)).sum()
+    }
+
+    /// Evaluate the pact's quorum (SPEC-UBL-PACT v1.0 §9 step 5) over
+    /// distinct signers whose signatures already verified: `threshold` of
+    /// total weight, and every group's threshold of its members' weight
+    pub fn check_quorum(&self, signed: &[&str]) -> Result<()> {
+        check_quorum(self.threshold, &self.groups, |s| self.weight(s), signed)
+    }
+
+    /// BLAKE3 over the canonical JSON of the pact's terms (hex), `signers`
+    /// sorted: the draft its signers adopt (SPEC-UBL-PACT v1.0 §4.4)
+    pub fn draft_hash(&self) -> String {
+        let mut value = serde_json::to_value(self).expect("pact serializes");
+        let mut signers: Vec<&String> = self.signers.iter().collect();
+        signers.sort();
+        value["signers"] = serde_json::json!(signers);
+        let canonical = ubl_atom::canonicalize(&value).expect("pact is finite JSON");
+        ubl_kernel::hash_atom(&canonical)
+    }
+
+    /// Canonical bytes a signer signs to adopt the draft `draft_hash`:
+    ///
+    /// ```text
+    /// "ubl:pact-draft:v1\n" || u32be(len pact_id) || pact_id
+    ///                       || u32be(len draft_hash) || lowercase(draft_hash)
+    /// ```
+    pub fn adoption_bytes(pact_id: &str, draft_hash: &str) -> Vec<u8> {
+        let draft_hash = draft_hash.to_ascii_lowercase();
+        let mut bytes = Vec::with_capacity(DRAFT_DOMAIN.len() + 8 + pact_id.len() + draft_hash.len());
+        bytes.extend_from_slice(DRAFT_DOMAIN);
+        for field in [pact_id.as_bytes(), draft_hash.as_bytes()] {
+            bytes.extend_from_slice(&(field.len() as u32).to_be_bytes());
+            bytes.extend_from_slice(field);
         }
+        bytes
+    }
+
+    /// Check that distinct signers of this pact meeting its own quorum
+    /// signed [`Pact::adoption_bytes`] of its draft (the bootstrap quorum)
+    pub fn verify_adoption(&self, signatures: &[PactSignature]) -> Result<()> {
+        self.verify_adoption_by(self, signatures)
+    }
 
-        // Count valid signatures
-        let mut valid_count = 0;
-        let mut seen_pubkeys = HashSet::new();
+    /// [`Pact::verify_adoption`] where the signers sign with the keys
+    /// `resolved` gives them ([`Pact::with_signer_keys`]); the draft is
+    /// still this pact's
+    pub fn verify_adoption_by(&self, resolved: &Pact, signatures: &[PactSignature]) -> Result<()> {
+        let message = Self::adoption_bytes(&self.pact_id, &self.draft_hash());
+        verify_quorum(resolved, &message, signatures)
+    }
 
-        for sig in &proof.signatures {
-            // Check for duplicates
-            if !seen_pubkeys.insert(&sig.pubkey) {
-                continue;
-            }
+    /// Signers and veto signers named by SID rather than by key
+    pub fn sid_signers(&self) -> impl Iterator<Item = &str> {
+        self.signers
+            .iter()
+            .chain(&self.veto_signers)
+            .map(String::as_str)
+            .filter(|s| s.starts_with(SID_PREFIX))
+    }
 
-            // Check if signer is authorized
-            if !pact.signers.contains(&sig.pubkey) {
-                return Err(PactError::UnauthorizedSigner(sig.pubkey.clone()));
+    /// These terms with every SID replaced by the key `key_of` resolves it
+    /// to, in signers, weights, groups and veto signers alike. A SID that
+    /// resolves to no key is dropped: it cannot sign.
+    pub fn with_signer_keys(&self, key_of: impl Fn(&str) -> Option<String>) -> Pact {
+        let resolve = |signer: &String| {
+            if signer.starts_with(SID_PREFIX) {
+                key_of(signer)
+            } else {
+                Some(signer.clone())
             }
+        };
+        Pact {
+            signers: self.signers.iter().filter_map(resolve).collect(),
+            weights: self
+                .weights
+                .as_ref()
+                .map(|w| w.iter().filter_map(|(s, w)| Some((resolve(s)?, *w))).collect()),
+            groups: self
+                .groups
+                .iter()
+                .map(|g| SignerGroup {
+                    members: g.members.iter().filter_map(resolve).collect(),
+                    ..g.clone()
+                })
+                .collect(),
+            veto_signers: self.veto_signers.iter().filter_map(resolve).collect(),
+            ..self.clone()
+        }
+    }
 
-            // In a real implementation, we'd verify the signature here
-            // For now, we trust the signature is valid
-            valid_count += 1;
+    /// Successor renewing this pact as `pact_id` over `window`: the same
+    /// terms and signers, which the caller may change before registering it
+    /// with [`PactRegistry::renew`]
+    pub fn renewal(&self, pact_id: impl Into<String>, window: TimeWindow) -> Pact {
+        Pact {
+            pact_id: pact_id.into(),
+            window,
+            predecessor: Some(self.pact_id.clone()),
+            ..self.clone()
         }
+    }
+}
+
+/// Prefix of a signer named by its UBL ID SID instead of a public key
+pub const SID_PREFIX: &str = "ubl:sid:";
+
+/// A key a SID held, from its UBL ID rotation history
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SignerKey {
+    /// Public key (hex)
+    pub pubkey: String,
+
+    /// Unix timestamp from which the SID signs with it
+    pub valid_from: i64,
+
+    /// Unix timestamp at which it was rotated out or revoked, if it was
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revoked_at: Option<i64>,
+}
+
+impl SignerKey {
+    /// Whether the SID signs with this key at `now`
+    pub fn in_force(&self, now: i64) -> bool {
+        self.valid_from <= now && !self.revoked_at.is_some_and(|r| now >= r)
+    }
+}
+
+/// Domain tag of the bytes a signer signs to adopt a pact draft
+pub const DRAFT_DOMAIN: &[u8] = b"ubl:pact-draft:v1\n";
+
+/// A named subset of a pact's signers with a threshold of its own
+/// (SPEC-UBL-PACT v1.0 §4.3): "2 of admins AND 1 of auditors" is two groups
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SignerGroup {
+    /// Group (role) name, unique within the pact
+    pub name: String,
+
+    /// Members (public keys in hex), each one of the pact's signers
+    pub members: BTreeSet<String>,
+
+    /// Minimum weight of valid signatures from members
+    pub threshold: usize,
+}
 
-        // Check threshold
-        if valid_count < pact.threshold {
-            return Err(PactError::InsufficientSignatures {
-                got: valid_count,
-                need: pact.threshold,
+/// `threshold` of total weight over `signed`, then each group's threshold
+/// over the members among them
+pub(crate) fn check_quorum(
+    threshold: usize,
+    groups: &[SignerGroup],
+    weight: impl Fn(&str) -> usize,
+    signed: &[&str],
+) -> Result<()> {
+    let got: usize = signed.iter().map(|s| weight(s)).sum();
+    if got < threshold {
+        return Err(PactError::InsufficientSignatures { got, need: threshold });
+    }
+    for group in groups {
+        let got: usize = signed
+            .iter()
+            .filter(|s| group.members.contains(**s))
+            .map(|s| weight(s))
+            .sum();
+        if got < group.threshold {
+            return Err(PactError::GroupQuorumNotMet {
+                group: group.name.clone(),
+                got,
+                need: group.threshold,
             });
         }
+    }
+    Ok(())
+}
 
-        Ok(())
+/// Most delegations walked from a signing key back to a pact signer
+pub const MAX_DELEGATION_DEPTH: usize = 4;
+
+/// The signer of `pact` that `pubkey` signs for: itself if it is one, or
+/// the signer at the root of its delegation chain, each delegation on the
+/// way for this pact, in its window at `now` and signed by its parent
+fn delegating_signer<'a>(
+    pact: &'a Pact,
+    pubkey: &'a str,
+    delegations: &'a [SignerDelegation],
+    now: i64,
+) -> Result<&'a str> {
+    let mut key = pubkey;
+    for depth in 0..=MAX_DELEGATION_DEPTH {
+        if pact.signers.contains(key) {
+            return Ok(key);
+        }
+        if depth == MAX_DELEGATION_DEPTH {
+            break;
+        }
+        let Some(delegation) = delegations.iter().find(|d| d.delegate == key) else {
+            return Err(PactError::UnauthorizedSigner(pubkey.to_string()));
+        };
+        if delegation.pact_id != pact.pact_id {
+            return Err(PactError::InvalidDelegation(format!("{} is delegated under another pact", key)));
+        }
+        if !delegation.window.is_valid(now) {
+            return Err(PactError::InvalidDelegation(format!("delegation to {} is not in force", key)));
+        }
+        let message =
+            SignerDelegation::signing_bytes(&pact.pact_id, &delegation.parent, key, &delegation.window);
+        ubl_kernel::verify(&delegation.parent, &message, &delegation.signature)
+            .map_err(|_| PactError::InvalidSignature(delegation.parent.clone()))?;
+        key = &delegation.parent;
     }
+    Err(PactError::InvalidDelegation(format!(
+        "{} is more than {} delegations from a signer",
+        pubkey, MAX_DELEGATION_DEPTH
+    )))
 }
 
-impl Default for PactRegistry {
-    fn default() -> Self {
-        Self::new()
+/// Domain tag of the bytes a pact revocation signer signs
+pub const REVOCATION_DOMAIN: &[u8] = b"ubl:pact-revoke:v1\n";
+
+/// Revocation of a pact before its `not_after` (SPEC-UBL-PACT v1.0 §9.1),
+/// signed by a quorum of the pact's own signers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PactRevocation {
+    /// Pact being revoked
+    pub pact_id: String,
+
+    /// Unix timestamp from which proofs under the pact are refused
+    pub revoked_at: i64,
+
+    /// Why the pact is revoked (e.g. a compromised signer key)
+    pub reason: String,
+
+    /// Signatures over [`PactRevocation::signing_bytes`]
+    pub signatures: Vec<PactSignature>,
+}
+
+impl PactRevocation {
+    /// Canonical bytes a revocation signer signs:
+    ///
+    /// ```text
+    /// "ubl:pact-revoke:v1\n" || u32be(len pact_id) || pact_id
+    ///                        || i64be(revoked_at)
+    ///                        || u32be(len reason) || reason
+    /// ```
+    pub fn signing_bytes(pact_id: &str, revoked_at: i64, reason: &str) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(REVOCATION_DOMAIN.len() + 16 + pact_id.len() + reason.len());
+        bytes.extend_from_slice(REVOCATION_DOMAIN);
+        bytes.extend_from_slice(&(pact_id.len() as u32).to_be_bytes());
+        bytes.extend_from_slice(pact_id.as_bytes());
+        bytes.extend_from_slice(&revoked_at.to_be_bytes());
+        bytes.extend_from_slice(&(reason.len() as u32).to_be_bytes());
+        bytes.extend_from_slice(reason.as_bytes());
+        bytes
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Domain tag of the bytes a veto signer signs
+pub const VETO_DOMAIN: &[u8] = b"ubl:pact-veto:v1\n";
 
-    fn make_pact(threshold: usize, signers: Vec<&str>) -> Pact {
-        Pact {
-            pact_id: "pact_test".to_string(),
-            version: 1,
-            scope: PactScope::Container,
-            threshold,
-            signers: signers.into_iter().map(|s| s.to_string()).collect(),
-            window: TimeWindow {
-                not_before: 0,
-                not_after: i64::MAX,
-            },
-            risk_level: RiskLevel::L2,
-            container_id: Some("test".to_string()),
+/// Veto by one of the pact's `veto_signers` (SPEC-UBL-PACT v1.0 §9.2):
+/// a counter-signature against one link, or, without `link_hash`, against
+/// every proof under the pact from `vetoed_at` on
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PactVeto {
+    /// Pact vetoed
+    pub pact_id: String,
+
+    /// `hash_link` of the link vetoed; `None` vetoes the pact
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub link_hash: Option<String>,
+
+    /// Unix timestamp from which the veto holds, within the pact's window
+    pub vetoed_at: i64,
+
+    /// Why (e.g. a compliance finding)
+    pub reason: String,
+
+    /// Veto signer's signature over [`PactVeto::signing_bytes`]
+    pub signature: PactSignature,
+}
+
+impl PactVeto {
+    /// Canonical bytes a veto signer signs; an absent `link_hash` is empty:
+    ///
+    /// ```text
+    /// "ubl:pact-veto:v1\n" || u32be(len pact_id) || pact_id
+    ///                      || u32be(len link_hash) || lowercase(link_hash)
+    ///                      || i64be(vetoed_at)
+    ///                      || u32be(len reason) || reason
+    /// ```
+    pub fn signing_bytes(pact_id: &str, link_hash: Option<&str>, vetoed_at: i64, reason: &str) -> Vec<u8> {
+        let link_hash = link_hash.unwrap_or_default().to_ascii_lowercase();
+        let mut bytes = Vec::with_capacity(VETO_DOMAIN.len() + 20 + pact_id.len() + link_hash.len() + reason.len());
+        bytes.extend_from_slice(VETO_DOMAIN);
+        for field in [pact_id.as_bytes(), link_hash.as_bytes()] {
+            bytes.extend_from_slice(&(field.len() as u32).to_be_bytes());
+            bytes.extend_from_slice(field);
         }
+        bytes.extend_from_slice(&vetoed_at.to_be_bytes());
+        bytes.extend_from_slice(&(reason.len() as u32).to_be_bytes());
+        bytes.extend_from_slice(reason.as_bytes());
+        bytes
     }
 
-    #[test]
-    fn test_valid_pact() {
-        let mut registry = PactRegistry::new();
-        registry.register(make_pact(2, vec!["alice", "bob", "charlie"]));
+    /// Whether the veto refuses a proof for `link_hash` validated at `now`
+    pub fn applies(&self, link_hash: &str, now: i64) -> bool {
+        now >= self.vetoed_at
+            && match &self.link_hash {
+                Some(h) => h.eq_ignore_ascii_case(link_hash),
+                None => true,
+            }
+    }
+}
 
-        let proof = PactProof {
-            pact_id: "pact_test".to_string(),
-            signatures: vec![
-                PactSignature {
-                    pubkey: "alice".to_string(),
-                    signature: "sig1".to_string(),
-                },
-                PactSignature {
-                    pubkey: "bob".to_string(),
-                    signature: "sig2".to_string(),
-                },
-            ],
-        };
+/// Check that distinct authorized signers meeting the pact's quorum signed `message`
+fn verify_quorum(pact: &Pact, message: &[u8], signatures: &[PactSignature]) -> Result<()> {
+    verify_delegated_quorum(pact, message, signatures, &[], 0, &mut SignatureTally::default())
+}
 
-        let result = registry.validate(&proof, 0x01, 1000);
-        assert!(result.is_ok());
-    }
+/// [`verify_quorum`], where a key may also sign for the signer it holds a
+/// delegation chain from, in force at `now`
+fn verify_delegated_quorum(
+    pact: &Pact,
+    message: &[u8],
+    signatures: &[PactSignature],
+    delegations: &[SignerDelegation],
+    now: i64,
+    tally: &mut SignatureTally,
+) -> Result<()> {
+    let mut valid = Vec::new();
+    let mut seen_pubkeys = HashSet::new();
+    let mut first_error = None;
 
-    #[test]
-    fn test_insufficient_signatures() {
-        let mut registry = PactRegistry::new();
-        registry.register(make_pact(3, vec!["alice", "bob", "charlie"]));
+    // Every signature is checked and tallied; the first failure decides
+    for sig in signatures {
+        // Check for duplicates
+        if !seen_pubkeys.insert(&sig.pubkey) {
+            tally.reject(&sig.pubkey, "signed twice");
+            continue;
+        }
 
-        let proof = PactProof {
-            pact_id: "pact_test".to_string(),
-            signatures: vec![PactSignature {
-                pubkey: "alice".to_string(),
-                signature: "sig1".to_string(),
-            }],
-        };
+        // Check if signer is authorized, directly or by delegation
+        let checked = delegating_signer(pact, &sig.pubkey, delegations, now).and_then(|signer| {
+            ubl_kernel::verify(&sig.pubkey, message, &sig.signature)
+                .map(|_| signer)
+                .map_err(|_| PactError::InvalidSignature(sig.pubkey.clone()))
+        });
+        match checked {
+            Ok(signer) => {
+                tally.counted.push(sig.pubkey.clone());
+                // A signer counts once, however many of its keys signed
+                if !valid.contains(&signer) {
+                    valid.push(signer);
+                }
+            }
+            Err(e) => {
+                tally.reject(&sig.pubkey, &e);
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+    if let Some(e) = first_error {
+        return Err(e);
+    }
 
-        let result = registry.validate(&proof, 0x01, 1000);
-        assert!(matches!(
-            result,
-            Err(PactError::InsufficientSignatures { got: 1, need: 3 })
+    // Check threshold and group thresholds
+    pact.check_quorum(&valid)
+}
+
+/// Check a FROST threshold signature over `message` under the pact's group
+/// key. FROST(Ed25519, SHA-512) signatures verify as plain Ed25519; the
+/// quorum was enforced by the signers' key generation, not here.
+fn verify_threshold(
+    pact: &Pact,
+    message: &[u8],
+    proof: &PactProof,
+    signature: &str,
+    tally: &mut SignatureTally,
+) -> Result<()> {
+    let Some(group_key) = &pact.frost_group_key else {
+        return Err(PactError::InvalidThresholdProof(format!("pact {} has no FROST group key", pact.pact_id)));
+    };
+    if !proof.signatures.is_empty() || !proof.delegations.is_empty() {
+        return Err(PactError::InvalidThresholdProof(
+            "a threshold proof carries no individual signatures".to_string(),
         ));
     }
+    if ubl_kernel::verify(group_key, message, signature).is_err() {
+        let e = PactError::InvalidSignature(group_key.clone());
+        tally.reject(group_key, &e);
+        return Err(e);
+    }
+    tally.counted.push(group_key.clone());
+    Ok(())
+}
+
+/// Check a BLS12-381 aggregate over `message`: its signers must be
+/// distinct, authorized and meet the pact's quorum before the one
+/// aggregate verification
+fn verify_aggregate(
+    pact: &Pact,
+    message: &[u8],
+    proof: &PactProof,
+    aggregate: &BlsAggregate,
+    tally: &mut SignatureTally,
+) -> Result<()> {
+    if !proof.signatures.is_empty() || !proof.delegations.is_empty() || proof.frost_signature.is_some() {
+        return Err(PactError::SchemeMismatch {
+            pact: SignatureScheme::Bls12381,
+            proof: SignatureScheme::Ed25519,
+        });
+    }
+    let mut signed = Vec::with_capacity(aggregate.signers.len());
+    for signer in &aggregate.signers {
+        if signed.contains(&signer.as_str()) {
+            return Err(PactError::DuplicateSigner(signer.clone()));
+        }
+        if !pact.signers.contains(signer) {
+            return Err(PactError::UnauthorizedSigner(signer.clone()));
+        }
+        signed.push(signer.as_str());
+    }
+    pact.check_quorum(&signed)?;
+    // The aggregate verifies for all its signers or for none
+    let verified = verify_bls(&signed, message, &aggregate.signature);
+    for signer in signed {
+        match &verified {
+            Ok(()) => tally.counted.push(signer.to_string()),
+            Err(e) => tally.reject(signer, e),
+        }
+    }
+    verified
+}
 
-    #[test]
-    fn test_unauthorized_signer() {
-        let mut registry = PactRegistry::new();
-        registry.register(make_pact(1, vec!["alice", "bob"]));
+#[cfg(feature = "bls")]
+use bls::verify_aggregate as verify_bls;
 
-        let proof = PactProof {
-            pact_id: "pact_test".to_string(),
-            signatures: vec![PactSignature {
-                pubkey: "eve".to_string(),
-                signature: "sig1".to_string(),
-            }],
-        };
+/// Without the `bls` feature no BLS12-381 proof verifies
+#[cfg(not(feature = "bls"))]
+fn verify_bls(_pubkeys: &[&str], _message: &[u8], _signature: &str) -> Result<()> {
+    Err(PactError::UnsupportedScheme(SignatureScheme::Bls12381))
+}
 
-        let result = registry.validate(&proof, 0x01, 1000);
-        assert!(matches!(result, Err(PactError::UnauthorizedSigner(_))));
-    }
+/// A signature that did not count towards a proof's quorum
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RejectedSignature {
+    /// Key that signed
+    pub pubkey: String,
 
-    #[test]
-    fn test_expired_pact() {
-        let mut registry = PactRegistry::new();
-        let mut pact = make_pact(1, vec!["alice"]);
-        pact.window.not_after = 1000;
-        registry.register(pact);
+    /// Why it did not count
+    pub reason: String,
+}
 
-        let proof = PactProof {
-            pact_id: "pact_test".to_string(),
-            signatures: vec![PactSignature {
-                pubkey: "alice".to_string(),
-                signature: "sig1".to_string(),
-            }],
-        };
+/// Which signatures of a proof counted towards its quorum, and which did not
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SignatureTally {
+    /// Keys whose signature verified and counted, for themselves or for
+    /// the signer delegating to them (the group key of a FROST proof)
+    pub counted: Vec<String>,
 
-        let result = registry.validate(&proof, 0x01, 2000);
-        assert!(matches!(result, Err(PactError::PactExpired)));
+    /// Signatures that were checked and did not count
+    pub rejected: Vec<RejectedSignature>,
+}
+
+impl SignatureTally {
+    fn reject(&mut self, pubkey: &str, reason: impl ToString) {
+        self.rejected.push(RejectedSignature {
+            pubkey: pubkey.to_string(),
+            reason: reason.to_string(),
+        });
     }
+}
 
-    #[test]
-    fn test_risk_mismatch() {
-        let mut registry = PactRegistry::new();
-        let mut pact = make_pact(1, vec!["alice"]);
-        pact.risk_level = RiskLevel::L1; // Too low for Conservation
-        registry.register(pact);
+/// One [`PactRegistry::validate`] call and its outcome
+#[derive(Debug, Clone)]
+pub struct ValidationEvent {
+    /// Pact the proof names
+    pub pact_id: String,
 
-        let proof = PactProof {
-            pact_id: "pact_test".to_string(),
-            signatures: vec![PactSignature {
-                pubkey: "alice".to_string(),
-                signature: "sig1".to_string(),
-            }],
-        };
+    /// Container the proof was presented for
+    pub container_id: String,
 
-        let result = registry.validate(&proof, 0x01, 1000); // Conservation requires L2
-        assert!(matches!(result, Err(PactError::RiskMismatch { .. })));
+    /// Link it was to authorize (lowercase)
+    pub link_hash: String,
+
+    /// Nonce its signatures commit to
+    pub nonce: u64,
+
+    /// Intent class of the link
+    pub intent_class: u8,
+
+    /// Time of validation (unix)
+    pub at: i64,
+
+    /// How its signatures fared; empty if validation failed before them
+    /// (unknown pact, revoked, expired, out of scope…)
+    pub signatures: SignatureTally,
+
+    /// Why the proof was refused; `None` if it was accepted
+    pub error: Option<PactError>,
+}
+
+/// Receiver of every validation a registry makes, success or failure, e.g.
+/// for a forensic audit trail ([`PactRegistry::set_sink`])
+pub trait ValidationSink: Send + Sync {
+    /// Record one validation. Called once its outcome is decided, which
+    /// recording cannot change: a sink that fails must handle it itself.
+    fn record(&self, event: &ValidationEvent);
+}
+
+/// Pact registry for validation
+pub struct PactRegistry {
+    pacts: std::collections::HashMap<String, Pact>,
+    revocations: std::collections::HashMap<String, PactRevocation>,
+    vetoes: std::collections::HashMap<String, Vec<PactVeto>>,
+    /// Successor of each renewed pact
+    successors: std::collections::HashMap<String, String>,
+    /// Seconds past `not_after` a renewed pact's proofs are still accepted
+    renewal_grace: i64,
+    /// Namespace of each container placed in one
+    namespaces: std::collections::HashMap<String, String>,
+    /// Proofs accepted under each pact, counted against `max_uses`
+    uses: std::collections::HashMap<String, u64>,
+    /// Consumed proofs, by pact, link hash (lowercase) and nonce
+    consumed: HashSet<(String, String, u64)>,
+    /// Key history of each SID named as a signer
+    signer_keys: std::collections::HashMap<String, Vec<SignerKey>>,
+    /// Where validations are recorded, if anywhere
+    sink: Option<Box<dyn ValidationSink>>,
+    /// The commit path's freeze, while in force
+    frozen: Option<freeze::Freeze>,
+}
+
+impl PactRegistry {
+    /// Create a new empty registry
+    pub fn new() -> Self {
+        Self {
+            pacts: std::collections::HashMap::new(),
+            revocations: std::collections::HashMap::new(),
+            vetoes: std::collections::HashMap::new(),
+            successors: std::collections::HashMap::new(),
+            renewal_grace: 0,
+            namespaces: std::collections::HashMap::new(),
+            uses: std::collections::HashMap::new(),
+            consumed: HashSet::new(),
+            signer_keys: std::collections::HashMap::new(),
+            sink: None,
+            frozen: None,
+        }
+    }
+
+    /// Record every validation from now on in `sink`
+    pub fn set_sink(&mut self, sink: impl ValidationSink + 'static) {
+        self.sink = Some(Box::new(sink));
     }
-}
\ No newline at end of file
+
+    /// Place `container_id` in `namespace`, so pacts scoped to that
+    /// namespace cover it; a container is in one namespace at a time
+    pub fn set_namespace(&mut self, container_id: impl Into<String>, namespace: impl Into<String>) {
+        self.namespaces.insert(container_id.into(), namespace.into());
+    }
+
+    /// Namespace `container_id` was placed in, if any
+    pub fn namespace(&self, container_id: &str) -> Option<&str> {
+        self.namespaces.get(container_id).map(String::as_str)
+    }
+
+    /// Record `sid`'s key history (its UBL ID rotations), replacing any
+    /// recorded before
+    pub fn set_signer_keys(&mut self, sid: impl Into<String>, keys: Vec<SignerKey>) {
+        self.signer_keys.insert(sid.into(), keys);
+    }
+
+    /// The key `sid` signs with at `now`: of its keys in force then, the
+    /// latest to take effect
+    pub fn signer_key(&self, sid: &str, now: i64) -> Option<&str> {
+        self.signer_keys
+            .get(sid)?
+            .iter()
+            .filter(|k| k.in_force(now))
+            .max_by_key(|k| k.valid_from)
+            .map(|k| k.pubkey.as_str())
+    }
+
+    /// `pact`'s terms with each SID signer resolved to its key at `now`
+    fn resolve(&self, pact: &Pact, now: i64) -> Pact {
+        pact.with_signer_keys(|sid| self.signer_key(sid, now).map(str::to_string))
+    }
+
+    /// Register a pact. One naming a `predecessor` that has no successor
+    /// yet becomes it; [`PactRegistry::renew`] checks the renewal first.
+    pub fn register(&mut self, pact: Pact) {
+        if let Some(predecessor) = &pact.predecessor {
+            self.successors
+                .entry(predecessor.clone())
+                .or_insert_with(|| pact.pact_id.clone());
+        }
+        self.pacts.insert(pact.pact_id.clone(), pact);
+    }
+
+    /// Register `successor` as the renewal of its `predecessor`
+    /// (SPEC-UBL-PACT v1.0 §9.3): the predecessor must be registered and
+    /// not yet renewed, and the successor a new pact. A pact may be renewed
+    /// after it expired; its proofs then get the renewal grace, if any.
+    pub fn renew(&mut self, successor: Pact) -> Result<()> {
+        let Some(predecessor_id) = &successor.predecessor else {
+            return Err(PactError::InvalidRenewal("successor names no predecessor".to_string()));
+        };
+        if self.get(predecessor_id).is_none() {
+            return Err(PactError::UnknownPact(predecessor_id.clone()));
+        }
+        if let Some(existing) = self.successors.get(predecessor_id) {
+            return Err(PactError::InvalidRenewal(format!(
+                "{} is already renewed by {}",
+                predecessor_id, existing
+            )));
+        }
+        if self.pacts.contains_key(&successor.pact_id) {
+            return Err(PactError::InvalidRenewal(format!("{} is already registered", successor.pact_id)));
+        }
+        self.register(successor);
+        Ok(())
+    }
+
+    /// Register `child` on the authority of its parent meta-pact: `proof`
+    /// must be a proof under `child.parent` for the child's
+    /// [`Pact::draft_hash`], as [`PactRegistry::check_child`] checks. The
+    /// proof is consumed, so it registers one child.
+    pub fn register_child(&mut self, child: Pact, proof: &PactProof, now: i64) -> Result<()> {
+        self.check_child(&child, proof, now)?;
+        self.consume(proof, &child.draft_hash())?;
+        self.register(child);
+        Ok(())
+    }
+
+    /// Whether `proof` lets `child` be registered at `now`: its parent, the
+    /// pact the proof is under, issues pacts and is in force; the child
+    /// names it, is new, stays within its scope and does not exceed its
+    /// risk level; and the proof meets the parent's quorum for the child's
+    /// risk level over [`PactProof::signing_bytes`]`(child.draft_hash(),
+    /// parent, nonce)`.
+    pub fn check_child(&self, child: &Pact, proof: &PactProof, now: i64) -> Result<()> {
+        let parent = self
+            .get(&proof.pact_id)
+            .ok_or_else(|| PactError::UnknownPact(proof.pact_id.clone()))?;
+        if !parent.issues_pacts {
+            return Err(PactError::InvalidChildPact(format!("{} is not a meta-pact", parent.pact_id)));
+        }
+        if child.parent.as_deref() != Some(parent.pact_id.as_str()) {
+            return Err(PactError::InvalidChildPact(format!(
+                "{} does not name {} as its parent",
+                child.pact_id, parent.pact_id
+            )));
+        }
+        if self.pacts.contains_key(&child.pact_id) {
+            return Err(PactError::InvalidChildPact(format!("{} is already registered", child.pact_id)));
+        }
+        if !self.parent_covers(parent, child) {
+            return Err(PactError::InvalidChildPact(format!(
+                "{:?}-scoped {} is outside {}'s {:?} scope",
+                child.scope, child.pact_id, parent.pact_id, parent.scope
+            )));
+        }
+        let draft_hash = child.draft_hash();
+        self.check_standing(parent, proof, &draft_hash, now)?;
+        let message = PactProof::signing_bytes(&draft_hash, &parent.pact_id, proof.nonce);
+        self.check_signatures(parent, child.risk_level, &message, proof, now, &mut SignatureTally::default())
+    }
+
+    /// Whether everything `child` may govern is within `parent`'s scope
+    fn parent_covers(&self, parent: &Pact, child: &Pact) -> bool {
+        match (parent.scope, child.scope) {
+            (PactScope::Global, _) => true,
+            (PactScope::Namespace, PactScope::Namespace) => {
+                parent.namespace.is_some() && parent.namespace == child.namespace
+            }
+            (_, PactScope::Container) => child
+                .container_id
+                .as_deref()
+                .is_some_and(|c| parent.covers(c, self.namespace(c))),
+            _ => false,
+        }
+    }
+
+    /// The pact renewing `pact_id`, if it was renewed
+    pub fn successor(&self, pact_id: &str) -> Option<&Pact> {
+        self.successors.get(pact_id).and_then(|s| self.get(s))
+    }
+
+    /// Every registered pact in `pact_id`'s lineage, oldest first: its
+    /// predecessors, the pact itself, then its successors
+    pub fn lineage(&self, pact_id: &str) -> Vec<&Pact> {
+        let mut seen = HashSet::new();
+        let mut lineage = Vec::new();
+        let mut at = self.get(pact_id);
+        while let Some(pact) = at.filter(|p| seen.insert(p.pact_id.as_str())) {
+            lineage.push(pact);
+            at = pact.predecessor.as_deref().and_then(|p| self.get(p));
+        }
+        lineage.reverse();
+        at = self.get(pact_id).and_then(|p| self.successor(&p.pact_id));
+        while let Some(pact) = at.filter(|p| seen.insert(p.pact_id.as_str())) {
+            lineage.push(pact);
+            at = self.successor(&pact.pact_id);
+        }
+        lineage
+    }
+
+    /// Accept proofs under an expired pact for `seconds` past its
+    /// `not_after` when it has a successor in force (0, the default, never)
+    pub fn set_renewal_grace(&mut self, seconds: i64) {
+        self.renewal_grace = seconds.max(0);
+    }
+
+    /// Seconds of renewal grace
+    pub fn renewal_grace(&self) -> i64 {
+        self.renewal_grace
+    }
+
+    /// Whether `pact`, expired at `now`, was renewed within the grace
+    /// period: `now` is at most the grace past its end and its successor
+    /// is in force
+    fn renewed_within_grace(&self, pact: &Pact, now: i64) -> bool {
+        now > pact.window.not_after
+            && now - pact.window.not_after <= self.renewal_grace
+            && self.successor(&pact.pact_id).is_some_and(|s| {
+                s.window.is_valid(now) && !self.revocation(&s.pact_id).is_some_and(|r| now >= r.revoked_at)
+            })
+    }
+
+    /// Get a pact by ID
+    pub fn get(&self, pact_id: &str) -> Option<&Pact> {
+        self.pacts.get(pact_id)
+    }
+
+    /// Proofs recorded under `pact_id` with [`PactRegistry::record_use`]
+    pub fn uses(&self, pact_id: &str) -> u64 {
+        self.uses.get(pact_id).copied().unwrap_or(0)
+    }
+
+    /// Count one use of `pact_id`, once a proof under it was accepted, and
+    /// return the uses so far. Validation checks the count, it does not
+    /// record it; past `max_uses` this fails and counts nothing. Like a
+    /// revocation, the count outlives the pact being registered again.
+    pub fn record_use(&mut self, pact_id: &str) -> Result<u64> {
+        let pact = self
+            .get(pact_id)
+            .ok_or_else(|| PactError::UnknownPact(pact_id.to_string()))?;
+        let used = self.uses(pact_id);
+        pact.check_uses(used)?;
+        self.uses.insert(pact_id.to_string(), used + 1);
+        Ok(used + 1)
+    }
+
+    /// Mark `proof` for the link hashing to `link_hash` as consumed, once
+    /// the link was accepted, and count one use of its pact
+    /// ([`PactRegistry::record_use`]). Validating the same proof again, or
+    /// any proof under the pact for that link and nonce, then fails with
+    /// [`PactError::Replayed`]; consuming twice fails the same way.
+    pub fn consume(&mut self, proof: &PactProof, link_hash: &str) -> Result<()> {
+        let key = consumed_key(proof, link_hash);
+        if self.consumed.contains(&key) {
+            return Err(PactError::Replayed {
+                link_hash: key.1,
+                nonce: key.2,
+            });
+        }
+        self.record_use(&proof.pact_id)?;
+        self.consumed.insert(key);
+        Ok(())
+    }
+
+    /// Whether `proof` for `link_hash` was consumed
+    pub fn is_consumed(&self, proof: &PactProof, link_hash: &str) -> bool {
+        self.consumed.contains(&consumed_key(proof, link_hash))
+    }
+
+    /// Revoke a registered pact. The revocation must carry a threshold of
+    /// the pact's signers over [`PactRevocation::signing_bytes`]; a pact is
+    /// revoked once, and registering it again does not lift the revocation.
+    pub fn revoke(&mut self, revocation: PactRevocation) -> Result<()> {
+        let pact = self
+            .get(&revocation.pact_id)
+            .ok_or_else(|| PactError::UnknownPact(revocation.pact_id.clone()))?;
+        if self.revocations.contains_key(&revocation.pact_id) {
+            return Err(PactError::Revoked);
+        }
+
+        let message = PactRevocation::signing_bytes(&revocation.pact_id, revocation.revoked_at, &revocation.reason);
+        verify_quorum(&self.resolve(pact, revocation.revoked_at), &message, &revocation.signatures)?;
+
+        self.revocations.insert(revocation.pact_id.clone(), revocation);
+        Ok(())
+    }
+
+    /// The accepted revocation of a pact, if any
+    pub fn revocation(&self, pact_id: &str) -> Option<&PactRevocation> {
+        self.revocations.get(pact_id)
+    }
+
+    /// Record a veto. It must be signed by one of the pact's `veto_signers`
+    /// and take effect within the pact's window; like a revocation, it
+    /// outlives the pact being registered again.
+    pub fn veto(&mut self, veto: PactVeto) -> Result<()> {
+        let pact = self
+            .get(&veto.pact_id)
+            .ok_or_else(|| PactError::UnknownPact(veto.pact_id.clone()))?;
+        if !self.resolve(pact, veto.vetoed_at).veto_signers.contains(&veto.signature.pubkey) {
+            return Err(PactError::UnauthorizedSigner(veto.signature.pubkey.clone()));
+        }
+        if !pact.window.is_valid(veto.vetoed_at) {
+            return Err(PactError::PactExpired);
+        }
+
+        let message = PactVeto::signing_bytes(&veto.pact_id, veto.link_hash.as_deref(), veto.vetoed_at, &veto.reason);
+        ubl_kernel::verify(&veto.signature.pubkey, &message, &veto.signature.signature)
+            .map_err(|_| PactError::InvalidSignature(veto.signature.pubkey.clone()))?;
+
+        self.vetoes.entry(veto.pact_id.clone()).or_default().push(veto);
+        Ok(())
+    }
+
+    /// Vetoes recorded against a pact, in the order they were accepted
+    pub fn vetoes(&self, pact_id: &str) -> &[PactVeto] {
+        self.vetoes.get(pact_id).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// Validate a pact proof (SPEC-UBL-PACT v1.0 §9) for the link to
+    /// `container_id` whose signing bytes hash (`hash_link`) to `link_hash`:
+    /// the proof must not be consumed ([`PactRegistry::consume`]), the
+    /// container must be within the pact's scope, and each signature must
+    /// verify over [`PactProof::signing_bytes`]`(link_hash, pact_id, nonce)`.
+    /// The outcome is recorded in the registry's sink, if it has one.
+    pub fn validate(
+        &self,
+        proof: &PactProof,
+        container_id: &str,
+        link_hash: &str,
+        intent_class: u8,
+        now: i64,
+    ) -> Result<()> {
+        self.validate_under(SpecVersion::LATEST, proof, container_id, link_hash, intent_class, now)
+    }
+
+    /// [`PactRegistry::validate`] under the rules of `spec`, the version the
+    /// link's container is committed under: the intent-to-risk mapping and
+    /// the signed bytes follow it
+    pub fn validate_under(
+        &self,
+        spec: SpecVersion,
+        proof: &PactProof,
+        container_id: &str,
+        link_hash: &str,
+        intent_class: u8,
+        now: i64,
+    ) -> Result<()> {
+        let mut event = ValidationEvent {
+            pact_id: proof.pact_id.clone(),
+            container_id: container_id.to_string(),
+            link_hash: link_hash.to_ascii_lowercase(),
+            nonce: proof.nonce,
+            intent_class,
+            at: now,
+            signatures: SignatureTally::default(),
+            error: None,
+        };
+        let outcome = self.check(spec, proof, &mut event);
+        if let Some(sink) = &self.sink {
+            event.error = outcome.as_ref().err().cloned();
+            sink.record(&event);
+        }
+        outcome
+    }
+
+    /// The checks of [`PactRegistry::validate_under`] for the link in
+    /// `event`, tallying the proof's signatures into it
+    fn check(&self, spec: SpecVersion, proof: &PactProof, event: &mut ValidationEvent) -> Result<()> {
+        let (container_id, link_hash, intent_class, now) =
+            (event.container_id.as_str(), event.link_hash.as_str(), event.intent_class, event.at);
+        let tally = &mut event.signatures;
+
+        // Check freeze: Observation links only, whatever the pact
+        if let Some(freeze) = self.frozen.as_ref().filter(|_| !freeze::admits(intent_class)) {
+            return Err(PactError::Frozen(freeze.pact_id.clone()));
+        }
+
+        // Get the pact
+        let pact = self
+            .get(&proof.pact_id)
+            .ok_or_else(|| PactError::UnknownPact(proof.pact_id.clone()))?;
+        self.check_standing(pact, proof, link_hash, now)?;
+
+        // Check scope: the pact's container, or a container in its namespace
+        if !pact.covers(container_id, self.namespace(container_id)) {
+            return Err(PactError::ScopeMismatch {
+                container_id: container_id.to_string(),
+                scope: pact.scope,
+            });
+        }
+
+        // Versioned rules
+        let (required_risk, message) = match spec {
+            SpecVersion::V1_0 => (
+                RiskLevel::from_intent_class(intent_class),
+                PactProof::signing_bytes(link_hash, &proof.pact_id, proof.nonce),
+            ),
+        };
+        self.check_signatures(pact, required_risk, &message, proof, now, tally)
+    }
+
+    /// Whether `pact` may still authorize `link_hash` at `now`, whatever
+    /// the signatures: not replayed, revoked, vetoed, expired or used up
+    fn check_standing(&self, pact: &Pact, proof: &PactProof, link_hash: &str, now: i64) -> Result<()> {
+        // Check replay: each proof authorizes one link, once
+        if self.is_consumed(proof, link_hash) {
+            return Err(PactError::Replayed {
+                link_hash: link_hash.to_ascii_lowercase(),
+                nonce: proof.nonce,
+            });
+        }
+
+        // Check revocation
+        if self.revocation(&proof.pact_id).is_some_and(|r| now >= r.revoked_at) {
+            return Err(PactError::Revoked);
+        }
+
+        // Check vetoes, whatever the signatures
+        if let Some(veto) = self.vetoes(&proof.pact_id).iter().find(|v| v.applies(link_hash, now)) {
+            return Err(PactError::Vetoed(veto.signature.pubkey.clone()));
+        }
+
+        // Check time window, past which a renewed pact may have grace
+        if !pact.window.is_valid(now) && !self.renewed_within_grace(pact, now) {
+            return Err(PactError::PactExpired);
+        }
+
+        // Check usage limit
+        pact.check_uses(self.uses(&proof.pact_id))
+    }
+
+    /// Whether the proof's signatures over `message` meet `pact`'s quorum
+    /// for `required_risk`, tallying them
+    fn check_signatures(
+        &self,
+        pact: &Pact,
+        required_risk: RiskLevel,
+        message: &[u8],
+        proof: &PactProof,
+        now: i64,
+        tally: &mut SignatureTally,
+    ) -> Result<()> {
+        // Check risk level
+        if pact.risk_level < required_risk {
+            return Err(PactError::RiskMismatch {
+                intent: required_risk,
+                pact: pact.risk_level,
+            });
+        }
+
+        // The quorum this risk level asks for
+        let group_threshold = pact.threshold;
+        let pact = pact.at_risk(required_risk);
+
+        // A BLS12-381 pact: one aggregate signature for the quorum
+        let scheme = if proof.bls_aggregate.is_some() {
+            SignatureScheme::Bls12381
+        } else {
+            SignatureScheme::Ed25519
+        };
+        if scheme != pact.signature_scheme {
+            return Err(PactError::SchemeMismatch {
+                pact: pact.signature_scheme,
+                proof: scheme,
+            });
+        }
+        if let Some(aggregate) = &proof.bls_aggregate {
+            return verify_aggregate(&pact, message, proof, aggregate, tally);
+        }
+
+        // A threshold proof: one signature under the group key, generated
+        // for the pact's base threshold
+        if let Some(signature) = &proof.frost_signature {
+            if pact.threshold > group_threshold {
+                return Err(PactError::InvalidThresholdProof(format!(
+                    "the group key signs for {} of the signers; {:?} links need {}",
+                    group_threshold, required_risk, pact.threshold
+                )));
+            }
+            return verify_threshold(&pact, message, proof, signature, tally);
+        }
+
+        // SID signers sign with the key they hold at `now`
+        let resolved = self.resolve(&pact, now);
+        verify_delegated_quorum(&resolved, message, &proof.signatures, &proof.delegations, now, tally)
+    }
+}
+
+/// What identifies a consumed proof: the signatures all commit to these
+fn consumed_key(proof: &PactProof, link_hash: &str) -> (String, String, u64) {
+    (proof.pact_id.clone(), link_hash.to_ascii_lowercase(), proof.nonce)
+}
+
+impl Default for PactRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::SigningKey;
+
+    const LINK_HASH: &str = "5f3c8e1d9a7b6c4e2f0a1b3c5d7e9f8a6b4c2d0e1f3a5b7c9d8e6f4a2b0c1d3e";
+    const CONTAINER: &str = "test";
+
+    fn key(name: &str) -> SigningKey {
+        SigningKey::from_bytes(blake3::hash(name.as_bytes()).as_bytes())
+    }
+
+    fn pubkey(name: &str) -> String {
+        ubl_kernel::pubkey_from_signing_key(&key(name))
+    }
+
+    fn sign(name: &str, link_hash: &str) -> PactSignature {
+        PactSignature {
+            pubkey: pubkey(name),
+            signature: ubl_kernel::sign(&key(name), &PactProof::signing_bytes(link_hash, "pact_test", 0)),
+        }
+    }
+
+    fn make_pact(threshold: usize, signers: Vec<&str>) -> Pact {
+        let window = TimeWindow {
+            not_before: 0,
+            not_after: i64::MAX,
+        };
+        Pact {
+            signers: signers.into_iter().map(pubkey).collect(),
+            ..templates::base("pact_test", CONTAINER, &[], threshold, window, RiskLevel::L2)
+        }
+    }
+
+    fn proof(signers: &[&str]) -> PactProof {
+        PactProof {
+            pact_id: "pact_test".to_string(),
+            signatures: signers.iter().map(|s| sign(s, LINK_HASH)).collect(),
+            nonce: 0,
+            delegations: Vec::new(),
+            frost_signature: None,
+            bls_aggregate: None,
+        }
+    }
+
+    #[test]
+    fn test_valid_pact() {
+        let mut registry = PactRegistry::new();
+        registry.register(make_pact(2, vec!["alice", "bob", "charlie"]));
+
+        let result = registry.validate(&proof(&["alice", "bob"]), CONTAINER, LINK_HASH, 0x01, 1000);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_validate_under_each_spec_version() {
+        let mut registry = PactRegistry::new();
+        registry.register(make_pact(2, vec!["alice", "bob", "charlie"]));
+
+        for spec in SpecVersion::ALL {
+            assert!(registry
+                .validate_under(*spec, &proof(&["alice", "bob"]), CONTAINER, LINK_HASH, 0x01, 1000)
+                .is_ok());
+            assert!(matches!(
+                registry.validate_under(*spec, &proof(&["alice"]), CONTAINER, LINK_HASH, 0x01, 1000),
+                Err(PactError::InsufficientSignatures { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn test_insufficient_signatures() {
+        let mut registry = PactRegistry::new();
+        registry.register(make_pact(3, vec!["alice", "bob", "charlie"]));
+
+        let result = registry.validate(&proof(&["alice"]), CONTAINER, LINK_HASH, 0x01, 1000);
+        assert!(matches!(
+            result,
+            Err(PactError::InsufficientSignatures { got: 1, need: 3 })
+        ));
+    }
+
+    #[test]
+    fn test_weighted_threshold() {
+        let mut registry = PactRegistry::new();
+        let mut pact = make_pact(3, vec!["chair", "alice", "bob", "charlie"]);
+        pact.weights = Some([(pubkey("chair"), 2)].into_iter().collect());
+        assert_eq!(pact.total_weight(), 5);
+        registry.register(pact);
+
+        assert!(registry.validate(&proof(&["chair", "alice"]), CONTAINER, LINK_HASH, 0x01, 1000).is_ok());
+        assert!(registry.validate(&proof(&["alice", "bob", "charlie"]), CONTAINER, LINK_HASH, 0x01, 1000).is_ok());
+        assert_eq!(
+            registry.validate(&proof(&["alice", "bob"]), CONTAINER, LINK_HASH, 0x01, 1000),
+            Err(PactError::InsufficientSignatures { got: 2, need: 3 })
+        );
+        // A duplicate still counts once
+        assert_eq!(
+            registry.validate(&proof(&["chair", "chair"]), CONTAINER, LINK_HASH, 0x01, 1000),
+            Err(PactError::InsufficientSignatures { got: 2, need: 3 })
+        );
+    }
+
+    #[test]
+    fn test_meta_pact_registers_children() {
+        let mut registry = PactRegistry::new();
+        let mut parent = make_pact(2, vec!["alice", "bob", "charlie"]);
+        parent.scope = PactScope::Namespace;
+        parent.namespace = Some("acme".to_string());
+        parent.risk_level = RiskLevel::L4;
+        parent.issues_pacts = true;
+        registry.register(parent);
+        registry.set_namespace("acme.fund", "acme");
+
+        let child = |pact_id: &str, scope: PactScope, risk_level: RiskLevel| Pact {
+            pact_id: pact_id.to_string(),
+            scope,
+            container_id: Some("acme.fund".to_string()),
+            namespace: Some("acme".to_string()),
+            risk_level,
+            parent: Some("pact_test".to_string()),
+            ..make_pact(1, vec!["dave"])
+        };
+        let authorize = |child: &Pact, signers: &[&str]| PactProof {
+            signatures: signers.iter().map(|s| sign(s, &child.draft_hash())).collect(),
+            ..proof(&[])
+        };
+
+        let fund = child("pact_fund", PactScope::Container, RiskLevel::L3);
+        assert!(matches!(
+            registry.register_child(fund.clone(), &authorize(&fund, &["alice"]), 1000),
+            Err(PactError::InsufficientSignatures { got: 1, need: 2 })
+        ));
+        // Signed for other terms
+        let raised = Pact {
+            threshold: 2,
+            ..fund.clone()
+        };
+        assert!(registry.register_child(raised, &authorize(&fund, &["alice", "bob"]), 1000).is_err());
+        registry.register_child(fund.clone(), &authorize(&fund, &["alice", "bob"]), 1000).unwrap();
+        assert_eq!(registry.get("pact_fund").unwrap().parent.as_deref(), Some("pact_test"));
+        assert!(matches!(
+            registry.register_child(fund.clone(), &authorize(&fund, &["alice", "bob"]), 1000),
+            Err(PactError::InvalidChildPact(_))
+        ));
+
+        // Not above the parent's risk level, nor outside its namespace
+        let risky = child("pact_risky", PactScope::Namespace, RiskLevel::L5);
+        assert!(matches!(
+            registry.register_child(risky.clone(), &authorize(&risky, &["alice", "bob"]), 1000),
+            Err(PactError::RiskMismatch { .. })
+        ));
+        let global = child("pact_global", PactScope::Global, RiskLevel::L2);
+        assert!(matches!(
+            registry.register_child(global.clone(), &authorize(&global, &["alice", "bob"]), 1000),
+            Err(PactError::InvalidChildPact(_))
+        ));
+
+        // A child issues no pacts unless it is a meta-pact itself
+        let grandchild = Pact {
+            parent: Some("pact_fund".to_string()),
+            ..child("pact_grandchild", PactScope::Container, RiskLevel::L2)
+        };
+        let under_fund = PactProof {
+            pact_id: "pact_fund".to_string(),
+            ..authorize(&grandchild, &["dave"])
+        };
+        assert!(matches!(
+            registry.check_child(&grandchild, &under_fund, 1000),
+            Err(PactError::InvalidChildPact(_))
+        ));
+    }
+
+    #[test]
+    fn test_emergency_freeze() {
+        use freeze::FreezeAction;
+
+        let mut registry = PactRegistry::new();
+        registry.register(make_pact(2, vec!["alice", "bob", "charlie"]));
+        let mut emergency = make_pact(2, vec!["dave", "erin", "frank"]);
+        emergency.pact_id = "pact_emergency".to_string();
+        emergency.scope = PactScope::Global;
+        emergency.container_id = None;
+        emergency.emergency = true;
+        registry.register(emergency);
+
+        let acting = |action: FreezeAction, signers: &[&str], nonce: u64| {
+            let message = PactProof::signing_bytes(&action.subject(), "pact_emergency", nonce);
+            PactProof {
+                pact_id: "pact_emergency".to_string(),
+                signatures: signers
+                    .iter()
+                    .map(|s| PactSignature {
+                        pubkey: pubkey(s),
+                        signature: ubl_kernel::sign(&key(s), &message),
+                    })
+                    .collect(),
+                nonce,
+                ..proof(&[])
+            }
+        };
+
+        // Only a Global emergency pact freezes
+        let mut ordinary = acting(FreezeAction::Freeze, &[], 0);
+        ordinary.pact_id = "pact_test".to_string();
+        assert!(matches!(
+            registry.apply_freeze(&ordinary, FreezeAction::Freeze, 1000),
+            Err(PactError::NotEmergencyPact(_))
+        ));
+        assert!(matches!(
+            registry.apply_freeze(&acting(FreezeAction::Freeze, &["dave"], 0), FreezeAction::Freeze, 1000),
+            Err(PactError::InsufficientSignatures { got: 1, need: 2 })
+        ));
+
+        let freeze = acting(FreezeAction::Freeze, &["dave", "erin"], 0);
+        registry.apply_freeze(&freeze, FreezeAction::Freeze, 1000).unwrap();
+        assert_eq!(registry.frozen().unwrap().pact_id, "pact_emergency");
+        assert!(matches!(
+            registry.validate(&proof(&["alice", "bob"]), CONTAINER, LINK_HASH, 0x01, 1000),
+            Err(PactError::Frozen(_))
+        ));
+        assert!(registry.validate(&proof(&["alice", "bob"]), CONTAINER, LINK_HASH, 0x00, 1000).is_ok());
+
+        // The freeze proof cannot lift, nor act again
+        assert!(registry.apply_freeze(&freeze, FreezeAction::Lift, 1000).is_err());
+        assert!(matches!(
+            registry.apply_freeze(&freeze, FreezeAction::Freeze, 1000),
+            Err(PactError::Replayed { .. })
+        ));
+        registry
+            .apply_freeze(&acting(FreezeAction::Lift, &["erin", "frank"], 1), FreezeAction::Lift, 1000)
+            .unwrap();
+        assert!(registry.frozen().is_none());
+        assert!(registry.validate(&proof(&["alice", "bob"]), CONTAINER, LINK_HASH, 0x01, 1000).is_ok());
+    }
+
+    #[test]
+    fn test_risk_thresholds() {
+        let mut registry = PactRegistry::new();
+        let mut pact = make_pact(3, vec!["alice", "bob", "charlie"]);
+        pact.risk_level = RiskLevel::L5;
+        pact.risk_thresholds = Some([(RiskLevel::L2, 1), (RiskLevel::L4, 3)].into_iter().collect());
+        assert_eq!(
+            [RiskLevel::L0, RiskLevel::L2, RiskLevel::L3, RiskLevel::L5].map(|r| pact.threshold_for(r)),
+            [1, 1, 3, 3]
+        );
+        let json = serde_json::to_value(&pact).unwrap();
+        assert_eq!(json["risk_thresholds"], serde_json::json!({"L2": 1, "L4": 3}));
+        registry.register(serde_json::from_value(json).unwrap());
+
+        // Conservation (L2) takes one signer, Entropy (L4) all three
+        assert!(registry.validate(&proof(&["bob"]), CONTAINER, LINK_HASH, 0x01, 1000).is_ok());
+        assert_eq!(
+            registry.validate(&proof(&["bob"]), CONTAINER, LINK_HASH, 0x02, 1000),
+            Err(PactError::InsufficientSignatures { got: 1, need: 3 })
+        );
+        assert!(registry.validate(&proof(&["alice", "bob", "charlie"]), CONTAINER, LINK_HASH, 0x02, 1000).is_ok());
+    }
+
+    #[test]
+    fn test_risk_thresholds_never_inherit_a_lower_level() {
+        // Above the highest entry the base threshold holds, however low
+        // the entries below
+        let mut pact = make_pact(3, vec!["alice", "bob", "charlie"]);
+        pact.risk_level = RiskLevel::L5;
+        pact.risk_thresholds = Some([(RiskLevel::L1, 1)].into_iter().collect());
+        assert_eq!(
+            [RiskLevel::L0, RiskLevel::L1, RiskLevel::L2, RiskLevel::L4].map(|r| pact.threshold_for(r)),
+            [1, 1, 3, 3]
+        );
+        assert_eq!(pact.at_risk(RiskLevel::L4).threshold, 3);
+    }
+
+    #[test]
+    fn test_pact_without_weights_round_trips() {
+        let pact = make_pact(2, vec!["alice", "bob"]);
+        let json = serde_json::to_value(&pact).unwrap();
+        assert!(json.get("weights").is_none());
+        assert!(json.get("groups").is_none());
+        assert!(json.get("veto_signers").is_none());
+        let back: Pact = serde_json::from_value(json).unwrap();
+        assert!(back.weights.is_none());
+        assert!(back.groups.is_empty());
+        assert_eq!(back.weight(&pubkey("alice")), 1);
+    }
+
+    fn group(name: &str, members: &[&str], threshold: usize) -> SignerGroup {
+        SignerGroup {
+            name: name.to_string(),
+            members: members.iter().map(|m| pubkey(m)).collect(),
+            threshold,
+        }
+    }
+
+    #[test]
+    fn test_group_quorum() {
+        // 2 of admins AND 1 of auditors
+        let mut registry = PactRegistry::new();
+        let mut pact = make_pact(3, vec!["ann", "abe", "amy", "otto", "olga"]);
+        pact.groups = vec![group("admins", &["ann", "abe", "amy"], 2), group("auditors", &["otto", "olga"], 1)];
+        registry.register(pact);
+
+        assert!(registry.validate(&proof(&["ann", "abe", "otto"]), CONTAINER, LINK_HASH, 0x01, 1000).is_ok());
+        assert_eq!(
+            registry.validate(&proof(&["ann", "abe", "amy"]), CONTAINER, LINK_HASH, 0x01, 1000),
+            Err(PactError::GroupQuorumNotMet { group: "auditors".to_string(), got: 0, need: 1 })
+        );
+        assert_eq!(
+            registry.validate(&proof(&["ann", "otto", "olga"]), CONTAINER, LINK_HASH, 0x01, 1000),
+            Err(PactError::GroupQuorumNotMet { group: "admins".to_string(), got: 1, need: 2 })
+        );
+        // Groups add to the overall threshold, they do not replace it
+        assert_eq!(
+            registry.validate(&proof(&["ann", "abe"]), CONTAINER, LINK_HASH, 0x01, 1000),
+            Err(PactError::InsufficientSignatures { got: 2, need: 3 })
+        );
+    }
+
+    #[test]
+    fn test_group_quorum_counts_weight() {
+        let mut pact = make_pact(2, vec!["chair", "ann", "otto"]);
+        pact.weights = Some([(pubkey("chair"), 2)].into_iter().collect());
+        pact.groups = vec![group("admins", &["chair", "ann"], 2)];
+        assert!(pact.check_quorum(&[pubkey("chair").as_str()]).is_ok());
+        assert_eq!(
+            pact.check_quorum(&[pubkey("ann").as_str(), pubkey("otto").as_str()]),
+            Err(PactError::GroupQuorumNotMet { group: "admins".to_string(), got: 1, need: 2 })
+        );
+    }
+
+    #[test]
+    fn test_unauthorized_signer() {
+        let mut registry = PactRegistry::new();
+        registry.register(make_pact(1, vec!["alice", "bob"]));
+
+        let result = registry.validate(&proof(&["eve"]), CONTAINER, LINK_HASH, 0x01, 1000);
+        assert!(matches!(result, Err(PactError::UnauthorizedSigner(_))));
+    }
+
+    #[test]
+    fn test_scope_mismatch() {
+        let mut registry = PactRegistry::new();
+        let mut pact = make_pact(1, vec!["alice"]);
+        pact.scope = PactScope::Namespace;
+        pact.namespace = Some("acme".to_string());
+        registry.register(pact);
+
+        // Only containers placed in the pact's namespace are covered
+        let outside = registry.validate(&proof(&["alice"]), "C.Acme", LINK_HASH, 0x01, 1000);
+        assert_eq!(
+            outside,
+            Err(PactError::ScopeMismatch { container_id: "C.Acme".to_string(), scope: PactScope::Namespace })
+        );
+        registry.set_namespace("C.Acme", "acme");
+        assert!(registry.validate(&proof(&["alice"]), "C.Acme", LINK_HASH, 0x01, 1000).is_ok());
+        registry.set_namespace("C.Acme", "globex");
+        assert!(registry.validate(&proof(&["alice"]), "C.Acme", LINK_HASH, 0x01, 1000).is_err());
+
+        // A container-scoped pact covers its own container only
+        registry.register(make_pact(1, vec!["alice"]));
+        assert!(registry.validate(&proof(&["alice"]), CONTAINER, LINK_HASH, 0x01, 1000).is_ok());
+        let elsewhere = registry.validate(&proof(&["alice"]), "other", LINK_HASH, 0x01, 1000);
+        assert!(matches!(elsewhere, Err(PactError::ScopeMismatch { .. })));
+    }
+
+    #[test]
+    fn test_invalid_signature() {
+        let mut registry = PactRegistry::new();
+        registry.register(make_pact(1, vec!["alice"]));
+
+        // Signed for another link
+        let other = LINK_HASH.replace('5', "6");
+        let stale = PactProof {
+            pact_id: "pact_test".to_string(),
+            signatures: vec![sign("alice", &other)],
+            nonce: 0,
+            delegations: Vec::new(),
+            frost_signature: None,
+            bls_aggregate: None,
+        };
+        let result = registry.validate(&stale, CONTAINER, LINK_HASH, 0x01, 1000);
+        assert_eq!(result, Err(PactError::InvalidSignature(pubkey("alice"))));
+        assert!(registry.validate(&stale, CONTAINER, &other, 0x01, 1000).is_ok());
+
+        // Same signature under another nonce
+        let mut renonced = proof(&["alice"]);
+        renonced.nonce = 1;
+        let result = registry.validate(&renonced, CONTAINER, LINK_HASH, 0x01, 1000);
+        assert!(matches!(result, Err(PactError::InvalidSignature(_))));
+
+        // Garbage signature from an authorized key
+        let mut forged = proof(&["alice"]);
+        forged.signatures[0].signature = "00".repeat(64);
+        let result = registry.validate(&forged, CONTAINER, LINK_HASH, 0x01, 1000);
+        assert!(matches!(result, Err(PactError::InvalidSignature(_))));
+    }
+
+    #[test]
+    fn test_signing_bytes_layout() {
+        let bytes = PactProof::signing_bytes(&LINK_HASH.to_ascii_uppercase(), "pact_test", 7);
+        let mut expected = b"ubl:pact:v1\n".to_vec();
+        expected.extend_from_slice(&[0, 0, 0, 9]);
+        expected.extend_from_slice(b"pact_test");
+        expected.extend_from_slice(&[0, 0, 0, 64]);
+        expected.extend_from_slice(LINK_HASH.as_bytes());
+        expected.extend_from_slice(&7u64.to_be_bytes());
+        assert_eq!(bytes, expected);
+        // Length prefixes keep field boundaries unambiguous
+        assert_ne!(
+            PactProof::signing_bytes("ab", "pact", 0),
+            PactProof::signing_bytes("b", "pacta", 0)
+        );
+    }
+
+    #[test]
+    fn test_expired_pact() {
+        let mut registry = PactRegistry::new();
+        let mut pact = make_pact(1, vec!["alice"]);
+        pact.window.not_after = 1000;
+        registry.register(pact);
+
+        let result = registry.validate(&proof(&["alice"]), CONTAINER, LINK_HASH, 0x01, 2000);
+        assert!(matches!(result, Err(PactError::PactExpired)));
+    }
+
+    fn window(not_before: i64, not_after: i64) -> TimeWindow {
+        TimeWindow {
+            not_before,
+            not_after,
+        }
+    }
+
+    #[test]
+    fn test_renewal_lineage_and_grace() {
+        let mut registry = PactRegistry::new();
+        let mut first = make_pact(1, vec!["alice", "bob"]);
+        first.window.not_after = 1000;
+        let second = first.renewal("pact_test_2", window(1200, 2000));
+        assert_eq!(second.signers, first.signers);
+        let mut third = second.renewal("pact_test_3", window(2000, 3000));
+        third.signers.remove(&pubkey("alice"));
+        registry.register(first.clone());
+
+        // Out of order or twice: refused
+        assert!(matches!(registry.renew(third.clone()), Err(PactError::UnknownPact(_))));
+        registry.renew(second.clone()).unwrap();
+        let rival = first.renewal("pact_rival", window(1200, 2000));
+        assert!(matches!(registry.renew(rival), Err(PactError::InvalidRenewal(_))));
+        registry.renew(third).unwrap();
+
+        let ids = |id: &str| -> Vec<String> { registry.lineage(id).iter().map(|p| p.pact_id.clone()).collect() };
+        assert_eq!(ids("pact_test_2"), ["pact_test", "pact_test_2", "pact_test_3"]);
+        assert_eq!(ids("pact_test"), ids("pact_test_3"));
+        assert_eq!(registry.successor("pact_test").unwrap().pact_id, "pact_test_2");
+
+        // Past not_after, a proof under the renewed pact holds only with
+        // grace, and only once the successor is in force
+        let expired = registry.validate(&proof(&["alice"]), CONTAINER, LINK_HASH, 0x01, 1500);
+        assert_eq!(expired, Err(PactError::PactExpired));
+        registry.set_renewal_grace(600);
+        assert_eq!(
+            registry.validate(&proof(&["alice"]), CONTAINER, LINK_HASH, 0x01, 1100),
+            Err(PactError::PactExpired)
+        );
+        assert!(registry.validate(&proof(&["alice"]), CONTAINER, LINK_HASH, 0x01, 1500).is_ok());
+        assert_eq!(
+            registry.validate(&proof(&["alice"]), CONTAINER, LINK_HASH, 0x01, 1601),
+            Err(PactError::PactExpired)
+        );
+    }
+
+    #[test]
+    fn test_single_use_pact() {
+        let mut registry = PactRegistry::new();
+        let mut pact = make_pact(1, vec!["alice"]);
+        pact.max_uses = Some(1);
+        registry.register(pact);
+        let valid = proof(&["alice"]);
+
+        assert!(registry.validate(&valid, CONTAINER, LINK_HASH, 0x01, 1000).is_ok());
+        assert_eq!(registry.record_use("pact_test"), Ok(1));
+        assert_eq!(
+            registry.validate(&valid, CONTAINER, LINK_HASH, 0x01, 1000),
+            Err(PactError::UsesExhausted { used: 1, max: 1 })
+        );
+        assert_eq!(registry.record_use("pact_test"), Err(PactError::UsesExhausted { used: 1, max: 1 }));
+        assert_eq!(registry.uses("pact_test"), 1);
+
+        // Registering it again does not reset the count; no limit, no check
+        registry.register(make_pact(1, vec!["alice"]));
+        assert_eq!(registry.record_use("pact_test"), Ok(2));
+        assert!(registry.validate(&valid, CONTAINER, LINK_HASH, 0x01, 1000).is_ok());
+        assert!(matches!(registry.record_use("pact_other"), Err(PactError::UnknownPact(_))));
+    }
+
+    #[derive(Clone, Default)]
+    struct Recorder(std::sync::Arc<std::sync::Mutex<Vec<ValidationEvent>>>);
+
+    impl ValidationSink for Recorder {
+        fn record(&self, event: &ValidationEvent) {
+            self.0.lock().unwrap().push(event.clone());
+        }
+    }
+
+    #[test]
+    fn test_validations_are_recorded() {
+        let recorder = Recorder::default();
+        let mut registry = PactRegistry::new();
+        registry.register(make_pact(2, vec!["alice", "bob", "charlie"]));
+        registry.set_sink(recorder.clone());
+
+        registry.validate(&proof(&["alice", "bob"]), CONTAINER, LINK_HASH, 0x01, 1000).unwrap();
+        let mut forged = proof(&["alice", "bob", "mallory"]);
+        forged.signatures[1].signature = sign("bob", &LINK_HASH.replace('5', "6")).signature;
+        assert!(registry.validate(&forged, CONTAINER, LINK_HASH, 0x01, 1000).is_err());
+        assert!(registry.validate(&proof(&["alice"]), "elsewhere", LINK_HASH, 0x01, 1000).is_err());
+
+        let events = recorder.0.lock().unwrap();
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[0].signatures.counted, [pubkey("alice"), pubkey("bob")]);
+        assert_eq!((events[0].link_hash.as_str(), &events[0].error), (LINK_HASH, &None));
+
+        // Every signature is tallied, and the first failure is the outcome
+        assert_eq!(events[1].signatures.counted, [pubkey("alice")]);
+        let rejected: Vec<_> = events[1].signatures.rejected.iter().map(|r| r.pubkey.clone()).collect();
+        assert_eq!(rejected, [pubkey("bob"), pubkey("mallory")]);
+        assert_eq!(events[1].error, Some(PactError::InvalidSignature(pubkey("bob"))));
+
+        // Refused before any signature was looked at
+        assert_eq!(events[2].signatures, SignatureTally::default());
+        assert!(matches!(events[2].error, Some(PactError::ScopeMismatch { .. })));
+    }
+
+    #[test]
+    fn test_consumed_proof_is_not_replayed() {
+        let mut registry = PactRegistry::new();
+        registry.register(make_pact(2, vec!["alice", "bob", "charlie"]));
+        let valid = proof(&["alice", "bob"]);
+        let replayed = Err(PactError::Replayed { link_hash: LINK_HASH.to_string(), nonce: 0 });
+
+        registry.consume(&valid, &LINK_HASH.to_ascii_uppercase()).unwrap();
+        assert_eq!(registry.uses("pact_test"), 1);
+        assert_eq!(registry.validate(&valid, CONTAINER, LINK_HASH, 0x01, 1000), replayed);
+        // Other signers, same link and nonce: the same authorization
+        assert_eq!(registry.validate(&proof(&["bob", "charlie"]), CONTAINER, LINK_HASH, 0x01, 1000), replayed);
+        assert_eq!(registry.consume(&valid, LINK_HASH), replayed);
+        assert_eq!(registry.uses("pact_test"), 1);
+
+        // On another link the signatures do not verify; a fresh nonce is a new proof
+        let other = LINK_HASH.replace('5', "6");
+        assert!(matches!(
+            registry.validate(&valid, CONTAINER, &other, 0x01, 1000),
+            Err(PactError::InvalidSignature(_))
+        ));
+        let mut fresh = proof(&["alice", "bob"]);
+        fresh.nonce = 1;
+        fresh.signatures = ["alice", "bob"]
+            .iter()
+            .map(|s| PactSignature {
+                pubkey: pubkey(s),
+                signature: ubl_kernel::sign(&key(s), &PactProof::signing_bytes(LINK_HASH, "pact_test", 1)),
+            })
+            .collect();
+        assert!(registry.validate(&fresh, CONTAINER, LINK_HASH, 0x01, 1000).is_ok());
+    }
+
+    fn delegation(parent: &str, delegate: &str, window: TimeWindow) -> SignerDelegation {
+        let message = SignerDelegation::signing_bytes("pact_test", &pubkey(parent), &pubkey(delegate), &window);
+        SignerDelegation {
+            pact_id: "pact_test".to_string(),
+            parent: pubkey(parent),
+            delegate: pubkey(delegate),
+            signature: ubl_kernel::sign(&key(parent), &message),
+            window,
+        }
+    }
+
+    #[test]
+    fn test_delegation_chain() {
+        let mut registry = PactRegistry::new();
+        registry.register(make_pact(2, vec!["alice", "bob", "charlie"]));
+
+        // alice -> laptop -> phone; the phone signs for alice
+        let mut delegated = proof(&["phone", "bob"]);
+        delegated.delegations = vec![
+            delegation("alice", "laptop", window(0, 2000)),
+            delegation("laptop", "phone", window(500, 1500)),
+        ];
+        assert!(registry.validate(&delegated, CONTAINER, LINK_HASH, 0x01, 1000).is_ok());
+        assert!(matches!(
+            registry.validate(&delegated, CONTAINER, LINK_HASH, 0x01, 1600),
+            Err(PactError::InvalidDelegation(_))
+        ));
+
+        // alice and her delegate are one signer
+        let mut twice = proof(&["alice", "phone"]);
+        twice.delegations = delegated.delegations.clone();
+        assert_eq!(
+            registry.validate(&twice, CONTAINER, LINK_HASH, 0x01, 1000),
+            Err(PactError::InsufficientSignatures { got: 1, need: 2 })
+        );
+
+        // A stretched window breaks the parent's signature
+        let mut stretched = delegated.clone();
+        stretched.delegations[1].window.not_after = 5000;
+        assert_eq!(
+            registry.validate(&stretched, CONTAINER, LINK_HASH, 0x01, 1000),
+            Err(PactError::InvalidSignature(pubkey("laptop")))
+        );
+
+        // Keys outside the pact delegate nothing
+        let mut rogue = proof(&["mallory", "bob"]);
+        rogue.delegations = vec![delegation("eve", "mallory", window(0, 2000))];
+        assert_eq!(
+            registry.validate(&rogue, CONTAINER, LINK_HASH, 0x01, 1000),
+            Err(PactError::UnauthorizedSigner(pubkey("mallory")))
+        );
+
+        // Chains are bounded: four delegations from alice, not five
+        let names: Vec<String> = (0..=MAX_DELEGATION_DEPTH).map(|i| format!("key{}", i)).collect();
+        let parents = std::iter::once("alice").chain(names.iter().map(String::as_str));
+        let mut deep = proof(&["bob"]);
+        deep.delegations = parents.zip(&names).map(|(p, d)| delegation(p, d, window(0, 2000))).collect();
+        deep.signatures.push(sign(&names[MAX_DELEGATION_DEPTH - 1], LINK_HASH));
+        assert!(registry.validate(&deep, CONTAINER, LINK_HASH, 0x01, 1000).is_ok());
+        deep.signatures[1] = sign(&names[MAX_DELEGATION_DEPTH], LINK_HASH);
+        assert!(matches!(
+            registry.validate(&deep, CONTAINER, LINK_HASH, 0x01, 1000),
+            Err(PactError::InvalidDelegation(_))
+        ));
+    }
+
+    #[test]
+    fn test_sid_signer_follows_key_rotation() {
+        let sid = format!("{}{}", SID_PREFIX, "ab".repeat(32));
+        let mut registry = PactRegistry::new();
+        let mut pact = make_pact(2, vec!["bob"]);
+        pact.signers.insert(sid.clone());
+        pact.weights = Some([(sid.clone(), 2)].into_iter().collect());
+        registry.register(pact);
+        // Rotated from old to new at 1000
+        registry.set_signer_keys(
+            sid.as_str(),
+            vec![
+                SignerKey { pubkey: pubkey("old"), valid_from: 0, revoked_at: Some(1000) },
+                SignerKey { pubkey: pubkey("new"), valid_from: 1000, revoked_at: None },
+            ],
+        );
+        assert_eq!(registry.signer_key(&sid, 999), Some(pubkey("old").as_str()));
+
+        assert!(registry.validate(&proof(&["old"]), CONTAINER, LINK_HASH, 0x01, 500).is_ok());
+        assert_eq!(
+            registry.validate(&proof(&["old"]), CONTAINER, LINK_HASH, 0x01, 1500),
+            Err(PactError::UnauthorizedSigner(pubkey("old")))
+        );
+        assert!(registry.validate(&proof(&["new"]), CONTAINER, LINK_HASH, 0x01, 1500).is_ok());
+        assert!(matches!(
+            registry.validate(&proof(&["new"]), CONTAINER, LINK_HASH, 0x01, 500),
+            Err(PactError::UnauthorizedSigner(_))
+        ));
+        // The resolved terms carry the SID's weight over to its key
+        assert_eq!(registry.resolve(registry.get("pact_test").unwrap(), 1500).weight(&pubkey("new")), 2);
+    }
+
+    #[test]
+    fn test_frost_threshold_proof() {
+        // A FROST signature verifies as Ed25519 under the group key; the
+        // group's secret stands in for the signers' rounds here
+        let mut registry = PactRegistry::new();
+        let mut pact = make_pact(2, vec!["alice", "bob", "charlie"]);
+        pact.frost_group_key = Some(pubkey("group"));
+        registry.register(pact);
+        let message = PactProof::signing_bytes(LINK_HASH, "pact_test", 0);
+        let mut threshold = proof(&[]);
+        threshold.frost_signature = Some(ubl_kernel::sign(&key("group"), &message));
+
+        assert!(registry.validate(&threshold, CONTAINER, LINK_HASH, 0x01, 1000).is_ok());
+        assert_eq!(
+            registry.validate(&threshold, CONTAINER, &LINK_HASH.replace('5', "6"), 0x01, 1000),
+            Err(PactError::InvalidSignature(pubkey("group")))
+        );
+        // Individual signatures still work, but do not mix with a threshold one
+        assert!(registry.validate(&proof(&["alice", "bob"]), CONTAINER, LINK_HASH, 0x01, 1000).is_ok());
+        let mut mixed = threshold.clone();
+        mixed.signatures = vec![sign("alice", LINK_HASH)];
+        assert!(matches!(
+            registry.validate(&mixed, CONTAINER, LINK_HASH, 0x01, 1000),
+            Err(PactError::InvalidThresholdProof(_))
+        ));
+
+        // No group key, no threshold proofs
+        registry.register(make_pact(2, vec!["alice", "bob", "charlie"]));
+        assert!(matches!(
+            registry.validate(&threshold, CONTAINER, LINK_HASH, 0x01, 1000),
+            Err(PactError::InvalidThresholdProof(_))
+        ));
+    }
+
+    #[test]
+    fn test_signature_scheme_mismatch() {
+        let mut registry = PactRegistry::new();
+        let mut pact = make_pact(2, vec!["alice", "bob", "charlie"]);
+        pact.signature_scheme = SignatureScheme::Bls12381;
+        registry.register(pact);
+        assert_eq!(
+            registry.validate(&proof(&["alice", "bob"]), CONTAINER, LINK_HASH, 0x01, 1000),
+            Err(PactError::SchemeMismatch {
+                pact: SignatureScheme::Bls12381,
+                proof: SignatureScheme::Ed25519,
+            })
+        );
+        let mut aggregate = proof(&[]);
+        aggregate.bls_aggregate = Some(BlsAggregate {
+            signers: vec![pubkey("alice"), pubkey("bob")],
+            signature: "00".to_string(),
+        });
+        #[cfg(not(feature = "bls"))]
+        assert_eq!(
+            registry.validate(&aggregate, CONTAINER, LINK_HASH, 0x01, 1000),
+            Err(PactError::UnsupportedScheme(SignatureScheme::Bls12381))
+        );
+
+        // An Ed25519 pact takes no aggregate
+        registry.register(make_pact(2, vec!["alice", "bob", "charlie"]));
+        assert_eq!(
+            registry.validate(&aggregate, CONTAINER, LINK_HASH, 0x01, 1000),
+            Err(PactError::SchemeMismatch {
+                pact: SignatureScheme::Ed25519,
+                proof: SignatureScheme::Bls12381,
+            })
+        );
+    }
+
+    fn revocation(signers: &[&str], revoked_at: i64) -> PactRevocation {
+        let message = PactRevocation::signing_bytes("pact_test", revoked_at, "key leaked");
+        PactRevocation {
+            pact_id: "pact_test".to_string(),
+            revoked_at,
+            reason: "key leaked".to_string(),
+            signatures: signers
+                .iter()
+                .map(|s| PactSignature {
+                    pubkey: pubkey(s),
+                    signature: ubl_kernel::sign(&key(s), &message),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_revoked_pact() {
+        let mut registry = PactRegistry::new();
+        registry.register(make_pact(2, vec!["alice", "bob", "charlie"]));
+        let valid = proof(&["alice", "bob"]);
+
+        registry.revoke(revocation(&["bob", "charlie"], 1000)).unwrap();
+        assert!(registry.validate(&valid, CONTAINER, LINK_HASH, 0x01, 999).is_ok());
+        assert_eq!(registry.validate(&valid, CONTAINER, LINK_HASH, 0x01, 1000), Err(PactError::Revoked));
+
+        // Re-registering does not lift it, nor can it be revoked twice
+        registry.register(make_pact(2, vec!["alice", "bob", "charlie"]));
+        assert_eq!(registry.validate(&valid, CONTAINER, LINK_HASH, 0x01, 1000), Err(PactError::Revoked));
+        assert_eq!(registry.revoke(revocation(&["alice", "bob"], 2000)), Err(PactError::Revoked));
+        assert_eq!(registry.revocation("pact_test").unwrap().revoked_at, 1000);
+    }
+
+    #[test]
+    fn test_revocation_needs_quorum() {
+        let mut registry = PactRegistry::new();
+        registry.register(make_pact(2, vec!["alice", "bob", "charlie"]));
+
+        assert!(matches!(
+            registry.revoke(revocation(&["alice", "alice"], 1000)),
+            Err(PactError::InsufficientSignatures { got: 1, need: 2 })
+        ));
+        assert!(matches!(
+            registry.revoke(revocation(&["alice", "eve"], 1000)),
+            Err(PactError::UnauthorizedSigner(_))
+        ));
+
+        // Signed for another moment
+        let mut moved = revocation(&["alice", "bob"], 1000);
+        moved.revoked_at = 5000;
+        assert!(matches!(registry.revoke(moved), Err(PactError::InvalidSignature(_))));
+
+        let mut unknown = revocation(&["alice", "bob"], 1000);
+        unknown.pact_id = "pact_other".to_string();
+        assert!(matches!(registry.revoke(unknown), Err(PactError::UnknownPact(_))));
+
+        assert!(registry.revocation("pact_test").is_none());
+        assert!(registry.validate(&proof(&["alice", "bob"]), CONTAINER, LINK_HASH, 0x01, 1000).is_ok());
+    }
+
+    fn veto(name: &str, link_hash: Option<&str>, vetoed_at: i64) -> PactVeto {
+        PactVeto {
+            pact_id: "pact_test".to_string(),
+            link_hash: link_hash.map(str::to_string),
+            vetoed_at,
+            reason: "compliance hold".to_string(),
+            signature: PactSignature {
+                pubkey: pubkey(name),
+                signature: ubl_kernel::sign(
+                    &key(name),
+                    &PactVeto::signing_bytes("pact_test", link_hash, vetoed_at, "compliance hold"),
+                ),
+            },
+        }
+    }
+
+    #[test]
+    fn test_veto_overrides_quorum() {
+        let mut registry = PactRegistry::new();
+        let mut pact = make_pact(2, vec!["alice", "bob", "charlie"]);
+        pact.veto_signers = [pubkey("officer")].into_iter().collect();
+        registry.register(pact);
+        let unanimous = proof(&["alice", "bob", "charlie"]);
+        let other = LINK_HASH.replace('5', "6");
+
+        // Counter-signature against one link
+        registry.veto(veto("officer", Some(LINK_HASH.to_ascii_uppercase().as_str()), 1000)).unwrap();
+        assert!(registry.validate(&unanimous, CONTAINER, LINK_HASH, 0x01, 999).is_ok());
+        assert_eq!(
+            registry.validate(&unanimous, CONTAINER, LINK_HASH, 0x01, 1000),
+            Err(PactError::Vetoed(pubkey("officer")))
+        );
+        let elsewhere = PactProof {
+            pact_id: "pact_test".to_string(),
+            signatures: vec![sign("alice", &other), sign("bob", &other)],
+            nonce: 0,
+            delegations: Vec::new(),
+            frost_signature: None,
+            bls_aggregate: None,
+        };
+        assert!(registry.validate(&elsewhere, CONTAINER, &other, 0x01, 1000).is_ok());
+
+        // Veto of the pact from 2000 on
+        registry.veto(veto("officer", None, 2000)).unwrap();
+        assert!(registry.validate(&elsewhere, CONTAINER, &other, 0x01, 1999).is_ok());
+        assert_eq!(
+            registry.validate(&elsewhere, CONTAINER, &other, 0x01, 2000),
+            Err(PactError::Vetoed(pubkey("officer")))
+        );
+        assert_eq!(registry.vetoes("pact_test").len(), 2);
+    }
+
+    #[test]
+    fn test_veto_needs_veto_signer() {
+        let mut registry = PactRegistry::new();
+        let mut pact = make_pact(2, vec!["alice", "bob"]);
+        pact.veto_signers = [pubkey("officer")].into_iter().collect();
+        pact.window.not_after = 5000;
+        registry.register(pact);
+
+        // Signers approve; they do not veto
+        assert_eq!(
+            registry.veto(veto("alice", None, 1000)),
+            Err(PactError::UnauthorizedSigner(pubkey("alice")))
+        );
+        // Outside the window
+        assert_eq!(registry.veto(veto("officer", None, 6000)), Err(PactError::PactExpired));
+        // Signed for another moment
+        let mut moved = veto("officer", None, 1000);
+        moved.vetoed_at = 10;
+        assert_eq!(registry.veto(moved), Err(PactError::InvalidSignature(pubkey("officer"))));
+
+        assert!(registry.vetoes("pact_test").is_empty());
+        assert!(registry.validate(&proof(&["alice", "bob"]), CONTAINER, LINK_HASH, 0x01, 1000).is_ok());
+    }
+
+    fn adopt(pact: &Pact, name: &str) -> PactSignature {
+        PactSignature {
+            pubkey: pubkey(name),
+            signature: ubl_kernel::sign(&key(name), &Pact::adoption_bytes(&pact.pact_id, &pact.draft_hash())),
+        }
+    }
+
+    #[test]
+    fn test_draft_adoption_needs_own_quorum() {
+        let pact = make_pact(2, vec!["alice", "bob", "charlie"]);
+        // Same terms, however the signer set iterates
+        assert_eq!(pact.draft_hash(), make_pact(2, vec!["charlie", "bob", "alice"]).draft_hash());
+
+        assert!(pact.verify_adoption(&[adopt(&pact, "alice"), adopt(&pact, "charlie")]).is_ok());
+        assert!(matches!(
+            pact.verify_adoption(&[adopt(&pact, "alice"), adopt(&pact, "alice")]),
+            Err(PactError::InsufficientSignatures { got: 1, need: 2 })
+        ));
+        assert!(matches!(
+            pact.verify_adoption(&[adopt(&pact, "alice"), adopt(&pact, "eve")]),
+            Err(PactError::UnauthorizedSigner(_))
+        ));
+
+        // Signatures over other terms do not carry over
+        let mut amended = pact.clone();
+        amended.window.not_after = 5000;
+        assert_ne!(amended.draft_hash(), pact.draft_hash());
+        assert!(matches!(
+            amended.verify_adoption(&[adopt(&pact, "alice"), adopt(&pact, "bob")]),
+            Err(PactError::InvalidSignature(_))
+        ));
+    }
+
+    #[test]
+    fn test_risk_mismatch() {
+        let mut registry = PactRegistry::new();
+        let mut pact = make_pact(1, vec!["alice"]);
+        pact.risk_level = RiskLevel::L1; // Too low for Conservation
+        registry.register(pact);
+
+        // Conservation requires L2
+        let result = registry.validate(&proof(&["alice"]), CONTAINER, LINK_HASH, 0x01, 1000);
+        assert!(matches!(result, Err(PactError::RiskMismatch { .. })));
+    }
+}
@@ -0,0 +1,406 @@
+//! Offline pact ceremonies (air-gapped signers)
+//!
+//! 1. [`export`] produces a [`SigningRequestFile`]: the canonical
+//!    [`SigningRequest`] plus its BLAKE3 `request_hash`.
+//! 2. The offline signer (`ubl pact offline:sign`, or [`sign`]) recomputes the
+//!    hash, checks the request is addressed to its key, and signs
+//!    [`PactProof::signing_bytes`]`(link_hash, pact_id, nonce)`.
+//! 3. The resulting [`SignatureFile`] is imported into a [`PendingProof`],
+//!    which checks the request hash, pact, link hash, signer and signature
+//!    before accepting it.
+//!
+//! Offline signatures are ordinary [`PactSignature`]s once imported. A signer
+//! without the request file can sign the link hash, pact id and nonce alone
+//! with the `ubl-pact-sign` binary, which prints the [`PactSignature`].
+
+use std::collections::BTreeMap;
+
+use ed25519_dalek::SigningKey;
+use serde::{Deserialize, Serialize};
+
+use crate::{Pact, PactError, PactProof, PactScope, PactSignature, Result, RiskLevel, SignerGroup};
+
+/// `format` of a signing request file
+pub const REQUEST_FORMAT: &str = "ubl.pact.signing_request.v1";
+
+/// `format` of a signature file
+pub const SIGNATURE_FORMAT: &str = "ubl.pact.signature.v1";
+
+/// What an offline signer is asked to approve
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SigningRequest {
+    /// Always [`REQUEST_FORMAT`]
+    pub format: String,
+    /// Pact the signature counts towards
+    pub pact_id: String,
+    /// Container of the link being authorized
+    pub container_id: String,
+    /// Hash of the link's signing bytes (hex), the value signers approve
+    pub link_hash: String,
+    /// Nonce of the proof being assembled
+    #[serde(default)]
+    pub nonce: u64,
+    /// Intent class of the link
+    pub intent_class: u8,
+    /// Risk level the pact authorizes
+    pub risk_level: RiskLevel,
+    /// Signature weight required by the pact
+    pub threshold: usize,
+    /// Authorized signers (hex public keys, sorted)
+    pub signers: Vec<String>,
+    /// Signer weights, when the pact sets them ([`Pact::weights`])
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weights: Option<BTreeMap<String, usize>>,
+    /// Signer groups and their thresholds, when the pact sets them ([`Pact::groups`])
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub groups: Vec<SignerGroup>,
+    /// Unix timestamp the request was exported at
+    pub issued_at: i64,
+    /// Unix timestamp after which the request must not be signed
+    pub not_after: i64,
+}
+
+impl SigningRequest {
+    /// BLAKE3 over the canonical JSON of the request (hex)
+    pub fn hash(&self) -> String {
+        let value = serde_json::to_value(self).expect("signing request serializes");
+        let canonical = ubl_atom::canonicalize(&value).expect("signing request is finite JSON");
+        ubl_kernel::hash_atom(&canonical)
+    }
+}
+
+/// Exported signing request, as written to disk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SigningRequestFile {
+    /// The request
+    pub request: SigningRequest,
+    /// [`SigningRequest::hash`] at export time
+    pub request_hash: String,
+}
+
+impl SigningRequestFile {
+    /// Check the file was not altered since export
+    pub fn verify(&self) -> Result<()> {
+        if self.request.format != REQUEST_FORMAT {
+            return Err(PactError::InvalidRequest(format!("unsupported format {}", self.request.format)));
+        }
+        let computed = self.request.hash();
+        if computed != self.request_hash {
+            return Err(PactError::RequestHashMismatch {
+                declared: self.request_hash.clone(),
+                computed,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// One signer's answer to a signing request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignatureFile {
+    /// Always [`SIGNATURE_FORMAT`]
+    pub format: String,
+    /// Hash of the request that was signed
+    pub request_hash: String,
+    /// Pact the signature counts towards
+    pub pact_id: String,
+    /// Link hash that was signed
+    pub link_hash: String,
+    /// Proof nonce that was signed
+    #[serde(default)]
+    pub nonce: u64,
+    /// Signer's public key (hex)
+    pub pubkey: String,
+    /// Ed25519 signature over [`PactProof::signing_bytes`] (hex)
+    pub signature: String,
+}
+
+/// Export a signing request for `link_hash` under `pact`, for a proof with
+/// `nonce`; a container-scoped pact exports only for its own container
+pub fn export(
+    pact: &Pact,
+    container_id: &str,
+    link_hash: &str,
+    nonce: u64,
+    intent_class: u8,
+    now: i64,
+) -> Result<SigningRequestFile> {
+    if !is_hash_hex(link_hash) {
+        return Err(PactError::InvalidRequest("link_hash must be 32 bytes of hex".to_string()));
+    }
+    if !pact.window.is_valid(now) {
+        return Err(PactError::PactExpired);
+    }
+    if pact.scope == PactScope::Container && pact.container_id.as_deref() != Some(container_id) {
+        return Err(PactError::ScopeMismatch {
+            container_id: container_id.to_string(),
+            scope: pact.scope,
+        });
+    }
+    let mut signers: Vec<String> = pact.signers.iter().cloned().collect();
+    signers.sort();
+    let request = SigningRequest {
+        format: REQUEST_FORMAT.to_string(),
+        pact_id: pact.pact_id.clone(),
+        container_id: container_id.to_string(),
+        link_hash: link_hash.to_ascii_lowercase(),
+        nonce,
+        intent_class,
+        risk_level: pact.risk_level,
+        threshold: pact.threshold_for(RiskLevel::from_intent_class(intent_class)),
+        signers,
+        weights: pact.weights.as_ref().map(|w| w.iter().map(|(k, v)| (k.clone(), *v)).collect()),
+        groups: pact.groups.clone(),
+        issued_at: now,
+        not_after: pact.window.not_after,
+    };
+    let request_hash = request.hash();
+    Ok(SigningRequestFile { request, request_hash })
+}
+
+/// Offline side: verify a request file and sign it with `key`
+pub fn sign(file: &SigningRequestFile, key: &SigningKey, now: i64) -> Result<SignatureFile> {
+    file.verify()?;
+    let pubkey = ubl_kernel::pubkey_from_signing_key(key);
+    if !file.request.signers.contains(&pubkey) {
+        return Err(PactError::UnauthorizedSigner(pubkey));
+    }
+    if now > file.request.not_after {
+        return Err(PactError::PactExpired);
+    }
+    let message = PactProof::signing_bytes(&file.request.link_hash, &file.request.pact_id, file.request.nonce);
+    Ok(SignatureFile {
+        format: SIGNATURE_FORMAT.to_string(),
+        request_hash: file.request_hash.clone(),
+        pact_id: file.request.pact_id.clone(),
+        link_hash: file.request.link_hash.clone(),
+        nonce: file.request.nonce,
+        pubkey,
+        signature: ubl_kernel::sign(key, &message),
+    })
+}
+
+/// Proof being assembled from imported signature files
+#[derive(Debug, Clone)]
+pub struct PendingProof {
+    request: SigningRequest,
+    request_hash: String,
+    proof: PactProof,
+}
+
+impl PendingProof {
+    /// Start collecting signatures for an exported request
+    pub fn new(file: &SigningRequestFile) -> Result<Self> {
+        file.verify()?;
+        Ok(Self {
+            request: file.request.clone(),
+            request_hash: file.request_hash.clone(),
+            proof: PactProof {
+                pact_id: file.request.pact_id.clone(),
+                signatures: Vec::new(),
+                nonce: file.request.nonce,
+                delegations: Vec::new(),
+                frost_signature: None,
+                bls_aggregate: None,
+            },
+        })
+    }
+
+    /// The request signatures are collected for
+    pub fn request(&self) -> &SigningRequest {
+        &self.request
+    }
+
+    /// Import one signature file; every binding is checked before it is accepted
+    pub fn import(&mut self, sig: &SignatureFile) -> Result<()> {
+        if sig.format != SIGNATURE_FORMAT {
+            return Err(PactError::InvalidRequest(format!("unsupported format {}", sig.format)));
+        }
+        bind("request_hash", &self.request_hash, &sig.request_hash)?;
+        bind("pact_id", &self.request.pact_id, &sig.pact_id)?;
+        bind("link_hash", &self.request.link_hash, &sig.link_hash)?;
+        bind("nonce", &self.request.nonce.to_string(), &sig.nonce.to_string())?;
+
+        let pubkey = sig.pubkey.to_ascii_lowercase();
+        if !self.request.signers.contains(&pubkey) {
+            return Err(PactError::UnauthorizedSigner(pubkey));
+        }
+        if self.proof.signatures.iter().any(|s| s.pubkey == pubkey) {
+            return Err(PactError::DuplicateSigner(pubkey));
+        }
+        let message = PactProof::signing_bytes(&self.request.link_hash, &self.request.pact_id, self.request.nonce);
+        ubl_kernel::verify(&pubkey, &message, &sig.signature)
+            .map_err(|_| PactError::InvalidSignature(pubkey.clone()))?;
+
+        self.proof.signatures.push(PactSignature {
+            pubkey,
+            signature: sig.signature.to_ascii_lowercase(),
+        });
+        Ok(())
+    }
+
+    fn weight(&self, pubkey: &str) -> usize {
+        self.request
+            .weights
+            .as_ref()
+            .and_then(|w| w.get(pubkey))
+            .copied()
+            .unwrap_or(1)
+    }
+
+    /// Weight of the signatures collected so far (their count when the
+    /// pact sets no weights)
+    pub fn collected(&self) -> usize {
+        self.proof.signatures.iter().map(|s| self.weight(&s.pubkey)).sum()
+    }
+
+    fn quorum(&self) -> Result<()> {
+        let signed: Vec<&str> = self.proof.signatures.iter().map(|s| s.pubkey.as_str()).collect();
+        crate::check_quorum(self.request.threshold, &self.request.groups, |s| self.weight(s), &signed)
+    }
+
+    /// Whether the threshold and every group's threshold are met
+    pub fn is_complete(&self) -> bool {
+        self.quorum().is_ok()
+    }
+
+    /// The assembled proof, once the quorum is met
+    pub fn into_proof(self) -> Result<PactProof> {
+        self.quorum()?;
+        Ok(self.proof)
+    }
+}
+
+fn bind(field: &str, expected: &str, got: &str) -> Result<()> {
+    if !expected.eq_ignore_ascii_case(got) {
+        return Err(PactError::BindingMismatch {
+            field: field.to_string(),
+            expected: expected.to_string(),
+            got: got.to_string(),
+        });
+    }
+    Ok(())
+}
+
+fn is_hash_hex(s: &str) -> bool {
+    s.len() == 64 && s.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TimeWindow;
+
+    fn pact(keys: &[&SigningKey]) -> Pact {
+        let window = TimeWindow { not_before: 0, not_after: 5000 };
+        Pact {
+            signers: keys.iter().map(|k| ubl_kernel::pubkey_from_signing_key(k)).collect(),
+            ..crate::templates::base("pact_offline", "C.Evolution", &[], 2, window, RiskLevel::L5)
+        }
+    }
+
+    #[test]
+    fn test_offline_round_trip() {
+        let (_, alice) = ubl_kernel::generate_keypair();
+        let (_, bob) = ubl_kernel::generate_keypair();
+        let pact = pact(&[&alice, &bob]);
+        let file = export(&pact, "C.Evolution", &"ab".repeat(32), 5, 0x03, 1000).unwrap();
+        let elsewhere = export(&pact, "C.Other", &"ab".repeat(32), 5, 0x03, 1000);
+        assert!(matches!(elsewhere, Err(PactError::ScopeMismatch { .. })));
+
+        let mut pending = PendingProof::new(&file).unwrap();
+        pending.import(&sign(&file, &alice, 1100).unwrap()).unwrap();
+        assert!(!pending.is_complete());
+        pending.import(&sign(&file, &bob, 1200).unwrap()).unwrap();
+
+        let proof = pending.into_proof().unwrap();
+        assert_eq!(proof.pact_id, "pact_offline");
+        assert_eq!(proof.signatures.len(), 2);
+        assert_eq!(proof.nonce, 5);
+
+        // Offline signatures validate like any other
+        let mut registry = crate::PactRegistry::new();
+        registry.register(pact);
+        registry.validate(&proof, "C.Evolution", &"ab".repeat(32), 0x03, 1300).unwrap();
+    }
+
+    #[test]
+    fn test_binding_checks() {
+        let (_, alice) = ubl_kernel::generate_keypair();
+        let (_, bob) = ubl_kernel::generate_keypair();
+        let (_, eve) = ubl_kernel::generate_keypair();
+        let file = export(&pact(&[&alice, &bob]), "C.Evolution", &"ab".repeat(32), 5, 0x03, 1000).unwrap();
+
+        // A request edited after export is refused by the offline signer
+        let mut tampered = file.clone();
+        tampered.request.link_hash = "cd".repeat(32);
+        assert!(matches!(sign(&tampered, &alice, 1100), Err(PactError::RequestHashMismatch { .. })));
+        assert!(matches!(sign(&file, &eve, 1100), Err(PactError::UnauthorizedSigner(_))));
+
+        let mut pending = PendingProof::new(&file).unwrap();
+        let good = sign(&file, &alice, 1100).unwrap();
+
+        // A signature for another request is refused on import
+        let other = export(&pact(&[&alice, &bob]), "C.Evolution", &"cd".repeat(32), 5, 0x03, 1000).unwrap();
+        let foreign = sign(&other, &alice, 1100).unwrap();
+        assert!(matches!(pending.import(&foreign), Err(PactError::BindingMismatch { field, .. }) if field == "request_hash"));
+
+        let mut forged = good.clone();
+        forged.signature = sign(&file, &bob, 1100).unwrap().signature;
+        assert!(matches!(pending.import(&forged), Err(PactError::InvalidSignature(_))));
+
+        pending.import(&good).unwrap();
+        assert!(matches!(pending.import(&good), Err(PactError::DuplicateSigner(_))));
+        assert!(matches!(pending.into_proof(), Err(PactError::InsufficientSignatures { got: 1, need: 2 })));
+    }
+
+    #[test]
+    fn test_weighted_request() {
+        let (_, chair) = ubl_kernel::generate_keypair();
+        let (_, alice) = ubl_kernel::generate_keypair();
+        let mut weighted = pact(&[&chair, &alice]);
+        weighted.weights = Some([(ubl_kernel::pubkey_from_signing_key(&chair), 2)].into_iter().collect());
+
+        // Weights are part of what signers approve
+        let plain = export(&pact(&[&chair, &alice]), "C.Evolution", &"ab".repeat(32), 5, 0x03, 1000).unwrap();
+        assert!(serde_json::to_value(&plain.request).unwrap().get("weights").is_none());
+        let file = export(&weighted, "C.Evolution", &"ab".repeat(32), 5, 0x03, 1000).unwrap();
+        assert_ne!(file.request_hash, plain.request_hash);
+
+        let mut pending = PendingProof::new(&file).unwrap();
+        pending.import(&sign(&file, &chair, 1100).unwrap()).unwrap();
+        assert_eq!(pending.collected(), 2);
+        let proof = pending.into_proof().unwrap();
+
+        let mut registry = crate::PactRegistry::new();
+        registry.register(weighted);
+        registry.validate(&proof, "C.Evolution", &"ab".repeat(32), 0x03, 1300).unwrap();
+    }
+
+    #[test]
+    fn test_grouped_request() {
+        let (_, admin) = ubl_kernel::generate_keypair();
+        let (_, auditor) = ubl_kernel::generate_keypair();
+        let (_, other) = ubl_kernel::generate_keypair();
+        let mut grouped = pact(&[&admin, &auditor, &other]);
+        grouped.groups = vec![SignerGroup {
+            name: "auditors".to_string(),
+            members: [ubl_kernel::pubkey_from_signing_key(&auditor)].into_iter().collect(),
+            threshold: 1,
+        }];
+        let file = export(&grouped, "C.Evolution", &"ab".repeat(32), 5, 0x03, 1000).unwrap();
+        assert_eq!(file.request.groups, grouped.groups);
+
+        let mut pending = PendingProof::new(&file).unwrap();
+        pending.import(&sign(&file, &admin, 1100).unwrap()).unwrap();
+        pending.import(&sign(&file, &other, 1100).unwrap()).unwrap();
+        assert_eq!(pending.collected(), 2);
+        assert!(!pending.is_complete());
+        pending.import(&sign(&file, &auditor, 1100).unwrap()).unwrap();
+        let proof = pending.into_proof().unwrap();
+
+        let mut registry = crate::PactRegistry::new();
+        registry.register(grouped);
+        registry.validate(&proof, "C.Evolution", &"ab".repeat(32), 0x03, 1300).unwrap();
+    }
+}
@@ -0,0 +1,122 @@
+//! PKCS#11 signer (feature `pkcs11`)
+//!
+//! [`Pkcs11Signer`] signs with an Ed25519 key held in an HSM or hardware
+//! token (YubiHSM, Nitrokey, SoftHSM for tests…) through the vendor's
+//! PKCS#11 module, with `CKM_EDDSA`. The secret never leaves the token; the
+//! signer only ever sees signatures, so it is
+//! [`hardware_backed`](PactSigner::hardware_backed).
+//!
+//! The key pair is found by `CKA_LABEL`: the private key signs and the
+//! public key's `CKA_EC_POINT` gives [`PactSigner::pubkey`]. The token is
+//! found by its label, and the session logs in as the user with the PIN.
+
+use std::path::Path;
+
+use cryptoki::context::{CInitializeArgs, Pkcs11};
+use cryptoki::mechanism::Mechanism;
+use cryptoki::object::{Attribute, AttributeType, KeyType, ObjectClass, ObjectHandle};
+use cryptoki::session::{Session, UserType};
+use cryptoki::types::AuthPin;
+
+use crate::signer::PactSigner;
+use crate::{PactError, Result};
+
+fn failed(e: impl std::fmt::Display) -> PactError {
+    PactError::SignerFailed(e.to_string())
+}
+
+/// Ed25519 key on a PKCS#11 token
+pub struct Pkcs11Signer {
+    session: Session,
+    key: ObjectHandle,
+    pubkey: String,
+}
+
+impl Pkcs11Signer {
+    /// Log in to the token labelled `token_label` through the PKCS#11
+    /// `module` (a shared library) and find the key pair labelled
+    /// `key_label`
+    pub fn open(module: impl AsRef<Path>, token_label: &str, key_label: &str, pin: &str) -> Result<Self> {
+        let pkcs11 = Pkcs11::new(module.as_ref()).map_err(failed)?;
+        pkcs11.initialize(CInitializeArgs::OsThreads).map_err(failed)?;
+        let slot = pkcs11
+            .get_slots_with_token()
+            .map_err(failed)?
+            .into_iter()
+            .find(|slot| pkcs11.get_token_info(*slot).is_ok_and(|info| info.label() == token_label))
+            .ok_or_else(|| failed(format!("no token labelled {}", token_label)))?;
+        let session = pkcs11.open_ro_session(slot).map_err(failed)?;
+        session.login(UserType::User, Some(&AuthPin::new(pin.to_string()))).map_err(failed)?;
+
+        let find = |class: ObjectClass, kind: &str| {
+            let template = [
+                Attribute::Class(class),
+                Attribute::KeyType(KeyType::EC_EDWARDS),
+                Attribute::Label(key_label.as_bytes().to_vec()),
+            ];
+            match session.find_objects(&template).map_err(failed)?.as_slice() {
+                [handle] => Ok(*handle),
+                [] => Err(failed(format!("no Ed25519 {} key labelled {}", kind, key_label))),
+                _ => Err(failed(format!("several Ed25519 {} keys labelled {}", kind, key_label))),
+            }
+        };
+        let key = find(ObjectClass::PRIVATE_KEY, "private")?;
+        let public = find(ObjectClass::PUBLIC_KEY, "public")?;
+        let point = session
+            .get_attributes(public, &[AttributeType::EcPoint])
+            .map_err(failed)?
+            .into_iter()
+            .find_map(|attribute| match attribute {
+                Attribute::EcPoint(point) => Some(point),
+                _ => None,
+            })
+            .ok_or_else(|| failed(format!("public key {} has no CKA_EC_POINT", key_label)))?;
+        let pubkey = ec_point_key(&point).ok_or_else(|| failed(format!("{} is not an Ed25519 key", key_label)))?;
+        Ok(Pkcs11Signer {
+            session,
+            key,
+            pubkey: hex::encode(pubkey),
+        })
+    }
+}
+
+/// Ed25519 public key from `CKA_EC_POINT`: DER OCTET STRING of the 32
+/// bytes, as PKCS#11 3.0 specifies, or the bare 32 bytes some tokens return
+fn ec_point_key(point: &[u8]) -> Option<[u8; 32]> {
+    match point {
+        [0x04, 0x20, key @ ..] if key.len() == 32 => key.try_into().ok(),
+        key => key.try_into().ok(),
+    }
+}
+
+impl PactSigner for Pkcs11Signer {
+    fn pubkey(&self) -> String {
+        self.pubkey.clone()
+    }
+
+    fn hardware_backed(&self) -> bool {
+        true
+    }
+
+    fn sign(&self, message: &[u8]) -> Result<String> {
+        let signature = self.session.sign(&Mechanism::Eddsa, self.key, message).map_err(failed)?;
+        if signature.len() != 64 {
+            return Err(failed(format!("token returned a {}-byte signature", signature.len())));
+        }
+        Ok(hex::encode(signature))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ec_point_key() {
+        let mut der = vec![0x04, 0x20];
+        der.extend_from_slice(&[9; 32]);
+        assert_eq!(ec_point_key(&der), Some([9; 32]));
+        assert_eq!(ec_point_key(&[9; 32]), Some([9; 32]));
+        assert_eq!(ec_point_key(&der[..20]), None);
+    }
+}
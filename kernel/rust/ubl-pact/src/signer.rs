@@ -0,0 +1,113 @@
+//! Pact signers
+//!
+//! A [`PactSigner`] holds one signer's key wherever it lives and produces
+//! its [`PactSignature`]s: an [`ed25519_dalek::SigningKey`] in memory, or a
+//! key in an HSM or hardware token ([`crate::pkcs11`], feature `pkcs11`).
+//! [`sign_link`] signs a link under a pact and refuses pacts at or above
+//! [`HARDWARE_RISK`] unless the signer is hardware-backed, so L4/L5
+//! authority never comes from a key that could have been copied.
+//!
+//! The rule binds signers, not verifiers: a signature looks the same
+//! whoever produced it. Validation ([`crate::PactRegistry::validate`]) is
+//! unchanged.
+
+use ed25519_dalek::SigningKey;
+
+use crate::{Pact, PactError, PactProof, PactSignature, Result, RiskLevel, SignatureScheme};
+
+/// Lowest risk level whose signatures must come from hardware
+pub const HARDWARE_RISK: RiskLevel = RiskLevel::L4;
+
+/// Source of one signer's Ed25519 signatures
+pub trait PactSigner {
+    /// Public key the signer signs with (hex, lowercase)
+    fn pubkey(&self) -> String;
+
+    /// Whether the secret key never leaves hardware (HSM, token)
+    fn hardware_backed(&self) -> bool {
+        false
+    }
+
+    /// Signature over `message` (hex)
+    fn sign(&self, message: &[u8]) -> Result<String>;
+}
+
+impl PactSigner for SigningKey {
+    fn pubkey(&self) -> String {
+        ubl_kernel::pubkey_from_signing_key(self)
+    }
+
+    fn sign(&self, message: &[u8]) -> Result<String> {
+        Ok(ubl_kernel::sign(self, message))
+    }
+}
+
+/// `signer`'s signature over [`PactProof::signing_bytes`]`(link_hash,
+/// pact_id, nonce)` for a proof under `pact`. Fails with
+/// [`PactError::HardwareSignerRequired`] for a pact at or above
+/// [`HARDWARE_RISK`] and a signer that is not hardware-backed.
+pub fn sign_link(signer: &dyn PactSigner, pact: &Pact, link_hash: &str, nonce: u64) -> Result<PactSignature> {
+    if pact.signature_scheme != SignatureScheme::Ed25519 {
+        return Err(PactError::SchemeMismatch {
+            pact: pact.signature_scheme,
+            proof: SignatureScheme::Ed25519,
+        });
+    }
+    if pact.risk_level >= HARDWARE_RISK && !signer.hardware_backed() {
+        return Err(PactError::HardwareSignerRequired(pact.risk_level));
+    }
+    let message = PactProof::signing_bytes(link_hash, &pact.pact_id, nonce);
+    Ok(PactSignature {
+        pubkey: signer.pubkey(),
+        signature: signer.sign(&message)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::templates;
+    use crate::TimeWindow;
+
+    /// In-memory key claiming to be hardware, as a token would
+    struct Token(SigningKey);
+
+    impl PactSigner for Token {
+        fn pubkey(&self) -> String {
+            self.0.pubkey()
+        }
+
+        fn hardware_backed(&self) -> bool {
+            true
+        }
+
+        fn sign(&self, message: &[u8]) -> Result<String> {
+            PactSigner::sign(&self.0, message)
+        }
+    }
+
+    fn pact(key: &SigningKey, risk_level: RiskLevel) -> Pact {
+        let pubkey = key.pubkey();
+        let window = TimeWindow {
+            not_before: 0,
+            not_after: 1000,
+        };
+        templates::two_of_three("pact_vault", "C.Vault", [&pubkey, "aa", "bb"], window, risk_level)
+    }
+
+    #[test]
+    fn test_high_risk_needs_hardware() {
+        let key = SigningKey::from_bytes(&[5; 32]);
+        let l3 = sign_link(&key, &pact(&key, RiskLevel::L3), "0xABC", 1).unwrap();
+        let message = PactProof::signing_bytes("0xabc", "pact_vault", 1);
+        assert!(ubl_kernel::verify(&l3.pubkey, &message, &l3.signature).is_ok());
+
+        let l4 = pact(&key, RiskLevel::L4);
+        assert_eq!(
+            sign_link(&key, &l4, "0xabc", 1).unwrap_err(),
+            PactError::HardwareSignerRequired(RiskLevel::L4)
+        );
+        let token = Token(key);
+        assert_eq!(sign_link(&token, &l4, "0xabc", 1).unwrap().signature, l3.signature);
+    }
+}
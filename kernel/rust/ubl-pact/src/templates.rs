@@ -0,0 +1,222 @@
+//! Pact templates
+//!
+//! Constructors for the governance setups most containers need, so the
+//! quorum structure comes out right without writing a [`Pact`] by hand:
+//!
+//! | Template           | Quorum                         | Besides                                 |
+//! |--------------------|--------------------------------|-----------------------------------------|
+//! | [`two_of_three`]   | any 2 of 3 keys                |                                         |
+//! | [`board_quorum`]   | a strict majority of the board | veto keys, e.g. a compliance officer's  |
+//! | [`emergency`]      | `threshold` of the responders  | `duration` seconds from `now`, one use  |
+//!
+//! Each pact is scoped to one container and signed with Ed25519 keys. The
+//! result is a plain [`Pact`]: change any field before registering it, e.g.
+//! `scope` and `namespace` to cover a namespace instead.
+
+use std::collections::BTreeSet;
+
+use crate::{Pact, PactScope, RiskLevel, TimeWindow};
+
+/// Pact with the fields every template shares; tests in this crate start
+/// from it too
+pub(crate) fn base(
+    pact_id: &str,
+    container_id: &str,
+    signers: &[&str],
+    threshold: usize,
+    window: TimeWindow,
+    risk_level: RiskLevel,
+) -> Pact {
+    Pact {
+        pact_id: pact_id.to_string(),
+        version: 1,
+        scope: PactScope::Container,
+        threshold,
+        signers: signers.iter().map(|s| s.to_ascii_lowercase()).collect(),
+        weights: None,
+        groups: Vec::new(),
+        veto_signers: BTreeSet::new(),
+        window,
+        risk_level,
+        container_id: Some(container_id.to_string()),
+        namespace: None,
+        predecessor: None,
+        max_uses: None,
+        frost_group_key: None,
+        signature_scheme: Default::default(),
+        risk_thresholds: None,
+        parent: None,
+        issues_pacts: false,
+        emergency: false,
+    }
+}
+
+/// 2-of-3 multisig over `container_id`: any two of the three keys sign
+pub fn two_of_three(
+    pact_id: &str,
+    container_id: &str,
+    signers: [&str; 3],
+    window: TimeWindow,
+    risk_level: RiskLevel,
+) -> Pact {
+    base(pact_id, container_id, &signers, 2, window, risk_level)
+}
+
+/// Board quorum over `container_id`: a strict majority of `board` signs,
+/// and any of `veto` can stop the pact or a link ([`crate::PactVeto`])
+/// however many approved
+///
+/// # Panics
+/// If `board` is empty or names a key twice.
+pub fn board_quorum(
+    pact_id: &str,
+    container_id: &str,
+    board: &[&str],
+    veto: &[&str],
+    window: TimeWindow,
+    risk_level: RiskLevel,
+) -> Pact {
+    let mut pact = base(pact_id, container_id, board, board.len() / 2 + 1, window, risk_level);
+    assert!(
+        !board.is_empty() && pact.signers.len() == board.len(),
+        "a board quorum needs distinct board members"
+    );
+    pact.veto_signers = veto.iter().map(|s| s.to_ascii_lowercase()).collect();
+    pact
+}
+
+/// Time-boxed emergency pact over `container_id`: `threshold` of
+/// `signers`, valid for `duration` seconds from `now` and for a single
+/// proof ([`Pact::max_uses`]), at the highest risk level
+///
+/// # Panics
+/// If `threshold` is 0 or more than the distinct `signers`.
+pub fn emergency(
+    pact_id: &str,
+    container_id: &str,
+    signers: &[&str],
+    threshold: usize,
+    now: i64,
+    duration: i64,
+) -> Pact {
+    let window = TimeWindow {
+        not_before: now,
+        not_after: now.saturating_add(duration),
+    };
+    let mut pact = base(pact_id, container_id, signers, threshold, window, RiskLevel::L5);
+    assert!(
+        threshold > 0 && threshold <= pact.signers.len(),
+        "threshold {} is not reachable with {} signers",
+        threshold,
+        pact.signers.len()
+    );
+    pact.max_uses = Some(1);
+    pact
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{PactError, PactProof, PactRegistry, PactSignature};
+
+    const LINK_HASH: &str = "0xabc";
+
+    fn key(name: &str) -> ed25519_dalek::SigningKey {
+        ed25519_dalek::SigningKey::from_bytes(blake3::hash(name.as_bytes()).as_bytes())
+    }
+
+    fn pubkey(name: &str) -> String {
+        ubl_kernel::pubkey_from_signing_key(&key(name))
+    }
+
+    fn proof(pact_id: &str, signers: &[&str]) -> PactProof {
+        let message = PactProof::signing_bytes(LINK_HASH, pact_id, 0);
+        PactProof {
+            pact_id: pact_id.to_string(),
+            signatures: signers
+                .iter()
+                .map(|s| PactSignature {
+                    pubkey: pubkey(s),
+                    signature: ubl_kernel::sign(&key(s), &message),
+                })
+                .collect(),
+            nonce: 0,
+            delegations: Vec::new(),
+            frost_signature: None,
+            bls_aggregate: None,
+        }
+    }
+
+    fn window() -> TimeWindow {
+        TimeWindow {
+            not_before: 0,
+            not_after: 1000,
+        }
+    }
+
+    #[test]
+    fn test_two_of_three() {
+        let keys = [pubkey("alice"), pubkey("bob"), pubkey("carol")];
+        let mut registry = PactRegistry::new();
+        registry.register(two_of_three(
+            "pact_multisig",
+            "C.Test",
+            [&keys[0], &keys[1], &keys[2]],
+            window(),
+            RiskLevel::L3,
+        ));
+        let validate =
+            |signers: &[&str]| registry.validate(&proof("pact_multisig", signers), "C.Test", LINK_HASH, 0x01, 500);
+        assert!(validate(&["alice", "carol"]).is_ok());
+        assert!(matches!(validate(&["bob"]), Err(PactError::InsufficientSignatures { got: 1, need: 2 })));
+    }
+
+    #[test]
+    fn test_board_quorum() {
+        let board: Vec<String> = ["a", "b", "c", "d"].iter().map(|s| pubkey(s)).collect();
+        let board: Vec<&str> = board.iter().map(String::as_str).collect();
+        let officer = pubkey("officer");
+        let pact = board_quorum("pact_board", "C.Test", &board, &[&officer], window(), RiskLevel::L4);
+        assert_eq!((pact.threshold, pact.veto_signers.len()), (3, 1));
+        assert!(!pact.signers.contains(&officer));
+
+        let mut registry = PactRegistry::new();
+        registry.register(pact);
+        let validate =
+            |signers: &[&str]| registry.validate(&proof("pact_board", signers), "C.Test", LINK_HASH, 0x02, 500);
+        assert!(validate(&["a", "b", "c"]).is_ok());
+        assert!(validate(&["a", "b"]).is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "distinct board members")]
+    fn test_board_quorum_needs_a_board() {
+        board_quorum("pact_board", "C.Test", &[], &[], window(), RiskLevel::L4);
+    }
+
+    #[test]
+    fn test_emergency() {
+        let keys = [pubkey("alice"), pubkey("bob")];
+        let pact = emergency("pact_emergency", "C.Test", &[&keys[0], &keys[1]], 2, 100, 3600);
+        assert_eq!((pact.window.not_before, pact.window.not_after), (100, 3700));
+        assert_eq!((pact.risk_level, pact.max_uses), (RiskLevel::L5, Some(1)));
+
+        let mut registry = PactRegistry::new();
+        registry.register(pact);
+        let emergency = proof("pact_emergency", &["alice", "bob"]);
+        assert!(registry.validate(&emergency, "C.Test", LINK_HASH, 0x03, 200).is_ok());
+        assert_eq!(
+            registry.validate(&emergency, "C.Test", LINK_HASH, 0x03, 4000),
+            Err(PactError::PactExpired)
+        );
+        registry.consume(&emergency, LINK_HASH).unwrap();
+        let again = PactProof {
+            nonce: 1,
+            ..proof("pact_emergency", &["alice", "bob"])
+        };
+        assert!(matches!(
+            registry.validate(&again, "C.Test", LINK_HASH, 0x03, 200),
+            Err(PactError::UsesExhausted { used: 1, max: 1 })
+        ));
+    }
+}
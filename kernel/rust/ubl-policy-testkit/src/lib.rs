@@ -0,0 +1,457 @@
+//! # UBL Policy Testkit
+//!
+//! Table-driven simulation of policies before deployment.
+//!
+//! A [`Table`] lists intents and the [`Expectation`] each one must meet. The
+//! table runs either against one policy ([`PolicyVM::evaluate`]) or against
+//! every policy attached to a container ([`PolicyVM::evaluate_all`]).
+//! [`run`] returns a [`Report`] with per-case results. Its golden rendering
+//! is canonical JSON, one decision per case, so a policy change shows up in
+//! review as a diff of the golden file.
+//!
+//! ```json
+//! {
+//!   "policy_id": "default",
+//!   "timestamp": 1000,
+//!   "cases": [
+//!     { "name": "small transfer", "intent": {"type": "transfer", "amount": 50},
+//!       "expect": {"decision": "allow", "intent_class": 1, "required_pact": null} },
+//!     { "name": "unknown", "intent": {"type": "burn"},
+//!       "expect": {"decision": "deny", "deny_code": "unknown_intent"} }
+//!   ]
+//! }
+//! ```
+
+#![deny(unsafe_code)]
+#![warn(missing_docs)]
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use thiserror::Error;
+use ubl_policy_vm::{deny, Constraint, DenyCode, EvaluationContext, PolicyVM, TranslationDecision};
+
+/// Set to `1` to rewrite golden files instead of comparing against them
+pub const UPDATE_GOLDEN_ENV: &str = "UBL_UPDATE_GOLDEN";
+
+/// Errors from loading tables or checking golden files
+#[derive(Error, Debug)]
+pub enum TestkitError {
+    /// Table or golden file is not valid JSON for its shape
+    #[error("Parse error: {0}")]
+    Parse(#[from] serde_json::Error),
+
+    /// Table or golden file could not be read or written
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// Table names neither a policy nor a container
+    #[error("Table must set policy_id or container_id")]
+    NoTarget,
+
+    /// Decisions differ from the golden file
+    #[error("Golden mismatch in {path}:\n{diff}")]
+    GoldenMismatch {
+        /// Golden file compared against
+        path: String,
+        /// One line per differing case
+        diff: String,
+    },
+}
+
+/// Result type for testkit operations
+pub type Result<T> = std::result::Result<T, TestkitError>;
+
+/// Decision kind an expectation asserts on
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DecisionKind {
+    /// `TranslationDecision::Allow`
+    Allow,
+    /// `TranslationDecision::Deny`
+    Deny,
+    /// Evaluation returned an error (e.g. no active version)
+    Error,
+}
+
+/// What a case's decision must look like; unset fields are not checked
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Expectation {
+    /// Allow, deny or error
+    #[serde(default)]
+    pub decision: Option<DecisionKind>,
+    /// Intent class of an Allow
+    #[serde(default)]
+    pub intent_class: Option<u8>,
+    /// Pact of an Allow; `null` asserts that no pact is required
+    #[serde(default, deserialize_with = "present")]
+    pub required_pact: Option<Option<String>>,
+    /// Exact constraint snapshot of an Allow
+    #[serde(default)]
+    pub constraints: Option<Vec<Constraint>>,
+    /// Code of a Deny
+    #[serde(default)]
+    pub deny_code: Option<DenyCode>,
+    /// Substring of the Deny reason (`code: detail`) or error message
+    #[serde(default)]
+    pub reason_contains: Option<String>,
+}
+
+/// Distinguishes an absent field (`None`) from an explicit `null` (`Some(None)`)
+fn present<'de, D>(d: D) -> std::result::Result<Option<Option<String>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Option::<String>::deserialize(d).map(Some)
+}
+
+/// One intent to simulate
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Case {
+    /// Case name, unique within the table (the golden key)
+    pub name: String,
+    /// Intent payload
+    pub intent: Value,
+    /// Actor; defaults to the table's
+    #[serde(default)]
+    pub actor: Option<String>,
+    /// Evaluation timestamp; defaults to the table's
+    #[serde(default)]
+    pub timestamp: Option<i64>,
+    /// Container state, if the policy reads it
+    #[serde(default)]
+    pub state: Option<Value>,
+    /// Assertions on the decision
+    #[serde(default)]
+    pub expect: Expectation,
+}
+
+/// A table of cases against one policy or one container's policies
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Table {
+    /// Policy to evaluate; when unset, `container_id`'s attached policies are composed
+    #[serde(default)]
+    pub policy_id: Option<String>,
+    /// Container the intents target
+    #[serde(default)]
+    pub container_id: Option<String>,
+    /// Default actor
+    #[serde(default = "default_actor")]
+    pub actor: String,
+    /// Default evaluation timestamp
+    #[serde(default)]
+    pub timestamp: i64,
+    /// Spec version the container is committed under
+    #[serde(default)]
+    pub spec: ubl_policy_vm::SpecVersion,
+    /// Cases, run in order
+    pub cases: Vec<Case>,
+}
+
+fn default_actor() -> String {
+    "testkit".to_string()
+}
+
+impl Table {
+    /// Parse a table from JSON
+    pub fn from_json(json: &str) -> Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Load a table from a JSON file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        Self::from_json(&std::fs::read_to_string(path)?)
+    }
+}
+
+/// Outcome of one case
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CaseResult {
+    /// Case name
+    pub name: String,
+    /// Decision, or the evaluation error message
+    pub decision: std::result::Result<TranslationDecision, String>,
+    /// Unmet expectations (empty when the case passed)
+    pub failures: Vec<String>,
+}
+
+impl CaseResult {
+    /// Whether every expectation held
+    pub fn passed(&self) -> bool {
+        self.failures.is_empty()
+    }
+}
+
+/// Results of a table run
+#[derive(Debug, Clone)]
+pub struct Report {
+    /// Results in table order
+    pub cases: Vec<CaseResult>,
+}
+
+/// Run every case of `table` against `vm`
+pub fn run(vm: &PolicyVM, table: &Table) -> Result<Report> {
+    if table.policy_id.is_none() && table.container_id.is_none() {
+        return Err(TestkitError::NoTarget);
+    }
+    let container_id = table
+        .container_id
+        .clone()
+        .or_else(|| table.policy_id.clone())
+        .unwrap_or_default();
+
+    let cases = table
+        .cases
+        .iter()
+        .map(|case| {
+            let context = EvaluationContext {
+                container_id: container_id.clone(),
+                actor: case.actor.clone().unwrap_or_else(|| table.actor.clone()),
+                intent: case.intent.clone(),
+                state: case.state.clone(),
+                timestamp: case.timestamp.unwrap_or(table.timestamp),
+                spec: table.spec,
+            };
+            let decision = match &table.policy_id {
+                Some(policy_id) => vm.evaluate(policy_id, &context),
+                None => vm.evaluate_all(&container_id, &context),
+            }
+            .map_err(|e| e.to_string());
+            CaseResult {
+                name: case.name.clone(),
+                failures: check(&case.expect, &decision),
+                decision,
+            }
+        })
+        .collect();
+    Ok(Report { cases })
+}
+
+/// Unmet expectations for one decision
+pub fn check(expect: &Expectation, decision: &std::result::Result<TranslationDecision, String>) -> Vec<String> {
+    let mut failures = Vec::new();
+    let kind = match decision {
+        Ok(TranslationDecision::Allow { .. }) => DecisionKind::Allow,
+        Ok(TranslationDecision::Deny { .. }) => DecisionKind::Deny,
+        Err(_) => DecisionKind::Error,
+    };
+    if let Some(want) = expect.decision {
+        if want != kind {
+            failures.push(format!("decision: expected {:?}, got {:?}", want, kind));
+        }
+    }
+
+    let (code, reason) = match decision {
+        Ok(TranslationDecision::Deny { code, detail }) => (Some(*code), deny::describe(*code, detail.as_deref())),
+        Ok(TranslationDecision::Allow { .. }) => (None, String::new()),
+        Err(e) => (None, e.clone()),
+    };
+    match decision {
+        Ok(TranslationDecision::Allow {
+            intent_class,
+            required_pact,
+            constraints,
+        }) => {
+            if let Some(want) = expect.intent_class {
+                if want != *intent_class {
+                    failures.push(format!("intent_class: expected 0x{:02x}, got 0x{:02x}", want, intent_class));
+                }
+            }
+            if let Some(want) = &expect.required_pact {
+                if want != required_pact {
+                    failures.push(format!("required_pact: expected {:?}, got {:?}", want, required_pact));
+                }
+            }
+            if let Some(want) = &expect.constraints {
+                if want != constraints {
+                    failures.push(format!("constraints: expected {:?}, got {:?}", want, constraints));
+                }
+            }
+            if expect.reason_contains.is_some() {
+                failures.push("reason_contains: decision has no reason".to_string());
+            }
+            if expect.deny_code.is_some() {
+                failures.push("deny_code: decision is not a Deny".to_string());
+            }
+        }
+        Ok(TranslationDecision::Deny { .. }) | Err(_) => {
+            if let Some(want) = expect.deny_code {
+                if Some(want) != code {
+                    failures.push(format!("deny_code: expected {}, got {:?}", want, code));
+                }
+            }
+            if let Some(want) = &expect.reason_contains {
+                if !reason.contains(want.as_str()) {
+                    failures.push(format!("reason: expected to contain {:?}, got {:?}", want, reason));
+                }
+            }
+            if expect.intent_class.is_some() || expect.required_pact.is_some() || expect.constraints.is_some() {
+                failures.push(format!("decision has no Allow fields: {}", reason));
+            }
+        }
+    }
+    failures
+}
+
+impl Report {
+    /// Cases with unmet expectations
+    pub fn failed(&self) -> Vec<&CaseResult> {
+        self.cases.iter().filter(|c| !c.passed()).collect()
+    }
+
+    /// Human-readable summary, one line per case
+    pub fn summary(&self) -> String {
+        let mut out = String::new();
+        for case in &self.cases {
+            if case.passed() {
+                out.push_str(&format!("ok    {}\n", case.name));
+            } else {
+                out.push_str(&format!("FAIL  {}\n", case.name));
+                for f in &case.failures {
+                    out.push_str(&format!("        {}\n", f));
+                }
+            }
+        }
+        let failed = self.failed().len();
+        out.push_str(&format!("{} cases, {} passed, {} failed\n", self.cases.len(), self.cases.len() - failed, failed));
+        out
+    }
+
+    /// Panic with the summary if any case failed (for use in `#[test]`s)
+    pub fn assert_passed(&self) {
+        if !self.failed().is_empty() {
+            panic!("policy table failed:\n{}", self.summary());
+        }
+    }
+
+    /// Golden rendering: case name to decision, canonical JSON
+    pub fn golden(&self) -> Value {
+        let map: serde_json::Map<String, Value> = self
+            .cases
+            .iter()
+            .map(|c| {
+                let decision = match &c.decision {
+                    Ok(d) => serde_json::to_value(d).expect("decision serializes"),
+                    Err(e) => serde_json::json!({ "Error": e }),
+                };
+                (c.name.clone(), decision)
+            })
+            .collect();
+        Value::Object(map)
+    }
+
+    /// Compare against a golden file, or rewrite it when `UBL_UPDATE_GOLDEN=1`
+    pub fn check_golden(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let current = self.golden();
+        if std::env::var(UPDATE_GOLDEN_ENV).as_deref() == Ok("1") {
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir)?;
+            }
+            std::fs::write(path, format!("{}\n", serde_json::to_string_pretty(&current)?))?;
+            return Ok(());
+        }
+
+        let stored: Value = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+        let diff = golden_diff(&stored, &current);
+        if diff.is_empty() {
+            Ok(())
+        } else {
+            Err(TestkitError::GoldenMismatch {
+                path: path.display().to_string(),
+                diff: diff.join("\n"),
+            })
+        }
+    }
+}
+
+/// Per-case differences between two golden renderings
+fn golden_diff(stored: &Value, current: &Value) -> Vec<String> {
+    let empty = serde_json::Map::new();
+    let stored = stored.as_object().unwrap_or(&empty);
+    let current = current.as_object().unwrap_or(&empty);
+    let mut names: Vec<&String> = stored.keys().chain(current.keys()).collect();
+    names.sort();
+    names.dedup();
+
+    names
+        .into_iter()
+        .filter_map(|name| match (stored.get(name), current.get(name)) {
+            (Some(a), Some(b)) if a == b => None,
+            (Some(a), Some(b)) => Some(format!("~ {}: {} -> {}", name, a, b)),
+            (Some(a), None) => Some(format!("- {}: {}", name, a)),
+            (None, Some(b)) => Some(format!("+ {}: {}", name, b)),
+            (None, None) => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ubl_policy_vm::Policy;
+
+    fn vm() -> PolicyVM {
+        let mut vm = PolicyVM::new();
+        vm.register(Policy {
+            policy_id: "default".to_string(),
+            version: "1".to_string(),
+            bytecode_hash: "h".to_string(),
+            bytecode: vec![],
+            description: String::new(),
+            active_from: 0,
+        })
+        .unwrap();
+        vm
+    }
+
+    const TABLE: &str = r#"{
+        "policy_id": "default",
+        "timestamp": 1000,
+        "cases": [
+            {"name": "observe", "intent": {"type": "observe"},
+             "expect": {"decision": "allow", "intent_class": 0, "required_pact": null}},
+            {"name": "large transfer", "intent": {"type": "transfer", "amount": 20000},
+             "expect": {"required_pact": "high_value_transfer",
+                        "constraints": [{"kind": "max_amount", "value": "10000"}]}},
+            {"name": "unknown", "intent": {"type": "burn"},
+             "expect": {"decision": "deny", "deny_code": "unknown_intent", "reason_contains": "Unknown intent"}},
+            {"name": "before policy", "intent": {"type": "observe"}, "timestamp": -1,
+             "expect": {"decision": "error"}}
+        ]
+    }"#;
+
+    #[test]
+    fn test_table_passes() {
+        let report = run(&vm(), &Table::from_json(TABLE).unwrap()).unwrap();
+        report.assert_passed();
+        assert_eq!(report.cases.len(), 4);
+    }
+
+    #[test]
+    fn test_failures_are_reported() {
+        let mut table = Table::from_json(TABLE).unwrap();
+        table.cases[0].expect.intent_class = Some(0x01);
+        table.cases[2].expect.decision = Some(DecisionKind::Allow);
+
+        let report = run(&vm(), &table).unwrap();
+        let failed: Vec<_> = report.failed().iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(failed, vec!["observe", "unknown"]);
+        assert!(report.summary().contains("4 cases, 2 passed, 2 failed"));
+    }
+
+    #[test]
+    fn test_golden_diff() {
+        let report = run(&vm(), &Table::from_json(TABLE).unwrap()).unwrap();
+        let mut stored = report.golden();
+        assert!(golden_diff(&stored, &report.golden()).is_empty());
+
+        stored["observe"] = serde_json::json!({"Deny": {"reason": "closed"}});
+        stored.as_object_mut().unwrap().remove("unknown");
+        let diff = golden_diff(&stored, &report.golden());
+        assert_eq!(diff.len(), 2);
+        assert!(diff[0].starts_with("~ observe"));
+        assert!(diff[1].starts_with("+ unknown"));
+    }
+}
@@ -0,0 +1,19 @@
+#[test]
+fn ubl_policy_testkit_smoke() {
+    let mut vm = ubl_policy_vm::PolicyVM::new();
+    vm.register(ubl_policy_vm::Policy {
+        policy_id: "default".to_string(),
+        version: "1".to_string(),
+        bytecode_hash: "smoke".to_string(),
+        bytecode: vec![],
+        description: String::new(),
+        active_from: 0,
+    })
+    .unwrap();
+
+    let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden");
+    let table = ubl_policy_testkit::Table::load(format!("{}/default.table.json", dir)).unwrap();
+    let report = ubl_policy_testkit::run(&vm, &table).unwrap();
+    report.assert_passed();
+    report.check_golden(format!("{}/default.golden.json", dir)).unwrap();
+}
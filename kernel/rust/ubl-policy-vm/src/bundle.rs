@@ -0,0 +1,211 @@
+//! Signed policy bundles (SPEC-UBL-POLICY v1.0 §4, L5 material)
+//!
+//! A [`PolicyBundle`] carries one policy version with its bytecode, the
+//! BLAKE3 bytecode hash, and Ed25519 signatures over
+//! [`PolicyBundle::signing_bytes`]. The signatures cover the manifest (id,
+//! version, hash, description, activation), so neither the bytecode nor the
+//! activation time can be swapped after signing.
+//!
+//! Once a VM has [`GovernanceKeys`], it only registers policies through
+//! [`crate::PolicyVM::register_bundle`]: at least `threshold` distinct
+//! governance keys must have signed the bundle.
+
+use std::collections::BTreeSet;
+
+use ed25519_dalek::SigningKey;
+use serde::{Deserialize, Serialize};
+
+use crate::{Policy, PolicyError, Result};
+
+/// Domain tag prefixed to the canonical manifest before signing
+pub const POLICY_DOMAIN: &[u8] = b"ubl:policy\n";
+
+/// One governance signature over a bundle
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct BundleSignature {
+    /// Signer public key (hex)
+    pub pubkey: String,
+    /// Ed25519 signature over [`PolicyBundle::signing_bytes`] (hex)
+    pub signature: String,
+}
+
+/// A policy version with its governance signatures
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PolicyBundle {
+    /// The policy; `bytecode` travels as `bytecode_hex`
+    #[serde(flatten)]
+    pub policy: Policy,
+    /// Policy bytecode, hex-encoded
+    pub bytecode_hex: String,
+    /// Governance signatures
+    #[serde(default)]
+    pub signatures: Vec<BundleSignature>,
+}
+
+impl PolicyBundle {
+    /// Bundle a policy, unsigned
+    pub fn new(policy: Policy) -> Self {
+        let bytecode_hex = hex::encode(&policy.bytecode);
+        Self {
+            policy,
+            bytecode_hex,
+            signatures: Vec::new(),
+        }
+    }
+
+    /// `POLICY_DOMAIN` followed by the canonical JSON manifest
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        let p = &self.policy;
+        let manifest = serde_json::json!({
+            "policy_id": p.policy_id,
+            "version": p.version,
+            "bytecode_hash": p.bytecode_hash,
+            "description": p.description,
+            "active_from": p.active_from,
+        });
+        let canonical = ubl_atom::canonicalize(&manifest).expect("manifest is finite JSON");
+        [POLICY_DOMAIN, canonical.as_slice()].concat()
+    }
+
+    /// Add a signature with `key`
+    pub fn sign(&mut self, key: &SigningKey) {
+        self.signatures.push(BundleSignature {
+            pubkey: ubl_kernel::pubkey_from_signing_key(key),
+            signature: ubl_kernel::sign(key, &self.signing_bytes()),
+        });
+    }
+
+    /// The policy with its bytecode decoded, after checking the bytecode hash
+    pub fn into_policy(self) -> Result<Policy> {
+        let bytecode = hex::decode(&self.bytecode_hex).map_err(|_| PolicyError::InvalidBytecode)?;
+        let policy = Policy { bytecode, ..self.policy };
+        policy.verify_bytecode()?;
+        Ok(policy)
+    }
+}
+
+/// Keys whose signatures admit a policy, and how many are needed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GovernanceKeys {
+    keys: BTreeSet<String>,
+    threshold: usize,
+}
+
+impl GovernanceKeys {
+    /// Governance set; `threshold` is clamped to at least 1
+    pub fn new<I, S>(keys: I, threshold: usize) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        Self {
+            keys: keys.into_iter().map(|k| k.as_ref().to_ascii_lowercase()).collect(),
+            threshold: threshold.max(1),
+        }
+    }
+
+    /// Configured keys (hex)
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.keys.iter().map(String::as_str)
+    }
+
+    /// Signatures required
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+
+    /// Check the bundle carries `threshold` valid signatures from distinct governance keys
+    ///
+    /// Signatures from other keys are ignored; a governance key whose
+    /// signature does not verify rejects the bundle outright.
+    pub fn verify(&self, bundle: &PolicyBundle) -> Result<()> {
+        self.verify_signatures(&bundle.policy.policy_id, &bundle.signing_bytes(), &bundle.signatures)
+    }
+
+    /// Threshold check of `signatures` over `message`, as in [`GovernanceKeys::verify`]
+    pub(crate) fn verify_signatures(&self, subject: &str, message: &[u8], signatures: &[BundleSignature]) -> Result<()> {
+        let mut signed: BTreeSet<String> = BTreeSet::new();
+        for sig in signatures {
+            let pubkey = sig.pubkey.to_ascii_lowercase();
+            if !self.keys.contains(&pubkey) {
+                continue;
+            }
+            ubl_kernel::verify(&pubkey, message, &sig.signature)
+                .map_err(|_| PolicyError::InvalidBundleSignature(pubkey.clone()))?;
+            signed.insert(pubkey);
+        }
+        if signed.len() < self.threshold {
+            return Err(PolicyError::InsufficientGovernanceSignatures {
+                policy_id: subject.to_string(),
+                got: signed.len(),
+                need: self.threshold,
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bytecode_hash;
+
+    fn bundle() -> PolicyBundle {
+        let bytecode = b"tdln".to_vec();
+        PolicyBundle::new(Policy {
+            policy_id: "signed".to_string(),
+            version: "1".to_string(),
+            bytecode_hash: bytecode_hash(&bytecode),
+            bytecode,
+            description: "signed policy".to_string(),
+            active_from: 100,
+        })
+    }
+
+    #[test]
+    fn test_verify_threshold() {
+        let (gov_a, key_a) = ubl_kernel::generate_keypair();
+        let (gov_b, key_b) = ubl_kernel::generate_keypair();
+        let (_, outsider) = ubl_kernel::generate_keypair();
+        let governance = GovernanceKeys::new([&gov_a, &gov_b], 2);
+
+        let mut b = bundle();
+        b.sign(&key_a);
+        b.sign(&outsider);
+        assert!(matches!(
+            governance.verify(&b),
+            Err(PolicyError::InsufficientGovernanceSignatures { got: 1, need: 2, .. })
+        ));
+
+        // The same key twice still counts once
+        b.sign(&key_a);
+        assert!(governance.verify(&b).is_err());
+
+        b.sign(&key_b);
+        assert!(governance.verify(&b).is_ok());
+    }
+
+    #[test]
+    fn test_signature_covers_manifest() {
+        let (gov, key) = ubl_kernel::generate_keypair();
+        let governance = GovernanceKeys::new([&gov], 1);
+        let mut b = bundle();
+        b.sign(&key);
+
+        // Re-dating a signed version invalidates the signature
+        b.policy.active_from = 0;
+        assert!(matches!(governance.verify(&b), Err(PolicyError::InvalidBundleSignature(_))));
+    }
+
+    #[test]
+    fn test_bundle_round_trip() {
+        let b = bundle();
+        let json = serde_json::to_string(&b).unwrap();
+        let back: PolicyBundle = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.into_policy().unwrap().bytecode, b"tdln".to_vec());
+
+        let mut tampered = b.clone();
+        tampered.bytecode_hex = hex::encode(b"evil");
+        assert!(matches!(tampered.into_policy(), Err(PolicyError::BytecodeHashMismatch { .. })));
+    }
+}
@@ -0,0 +1,142 @@
+//! Decision cache (opt-in, see [`crate::PolicyVM::enable_decision_cache`])
+//!
+//! Evaluation is deterministic, so a decision can be reused for an identical
+//! context. Entries are keyed by BLAKE3 over the policy id and the canonical
+//! JSON of the [`EvaluationContext`]; the context timestamp is part of the
+//! key, so version selection is covered. Any change to the policy set clears
+//! the cache. When full, the oldest entry is evicted.
+
+use std::collections::{HashMap, VecDeque};
+
+use serde::Serialize;
+
+use crate::{EvaluationContext, TranslationDecision};
+
+/// Counters of a decision cache
+#[derive(Debug, Clone, Copy, Default, Serialize, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Maximum number of entries
+    pub capacity: usize,
+    /// Entries held
+    pub entries: usize,
+    /// Lookups answered from the cache
+    pub hits: u64,
+    /// Lookups that had to evaluate
+    pub misses: u64,
+}
+
+/// Bounded map from context hash to decision
+#[derive(Debug)]
+pub struct DecisionCache {
+    entries: HashMap<[u8; 32], TranslationDecision>,
+    order: VecDeque<[u8; 32]>,
+    stats: CacheStats,
+}
+
+/// Cache key for evaluating `policy_id` in `context`; `None` if the context
+/// has no canonical form (e.g. non-finite numbers)
+pub fn key(policy_id: &str, context: &EvaluationContext) -> Option<[u8; 32]> {
+    let value = serde_json::to_value(context).ok()?;
+    let canonical = ubl_atom::canonicalize(&value).ok()?;
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(policy_id.as_bytes());
+    hasher.update(&[0]);
+    hasher.update(&canonical);
+    Some(*hasher.finalize().as_bytes())
+}
+
+impl DecisionCache {
+    /// Empty cache holding at most `capacity` decisions (at least 1)
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            entries: HashMap::with_capacity(capacity),
+            order: VecDeque::with_capacity(capacity),
+            stats: CacheStats {
+                capacity,
+                ..CacheStats::default()
+            },
+        }
+    }
+
+    /// Cached decision for `key`, counting the hit or miss
+    pub fn get(&mut self, key: &[u8; 32]) -> Option<TranslationDecision> {
+        let found = self.entries.get(key).cloned();
+        if found.is_some() {
+            self.stats.hits += 1;
+        } else {
+            self.stats.misses += 1;
+        }
+        found
+    }
+
+    /// Store a decision, evicting the oldest entry when full
+    pub fn insert(&mut self, key: [u8; 32], decision: TranslationDecision) {
+        if self.entries.insert(key, decision).is_some() {
+            return;
+        }
+        self.order.push_back(key);
+        if self.order.len() > self.stats.capacity {
+            if let Some(oldest) = self.order.pop_front() {
+                self.entries.remove(&oldest);
+            }
+        }
+    }
+
+    /// Drop every entry; counters are kept
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+    }
+
+    /// Current counters
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            entries: self.entries.len(),
+            ..self.stats
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DenyCode;
+
+    fn context(amount: i64) -> EvaluationContext {
+        EvaluationContext {
+            container_id: "C.Bank".to_string(),
+            actor: "alice".to_string(),
+            intent: serde_json::json!({ "type": "transfer", "amount": amount }),
+            state: None,
+            timestamp: 1,
+            spec: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_key_is_canonical() {
+        let mut reordered = context(5);
+        reordered.intent = serde_json::json!({ "amount": 5, "type": "transfer" });
+        assert_eq!(key("p", &context(5)), key("p", &reordered));
+        assert_ne!(key("p", &context(5)), key("q", &context(5)));
+        assert_ne!(key("p", &context(5)), key("p", &context(6)));
+    }
+
+    #[test]
+    fn test_bounded_eviction() {
+        let mut cache = DecisionCache::new(2);
+        let deny = |r: &str| TranslationDecision::deny(DenyCode::Other, r);
+        cache.insert([1; 32], deny("a"));
+        cache.insert([2; 32], deny("b"));
+        cache.insert([3; 32], deny("c"));
+
+        assert_eq!(cache.get(&[1; 32]), None);
+        assert_eq!(cache.get(&[3; 32]), Some(deny("c")));
+        let stats = cache.stats();
+        assert_eq!((stats.entries, stats.hits, stats.misses), (2, 1, 1));
+
+        cache.clear();
+        assert_eq!(cache.stats().entries, 0);
+    }
+}
@@ -0,0 +1,423 @@
+//! Cedar policies (feature `cedar`)
+//!
+//! [`CedarEngine`] is the [`ExternalEngine`] named [`ENGINE`]: an
+//! [`ExternalPolicy`] for it carries a Cedar policy set, evaluated by the
+//! embedded `cedar-policy` authorizer. Each evaluation is one request:
+//!
+//! | Cedar       | From the [`EvaluationContext`]                              |
+//! |-------------|-------------------------------------------------------------|
+//! | `principal` | `Ubl::Actor::"<actor>"`                                     |
+//! | `action`    | `Ubl::Action::"<intent.type>"` (`"unknown"` when absent)    |
+//! | `resource`  | `Ubl::Container::"<container_id>"`                          |
+//! | `context`   | `{ intent, timestamp, spec, state? }`                       |
+//!
+//! Cedar has no null and no floats: in `context`, nulls are dropped and
+//! numbers outside `i64` become strings. Keys starting with `__` are dropped
+//! so a payload cannot smuggle in entity or extension escapes. There are no
+//! entities beyond the request's, so policies match on identity and
+//! `context`, not on hierarchy.
+//!
+//! The decision is read from the determining policies' annotations:
+//!
+//! ```cedar
+//! @intent_class("0")
+//! permit(principal, action in [Ubl::Action::"observe", Ubl::Action::"read"], resource);
+//!
+//! @intent_class("1") @required_pact("high_value_transfer") @constraint_max_delta("50000")
+//! permit(principal, action == Ubl::Action::"transfer", resource)
+//! when { context.intent.amount > 10000 };
+//!
+//! @deny_code("actor_blocked")
+//! forbid(principal == Ubl::Actor::"mallory", action, resource);
+//! ```
+//!
+//! - Allow: the intent class of the permits that allowed (`@intent_class`,
+//!   required on every permit), their `@required_pact` and a constraint per
+//!   `@constraint_<kind>`. Permits naming different classes or pacts deny
+//!   with [`DenyCode::IntentClassConflict`] or [`DenyCode::PactConflict`],
+//!   as [`crate::compose`] does across policies.
+//! - Deny by a `forbid`: its `@deny_code` (a [`DenyCode`] wire name,
+//!   default `other`), the detail naming the policy (`@id` or its position).
+//! - Deny because nothing permits: [`DenyCode::NoDecision`].
+//!
+//! A policy that errors during evaluation (missing attribute, type
+//! mismatch, overflow) fails the evaluation, as a CEL rule does; Cedar
+//! itself would skip it. Policy sets are parsed once, kept by bytecode hash
+//! like compiled WASM modules ([`crate::module_cache`]).
+
+use std::collections::BTreeSet;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+
+use cedar_policy::{
+    Authorizer, Context, Decision, Effect, Entities, EntityId, EntityTypeName, EntityUid, Policy as CedarPolicy,
+    PolicySet, Request,
+};
+use serde_json::Value;
+
+use crate::external::{ExternalEngine, ExternalPolicy};
+use crate::module_cache::{ModuleCache, ModuleCacheStats};
+use crate::{Constraint, DenyCode, EvaluationContext, Policy, PolicyError, Result, TranslationDecision};
+
+/// Engine name in [`ExternalPolicy`] bytecode
+pub const ENGINE: &str = "cedar";
+
+/// Namespace of the request's entity types
+pub const NAMESPACE: &str = "Ubl";
+
+/// Annotation carrying a permit's intent class
+const INTENT_CLASS: &str = "intent_class";
+/// Annotation carrying a permit's required pact
+const REQUIRED_PACT: &str = "required_pact";
+/// Prefix of annotations carrying a permit's constraints
+const CONSTRAINT_PREFIX: &str = "constraint_";
+/// Annotation carrying a forbid's deny code
+const DENY_CODE: &str = "deny_code";
+
+/// Cedar authorizer and parsed policy set cache
+pub struct CedarEngine {
+    authorizer: Authorizer,
+    sets: Mutex<ModuleCache<Arc<PolicySet>>>,
+}
+
+impl CedarEngine {
+    /// Engine keeping up to `capacity` parsed policy sets
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            authorizer: Authorizer::new(),
+            sets: Mutex::new(ModuleCache::new(capacity)),
+        }
+    }
+
+    /// Parsed policy set cache counters
+    pub fn stats(&self) -> ModuleCacheStats {
+        self.sets.lock().unwrap().stats()
+    }
+
+    /// `policy`'s parsed set, from the cache or parsed now
+    fn policy_set(&self, policy: &Policy, source: &str) -> Result<Arc<PolicySet>> {
+        if let Some(set) = self.sets.lock().unwrap().get(&policy.bytecode_hash) {
+            return Ok(set);
+        }
+        let set = Arc::new(parse(source).map_err(|reason| PolicyError::CompileFailed {
+            policy_id: policy.policy_id.clone(),
+            reason,
+        })?);
+        self.sets.lock().unwrap().insert(&policy.bytecode_hash, set.clone());
+        Ok(set)
+    }
+}
+
+impl ExternalEngine for CedarEngine {
+    fn name(&self) -> &str {
+        ENGINE
+    }
+
+    fn check(&self, source: &str) -> std::result::Result<(), String> {
+        parse(source).map(|_| ())
+    }
+
+    fn evaluate(&self, policy: &Policy, source: &str, context: &EvaluationContext) -> Result<TranslationDecision> {
+        let failed = |reason: String| PolicyError::ExecutionFailed(format!("{}: {}", policy.policy_id, reason));
+        let set = self.policy_set(policy, source)?;
+        let action = context.intent.get("type").and_then(Value::as_str).unwrap_or("unknown");
+        let request = Request::new(
+            uid("Actor", &context.actor).map_err(failed)?,
+            uid("Action", action).map_err(failed)?,
+            uid("Container", &context.container_id).map_err(failed)?,
+            Context::from_json_value(request_context(context), None).map_err(|e| failed(e.to_string()))?,
+            None,
+        )
+        .map_err(|e| failed(e.to_string()))?;
+
+        let response = self.authorizer.is_authorized(&request, &set, &Entities::empty());
+        if let Some(e) = response.diagnostics().errors().next() {
+            return Err(failed(e.to_string()));
+        }
+        let mut determining: Vec<&CedarPolicy> = response
+            .diagnostics()
+            .reason()
+            .filter_map(|id| set.policy(id))
+            .collect();
+        // Parsed ids are policy0, policy1, …: this is source order
+        determining.sort_by_key(|p| {
+            let id = p.id().to_string();
+            (id.len(), id)
+        });
+        match response.decision() {
+            Decision::Allow => allow(&determining).map_err(failed),
+            Decision::Deny => match determining.first() {
+                Some(p) => Ok(TranslationDecision::deny(
+                    deny_code(p).map_err(failed)?,
+                    format!("forbidden by {}", label(p)),
+                )),
+                None => Ok(TranslationDecision::deny(DenyCode::NoDecision, "no Cedar policy permits")),
+            },
+        }
+    }
+}
+
+/// Parse a policy set, requiring the annotations decisions are read from
+fn parse(source: &str) -> std::result::Result<PolicySet, String> {
+    let set = PolicySet::from_str(source).map_err(|e| e.to_string())?;
+    for p in set.policies() {
+        match p.effect() {
+            Effect::Permit => intent_class(p).map(|_| ())?,
+            Effect::Forbid => deny_code(p).map(|_| ())?,
+        }
+    }
+    Ok(set)
+}
+
+/// `@id` of a policy, or the id the parser gave it
+fn label(p: &CedarPolicy) -> String {
+    p.annotation("id").map_or_else(|| p.id().to_string(), str::to_string)
+}
+
+fn intent_class(p: &CedarPolicy) -> std::result::Result<u8, String> {
+    let class = p
+        .annotation(INTENT_CLASS)
+        .ok_or_else(|| format!("permit {} has no @{}", label(p), INTENT_CLASS))?;
+    class
+        .parse()
+        .map_err(|_| format!("permit {}: @{}({:?}) is not 0-255", label(p), INTENT_CLASS, class))
+}
+
+fn deny_code(p: &CedarPolicy) -> std::result::Result<DenyCode, String> {
+    match p.annotation(DENY_CODE) {
+        None => Ok(DenyCode::Other),
+        Some(code) => serde_json::from_value(Value::String(code.to_string()))
+            .map_err(|_| format!("forbid {}: @{}({:?}) is not a deny code", label(p), DENY_CODE, code)),
+    }
+}
+
+/// Merge the permits that allowed a request into one decision
+fn allow(permits: &[&CedarPolicy]) -> std::result::Result<TranslationDecision, String> {
+    let mut classes = BTreeSet::new();
+    let mut pacts = BTreeSet::new();
+    let mut constraints = Vec::new();
+    for p in permits {
+        classes.insert(intent_class(p)?);
+        if let Some(pact) = p.annotation(REQUIRED_PACT) {
+            pacts.insert(pact.to_string());
+        }
+        for (key, value) in p.annotations() {
+            if let Some(kind) = key.strip_prefix(CONSTRAINT_PREFIX) {
+                let c = Constraint {
+                    kind: kind.to_string(),
+                    value: value.to_string(),
+                };
+                if !constraints.contains(&c) {
+                    constraints.push(c);
+                }
+            }
+        }
+    }
+    if classes.len() > 1 {
+        return Ok(TranslationDecision::deny(
+            DenyCode::IntentClassConflict,
+            format!("permits allow intent classes {:?}", classes),
+        ));
+    }
+    if pacts.len() > 1 {
+        return Ok(TranslationDecision::deny(
+            DenyCode::PactConflict,
+            format!("permits require pacts {:?}", pacts),
+        ));
+    }
+    Ok(TranslationDecision::Allow {
+        intent_class: classes.pop_first().ok_or("allowed without a determining permit")?,
+        required_pact: pacts.pop_first(),
+        constraints,
+    })
+}
+
+fn uid(kind: &str, id: &str) -> std::result::Result<EntityUid, String> {
+    let name = EntityTypeName::from_str(&format!("{}::{}", NAMESPACE, kind)).map_err(|e| e.to_string())?;
+    Ok(EntityUid::from_type_name_and_id(name, EntityId::new(id)))
+}
+
+/// The request's `context` record
+fn request_context(context: &EvaluationContext) -> Value {
+    let mut record = serde_json::Map::new();
+    if let Some(intent) = cedar_value(&context.intent) {
+        record.insert("intent".to_string(), intent);
+    }
+    record.insert("timestamp".to_string(), Value::from(context.timestamp));
+    record.insert("spec".to_string(), Value::from(context.spec.as_str()));
+    if let Some(state) = context.state.as_ref().and_then(cedar_value) {
+        record.insert("state".to_string(), state);
+    }
+    Value::Object(record)
+}
+
+/// `value` as Cedar JSON: no nulls, no floats, no escapes
+fn cedar_value(value: &Value) -> Option<Value> {
+    match value {
+        Value::Null => None,
+        Value::Number(n) => Some(match n.as_i64() {
+            Some(i) => Value::from(i),
+            None => Value::String(n.to_string()),
+        }),
+        Value::Array(items) => Some(Value::Array(items.iter().filter_map(cedar_value).collect())),
+        Value::Object(fields) => Some(Value::Object(
+            fields
+                .iter()
+                .filter(|(k, _)| !k.starts_with("__"))
+                .filter_map(|(k, v)| cedar_value(v).map(|v| (k.clone(), v)))
+                .collect(),
+        )),
+        other => Some(other.clone()),
+    }
+}
+
+/// Policy bytecode for a Cedar policy set
+pub fn to_bytecode(source: &str) -> Vec<u8> {
+    ExternalPolicy::new(ENGINE, source).to_bytecode()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bytecode_hash, PolicyVM, SpecVersion};
+
+    const POLICIES: &str = r#"
+        @intent_class("0")
+        permit(principal, action in [Ubl::Action::"observe", Ubl::Action::"read"], resource);
+
+        @intent_class("1")
+        permit(principal, action == Ubl::Action::"transfer", resource)
+        when { context.intent.amount <= 10000 };
+
+        @intent_class("1") @required_pact("high_value_transfer") @constraint_max_delta("50000")
+        permit(principal, action == Ubl::Action::"transfer", resource)
+        when { context.intent.amount > 10000 };
+
+        @id("blocklist") @deny_code("actor_blocked")
+        forbid(principal == Ubl::Actor::"mallory", action, resource);
+    "#;
+
+    fn vm(source: &str) -> PolicyVM {
+        let bytecode = to_bytecode(source);
+        let mut vm = PolicyVM::new();
+        vm.set_external_engine(Arc::new(CedarEngine::new(8)));
+        vm.register(Policy {
+            policy_id: "cedar".to_string(),
+            version: "1".to_string(),
+            bytecode_hash: bytecode_hash(&bytecode),
+            bytecode,
+            description: String::new(),
+            active_from: 0,
+        })
+        .unwrap();
+        vm
+    }
+
+    fn decide(vm: &PolicyVM, actor: &str, intent: Value) -> Result<TranslationDecision> {
+        vm.evaluate(
+            "cedar",
+            &EvaluationContext {
+                container_id: "C.Test".to_string(),
+                actor: actor.to_string(),
+                intent,
+                state: None,
+                timestamp: 1,
+                spec: SpecVersion::V1_0,
+            },
+        )
+    }
+
+    #[test]
+    fn test_annotations_decide() {
+        let vm = vm(POLICIES);
+        assert_eq!(
+            decide(&vm, "alice", serde_json::json!({ "type": "read", "note": null })).unwrap(),
+            TranslationDecision::Allow {
+                intent_class: 0,
+                required_pact: None,
+                constraints: vec![],
+            }
+        );
+        assert_eq!(
+            decide(&vm, "alice", serde_json::json!({ "type": "transfer", "amount": 20000 })).unwrap(),
+            TranslationDecision::Allow {
+                intent_class: 1,
+                required_pact: Some("high_value_transfer".to_string()),
+                constraints: vec![Constraint {
+                    kind: "max_delta".to_string(),
+                    value: "50000".to_string(),
+                }],
+            }
+        );
+        assert_eq!(
+            decide(&vm, "mallory", serde_json::json!({ "type": "read" })).unwrap(),
+            TranslationDecision::deny(DenyCode::ActorBlocked, "forbidden by blocklist")
+        );
+        assert!(matches!(
+            decide(&vm, "alice", serde_json::json!({ "type": "evolve" })).unwrap(),
+            TranslationDecision::Deny { code: DenyCode::NoDecision, .. }
+        ));
+    }
+
+    #[test]
+    fn test_erroring_policy_fails_evaluation() {
+        let vm = vm(POLICIES);
+        // No amount: the transfer permits error instead of being skipped
+        assert!(matches!(
+            decide(&vm, "alice", serde_json::json!({ "type": "transfer" })),
+            Err(PolicyError::ExecutionFailed(_))
+        ));
+        // A float is a string to Cedar
+        assert!(matches!(
+            decide(&vm, "alice", serde_json::json!({ "type": "transfer", "amount": 1.5 })),
+            Err(PolicyError::ExecutionFailed(_))
+        ));
+    }
+
+    #[test]
+    fn test_conflicting_permits() {
+        let vm = vm(r#"
+            @intent_class("0") permit(principal, action, resource);
+            @intent_class("1") permit(principal, action == Ubl::Action::"transfer", resource);
+        "#);
+        assert!(matches!(
+            decide(&vm, "alice", serde_json::json!({ "type": "read" })).unwrap(),
+            TranslationDecision::Allow { intent_class: 0, .. }
+        ));
+        assert!(matches!(
+            decide(&vm, "alice", serde_json::json!({ "type": "transfer" })).unwrap(),
+            TranslationDecision::Deny { code: DenyCode::IntentClassConflict, .. }
+        ));
+    }
+
+    #[test]
+    fn test_check_requires_annotations() {
+        let engine = CedarEngine::new(1);
+        assert!(engine.check(POLICIES).is_ok());
+        assert!(engine.check("permit(principal, action, resource);").is_err());
+        assert!(engine.check(r#"@intent_class("256") permit(principal, action, resource);"#).is_err());
+        assert!(engine.check(r#"@deny_code("nope") forbid(principal, action, resource);"#).is_err());
+        assert!(engine.check("permit(principal, action, resource").is_err());
+    }
+
+    #[test]
+    fn test_context_mapping() {
+        let context = EvaluationContext {
+            container_id: "C.Test".to_string(),
+            actor: "alice".to_string(),
+            intent: serde_json::json!({ "type": "t", "x": null, "f": 0.5, "__entity": { "type": "A", "id": "b" } }),
+            state: Some(serde_json::json!({ "big": u64::MAX })),
+            timestamp: 7,
+            spec: SpecVersion::V1_0,
+        };
+        assert_eq!(
+            request_context(&context),
+            serde_json::json!({
+                "intent": { "type": "t", "f": "0.5" },
+                "timestamp": 7,
+                "spec": "1.0",
+                "state": { "big": u64::MAX.to_string() },
+            })
+        );
+    }
+}
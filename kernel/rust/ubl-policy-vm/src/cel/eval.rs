@@ -0,0 +1,427 @@
+//! CEL values and evaluation
+//!
+//! Errors follow CEL: they propagate, except that `&&` and `||` absorb an
+//! error when the other side alone decides the result. Integer arithmetic is
+//! checked; overflow and division by zero are errors.
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+
+use super::parse::{BinOp, Expr, Func, Macro, Method, UnOp};
+use crate::{Interrupt, StateReader};
+
+/// A CEL value; JSON maps to it one-to-one (integral numbers become `Int`)
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Value {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Double(f64),
+    String(String),
+    List(Vec<Value>),
+    Map(BTreeMap<String, Value>),
+}
+
+impl From<&serde_json::Value> for Value {
+    fn from(v: &serde_json::Value) -> Self {
+        match v {
+            serde_json::Value::Null => Value::Null,
+            serde_json::Value::Bool(b) => Value::Bool(*b),
+            serde_json::Value::Number(n) => match n.as_i64() {
+                Some(i) => Value::Int(i),
+                None => Value::Double(n.as_f64().unwrap_or(f64::NAN)),
+            },
+            serde_json::Value::String(s) => Value::String(s.clone()),
+            serde_json::Value::Array(a) => Value::List(a.iter().map(Value::from).collect()),
+            serde_json::Value::Object(o) => Value::Map(o.iter().map(|(k, v)| (k.clone(), Value::from(v))).collect()),
+        }
+    }
+}
+
+impl Value {
+    fn type_name(&self) -> &'static str {
+        match self {
+            Value::Null => "null",
+            Value::Bool(_) => "bool",
+            Value::Int(_) => "int",
+            Value::Double(_) => "double",
+            Value::String(_) => "string",
+            Value::List(_) => "list",
+            Value::Map(_) => "map",
+        }
+    }
+}
+
+type Eval = Result<Value, String>;
+
+fn no_overload(op: &str, args: &[&Value]) -> String {
+    let types: Vec<_> = args.iter().map(|v| v.type_name()).collect();
+    format!("no such overload: {}({})", op, types.join(", "))
+}
+
+/// Variables in scope: the environment, then macro variables innermost last
+pub(crate) struct Activation<'a> {
+    vars: Vec<(&'a str, Value)>,
+    interrupt: Option<&'a Interrupt>,
+    reader: Option<&'a dyn StateReader>,
+}
+
+impl<'a> Activation<'a> {
+    pub(crate) fn new(vars: Vec<(&'a str, Value)>) -> Self {
+        Self {
+            vars,
+            interrupt: None,
+            reader: None,
+        }
+    }
+
+    /// Answer `balance()` and `field()` from `reader`
+    pub(crate) fn reading(mut self, reader: Option<&'a dyn StateReader>) -> Self {
+        self.reader = reader;
+        self
+    }
+
+    /// Fail every node once `interrupt` is tripped
+    pub(crate) fn watching(mut self, interrupt: Option<&'a Interrupt>) -> Self {
+        self.interrupt = interrupt;
+        self
+    }
+
+    fn get(&self, name: &str) -> Eval {
+        self.vars
+            .iter()
+            .rev()
+            .find(|(n, _)| *n == name)
+            .map(|(_, v)| v.clone())
+            .ok_or_else(|| format!("undeclared reference to '{}'", name))
+    }
+}
+
+/// Evaluate `expr` under `env`
+pub(crate) fn eval<'a>(expr: &'a Expr, env: &mut Activation<'a>) -> Eval {
+    if env.interrupt.is_some_and(Interrupt::is_tripped) {
+        return Err("evaluation interrupted".to_string());
+    }
+    match expr {
+        Expr::Lit(v) => Ok(v.clone()),
+        Expr::Var(name) => env.get(name),
+        Expr::List(items) => items.iter().map(|e| eval(e, env)).collect::<Result<_, _>>().map(Value::List),
+        Expr::Map(entries) => {
+            let mut map = BTreeMap::new();
+            for (k, v) in entries {
+                let key = match eval(k, env)? {
+                    Value::String(s) => s,
+                    other => return Err(format!("map keys must be strings, got {}", other.type_name())),
+                };
+                if map.insert(key.clone(), eval(v, env)?).is_some() {
+                    return Err(format!("duplicate map key {:?}", key));
+                }
+            }
+            Ok(Value::Map(map))
+        }
+        Expr::Select(target, field) => match eval(target, env)? {
+            Value::Map(m) => m.get(field).cloned().ok_or_else(|| format!("no such key: {}", field)),
+            other => Err(format!("cannot select '{}' from {}", field, other.type_name())),
+        },
+        Expr::Has(target, field) => match eval(target, env)? {
+            Value::Map(m) => Ok(Value::Bool(m.contains_key(field))),
+            other => Err(format!("has() on {}", other.type_name())),
+        },
+        Expr::Index(target, index) => {
+            let (target, index) = (eval(target, env)?, eval(index, env)?);
+            match (&target, &index) {
+                (Value::List(l), Value::Int(i)) => usize::try_from(*i)
+                    .ok()
+                    .and_then(|i| l.get(i))
+                    .cloned()
+                    .ok_or_else(|| format!("index {} out of range", i)),
+                (Value::Map(m), Value::String(k)) => m.get(k).cloned().ok_or_else(|| format!("no such key: {}", k)),
+                _ => Err(no_overload("_[_]", &[&target, &index])),
+            }
+        }
+        Expr::Call(func @ (Func::Balance | Func::Field), args) => {
+            let args = args.iter().map(|e| eval(e, env)).collect::<Result<Vec<_>, _>>()?;
+            read_state(*func, &args, env.reader)
+        }
+        Expr::Call(func, args) => call(*func, eval(&args[0], env)?),
+        Expr::Method(target, method, args) => {
+            let target = eval(target, env)?;
+            let args = args.iter().map(|e| eval(e, env)).collect::<Result<Vec<_>, _>>()?;
+            method_call(*method, target, args)
+        }
+        Expr::Comprehension { kind, range, var, body } => {
+            let items = match eval(range, env)? {
+                Value::List(l) => l,
+                Value::Map(m) => m.into_keys().map(Value::String).collect(),
+                other => return Err(format!("cannot iterate over {}", other.type_name())),
+            };
+            comprehension(*kind, items, var, body, env)
+        }
+        Expr::Unary(op, e) => {
+            let v = eval(e, env)?;
+            match (op, &v) {
+                (UnOp::Not, Value::Bool(b)) => Ok(Value::Bool(!b)),
+                (UnOp::Neg, Value::Int(n)) => n.checked_neg().map(Value::Int).ok_or_else(|| "integer overflow".to_string()),
+                (UnOp::Neg, Value::Double(d)) => Ok(Value::Double(-d)),
+                (UnOp::Not, _) => Err(no_overload("!_", &[&v])),
+                (UnOp::Neg, _) => Err(no_overload("-_", &[&v])),
+            }
+        }
+        Expr::Binary(op, l, r) => binary(*op, eval(l, env)?, eval(r, env)?),
+        Expr::And(l, r) => logic(false, eval(l, env), || eval(r, env)),
+        Expr::Or(l, r) => logic(true, eval(l, env), || eval(r, env)),
+        Expr::Cond(c, t, f) => match eval(c, env)? {
+            Value::Bool(true) => eval(t, env),
+            Value::Bool(false) => eval(f, env),
+            other => Err(no_overload("_?_:_", &[&other])),
+        },
+    }
+}
+
+/// `&&` (`decisive` false) and `||` (`decisive` true)
+fn logic(decisive: bool, lhs: Eval, rhs: impl FnOnce() -> Eval) -> Eval {
+    let op = if decisive { "_||_" } else { "_&&_" };
+    if lhs == Ok(Value::Bool(decisive)) {
+        return lhs;
+    }
+    let rhs = rhs();
+    if rhs == Ok(Value::Bool(decisive)) {
+        return rhs;
+    }
+    match (lhs?, rhs?) {
+        (Value::Bool(_), Value::Bool(_)) => Ok(Value::Bool(!decisive)),
+        (l, r) => Err(no_overload(op, &[&l, &r])),
+    }
+}
+
+fn comprehension<'a>(kind: Macro, items: Vec<Value>, var: &'a str, body: &'a Expr, env: &mut Activation<'a>) -> Eval {
+    let mut results = Vec::new();
+    let mut matched = 0usize;
+    let mut error = None;
+    for item in items {
+        env.vars.push((var, item.clone()));
+        let out = eval(body, env);
+        env.vars.pop();
+        match (kind, out) {
+            (Macro::Map, out) => results.push(out?),
+            (_, Ok(Value::Bool(b))) => {
+                if b {
+                    matched += 1;
+                    if kind == Macro::Filter {
+                        results.push(item);
+                    }
+                }
+                match kind {
+                    Macro::All if !b => return Ok(Value::Bool(false)),
+                    Macro::Exists if b => return Ok(Value::Bool(true)),
+                    _ => {}
+                }
+            }
+            (_, Ok(other)) => return Err(no_overload("predicate", &[&other])),
+            // all/exists can still be decided by a later element, as with && and ||
+            (Macro::All | Macro::Exists, Err(e)) => error = error.or(Some(e)),
+            (_, Err(e)) => return Err(e),
+        }
+    }
+    if let Some(e) = error {
+        return Err(e);
+    }
+    Ok(match kind {
+        Macro::All => Value::Bool(true),
+        Macro::Exists => Value::Bool(false),
+        Macro::ExistsOne => Value::Bool(matched == 1),
+        Macro::Map | Macro::Filter => Value::List(results),
+    })
+}
+
+fn call(func: Func, arg: Value) -> Eval {
+    match (func, &arg) {
+        (Func::Size, _) => size(&arg),
+        (Func::Int, Value::Int(_)) => Ok(arg),
+        (Func::Int, Value::Double(d)) if d.is_finite() && d.trunc() >= i64::MIN as f64 && d.trunc() < i64::MAX as f64 => {
+            Ok(Value::Int(d.trunc() as i64))
+        }
+        (Func::Int, Value::String(s)) => s.parse().map(Value::Int).map_err(|_| format!("int({:?}) is not an integer", s)),
+        (Func::Double, Value::Double(_)) => Ok(arg),
+        (Func::Double, Value::Int(n)) => Ok(Value::Double(*n as f64)),
+        (Func::Double, Value::String(s)) => s.parse().map(Value::Double).map_err(|_| format!("double({:?}) is not a number", s)),
+        (Func::String, Value::String(_)) => Ok(arg),
+        (Func::String, Value::Int(n)) => Ok(Value::String(n.to_string())),
+        (Func::String, Value::Double(d)) => Ok(Value::String(d.to_string())),
+        (Func::String, Value::Bool(b)) => Ok(Value::String(b.to_string())),
+        (Func::Balance | Func::Field, _) => unreachable!("state functions are evaluated by read_state"),
+        (Func::Int, _) => Err(no_overload("int", &[&arg])),
+        (Func::Double, _) => Err(no_overload("double", &[&arg])),
+        (Func::String, _) => Err(no_overload("string", &[&arg])),
+    }
+}
+
+/// `balance(container_id)` and `field(container_id, path)`
+fn read_state(func: Func, args: &[Value], reader: Option<&dyn StateReader>) -> Eval {
+    let name = if func == Func::Balance { "balance" } else { "field" };
+    let reader = reader.ok_or_else(|| format!("{}() needs a state reader", name))?;
+    match (func, args) {
+        (Func::Balance, [Value::String(c)]) => match reader.get_balance(c).map_err(|e| e.to_string())? {
+            None => Ok(Value::Null),
+            Some(b) => i64::try_from(b)
+                .map(Value::Int)
+                .map_err(|_| format!("balance of {} does not fit an int", c)),
+        },
+        (Func::Field, [Value::String(c), Value::String(path)]) => Ok(reader
+            .get_field(c, path)
+            .map_err(|e| e.to_string())?
+            .map_or(Value::Null, |v| Value::from(&v))),
+        _ => Err(no_overload(name, &args.iter().collect::<Vec<_>>())),
+    }
+}
+
+fn size(v: &Value) -> Eval {
+    let n = match v {
+        Value::String(s) => s.chars().count(),
+        Value::List(l) => l.len(),
+        Value::Map(m) => m.len(),
+        _ => return Err(no_overload("size", &[v])),
+    };
+    Ok(Value::Int(n as i64))
+}
+
+fn method_call(method: Method, target: Value, args: Vec<Value>) -> Eval {
+    if method == Method::Size {
+        return size(&target);
+    }
+    let (Value::String(s), [Value::String(arg)]) = (&target, args.as_slice()) else {
+        let name = match method {
+            Method::StartsWith => "startsWith",
+            Method::EndsWith => "endsWith",
+            _ => "contains",
+        };
+        let refs: Vec<&Value> = std::iter::once(&target).chain(&args).collect();
+        return Err(no_overload(name, &refs));
+    };
+    Ok(Value::Bool(match method {
+        Method::StartsWith => s.starts_with(arg.as_str()),
+        Method::EndsWith => s.ends_with(arg.as_str()),
+        _ => s.contains(arg.as_str()),
+    }))
+}
+
+fn equals(l: &Value, r: &Value) -> bool {
+    match (l, r) {
+        (Value::Int(a), Value::Double(b)) | (Value::Double(b), Value::Int(a)) => (*a as f64) == *b,
+        (Value::List(a), Value::List(b)) => a.len() == b.len() && a.iter().zip(b).all(|(x, y)| equals(x, y)),
+        (Value::Map(a), Value::Map(b)) => {
+            a.len() == b.len() && a.iter().all(|(k, v)| b.get(k).is_some_and(|w| equals(v, w)))
+        }
+        _ => l == r,
+    }
+}
+
+fn compare(l: &Value, r: &Value) -> Option<Ordering> {
+    match (l, r) {
+        (Value::Int(a), Value::Int(b)) => Some(a.cmp(b)),
+        (Value::Double(a), Value::Double(b)) => a.partial_cmp(b),
+        (Value::Int(a), Value::Double(b)) => (*a as f64).partial_cmp(b),
+        (Value::Double(a), Value::Int(b)) => a.partial_cmp(&(*b as f64)),
+        (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+        (Value::Bool(a), Value::Bool(b)) => Some(a.cmp(b)),
+        _ => None,
+    }
+}
+
+fn binary(op: BinOp, l: Value, r: Value) -> Eval {
+    let overflow = || "integer overflow".to_string();
+    let fail = |name: &str| Err(no_overload(name, &[&l, &r]));
+    match op {
+        BinOp::Eq => Ok(Value::Bool(equals(&l, &r))),
+        BinOp::Ne => Ok(Value::Bool(!equals(&l, &r))),
+        BinOp::Lt | BinOp::Le | BinOp::Gt | BinOp::Ge => {
+            let Some(ord) = compare(&l, &r) else {
+                return fail("_<_");
+            };
+            Ok(Value::Bool(match op {
+                BinOp::Lt => ord == Ordering::Less,
+                BinOp::Le => ord != Ordering::Greater,
+                BinOp::Gt => ord == Ordering::Greater,
+                _ => ord != Ordering::Less,
+            }))
+        }
+        BinOp::In => match &r {
+            Value::List(items) => Ok(Value::Bool(items.iter().any(|v| equals(&l, v)))),
+            Value::Map(m) => match &l {
+                Value::String(k) => Ok(Value::Bool(m.contains_key(k))),
+                _ => fail("@in"),
+            },
+            _ => fail("@in"),
+        },
+        BinOp::Add => match (&l, &r) {
+            (Value::Int(a), Value::Int(b)) => a.checked_add(*b).map(Value::Int).ok_or_else(overflow),
+            (Value::Double(a), Value::Double(b)) => Ok(Value::Double(a + b)),
+            (Value::String(a), Value::String(b)) => Ok(Value::String(format!("{}{}", a, b))),
+            (Value::List(a), Value::List(b)) => Ok(Value::List(a.iter().chain(b).cloned().collect())),
+            _ => fail("_+_"),
+        },
+        BinOp::Sub | BinOp::Mul | BinOp::Div | BinOp::Rem => match (&l, &r) {
+            (Value::Int(a), Value::Int(b)) => {
+                let out = match op {
+                    BinOp::Sub => a.checked_sub(*b),
+                    BinOp::Mul => a.checked_mul(*b),
+                    _ if *b == 0 => return Err("division by zero".to_string()),
+                    BinOp::Div => a.checked_div(*b),
+                    _ => a.checked_rem(*b),
+                };
+                out.map(Value::Int).ok_or_else(overflow)
+            }
+            (Value::Double(a), Value::Double(b)) if op != BinOp::Rem => Ok(Value::Double(match op {
+                BinOp::Sub => a - b,
+                BinOp::Mul => a * b,
+                _ => a / b,
+            })),
+            _ => fail(match op {
+                BinOp::Sub => "_-_",
+                BinOp::Mul => "_*_",
+                BinOp::Div => "_/_",
+                _ => "_%_",
+            }),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::parse::parse;
+    use super::*;
+
+    fn run(src: &str) -> Eval {
+        let intent = serde_json::json!({"type": "transfer", "amount": 150, "tags": ["a", "b"], "rate": 1.5});
+        let expr = parse(src, &["intent"]).unwrap();
+        eval(&expr, &mut Activation::new(vec![("intent", Value::from(&intent))]))
+    }
+
+    #[test]
+    fn test_operators_and_macros() {
+        for (src, want) in [
+            ("intent.amount * 2 + 1 == 301", true),
+            ("intent.amount > 100 && intent.type in ['transfer', 'send']", true),
+            ("intent.rate < intent.amount", true),
+            ("size(intent.tags) == 2 && intent.tags.exists(t, t == 'b')", true),
+            ("intent.tags.all(t, t.startsWith('a'))", false),
+            ("intent.tags.map(t, t + '!') == ['a!', 'b!']", true),
+            ("has(intent.memo) ? intent.memo == '' : true", true),
+            ("'amount' in intent && !('memo' in intent)", true),
+            ("int('42') - 50 == -8", true),
+            ("{'k': intent.amount}.k == 150", true),
+        ] {
+            assert_eq!(run(src), Ok(Value::Bool(want)), "{}", src);
+        }
+    }
+
+    #[test]
+    fn test_errors_propagate_unless_absorbed() {
+        assert!(run("intent.memo == 'x'").unwrap_err().contains("no such key"));
+        assert_eq!(run("intent.memo == 'x' || true"), Ok(Value::Bool(true)));
+        assert_eq!(run("false && intent.memo == 'x'"), Ok(Value::Bool(false)));
+        assert!(run("intent.amount / 0 > 1").unwrap_err().contains("division by zero"));
+        assert!(run("9223372036854775807 + intent.amount > 0").unwrap_err().contains("overflow"));
+        assert!(run("intent.type + 1 == 2").unwrap_err().contains("no such overload"));
+    }
+}
@@ -0,0 +1,204 @@
+//! CEL policy backend (Common Expression Language)
+//!
+//! A policy whose bytecode starts with [`MAGIC`] is a [`CelPolicy`] in JSON:
+//! ordered rules, each a CEL expression over the [`EvaluationContext`] and
+//! the decision it yields. The first rule whose `when` is true decides; if
+//! none is, the policy denies with [`DenyCode::NoDecision`].
+//!
+//! ```json
+//! { "rules": [
+//!   { "when": "intent.type in ['observe', 'read']", "allow": { "intent_class": 0 } },
+//!   { "when": "intent.type == 'transfer' && intent.amount > 10000",
+//!     "allow": { "intent_class": 1, "required_pact": "high_value_transfer" } },
+//!   { "when": "actor in state.blocked", "deny": { "code": "actor_blocked" } }
+//! ] }
+//! ```
+//!
+//! Expressions see `container_id`, `actor`, `intent`, `state` (null when
+//! absent) and `timestamp`; `balance(container_id)` and
+//! `field(container_id, path)` ask the VM's [`StateReader`] on demand
+//! (see [`crate::state`]). Rules are compiled once, when the policy is
+//! registered; a rule that does not parse, nests deeper than 100 levels or
+//! names anything undeclared rejects the registration. A rule that fails at evaluation (missing key,
+//! type mismatch, overflow) fails the evaluation rather than being skipped.
+//!
+//! Supported CEL: literals, lists and string-keyed maps; `! - * / % + < <= >
+//! >= == != in && || ?:`; field selection and indexing; `has()`, `size()`,
+//! `int()`, `double()`, `string()`, `balance()`, `field()`; `startsWith`, `endsWith`, `contains`;
+//! the `all`, `exists`, `exists_one`, `map` and `filter` macros.
+
+mod eval;
+mod parse;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Constraint, DenyCode, EvaluationContext, Interrupt, PolicyError, Result, StateReader, TranslationDecision};
+use eval::{Activation, Value};
+use parse::Expr;
+
+/// Bytecode prefix marking a CEL policy; the JSON document follows it
+pub const MAGIC: &[u8] = b"cel/1\n";
+
+/// Context variables an expression may reference
+pub const VARIABLES: &[&str] = &["container_id", "actor", "intent", "state", "timestamp"];
+
+/// CEL policy source
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CelPolicy {
+    /// Rules, tried in order
+    pub rules: Vec<CelRule>,
+}
+
+/// One rule: a condition and the decision it yields
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CelRule {
+    /// CEL expression that must evaluate to a bool
+    pub when: String,
+    /// Decision when `when` is true
+    #[serde(flatten)]
+    pub then: CelOutcome,
+}
+
+/// Decision of a matching rule
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CelOutcome {
+    /// Allow with an intent class, optional pact and constraints
+    Allow {
+        /// Intent class permitted
+        intent_class: u8,
+        /// Pact required (if any)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        required_pact: Option<String>,
+        /// Constraints snapshot
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        constraints: Vec<Constraint>,
+    },
+    /// Deny with a canonical code
+    Deny {
+        /// Canonical reason
+        code: DenyCode,
+        /// Human-readable detail
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        detail: Option<String>,
+    },
+}
+
+/// Whether `bytecode` is a CEL policy
+pub fn is_cel(bytecode: &[u8]) -> bool {
+    bytecode.starts_with(MAGIC)
+}
+
+impl CelPolicy {
+    /// Policy bytecode: [`MAGIC`] followed by the JSON document
+    pub fn to_bytecode(&self) -> Vec<u8> {
+        let mut out = MAGIC.to_vec();
+        out.extend(serde_json::to_vec(self).expect("CEL policy serializes"));
+        out
+    }
+}
+
+/// A compiled CEL policy
+#[derive(Debug, Clone)]
+pub struct CelProgram {
+    rules: Vec<(Expr, CelOutcome)>,
+    /// Some rule calls `balance()` or `field()`
+    reads_state: bool,
+}
+
+impl CelProgram {
+    /// Parse and resolve every rule; the error names the first rule that fails
+    pub fn compile(policy: &CelPolicy) -> std::result::Result<Self, String> {
+        let rules = policy
+            .rules
+            .iter()
+            .enumerate()
+            .map(|(i, rule)| {
+                parse::parse(&rule.when, VARIABLES)
+                    .map(|expr| (expr, rule.then.clone()))
+                    .map_err(|e| format!("rule {}: {}", i, e))
+            })
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        let reads_state = rules.iter().any(|(expr, _)| expr.reads_state());
+        Ok(Self { rules, reads_state })
+    }
+
+    /// Whether a rule reads state through the [`StateReader`]; such
+    /// decisions are not determined by the context alone
+    pub fn reads_state(&self) -> bool {
+        self.reads_state
+    }
+
+    /// Compile CEL policy bytecode
+    pub fn from_bytecode(bytecode: &[u8]) -> std::result::Result<Self, String> {
+        let body = bytecode.strip_prefix(MAGIC).ok_or("missing CEL header")?;
+        let policy: CelPolicy = serde_json::from_slice(body).map_err(|e| e.to_string())?;
+        Self::compile(&policy)
+    }
+
+    /// Decide `context`: the first rule whose condition holds
+    pub fn evaluate(&self, context: &EvaluationContext) -> Result<TranslationDecision> {
+        self.run(context, None, None)
+    }
+
+    /// [`CelProgram::evaluate`] with `balance()` and `field()` answered by `reader`
+    pub fn evaluate_reading(&self, context: &EvaluationContext, reader: &dyn StateReader) -> Result<TranslationDecision> {
+        self.run(context, None, Some(reader))
+    }
+
+    /// [`CelProgram::evaluate`], checking `interrupt` before every node
+    pub(crate) fn run(
+        &self,
+        context: &EvaluationContext,
+        interrupt: Option<&Interrupt>,
+        reader: Option<&dyn StateReader>,
+    ) -> Result<TranslationDecision> {
+        let state = context.state.as_ref().map_or(Value::Null, Value::from);
+        for (i, (expr, outcome)) in self.rules.iter().enumerate() {
+            if interrupt.is_some_and(Interrupt::is_tripped) {
+                return Err(PolicyError::Timeout);
+            }
+            let mut env = Activation::new(vec![
+                ("container_id", Value::String(context.container_id.clone())),
+                ("actor", Value::String(context.actor.clone())),
+                ("intent", Value::from(&context.intent)),
+                ("state", state.clone()),
+                ("timestamp", Value::Int(context.timestamp)),
+            ])
+            .watching(interrupt)
+            .reading(reader);
+            let result = eval::eval(expr, &mut env);
+            // Whatever an interrupted rule returned, it did not run to the end
+            if interrupt.is_some_and(Interrupt::is_tripped) {
+                return Err(PolicyError::Timeout);
+            }
+            match result {
+                Ok(Value::Bool(false)) => continue,
+                Ok(Value::Bool(true)) => return Ok(decision(outcome)),
+                Ok(other) => {
+                    return Err(PolicyError::ExecutionFailed(format!(
+                        "rule {}: condition is {:?}, not a bool",
+                        i, other
+                    )))
+                }
+                Err(e) => return Err(PolicyError::ExecutionFailed(format!("rule {}: {}", i, e))),
+            }
+        }
+        Ok(TranslationDecision::deny(DenyCode::NoDecision, "no CEL rule matched"))
+    }
+}
+
+fn decision(outcome: &CelOutcome) -> TranslationDecision {
+    match outcome.clone() {
+        CelOutcome::Allow {
+            intent_class,
+            required_pact,
+            constraints,
+        } => TranslationDecision::Allow {
+            intent_class,
+            required_pact,
+            constraints,
+        },
+        CelOutcome::Deny { code, detail } => TranslationDecision::Deny { code, detail },
+    }
+}
@@ -0,0 +1,590 @@
+//! CEL lexer and parser
+//!
+//! Produces an [`Expr`] tree with every identifier and function resolved, so
+//! a program that parses cannot fail later on an undeclared name.
+//!
+//! Nesting is bounded by [`MAX_DEPTH`]: parentheses, lists, maps, call
+//! arguments, unary operators and each operator in a chain (`a + b + c`)
+//! deepen the tree a level. Parsing, evaluating and dropping a tree all
+//! recurse once per level, so a rule like `!!!!…` or `((((…` is refused at
+//! registration instead of overflowing the stack.
+
+use super::eval::Value;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Tok {
+    Int(i64),
+    Double(f64),
+    Str(String),
+    Ident(String),
+    Punct(&'static str),
+}
+
+/// Deepest [`Expr`] tree a rule may parse to
+pub(crate) const MAX_DEPTH: usize = 100;
+
+const PUNCT: &[&str] = &[
+    "&&", "||", "==", "!=", "<=", ">=", "(", ")", "[", "]", "{", "}", ".", ",", ":", "?", "!", "-", "+", "*", "/",
+    "%", "<", ">",
+];
+
+fn lex(src: &str) -> Result<Vec<(usize, Tok)>, String> {
+    let chars: Vec<char> = src.chars().collect();
+    let mut out = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let start = i;
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit() {
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '.') {
+                // `1.size()` is not CEL; a dot only continues a number when a digit follows
+                if chars[i] == '.' && !chars.get(i + 1).is_some_and(|d| d.is_ascii_digit()) {
+                    break;
+                }
+                if matches!(chars[i], 'e' | 'E') && matches!(chars.get(i + 1), Some('+' | '-')) {
+                    i += 1;
+                }
+                i += 1;
+            }
+            let text: String = chars[start..i].iter().collect();
+            let tok = if let Some(hex) = text.strip_prefix("0x") {
+                i64::from_str_radix(hex, 16).map(Tok::Int).ok()
+            } else if text.contains(['.', 'e', 'E']) {
+                text.parse().map(Tok::Double).ok()
+            } else {
+                text.parse().map(Tok::Int).ok()
+            };
+            out.push((start, tok.ok_or_else(|| format!("invalid number {} at {}", text, start))?));
+        } else if c.is_alphabetic() || c == '_' {
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            out.push((start, Tok::Ident(chars[start..i].iter().collect())));
+        } else if c == '"' || c == '\'' {
+            i += 1;
+            let mut s = String::new();
+            loop {
+                match chars.get(i) {
+                    None => return Err(format!("unterminated string at {}", start)),
+                    Some(&q) if q == c => break,
+                    Some('\\') => {
+                        i += 1;
+                        s.push(match chars.get(i) {
+                            Some('n') => '\n',
+                            Some('t') => '\t',
+                            Some('r') => '\r',
+                            Some(&e @ ('\\' | '"' | '\'')) => e,
+                            _ => return Err(format!("invalid escape at {}", i)),
+                        });
+                    }
+                    Some(&ch) => s.push(ch),
+                }
+                i += 1;
+            }
+            i += 1;
+            out.push((start, Tok::Str(s)));
+        } else {
+            let rest: String = chars[i..chars.len().min(i + 2)].iter().collect();
+            let p = PUNCT
+                .iter()
+                .find(|p| rest.starts_with(**p))
+                .ok_or_else(|| format!("unexpected '{}' at {}", c, start))?;
+            i += p.len();
+            out.push((start, Tok::Punct(p)));
+        }
+    }
+    Ok(out)
+}
+
+/// Unary operators
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum UnOp {
+    Not,
+    Neg,
+}
+
+/// Binary operators other than the logical ones
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    In,
+}
+
+/// Global functions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Func {
+    Size,
+    Int,
+    Double,
+    String,
+    /// `balance(container_id)`, from the state reader
+    Balance,
+    /// `field(container_id, path)`, from the state reader
+    Field,
+}
+
+/// Receiver-style functions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Method {
+    Size,
+    StartsWith,
+    EndsWith,
+    Contains,
+}
+
+/// Comprehension macros over lists (elements) and maps (keys)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Macro {
+    All,
+    Exists,
+    ExistsOne,
+    Map,
+    Filter,
+}
+
+/// Resolved expression tree
+#[derive(Debug, Clone)]
+pub(crate) enum Expr {
+    Lit(Value),
+    /// Variable, by name; declared in the environment or by a macro
+    Var(String),
+    List(Vec<Expr>),
+    Map(Vec<(Expr, Expr)>),
+    Select(Box<Expr>, String),
+    Has(Box<Expr>, String),
+    Index(Box<Expr>, Box<Expr>),
+    Call(Func, Vec<Expr>),
+    Method(Box<Expr>, Method, Vec<Expr>),
+    Comprehension {
+        kind: Macro,
+        range: Box<Expr>,
+        var: String,
+        body: Box<Expr>,
+    },
+    Unary(UnOp, Box<Expr>),
+    Binary(BinOp, Box<Expr>, Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Cond(Box<Expr>, Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    /// Whether evaluating this calls the state reader
+    pub(crate) fn reads_state(&self) -> bool {
+        match self {
+            Expr::Lit(_) | Expr::Var(_) => false,
+            Expr::Call(Func::Balance | Func::Field, _) => true,
+            Expr::List(items) | Expr::Call(_, items) => items.iter().any(Expr::reads_state),
+            Expr::Map(entries) => entries.iter().any(|(k, v)| k.reads_state() || v.reads_state()),
+            Expr::Select(e, _) | Expr::Has(e, _) | Expr::Unary(_, e) => e.reads_state(),
+            Expr::Method(e, _, args) => e.reads_state() || args.iter().any(Expr::reads_state),
+            Expr::Comprehension { range, body, .. } => range.reads_state() || body.reads_state(),
+            Expr::Index(a, b) | Expr::Binary(_, a, b) | Expr::And(a, b) | Expr::Or(a, b) => {
+                a.reads_state() || b.reads_state()
+            }
+            Expr::Cond(c, t, f) => c.reads_state() || t.reads_state() || f.reads_state(),
+        }
+    }
+}
+
+struct Parser<'a> {
+    toks: Vec<(usize, Tok)>,
+    pos: usize,
+    scope: Vec<String>,
+    globals: &'a [&'a str],
+    /// Levels of nesting around the current token
+    depth: usize,
+}
+
+/// Parse `src`, resolving identifiers against `globals`
+pub(crate) fn parse(src: &str, globals: &[&str]) -> Result<Expr, String> {
+    let mut p = Parser {
+        toks: lex(src)?,
+        pos: 0,
+        scope: Vec::new(),
+        globals,
+        depth: 0,
+    };
+    let expr = p.expr()?;
+    match p.toks.get(p.pos) {
+        None => Ok(expr),
+        Some((at, _)) => Err(format!("unexpected input at {}", at)),
+    }
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Tok> {
+        self.toks.get(self.pos).map(|(_, t)| t)
+    }
+
+    fn at(&self) -> usize {
+        self.toks.get(self.pos).map_or_else(|| self.toks.last().map_or(0, |(i, _)| i + 1), |(i, _)| *i)
+    }
+
+    fn eat(&mut self, p: &str) -> bool {
+        if matches!(self.peek(), Some(Tok::Punct(q)) if *q == p) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, p: &str) -> Result<(), String> {
+        if self.eat(p) {
+            Ok(())
+        } else {
+            Err(format!("expected '{}' at {}", p, self.at()))
+        }
+    }
+
+    fn ident(&mut self) -> Result<String, String> {
+        match self.peek().cloned() {
+            Some(Tok::Ident(name)) => {
+                self.pos += 1;
+                Ok(name)
+            }
+            _ => Err(format!("expected identifier at {}", self.at())),
+        }
+    }
+
+    /// Go one level deeper, failing past [`MAX_DEPTH`]
+    fn descend(&mut self) -> Result<(), String> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(format!("expression nested deeper than {} at {}", MAX_DEPTH, self.at()));
+        }
+        Ok(())
+    }
+
+    /// `f` one level deeper
+    fn nested(&mut self, f: impl FnOnce(&mut Self) -> Result<Expr, String>) -> Result<Expr, String> {
+        self.descend()?;
+        let e = f(self);
+        self.depth -= 1;
+        e
+    }
+
+    fn expr(&mut self) -> Result<Expr, String> {
+        self.nested(Self::conditional)
+    }
+
+    fn conditional(&mut self) -> Result<Expr, String> {
+        let cond = self.or()?;
+        if !self.eat("?") {
+            return Ok(cond);
+        }
+        let then = self.or()?;
+        self.expect(":")?;
+        let otherwise = self.expr()?;
+        Ok(Expr::Cond(Box::new(cond), Box::new(then), Box::new(otherwise)))
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.and()?;
+        let depth = self.depth;
+        while self.eat("||") {
+            self.descend()?;
+            lhs = Expr::Or(Box::new(lhs), Box::new(self.and()?));
+        }
+        self.depth = depth;
+        Ok(lhs)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.relation()?;
+        let depth = self.depth;
+        while self.eat("&&") {
+            self.descend()?;
+            lhs = Expr::And(Box::new(lhs), Box::new(self.relation()?));
+        }
+        self.depth = depth;
+        Ok(lhs)
+    }
+
+    fn relation(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.addition()?;
+        let depth = self.depth;
+        loop {
+            let op = match self.peek() {
+                Some(Tok::Punct("==")) => BinOp::Eq,
+                Some(Tok::Punct("!=")) => BinOp::Ne,
+                Some(Tok::Punct("<")) => BinOp::Lt,
+                Some(Tok::Punct("<=")) => BinOp::Le,
+                Some(Tok::Punct(">")) => BinOp::Gt,
+                Some(Tok::Punct(">=")) => BinOp::Ge,
+                Some(Tok::Ident(w)) if w == "in" => BinOp::In,
+                _ => {
+                    self.depth = depth;
+                    return Ok(lhs);
+                }
+            };
+            self.pos += 1;
+            self.descend()?;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(self.addition()?));
+        }
+    }
+
+    fn addition(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.multiplication()?;
+        let depth = self.depth;
+        loop {
+            let op = if self.eat("+") {
+                BinOp::Add
+            } else if self.eat("-") {
+                BinOp::Sub
+            } else {
+                self.depth = depth;
+                return Ok(lhs);
+            };
+            self.descend()?;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(self.multiplication()?));
+        }
+    }
+
+    fn multiplication(&mut self) -> Result<Expr, String> {
+        let mut lhs = self.unary()?;
+        let depth = self.depth;
+        loop {
+            let op = if self.eat("*") {
+                BinOp::Mul
+            } else if self.eat("/") {
+                BinOp::Div
+            } else if self.eat("%") {
+                BinOp::Rem
+            } else {
+                self.depth = depth;
+                return Ok(lhs);
+            };
+            self.descend()?;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(self.unary()?));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.eat("!") {
+            return Ok(Expr::Unary(UnOp::Not, Box::new(self.nested(Self::unary)?)));
+        }
+        if self.eat("-") {
+            return Ok(Expr::Unary(UnOp::Neg, Box::new(self.nested(Self::unary)?)));
+        }
+        self.member()
+    }
+
+    fn member(&mut self) -> Result<Expr, String> {
+        let mut e = self.primary()?;
+        let depth = self.depth;
+        loop {
+            if self.eat(".") {
+                self.descend()?;
+                let name = self.ident()?;
+                if self.eat("(") {
+                    e = self.method(e, &name)?;
+                } else {
+                    e = Expr::Select(Box::new(e), name);
+                }
+            } else if self.eat("[") {
+                self.descend()?;
+                let index = self.expr()?;
+                self.expect("]")?;
+                e = Expr::Index(Box::new(e), Box::new(index));
+            } else {
+                self.depth = depth;
+                return Ok(e);
+            }
+        }
+    }
+
+    fn args(&mut self) -> Result<Vec<Expr>, String> {
+        let mut args = Vec::new();
+        if self.eat(")") {
+            return Ok(args);
+        }
+        loop {
+            args.push(self.expr()?);
+            if self.eat(")") {
+                return Ok(args);
+            }
+            self.expect(",")?;
+        }
+    }
+
+    fn method(&mut self, target: Expr, name: &str) -> Result<Expr, String> {
+        let at = self.at();
+        let kind = match name {
+            "all" => Some(Macro::All),
+            "exists" => Some(Macro::Exists),
+            "exists_one" => Some(Macro::ExistsOne),
+            "map" => Some(Macro::Map),
+            "filter" => Some(Macro::Filter),
+            _ => None,
+        };
+        if let Some(kind) = kind {
+            let var = self.ident()?;
+            self.expect(",")?;
+            self.scope.push(var.clone());
+            let body = self.expr();
+            self.scope.pop();
+            let body = body?;
+            self.expect(")")?;
+            return Ok(Expr::Comprehension {
+                kind,
+                range: Box::new(target),
+                var,
+                body: Box::new(body),
+            });
+        }
+        let (method, arity) = match name {
+            "size" => (Method::Size, 0),
+            "startsWith" => (Method::StartsWith, 1),
+            "endsWith" => (Method::EndsWith, 1),
+            "contains" => (Method::Contains, 1),
+            _ => return Err(format!("unknown function {} at {}", name, at)),
+        };
+        let args = self.args()?;
+        if args.len() != arity {
+            return Err(format!("{} takes {} argument(s), got {} at {}", name, arity, args.len(), at));
+        }
+        Ok(Expr::Method(Box::new(target), method, args))
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        let at = self.at();
+        let tok = self.peek().cloned().ok_or_else(|| format!("unexpected end of expression at {}", at))?;
+        self.pos += 1;
+        match tok {
+            Tok::Int(n) => Ok(Expr::Lit(Value::Int(n))),
+            Tok::Double(d) => Ok(Expr::Lit(Value::Double(d))),
+            Tok::Str(s) => Ok(Expr::Lit(Value::String(s))),
+            Tok::Punct("(") => {
+                let e = self.expr()?;
+                self.expect(")")?;
+                Ok(e)
+            }
+            Tok::Punct("[") => {
+                let mut items = Vec::new();
+                while !self.eat("]") {
+                    items.push(self.expr()?);
+                    if !self.eat(",") {
+                        self.expect("]")?;
+                        break;
+                    }
+                }
+                Ok(Expr::List(items))
+            }
+            Tok::Punct("{") => {
+                let mut entries = Vec::new();
+                while !self.eat("}") {
+                    let k = self.expr()?;
+                    self.expect(":")?;
+                    entries.push((k, self.expr()?));
+                    if !self.eat(",") {
+                        self.expect("}")?;
+                        break;
+                    }
+                }
+                Ok(Expr::Map(entries))
+            }
+            Tok::Ident(name) => match name.as_str() {
+                "true" => Ok(Expr::Lit(Value::Bool(true))),
+                "false" => Ok(Expr::Lit(Value::Bool(false))),
+                "null" => Ok(Expr::Lit(Value::Null)),
+                _ if self.eat("(") => self.call(&name, at),
+                _ if self.scope.contains(&name) || self.globals.contains(&name.as_str()) => Ok(Expr::Var(name)),
+                _ => Err(format!("undeclared reference to '{}' at {}", name, at)),
+            },
+            Tok::Punct(p) => Err(format!("unexpected '{}' at {}", p, at)),
+        }
+    }
+
+    fn call(&mut self, name: &str, at: usize) -> Result<Expr, String> {
+        if name == "has" {
+            let arg = self.expr()?;
+            self.expect(")")?;
+            return match arg {
+                Expr::Select(target, field) => Ok(Expr::Has(target, field)),
+                _ => Err(format!("has() needs a field selection like has(a.b) at {}", at)),
+            };
+        }
+        let func = match name {
+            "size" => Func::Size,
+            "int" => Func::Int,
+            "double" => Func::Double,
+            "string" => Func::String,
+            "balance" => Func::Balance,
+            "field" => Func::Field,
+            _ => return Err(format!("unknown function {} at {}", name, at)),
+        };
+        let arity = if func == Func::Field { 2 } else { 1 };
+        let args = self.args()?;
+        if args.len() != arity {
+            let plural = if arity == 1 { "" } else { "s" };
+            return Err(format!("{} takes {} argument{}, got {} at {}", name, arity, plural, args.len(), at));
+        }
+        Ok(Expr::Call(func, args))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rejects_unknown_names_and_bad_syntax() {
+        let globals = ["intent", "actor"];
+        assert!(parse("intent.amount > 10 && actor.startsWith('ubl:')", &globals).is_ok());
+        assert!(parse("intent.items.all(i, i.qty > 0)", &globals).is_ok());
+        assert!(parse("i.qty > 0", &globals).unwrap_err().contains("undeclared reference to 'i'"));
+        assert!(parse("intent.amount >", &globals).is_err());
+        assert!(parse("frobnicate(intent)", &globals).unwrap_err().contains("unknown function"));
+        assert!(parse("has(intent)", &globals).is_err());
+        assert!(parse("(actor", &globals).is_err());
+        assert!(parse("field(actor)", &globals).unwrap_err().contains("takes 2 arguments"));
+    }
+
+    #[test]
+    fn test_bounds_nesting() {
+        let globals = ["intent"];
+        let deep = |open: &str, inner: &str, close: &str, n: usize| {
+            format!("{}{}{}", open.repeat(n), inner, close.repeat(n))
+        };
+        assert!(parse(&deep("!", "true", "", MAX_DEPTH / 2), &globals).is_ok());
+        assert!(parse(&deep("(", "1", ")", MAX_DEPTH / 2), &globals).is_ok());
+        assert!(parse(&vec!["1"; MAX_DEPTH / 2].join(" + "), &globals).is_ok());
+
+        // Far past the bound: refused, not a stack overflow
+        for src in [
+            deep("!", "true", "", 100_000),
+            deep("-", "1", "", 100_000),
+            deep("(", "1", ")", 100_000),
+            deep("[", "1", "]", 100_000),
+            deep("size(", "intent", ")", 100_000),
+            vec!["1"; 100_000].join(" + "),
+            vec!["true"; 100_000].join(" && "),
+            format!("intent{}", ".a".repeat(100_000)),
+            format!("{}1", "true ? 1 : ".repeat(100_000)),
+        ] {
+            assert!(parse(&src, &globals).unwrap_err().contains("nested deeper than"));
+        }
+    }
+
+    #[test]
+    fn test_marks_state_reads() {
+        let globals = ["intent", "container_id"];
+        assert!(!parse("size(intent.items) > 0", &globals).unwrap().reads_state());
+        assert!(parse("intent.items.all(i, balance(i) > 0)", &globals).unwrap().reads_state());
+        assert!(parse("true || field(container_id, 'tier') == 'gold'", &globals).unwrap().reads_state());
+    }
+}
@@ -0,0 +1,279 @@
+//! Policy composition (several policies governing one container)
+//!
+//! Decisions are merged in policy-id order, so the result never depends on
+//! the order in which policies were attached or evaluated. Under every mode
+//! a single Deny denies, and the allowing policies must agree on the intent
+//! class and on the pact they require.
+//!
+//! ## Priority across binding layers
+//!
+//! A container can match policies at several layers ([`crate::PolicyVM::layers`]),
+//! highest priority first: its own attachments, then each namespace that
+//! prefixes its id from longest to shortest, the global (empty) namespace
+//! last. Each layer is merged as above, then [`resolve`] combines them:
+//!
+//! 1. A Deny at any layer denies; the highest-priority denial is reported.
+//!    A more specific layer cannot allow what a broader one forbids.
+//! 2. The intent class comes from the highest-priority layer. A broader
+//!    layer that allowed a different class is overruled.
+//! 3. The pact comes from the highest-priority layer that requires one; a
+//!    layer requiring none does not lift a broader requirement.
+//! 4. Constraints from every layer are carried, highest priority first, and
+//!    collapse per the container's [`CompositionMode`].
+//!
+//! The [`Resolution`] names the policy that decided and the policies it
+//! overruled, so the outcome can be audited.
+
+use serde::{Deserialize, Serialize};
+
+use crate::constraints::tightest;
+use crate::{BindingSource, Constraint, DenyCode, TranslationDecision};
+
+/// How the decisions of a container's policies are combined
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum CompositionMode {
+    /// Every policy must allow; all constraints are carried, deduplicated
+    #[default]
+    AllMustAllow,
+    /// Every policy must allow; constraints collapse to the tightest bound per kind
+    MostRestrictiveWins,
+}
+
+/// Decision for a container with the policy that decided it
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Resolution {
+    /// Combined decision
+    pub decision: TranslationDecision,
+    /// Policy whose decision prevailed: the denying or conflicting policy,
+    /// or the first allowing policy of the highest-priority layer
+    pub decided_by: Option<String>,
+    /// Layer of `decided_by`
+    pub source: Option<BindingSource>,
+    /// Allowing policies of broader layers whose intent class or pact was set aside
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub overruled: Vec<String>,
+}
+
+/// Merge per-policy decisions, given as `(policy_id, decision)` in policy-id order
+pub fn merge(mode: CompositionMode, decisions: &[(String, TranslationDecision)]) -> TranslationDecision {
+    merge_decided(mode, decisions).0
+}
+
+/// Combine binding layers, given highest priority first, each as its
+/// `(policy_id, decision)` pairs in policy-id order
+pub fn resolve(mode: CompositionMode, layers: &[(BindingSource, Vec<(String, TranslationDecision)>)]) -> Resolution {
+    let mut allowed = Vec::with_capacity(layers.len());
+    for (source, decisions) in layers {
+        let (decision, decided_by) = merge_decided(CompositionMode::AllMustAllow, decisions);
+        match decision {
+            TranslationDecision::Allow {
+                intent_class,
+                required_pact,
+                constraints,
+            } => allowed.push((source, decided_by, intent_class, required_pact, constraints)),
+            deny => {
+                return Resolution {
+                    decision: deny,
+                    decided_by,
+                    source: Some(source.clone()),
+                    overruled: Vec::new(),
+                }
+            }
+        }
+    }
+    let Some((source, decided_by, intent_class, _, _)) = allowed.first().cloned() else {
+        return Resolution {
+            decision: TranslationDecision::deny(DenyCode::NoDecision, "no policy decided"),
+            decided_by: None,
+            source: None,
+            overruled: Vec::new(),
+        };
+    };
+
+    let required_pact = allowed.iter().find_map(|(.., pact, _)| pact.clone());
+    let mut overruled = Vec::new();
+    let mut constraints: Vec<Constraint> = Vec::new();
+    for (_, by, class, pact, own) in &allowed {
+        if *class != intent_class || (pact.is_some() && *pact != required_pact) {
+            overruled.extend(by.clone());
+        }
+        for c in own {
+            if !constraints.contains(c) {
+                constraints.push(c.clone());
+            }
+        }
+    }
+    if mode == CompositionMode::MostRestrictiveWins {
+        constraints = tightest(&constraints);
+    }
+    Resolution {
+        decision: TranslationDecision::Allow {
+            intent_class,
+            required_pact,
+            constraints,
+        },
+        decided_by,
+        source: Some(source.clone()),
+        overruled,
+    }
+}
+
+/// Merged decision and the policy that decided it
+fn merge_decided(mode: CompositionMode, decisions: &[(String, TranslationDecision)]) -> (TranslationDecision, Option<String>) {
+    let mut class: Option<(&str, u8)> = None;
+    let mut pact: Option<(&str, &String)> = None;
+    let mut constraints: Vec<Constraint> = Vec::new();
+
+    for (policy_id, decision) in decisions {
+        match decision {
+            TranslationDecision::Deny { code, detail } => {
+                let deny = TranslationDecision::Deny {
+                    code: *code,
+                    detail: Some(match detail {
+                        Some(d) => format!("{}: {}", policy_id, d),
+                        None => policy_id.clone(),
+                    }),
+                };
+                return (deny, Some(policy_id.clone()));
+            }
+            TranslationDecision::Allow {
+                intent_class,
+                required_pact,
+                constraints: own,
+            } => {
+                match class {
+                    Some((first, c)) if c != *intent_class => {
+                        let deny = TranslationDecision::deny(
+                            DenyCode::IntentClassConflict,
+                            format!(
+                                "intent class conflict: {} allows 0x{:02x}, {} allows 0x{:02x}",
+                                first, c, policy_id, intent_class
+                            ),
+                        );
+                        return (deny, Some(policy_id.clone()));
+                    }
+                    Some(_) => {}
+                    None => class = Some((policy_id, *intent_class)),
+                }
+                if let Some(required) = required_pact {
+                    match pact {
+                        Some((first, p)) if p != required => {
+                            let deny = TranslationDecision::deny(
+                                DenyCode::PactConflict,
+                                format!("pact conflict: {} requires {}, {} requires {}", first, p, policy_id, required),
+                            );
+                            return (deny, Some(policy_id.clone()));
+                        }
+                        Some(_) => {}
+                        None => pact = Some((policy_id, required)),
+                    }
+                }
+                for c in own {
+                    if !constraints.contains(c) {
+                        constraints.push(c.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    let Some((decided_by, intent_class)) = class else {
+        return (TranslationDecision::deny(DenyCode::NoDecision, "no policy decided"), None);
+    };
+    if mode == CompositionMode::MostRestrictiveWins {
+        constraints = tightest(&constraints);
+    }
+    let allow = TranslationDecision::Allow {
+        intent_class,
+        required_pact: pact.map(|(_, p)| p.clone()),
+        constraints,
+    };
+    (allow, Some(decided_by.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn allow(class: u8, pact: Option<&str>, constraints: &[(&str, &str)]) -> TranslationDecision {
+        TranslationDecision::Allow {
+            intent_class: class,
+            required_pact: pact.map(String::from),
+            constraints: constraints
+                .iter()
+                .map(|(k, v)| Constraint { kind: k.to_string(), value: v.to_string() })
+                .collect(),
+        }
+    }
+
+    fn named(decisions: Vec<TranslationDecision>) -> Vec<(String, TranslationDecision)> {
+        decisions.into_iter().enumerate().map(|(i, d)| (format!("p{}", i), d)).collect()
+    }
+
+    #[test]
+    fn test_modes_merge_constraints() {
+        let decisions = named(vec![
+            allow(0x01, None, &[("max_amount", "10000")]),
+            allow(0x01, Some("high_value_transfer"), &[("max_amount", "500"), ("max_amount", "10000")]),
+        ]);
+        assert_eq!(
+            merge(CompositionMode::AllMustAllow, &decisions),
+            allow(0x01, Some("high_value_transfer"), &[("max_amount", "10000"), ("max_amount", "500")])
+        );
+        assert_eq!(
+            merge(CompositionMode::MostRestrictiveWins, &decisions),
+            allow(0x01, Some("high_value_transfer"), &[("max_amount", "500")])
+        );
+    }
+
+    #[test]
+    fn test_layers_by_priority() {
+        let container = BindingSource::Container;
+        let bank = BindingSource::Namespace("C.Bank.".to_string());
+        let global = BindingSource::Namespace(String::new());
+        let layer = |source: &BindingSource, id: &str, d: TranslationDecision| (source.clone(), vec![(id.to_string(), d)]);
+
+        // The specific layer sets the class, a broader one still adds its pact and bounds
+        let r = resolve(
+            CompositionMode::MostRestrictiveWins,
+            &[
+                layer(&container, "own", allow(0x02, None, &[("max_amount", "900")])),
+                layer(&bank, "bank", allow(0x01, Some("treasury"), &[("max_amount", "500")])),
+                layer(&global, "base", allow(0x02, None, &[("containers", "C.Bank.T")])),
+            ],
+        );
+        assert_eq!(
+            r.decision,
+            allow(0x02, Some("treasury"), &[("max_amount", "500"), ("containers", "C.Bank.T")])
+        );
+        assert_eq!((r.decided_by.as_deref(), r.source), (Some("own"), Some(container.clone())));
+        assert_eq!(r.overruled, ["bank"]);
+
+        // A broader Deny still denies, and names who denied
+        let r = resolve(
+            CompositionMode::AllMustAllow,
+            &[
+                layer(&container, "own", allow(0x01, None, &[])),
+                layer(&global, "base", TranslationDecision::deny(DenyCode::ActorBlocked, "frozen")),
+            ],
+        );
+        assert!(matches!(r.decision, TranslationDecision::Deny { code: DenyCode::ActorBlocked, .. }));
+        assert_eq!((r.decided_by.as_deref(), r.source), (Some("base"), Some(global)));
+    }
+
+    #[test]
+    fn test_conflicts_deny() {
+        let classes = named(vec![allow(0x01, None, &[]), allow(0x02, None, &[])]);
+        assert!(matches!(
+            merge(CompositionMode::AllMustAllow, &classes),
+            TranslationDecision::Deny { code: DenyCode::IntentClassConflict, .. }
+        ));
+
+        let pacts = named(vec![allow(0x01, Some("a"), &[]), allow(0x01, Some("b"), &[])]);
+        assert!(matches!(
+            merge(CompositionMode::MostRestrictiveWins, &pacts),
+            TranslationDecision::Deny { code: DenyCode::PactConflict, .. }
+        ));
+    }
+}
@@ -0,0 +1,349 @@
+//! Constraint enforcement (SPEC-UBL-POLICY v1.0 §6.2)
+//!
+//! `TranslationDecision::Allow` carries a constraints snapshot. The snapshot
+//! is binding: the commit that follows the decision must satisfy every
+//! constraint, checked here against the facts of the actual commit.
+//!
+//! Supported kinds:
+//!
+//! | kind          | value                     | rule                              |
+//! |---------------|---------------------------|-----------------------------------|
+//! | `max_amount`  | integer                   | `|physics_delta| <= value`        |
+//! | `max_delta`   | integer                   | `|physics_delta| <= value`        |
+//! | `min_delta`   | integer                   | `physics_delta >= value`          |
+//! | `time_window` | `start..end` (either open)| `start <= timestamp < end`        |
+//! | `containers`  | comma-separated ids       | `container_id` is listed          |
+//! | `schedule`    | [`Schedule`] syntax       | `timestamp` (unix seconds) inside |
+//! | `risk_level`  | `L0`..`L5`                | enforced by pact validation       |
+//! | `max_atom_size` | integer (bytes)         | stored atom, `atom_size <= value` |
+//!
+//! Unknown kinds and malformed values fail closed.
+//!
+//! `schedule` is also checked when the decision is made: the VM turns an
+//! Allow whose schedule excludes `EvaluationContext::timestamp` into a Deny
+//! with [`DenyCode::OutsideSchedule`] ([`apply_schedules`]).
+
+use serde::{Deserialize, Serialize};
+
+use crate::schedule::Schedule;
+use crate::{Constraint, DenyCode, PolicyError, Result, TranslationDecision};
+
+/// What a commit actually does, as seen by constraint enforcement
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CommitFacts {
+    /// Target container
+    pub container_id: String,
+    /// Intent class byte (SPEC-UBL-LINK v1.0 §3.2)
+    pub intent_class: u8,
+    /// Physical delta of the commit
+    pub physics_delta: i128,
+    /// Commit time, same unit as `EvaluationContext::timestamp`
+    pub timestamp: i64,
+    /// Canonical size of the committed atom, when the atom store holds it
+    #[serde(default)]
+    pub atom_size: Option<u64>,
+}
+
+/// Check every constraint against the commit, stopping at the first violation
+pub fn enforce(constraints: &[Constraint], facts: &CommitFacts) -> Result<()> {
+    constraints.iter().try_for_each(|c| check(c, facts))
+}
+
+/// Every constraint the commit would violate, in snapshot order
+pub fn violations(constraints: &[Constraint], facts: &CommitFacts) -> Vec<PolicyError> {
+    constraints.iter().filter_map(|c| check(c, facts).err()).collect()
+}
+
+fn check(constraint: &Constraint, facts: &CommitFacts) -> Result<()> {
+    let violated = |reason: String| PolicyError::ConstraintViolated {
+        kind: constraint.kind.clone(),
+        reason,
+    };
+    match constraint.kind.as_str() {
+        "max_amount" | "max_delta" => {
+            let max = parse_int(constraint)?;
+            if max < 0 {
+                return Err(invalid(constraint));
+            }
+            if facts.physics_delta.unsigned_abs() > max.unsigned_abs() {
+                return Err(violated(format!(
+                    "|delta| {} exceeds {}",
+                    facts.physics_delta.unsigned_abs(),
+                    max
+                )));
+            }
+        }
+        "min_delta" => {
+            let min = parse_int(constraint)?;
+            if facts.physics_delta < min {
+                return Err(violated(format!("delta {} below {}", facts.physics_delta, min)));
+            }
+        }
+        "time_window" => {
+            let (start, end) = parse_window(constraint)?;
+            if start.is_some_and(|s| facts.timestamp < s) || end.is_some_and(|e| facts.timestamp >= e) {
+                return Err(violated(format!(
+                    "timestamp {} outside {}",
+                    facts.timestamp, constraint.value
+                )));
+            }
+        }
+        "containers" => {
+            if !constraint.value.split(',').any(|c| c.trim() == facts.container_id) {
+                return Err(violated(format!("container {} not allowed", facts.container_id)));
+            }
+        }
+        "schedule" => {
+            if !parse_schedule(constraint)?.contains(facts.timestamp) {
+                return Err(violated(format!(
+                    "timestamp {} outside {}",
+                    facts.timestamp, constraint.value
+                )));
+            }
+        }
+        "max_atom_size" => {
+            let max = parse_int(constraint)?;
+            if max < 0 {
+                return Err(invalid(constraint));
+            }
+            match facts.atom_size {
+                None => {
+                    return Err(violated("atom size unknown: the atom is not in the atom store".to_string()))
+                }
+                Some(size) if i128::from(size) > max => {
+                    return Err(violated(format!("atom of {} bytes exceeds {}", size, max)))
+                }
+                Some(_) => {}
+            }
+        }
+        // Carried for pact validation, which owns risk tiers
+        "risk_level" => {}
+        other => return Err(PolicyError::UnknownConstraint(other.to_string())),
+    }
+    Ok(())
+}
+
+/// Deny an Allow whose `schedule` constraints exclude `timestamp`.
+/// A schedule that does not parse fails the evaluation.
+pub fn apply_schedules(decision: TranslationDecision, timestamp: i64) -> Result<TranslationDecision> {
+    let TranslationDecision::Allow { constraints, .. } = &decision else {
+        return Ok(decision);
+    };
+    for c in constraints.iter().filter(|c| c.kind == "schedule") {
+        if !parse_schedule(c)?.contains(timestamp) {
+            return Ok(TranslationDecision::deny(
+                DenyCode::OutsideSchedule,
+                format!("timestamp {} outside {}", timestamp, c.value),
+            ));
+        }
+    }
+    Ok(decision)
+}
+
+/// Collapse constraints to the tightest bound per kind, keeping first-seen
+/// kind order. Used when several policies govern one commit.
+///
+/// Kinds without a known ordering (or values that do not parse) are kept
+/// as-is, so [`enforce`] still sees and rejects them.
+pub fn tightest(constraints: &[Constraint]) -> Vec<Constraint> {
+    let mut merged: Vec<Constraint> = Vec::new();
+    for c in constraints {
+        let slot = merged.iter_mut().find(|m| m.kind == c.kind);
+        match slot {
+            Some(m) => match tighter(m, c) {
+                Some(value) => m.value = value,
+                None if m.value == c.value => {}
+                None => merged.push(c.clone()),
+            },
+            None => merged.push(c.clone()),
+        }
+    }
+    merged
+}
+
+/// Tighter of two same-kind constraints, or None if they do not compare
+fn tighter(a: &Constraint, b: &Constraint) -> Option<String> {
+    match a.kind.as_str() {
+        "max_amount" | "max_delta" | "max_atom_size" => {
+            let (x, y) = (parse_int(a).ok()?, parse_int(b).ok()?);
+            Some(x.min(y).to_string())
+        }
+        "min_delta" => {
+            let (x, y) = (parse_int(a).ok()?, parse_int(b).ok()?);
+            Some(x.max(y).to_string())
+        }
+        "time_window" => {
+            let ((s1, e1), (s2, e2)) = (parse_window(a).ok()?, parse_window(b).ok()?);
+            let start = s1.max(s2);
+            let end = match (e1, e2) {
+                (Some(x), Some(y)) => Some(x.min(y)),
+                (x, y) => x.or(y),
+            };
+            let fmt = |v: Option<i64>| v.map(|v| v.to_string()).unwrap_or_default();
+            Some(format!("{}..{}", fmt(start), fmt(end)))
+        }
+        "containers" => {
+            let other: Vec<&str> = b.value.split(',').map(str::trim).collect();
+            let mut both: Vec<&str> = a.value.split(',').map(str::trim).filter(|c| other.contains(c)).collect();
+            both.sort_unstable();
+            both.dedup();
+            Some(both.join(","))
+        }
+        "risk_level" => {
+            let level = |c: &Constraint| c.value.strip_prefix('L')?.parse::<u8>().ok();
+            let (x, y) = (level(a)?, level(b)?);
+            Some(format!("L{}", x.max(y)))
+        }
+        _ => None,
+    }
+}
+
+fn invalid(constraint: &Constraint) -> PolicyError {
+    PolicyError::InvalidConstraint {
+        kind: constraint.kind.clone(),
+        value: constraint.value.clone(),
+    }
+}
+
+fn parse_int(constraint: &Constraint) -> Result<i128> {
+    constraint.value.trim().parse().map_err(|_| invalid(constraint))
+}
+
+fn parse_schedule(constraint: &Constraint) -> Result<Schedule> {
+    Schedule::parse(&constraint.value).map_err(|_| invalid(constraint))
+}
+
+fn parse_window(constraint: &Constraint) -> Result<(Option<i64>, Option<i64>)> {
+    let (start, end) = constraint.value.split_once("..").ok_or_else(|| invalid(constraint))?;
+    let bound = |s: &str| -> Result<Option<i64>> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Ok(None);
+        }
+        s.parse().map(Some).map_err(|_| invalid(constraint))
+    };
+    Ok((bound(start)?, bound(end)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn c(kind: &str, value: &str) -> Constraint {
+        Constraint {
+            kind: kind.to_string(),
+            value: value.to_string(),
+        }
+    }
+
+    fn facts(delta: i128, timestamp: i64) -> CommitFacts {
+        CommitFacts {
+            container_id: "wallet".to_string(),
+            intent_class: 0x01,
+            physics_delta: delta,
+            timestamp,
+            atom_size: None,
+        }
+    }
+
+    #[test]
+    fn test_max_amount_bounds_magnitude() {
+        let max = [c("max_amount", "10000")];
+        assert!(enforce(&max, &facts(-10000, 0)).is_ok());
+        assert!(matches!(
+            enforce(&max, &facts(-10001, 0)),
+            Err(PolicyError::ConstraintViolated { .. })
+        ));
+        assert!(enforce(&[c("min_delta", "0")], &facts(-1, 0)).is_err());
+    }
+
+    #[test]
+    fn test_time_window_and_containers() {
+        let window = [c("time_window", "100..200")];
+        assert!(enforce(&window, &facts(0, 100)).is_ok());
+        assert!(enforce(&window, &facts(0, 200)).is_err());
+        assert!(enforce(&[c("time_window", "..200")], &facts(0, -5)).is_ok());
+
+        assert!(enforce(&[c("containers", "ledger, wallet")], &facts(0, 0)).is_ok());
+        assert!(enforce(&[c("containers", "ledger")], &facts(0, 0)).is_err());
+    }
+
+    #[test]
+    fn test_schedule_at_evaluation_and_commit() {
+        // 2024-01-01 was a Monday; 12:00 and Saturday 12:00 UTC
+        let (monday_noon, saturday_noon) = (1_704_110_400, 1_704_110_400 + 5 * 86_400);
+        let hours = [c("schedule", "mon-fri 09:00-18:00")];
+        assert!(enforce(&hours, &facts(0, monday_noon)).is_ok());
+        assert!(matches!(
+            enforce(&hours, &facts(0, saturday_noon)),
+            Err(PolicyError::ConstraintViolated { .. })
+        ));
+
+        let allow = TranslationDecision::Allow {
+            intent_class: 0x01,
+            required_pact: None,
+            constraints: hours.to_vec(),
+        };
+        assert_eq!(apply_schedules(allow.clone(), monday_noon).unwrap(), allow);
+        assert!(matches!(
+            apply_schedules(allow, saturday_noon).unwrap(),
+            TranslationDecision::Deny { code: DenyCode::OutsideSchedule, .. }
+        ));
+        assert!(matches!(
+            enforce(&[c("schedule", "weekdays")], &facts(0, 0)),
+            Err(PolicyError::InvalidConstraint { .. })
+        ));
+    }
+
+    #[test]
+    fn test_tightest_per_kind() {
+        let merged = tightest(&[
+            c("max_amount", "10000"),
+            c("time_window", "100.."),
+            c("max_amount", "500"),
+            c("containers", "a,b,wallet"),
+            c("time_window", "..300"),
+            c("containers", "wallet,a"),
+            c("risk_level", "L2"),
+            c("risk_level", "L5"),
+            c("geo_fence", "eu"),
+            c("geo_fence", "us"),
+        ]);
+        assert_eq!(
+            merged,
+            vec![
+                c("max_amount", "500"),
+                c("time_window", "100..300"),
+                c("containers", "a,wallet"),
+                c("risk_level", "L5"),
+                c("geo_fence", "eu"),
+                c("geo_fence", "us"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_max_atom_size_needs_a_stored_atom() {
+        let max = [c("max_atom_size", "1048576")];
+        let stored = |size| CommitFacts { atom_size: Some(size), ..facts(0, 0) };
+        assert!(enforce(&max, &stored(1 << 20)).is_ok());
+        assert!(matches!(
+            enforce(&max, &stored((1 << 20) + 1)),
+            Err(PolicyError::ConstraintViolated { .. })
+        ));
+        assert!(matches!(enforce(&max, &facts(0, 0)), Err(PolicyError::ConstraintViolated { .. })));
+    }
+
+    #[test]
+    fn test_unknown_and_malformed_fail_closed() {
+        assert!(enforce(&[c("risk_level", "L5")], &facts(0, 0)).is_ok());
+        assert!(matches!(
+            enforce(&[c("geo_fence", "eu")], &facts(0, 0)),
+            Err(PolicyError::UnknownConstraint(_))
+        ));
+        assert!(matches!(
+            enforce(&[c("max_delta", "lots")], &facts(0, 0)),
+            Err(PolicyError::InvalidConstraint { .. })
+        ));
+    }
+}
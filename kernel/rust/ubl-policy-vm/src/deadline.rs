@@ -0,0 +1,180 @@
+//! Interrupting a running evaluation
+//!
+//! An [`Interrupt`] plays the part of WASM epoch interruption for the
+//! interpreters the VM runs: the CEL evaluator checks it before every node
+//! and between rules, the builtin rules before they start. Tripping it from
+//! any thread makes the evaluation return [`PolicyError::Timeout`] at its
+//! next check; a decision is never produced from a half-run policy, and
+//! timeouts, like every error, are never cached.
+//!
+//! With the `async` feature, [`PolicyVM::evaluate_async`] and
+//! [`PolicyVM::evaluate_resolved_async`] run the evaluation on tokio's
+//! blocking pool under a wall-clock budget: when the budget elapses the
+//! caller gets [`PolicyError::Timeout`] at once and the interrupt is tripped
+//! so the abandoned evaluation stops too. They take the shared
+//! `Arc<RwLock<PolicyVM>>` rather than `&self` because the evaluation
+//! outlives the caller's borrow, and a lock guard cannot be held across an
+//! await in a `Send` future.
+//!
+//! [`PolicyError::Timeout`]: crate::PolicyError::Timeout
+//! [`PolicyVM::evaluate_async`]: crate::PolicyVM::evaluate_async
+//! [`PolicyVM::evaluate_resolved_async`]: crate::PolicyVM::evaluate_resolved_async
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Handle that stops an evaluation; clones share one flag
+#[derive(Debug, Clone, Default)]
+pub struct Interrupt(Arc<AtomicBool>);
+
+impl Interrupt {
+    /// An interrupt not yet tripped
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stop every evaluation watching this interrupt
+    pub fn trip(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether [`Interrupt::trip`] has been called
+    pub fn is_tripped(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(feature = "async")]
+mod budget {
+    use std::sync::{Arc, RwLock};
+    use std::time::Duration;
+
+    use super::Interrupt;
+    use crate::{EvaluationContext, PolicyError, PolicyVM, Resolution, Result, TranslationDecision};
+
+    /// Run `f` on the blocking pool; past `budget`, trip its interrupt and time out
+    async fn within<T: Send + 'static>(
+        vm: &Arc<RwLock<PolicyVM>>,
+        budget: Duration,
+        f: impl FnOnce(&PolicyVM, &Interrupt) -> Result<T> + Send + 'static,
+    ) -> Result<T> {
+        let interrupt = Interrupt::new();
+        let (vm, watched) = (vm.clone(), interrupt.clone());
+        let task = tokio::task::spawn_blocking(move || f(&vm.read().unwrap(), &watched));
+        match tokio::time::timeout(budget, task).await {
+            Ok(joined) => joined.map_err(|e| PolicyError::ExecutionFailed(format!("evaluation task failed: {}", e)))?,
+            Err(_) => {
+                interrupt.trip();
+                Err(PolicyError::Timeout)
+            }
+        }
+    }
+
+    impl PolicyVM {
+        /// [`PolicyVM::evaluate`] under a wall-clock `budget` (see [`crate::deadline`])
+        pub async fn evaluate_async(
+            vm: &Arc<RwLock<PolicyVM>>,
+            policy_id: &str,
+            context: &EvaluationContext,
+            budget: Duration,
+        ) -> Result<TranslationDecision> {
+            let (policy_id, context) = (policy_id.to_string(), context.clone());
+            within(vm, budget, move |vm, interrupt| {
+                vm.evaluate_interruptible(&policy_id, &context, interrupt)
+            })
+            .await
+        }
+
+        /// [`PolicyVM::evaluate_resolved`] under a wall-clock `budget` for all layers together
+        pub async fn evaluate_resolved_async(
+            vm: &Arc<RwLock<PolicyVM>>,
+            container_id: &str,
+            context: &EvaluationContext,
+            budget: Duration,
+        ) -> Result<Resolution> {
+            let (container_id, context) = (container_id.to_string(), context.clone());
+            within(vm, budget, move |vm, interrupt| {
+                vm.evaluate_resolved_interruptible(&container_id, &context, interrupt)
+            })
+            .await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CelPolicy, EvaluationContext, Policy, PolicyError, PolicyVM, TranslationDecision};
+    use serde_json::json;
+
+    /// A CEL policy quadratic in the length of `intent.items`
+    fn slow_vm() -> PolicyVM {
+        let source: CelPolicy = serde_json::from_value(json!({"rules": [
+            {"when": "intent.items.all(x, intent.items.exists(y, y == x))", "allow": {"intent_class": 0}}
+        ]}))
+        .unwrap();
+        let bytecode = source.to_bytecode();
+        let mut vm = PolicyVM::new();
+        vm.register(Policy {
+            policy_id: "slow".to_string(),
+            version: "1.0".to_string(),
+            bytecode_hash: crate::bytecode_hash(&bytecode),
+            bytecode,
+            description: "quadratic".to_string(),
+            active_from: 0,
+        })
+        .unwrap();
+        vm
+    }
+
+    fn context(items: usize) -> EvaluationContext {
+        EvaluationContext {
+            container_id: "C.Test".to_string(),
+            actor: "alice".to_string(),
+            intent: json!({ "items": (0..items).collect::<Vec<_>>() }),
+            state: None,
+            timestamp: 0,
+            spec: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_tripped_interrupt_times_out() {
+        let vm = slow_vm();
+        let interrupt = Interrupt::new();
+        assert!(matches!(
+            vm.evaluate_interruptible("slow", &context(10), &interrupt),
+            Ok(TranslationDecision::Allow { .. })
+        ));
+        interrupt.trip();
+        assert!(matches!(
+            vm.evaluate_interruptible("slow", &context(10), &interrupt),
+            Err(PolicyError::Timeout)
+        ));
+        assert!(vm.evaluate("slow", &context(10)).is_ok());
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_budget_interrupts_evaluation() {
+        use std::sync::{Arc, RwLock};
+        use std::time::{Duration, Instant};
+
+        let vm = Arc::new(RwLock::new(slow_vm()));
+        let fast = PolicyVM::evaluate_async(&vm, "slow", &context(10), Duration::from_secs(5)).await;
+        assert!(matches!(fast, Ok(TranslationDecision::Allow { .. })));
+
+        let start = Instant::now();
+        let slow = PolicyVM::evaluate_async(&vm, "slow", &context(50_000), Duration::from_millis(5)).await;
+        assert!(matches!(slow, Err(PolicyError::Timeout)));
+        assert!(start.elapsed() < Duration::from_secs(1));
+        // The abandoned evaluation stops and releases its read lock
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while vm.try_write().is_err() {
+                tokio::time::sleep(Duration::from_millis(1)).await;
+            }
+        })
+        .await
+        .unwrap();
+    }
+}
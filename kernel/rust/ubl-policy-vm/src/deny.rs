@@ -0,0 +1,88 @@
+//! Canonical deny codes
+//!
+//! A Deny carries one of these codes plus an optional free-text detail.
+//! Callers branch on the code; the detail is for humans and may change
+//! between versions.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use ubl_errors::ErrorCatalog;
+
+/// Why a translation was denied (a commit denied by policy is answered 403)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, ErrorCatalog)]
+#[serde(rename_all = "snake_case")]
+#[catalog(source = "policy", status = 403)]
+pub enum DenyCode {
+    /// The intent type is not one the policy knows
+    #[catalog(code = "unknown_intent")]
+    UnknownIntent,
+    /// An amount is over what the policy allows
+    #[catalog(code = "amount_exceeded")]
+    AmountExceeded,
+    /// The actor may not perform this intent
+    #[catalog(code = "actor_blocked")]
+    ActorBlocked,
+    /// The intent needs a pact the commit does not carry
+    #[catalog(code = "pact_required")]
+    PactRequired,
+    /// Composed policies allow different intent classes
+    #[catalog(code = "intent_class_conflict")]
+    IntentClassConflict,
+    /// Composed policies require different pacts
+    #[catalog(code = "pact_conflict")]
+    PactConflict,
+    /// The evaluation time is outside the policy's `schedule` constraint
+    #[catalog(code = "outside_schedule")]
+    OutsideSchedule,
+    /// No policy produced a decision
+    #[catalog(code = "no_decision")]
+    NoDecision,
+    /// A policy rule with no canonical code
+    #[catalog(code = "other")]
+    Other,
+}
+
+impl DenyCode {
+    /// Wire name (`unknown_intent`, …), its code in the error catalog
+    pub fn as_str(self) -> &'static str {
+        self.code()
+    }
+}
+
+impl fmt::Display for DenyCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// `code` or `code: detail`, for logs and plain-text bodies
+pub fn describe(code: DenyCode, detail: Option<&str>) -> String {
+    match detail {
+        Some(d) => format!("{}: {}", code, d),
+        None => code.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TranslationDecision;
+
+    #[test]
+    fn test_deny_wire_shape() {
+        let d = TranslationDecision::deny(DenyCode::AmountExceeded, "over 10000");
+        assert_eq!(
+            serde_json::to_value(&d).unwrap(),
+            serde_json::json!({ "Deny": { "code": "amount_exceeded", "detail": "over 10000" } })
+        );
+        let bare: TranslationDecision = serde_json::from_str(r#"{"Deny":{"code":"actor_blocked"}}"#).unwrap();
+        assert_eq!(bare, TranslationDecision::Deny { code: DenyCode::ActorBlocked, detail: None });
+        assert_eq!(describe(DenyCode::PactRequired, Some("escrow")), "pact_required: escrow");
+
+        // The catalog lists each code under its wire name
+        for entry in DenyCode::catalog() {
+            let code: DenyCode = serde_json::from_value(serde_json::json!(entry.code)).unwrap();
+            assert_eq!(code.as_str(), entry.code);
+        }
+    }
+}
@@ -0,0 +1,184 @@
+//! External policy engines
+//!
+//! Teams that already author their rules for another engine register them
+//! as they are: the bytecode is [`MAGIC`], the engine name on one line, and
+//! the source in that engine's language ([`ExternalPolicy`]). The VM hands
+//! such a policy to the [`ExternalEngine`] registered under that name
+//! ([`PolicyVM::set_external_engine`]), which maps the
+//! [`EvaluationContext`] onto its own request schema and answers with a
+//! [`TranslationDecision`]. Composition, schedules, the decision cache and
+//! observers treat the result like any other backend's.
+//!
+//! With an engine registered, a policy for it is checked when it is
+//! registered and rejected with [`PolicyError::CompileFailed`] if the engine
+//! refuses the source. A policy naming an engine the VM does not have is
+//! registered (the engine may be set later, as on startup) but fails every
+//! evaluation with [`PolicyError::ExecutionFailed`]; it never falls back to
+//! the builtin rules.
+//!
+//! The engine must decide from the context alone, the same way on every
+//! host. [`crate::cedar`] (feature `cedar`) embeds Cedar. OPA would plug in
+//! the same way, running its Rego compiled to WASM; Rego in the subset of
+//! [`crate::rego`] can be imported to CEL instead.
+//!
+//! [`PolicyVM::set_external_engine`]: crate::PolicyVM::set_external_engine
+
+use crate::{EvaluationContext, Policy, PolicyError, Result, TranslationDecision};
+
+/// Bytecode prefix marking an external policy; the engine name and a newline
+/// follow it, then the source
+pub const MAGIC: &[u8] = b"ext/1\n";
+
+/// Whether `bytecode` is an external policy
+pub fn is_external(bytecode: &[u8]) -> bool {
+    bytecode.starts_with(MAGIC)
+}
+
+/// Source of a policy for an external engine
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExternalPolicy {
+    /// Engine name ([`ExternalEngine::name`])
+    pub engine: String,
+    /// Policy source, in the engine's language
+    pub source: String,
+}
+
+impl ExternalPolicy {
+    /// Policy for `engine`
+    pub fn new(engine: impl Into<String>, source: impl Into<String>) -> Self {
+        Self {
+            engine: engine.into(),
+            source: source.into(),
+        }
+    }
+
+    /// Policy bytecode: [`MAGIC`], the engine name, a newline, the source
+    pub fn to_bytecode(&self) -> Vec<u8> {
+        let mut out = MAGIC.to_vec();
+        out.extend(self.engine.as_bytes());
+        out.push(b'\n');
+        out.extend(self.source.as_bytes());
+        out
+    }
+
+    /// Split external policy bytecode into engine name and source
+    pub fn from_bytecode(bytecode: &[u8]) -> std::result::Result<Self, String> {
+        let body = bytecode.strip_prefix(MAGIC).ok_or("missing external policy header")?;
+        let body = std::str::from_utf8(body).map_err(|e| format!("source is not UTF-8: {}", e))?;
+        let (engine, source) = body.split_once('\n').ok_or("missing engine name")?;
+        if engine.is_empty() || !engine.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_' || b == b'-') {
+            return Err(format!("invalid engine name {:?}", engine));
+        }
+        Ok(Self::new(engine, source))
+    }
+}
+
+/// A policy engine outside the VM, evaluating [`ExternalPolicy`] sources
+pub trait ExternalEngine: Send + Sync {
+    /// Name policies select the engine by (`cedar`, …)
+    fn name(&self) -> &str;
+
+    /// Whether `source` is a policy the engine accepts; the error says why not
+    fn check(&self, source: &str) -> std::result::Result<(), String>;
+
+    /// Decide `context` under `policy`, whose bytecode carries `source`
+    fn evaluate(&self, policy: &Policy, source: &str, context: &EvaluationContext) -> Result<TranslationDecision>;
+}
+
+/// Engine name and source of `policy`, for [`crate::PolicyVM`]'s dispatch
+pub(crate) fn source(policy: &Policy) -> Result<ExternalPolicy> {
+    ExternalPolicy::from_bytecode(&policy.bytecode)
+        .map_err(|e| PolicyError::ExecutionFailed(format!("{}: {}", policy.policy_id, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bytecode_hash, DenyCode, PolicyVM, SpecVersion};
+    use std::sync::Arc;
+
+    /// Allows the actors listed in the source, one per line
+    struct AllowList;
+
+    impl ExternalEngine for AllowList {
+        fn name(&self) -> &str {
+            "allowlist"
+        }
+
+        fn check(&self, source: &str) -> std::result::Result<(), String> {
+            match source.lines().position(str::is_empty) {
+                Some(i) => Err(format!("line {}: empty actor", i + 1)),
+                None => Ok(()),
+            }
+        }
+
+        fn evaluate(&self, _: &Policy, source: &str, context: &EvaluationContext) -> Result<TranslationDecision> {
+            if source.lines().any(|a| a == context.actor) {
+                Ok(TranslationDecision::Allow {
+                    intent_class: 1,
+                    required_pact: None,
+                    constraints: vec![],
+                })
+            } else {
+                Ok(TranslationDecision::deny(DenyCode::ActorBlocked, "not listed"))
+            }
+        }
+    }
+
+    fn policy(source: &str) -> Policy {
+        let bytecode = ExternalPolicy::new("allowlist", source).to_bytecode();
+        Policy {
+            policy_id: "ext".to_string(),
+            version: "1".to_string(),
+            bytecode_hash: bytecode_hash(&bytecode),
+            bytecode,
+            description: String::new(),
+            active_from: 0,
+        }
+    }
+
+    fn context(actor: &str) -> EvaluationContext {
+        EvaluationContext {
+            container_id: "C.Test".to_string(),
+            actor: actor.to_string(),
+            intent: serde_json::json!({ "type": "transfer" }),
+            state: None,
+            timestamp: 1,
+            spec: SpecVersion::V1_0,
+        }
+    }
+
+    #[test]
+    fn test_bytecode_round_trip() {
+        let p = ExternalPolicy::new("cedar", "permit(principal, action, resource);\n");
+        assert!(is_external(&p.to_bytecode()));
+        assert_eq!(ExternalPolicy::from_bytecode(&p.to_bytecode()).unwrap(), p);
+        assert!(ExternalPolicy::from_bytecode(b"ext/1\nno source").is_err());
+        assert!(ExternalPolicy::from_bytecode(b"ext/1\nce dar\npermit").is_err());
+        assert!(ExternalPolicy::from_bytecode(b"cel/1\n{}").is_err());
+    }
+
+    #[test]
+    fn test_dispatch_to_engine() {
+        let mut vm = PolicyVM::new();
+        vm.register(policy("alice\nbob")).unwrap();
+        // No engine yet: registered, but never decided by the builtin rules
+        assert!(matches!(vm.evaluate("ext", &context("alice")), Err(PolicyError::ExecutionFailed(_))));
+
+        vm.set_external_engine(Arc::new(AllowList));
+        assert!(matches!(
+            vm.evaluate("ext", &context("alice")).unwrap(),
+            TranslationDecision::Allow { intent_class: 1, .. }
+        ));
+        assert!(matches!(
+            vm.evaluate("ext", &context("mallory")).unwrap(),
+            TranslationDecision::Deny { code: DenyCode::ActorBlocked, .. }
+        ));
+
+        // With the engine set, sources it refuses are not registered
+        let mut bad = policy("alice\n\nbob");
+        bad.version = "2".to_string();
+        bad.active_from = 1;
+        assert!(matches!(vm.register(bad), Err(PolicyError::CompileFailed { .. })));
+    }
+}
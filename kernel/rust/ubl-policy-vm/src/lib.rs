@@ -16,6 +16,48 @@
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+pub mod bundle;
+pub mod cache;
+#[cfg(feature = "cedar")]
+pub mod cedar;
+pub mod cel;
+pub mod compose;
+pub mod constraints;
+pub mod deadline;
+pub mod deny;
+pub mod external;
+pub mod migrate;
+pub mod module_cache;
+pub mod rego;
+pub mod rollout;
+#[cfg(feature = "wasmtime")]
+pub mod runtime;
+pub mod schedule;
+pub mod snapshot;
+pub mod state;
+pub mod wasm;
+
+pub use bundle::{BundleSignature, GovernanceKeys, PolicyBundle};
+pub use cache::CacheStats;
+#[cfg(feature = "cedar")]
+pub use cedar::CedarEngine;
+pub use cel::{CelPolicy, CelProgram};
+pub use compose::{CompositionMode, Resolution};
+pub use constraints::{enforce, violations, CommitFacts};
+pub use deadline::Interrupt;
+pub use deny::DenyCode;
+pub use external::{ExternalEngine, ExternalPolicy};
+pub use migrate::{MigrationReport, Migrator};
+pub use module_cache::ModuleCacheStats;
+pub use rego::RegoImport;
+pub use rollout::{BucketBy, Rollout, RolloutMonitor, RolloutStats, RolloutStep};
+#[cfg(feature = "wasmtime")]
+pub use runtime::WasmRuntime;
+pub use schedule::Schedule;
+pub use snapshot::PolicySnapshot;
+pub use state::StateReader;
+pub use ubl_link::SpecVersion;
+
 /// Errors from policy evaluation
 #[derive(Error, Debug, Clone, PartialEq, Eq)]
 pub enum PolicyError {
@@ -31,9 +73,130 @@ pub enum PolicyError {
     #[error("Invalid bytecode")]
     InvalidBytecode,
 
+    /// Policy source did not compile for its backend
+    #[error("Policy {policy_id} does not compile: {reason}")]
+    CompileFailed {
+        /// Policy identifier
+        policy_id: String,
+        /// Compiler message
+        reason: String,
+    },
+
+    /// WASM policy that could decide differently on different hosts
+    #[error("Policy {policy_id} is not deterministic: {reason}")]
+    NonDeterministic {
+        /// Policy identifier
+        policy_id: String,
+        /// What the analyzer found, and where
+        reason: String,
+    },
+
+    /// Rego source outside the importable subset
+    #[error("Rego import failed at line {line}: {reason}")]
+    RegoImport {
+        /// Source line (0 when not tied to one)
+        line: usize,
+        /// What was not understood
+        reason: String,
+    },
+
     /// Timeout during execution
     #[error("Execution timeout")]
     Timeout,
+
+    /// Version already registered with different content
+    #[error("Policy {policy_id} version {version} already registered")]
+    DuplicateVersion {
+        /// Policy identifier
+        policy_id: String,
+        /// Conflicting version
+        version: String,
+    },
+
+    /// No version of the policy was active at the evaluation time
+    #[error("Policy {policy_id} has no version active at {at}")]
+    NoActiveVersion {
+        /// Policy identifier
+        policy_id: String,
+        /// Evaluation timestamp
+        at: i64,
+    },
+
+    /// Declared bytecode hash does not match the bytecode
+    #[error("Bytecode hash mismatch: declared {declared}, computed {computed}")]
+    BytecodeHashMismatch {
+        /// Hash carried by the policy
+        declared: String,
+        /// BLAKE3 of the bytecode
+        computed: String,
+    },
+
+    /// Commit does not satisfy a constraint from the decision snapshot
+    #[error("Constraint {kind} violated: {reason}")]
+    ConstraintViolated {
+        /// Constraint kind
+        kind: String,
+        /// What the commit did against the bound
+        reason: String,
+    },
+
+    /// Constraint value cannot be interpreted for its kind
+    #[error("Invalid constraint {kind}: {value}")]
+    InvalidConstraint {
+        /// Constraint kind
+        kind: String,
+        /// Offending value
+        value: String,
+    },
+
+    /// Constraint kind the enforcer does not know; rejected rather than ignored
+    #[error("Unknown constraint kind: {0}")]
+    UnknownConstraint(String),
+
+    /// No policy is attached to the container
+    #[error("No policy attached to container {0}")]
+    NoPolicyAttached(String),
+
+    /// A representation migration could not be carried out or verified
+    #[error("Policy {policy_id} migration failed: {reason}")]
+    MigrationFailed {
+        /// Policy identifier
+        policy_id: String,
+        /// Step or check that failed
+        reason: String,
+    },
+
+    /// Governance keys are configured and the policy came without a bundle
+    #[error("Policy {0} must be registered as a signed bundle")]
+    UnsignedPolicy(String),
+
+    /// A governance key's signature over the bundle does not verify
+    #[error("Invalid governance signature from {0}")]
+    InvalidBundleSignature(String),
+
+    /// Too few governance keys signed the bundle
+    #[error("Policy {policy_id} has {got} governance signature(s), {need} required")]
+    InsufficientGovernanceSignatures {
+        /// Policy identifier
+        policy_id: String,
+        /// Distinct valid governance signatures
+        got: usize,
+        /// Configured threshold
+        need: usize,
+    },
+
+    /// Snapshot that cannot be imported as it stands
+    #[error("Invalid policy snapshot: {0}")]
+    InvalidSnapshot(String),
+
+    /// Rollout that cannot be applied to the policy's versions
+    #[error("Invalid rollout of {policy_id}: {reason}")]
+    InvalidRollout {
+        /// Policy identifier
+        policy_id: String,
+        /// What is wrong with it
+        reason: String,
+    },
 }
 
 /// Result type for policy operations
@@ -53,11 +216,24 @@ pub enum TranslationDecision {
     },
     /// Deny the translation
     Deny {
-        /// Reason for denial
-        reason: String,
+        /// Canonical reason, for callers to branch on
+        code: DenyCode,
+        /// Human-readable detail
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        detail: Option<String>,
     },
 }
 
+impl TranslationDecision {
+    /// Deny with `code` and a detail message
+    pub fn deny(code: DenyCode, detail: impl Into<String>) -> Self {
+        TranslationDecision::Deny {
+            code,
+            detail: Some(detail.into()),
+        }
+    }
+}
+
 /// A constraint from policy evaluation
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct Constraint {
@@ -67,8 +243,34 @@ pub struct Constraint {
     pub value: String,
 }
 
+/// One policy evaluation, as reported to an [`EvaluationObserver`]
+#[derive(Debug)]
+pub struct Evaluation<'a> {
+    /// Policy evaluated
+    pub policy_id: &'a str,
+    /// Decision, or why there is none
+    pub result: &'a Result<TranslationDecision>,
+    /// Wall time of the evaluation, cache lookup included
+    pub elapsed: std::time::Duration,
+    /// Answered from the decision cache
+    pub cached: bool,
+}
+
+/// Callback told of every [`PolicyVM::evaluate`] (metrics, tracing)
+pub type EvaluationObserver = std::sync::Arc<dyn Fn(&Evaluation<'_>) + Send + Sync>;
+
+/// Where the policies governing a container are bound
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BindingSource {
+    /// Attached to the container itself
+    Container,
+    /// Bound to a namespace prefix of the container id
+    Namespace(String),
+}
+
 /// Policy definition (SPEC-UBL-POLICY v1.0 §4)
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct Policy {
     /// Policy identifier
     pub policy_id: String,
@@ -86,6 +288,30 @@ pub struct Policy {
     
     /// Human-readable description
     pub description: String,
+
+    /// Timestamp from which this version governs evaluations
+    /// (compared against `EvaluationContext::timestamp`)
+    #[serde(default)]
+    pub active_from: i64,
+}
+
+/// BLAKE3 hex of policy bytecode, as carried in `Policy::bytecode_hash`
+pub fn bytecode_hash(bytecode: &[u8]) -> String {
+    hex::encode(blake3::hash(bytecode).as_bytes())
+}
+
+impl Policy {
+    /// Check that `bytecode_hash` matches the bytecode
+    pub fn verify_bytecode(&self) -> Result<()> {
+        let computed = bytecode_hash(&self.bytecode);
+        if computed != self.bytecode_hash {
+            return Err(PolicyError::BytecodeHashMismatch {
+                declared: self.bytecode_hash.clone(),
+                computed,
+            });
+        }
+        Ok(())
+    }
 }
 
 /// Policy evaluation context
@@ -100,16 +326,59 @@ pub struct EvaluationContext {
     /// Intent payload (JSON)
     pub intent: serde_json::Value,
     
-    /// Current state (optional)
+    /// Current state, materialized by the caller (optional); see
+    /// [`StateReader`] to read it on demand instead
     pub state: Option<serde_json::Value>,
     
     /// Timestamp
     pub timestamp: i64,
+
+    /// Spec version the container is committed under; contexts recorded
+    /// before versions were declared are v1.0
+    #[serde(default)]
+    pub spec: SpecVersion,
 }
 
 /// Policy VM - executes TDLN policies
+///
+/// Each policy id holds an ordered history of versions. A version governs
+/// from its `active_from` until the next version's `active_from`, so replaying
+/// a link with its original timestamp always selects the same version.
+///
+/// Containers can have several policies attached; [`PolicyVM::evaluate_all`]
+/// runs them in policy-id order and merges the decisions per the container's
+/// [`CompositionMode`]. Policies bound to namespaces prefixing its id apply
+/// too, below the container's own, in the priority order of [`compose`].
 pub struct PolicyVM {
-    policies: std::collections::HashMap<String, Policy>,
+    /// Versions per policy id, sorted by `active_from`
+    policies: std::collections::HashMap<String, Vec<Policy>>,
+    /// Policies attached per container, ordered by policy id
+    attachments: std::collections::HashMap<String, std::collections::BTreeSet<String>>,
+    /// Policies bound per namespace prefix, ordered by policy id
+    namespaces: std::collections::BTreeMap<String, std::collections::BTreeSet<String>>,
+    /// Compiled CEL programs per (policy id, version)
+    programs: std::collections::HashMap<(String, String), CelProgram>,
+    /// Governance signatures per (policy id, version) registered as bundles
+    signatures: std::collections::HashMap<(String, String), Vec<BundleSignature>>,
+    /// Composition mode per container (default `AllMustAllow`)
+    composition: std::collections::HashMap<String, CompositionMode>,
+    /// When set, only signed bundles are registered
+    governance: Option<GovernanceKeys>,
+    /// Opt-in decision cache, cleared on every policy-set change
+    cache: Option<std::sync::Mutex<cache::DecisionCache>>,
+    /// Executor of WASM bytecode, shared across rebuilt VMs
+    #[cfg(feature = "wasmtime")]
+    wasm_runtime: Option<std::sync::Arc<WasmRuntime>>,
+    /// Answers `balance()` and `field()` during evaluation
+    state_reader: Option<std::sync::Arc<dyn StateReader>>,
+    /// External policy engines by name, shared across rebuilt VMs
+    external: std::collections::HashMap<String, std::sync::Arc<dyn ExternalEngine>>,
+    /// Percentage rollouts per (policy id, candidate version)
+    rollouts: std::collections::HashMap<(String, String), Rollout>,
+    /// Counts decisions under rollouts, shared across rebuilt VMs
+    rollout_monitor: Option<std::sync::Arc<RolloutMonitor>>,
+    /// Told of every evaluation
+    observer: Option<EvaluationObserver>,
 }
 
 impl PolicyVM {
@@ -117,12 +386,419 @@ impl PolicyVM {
     pub fn new() -> Self {
         Self {
             policies: std::collections::HashMap::new(),
+            attachments: std::collections::HashMap::new(),
+            namespaces: std::collections::BTreeMap::new(),
+            programs: std::collections::HashMap::new(),
+            signatures: std::collections::HashMap::new(),
+            composition: std::collections::HashMap::new(),
+            governance: None,
+            cache: None,
+            #[cfg(feature = "wasmtime")]
+            wasm_runtime: None,
+            state_reader: None,
+            external: std::collections::HashMap::new(),
+            rollouts: std::collections::HashMap::new(),
+            rollout_monitor: None,
+            observer: None,
+        }
+    }
+
+    /// Report every evaluation to `observer` (`None` stops reporting)
+    pub fn set_observer(&mut self, observer: Option<EvaluationObserver>) {
+        self.observer = observer;
+    }
+
+    /// Observer set with [`PolicyVM::set_observer`]
+    pub fn observer(&self) -> Option<&EvaluationObserver> {
+        self.observer.as_ref()
+    }
+
+    /// Cache up to `capacity` decisions by context hash (see [`cache`])
+    pub fn enable_decision_cache(&mut self, capacity: usize) {
+        self.cache = Some(std::sync::Mutex::new(cache::DecisionCache::new(capacity)));
+    }
+
+    /// Decision cache counters, if the cache is enabled
+    pub fn cache_stats(&self) -> Option<CacheStats> {
+        self.cache.as_ref().map(|c| c.lock().unwrap().stats())
+    }
+
+    /// Run WASM policies on `runtime` (see [`runtime`]); `None` falls back
+    /// to the builtin rules
+    #[cfg(feature = "wasmtime")]
+    pub fn set_wasm_runtime(&mut self, runtime: Option<std::sync::Arc<WasmRuntime>>) {
+        self.wasm_runtime = runtime;
+    }
+
+    /// Runtime set with [`PolicyVM::set_wasm_runtime`]
+    #[cfg(feature = "wasmtime")]
+    pub fn wasm_runtime(&self) -> Option<&std::sync::Arc<WasmRuntime>> {
+        self.wasm_runtime.as_ref()
+    }
+
+    /// Read container state on demand through `reader` (see [`state`])
+    pub fn set_state_reader(&mut self, reader: Option<std::sync::Arc<dyn StateReader>>) {
+        self.state_reader = reader;
+    }
+
+    /// Reader set with [`PolicyVM::set_state_reader`]
+    pub fn state_reader(&self) -> Option<&std::sync::Arc<dyn StateReader>> {
+        self.state_reader.as_ref()
+    }
+
+    /// Evaluate external policies naming `engine` on it (see [`external`]),
+    /// replacing any engine of the same name
+    pub fn set_external_engine(&mut self, engine: std::sync::Arc<dyn ExternalEngine>) {
+        self.invalidate_cache();
+        self.external.insert(engine.name().to_string(), engine);
+    }
+
+    /// Engines set with [`PolicyVM::set_external_engine`]
+    pub fn external_engines(&self) -> impl Iterator<Item = &std::sync::Arc<dyn ExternalEngine>> {
+        self.external.values()
+    }
+
+    fn invalidate_cache(&mut self) {
+        if let Some(c) = &mut self.cache {
+            c.get_mut().unwrap().clear();
+        }
+    }
+
+    /// Require governance signatures on every policy registered from now on
+    pub fn set_governance_keys(&mut self, keys: Option<GovernanceKeys>) {
+        self.governance = keys;
+    }
+
+    /// Configured governance keys
+    pub fn governance_keys(&self) -> Option<&GovernanceKeys> {
+        self.governance.as_ref()
+    }
+
+    /// Attach a policy to a container. Returns false if it was already attached.
+    pub fn attach(&mut self, container_id: &str, policy_id: &str) -> bool {
+        self.attachments
+            .entry(container_id.to_string())
+            .or_default()
+            .insert(policy_id.to_string())
+    }
+
+    /// Detach a policy from a container. Returns false if it was not attached.
+    pub fn detach(&mut self, container_id: &str, policy_id: &str) -> bool {
+        let Some(set) = self.attachments.get_mut(container_id) else {
+            return false;
+        };
+        let removed = set.remove(policy_id);
+        if set.is_empty() {
+            self.attachments.remove(container_id);
+        }
+        removed
+    }
+
+    /// Policies attached to a container, in evaluation order
+    pub fn attached(&self, container_id: &str) -> Vec<&str> {
+        self.attachments
+            .get(container_id)
+            .map(|set| set.iter().map(String::as_str).collect())
+            .unwrap_or_default()
+    }
+
+    /// Bind a policy to every container whose id starts with `namespace`
+    /// (`""` binds every container). Returns false if it was already bound.
+    pub fn bind_namespace(&mut self, namespace: &str, policy_id: &str) -> bool {
+        self.namespaces
+            .entry(namespace.to_string())
+            .or_default()
+            .insert(policy_id.to_string())
+    }
+
+    /// Remove a namespace binding. Returns false if it was not bound.
+    pub fn unbind_namespace(&mut self, namespace: &str, policy_id: &str) -> bool {
+        let Some(set) = self.namespaces.get_mut(namespace) else {
+            return false;
+        };
+        let removed = set.remove(policy_id);
+        if set.is_empty() {
+            self.namespaces.remove(namespace);
+        }
+        removed
+    }
+
+    /// Every namespace binding, as `(namespace, policy ids)` in namespace order
+    pub fn namespace_bindings(&self) -> Vec<(&str, Vec<&str>)> {
+        self.namespaces
+            .iter()
+            .map(|(ns, set)| (ns.as_str(), set.iter().map(String::as_str).collect()))
+            .collect()
+    }
+
+    /// Highest-priority layer of policies bound to a container, and where
+    ///
+    /// Attachments on the container itself come first; otherwise the longest
+    /// namespace that prefixes the id. `None` when nothing is bound. Every
+    /// layer takes part in evaluation ([`PolicyVM::layers`]).
+    pub fn resolve(&self, container_id: &str) -> Option<(BindingSource, Vec<&str>)> {
+        let attached = self.attached(container_id);
+        if !attached.is_empty() {
+            return Some((BindingSource::Container, attached));
+        }
+        self.namespaces
+            .iter()
+            .filter(|(ns, _)| container_id.starts_with(ns.as_str()))
+            .max_by_key(|(ns, _)| ns.len())
+            .map(|(ns, set)| {
+                (
+                    BindingSource::Namespace(ns.clone()),
+                    set.iter().map(String::as_str).collect(),
+                )
+            })
+    }
+
+    /// Every layer of policies matching a container, highest priority first:
+    /// its attachments, then namespaces prefixing its id from longest to
+    /// shortest (the global, empty namespace last). See [`compose`].
+    pub fn layers(&self, container_id: &str) -> Vec<(BindingSource, Vec<&str>)> {
+        let mut layers = Vec::new();
+        let attached = self.attached(container_id);
+        if !attached.is_empty() {
+            layers.push((BindingSource::Container, attached));
+        }
+        let mut namespaces: Vec<_> = self
+            .namespaces
+            .iter()
+            .filter(|(ns, _)| container_id.starts_with(ns.as_str()))
+            .collect();
+        namespaces.sort_by_key(|(ns, _)| std::cmp::Reverse(ns.len()));
+        layers.extend(
+            namespaces
+                .into_iter()
+                .map(|(ns, set)| (BindingSource::Namespace(ns.clone()), set.iter().map(String::as_str).collect())),
+        );
+        layers
+    }
+
+    /// Set how a container's policy decisions are combined
+    pub fn set_composition(&mut self, container_id: &str, mode: CompositionMode) {
+        self.composition.insert(container_id.to_string(), mode);
+    }
+
+    /// Composition mode of a container
+    pub fn composition(&self, container_id: &str) -> CompositionMode {
+        self.composition.get(container_id).copied().unwrap_or_default()
+    }
+
+    /// Roll a registered version out to a share of contexts (see
+    /// [`rollout`]), replacing any rollout of the same version. The version
+    /// needs one before it for the rest to stay on.
+    pub fn set_rollout(&mut self, rollout: Rollout) -> Result<()> {
+        let invalid = |reason: String| PolicyError::InvalidRollout {
+            policy_id: rollout.policy_id.clone(),
+            reason,
+        };
+        rollout.check().map_err(invalid)?;
+        let versions = self.versions(&rollout.policy_id);
+        match versions.iter().position(|p| p.version == rollout.version) {
+            None => return Err(invalid(format!("version {} is not registered", rollout.version))),
+            Some(0) => return Err(invalid(format!("version {} has no version before it", rollout.version))),
+            Some(_) => {}
+        }
+        self.invalidate_cache();
+        self.rollouts
+            .insert((rollout.policy_id.clone(), rollout.version.clone()), rollout);
+        Ok(())
+    }
+
+    /// Rollout of a version, if it has one
+    pub fn rollout(&self, policy_id: &str, version: &str) -> Option<&Rollout> {
+        self.rollouts.get(&(policy_id.to_string(), version.to_string()))
+    }
+
+    /// Every rollout, ordered by policy id and version
+    pub fn rollouts(&self) -> Vec<&Rollout> {
+        let mut all: Vec<&Rollout> = self.rollouts.values().collect();
+        all.sort_by(|a, b| (&a.policy_id, &a.version).cmp(&(&b.policy_id, &b.version)));
+        all
+    }
+
+    /// Count decisions under rollouts in `monitor` (`None` stops counting)
+    pub fn set_rollout_monitor(&mut self, monitor: Option<std::sync::Arc<RolloutMonitor>>) {
+        self.rollout_monitor = monitor;
+    }
+
+    /// Monitor set with [`PolicyVM::set_rollout_monitor`]
+    pub fn rollout_monitor(&self) -> Option<&std::sync::Arc<RolloutMonitor>> {
+        self.rollout_monitor.as_ref()
+    }
+
+    /// Rollout of the version active at `at`, with that version (the
+    /// candidate) and the one before it (the baseline)
+    fn rollout_at(&self, policy_id: &str, at: i64) -> Option<(&Rollout, &Policy, &Policy)> {
+        let candidate = self.active_version(policy_id, at).ok()?;
+        let rollout = self.rollout(policy_id, &candidate.version)?;
+        let versions = self.versions(policy_id);
+        let idx = versions.iter().position(|p| p.version == candidate.version)?;
+        Some((rollout, candidate, versions.get(idx.checked_sub(1)?)?))
+    }
+
+    /// The version that governs `context`: the one active at its timestamp,
+    /// or, for contexts a rollout keeps off it, the one before
+    pub fn version_for(&self, policy_id: &str, context: &EvaluationContext) -> Result<&Policy> {
+        match self.rollout_at(policy_id, context.timestamp) {
+            Some((rollout, candidate, _)) if rollout.selects(context) => Ok(candidate),
+            Some((_, _, baseline)) => Ok(baseline),
+            None => self.active_version(policy_id, context.timestamp),
+        }
+    }
+
+    /// Register a policy version
+    ///
+    /// Re-registering an identical version is a no-op; a version string or
+    /// activation time that is already taken with different content is
+    /// rejected, since it would make historical evaluations ambiguous.
+    /// With governance keys configured, use [`PolicyVM::register_bundle`].
+    pub fn register(&mut self, policy: Policy) -> Result<()> {
+        if self.governance.is_some() {
+            return Err(PolicyError::UnsignedPolicy(policy.policy_id));
+        }
+        self.insert(policy)
+    }
+
+    /// Register a signed policy bundle
+    ///
+    /// The bytecode must match its hash and, when governance keys are
+    /// configured, the bundle must carry enough governance signatures.
+    pub fn register_bundle(&mut self, bundle: &PolicyBundle) -> Result<()> {
+        if let Some(keys) = &self.governance {
+            keys.verify(bundle)?;
         }
+        let policy = bundle.clone().into_policy()?;
+        let key = (policy.policy_id.clone(), policy.version.clone());
+        self.insert(policy)?;
+        // Kept so snapshots carry them ([`snapshot`])
+        let kept = self.signatures.entry(key).or_default();
+        for sig in &bundle.signatures {
+            if !kept.contains(sig) {
+                kept.push(sig.clone());
+            }
+        }
+        Ok(())
+    }
+
+    fn insert(&mut self, policy: Policy) -> Result<()> {
+        let program = if cel::is_cel(&policy.bytecode) {
+            Some(
+                CelProgram::from_bytecode(&policy.bytecode).map_err(|reason| PolicyError::CompileFailed {
+                    policy_id: policy.policy_id.clone(),
+                    reason,
+                })?,
+            )
+        } else {
+            if wasm::is_wasm(&policy.bytecode) {
+                wasm::analyze(&policy.policy_id, &policy.bytecode)?;
+            }
+            if external::is_external(&policy.bytecode) {
+                let compile_failed = |reason| PolicyError::CompileFailed {
+                    policy_id: policy.policy_id.clone(),
+                    reason,
+                };
+                let ext = ExternalPolicy::from_bytecode(&policy.bytecode).map_err(compile_failed)?;
+                if let Some(engine) = self.external.get(&ext.engine) {
+                    engine.check(&ext.source).map_err(compile_failed)?;
+                }
+            }
+            None
+        };
+        self.invalidate_cache();
+        let versions = self.policies.entry(policy.policy_id.clone()).or_default();
+        if let Some(existing) = versions
+            .iter()
+            .find(|p| p.version == policy.version || p.active_from == policy.active_from)
+        {
+            if existing.version == policy.version
+                && existing.bytecode_hash == policy.bytecode_hash
+                && existing.active_from == policy.active_from
+            {
+                return Ok(());
+            }
+            return Err(PolicyError::DuplicateVersion {
+                policy_id: policy.policy_id,
+                version: policy.version,
+            });
+        }
+        if let Some(program) = program {
+            self.programs
+                .insert((policy.policy_id.clone(), policy.version.clone()), program);
+        }
+        let at = versions.partition_point(|p| p.active_from < policy.active_from);
+        versions.insert(at, policy);
+        Ok(())
+    }
+
+    /// Number of registered policy ids
+    pub fn len(&self) -> usize {
+        self.policies.len()
+    }
+
+    /// Whether no policy is registered
+    pub fn is_empty(&self) -> bool {
+        self.policies.is_empty()
+    }
+
+    /// Latest registered version of a policy
+    pub fn get(&self, policy_id: &str) -> Option<&Policy> {
+        self.policies.get(policy_id).and_then(|v| v.last())
+    }
+
+    /// A specific version of a policy
+    pub fn get_version(&self, policy_id: &str, version: &str) -> Option<&Policy> {
+        self.policies
+            .get(policy_id)
+            .and_then(|v| v.iter().find(|p| p.version == version))
+    }
+
+    /// All versions of a policy, oldest activation first
+    pub fn versions(&self, policy_id: &str) -> &[Policy] {
+        self.policies.get(policy_id).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// The version that governs evaluations at `at`
+    pub fn active_version(&self, policy_id: &str, at: i64) -> Result<&Policy> {
+        let versions = self
+            .policies
+            .get(policy_id)
+            .ok_or_else(|| PolicyError::PolicyNotFound(policy_id.to_string()))?;
+        versions
+            .iter()
+            .rev()
+            .find(|p| p.active_from <= at)
+            .ok_or_else(|| PolicyError::NoActiveVersion {
+                policy_id: policy_id.to_string(),
+                at,
+            })
+    }
+
+    /// Remove every version of a policy, returning them
+    pub fn remove(&mut self, policy_id: &str) -> Vec<Policy> {
+        self.invalidate_cache();
+        self.programs.retain(|(id, _), _| id != policy_id);
+        self.signatures.retain(|(id, _), _| id != policy_id);
+        self.rollouts.retain(|(id, _), _| id != policy_id);
+        self.policies.remove(policy_id).unwrap_or_default()
     }
 
-    /// Register a policy
-    pub fn register(&mut self, policy: Policy) {
-        self.policies.insert(policy.policy_id.clone(), policy);
+    /// Remove one version of a policy
+    pub fn remove_version(&mut self, policy_id: &str, version: &str) -> Option<Policy> {
+        self.invalidate_cache();
+        let versions = self.policies.get_mut(policy_id)?;
+        let idx = versions.iter().position(|p| p.version == version)?;
+        let removed = versions.remove(idx);
+        let key = (policy_id.to_string(), version.to_string());
+        self.programs.remove(&key);
+        self.signatures.remove(&key);
+        self.rollouts.remove(&key);
+        if versions.is_empty() {
+            self.policies.remove(policy_id);
+        }
+        Some(removed)
     }
 
     /// Evaluate a policy (SPEC-UBL-POLICY v1.0 §6)
@@ -132,16 +808,149 @@ impl PolicyVM {
     /// 2. Execute it in a sandboxed environment
     /// 3. Return the translation decision
     /// 
-    /// For now, we implement a simple rule-based system
+    /// For now, we implement a simple rule-based system; CEL policies
+    /// ([`cel`]) run their compiled rules instead
+    ///
+    /// With the decision cache enabled, an identical context is answered
+    /// from the cache; errors are never cached. An observer
+    /// ([`PolicyVM::set_observer`]) is told of each evaluation either way.
     pub fn evaluate(
         &self,
         policy_id: &str,
         context: &EvaluationContext,
     ) -> Result<TranslationDecision> {
-        let _policy = self
-            .policies
-            .get(policy_id)
-            .ok_or_else(|| PolicyError::PolicyNotFound(policy_id.to_string()))?;
+        self.evaluate_watched(policy_id, context, None)
+    }
+
+    /// [`PolicyVM::evaluate`], stopping with [`PolicyError::Timeout`] once
+    /// `interrupt` is tripped (see [`deadline`])
+    pub fn evaluate_interruptible(
+        &self,
+        policy_id: &str,
+        context: &EvaluationContext,
+        interrupt: &Interrupt,
+    ) -> Result<TranslationDecision> {
+        self.evaluate_watched(policy_id, context, Some(interrupt))
+    }
+
+    fn evaluate_watched(
+        &self,
+        policy_id: &str,
+        context: &EvaluationContext,
+        interrupt: Option<&Interrupt>,
+    ) -> Result<TranslationDecision> {
+        let Some(observe) = &self.observer else {
+            return self.evaluate_cached(policy_id, context, interrupt).0;
+        };
+        let start = std::time::Instant::now();
+        let (result, cached) = self.evaluate_cached(policy_id, context, interrupt);
+        observe(&Evaluation {
+            policy_id,
+            result: &result,
+            elapsed: start.elapsed(),
+            cached,
+        });
+        result
+    }
+
+    /// Decision, and whether the cache answered it
+    fn evaluate_cached(
+        &self,
+        policy_id: &str,
+        context: &EvaluationContext,
+        interrupt: Option<&Interrupt>,
+    ) -> (Result<TranslationDecision>, bool) {
+        let Some(cache) = &self.cache else {
+            return (self.decide(policy_id, context, interrupt), false);
+        };
+        // Read state is not part of the key
+        let reads_state = self
+            .version_for(policy_id, context)
+            .ok()
+            .and_then(|p| self.programs.get(&(p.policy_id.clone(), p.version.clone())))
+            .is_some_and(CelProgram::reads_state);
+        let Some(key) = cache::key(policy_id, context).filter(|_| !reads_state) else {
+            return (self.decide(policy_id, context, interrupt), false);
+        };
+        if let Some(decision) = cache.lock().unwrap().get(&key) {
+            return (Ok(decision), true);
+        }
+        let decision = self.decide(policy_id, context, interrupt);
+        if let Ok(d) = &decision {
+            cache.lock().unwrap().insert(key, d.clone());
+        }
+        (decision, false)
+    }
+
+    /// Decision under the version governing `context`, counted by the
+    /// rollout monitor when a rollout is under way
+    fn decide(
+        &self,
+        policy_id: &str,
+        context: &EvaluationContext,
+        interrupt: Option<&Interrupt>,
+    ) -> Result<TranslationDecision> {
+        let Some((rollout, candidate, baseline)) = self.rollout_at(policy_id, context.timestamp) else {
+            return self.decide_under(self.active_version(policy_id, context.timestamp)?, context, interrupt);
+        };
+        if !rollout.selects(context) {
+            let decision = self.decide_under(baseline, context, interrupt)?;
+            if let Some(monitor) = &self.rollout_monitor {
+                monitor.baseline(rollout, &decision);
+            }
+            return Ok(decision);
+        }
+        let decision = self.decide_under(candidate, context, interrupt)?;
+        if let Some(monitor) = &self.rollout_monitor {
+            // Off the record: an error or timeout only leaves it uncompared
+            let shadow = self.decide_under(baseline, context, interrupt).ok();
+            monitor.candidate(rollout, &decision, shadow.as_ref());
+        }
+        Ok(decision)
+    }
+
+    /// Rule decision of `policy` under `context.spec`, with `schedule`
+    /// constraints checked at `context.timestamp`
+    fn decide_under(
+        &self,
+        policy: &Policy,
+        context: &EvaluationContext,
+        interrupt: Option<&Interrupt>,
+    ) -> Result<TranslationDecision> {
+        let decision = self.run(policy, context, interrupt)?;
+        match context.spec {
+            SpecVersion::V1_0 => constraints::apply_schedules(decision, context.timestamp),
+        }
+    }
+
+    fn run(
+        &self,
+        policy: &Policy,
+        context: &EvaluationContext,
+        interrupt: Option<&Interrupt>,
+    ) -> Result<TranslationDecision> {
+        if let Some(program) = self
+            .programs
+            .get(&(policy.policy_id.clone(), policy.version.clone()))
+        {
+            return program.run(context, interrupt, self.state_reader.as_deref());
+        }
+        if interrupt.is_some_and(Interrupt::is_tripped) {
+            return Err(PolicyError::Timeout);
+        }
+        #[cfg(feature = "wasmtime")]
+        if let Some(runtime) = &self.wasm_runtime {
+            if wasm::is_wasm(&policy.bytecode) {
+                return runtime.evaluate(policy, context, interrupt);
+            }
+        }
+        if external::is_external(&policy.bytecode) {
+            let ext = external::source(policy)?;
+            let engine = self.external.get(&ext.engine).ok_or_else(|| {
+                PolicyError::ExecutionFailed(format!("{}: no {} engine configured", policy.policy_id, ext.engine))
+            })?;
+            return engine.evaluate(policy, &ext.source, context);
+        }
 
         // Simple rule-based evaluation
         // In production, this would execute WASM
@@ -203,11 +1012,60 @@ impl PolicyVM {
                 }],
             }),
             
-            _ => Ok(TranslationDecision::Deny {
-                reason: format!("Unknown intent type: {}", intent_type),
-            }),
+            _ => Ok(TranslationDecision::deny(
+                DenyCode::UnknownIntent,
+                format!("Unknown intent type: {}", intent_type),
+            )),
         }
     }
+
+    /// Evaluate every policy governing a container ([`PolicyVM::layers`]) and merge the decisions
+    ///
+    /// Each policy is evaluated at `context.timestamp` with its active
+    /// version; an error from any policy fails the whole evaluation rather
+    /// than silently dropping it from the composition.
+    pub fn evaluate_all(&self, container_id: &str, context: &EvaluationContext) -> Result<TranslationDecision> {
+        self.evaluate_resolved(container_id, context).map(|r| r.decision)
+    }
+
+    /// [`PolicyVM::evaluate_all`], with the policy that decided ([`compose::resolve`])
+    pub fn evaluate_resolved(&self, container_id: &str, context: &EvaluationContext) -> Result<Resolution> {
+        self.resolve_watched(container_id, context, None)
+    }
+
+    /// [`PolicyVM::evaluate_resolved`], stopping with [`PolicyError::Timeout`]
+    /// once `interrupt` is tripped
+    pub fn evaluate_resolved_interruptible(
+        &self,
+        container_id: &str,
+        context: &EvaluationContext,
+        interrupt: &Interrupt,
+    ) -> Result<Resolution> {
+        self.resolve_watched(container_id, context, Some(interrupt))
+    }
+
+    fn resolve_watched(
+        &self,
+        container_id: &str,
+        context: &EvaluationContext,
+        interrupt: Option<&Interrupt>,
+    ) -> Result<Resolution> {
+        let layers = self.layers(container_id);
+        if layers.is_empty() {
+            return Err(PolicyError::NoPolicyAttached(container_id.to_string()));
+        }
+        let evaluated = layers
+            .into_iter()
+            .map(|(source, ids)| {
+                let decisions = ids
+                    .into_iter()
+                    .map(|id| Ok((id.to_string(), self.evaluate_watched(id, context, interrupt)?)))
+                    .collect::<Result<Vec<_>>>()?;
+                Ok((source, decisions))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(compose::resolve(self.composition(container_id), &evaluated))
+    }
 }
 
 impl Default for PolicyVM {
@@ -233,6 +1091,7 @@ mod tests {
             intent,
             state: None,
             timestamp: 1000,
+            spec: Default::default(),
         }
     }
 
@@ -245,7 +1104,9 @@ mod tests {
             bytecode_hash: "test".to_string(),
             bytecode: vec![],
             description: "Default policy".to_string(),
-        });
+            active_from: 0,
+        })
+        .unwrap();
 
         let context = make_context("observe", None);
         let decision = vm.evaluate("default", &context).unwrap();
@@ -267,7 +1128,9 @@ mod tests {
             bytecode_hash: "test".to_string(),
             bytecode: vec![],
             description: "Default policy".to_string(),
-        });
+            active_from: 0,
+        })
+        .unwrap();
 
         let context = make_context("transfer", Some(100));
         let decision = vm.evaluate("default", &context).unwrap();
@@ -294,7 +1157,9 @@ mod tests {
             bytecode_hash: "test".to_string(),
             bytecode: vec![],
             description: "Default policy".to_string(),
-        });
+            active_from: 0,
+        })
+        .unwrap();
 
         let context = make_context("transfer", Some(20000));
         let decision = vm.evaluate("default", &context).unwrap();
@@ -318,7 +1183,9 @@ mod tests {
             bytecode_hash: "test".to_string(),
             bytecode: vec![],
             description: "Default policy".to_string(),
-        });
+            active_from: 0,
+        })
+        .unwrap();
 
         let context = make_context("evolve", None);
         let decision = vm.evaluate("default", &context).unwrap();
@@ -345,11 +1212,344 @@ mod tests {
             bytecode_hash: "test".to_string(),
             bytecode: vec![],
             description: "Default policy".to_string(),
-        });
+            active_from: 0,
+        })
+        .unwrap();
 
         let context = make_context("hack_the_planet", None);
         let decision = vm.evaluate("default", &context).unwrap();
 
-        assert!(matches!(decision, TranslationDecision::Deny { .. }));
+        assert!(matches!(
+            decision,
+            TranslationDecision::Deny { code: DenyCode::UnknownIntent, .. }
+        ));
+    }
+
+    #[test]
+    fn test_verify_bytecode_hash() {
+        let bytecode = b"\0asm policy".to_vec();
+        let mut policy = Policy {
+            policy_id: "p".to_string(),
+            version: "1.0".to_string(),
+            bytecode_hash: bytecode_hash(&bytecode),
+            bytecode,
+            description: String::new(),
+            active_from: 0,
+        };
+        assert!(policy.verify_bytecode().is_ok());
+
+        policy.bytecode.push(0);
+        assert!(matches!(
+            policy.verify_bytecode(),
+            Err(PolicyError::BytecodeHashMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_remove_policy() {
+        let mut vm = PolicyVM::new();
+        vm.register(Policy {
+            policy_id: "default".to_string(),
+            version: "1.0".to_string(),
+            bytecode_hash: "test".to_string(),
+            bytecode: vec![],
+            description: "Default policy".to_string(),
+            active_from: 0,
+        })
+        .unwrap();
+        assert!(vm.get("default").is_some());
+        assert_eq!(vm.remove("default").len(), 1);
+        assert!(matches!(
+            vm.evaluate("default", &make_context("observe", None)),
+            Err(PolicyError::PolicyNotFound(_))
+        ));
+    }
+
+    fn make_version(version: &str, active_from: i64) -> Policy {
+        Policy {
+            policy_id: "versioned".to_string(),
+            version: version.to_string(),
+            bytecode_hash: format!("hash-{}", version),
+            bytecode: vec![],
+            description: String::new(),
+            active_from,
+        }
+    }
+
+    #[test]
+    fn test_evaluate_selects_version_active_at_timestamp() {
+        let mut vm = PolicyVM::new();
+        // Registration order must not matter
+        vm.register(make_version("2.0", 2000)).unwrap();
+        vm.register(make_version("1.0", 500)).unwrap();
+
+        assert_eq!(vm.active_version("versioned", 1000).unwrap().version, "1.0");
+        assert_eq!(vm.active_version("versioned", 2000).unwrap().version, "2.0");
+        assert_eq!(vm.active_version("versioned", 9999).unwrap().version, "2.0");
+        assert!(matches!(
+            vm.active_version("versioned", 100),
+            Err(PolicyError::NoActiveVersion { .. })
+        ));
+
+        let mut early = make_context("observe", None);
+        early.timestamp = 100;
+        assert!(matches!(
+            vm.evaluate("versioned", &early),
+            Err(PolicyError::NoActiveVersion { .. })
+        ));
+    }
+
+    #[test]
+    fn test_evaluate_all_composes_attached_policies() {
+        let mut vm = PolicyVM::new();
+        vm.register(make_version("1.0", 0)).unwrap();
+        let mut other = make_version("1.0", 0);
+        other.policy_id = "limits".to_string();
+        vm.register(other).unwrap();
+
+        let context = make_context("transfer", Some(20000));
+        assert!(matches!(
+            vm.evaluate_all("test", &context),
+            Err(PolicyError::NoPolicyAttached(_))
+        ));
+
+        assert!(vm.attach("test", "versioned"));
+        assert!(vm.attach("test", "limits"));
+        assert!(!vm.attach("test", "limits"));
+        assert_eq!(vm.attached("test"), vec!["limits", "versioned"]);
+
+        match vm.evaluate_all("test", &context).unwrap() {
+            TranslationDecision::Allow { required_pact, constraints, .. } => {
+                assert_eq!(required_pact.as_deref(), Some("high_value_transfer"));
+                assert_eq!(constraints.len(), 1);
+            }
+            _ => panic!("Expected Allow"),
+        }
+
+        // Deny carries the first denying policy in id order
+        match vm.evaluate_all("test", &make_context("hack_the_planet", None)).unwrap() {
+            TranslationDecision::Deny { code, detail } => {
+                assert_eq!(code, DenyCode::UnknownIntent);
+                assert!(detail.unwrap().starts_with("limits: "));
+            }
+            _ => panic!("Expected Deny"),
+        }
+
+        // A policy with nothing active fails the composition, it is not skipped
+        let mut late = make_version("1.0", 5000);
+        late.policy_id = "late".to_string();
+        vm.register(late).unwrap();
+        vm.attach("test", "late");
+        assert!(matches!(
+            vm.evaluate_all("test", &context),
+            Err(PolicyError::NoActiveVersion { .. })
+        ));
+        assert!(vm.detach("test", "late"));
+        assert_eq!(vm.composition("test"), CompositionMode::AllMustAllow);
+    }
+
+    #[test]
+    fn test_resolve_falls_back_to_longest_namespace() {
+        let mut vm = PolicyVM::new();
+        assert_eq!(vm.resolve("C.Bank.Treasury"), None);
+
+        assert!(vm.bind_namespace("", "global"));
+        assert!(vm.bind_namespace("C.Bank.", "bank"));
+        assert!(vm.bind_namespace("C.", "c"));
+        assert!(!vm.bind_namespace("C.", "c"));
+        assert_eq!(
+            vm.resolve("C.Bank.Treasury"),
+            Some((BindingSource::Namespace("C.Bank.".to_string()), vec!["bank"]))
+        );
+        assert_eq!(
+            vm.resolve("C.Shop"),
+            Some((BindingSource::Namespace("C.".to_string()), vec!["c"]))
+        );
+        assert_eq!(
+            vm.resolve("repo://t/r"),
+            Some((BindingSource::Namespace(String::new()), vec!["global"]))
+        );
+
+        // Attachments on the container itself come first; every layer still applies
+        vm.attach("C.Bank.Treasury", "own");
+        assert_eq!(vm.resolve("C.Bank.Treasury"), Some((BindingSource::Container, vec!["own"])));
+        assert_eq!(
+            vm.layers("C.Bank.Treasury"),
+            vec![
+                (BindingSource::Container, vec!["own"]),
+                (BindingSource::Namespace("C.Bank.".to_string()), vec!["bank"]),
+                (BindingSource::Namespace("C.".to_string()), vec!["c"]),
+                (BindingSource::Namespace(String::new()), vec!["global"]),
+            ]
+        );
+
+        assert!(vm.unbind_namespace("C.Bank.", "bank"));
+        assert!(!vm.unbind_namespace("C.Bank.", "bank"));
+        assert_eq!(vm.namespace_bindings(), vec![("", vec!["global"]), ("C.", vec!["c"])]);
+    }
+
+    #[test]
+    fn test_register_rejects_conflicting_version() {
+        let mut vm = PolicyVM::new();
+        vm.register(make_version("1.0", 0)).unwrap();
+        // Identical re-registration is idempotent
+        vm.register(make_version("1.0", 0)).unwrap();
+
+        let mut changed = make_version("1.0", 0);
+        changed.bytecode_hash = "other".to_string();
+        assert!(matches!(
+            vm.register(changed),
+            Err(PolicyError::DuplicateVersion { .. })
+        ));
+        // Same activation time as an existing version is ambiguous
+        assert!(vm.register(make_version("1.1", 0)).is_err());
+
+        assert!(vm.remove_version("versioned", "1.0").is_some());
+        assert!(vm.get("versioned").is_none());
+    }
+
+    #[test]
+    fn test_governance_requires_signed_bundle() {
+        let (gov, key) = ubl_kernel::generate_keypair();
+        let mut vm = PolicyVM::new();
+        vm.set_governance_keys(Some(GovernanceKeys::new([&gov], 1)));
+
+        let bytecode = vec![0u8];
+        let policy = Policy {
+            policy_id: "governed".to_string(),
+            version: "1".to_string(),
+            bytecode_hash: bytecode_hash(&bytecode),
+            bytecode,
+            description: String::new(),
+            active_from: 0,
+        };
+        assert!(matches!(
+            vm.register(policy.clone()),
+            Err(PolicyError::UnsignedPolicy(_))
+        ));
+
+        let mut bundle = PolicyBundle::new(policy);
+        assert!(matches!(
+            vm.register_bundle(&bundle),
+            Err(PolicyError::InsufficientGovernanceSignatures { got: 0, need: 1, .. })
+        ));
+        bundle.sign(&key);
+        vm.register_bundle(&bundle).unwrap();
+        assert_eq!(vm.get("governed").unwrap().bytecode, vec![0u8]);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_context_without_spec_is_v1_0() {
+        let context: EvaluationContext = serde_json::from_value(serde_json::json!({
+            "container_id": "test", "actor": "alice", "intent": {"type": "observe"},
+            "state": null, "timestamp": 10
+        }))
+        .unwrap();
+        assert_eq!(context.spec, SpecVersion::V1_0);
+        assert_eq!(serde_json::to_value(&context).unwrap()["spec"], "1.0");
+    }
+
+    #[test]
+    fn test_decision_cache_invalidated_on_change() {
+        let mut vm = PolicyVM::new();
+        vm.enable_decision_cache(16);
+        vm.register(make_version("1.0", 0)).unwrap();
+        let context = EvaluationContext {
+            container_id: "test".to_string(),
+            actor: "alice".to_string(),
+            intent: serde_json::json!({"type": "observe"}),
+            state: None,
+            timestamp: 10,
+            spec: Default::default(),
+        };
+
+        let first = vm.evaluate("versioned", &context).unwrap();
+        assert_eq!(vm.evaluate("versioned", &context).unwrap(), first);
+        let stats = vm.cache_stats().unwrap();
+        assert_eq!((stats.hits, stats.misses, stats.entries), (1, 1, 1));
+
+        vm.remove_version("versioned", "1.0");
+        assert_eq!(vm.cache_stats().unwrap().entries, 0);
+        // Errors are evaluated every time and never stored
+        assert!(vm.evaluate("versioned", &context).is_err());
+        assert_eq!(vm.cache_stats().unwrap().entries, 0);
+    }
+
+    #[test]
+    fn test_observer_sees_every_evaluation() {
+        let seen = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut vm = PolicyVM::new();
+        vm.enable_decision_cache(16);
+        let log = seen.clone();
+        vm.set_observer(Some(std::sync::Arc::new(move |e: &Evaluation<'_>| {
+            log.lock().unwrap().push((e.policy_id.to_string(), e.result.is_ok(), e.cached));
+        })));
+        vm.register(make_version("1.0", 100)).unwrap();
+
+        let mut context = make_context("observe", None);
+        context.timestamp = 200;
+        vm.evaluate("versioned", &context).unwrap();
+        vm.evaluate("versioned", &context).unwrap();
+        context.timestamp = 10;
+        assert!(vm.evaluate("versioned", &context).is_err());
+        assert_eq!(
+            *seen.lock().unwrap(),
+            vec![
+                ("versioned".to_string(), true, false),
+                ("versioned".to_string(), true, true),
+                ("versioned".to_string(), false, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cel_policy_compiled_at_registration() {
+        let source: CelPolicy = serde_json::from_value(json!({"rules": [
+            {"when": "actor in state.blocked", "deny": {"code": "actor_blocked"}},
+            {"when": "intent.type == 'transfer' && intent.amount > 500",
+             "allow": {"intent_class": 1, "required_pact": "treasury"}},
+            {"when": "intent.type == 'transfer'", "allow": {"intent_class": 1}}
+        ]}))
+        .unwrap();
+        let cel = |bytecode: Vec<u8>| Policy {
+            policy_id: "cel".to_string(),
+            version: "1.0".to_string(),
+            bytecode_hash: bytecode_hash(&bytecode),
+            bytecode,
+            description: "CEL".to_string(),
+            active_from: 0,
+        };
+        let mut vm = PolicyVM::new();
+        vm.register(cel(source.to_bytecode())).unwrap();
+
+        let mut context = make_context("transfer", Some(600));
+        context.state = Some(json!({"blocked": ["mallory"]}));
+        assert_eq!(
+            vm.evaluate("cel", &context).unwrap(),
+            TranslationDecision::Allow {
+                intent_class: 1,
+                required_pact: Some("treasury".to_string()),
+                constraints: vec![],
+            }
+        );
+        context.actor = "mallory".to_string();
+        assert!(matches!(
+            vm.evaluate("cel", &context).unwrap(),
+            TranslationDecision::Deny { code: DenyCode::ActorBlocked, .. }
+        ));
+        // `state.blocked` errors without state; nothing later can decide for it
+        assert!(matches!(
+            vm.evaluate("cel", &make_context("observe", None)),
+            Err(PolicyError::ExecutionFailed(_))
+        ));
+
+        let mut broken = source.clone();
+        broken.rules[1].when = "intent.amount >".to_string();
+        vm.remove("cel");
+        assert!(matches!(
+            vm.register(cel(broken.to_bytecode())),
+            Err(PolicyError::CompileFailed { .. })
+        ));
+        assert!(vm.get("cel").is_none());
+    }
+}
@@ -0,0 +1,343 @@
+//! Policy representation migrations (SPEC-UBL-POLICY v1.0, STRICT change-control)
+//!
+//! Stored bytecode is in one of the representations [`representation`]
+//! names: `cel/1` ([`crate::cel::MAGIC`]), `wasm/1` ([`crate::wasm::MAGIC`]),
+//! `ext/1` ([`crate::external::MAGIC`]), or `builtin` for bytecode the VM answers with its built-in rules. When a
+//! representation changes, a [`Migration`] step rewrites bytecode from the
+//! old one to the next, and [`MIGRATIONS`] lists the steps that have shipped.
+//!
+//! A [`Migrator`] chains steps to reach a target representation. For every
+//! policy it checks the stored bytecode against its hash, checks that each
+//! step produced the representation it claims, registers the result (so CEL
+//! compiles and WASM passes analysis), and evaluates the original and the
+//! migrated version on the probe contexts given: any difference in decision
+//! fails the migration. Policy id, version and `active_from` never change, so
+//! replaying history selects the same versions.
+//!
+//! The outcome is a [`MigrationReport`]: one record per policy with its old
+//! and new bytecode hash, signed by governance keys over
+//! [`MigrationReport::signing_bytes`]. Migrated bytecode changes the hash a
+//! [`crate::PolicyBundle`] signature covers; the report is the governance
+//! record for that change, and the caller stores the migrated policies.
+
+use ed25519_dalek::SigningKey;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bytecode_hash, cel, external, wasm, BundleSignature, EvaluationContext, GovernanceKeys, Policy, PolicyError, PolicyVM,
+    Result,
+};
+
+/// Domain tag prefixed to the canonical report before signing
+pub const MIGRATION_DOMAIN: &[u8] = b"ubl:policy-migration\n";
+
+/// CEL documents behind [`cel::MAGIC`]
+pub const CEL_V1: &str = "cel/1";
+/// WASM modules behind [`wasm::MAGIC`]
+pub const WASM_V1: &str = "wasm/1";
+/// External engine sources behind [`external::MAGIC`]
+pub const EXTERNAL_V1: &str = "ext/1";
+/// Bytecode evaluated by the VM's built-in rules
+pub const BUILTIN: &str = "builtin";
+
+/// Migration steps that have shipped, oldest first. No representation has
+/// changed yet; each change adds its step here.
+pub const MIGRATIONS: &[Migration] = &[];
+
+/// Representation of `bytecode`
+pub fn representation(bytecode: &[u8]) -> &'static str {
+    if cel::is_cel(bytecode) {
+        CEL_V1
+    } else if wasm::is_wasm(bytecode) {
+        WASM_V1
+    } else if external::is_external(bytecode) {
+        EXTERNAL_V1
+    } else {
+        BUILTIN
+    }
+}
+
+/// One rewrite from a representation to the next
+#[derive(Debug, Clone, Copy)]
+pub struct Migration {
+    /// Representation the step reads
+    pub from: &'static str,
+    /// Representation the step writes
+    pub to: &'static str,
+    /// Rewrite bytecode; the error says why it cannot be migrated
+    pub rewrite: fn(&[u8]) -> std::result::Result<Vec<u8>, String>,
+}
+
+/// What migration did to one policy version
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct MigrationRecord {
+    /// Policy identifier
+    pub policy_id: String,
+    /// Policy version
+    pub version: String,
+    /// Representation before
+    pub from: String,
+    /// Representation after
+    pub to: String,
+    /// Bytecode hash before
+    pub from_hash: String,
+    /// Bytecode hash after (equal to `from_hash` when already at the target)
+    pub to_hash: String,
+    /// Probe contexts both versions decided alike
+    pub probes: usize,
+}
+
+/// Signed record of a migration run
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct MigrationReport {
+    /// Representation every policy was migrated to
+    pub target: String,
+    /// One record per policy version, in input order
+    pub records: Vec<MigrationRecord>,
+    /// Governance signatures
+    #[serde(default)]
+    pub signatures: Vec<BundleSignature>,
+}
+
+impl MigrationReport {
+    /// `MIGRATION_DOMAIN` followed by the canonical JSON of target and records
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        let body = serde_json::json!({
+            "target": self.target,
+            "records": self.records,
+        });
+        let canonical = ubl_atom::canonicalize(&body).expect("report is finite JSON");
+        [MIGRATION_DOMAIN, canonical.as_slice()].concat()
+    }
+
+    /// Add a signature with `key`
+    pub fn sign(&mut self, key: &SigningKey) {
+        self.signatures.push(BundleSignature {
+            pubkey: ubl_kernel::pubkey_from_signing_key(key),
+            signature: ubl_kernel::sign(key, &self.signing_bytes()),
+        });
+    }
+
+    /// Check the report carries `threshold` valid signatures from distinct governance keys
+    pub fn verify(&self, keys: &GovernanceKeys) -> Result<()> {
+        let subject = format!("migration to {}", self.target);
+        keys.verify_signatures(&subject, &self.signing_bytes(), &self.signatures)
+    }
+}
+
+/// Chains [`Migration`] steps and checks their output
+#[derive(Debug, Clone)]
+pub struct Migrator {
+    steps: Vec<Migration>,
+    probes: Vec<EvaluationContext>,
+}
+
+impl Default for Migrator {
+    fn default() -> Self {
+        Self::new(MIGRATIONS.iter().copied())
+    }
+}
+
+impl Migrator {
+    /// Migrator over `steps`; when two leave the same representation, the first wins
+    pub fn new(steps: impl IntoIterator<Item = Migration>) -> Self {
+        Self {
+            steps: steps.into_iter().collect(),
+            probes: Vec::new(),
+        }
+    }
+
+    /// Contexts each migrated policy must decide exactly as before, evaluated as given
+    pub fn with_probes(mut self, probes: Vec<EvaluationContext>) -> Self {
+        self.probes = probes;
+        self
+    }
+
+    /// Steps from `from` to `to`, in order (empty when they are equal)
+    pub fn path(&self, from: &str, to: &str) -> std::result::Result<Vec<&Migration>, String> {
+        let mut path: Vec<&Migration> = Vec::new();
+        let mut at = from;
+        while at != to {
+            let step = self
+                .steps
+                .iter()
+                .find(|s| s.from == at)
+                .filter(|s| path.iter().all(|p| p.from != s.to))
+                .ok_or_else(|| format!("no migration path from {} to {}", from, to))?;
+            path.push(step);
+            at = step.to;
+        }
+        Ok(path)
+    }
+
+    /// Migrate one policy version to `target`
+    pub fn migrate(&self, policy: &Policy, target: &str) -> Result<(Policy, MigrationRecord)> {
+        policy.verify_bytecode()?;
+        let fail = |reason: String| PolicyError::MigrationFailed {
+            policy_id: policy.policy_id.clone(),
+            reason,
+        };
+        let from = representation(&policy.bytecode);
+        let path = self.path(from, target).map_err(fail)?;
+
+        let mut bytecode = policy.bytecode.clone();
+        for step in &path {
+            bytecode = (step.rewrite)(&bytecode).map_err(|e| fail(format!("{} -> {}: {}", step.from, step.to, e)))?;
+            let wrote = representation(&bytecode);
+            if wrote != step.to {
+                return Err(fail(format!("{} -> {} wrote {}", step.from, step.to, wrote)));
+            }
+        }
+        let migrated = Policy {
+            bytecode_hash: bytecode_hash(&bytecode),
+            bytecode,
+            ..policy.clone()
+        };
+        let probes = if path.is_empty() { 0 } else { self.check_probes(policy, &migrated)? };
+
+        let record = MigrationRecord {
+            policy_id: policy.policy_id.clone(),
+            version: policy.version.clone(),
+            from: from.to_string(),
+            to: target.to_string(),
+            from_hash: policy.bytecode_hash.clone(),
+            to_hash: migrated.bytecode_hash.clone(),
+            probes,
+        };
+        Ok((migrated, record))
+    }
+
+    /// Migrate every policy version to `target`; any failure fails the run
+    pub fn migrate_all(&self, policies: &[Policy], target: &str) -> Result<(Vec<Policy>, MigrationReport)> {
+        let mut migrated = Vec::with_capacity(policies.len());
+        let mut records = Vec::with_capacity(policies.len());
+        for policy in policies {
+            let (p, record) = self.migrate(policy, target)?;
+            migrated.push(p);
+            records.push(record);
+        }
+        let report = MigrationReport {
+            target: target.to_string(),
+            records,
+            signatures: Vec::new(),
+        };
+        Ok((migrated, report))
+    }
+
+    /// Register both versions and compare their decisions on every probe
+    fn check_probes(&self, before: &Policy, after: &Policy) -> Result<usize> {
+        let mut old = PolicyVM::new();
+        old.register(before.clone())?;
+        let mut new = PolicyVM::new();
+        new.register(after.clone())?;
+        for (i, probe) in self.probes.iter().enumerate() {
+            let was = old.evaluate(&before.policy_id, probe);
+            let now = new.evaluate(&after.policy_id, probe);
+            if was != now {
+                return Err(PolicyError::MigrationFailed {
+                    policy_id: before.policy_id.clone(),
+                    reason: format!("probe {} decided {:?} before and {:?} after", i, was, now),
+                });
+            }
+        }
+        Ok(self.probes.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cel::CelPolicy;
+
+    fn builtin() -> Policy {
+        let bytecode = b"tdln".to_vec();
+        Policy {
+            policy_id: "legacy".to_string(),
+            version: "1".to_string(),
+            bytecode_hash: bytecode_hash(&bytecode),
+            bytecode,
+            description: "built-in rules".to_string(),
+            active_from: 100,
+        }
+    }
+
+    fn cel_bytecode(rules: serde_json::Value) -> Vec<u8> {
+        let cel: CelPolicy = serde_json::from_value(serde_json::json!({ "rules": rules })).unwrap();
+        cel.to_bytecode()
+    }
+
+    /// The built-in rules for observations and small transfers
+    fn faithful(_: &[u8]) -> std::result::Result<Vec<u8>, String> {
+        Ok(cel_bytecode(serde_json::json!([
+            { "when": "intent.type in ['observe', 'read']", "allow": { "intent_class": 0 } },
+            { "when": "intent.type == 'transfer' && intent.amount <= 10000", "allow": { "intent_class": 1 } }
+        ])))
+    }
+
+    /// Allows everything as an observation
+    fn lax(_: &[u8]) -> std::result::Result<Vec<u8>, String> {
+        Ok(cel_bytecode(serde_json::json!([{ "when": "true", "allow": { "intent_class": 0 } }])))
+    }
+
+    fn to_cel(rewrite: fn(&[u8]) -> std::result::Result<Vec<u8>, String>) -> Migration {
+        Migration {
+            from: BUILTIN,
+            to: CEL_V1,
+            rewrite,
+        }
+    }
+
+    fn probes() -> Vec<EvaluationContext> {
+        [serde_json::json!({"type": "observe"}), serde_json::json!({"type": "transfer", "amount": 500})]
+            .into_iter()
+            .map(|intent| EvaluationContext {
+                container_id: "C.Bank".to_string(),
+                actor: "alice".to_string(),
+                intent,
+                state: None,
+                timestamp: 200,
+                spec: Default::default(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_migration_report_is_signed() {
+        let migrator = Migrator::new([to_cel(faithful)]).with_probes(probes());
+        let (migrated, mut report) = migrator.migrate_all(&[builtin()], CEL_V1).unwrap();
+        assert_eq!(representation(&migrated[0].bytecode), CEL_V1);
+        migrated[0].verify_bytecode().unwrap();
+        assert_eq!((migrated[0].version.as_str(), migrated[0].active_from), ("1", 100));
+        let record = &report.records[0];
+        assert_eq!((record.from.as_str(), record.probes), (BUILTIN, 2));
+        assert_eq!(record.from_hash, builtin().bytecode_hash);
+        assert_eq!(record.to_hash, migrated[0].bytecode_hash);
+
+        let (gov, key) = ubl_kernel::generate_keypair();
+        let governance = GovernanceKeys::new([&gov], 1);
+        assert!(report.verify(&governance).is_err());
+        report.sign(&key);
+        report.verify(&governance).unwrap();
+        report.records[0].to_hash = "0".repeat(64);
+        assert!(matches!(report.verify(&governance), Err(PolicyError::InvalidBundleSignature(_))));
+    }
+
+    #[test]
+    fn test_migration_refuses_changed_decisions() {
+        let err = Migrator::new([to_cel(lax)]).with_probes(probes()).migrate(&builtin(), CEL_V1).unwrap_err();
+        assert!(matches!(err, PolicyError::MigrationFailed { ref reason, .. } if reason.starts_with("probe 1")));
+
+        // No step reaches the target, and tampered bytecode is refused before any step runs
+        assert!(Migrator::default().migrate(&builtin(), CEL_V1).is_err());
+        let mut tampered = builtin();
+        tampered.bytecode = b"evil".to_vec();
+        assert!(matches!(
+            Migrator::new([to_cel(faithful)]).migrate(&tampered, CEL_V1),
+            Err(PolicyError::BytecodeHashMismatch { .. })
+        ));
+
+        // Already at the target: recorded unchanged
+        let (_, record) = Migrator::default().migrate(&builtin(), BUILTIN).unwrap();
+        assert_eq!(record.from_hash, record.to_hash);
+    }
+}
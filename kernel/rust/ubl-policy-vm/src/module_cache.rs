@@ -0,0 +1,131 @@
+//! Compiled module cache (see [`crate::runtime`])
+//!
+//! Compiling a WASM policy costs far more than running it, so compiled
+//! modules are kept by bytecode hash. The hash addresses the bytecode, not a
+//! policy version: a module stays valid across policy-set changes and
+//! reloads, and two versions with the same bytecode share one entry. When
+//! full, the least recently used module is evicted.
+
+use std::collections::{BTreeMap, HashMap};
+
+use serde::Serialize;
+
+/// Counters of a module cache
+#[derive(Debug, Clone, Copy, Default, Serialize, PartialEq, Eq)]
+pub struct ModuleCacheStats {
+    /// Maximum number of modules
+    pub capacity: usize,
+    /// Modules held
+    pub entries: usize,
+    /// Lookups answered with a compiled module
+    pub hits: u64,
+    /// Lookups that had to compile
+    pub misses: u64,
+    /// Modules dropped to make room
+    pub evictions: u64,
+}
+
+/// Bounded LRU map from bytecode hash to compiled module
+#[derive(Debug)]
+pub struct ModuleCache<M> {
+    /// Module and its last use, per bytecode hash
+    entries: HashMap<String, (M, u64)>,
+    /// Bytecode hash per last use, oldest first
+    recency: BTreeMap<u64, String>,
+    /// Use counter
+    tick: u64,
+    stats: ModuleCacheStats,
+}
+
+impl<M: Clone> ModuleCache<M> {
+    /// Empty cache holding at most `capacity` modules (at least 1)
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            entries: HashMap::with_capacity(capacity),
+            recency: BTreeMap::new(),
+            tick: 0,
+            stats: ModuleCacheStats {
+                capacity,
+                ..ModuleCacheStats::default()
+            },
+        }
+    }
+
+    /// Module compiled from the bytecode hashing to `hash`, counting the hit
+    /// or miss and marking it most recently used
+    pub fn get(&mut self, hash: &str) -> Option<M> {
+        self.tick += 1;
+        let Some((module, used)) = self.entries.get_mut(hash) else {
+            self.stats.misses += 1;
+            return None;
+        };
+        self.stats.hits += 1;
+        self.recency.remove(used);
+        *used = self.tick;
+        self.recency.insert(self.tick, hash.to_string());
+        Some(module.clone())
+    }
+
+    /// Store a compiled module, evicting the least recently used when full
+    pub fn insert(&mut self, hash: &str, module: M) {
+        self.tick += 1;
+        if let Some((_, used)) = self.entries.insert(hash.to_string(), (module, self.tick)) {
+            self.recency.remove(&used);
+        }
+        self.recency.insert(self.tick, hash.to_string());
+        while self.entries.len() > self.stats.capacity {
+            let Some((_, oldest)) = self.recency.pop_first() else {
+                break;
+            };
+            self.entries.remove(&oldest);
+            self.stats.evictions += 1;
+        }
+    }
+
+    /// Current counters
+    pub fn stats(&self) -> ModuleCacheStats {
+        ModuleCacheStats {
+            entries: self.entries.len(),
+            ..self.stats
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evicts_least_recently_used() {
+        let mut cache = ModuleCache::new(2);
+        cache.insert("a", 1);
+        cache.insert("b", 2);
+        // Touching `a` leaves `b` the oldest
+        assert_eq!(cache.get("a"), Some(1));
+        cache.insert("c", 3);
+        assert_eq!(cache.get("b"), None);
+        assert_eq!(cache.get("a"), Some(1));
+        assert_eq!(cache.get("c"), Some(3));
+        assert_eq!(
+            cache.stats(),
+            ModuleCacheStats {
+                capacity: 2,
+                entries: 2,
+                hits: 3,
+                misses: 1,
+                evictions: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn test_reinsert_does_not_evict() {
+        let mut cache = ModuleCache::new(1);
+        cache.insert("a", 1);
+        cache.insert("a", 2);
+        assert_eq!(cache.get("a"), Some(2));
+        assert_eq!(cache.stats().evictions, 0);
+        assert_eq!(ModuleCache::<u8>::new(0).stats().capacity, 1);
+    }
+}
@@ -0,0 +1,821 @@
+//! Rego import (OPA policies into the CEL backend)
+//!
+//! Translates a constrained subset of Rego into a [`CelPolicy`], so an
+//! existing policy corpus can be registered without an OPA runtime. The
+//! output is an ordinary CEL policy: sign and register its bytecode like any
+//! other.
+//!
+//! ```rego
+//! package ubl.bank
+//! import rego.v1
+//!
+//! deny := {"code": "actor_blocked"} if input.actor in {"mallory", "trudy"}
+//!
+//! allow := {"intent_class": 1, "required_pact": "high_value_transfer"} if {
+//!     input.intent.type == "transfer"
+//!     input.intent.amount > 10000
+//! }
+//!
+//! default deny := {"code": "unknown_intent"}
+//! ```
+//!
+//! Supported:
+//!
+//! - `allow := <object>` and `deny := <object>` rules, each with an optional
+//!   body (`if { … }`, `if <expr>`, or the pre-v1 `{ … }`). The object is the
+//!   decision: `intent_class`, `required_pact`, `constraints` for allow;
+//!   `code`, `detail` for deny. One `default` rule of either kind.
+//! - Bodies made of expressions, one per line or separated by `;`, all of
+//!   which must hold: comparisons (`==`, `=`, `!=`, `<`, `<=`, `>`, `>=`),
+//!   arithmetic, `in`, `not`, and `count`, `startswith`, `endswith`,
+//!   `contains`.
+//! - References into `input` (`input.intent.amount`, `input.state["k"]`).
+//!   `input` has the [`crate::EvaluationContext`] fields.
+//!
+//! Rules keep Rego's "deny overrides" reading: every deny rule is tried
+//! before any allow rule, each kind in source order, and the default comes
+//! last. As in Rego, a missing field makes its expression false rather than
+//! failing. A reference through a non-object value, or an ordering between
+//! mismatched types, fails the evaluation instead of comparing by type.
+//! Anything outside the subset is rejected with its line: `data`, local
+//! variables, `some`/`every`, partial rules, `with`, and other builtins.
+
+use crate::cel::{self, CelOutcome, CelPolicy, CelRule};
+use crate::{PolicyError, Result};
+
+/// Result of importing a Rego module
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegoImport {
+    /// The module's `package` path
+    pub package: String,
+    /// Translated policy
+    pub policy: CelPolicy,
+}
+
+/// Translate a Rego module into a CEL policy
+pub fn import(source: &str) -> Result<RegoImport> {
+    let toks = lex(source)?;
+    let mut p = Parser { toks, pos: 0, depth: 0 };
+    let mut package = None;
+    let (mut denies, mut allows, mut default) = (Vec::new(), Vec::new(), None);
+
+    loop {
+        p.skip_newlines();
+        let Some(tok) = p.peek().cloned() else { break };
+        let line = p.line();
+        match tok {
+            Tok::Ident(w) if w == "package" => {
+                p.pos += 1;
+                package = Some(p.dotted_path()?);
+            }
+            Tok::Ident(w) if w == "import" => {
+                p.pos += 1;
+                let path = p.dotted_path()?;
+                if path != "rego.v1" && path != "future.keywords" && !path.starts_with("future.keywords.") {
+                    return Err(err(line, format!("import {} is not supported", path)));
+                }
+            }
+            Tok::Ident(w) if w == "default" => {
+                p.pos += 1;
+                let (kind, outcome) = p.head()?;
+                if default.is_some() {
+                    return Err(err(line, "only one default rule is supported"));
+                }
+                p.end_of_statement()?;
+                default = Some(rule(kind, "true".to_string(), outcome, line)?);
+            }
+            Tok::Ident(_) => {
+                let (kind, outcome) = p.head()?;
+                let when = p.body()?;
+                let r = rule(kind, when, outcome, line)?;
+                match kind {
+                    Kind::Deny => denies.push(r),
+                    Kind::Allow => allows.push(r),
+                }
+            }
+            _ => return Err(err(line, "expected a rule")),
+        }
+    }
+
+    let package = package.ok_or_else(|| err(1, "missing package declaration"))?;
+    let policy = CelPolicy {
+        rules: denies.into_iter().chain(allows).chain(default).collect(),
+    };
+    // The translation must itself compile; a failure here is a translator bug
+    cel::CelProgram::compile(&policy).map_err(|reason| err(0, format!("translated CEL does not compile: {}", reason)))?;
+    Ok(RegoImport { package, policy })
+}
+
+fn err(line: usize, reason: impl Into<String>) -> PolicyError {
+    PolicyError::RegoImport {
+        line,
+        reason: reason.into(),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Allow,
+    Deny,
+}
+
+fn rule(kind: Kind, when: String, outcome: serde_json::Value, line: usize) -> Result<CelRule> {
+    let tagged = serde_json::json!({ match kind { Kind::Allow => "allow", Kind::Deny => "deny" }: outcome });
+    let then: CelOutcome = serde_json::from_value(tagged).map_err(|e| err(line, format!("invalid decision: {}", e)))?;
+    Ok(CelRule { when, then })
+}
+
+// ---------------------------------------------------------------------------
+// Lexer
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone, PartialEq)]
+enum Tok {
+    Int(i64),
+    Float(f64),
+    Str(String),
+    Ident(String),
+    Punct(&'static str),
+    Newline,
+}
+
+const PUNCT: &[&str] = &[
+    ":=", "==", "!=", "<=", ">=", "(", ")", "[", "]", "{", "}", ".", ",", ":", ";", "=", "<", ">", "+", "-", "*",
+    "/", "%",
+];
+
+fn lex(src: &str) -> Result<Vec<(usize, Tok)>> {
+    let mut out = Vec::new();
+    for (n, text) in src.lines().enumerate() {
+        let line = n + 1;
+        let chars: Vec<char> = text.chars().collect();
+        let mut i = 0;
+        while i < chars.len() {
+            let c = chars[i];
+            let start = i;
+            if c == '#' {
+                break;
+            } else if c.is_whitespace() {
+                i += 1;
+            } else if c.is_ascii_digit() {
+                while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.' || chars[i] == 'e') {
+                    i += 1;
+                }
+                let s: String = chars[start..i].iter().collect();
+                let tok = if s.contains(['.', 'e']) {
+                    s.parse().map(Tok::Float).ok()
+                } else {
+                    s.parse().map(Tok::Int).ok()
+                };
+                out.push((line, tok.ok_or_else(|| err(line, format!("invalid number {}", s)))?));
+            } else if c.is_alphabetic() || c == '_' {
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                    i += 1;
+                }
+                out.push((line, Tok::Ident(chars[start..i].iter().collect())));
+            } else if c == '"' {
+                i += 1;
+                let mut s = String::new();
+                loop {
+                    match chars.get(i) {
+                        None => return Err(err(line, "unterminated string")),
+                        Some('"') => break,
+                        Some('\\') => {
+                            i += 1;
+                            s.push(match chars.get(i) {
+                                Some('n') => '\n',
+                                Some('t') => '\t',
+                                Some(&e @ ('\\' | '"' | '/')) => e,
+                                _ => return Err(err(line, "unsupported escape")),
+                            });
+                        }
+                        Some(&ch) => s.push(ch),
+                    }
+                    i += 1;
+                }
+                i += 1;
+                out.push((line, Tok::Str(s)));
+            } else if c == '`' {
+                return Err(err(line, "raw strings are not supported"));
+            } else {
+                let rest: String = chars[i..chars.len().min(i + 2)].iter().collect();
+                let p = PUNCT
+                    .iter()
+                    .find(|p| rest.starts_with(**p))
+                    .ok_or_else(|| err(line, format!("unexpected '{}'", c)))?;
+                i += p.len();
+                out.push((line, Tok::Punct(p)));
+            }
+        }
+        out.push((line, Tok::Newline));
+    }
+    Ok(out)
+}
+
+// ---------------------------------------------------------------------------
+// Terms
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Clone)]
+enum Term {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Str(String),
+    Array(Vec<Term>),
+    Set(Vec<Term>),
+    Object(Vec<(Term, Term)>),
+    /// `input.a["b"]…`: the context field, then the path below it
+    Ref(String, Vec<Step>),
+    Call(String, Vec<Term>),
+    Not(Box<Term>),
+    Neg(Box<Term>),
+    Binary(&'static str, Box<Term>, Box<Term>),
+}
+
+#[derive(Debug, Clone)]
+enum Step {
+    Field(String),
+    Index(Box<Term>),
+}
+
+const BUILTINS: &[(&str, usize)] = &[("count", 1), ("startswith", 2), ("endswith", 2), ("contains", 2)];
+
+struct Parser {
+    toks: Vec<(usize, Tok)>,
+    pos: usize,
+    /// Bracket nesting; newlines only end statements at depth 0
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Tok> {
+        let mut i = self.pos;
+        while self.depth > 0 && matches!(self.toks.get(i), Some((_, Tok::Newline))) {
+            i += 1;
+        }
+        self.toks.get(i).map(|(_, t)| t)
+    }
+
+    fn next(&mut self) -> Option<Tok> {
+        while self.depth > 0 && matches!(self.toks.get(self.pos), Some((_, Tok::Newline))) {
+            self.pos += 1;
+        }
+        let t = self.toks.get(self.pos).map(|(_, t)| t.clone());
+        self.pos += 1;
+        t
+    }
+
+    fn line(&self) -> usize {
+        self.toks.get(self.pos).or(self.toks.last()).map_or(1, |(l, _)| *l)
+    }
+
+    fn skip_newlines(&mut self) {
+        while self.eat_newline() {}
+    }
+
+    fn eat_newline(&mut self) -> bool {
+        let hit = matches!(self.toks.get(self.pos), Some((_, Tok::Newline)));
+        if hit {
+            self.pos += 1;
+        }
+        hit
+    }
+
+    fn is(&self, p: &str) -> bool {
+        matches!(self.peek(), Some(Tok::Punct(q)) if *q == p)
+    }
+
+    fn is_word(&self, w: &str) -> bool {
+        matches!(self.peek(), Some(Tok::Ident(x)) if x == w)
+    }
+
+    fn eat(&mut self, p: &str) -> bool {
+        let hit = self.is(p);
+        if hit {
+            self.next();
+        }
+        hit
+    }
+
+    fn expect(&mut self, p: &str) -> Result<()> {
+        if self.eat(p) {
+            Ok(())
+        } else {
+            Err(err(self.line(), format!("expected '{}'", p)))
+        }
+    }
+
+    fn open(&mut self, p: &str) -> Result<()> {
+        self.expect(p)?;
+        self.depth += 1;
+        Ok(())
+    }
+
+    fn close(&mut self, p: &str) -> Result<()> {
+        self.expect(p)?;
+        self.depth -= 1;
+        Ok(())
+    }
+
+    fn ident(&mut self) -> Result<String> {
+        match self.next() {
+            Some(Tok::Ident(w)) => Ok(w),
+            _ => Err(err(self.line(), "expected a name")),
+        }
+    }
+
+    fn dotted_path(&mut self) -> Result<String> {
+        let mut path = self.ident()?;
+        while self.eat(".") {
+            path.push('.');
+            path.push_str(&self.ident()?);
+        }
+        self.end_of_statement()?;
+        Ok(path)
+    }
+
+    fn end_of_statement(&mut self) -> Result<()> {
+        match self.toks.get(self.pos) {
+            None | Some((_, Tok::Newline)) => Ok(()),
+            Some((line, _)) => Err(err(*line, "unexpected input after statement")),
+        }
+    }
+
+    /// `allow := <object>` / `deny := <object>`
+    fn head(&mut self) -> Result<(Kind, serde_json::Value)> {
+        let line = self.line();
+        let name = self.ident()?;
+        let kind = match name.as_str() {
+            "allow" => Kind::Allow,
+            "deny" => Kind::Deny,
+            _ => return Err(err(line, format!("only allow and deny rules are supported, not {}", name))),
+        };
+        if self.is("[") || self.is("(") || self.is_word("contains") {
+            return Err(err(line, "partial and function rules are not supported"));
+        }
+        if !self.eat(":=") && !self.eat("=") {
+            return Err(err(line, format!("{} needs a decision object: {} := {{...}}", name, name)));
+        }
+        let value = self.term()?;
+        Ok((kind, literal(&value, line)?))
+    }
+
+    /// Rule body after the head, as a CEL condition
+    fn body(&mut self) -> Result<String> {
+        let line = self.line();
+        let has_if = self.is_word("if");
+        if has_if {
+            self.next();
+        }
+        if self.is("{") {
+            // Not `open`: statements inside end at newlines
+            self.expect("{")?;
+            let mut conds = Vec::new();
+            loop {
+                while self.eat(";") || self.eat_newline() {}
+                if self.eat("}") {
+                    break;
+                }
+                if self.peek().is_none() {
+                    return Err(err(line, "unterminated rule body"));
+                }
+                conds.push(self.statement()?);
+                if !(self.is(";") || self.is("}") || matches!(self.toks.get(self.pos), Some((_, Tok::Newline)))) {
+                    return Err(err(self.line(), "expected end of expression"));
+                }
+            }
+            self.end_of_statement()?;
+            if conds.is_empty() {
+                return Err(err(line, "empty rule body"));
+            }
+            Ok(conds.join(" && "))
+        } else if has_if {
+            let cond = self.statement()?;
+            self.end_of_statement()?;
+            Ok(cond)
+        } else {
+            self.end_of_statement()?;
+            Ok("true".to_string())
+        }
+    }
+
+    /// One body expression, guarded so that missing fields make it false
+    fn statement(&mut self) -> Result<String> {
+        let line = self.line();
+        for w in ["some", "every", "with"] {
+            if self.is_word(w) {
+                return Err(err(line, format!("'{}' is not supported", w)));
+            }
+        }
+        let term = self.expr()?;
+        if self.is(":=") {
+            return Err(err(line, "local variables are not supported"));
+        }
+        if self.is_word("with") {
+            return Err(err(line, "'with' is not supported"));
+        }
+        guarded(&term, line)
+    }
+
+    fn expr(&mut self) -> Result<Term> {
+        if self.is_word("not") {
+            self.next();
+            return Ok(Term::Not(Box::new(self.expr()?)));
+        }
+        let lhs = self.sum()?;
+        let op = match self.peek() {
+            Some(Tok::Punct(p @ ("==" | "!=" | "<" | "<=" | ">" | ">="))) => *p,
+            Some(Tok::Punct("=")) => "==",
+            Some(Tok::Ident(w)) if w == "in" => "in",
+            _ => return Ok(lhs),
+        };
+        self.next();
+        Ok(Term::Binary(op, Box::new(lhs), Box::new(self.sum()?)))
+    }
+
+    fn sum(&mut self) -> Result<Term> {
+        let mut lhs = self.product()?;
+        loop {
+            let op = match self.peek() {
+                Some(Tok::Punct(p @ ("+" | "-"))) => *p,
+                _ => return Ok(lhs),
+            };
+            self.next();
+            lhs = Term::Binary(op, Box::new(lhs), Box::new(self.product()?));
+        }
+    }
+
+    fn product(&mut self) -> Result<Term> {
+        let mut lhs = self.unary()?;
+        loop {
+            let op = match self.peek() {
+                Some(Tok::Punct(p @ ("*" | "/" | "%"))) => *p,
+                _ => return Ok(lhs),
+            };
+            self.next();
+            lhs = Term::Binary(op, Box::new(lhs), Box::new(self.unary()?));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Term> {
+        if self.eat("-") {
+            return Ok(Term::Neg(Box::new(self.unary()?)));
+        }
+        self.term()
+    }
+
+    fn term(&mut self) -> Result<Term> {
+        let line = self.line();
+        match self.peek().cloned() {
+            Some(Tok::Int(n)) => {
+                self.next();
+                Ok(Term::Int(n))
+            }
+            Some(Tok::Float(f)) => {
+                self.next();
+                Ok(Term::Float(f))
+            }
+            Some(Tok::Str(s)) => {
+                self.next();
+                Ok(Term::Str(s))
+            }
+            Some(Tok::Punct("(")) => {
+                self.open("(")?;
+                let e = self.expr()?;
+                self.close(")")?;
+                Ok(e)
+            }
+            Some(Tok::Punct("[")) => {
+                self.open("[")?;
+                let items = self.items("]")?;
+                self.close("]")?;
+                Ok(Term::Array(items))
+            }
+            Some(Tok::Punct("{")) => self.braces(),
+            Some(Tok::Ident(w)) => {
+                self.next();
+                match w.as_str() {
+                    "true" => Ok(Term::Bool(true)),
+                    "false" => Ok(Term::Bool(false)),
+                    "null" => Ok(Term::Null),
+                    "input" => self.input_ref(line),
+                    "data" => Err(err(line, "data references are not supported; inline the values")),
+                    _ if self.is("(") => self.call(w, line),
+                    _ => Err(err(line, format!("unknown name {} (local variables are not supported)", w))),
+                }
+            }
+            _ => Err(err(line, "expected a value")),
+        }
+    }
+
+    fn items(&mut self, close: &str) -> Result<Vec<Term>> {
+        let mut items = Vec::new();
+        while !self.is(close) {
+            items.push(self.expr()?);
+            if !self.eat(",") {
+                break;
+            }
+        }
+        Ok(items)
+    }
+
+    /// `{}` object, `{k: v}` object or `{a, b}` set
+    fn braces(&mut self) -> Result<Term> {
+        self.open("{")?;
+        if self.eat("}") {
+            self.depth -= 1;
+            return Ok(Term::Object(Vec::new()));
+        }
+        let first = self.expr()?;
+        if self.eat(":") {
+            let mut entries = vec![(first, self.expr()?)];
+            while self.eat(",") && !self.is("}") {
+                let k = self.expr()?;
+                self.expect(":")?;
+                entries.push((k, self.expr()?));
+            }
+            self.close("}")?;
+            return Ok(Term::Object(entries));
+        }
+        let mut items = vec![first];
+        if self.eat(",") {
+            items.extend(self.items("}")?);
+        }
+        self.close("}")?;
+        Ok(Term::Set(items))
+    }
+
+    fn input_ref(&mut self, line: usize) -> Result<Term> {
+        let field = if self.eat(".") {
+            self.ident()?
+        } else if self.is("[") {
+            self.open("[")?;
+            let key = match self.next() {
+                Some(Tok::Str(s)) => s,
+                _ => return Err(err(line, "input[...] needs a string key")),
+            };
+            self.close("]")?;
+            key
+        } else {
+            return Err(err(line, "bare input is not supported; reference a field"));
+        };
+        if !cel::VARIABLES.contains(&field.as_str()) {
+            return Err(err(line, format!("input.{} is not a context field ({})", field, cel::VARIABLES.join(", "))));
+        }
+        let mut path = Vec::new();
+        loop {
+            if self.eat(".") {
+                path.push(Step::Field(self.ident()?));
+            } else if self.is("[") {
+                self.open("[")?;
+                path.push(Step::Index(Box::new(self.expr()?)));
+                self.close("]")?;
+            } else {
+                return Ok(Term::Ref(field, path));
+            }
+        }
+    }
+
+    fn call(&mut self, name: String, line: usize) -> Result<Term> {
+        let Some(&(_, arity)) = BUILTINS.iter().find(|(n, _)| *n == name) else {
+            return Err(err(line, format!("builtin {} is not supported", name)));
+        };
+        self.open("(")?;
+        let args = self.items(")")?;
+        self.close(")")?;
+        if args.len() != arity {
+            return Err(err(line, format!("{} takes {} argument(s)", name, arity)));
+        }
+        Ok(Term::Call(name, args))
+    }
+}
+
+/// A decision object must be a constant
+fn literal(t: &Term, line: usize) -> Result<serde_json::Value> {
+    use serde_json::Value;
+    Ok(match t {
+        Term::Null => Value::Null,
+        Term::Bool(b) => Value::Bool(*b),
+        Term::Int(n) => Value::from(*n),
+        Term::Float(f) => Value::from(*f),
+        Term::Str(s) => Value::String(s.clone()),
+        Term::Array(items) | Term::Set(items) => {
+            Value::Array(items.iter().map(|i| literal(i, line)).collect::<Result<_>>()?)
+        }
+        Term::Object(entries) => {
+            let mut map = serde_json::Map::new();
+            for (k, v) in entries {
+                let Term::Str(k) = k else {
+                    return Err(err(line, "decision keys must be strings"));
+                };
+                map.insert(k.clone(), literal(v, line)?);
+            }
+            Value::Object(map)
+        }
+        _ => return Err(err(line, "a decision must be a constant object")),
+    })
+}
+
+// ---------------------------------------------------------------------------
+// CEL emission
+// ---------------------------------------------------------------------------
+
+fn quote(s: &str) -> String {
+    serde_json::to_string(s).expect("string serializes")
+}
+
+fn emit(t: &Term, line: usize) -> Result<String> {
+    Ok(match t {
+        Term::Null => "null".to_string(),
+        Term::Bool(b) => b.to_string(),
+        Term::Int(n) => n.to_string(),
+        Term::Float(f) => format!("{:?}", f),
+        Term::Str(s) => quote(s),
+        Term::Array(items) | Term::Set(items) => {
+            let items = items.iter().map(|i| emit(i, line)).collect::<Result<Vec<_>>>()?;
+            format!("[{}]", items.join(", "))
+        }
+        Term::Object(entries) => {
+            let entries = entries
+                .iter()
+                .map(|(k, v)| Ok(format!("{}: {}", emit(k, line)?, emit(v, line)?)))
+                .collect::<Result<Vec<_>>>()?;
+            format!("{{{}}}", entries.join(", "))
+        }
+        Term::Ref(field, path) => {
+            let mut out = field.clone();
+            for step in path {
+                match step {
+                    Step::Field(f) => {
+                        out.push('.');
+                        out.push_str(f);
+                    }
+                    Step::Index(i) => out.push_str(&format!("[{}]", emit(i, line)?)),
+                }
+            }
+            out
+        }
+        Term::Call(name, args) => {
+            let args = args.iter().map(|a| emit(a, line)).collect::<Result<Vec<_>>>()?;
+            match name.as_str() {
+                "count" => format!("size({})", args[0]),
+                "startswith" => format!("({}).startsWith({})", args[0], args[1]),
+                "endswith" => format!("({}).endsWith({})", args[0], args[1]),
+                _ => format!("({}).contains({})", args[0], args[1]),
+            }
+        }
+        Term::Not(inner) => format!("!({})", guarded(inner, line)?),
+        Term::Neg(inner) => format!("-({})", emit(inner, line)?),
+        Term::Binary(op, l, r) => {
+            if *op == "in" && matches!(**r, Term::Object(_)) {
+                return Err(err(line, "'in' over an object literal is not supported; use a set"));
+            }
+            format!("({} {} {})", emit(l, line)?, op, emit(r, line)?)
+        }
+    })
+}
+
+/// `term`, preceded by presence checks for every field path it reads
+/// (outside nested `not`, which guards its own)
+fn guarded(t: &Term, line: usize) -> Result<String> {
+    let mut guards = Vec::new();
+    presence(t, &mut guards, line)?;
+    guards.push(emit(t, line)?);
+    Ok(guards.join(" && "))
+}
+
+fn presence(t: &Term, out: &mut Vec<String>, line: usize) -> Result<()> {
+    match t {
+        Term::Ref(field, path) => {
+            let mut prefix = field.clone();
+            if field == "state" && !path.is_empty() {
+                push_unique(out, "state != null".to_string());
+            }
+            for step in path {
+                match step {
+                    Step::Field(f) => {
+                        push_unique(out, format!("has({}.{})", prefix, f));
+                        prefix = format!("{}.{}", prefix, f);
+                    }
+                    Step::Index(i) => {
+                        presence(i, out, line)?;
+                        let index = emit(i, line)?;
+                        if let Term::Str(_) = **i {
+                            push_unique(out, format!("({} in {})", index, prefix));
+                        }
+                        prefix = format!("{}[{}]", prefix, index);
+                    }
+                }
+            }
+        }
+        Term::Array(items) | Term::Set(items) | Term::Call(_, items) => {
+            for i in items {
+                presence(i, out, line)?;
+            }
+        }
+        Term::Object(entries) => {
+            for (k, v) in entries {
+                presence(k, out, line)?;
+                presence(v, out, line)?;
+            }
+        }
+        Term::Neg(inner) => presence(inner, out, line)?,
+        Term::Binary(_, l, r) => {
+            presence(l, out, line)?;
+            presence(r, out, line)?;
+        }
+        Term::Not(_) | Term::Null | Term::Bool(_) | Term::Int(_) | Term::Float(_) | Term::Str(_) => {}
+    }
+    Ok(())
+}
+
+fn push_unique(out: &mut Vec<String>, guard: String) {
+    if !out.contains(&guard) {
+        out.push(guard);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DenyCode, EvaluationContext, TranslationDecision};
+
+    const BANK: &str = r#"
+package ubl.bank
+import rego.v1
+
+# Large transfers need the treasury pact
+allow := {"intent_class": 1, "required_pact": "high_value_transfer"} if {
+    input.intent.type == "transfer"
+    input.intent.amount > 10000
+}
+
+allow := {"intent_class": 1} if {
+    input.intent.type = "transfer"; not input.intent.frozen == true
+}
+
+deny := {"code": "actor_blocked", "detail": "blocked"} if input.actor in {"mallory", "trudy"}
+
+default deny := {"code": "unknown_intent"}
+"#;
+
+    fn decide(src: &str, actor: &str, intent: serde_json::Value) -> Result<TranslationDecision> {
+        let imported = import(src)?;
+        let program = cel::CelProgram::compile(&imported.policy).unwrap();
+        program.evaluate(&EvaluationContext {
+            container_id: "C.Bank".to_string(),
+            actor: actor.to_string(),
+            intent,
+            state: None,
+            timestamp: 0,
+            spec: Default::default(),
+        })
+    }
+
+    #[test]
+    fn test_import_orders_denies_first_and_guards_missing_fields() {
+        let imported = import(BANK).unwrap();
+        assert_eq!(imported.package, "ubl.bank");
+        assert_eq!(imported.policy.rules.len(), 4);
+        assert!(matches!(imported.policy.rules[0].then, CelOutcome::Deny { code: DenyCode::ActorBlocked, .. }));
+
+        let big = serde_json::json!({"type": "transfer", "amount": 20000});
+        assert!(matches!(
+            decide(BANK, "alice", big.clone()).unwrap(),
+            TranslationDecision::Allow { required_pact: Some(_), .. }
+        ));
+        assert!(matches!(
+            decide(BANK, "mallory", big).unwrap(),
+            TranslationDecision::Deny { code: DenyCode::ActorBlocked, .. }
+        ));
+        // No amount: the first allow is undefined, not an error; `not` of a missing field holds
+        assert!(matches!(
+            decide(BANK, "alice", serde_json::json!({"type": "transfer"})).unwrap(),
+            TranslationDecision::Allow { required_pact: None, .. }
+        ));
+        assert!(matches!(
+            decide(BANK, "alice", serde_json::json!({"type": "transfer", "frozen": true})).unwrap(),
+            TranslationDecision::Deny { code: DenyCode::UnknownIntent, .. }
+        ));
+    }
+
+    #[test]
+    fn test_rejects_outside_subset_with_line() {
+        for (src, line) in [
+            ("package p\nallow := {\"intent_class\": 0} if data.flags.open", 2),
+            ("package p\n\nallow := {\"intent_class\": 0} if {\n  x := input.actor\n  x == \"a\"\n}", 4),
+            ("package p\nviolation[msg] if { true }", 2),
+            ("package p\nallow := {\"intent_class\": 0} if regex.match(\"a\", input.actor)", 2),
+            ("package p\nimport data.lib", 2),
+            ("package p\nallow := {\"intent_class\": input.x}", 2),
+        ] {
+            match import(src) {
+                Err(PolicyError::RegoImport { line: l, .. }) => assert_eq!(l, line, "{}", src),
+                other => panic!("{}: {:?}", src, other),
+            }
+        }
+        assert!(import("allow := {\"intent_class\": 0}").is_err());
+    }
+}
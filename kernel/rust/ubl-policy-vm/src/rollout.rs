@@ -0,0 +1,319 @@
+//! Progressive delivery of policy versions
+//!
+//! A [`Rollout`] puts a new version of a policy (the candidate) in front of
+//! a share of commits only. Every context falls in one of 100 buckets by the
+//! BLAKE3 hash of its container id or actor ([`BucketBy`]); while the
+//! candidate is the version active at the context's timestamp, buckets below
+//! the rollout's percentage at that timestamp evaluate under it and the rest
+//! under the version before it (the baseline). The percentage is a schedule
+//! of [`RolloutStep`]s and a halt is a time, so replaying a link with its
+//! original timestamp selects the same version it was committed under.
+//!
+//! With a [`RolloutMonitor`] set on the VM, each decision under a rollout is
+//! counted per arm, and contexts in the candidate arm are also evaluated
+//! under the baseline, off the record, to count where the two disagree
+//! ([`RolloutStats`]). Decisions the cache answers are not counted. Once the
+//! candidate has decided `min_samples` contexts and denied more than
+//! `max_deny_percent` of them, [`Rollout::breached`] holds; halting is the
+//! caller's to record ([`Rollout::halt`]), after which every context is back
+//! on the baseline.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{EvaluationContext, TranslationDecision};
+
+/// What a context is bucketed by
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BucketBy {
+    /// `container_id`: a container is wholly on one version
+    #[default]
+    Container,
+    /// `actor`: an actor is on one version across containers
+    Actor,
+}
+
+/// From `from` on, `percent` of buckets evaluate under the candidate
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RolloutStep {
+    /// Unix seconds (compared against `EvaluationContext::timestamp`)
+    pub from: i64,
+    /// Share of buckets on the candidate, 0 to 100
+    pub percent: u8,
+}
+
+/// Percentage rollout of one policy version
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Rollout {
+    /// Policy identifier
+    pub policy_id: String,
+    /// Candidate version; it needs a version before it to fall back to
+    pub version: String,
+    /// What contexts are bucketed by
+    #[serde(default)]
+    pub bucket_by: BucketBy,
+    /// Percentage schedule, ordered by `from`; none before the first step
+    pub steps: Vec<RolloutStep>,
+    /// Unix seconds from which every context is back on the baseline
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub halted_at: Option<i64>,
+    /// Why the rollout was halted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub halt_reason: Option<String>,
+    /// Candidate deny rate, in percent, above which the rollout is breached
+    pub max_deny_percent: u8,
+    /// Candidate decisions needed before the deny rate is judged
+    pub min_samples: u64,
+}
+
+impl Rollout {
+    /// Check the schedule: percentages up to 100, steps in strictly
+    /// increasing `from` order
+    pub fn check(&self) -> std::result::Result<(), String> {
+        if let Some(step) = self.steps.iter().find(|s| s.percent > 100) {
+            return Err(format!("step from {} is {}%", step.from, step.percent));
+        }
+        if self.steps.windows(2).any(|w| w[0].from >= w[1].from) {
+            return Err("steps are not in increasing time order".to_string());
+        }
+        if self.max_deny_percent > 100 {
+            return Err(format!("max_deny_percent is {}", self.max_deny_percent));
+        }
+        Ok(())
+    }
+
+    /// Share of buckets on the candidate at `at`
+    pub fn percent_at(&self, at: i64) -> u8 {
+        if self.halted_at.is_some_and(|h| at >= h) {
+            return 0;
+        }
+        self.steps
+            .iter()
+            .rev()
+            .find(|s| s.from <= at)
+            .map_or(0, |s| s.percent)
+    }
+
+    /// Bucket of `context`, 0 to 99
+    pub fn bucket(&self, context: &EvaluationContext) -> u8 {
+        let key = match self.bucket_by {
+            BucketBy::Container => &context.container_id,
+            BucketBy::Actor => &context.actor,
+        };
+        let hash = blake3::hash(key.as_bytes());
+        let head = u64::from_be_bytes(hash.as_bytes()[..8].try_into().expect("8 bytes"));
+        (head % 100) as u8
+    }
+
+    /// Whether `context` evaluates under the candidate
+    pub fn selects(&self, context: &EvaluationContext) -> bool {
+        self.bucket(context) < self.percent_at(context.timestamp)
+    }
+
+    /// Whether the candidate's deny rate in `stats` is over the limit
+    pub fn breached(&self, stats: &RolloutStats) -> bool {
+        stats.candidate >= self.min_samples.max(1)
+            && stats.candidate_denied * 100 > u64::from(self.max_deny_percent) * stats.candidate
+    }
+
+    /// Stop the rollout from `at` on; a rollout halts once
+    pub fn halt(&mut self, at: i64, reason: impl Into<String>) -> bool {
+        if self.halted_at.is_some() {
+            return false;
+        }
+        self.halted_at = Some(at);
+        self.halt_reason = Some(reason.into());
+        true
+    }
+}
+
+/// Decisions under a rollout, per arm
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RolloutStats {
+    /// Decisions under the candidate
+    pub candidate: u64,
+    /// Of those, denials
+    pub candidate_denied: u64,
+    /// Decisions under the baseline
+    pub baseline: u64,
+    /// Of those, denials
+    pub baseline_denied: u64,
+    /// Candidate decisions the baseline also decided, off the record
+    pub compared: u64,
+    /// Of those, decisions the baseline would have made differently
+    pub diverged: u64,
+}
+
+/// Rollout counters, shared across rebuilt VMs like the WASM runtime
+#[derive(Debug, Default)]
+pub struct RolloutMonitor {
+    stats: Mutex<HashMap<(String, String), RolloutStats>>,
+}
+
+impl RolloutMonitor {
+    /// Monitor with no counts
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Counts for `rollout` so far
+    pub fn stats(&self, rollout: &Rollout) -> RolloutStats {
+        let key = (rollout.policy_id.clone(), rollout.version.clone());
+        self.stats.lock().unwrap().get(&key).copied().unwrap_or_default()
+    }
+
+    /// Count a decision under the baseline
+    pub(crate) fn baseline(&self, rollout: &Rollout, decision: &TranslationDecision) {
+        let key = (rollout.policy_id.clone(), rollout.version.clone());
+        let mut stats = self.stats.lock().unwrap();
+        let s = stats.entry(key).or_default();
+        s.baseline += 1;
+        s.baseline_denied += u64::from(is_deny(decision));
+    }
+
+    /// Count a decision under the candidate, and what the baseline would
+    /// have decided when it could tell
+    pub(crate) fn candidate(
+        &self,
+        rollout: &Rollout,
+        decision: &TranslationDecision,
+        baseline: Option<&TranslationDecision>,
+    ) {
+        let key = (rollout.policy_id.clone(), rollout.version.clone());
+        let mut stats = self.stats.lock().unwrap();
+        let s = stats.entry(key).or_default();
+        s.candidate += 1;
+        s.candidate_denied += u64::from(is_deny(decision));
+        if let Some(b) = baseline {
+            s.compared += 1;
+            s.diverged += u64::from(b != decision);
+        }
+    }
+}
+
+fn is_deny(decision: &TranslationDecision) -> bool {
+    matches!(decision, TranslationDecision::Deny { .. })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bytecode_hash, Policy, PolicyVM, SpecVersion};
+    use std::sync::Arc;
+
+    fn rollout(percent: u8) -> Rollout {
+        Rollout {
+            policy_id: "limits".to_string(),
+            version: "2".to_string(),
+            bucket_by: BucketBy::Container,
+            steps: vec![RolloutStep { from: 100, percent }],
+            halted_at: None,
+            halt_reason: None,
+            max_deny_percent: 20,
+            min_samples: 10,
+        }
+    }
+
+    fn context(container_id: &str, timestamp: i64) -> EvaluationContext {
+        EvaluationContext {
+            container_id: container_id.to_string(),
+            actor: "alice".to_string(),
+            intent: serde_json::json!({ "type": "transfer", "amount": 50 }),
+            state: None,
+            timestamp,
+            spec: SpecVersion::V1_0,
+        }
+    }
+
+    /// v1 allows every transfer, v2 denies them
+    fn vm() -> PolicyVM {
+        let mut vm = PolicyVM::new();
+        for (version, active_from, rules) in [
+            ("1", 0, r#"{"rules":[{"when":"true","allow":{"intent_class":1}}]}"#),
+            ("2", 100, r#"{"rules":[{"when":"true","deny":{"code":"amount_exceeded"}}]}"#),
+        ] {
+            let mut bytecode = crate::cel::MAGIC.to_vec();
+            bytecode.extend(rules.as_bytes());
+            vm.register(Policy {
+                policy_id: "limits".to_string(),
+                version: version.to_string(),
+                bytecode_hash: bytecode_hash(&bytecode),
+                bytecode,
+                description: String::new(),
+                active_from,
+            })
+            .unwrap();
+        }
+        vm
+    }
+
+    #[test]
+    fn test_schedule_and_halt() {
+        let mut r = rollout(10);
+        r.steps.push(RolloutStep { from: 200, percent: 50 });
+        assert!(r.check().is_ok());
+        assert_eq!((r.percent_at(99), r.percent_at(100), r.percent_at(250)), (0, 10, 50));
+        assert!(r.halt(300, "deny rate"));
+        assert!(!r.halt(400, "again"));
+        assert_eq!((r.percent_at(299), r.percent_at(300)), (50, 0));
+
+        r.steps.push(RolloutStep { from: 150, percent: 60 });
+        assert!(r.check().is_err());
+        assert!(rollout(101).check().is_err());
+    }
+
+    #[test]
+    fn test_buckets_split_contexts() {
+        let mut vm = vm();
+        vm.set_rollout(rollout(30)).unwrap();
+        let containers: Vec<String> = (0..1000).map(|i| format!("C.{}", i)).collect();
+        let on_candidate = containers
+            .iter()
+            .filter(|c| vm.version_for("limits", &context(c, 150)).unwrap().version == "2")
+            .count();
+        assert!((200..400).contains(&on_candidate), "{} of 1000 on the candidate", on_candidate);
+
+        // The same context always lands on the same version; before the
+        // first step, and for the version before, nothing changes
+        let c = context(&containers[0], 150);
+        let v = vm.version_for("limits", &c).unwrap().version.clone();
+        assert!((0..10).all(|_| vm.version_for("limits", &c).unwrap().version == v));
+        assert_eq!(vm.version_for("limits", &context("C.0", 99)).unwrap().version, "1");
+
+        // The first version has nothing to fall back to
+        let mut first = rollout(30);
+        first.version = "1".to_string();
+        assert!(matches!(vm.set_rollout(first), Err(crate::PolicyError::InvalidRollout { .. })));
+    }
+
+    #[test]
+    fn test_monitor_counts_divergence_and_breach() {
+        let mut vm = vm();
+        let monitor = Arc::new(RolloutMonitor::new());
+        vm.set_rollout_monitor(Some(monitor.clone()));
+        vm.set_rollout(rollout(50)).unwrap();
+        for i in 0..200 {
+            let c = context(&format!("C.{}", i), 150);
+            let on_candidate = vm.version_for("limits", &c).unwrap().version == "2";
+            assert_eq!(is_deny(&vm.evaluate("limits", &c).unwrap()), on_candidate);
+        }
+        let r = vm.rollout("limits", "2").unwrap().clone();
+        let stats = monitor.stats(&r);
+        assert_eq!(stats.candidate + stats.baseline, 200);
+        assert_eq!((stats.candidate_denied, stats.baseline_denied), (stats.candidate, 0));
+        assert_eq!((stats.compared, stats.diverged), (stats.candidate, stats.candidate));
+        assert!(r.breached(&stats));
+
+        // Halted: everyone is back on the baseline from then on
+        let mut halted = r.clone();
+        halted.halt(160, "deny rate");
+        vm.set_rollout(halted).unwrap();
+        assert!((0..50).all(|i| {
+            let c = context(&format!("C.{}", i), 170);
+            vm.version_for("limits", &c).unwrap().version == "1" && !is_deny(&vm.evaluate("limits", &c).unwrap())
+        }));
+    }
+}
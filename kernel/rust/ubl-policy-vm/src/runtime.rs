@@ -0,0 +1,265 @@
+//! Executing WASM policies (feature `wasmtime`)
+//!
+//! With a [`WasmRuntime`] set on the VM ([`PolicyVM::set_wasm_runtime`]),
+//! policies whose bytecode is a WASM module run on wasmtime instead of the
+//! builtin rules. Modules are compiled once and kept in a
+//! [`ModuleCache`] by bytecode hash; only instantiation is paid per
+//! evaluation. The runtime is shared behind an `Arc`, so a VM rebuilt on
+//! reload keeps the compiled modules and their counters.
+//!
+//! The host ABI, imported from [`HOST_MODULE`]:
+//!
+//! | Function         | Signature          | Effect                                        |
+//! |------------------|--------------------|-----------------------------------------------|
+//! | `context_len`    | `() -> i32`        | Length of the JSON [`EvaluationContext`]      |
+//! | `context_read`   | `(ptr: i32)`       | Copy the context JSON to `ptr`                |
+//! | `decision_write` | `(ptr: i32, len: i32)` | Hand back a JSON [`TranslationDecision`]  |
+//!
+//! The module exports `memory` and `evaluate: () -> ()`; the last decision
+//! written before `evaluate` returns is the policy's. The engine has SIMD,
+//! relaxed SIMD and threads off and canonicalizes NaNs, matching what
+//! [`crate::wasm::analyze`] admits. Every evaluation gets
+//! [`FUEL_PER_EVALUATION`]; running out, or a tripped [`Interrupt`] seen at
+//! a host call, is [`PolicyError::Timeout`].
+//!
+//! [`PolicyVM::set_wasm_runtime`]: crate::PolicyVM::set_wasm_runtime
+//! [`HOST_MODULE`]: crate::wasm::HOST_MODULE
+
+use std::sync::Mutex;
+
+use wasmtime::{Caller, Config, Engine, Linker, Module, Store, Trap};
+
+use crate::module_cache::{ModuleCache, ModuleCacheStats};
+use crate::wasm::HOST_MODULE;
+use crate::{EvaluationContext, Interrupt, Policy, PolicyError, Result, TranslationDecision};
+
+/// Fuel (roughly, WASM instructions) one evaluation may burn
+pub const FUEL_PER_EVALUATION: u64 = 10_000_000;
+
+/// Per-evaluation state the host functions see
+struct Host {
+    context: Vec<u8>,
+    decision: Option<Vec<u8>>,
+    interrupt: Option<Interrupt>,
+    interrupted: bool,
+}
+
+impl Host {
+    /// Note a tripped interrupt; the call then traps
+    fn check(&mut self) -> wasmtime::Result<()> {
+        if self.interrupt.as_ref().is_some_and(Interrupt::is_tripped) {
+            self.interrupted = true;
+            return Err(wasmtime::Error::msg("interrupted"));
+        }
+        Ok(())
+    }
+}
+
+/// The guest's exported memory
+fn memory(caller: &mut Caller<'_, Host>) -> wasmtime::Result<wasmtime::Memory> {
+    caller
+        .get_export("memory")
+        .and_then(|e| e.into_memory())
+        .ok_or_else(|| wasmtime::Error::msg("module exports no memory"))
+}
+
+/// Bounds-checked `ptr..ptr + len` of linear memory
+fn range(ptr: i32, len: usize, size: usize) -> wasmtime::Result<std::ops::Range<usize>> {
+    let start = u32::try_from(ptr).map_err(|_| wasmtime::Error::msg("negative pointer"))? as usize;
+    match start.checked_add(len) {
+        Some(end) if end <= size => Ok(start..end),
+        _ => Err(wasmtime::Error::msg("access out of linear memory")),
+    }
+}
+
+/// wasmtime engine, host ABI and compiled module cache
+pub struct WasmRuntime {
+    engine: Engine,
+    linker: Linker<Host>,
+    modules: Mutex<ModuleCache<Module>>,
+}
+
+impl WasmRuntime {
+    /// Runtime keeping up to `capacity` compiled modules
+    pub fn new(capacity: usize) -> Result<Self> {
+        let mut config = Config::new();
+        config
+            .wasm_simd(false)
+            .wasm_relaxed_simd(false)
+            .wasm_threads(false)
+            .cranelift_nan_canonicalization(true)
+            .consume_fuel(true);
+        let engine = Engine::new(&config).map_err(|e| PolicyError::ExecutionFailed(e.to_string()))?;
+
+        let mut linker = Linker::new(&engine);
+        let host = |e: wasmtime::Error| PolicyError::ExecutionFailed(e.to_string());
+        linker
+            .func_wrap(HOST_MODULE, "context_len", |caller: Caller<'_, Host>| {
+                caller.data().context.len() as i32
+            })
+            .map_err(host)?;
+        linker
+            .func_wrap(HOST_MODULE, "context_read", |mut caller: Caller<'_, Host>, ptr: i32| {
+                caller.data_mut().check()?;
+                let memory = memory(&mut caller)?;
+                let (mem, host) = memory.data_and_store_mut(&mut caller);
+                let range = range(ptr, host.context.len(), mem.len())?;
+                mem[range].copy_from_slice(&host.context);
+                Ok(())
+            })
+            .map_err(host)?;
+        linker
+            .func_wrap(
+                HOST_MODULE,
+                "decision_write",
+                |mut caller: Caller<'_, Host>, ptr: i32, len: i32| {
+                    caller.data_mut().check()?;
+                    let len = u32::try_from(len).map_err(|_| wasmtime::Error::msg("negative length"))? as usize;
+                    let memory = memory(&mut caller)?;
+                    let (mem, host) = memory.data_and_store_mut(&mut caller);
+                    host.decision = Some(mem[range(ptr, len, mem.len())?].to_vec());
+                    Ok(())
+                },
+            )
+            .map_err(host)?;
+
+        Ok(Self {
+            engine,
+            linker,
+            modules: Mutex::new(ModuleCache::new(capacity)),
+        })
+    }
+
+    /// Compiled module cache counters
+    pub fn stats(&self) -> ModuleCacheStats {
+        self.modules.lock().unwrap().stats()
+    }
+
+    /// `policy`'s compiled module, from the cache or compiled now
+    pub fn module(&self, policy: &Policy) -> Result<Module> {
+        if let Some(module) = self.modules.lock().unwrap().get(&policy.bytecode_hash) {
+            return Ok(module);
+        }
+        // Compile outside the lock; a concurrent miss on the same hash
+        // compiles twice and the second insert replaces the first
+        let module = Module::new(&self.engine, &policy.bytecode).map_err(|e| PolicyError::CompileFailed {
+            policy_id: policy.policy_id.clone(),
+            reason: e.to_string(),
+        })?;
+        self.modules.lock().unwrap().insert(&policy.bytecode_hash, module.clone());
+        Ok(module)
+    }
+
+    /// Run `policy`'s `evaluate` export on `context`
+    pub fn evaluate(
+        &self,
+        policy: &Policy,
+        context: &EvaluationContext,
+        interrupt: Option<&Interrupt>,
+    ) -> Result<TranslationDecision> {
+        let failed = |reason: String| PolicyError::ExecutionFailed(format!("{}: {}", policy.policy_id, reason));
+        let module = self.module(policy)?;
+        let host = Host {
+            context: serde_json::to_vec(context).map_err(|e| failed(e.to_string()))?,
+            decision: None,
+            interrupt: interrupt.cloned(),
+            interrupted: false,
+        };
+        let mut store = Store::new(&self.engine, host);
+        store.set_fuel(FUEL_PER_EVALUATION).map_err(|e| failed(e.to_string()))?;
+
+        let outcome = self
+            .linker
+            .instantiate(&mut store, &module)
+            .and_then(|instance| instance.get_typed_func::<(), ()>(&mut store, "evaluate"))
+            .and_then(|evaluate| evaluate.call(&mut store, ()));
+        if let Err(e) = outcome {
+            if store.data().interrupted || e.downcast_ref::<Trap>() == Some(&Trap::OutOfFuel) {
+                return Err(PolicyError::Timeout);
+            }
+            return Err(failed(e.to_string()));
+        }
+
+        let decision = store
+            .into_data()
+            .decision
+            .ok_or_else(|| failed("no decision written".to_string()))?;
+        serde_json::from_slice(&decision).map_err(|e| failed(format!("decision is not valid JSON: {}", e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bytecode_hash, PolicyVM};
+    use std::sync::Arc;
+
+    const ALLOW: &str = r#"
+        (module
+          (import "ubl" "decision_write" (func $decide (param i32 i32)))
+          (memory (export "memory") 1 1)
+          (data (i32.const 0) "{\"Allow\":{\"intent_class\":0,\"required_pact\":null,\"constraints\":[]}}")
+          (func (export "evaluate")
+            (call $decide (i32.const 0) (i32.const 66))))
+    "#;
+
+    const SPIN: &str = r#"
+        (module
+          (memory (export "memory") 1 1)
+          (func (export "evaluate")
+            (loop $spin (br $spin))))
+    "#;
+
+    fn policy(id: &str, src: &str) -> Policy {
+        let bytecode = wat::parse_str(src).unwrap();
+        Policy {
+            policy_id: id.to_string(),
+            version: "1".to_string(),
+            bytecode_hash: bytecode_hash(&bytecode),
+            bytecode,
+            description: id.to_string(),
+            active_from: 0,
+        }
+    }
+
+    fn context() -> EvaluationContext {
+        EvaluationContext {
+            container_id: "C.Test".to_string(),
+            actor: "alice".to_string(),
+            intent: serde_json::json!({ "type": "anything" }),
+            state: None,
+            timestamp: 0,
+            spec: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_compiles_once_per_bytecode() {
+        let runtime = Arc::new(WasmRuntime::new(1).unwrap());
+        let mut vm = PolicyVM::new();
+        vm.set_wasm_runtime(Some(runtime.clone()));
+        vm.register(policy("allow", ALLOW)).unwrap();
+        vm.register(policy("spin", SPIN)).unwrap();
+
+        for _ in 0..3 {
+            assert!(matches!(vm.evaluate("allow", &context()), Ok(TranslationDecision::Allow { .. })));
+        }
+        assert_eq!((runtime.stats().hits, runtime.stats().misses), (2, 1));
+
+        // Capacity 1: the second module evicts the first
+        assert_eq!(vm.evaluate("spin", &context()), Err(PolicyError::Timeout));
+        vm.evaluate("allow", &context()).unwrap();
+        let stats = runtime.stats();
+        assert_eq!((stats.entries, stats.misses, stats.evictions), (1, 3, 2));
+    }
+
+    #[test]
+    fn test_missing_decision_fails() {
+        let runtime = WasmRuntime::new(4).unwrap();
+        let silent = policy("silent", "(module (memory (export \"memory\") 1 1) (func (export \"evaluate\")))");
+        assert!(matches!(
+            runtime.evaluate(&silent, &context(), None),
+            Err(PolicyError::ExecutionFailed(reason)) if reason.contains("no decision")
+        ));
+    }
+}
@@ -0,0 +1,271 @@
+//! Weekly schedules for the `schedule` constraint
+//!
+//! A schedule lists the hours of the week in which an intent may run, such
+//! as `mon-fri 09:00-18:00`. Windows are separated by `;` and a leading
+//! `UTC+HH:MM` / `UTC-HH:MM` shifts every window by a fixed offset (no
+//! daylight saving; the default is UTC):
+//!
+//! ```text
+//! UTC-03:00 mon-fri 09:00-18:00; sat 10:00-14:00
+//! fri,sat 22:00-02:00
+//! ```
+//!
+//! Days are `mon`..`sun`, ranges (`fri-mon` wraps), lists, or `*`. Times run
+//! from `00:00` to `24:00`, start inclusive and end exclusive. A window whose
+//! end is before its start runs overnight into the following day, which is
+//! still counted from the listed day. [`Schedule::contains`] reads a unix
+//! timestamp in seconds with integer arithmetic only, so every host agrees.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use thiserror::Error;
+
+const DAYS: [&str; 7] = ["mon", "tue", "wed", "thu", "fri", "sat", "sun"];
+const SECS_PER_DAY: i64 = 86_400;
+
+/// A schedule string that does not parse
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("invalid schedule: {0}")]
+pub struct ScheduleError(pub String);
+
+/// One recurring window within the week
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScheduleWindow {
+    /// Days the window opens on, Monday first
+    pub days: [bool; 7],
+    /// Opening time, seconds after local midnight
+    pub start: u32,
+    /// Closing time, seconds after local midnight; before `start` runs overnight
+    pub end: u32,
+}
+
+/// Weekly schedule: the union of its windows
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Schedule {
+    /// Fixed offset of the windows from UTC, in minutes
+    pub utc_offset_minutes: i32,
+    /// Windows, any of which admits a timestamp
+    pub windows: Vec<ScheduleWindow>,
+}
+
+impl Schedule {
+    /// Parse the schedule syntax described in the module docs
+    pub fn parse(s: &str) -> Result<Self, ScheduleError> {
+        let err = |m: String| ScheduleError(m);
+        let mut rest = s.trim();
+        let mut utc_offset_minutes = 0;
+        if let Some(tail) = rest.strip_prefix("UTC") {
+            let (offset, after) = tail.split_once(char::is_whitespace).unwrap_or((tail, ""));
+            utc_offset_minutes = parse_offset(offset).ok_or_else(|| err(format!("bad UTC offset {:?}", offset)))?;
+            rest = after;
+        }
+
+        let mut windows = Vec::new();
+        for window in rest.split(';').map(str::trim) {
+            let (days, times) = window
+                .split_once(char::is_whitespace)
+                .ok_or_else(|| err(format!("expected `<days> HH:MM-HH:MM`, got {:?}", window)))?;
+            let (start, end) = times
+                .trim()
+                .split_once('-')
+                .ok_or_else(|| err(format!("expected HH:MM-HH:MM, got {:?}", times.trim())))?;
+            let (start, end) = (parse_time(start)?, parse_time(end)?);
+            if start == end {
+                return Err(err(format!("empty window {:?}", window)));
+            }
+            windows.push(ScheduleWindow {
+                days: parse_days(days)?,
+                start,
+                end,
+            });
+        }
+        Ok(Self {
+            utc_offset_minutes,
+            windows,
+        })
+    }
+
+    /// Whether unix time `timestamp` (seconds) falls inside any window
+    pub fn contains(&self, timestamp: i64) -> bool {
+        let local = timestamp + self.utc_offset_minutes as i64 * 60;
+        // 1970-01-01 was a Thursday
+        let weekday = (local.div_euclid(SECS_PER_DAY) + 3).rem_euclid(7) as usize;
+        let yesterday = (weekday + 6) % 7;
+        let secs = local.rem_euclid(SECS_PER_DAY) as u32;
+        self.windows.iter().any(|w| {
+            if w.start < w.end {
+                w.days[weekday] && (w.start..w.end).contains(&secs)
+            } else {
+                (w.days[weekday] && secs >= w.start) || (w.days[yesterday] && secs < w.end)
+            }
+        })
+    }
+}
+
+impl TryFrom<String> for Schedule {
+    type Error = ScheduleError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        Self::parse(&s)
+    }
+}
+
+impl From<Schedule> for String {
+    fn from(s: Schedule) -> String {
+        s.to_string()
+    }
+}
+
+impl std::str::FromStr for Schedule {
+    type Err = ScheduleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for Schedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.utc_offset_minutes != 0 {
+            let sign = if self.utc_offset_minutes < 0 { '-' } else { '+' };
+            let m = self.utc_offset_minutes.unsigned_abs();
+            write!(f, "UTC{}{:02}:{:02} ", sign, m / 60, m % 60)?;
+        }
+        for (i, w) in self.windows.iter().enumerate() {
+            if i > 0 {
+                f.write_str("; ")?;
+            }
+            write!(f, "{} {}-{}", fmt_days(&w.days), fmt_time(w.start), fmt_time(w.end))?;
+        }
+        Ok(())
+    }
+}
+
+fn parse_offset(s: &str) -> Option<i32> {
+    let sign = match s.as_bytes().first()? {
+        b'+' => 1,
+        b'-' => -1,
+        _ => return None,
+    };
+    let (h, m) = s[1..].split_once(':')?;
+    let (h, m): (i32, i32) = (h.parse().ok()?, m.parse().ok()?);
+    (h <= 14 && m < 60).then_some(sign * (h * 60 + m))
+}
+
+fn parse_time(s: &str) -> Result<u32, ScheduleError> {
+    let s = s.trim();
+    let parsed = s
+        .split_once(':')
+        .filter(|(h, m)| h.len() == 2 && m.len() == 2)
+        .and_then(|(h, m)| Some((h.parse::<u32>().ok()?, m.parse::<u32>().ok()?)))
+        .filter(|&(h, m)| m < 60 && (h < 24 || (h, m) == (24, 0)));
+    parsed
+        .map(|(h, m)| (h * 60 + m) * 60)
+        .ok_or_else(|| ScheduleError(format!("bad time {:?}", s)))
+}
+
+fn parse_day(s: &str) -> Result<usize, ScheduleError> {
+    DAYS.iter()
+        .position(|d| d.eq_ignore_ascii_case(s.trim()))
+        .ok_or_else(|| ScheduleError(format!("unknown day {:?}", s.trim())))
+}
+
+fn parse_days(s: &str) -> Result<[bool; 7], ScheduleError> {
+    let mut days = [false; 7];
+    for item in s.split(',') {
+        if item.trim() == "*" {
+            days = [true; 7];
+            continue;
+        }
+        match item.split_once('-') {
+            Some((from, to)) => {
+                let (from, to) = (parse_day(from)?, parse_day(to)?);
+                let mut d = from;
+                loop {
+                    days[d] = true;
+                    if d == to {
+                        break;
+                    }
+                    d = (d + 1) % 7;
+                }
+            }
+            None => days[parse_day(item)?] = true,
+        }
+    }
+    Ok(days)
+}
+
+fn fmt_days(days: &[bool; 7]) -> String {
+    if days.iter().all(|&d| d) {
+        return "*".to_string();
+    }
+    let mut runs = Vec::new();
+    let mut i = 0;
+    while i < 7 {
+        if !days[i] {
+            i += 1;
+            continue;
+        }
+        let start = i;
+        while i + 1 < 7 && days[i + 1] {
+            i += 1;
+        }
+        runs.push(if start == i {
+            DAYS[start].to_string()
+        } else {
+            format!("{}-{}", DAYS[start], DAYS[i])
+        });
+        i += 1;
+    }
+    runs.join(",")
+}
+
+fn fmt_time(secs: u32) -> String {
+    format!("{:02}:{:02}", secs / 3600, secs / 60 % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2024-01-01 00:00 UTC was a Monday
+    const MONDAY: i64 = 1_704_067_200;
+    const HOUR: i64 = 3600;
+
+    #[test]
+    fn test_business_hours() {
+        let s = Schedule::parse("mon-fri 09:00-18:00").unwrap();
+        assert!(s.contains(MONDAY + 9 * HOUR));
+        assert!(!s.contains(MONDAY + 18 * HOUR));
+        assert!(!s.contains(MONDAY + 8 * HOUR + 3599));
+        assert!(s.contains(MONDAY + 4 * 24 * HOUR + 12 * HOUR));
+        assert!(!s.contains(MONDAY + 5 * 24 * HOUR + 12 * HOUR));
+        assert!(!s.contains(MONDAY - 24 * HOUR + 12 * HOUR));
+        assert_eq!(s.to_string(), "mon-fri 09:00-18:00");
+    }
+
+    #[test]
+    fn test_offset_and_overnight() {
+        // 09:00 at UTC-03:00 is 12:00 UTC
+        let s = Schedule::parse("UTC-03:00 mon 09:00-10:00").unwrap();
+        assert!(s.contains(MONDAY + 12 * HOUR));
+        assert!(!s.contains(MONDAY + 9 * HOUR));
+
+        let s = Schedule::parse("sun 22:00-02:00").unwrap();
+        assert!(s.contains(MONDAY + HOUR));
+        assert!(s.contains(MONDAY - HOUR));
+        assert!(!s.contains(MONDAY + 23 * HOUR));
+        assert_eq!(Schedule::parse("fri-mon 00:00-24:00").unwrap().to_string(), "mon,fri-sun 00:00-24:00");
+    }
+
+    #[test]
+    fn test_malformed_schedules() {
+        for bad in ["", "mon", "mon 9:00-18:00", "mon 09:00-09:00", "xyz 09:00-10:00", "UTC+3 mon 09:00-10:00", "mon 24:01-01:00"] {
+            assert!(Schedule::parse(bad).is_err(), "{:?}", bad);
+        }
+        let s: Schedule = serde_json::from_str("\"UTC+05:30 * 00:00-12:00\"").unwrap();
+        assert_eq!(s.utc_offset_minutes, 330);
+        assert_eq!(serde_json::to_string(&s).unwrap(), "\"UTC+05:30 * 00:00-12:00\"");
+    }
+}
@@ -0,0 +1,289 @@
+//! Policy registry snapshots (SPEC-UBL-POLICY v1.0 §4)
+//!
+//! [`PolicyVM::export_snapshot`] writes the whole policy set as a
+//! [`PolicySnapshot`]: every version as a [`PolicyBundle`] with the
+//! governance signatures it was registered with, container attachments,
+//! namespace bindings, composition modes and rollouts. Everything is ordered (policy
+//! id, then activation; signatures by key), so two VMs holding the same set
+//! export byte-identical snapshots, and `snapshot_hash` (BLAKE3 over
+//! [`SNAPSHOT_DOMAIN`] and the canonical body) addresses it.
+//!
+//! A new node bootstraps with [`PolicyVM::import_snapshot`] from a peer,
+//! comparing the returned hash with the one it trusts. Import recomputes the
+//! hash, checks each bytecode hash and, when the VM has governance keys, each
+//! bundle's signatures, then replaces the VM's policy set in one step: on
+//! any error the VM is left as it was.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{CompositionMode, Policy, PolicyBundle, PolicyError, PolicyVM, Result, Rollout};
+
+/// Format tag of snapshots this VM writes and reads
+pub const SNAPSHOT_FORMAT: &str = "ubl-policy-snapshot/1";
+
+/// Domain tag prefixed to the canonical body before hashing
+pub const SNAPSHOT_DOMAIN: &[u8] = b"ubl:policy-snapshot\n";
+
+/// The policy set of a VM, hash-addressed
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PolicySnapshot {
+    /// [`SNAPSHOT_FORMAT`]
+    pub format: String,
+    /// BLAKE3 hex of [`PolicySnapshot::hashed_bytes`]
+    pub snapshot_hash: String,
+    /// Every policy version, by policy id then `active_from`
+    pub policies: Vec<PolicyBundle>,
+    /// Policies attached per container
+    #[serde(default)]
+    pub attachments: BTreeMap<String, Vec<String>>,
+    /// Policies bound per namespace prefix
+    #[serde(default)]
+    pub namespaces: BTreeMap<String, Vec<String>>,
+    /// Composition modes other than the default, per container
+    #[serde(default)]
+    pub composition: BTreeMap<String, CompositionMode>,
+    /// Rollouts, by policy id then version
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rollouts: Vec<Rollout>,
+}
+
+impl PolicySnapshot {
+    /// `SNAPSHOT_DOMAIN` followed by the canonical JSON of everything but the hash
+    pub fn hashed_bytes(&self) -> Result<Vec<u8>> {
+        let mut body = serde_json::to_value(self).map_err(|e| PolicyError::InvalidSnapshot(e.to_string()))?;
+        if let Some(map) = body.as_object_mut() {
+            map.remove("snapshot_hash");
+        }
+        let canonical = ubl_atom::canonicalize(&body).map_err(|e| PolicyError::InvalidSnapshot(e.to_string()))?;
+        Ok([SNAPSHOT_DOMAIN, canonical.as_slice()].concat())
+    }
+
+    /// Hash the snapshot's content addresses
+    pub fn compute_hash(&self) -> Result<String> {
+        Ok(hex::encode(blake3::hash(&self.hashed_bytes()?).as_bytes()))
+    }
+
+    /// Check the format tag and that `snapshot_hash` matches the content
+    pub fn verify(&self) -> Result<()> {
+        if self.format != SNAPSHOT_FORMAT {
+            return Err(PolicyError::InvalidSnapshot(format!(
+                "format {:?}, expected {:?}",
+                self.format, SNAPSHOT_FORMAT
+            )));
+        }
+        let computed = self.compute_hash()?;
+        if computed != self.snapshot_hash {
+            return Err(PolicyError::InvalidSnapshot(format!(
+                "hash mismatch: declared {}, computed {}",
+                self.snapshot_hash, computed
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl PolicyVM {
+    /// The whole policy set as a canonical, hash-addressed snapshot
+    pub fn export_snapshot(&self) -> Result<PolicySnapshot> {
+        let ids: std::collections::BTreeSet<&String> = self.policies.keys().collect();
+        let mut policies = Vec::new();
+        for id in ids {
+            for policy in &self.policies[id] {
+                let mut bundle = PolicyBundle::new(policy.clone());
+                let key = (policy.policy_id.clone(), policy.version.clone());
+                if let Some(signatures) = self.signatures.get(&key) {
+                    bundle.signatures = signatures.clone();
+                    bundle
+                        .signatures
+                        .sort_by(|a, b| (&a.pubkey, &a.signature).cmp(&(&b.pubkey, &b.signature)));
+                }
+                policies.push(bundle);
+            }
+        }
+        let mut snapshot = PolicySnapshot {
+            format: SNAPSHOT_FORMAT.to_string(),
+            snapshot_hash: String::new(),
+            policies,
+            attachments: self
+                .attachments
+                .iter()
+                .map(|(c, set)| (c.clone(), set.iter().cloned().collect()))
+                .collect(),
+            namespaces: self
+                .namespaces
+                .iter()
+                .map(|(ns, set)| (ns.clone(), set.iter().cloned().collect()))
+                .collect(),
+            composition: self
+                .composition
+                .iter()
+                .filter(|(_, mode)| **mode != CompositionMode::default())
+                .map(|(c, mode)| (c.clone(), *mode))
+                .collect(),
+            rollouts: self.rollouts().into_iter().cloned().collect(),
+        };
+        snapshot.snapshot_hash = snapshot.compute_hash()?;
+        Ok(snapshot)
+    }
+
+    /// Replace the policy set with `snapshot`'s, returning its hash
+    ///
+    /// Governance keys, the observer and the decision cache setting stay;
+    /// the cache is cleared.
+    pub fn import_snapshot(&mut self, snapshot: &PolicySnapshot) -> Result<String> {
+        snapshot.verify()?;
+        let mut staged = PolicyVM::new();
+        staged.governance = self.governance.clone();
+        let mut previous: Option<&Policy> = None;
+        for bundle in &snapshot.policies {
+            let p = &bundle.policy;
+            // Canonical order is part of the hashed content; a reordered
+            // snapshot from a non-conforming writer is refused, not fixed up
+            if let Some(prev) = previous {
+                if (&prev.policy_id, prev.active_from) >= (&p.policy_id, p.active_from) {
+                    return Err(PolicyError::InvalidSnapshot(format!(
+                        "{} version {} is out of order",
+                        p.policy_id, p.version
+                    )));
+                }
+            }
+            previous = Some(p);
+            staged.register_bundle(bundle)?;
+        }
+        for (container_id, ids) in &snapshot.attachments {
+            for id in ids {
+                staged.attach(container_id, id);
+            }
+        }
+        for (namespace, ids) in &snapshot.namespaces {
+            for id in ids {
+                staged.bind_namespace(namespace, id);
+            }
+        }
+        for (container_id, mode) in &snapshot.composition {
+            staged.set_composition(container_id, *mode);
+        }
+        for rollout in &snapshot.rollouts {
+            staged.set_rollout(rollout.clone())?;
+        }
+
+        self.invalidate_cache();
+        self.policies = staged.policies;
+        self.programs = staged.programs;
+        self.signatures = staged.signatures;
+        self.attachments = staged.attachments;
+        self.namespaces = staged.namespaces;
+        self.composition = staged.composition;
+        self.rollouts = staged.rollouts;
+        Ok(snapshot.snapshot_hash.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bytecode_hash, GovernanceKeys};
+
+    fn policy(id: &str, version: &str, active_from: i64) -> Policy {
+        let bytecode = format!("tdln:{}:{}", id, version).into_bytes();
+        Policy {
+            policy_id: id.to_string(),
+            version: version.to_string(),
+            bytecode_hash: bytecode_hash(&bytecode),
+            bytecode,
+            description: format!("{} v{}", id, version),
+            active_from,
+        }
+    }
+
+    fn populated(order: &[(&str, &str, i64)]) -> PolicyVM {
+        let mut vm = PolicyVM::new();
+        for (id, version, at) in order {
+            vm.register(policy(id, version, *at)).unwrap();
+        }
+        vm.attach("C.Bank", "limits");
+        vm.bind_namespace("C.", "baseline");
+        vm.set_composition("C.Bank", CompositionMode::MostRestrictiveWins);
+        vm
+    }
+
+    #[test]
+    fn test_export_is_deterministic() {
+        let a = populated(&[("limits", "1", 0), ("baseline", "1", 0), ("limits", "2", 100)]);
+        let b = populated(&[("limits", "2", 100), ("limits", "1", 0), ("baseline", "1", 0)]);
+        let (sa, sb) = (a.export_snapshot().unwrap(), b.export_snapshot().unwrap());
+        assert_eq!(sa, sb);
+        assert_eq!(serde_json::to_vec(&sa).unwrap(), serde_json::to_vec(&sb).unwrap());
+        let order: Vec<_> = sa.policies.iter().map(|b| (b.policy.policy_id.as_str(), b.policy.active_from)).collect();
+        assert_eq!(order, [("baseline", 0), ("limits", 0), ("limits", 100)]);
+    }
+
+    #[test]
+    fn test_import_round_trip() {
+        let source = populated(&[("limits", "1", 0), ("baseline", "1", 0), ("limits", "2", 100)]);
+        let snapshot = source.export_snapshot().unwrap();
+        let json = serde_json::to_string(&snapshot).unwrap();
+
+        let mut node = PolicyVM::new();
+        node.register(policy("stale", "1", 0)).unwrap();
+        let hash = node.import_snapshot(&serde_json::from_str(&json).unwrap()).unwrap();
+        assert_eq!(hash, snapshot.snapshot_hash);
+        assert!(node.get("stale").is_none());
+        assert_eq!(node.versions("limits").len(), 2);
+        assert_eq!(node.attached("C.Bank"), ["limits"]);
+        assert_eq!(node.composition("C.Bank"), CompositionMode::MostRestrictiveWins);
+        assert_eq!(node.export_snapshot().unwrap(), snapshot);
+    }
+
+    #[test]
+    fn test_import_rejects_tampering() {
+        let source = populated(&[("limits", "1", 0), ("baseline", "1", 0)]);
+        let mut node = PolicyVM::new();
+        node.register(policy("kept", "1", 0)).unwrap();
+
+        let mut tampered = source.export_snapshot().unwrap();
+        tampered.attachments.insert("C.Other".to_string(), vec!["limits".to_string()]);
+        assert!(matches!(node.import_snapshot(&tampered), Err(PolicyError::InvalidSnapshot(_))));
+
+        // Re-hashed, but the bytecode no longer matches its declared hash
+        let mut swapped = source.export_snapshot().unwrap();
+        swapped.policies[0].bytecode_hex = hex::encode(b"evil");
+        swapped.snapshot_hash = swapped.compute_hash().unwrap();
+        assert!(matches!(node.import_snapshot(&swapped), Err(PolicyError::BytecodeHashMismatch { .. })));
+
+        let mut reordered = source.export_snapshot().unwrap();
+        reordered.policies.reverse();
+        reordered.snapshot_hash = reordered.compute_hash().unwrap();
+        assert!(matches!(node.import_snapshot(&reordered), Err(PolicyError::InvalidSnapshot(_))));
+
+        assert!(node.get("kept").is_some());
+    }
+
+    #[test]
+    fn test_governed_import_needs_signatures() {
+        let (gov, key) = ubl_kernel::generate_keypair();
+        let mut source = PolicyVM::new();
+        source.set_governance_keys(Some(GovernanceKeys::new([&gov], 1)));
+        let mut bundle = PolicyBundle::new(policy("limits", "1", 0));
+        bundle.sign(&key);
+        source.register_bundle(&bundle).unwrap();
+        let snapshot = source.export_snapshot().unwrap();
+        assert_eq!(snapshot.policies[0].signatures.len(), 1);
+
+        let mut node = PolicyVM::new();
+        node.set_governance_keys(Some(GovernanceKeys::new([&gov], 1)));
+        node.import_snapshot(&snapshot).unwrap();
+
+        let mut unsigned = snapshot.clone();
+        unsigned.policies[0].signatures.clear();
+        unsigned.snapshot_hash = unsigned.compute_hash().unwrap();
+        let mut other = PolicyVM::new();
+        other.set_governance_keys(Some(GovernanceKeys::new([&gov], 1)));
+        assert!(matches!(
+            other.import_snapshot(&unsigned),
+            Err(PolicyError::InsufficientGovernanceSignatures { .. })
+        ));
+    }
+}
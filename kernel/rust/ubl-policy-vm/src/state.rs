@@ -0,0 +1,149 @@
+//! Container state read on demand
+//!
+//! [`EvaluationContext::state`] carries state as one JSON blob the caller
+//! builds before evaluating, which for a large container means reading far
+//! more than any rule looks at. A [`StateReader`] set on the VM
+//! ([`PolicyVM::set_state_reader`]) is asked instead, only for what a rule
+//! actually reads. CEL policies reach it through two functions:
+//!
+//! - `balance(container_id)`: the container's balance (the sum of its
+//!   `physics_delta`s), `null` for a container with no entries;
+//! - `field(container_id, path)`: the value at a dot-separated `path` of
+//!   the container's state, `null` when absent.
+//!
+//! A reader error fails the evaluation. Decisions of a policy that calls
+//! either function depend on more than the context, so they are never
+//! answered from or stored in the decision cache. The `state` variable keeps
+//! working for callers that already hold the blob.
+//!
+//! [`EvaluationContext::state`]: crate::EvaluationContext::state
+//! [`PolicyVM::set_state_reader`]: crate::PolicyVM::set_state_reader
+
+use crate::Result;
+
+/// Source of container state for policies, called during evaluation
+///
+/// Evaluation is synchronous: an implementation backed by async I/O blocks
+/// on it. Answers should be consistent for the duration of one evaluation.
+pub trait StateReader: Send + Sync {
+    /// Balance of `container_id`; `None` if it has no entries
+    fn get_balance(&self, container_id: &str) -> Result<Option<i128>>;
+
+    /// Value at the dot-separated `path` of `container_id`'s state; `None` if absent
+    fn get_field(&self, container_id: &str, path: &str) -> Result<Option<serde_json::Value>>;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::{bytecode_hash, CelPolicy, DenyCode, EvaluationContext, Policy, PolicyError, PolicyVM, TranslationDecision};
+
+    /// In-memory state that counts how often it is asked
+    #[derive(Default)]
+    struct MockState {
+        balances: HashMap<String, i128>,
+        fields: HashMap<(String, String), serde_json::Value>,
+        reads: AtomicUsize,
+    }
+
+    impl StateReader for MockState {
+        fn get_balance(&self, container_id: &str) -> Result<Option<i128>> {
+            self.reads.fetch_add(1, Ordering::Relaxed);
+            Ok(self.balances.get(container_id).copied())
+        }
+
+        fn get_field(&self, container_id: &str, path: &str) -> Result<Option<serde_json::Value>> {
+            self.reads.fetch_add(1, Ordering::Relaxed);
+            if container_id == "C.Broken" {
+                return Err(PolicyError::ExecutionFailed("state store unavailable".to_string()));
+            }
+            Ok(self.fields.get(&(container_id.to_string(), path.to_string())).cloned())
+        }
+    }
+
+    fn vm(state: Arc<MockState>) -> PolicyVM {
+        let source: CelPolicy = serde_json::from_value(json!({"rules": [
+            {"when": "intent.type == 'observe'", "allow": {"intent_class": 0}},
+            {"when": "field(container_id, 'tier') == 'frozen'", "deny": {"code": "actor_blocked"}},
+            {"when": "balance(container_id) - intent.amount >= 0", "allow": {"intent_class": 1}},
+            {"when": "true", "deny": {"code": "amount_exceeded"}}
+        ]}))
+        .unwrap();
+        let bytecode = source.to_bytecode();
+        let mut vm = PolicyVM::new();
+        vm.register(Policy {
+            policy_id: "funds".to_string(),
+            version: "1".to_string(),
+            bytecode_hash: bytecode_hash(&bytecode),
+            bytecode,
+            description: "spend within balance".to_string(),
+            active_from: 0,
+        })
+        .unwrap();
+        vm.set_state_reader(Some(state));
+        vm
+    }
+
+    fn context(container_id: &str, intent: serde_json::Value) -> EvaluationContext {
+        EvaluationContext {
+            container_id: container_id.to_string(),
+            actor: "alice".to_string(),
+            intent,
+            state: None,
+            timestamp: 0,
+            spec: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_reads_only_what_rules_reach() {
+        let mut state = MockState::default();
+        state.balances.insert("C.Alice".to_string(), 100);
+        state.fields.insert(("C.Frozen".to_string(), "tier".to_string()), json!("frozen"));
+        let state = Arc::new(state);
+        let vm = vm(state.clone());
+
+        vm.evaluate("funds", &context("C.Alice", json!({"type": "observe"}))).unwrap();
+        assert_eq!(state.reads.load(Ordering::Relaxed), 0);
+
+        let spend = |c: &str, amount: i64| vm.evaluate("funds", &context(c, json!({"type": "transfer", "amount": amount})));
+        assert!(matches!(spend("C.Alice", 80), Ok(TranslationDecision::Allow { intent_class: 1, .. })));
+        assert_eq!(state.reads.load(Ordering::Relaxed), 2);
+        assert!(matches!(
+            spend("C.Alice", 120),
+            Ok(TranslationDecision::Deny { code: DenyCode::AmountExceeded, .. })
+        ));
+        assert!(matches!(
+            spend("C.Frozen", 1),
+            Ok(TranslationDecision::Deny { code: DenyCode::ActorBlocked, .. })
+        ));
+        // No entries: `balance()` is null, and null arithmetic is an error
+        assert!(matches!(spend("C.Nobody", 1), Err(PolicyError::ExecutionFailed(_))));
+        let err = spend("C.Broken", 1).unwrap_err().to_string();
+        assert!(err.contains("state store unavailable"), "{}", err);
+    }
+
+    #[test]
+    fn test_state_reads_bypass_decision_cache() {
+        let mut state = MockState::default();
+        state.balances.insert("C.Alice".to_string(), 100);
+        let state = Arc::new(state);
+        let mut vm = vm(state.clone());
+        vm.enable_decision_cache(16);
+
+        let ctx = context("C.Alice", json!({"type": "transfer", "amount": 50}));
+        vm.evaluate("funds", &ctx).unwrap();
+        vm.evaluate("funds", &ctx).unwrap();
+        assert_eq!(state.reads.load(Ordering::Relaxed), 4);
+        assert_eq!(vm.cache_stats().unwrap().entries, 0);
+
+        vm.set_state_reader(None);
+        assert!(matches!(vm.evaluate("funds", &ctx), Err(PolicyError::ExecutionFailed(e)) if e.contains("state reader")));
+    }
+}
@@ -0,0 +1,465 @@
+//! Static determinism analysis for WASM policy bytecode
+//!
+//! A policy whose bytecode is a WASM module (it starts with [`MAGIC`]) is
+//! scanned when it is registered. Anything that could let two executors
+//! disagree rejects it with [`PolicyError::NonDeterministic`]:
+//!
+//! - floating point or SIMD: `f32`/`f64`/`v128` in any signature, local or
+//!   global, and every float or vector instruction (NaN payloads and
+//!   relaxed SIMD are platform-dependent);
+//! - imports other than the functions of the host ABI ([`HOST_MODULE`],
+//!   [`HOST_FUNCTIONS`]); imported memories, tables and globals included;
+//! - threads: shared memories and atomic instructions;
+//! - memory that can grow past [`MAX_MEMORY_PAGES`]: every memory must
+//!   declare a maximum within the cap, so `memory.grow` either succeeds or
+//!   fails the same way on every host.
+//!
+//! Opcodes and sections the analyzer does not know are rejected too. A
+//! module it cannot prove deterministic is not registered.
+
+use crate::PolicyError;
+
+/// WASM binary header: `\0asm`, version 1
+pub const MAGIC: &[u8] = b"\0asm\x01\x00\x00\x00";
+
+/// Module the host ABI is imported from
+pub const HOST_MODULE: &str = "ubl";
+
+/// Functions a policy may import from [`HOST_MODULE`]: the length of the
+/// JSON evaluation context, copying it into linear memory, and handing back
+/// the JSON decision
+pub const HOST_FUNCTIONS: &[&str] = &["context_len", "context_read", "decision_write"];
+
+/// Largest memory a policy may declare, in 64 KiB pages (4 MiB)
+pub const MAX_MEMORY_PAGES: u32 = 64;
+
+/// Whether `bytecode` is a WASM module
+pub fn is_wasm(bytecode: &[u8]) -> bool {
+    bytecode.starts_with(&MAGIC[..4])
+}
+
+/// Why a module was refused
+enum Finding {
+    Malformed(String),
+    NonDeterministic(String),
+}
+
+type Scan<T> = std::result::Result<T, Finding>;
+
+fn nondet<T>(reason: impl Into<String>) -> Scan<T> {
+    Err(Finding::NonDeterministic(reason.into()))
+}
+
+/// Check a WASM policy for nondeterminism before it is registered
+pub fn analyze(policy_id: &str, bytecode: &[u8]) -> crate::Result<()> {
+    scan_module(bytecode).map_err(|finding| match finding {
+        Finding::Malformed(reason) => PolicyError::CompileFailed {
+            policy_id: policy_id.to_string(),
+            reason: format!("malformed WASM: {}", reason),
+        },
+        Finding::NonDeterministic(reason) => PolicyError::NonDeterministic {
+            policy_id: policy_id.to_string(),
+            reason,
+        },
+    })
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+    /// Offset of `bytes` within the module, for messages
+    base: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8], base: usize) -> Self {
+        Self { bytes, pos: 0, base }
+    }
+
+    fn offset(&self) -> usize {
+        self.base + self.pos
+    }
+
+    fn done(&self) -> bool {
+        self.pos >= self.bytes.len()
+    }
+
+    fn byte(&mut self) -> Scan<u8> {
+        let b = *self
+            .bytes
+            .get(self.pos)
+            .ok_or_else(|| Finding::Malformed(format!("unexpected end at byte {:#x}", self.offset())))?;
+        self.pos += 1;
+        Ok(b)
+    }
+
+    fn take(&mut self, n: usize) -> Scan<&'a [u8]> {
+        if self.bytes.len() - self.pos < n {
+            return Err(Finding::Malformed(format!("unexpected end at byte {:#x}", self.offset())));
+        }
+        let out = &self.bytes[self.pos..self.pos + n];
+        self.pos += n;
+        Ok(out)
+    }
+
+    fn u32(&mut self) -> Scan<u32> {
+        let mut result = 0u64;
+        for shift in (0..35).step_by(7) {
+            let b = self.byte()?;
+            result |= u64::from(b & 0x7f) << shift;
+            if b & 0x80 == 0 {
+                return u32::try_from(result)
+                    .map_err(|_| Finding::Malformed(format!("integer overflow at byte {:#x}", self.offset())));
+            }
+        }
+        Err(Finding::Malformed(format!("integer too long at byte {:#x}", self.offset())))
+    }
+
+    /// Skip a signed LEB128 of at most `max_bytes`
+    fn skip_signed(&mut self, max_bytes: usize) -> Scan<()> {
+        for _ in 0..max_bytes {
+            if self.byte()? & 0x80 == 0 {
+                return Ok(());
+            }
+        }
+        Err(Finding::Malformed(format!("integer too long at byte {:#x}", self.offset())))
+    }
+
+    fn name(&mut self) -> Scan<&'a str> {
+        let len = self.u32()? as usize;
+        std::str::from_utf8(self.take(len)?).map_err(|_| Finding::Malformed("name is not UTF-8".to_string()))
+    }
+}
+
+fn value_type(r: &mut Reader, what: &str) -> Scan<()> {
+    let at = r.offset();
+    match r.byte()? {
+        0x7f | 0x7e | 0x70 | 0x6f => Ok(()),
+        0x7d => nondet(format!("f32 {} at byte {:#x}", what, at)),
+        0x7c => nondet(format!("f64 {} at byte {:#x}", what, at)),
+        0x7b => nondet(format!("v128 {} at byte {:#x}", what, at)),
+        t => Err(Finding::Malformed(format!("unknown value type {:#x} at byte {:#x}", t, at))),
+    }
+}
+
+fn limits(r: &mut Reader, memory: bool) -> Scan<()> {
+    let at = r.offset();
+    let flags = r.byte()?;
+    if memory && flags & 0x02 != 0 {
+        return nondet(format!("shared memory at byte {:#x} (threads)", at));
+    }
+    if flags & !0x01 != 0 {
+        return Err(Finding::Malformed(format!("unsupported limits {:#x} at byte {:#x}", flags, at)));
+    }
+    r.u32()?;
+    let max = if flags & 0x01 != 0 { Some(r.u32()?) } else { None };
+    if memory {
+        match max {
+            None => return nondet(format!("memory at byte {:#x} declares no maximum", at)),
+            Some(max) if max > MAX_MEMORY_PAGES => {
+                return nondet(format!(
+                    "memory at byte {:#x} may grow to {} pages (cap {})",
+                    at, max, MAX_MEMORY_PAGES
+                ))
+            }
+            Some(_) => {}
+        }
+    }
+    Ok(())
+}
+
+fn scan_module(bytecode: &[u8]) -> Scan<()> {
+    if !bytecode.starts_with(MAGIC) {
+        return Err(Finding::Malformed("not a version 1 WASM module".to_string()));
+    }
+    let mut r = Reader::new(bytecode, 0);
+    r.take(MAGIC.len())?;
+    while !r.done() {
+        let id = r.byte()?;
+        let len = r.u32()? as usize;
+        let base = r.offset();
+        let mut s = Reader::new(r.take(len)?, base);
+        match id {
+            0 => {} // custom: names, producers; never executed
+            1 => section(&mut s, |s| {
+                if s.byte()? != 0x60 {
+                    return Err(Finding::Malformed("expected a function type".to_string()));
+                }
+                for _ in 0..2 {
+                    for _ in 0..s.u32()? {
+                        value_type(s, "in a function signature")?;
+                    }
+                }
+                Ok(())
+            })?,
+            2 => section(&mut s, |s| {
+                let module = s.name()?;
+                let field = s.name()?;
+                let kind = s.byte()?;
+                if module != HOST_MODULE || kind != 0x00 || !HOST_FUNCTIONS.contains(&field) {
+                    return nondet(format!("import {}.{} is outside the host ABI", module, field));
+                }
+                s.u32()?;
+                Ok(())
+            })?,
+            3 => section(&mut s, |s| s.u32().map(drop))?,
+            4 => section(&mut s, |s| {
+                value_type(s, "table element")?;
+                limits(s, false)
+            })?,
+            5 => section(&mut s, |s| limits(s, true))?,
+            6 => section(&mut s, |s| {
+                value_type(s, "global")?;
+                s.byte()?;
+                expr(s)
+            })?,
+            7 => section(&mut s, |s| {
+                s.name()?;
+                s.byte()?;
+                s.u32().map(drop)
+            })?,
+            8 => {
+                s.u32()?;
+            }
+            9 => section(&mut s, element)?,
+            10 => {
+                let count = s.u32()?;
+                for func in 0..count {
+                    let size = s.u32()? as usize;
+                    let base = s.offset();
+                    let mut body = Reader::new(s.take(size)?, base);
+                    for _ in 0..body.u32()? {
+                        body.u32()?;
+                        value_type(&mut body, &format!("local in function {}", func))?;
+                    }
+                    code(&mut body).map_err(|f| match f {
+                        Finding::NonDeterministic(reason) => {
+                            Finding::NonDeterministic(format!("{} in function {}", reason, func))
+                        }
+                        other => other,
+                    })?;
+                }
+            }
+            11 => section(&mut s, |s| {
+                match s.u32()? {
+                    0 => expr(s)?,
+                    1 => {}
+                    2 => {
+                        s.u32()?;
+                        expr(s)?;
+                    }
+                    k => return Err(Finding::Malformed(format!("unknown data segment kind {}", k))),
+                }
+                let len = s.u32()? as usize;
+                s.take(len).map(drop)
+            })?,
+            12 => {
+                s.u32()?;
+            }
+            _ => return Err(Finding::Malformed(format!("unsupported section {} at byte {:#x}", id, base))),
+        }
+        if !s.done() && id != 0 {
+            return Err(Finding::Malformed(format!("section {} has trailing bytes", id)));
+        }
+    }
+    Ok(())
+}
+
+/// A vector section: count, then `item` that many times
+fn section(s: &mut Reader, mut item: impl FnMut(&mut Reader) -> Scan<()>) -> Scan<()> {
+    for _ in 0..s.u32()? {
+        item(s)?;
+    }
+    Ok(())
+}
+
+fn element(s: &mut Reader) -> Scan<()> {
+    let kind = s.u32()?;
+    if kind > 7 {
+        return Err(Finding::Malformed(format!("unknown element segment kind {}", kind)));
+    }
+    if kind & 0x01 == 0 {
+        if kind & 0x02 != 0 {
+            s.u32()?;
+        }
+        expr(s)?;
+    }
+    if kind & 0x03 != 0 {
+        s.byte()?; // elemkind or reftype
+    }
+    for _ in 0..s.u32()? {
+        if kind & 0x04 != 0 {
+            expr(s)?;
+        } else {
+            s.u32()?;
+        }
+    }
+    Ok(())
+}
+
+/// Constant expression (initializers): instructions up to the final `end`
+fn expr(s: &mut Reader) -> Scan<()> {
+    code(s)
+}
+
+/// Instructions up to the `end` closing the outermost block
+fn code(r: &mut Reader) -> Scan<()> {
+    let mut depth = 1usize;
+    loop {
+        let at = r.offset();
+        let op = r.byte()?;
+        let float = |name: &str| nondet(format!("{} at byte {:#x}", name, at));
+        match op {
+            0x00 | 0x01 | 0x0f | 0x1a | 0x1b | 0xd1 => {}
+            0x45..=0x5a | 0x67..=0x8a | 0xa7 | 0xac | 0xad | 0xc0..=0xc4 => {}
+            0x02..=0x04 => {
+                depth += 1;
+                let bt = r.bytes.get(r.pos).copied();
+                match bt {
+                    Some(0x40) => r.pos += 1,
+                    Some(0x7f | 0x7e | 0x7d | 0x7c | 0x7b | 0x70 | 0x6f) => value_type(r, "block result")?,
+                    _ => r.skip_signed(5)?,
+                }
+            }
+            0x05 => {}
+            0x0b => {
+                depth -= 1;
+                if depth == 0 {
+                    return Ok(());
+                }
+            }
+            0x0c | 0x0d | 0x10 | 0x20..=0x26 | 0xd2 => {
+                r.u32()?;
+            }
+            0x0e => {
+                for _ in 0..r.u32()? {
+                    r.u32()?;
+                }
+                r.u32()?;
+            }
+            0x11 => {
+                r.u32()?;
+                r.u32()?;
+            }
+            0x1c => {
+                for _ in 0..r.u32()? {
+                    value_type(r, "select type")?;
+                }
+            }
+            0x2a | 0x2b | 0x38 | 0x39 => return float("float load/store"),
+            0x28..=0x3e => {
+                r.u32()?;
+                r.u32()?;
+            }
+            0x3f | 0x40 => {
+                r.byte()?;
+            }
+            0x41 => r.skip_signed(5)?,
+            0x42 => r.skip_signed(10)?,
+            0x43 | 0x44 => return float("float constant"),
+            0x5b..=0x66 => return float("float comparison"),
+            0x8b..=0xa6 => return float("float arithmetic"),
+            0xa8..=0xab | 0xae..=0xbf => return float("float conversion"),
+            0xd0 => {
+                r.byte()?;
+            }
+            0xfc => match r.u32()? {
+                0..=7 => return float("saturating float truncation"),
+                8 => {
+                    r.u32()?;
+                    r.byte()?;
+                }
+                9 | 13 | 15..=17 => {
+                    r.u32()?;
+                }
+                10 => {
+                    r.byte()?;
+                    r.byte()?;
+                }
+                11 => {
+                    r.byte()?;
+                }
+                12 | 14 => {
+                    r.u32()?;
+                    r.u32()?;
+                }
+                sub => return Err(Finding::Malformed(format!("unknown opcode 0xfc {} at byte {:#x}", sub, at))),
+            },
+            0xfd => return nondet(format!("SIMD instruction at byte {:#x}", at)),
+            0xfe => return nondet(format!("atomic instruction at byte {:#x} (threads)", at)),
+            _ => return Err(Finding::Malformed(format!("unsupported opcode {:#x} at byte {:#x}", op, at))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn section(id: u8, body: &[u8]) -> Vec<u8> {
+        let mut out = vec![id, body.len() as u8];
+        out.extend_from_slice(body);
+        out
+    }
+
+    /// One `() -> i32` function running `body`, with optional memory limits and imports
+    fn module(body: &[u8], memory: Option<&[u8]>, imports: Option<&[u8]>) -> Vec<u8> {
+        let mut m = MAGIC.to_vec();
+        m.extend(section(1, &[1, 0x60, 0, 1, 0x7f]));
+        if let Some(i) = imports {
+            m.extend(section(2, i));
+        }
+        m.extend(section(3, &[1, 0]));
+        if let Some(limits) = memory {
+            let mut mem = vec![1];
+            mem.extend_from_slice(limits);
+            m.extend(section(5, &mem));
+        }
+        let mut func = vec![0];
+        func.extend_from_slice(body);
+        func.push(0x0b);
+        let mut code = vec![1, func.len() as u8];
+        code.extend(func);
+        m.extend(section(10, &code));
+        m
+    }
+
+    fn reason(bytecode: &[u8]) -> String {
+        match analyze("p", bytecode) {
+            Err(PolicyError::NonDeterministic { reason, .. }) => reason,
+            other => panic!("expected NonDeterministic, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_integer_module_with_capped_memory_passes() {
+        // i32.const 1, block (i32.const 2, drop), memory.size, i32.add
+        let body = [0x41, 1, 0x02, 0x40, 0x41, 2, 0x1a, 0x0b, 0x3f, 0, 0x6a];
+        let import = [1, 3, b'u', b'b', b'l', 11, b'c', b'o', b'n', b't', b'e', b'x', b't', b'_', b'l', b'e', b'n', 0, 0];
+        analyze("p", &module(&body, Some(&[0x01, 1, 16]), Some(&import))).unwrap();
+    }
+
+    #[test]
+    fn test_nondeterminism_is_rejected_with_reason() {
+        // f64.const 1.0, i64.trunc_f64_s, i32.wrap_i64
+        let mut float = vec![0x44];
+        float.extend(1.0f64.to_le_bytes());
+        float.extend([0xb0, 0xa7]);
+        assert!(reason(&module(&float, None, None)).starts_with("float constant at byte"));
+
+        let wasi = [1, 4, b'w', b'a', b's', b'i', 4, b'r', b'a', b'n', b'd', 0, 0];
+        assert_eq!(reason(&module(&[0x41, 0], None, Some(&wasi))), "import wasi.rand is outside the host ABI");
+
+        assert!(reason(&module(&[0x41, 0], Some(&[0x00, 1]), None)).contains("declares no maximum"));
+        assert!(reason(&module(&[0x41, 0], Some(&[0x01, 1, 0x7f]), None)).contains("127 pages"));
+        assert!(reason(&module(&[0x41, 0], Some(&[0x03, 1, 2]), None)).starts_with("shared memory"));
+        assert!(reason(&module(&[0xfe, 0x03, 0x00, 0x41, 0], None, None)).starts_with("atomic instruction"));
+    }
+
+    #[test]
+    fn test_malformed_module_fails_to_compile() {
+        let mut truncated = module(&[0x41, 0], None, None);
+        truncated.truncate(truncated.len() - 2);
+        assert!(matches!(analyze("p", &truncated), Err(PolicyError::CompileFailed { .. })));
+    }
+}
@@ -0,0 +1,356 @@
+//! # Commit admission control
+//!
+//! Under overload `/link/commit` sheds work by priority before it touches
+//! the database, instead of letting every commit queue on the pool:
+//!
+//! - `reserved`: Observation and Evolution drafts, and every draft for a
+//!   governance container. Always admitted, so reads recorded as
+//!   Observations and governance operations keep working while the rest is
+//!   shed.
+//! - `normal`: other drafts. Shed once the load reaches 1.0.
+//! - `low`: drafts sent with `X-UBL-Priority: low` (backfills, batch
+//!   imports). Shed from [`LOW_SHED_AT`]. The header can only lower a
+//!   draft's priority.
+//!
+//! The load is the higher of two ratios: commits in flight on this instance
+//! over `UBL_ADMISSION_MAX_INFLIGHT` (default 256), and the database latency
+//! over `UBL_ADMISSION_DB_LATENCY_MS` (default 250). The latency is a moving
+//! average of a `SELECT 1` on the primary pool every [`PROBE_MS`]; waiting
+//! for a connection counts, so an exhausted pool shows up here.
+//!
+//! A shed commit answers 429 with `Retry-After` (seconds) and a JSON body:
+//! `{ok: false, error, admission: {priority, reason, load, retry_after_ms,
+//! jitter_ms}}`. `reason` is the signal over its limit (`queue_depth` or
+//! `db_latency`). The hint grows with the square of the load, from
+//! [`MIN_RETRY_MS`] up to [`MAX_RETRY_MS`]; clients should wait
+//! `retry_after_ms` plus a random share of `jitter_ms` so retries do not
+//! arrive together. Rejections count in `ubl_admission_rejections_total`;
+//! the signals are exported as `ubl_admission_*` and in `/autoscale/signals`.
+
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use axum::{
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+use tracing::warn;
+use ubl_link::IntentClass;
+use ubl_membrane::ContainerProfile;
+
+use crate::db::LinkDraft;
+use crate::{metrics, AppState};
+
+/// Header a client lowers a draft's priority with
+pub const PRIORITY_HEADER: &str = "x-ubl-priority";
+
+/// Load from which `low` drafts are shed
+pub const LOW_SHED_AT: f64 = 0.8;
+
+/// How often the database latency is probed
+pub const PROBE_MS: u64 = 500;
+
+/// Shortest backoff hint
+pub const MIN_RETRY_MS: u64 = 500;
+
+/// Longest backoff hint
+pub const MAX_RETRY_MS: u64 = 30_000;
+
+/// Weight of the newest probe in the latency average, out of 8
+const PROBE_WEIGHT: u64 = 2;
+
+/// How a draft ranks when commits are shed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Priority {
+    Low,
+    Normal,
+    Reserved,
+}
+
+impl Priority {
+    /// Priority of `link`, lowered to `low` by [`PRIORITY_HEADER`]
+    pub fn of(link: &LinkDraft, headers: &HeaderMap) -> Self {
+        let class = link.intent_class.parse::<IntentClass>().ok();
+        if matches!(class, Some(IntentClass::Observation | IntentClass::Evolution))
+            || ContainerProfile::for_container(&link.container_id) == ContainerProfile::Governance
+        {
+            return Priority::Reserved;
+        }
+        match headers.get(PRIORITY_HEADER).and_then(|v| v.to_str().ok()) {
+            Some(p) if p.eq_ignore_ascii_case("low") => Priority::Low,
+            _ => Priority::Normal,
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Priority::Low => "low",
+            Priority::Normal => "normal",
+            Priority::Reserved => "reserved",
+        }
+    }
+
+    /// Load from which drafts of this priority are shed
+    fn shed_at(self) -> f64 {
+        match self {
+            Priority::Low => LOW_SHED_AT,
+            Priority::Normal => 1.0,
+            Priority::Reserved => f64::INFINITY,
+        }
+    }
+}
+
+/// Limits the load is measured against
+#[derive(Debug, Clone, Copy)]
+pub struct Limits {
+    pub max_inflight: usize,
+    pub db_latency: Duration,
+}
+
+impl Limits {
+    /// `UBL_ADMISSION_MAX_INFLIGHT` and `UBL_ADMISSION_DB_LATENCY_MS`
+    pub fn from_env() -> Self {
+        let env = |name: &str, default: u64| {
+            std::env::var(name)
+                .ok()
+                .and_then(|n| n.parse().ok())
+                .filter(|&n| n > 0)
+                .unwrap_or(default)
+        };
+        Self {
+            max_inflight: env("UBL_ADMISSION_MAX_INFLIGHT", 256) as usize,
+            db_latency: Duration::from_millis(env("UBL_ADMISSION_DB_LATENCY_MS", 250)),
+        }
+    }
+}
+
+/// Load at one instant: each signal over its limit
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct Load {
+    pub inflight: usize,
+    pub db_latency_ms: f64,
+    pub queue_depth: f64,
+    pub db: f64,
+}
+
+impl Load {
+    pub fn new(limits: &Limits, inflight: usize, db_latency: Duration) -> Self {
+        Self {
+            inflight,
+            db_latency_ms: db_latency.as_secs_f64() * 1000.0,
+            queue_depth: inflight as f64 / limits.max_inflight as f64,
+            db: db_latency.as_secs_f64() / limits.db_latency.as_secs_f64(),
+        }
+    }
+
+    /// The higher of the two ratios
+    pub fn value(&self) -> f64 {
+        self.queue_depth.max(self.db)
+    }
+
+    fn reason(&self) -> &'static str {
+        if self.queue_depth >= self.db {
+            "queue_depth"
+        } else {
+            "db_latency"
+        }
+    }
+}
+
+/// Why a commit was shed, and when to come back
+#[derive(Debug, Clone, Serialize)]
+pub struct Shed {
+    pub priority: Priority,
+    pub reason: &'static str,
+    pub load: f64,
+    pub retry_after_ms: u64,
+    pub jitter_ms: u64,
+}
+
+#[derive(Serialize)]
+struct ShedBody {
+    ok: bool,
+    error: String,
+    admission: Shed,
+}
+
+impl IntoResponse for Shed {
+    fn into_response(self) -> Response {
+        let retry_after = self.retry_after_ms.div_ceil(1000);
+        let mut response = (
+            StatusCode::TOO_MANY_REQUESTS,
+            Json(ShedBody {
+                ok: false,
+                error: format!("server overloaded ({}); retry later", self.reason),
+                admission: self,
+            }),
+        )
+            .into_response();
+        response
+            .headers_mut()
+            .insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+        response
+    }
+}
+
+/// Whether a draft of `priority` is admitted under `load`
+pub fn decide(priority: Priority, load: &Load) -> Result<(), Shed> {
+    let value = load.value();
+    if value < priority.shed_at() {
+        return Ok(());
+    }
+    let retry_after_ms = ((MIN_RETRY_MS as f64 * value * value) as u64).clamp(MIN_RETRY_MS, MAX_RETRY_MS);
+    Err(Shed {
+        priority,
+        reason: load.reason(),
+        load: value,
+        retry_after_ms,
+        jitter_ms: retry_after_ms / 2,
+    })
+}
+
+/// Admission state of this instance
+#[derive(Debug)]
+pub struct Admission {
+    limits: Limits,
+    inflight: AtomicUsize,
+    /// Moving average of the probed latency, in microseconds
+    db_latency_us: AtomicU64,
+}
+
+/// Counts a commit in flight until dropped
+pub struct InFlight(Arc<Admission>);
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        self.0.inflight.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+impl Admission {
+    pub fn new(limits: Limits) -> Self {
+        Self {
+            limits,
+            inflight: AtomicUsize::new(0),
+            db_latency_us: AtomicU64::new(0),
+        }
+    }
+
+    pub fn load(&self) -> Load {
+        let latency = Duration::from_micros(self.db_latency_us.load(Ordering::Relaxed));
+        Load::new(&self.limits, self.inflight.load(Ordering::Relaxed), latency)
+    }
+
+    /// Admit a draft of `priority`, counting it in flight while the guard lives
+    pub fn admit(self: &Arc<Self>, priority: Priority) -> Result<InFlight, Shed> {
+        if let Err(shed) = decide(priority, &self.load()) {
+            metrics::ADMISSION_REJECTIONS
+                .with_label_values(&[priority.as_str(), shed.reason])
+                .inc();
+            warn!(
+                priority = priority.as_str(),
+                reason = shed.reason,
+                load = shed.load,
+                decision = "shed",
+                "🚦 commit shed"
+            );
+            return Err(shed);
+        }
+        let inflight = self.inflight.fetch_add(1, Ordering::Relaxed) + 1;
+        metrics::ADMISSION_INFLIGHT.set(inflight as i64);
+        Ok(InFlight(self.clone()))
+    }
+
+    fn record_probe(&self, latency: Duration) {
+        let sample = latency.as_micros().min(u64::MAX as u128) as u64;
+        let old = self.db_latency_us.load(Ordering::Relaxed);
+        let avg = if old == 0 {
+            sample
+        } else {
+            (old * (8 - PROBE_WEIGHT) + sample * PROBE_WEIGHT) / 8
+        };
+        self.db_latency_us.store(avg, Ordering::Relaxed);
+        metrics::ADMISSION_DB_LATENCY_US.set(avg as i64);
+        metrics::ADMISSION_INFLIGHT.set(self.inflight.load(Ordering::Relaxed) as i64);
+    }
+}
+
+/// Probe the primary's latency every [`PROBE_MS`]
+pub fn spawn_probe(state: AppState) {
+    tokio::spawn(async move {
+        let mut tick = tokio::time::interval(Duration::from_millis(PROBE_MS));
+        tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            tick.tick().await;
+            let t = Instant::now();
+            // A probe that cannot finish within the limit's 8x reads as that
+            let limit = state.admission.limits.db_latency * 8;
+            let latency = match tokio::time::timeout(limit, sqlx::query("SELECT 1").execute(&state.pool)).await {
+                Ok(Ok(_)) => t.elapsed(),
+                Ok(Err(e)) => {
+                    warn!("admission probe failed: {}", e);
+                    limit
+                }
+                Err(_) => limit,
+            };
+            state.admission.record_probe(latency);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limits() -> Limits {
+        Limits {
+            max_inflight: 100,
+            db_latency: Duration::from_millis(200),
+        }
+    }
+
+    #[test]
+    fn test_shed_by_priority() {
+        let calm = Load::new(&limits(), 10, Duration::from_millis(20));
+        let busy = Load::new(&limits(), 85, Duration::from_millis(20));
+        let overloaded = Load::new(&limits(), 10, Duration::from_millis(300));
+        for p in [Priority::Low, Priority::Normal, Priority::Reserved] {
+            assert!(decide(p, &calm).is_ok());
+        }
+        assert!(decide(Priority::Reserved, &overloaded).is_ok());
+        assert_eq!(decide(Priority::Low, &busy).unwrap_err().reason, "queue_depth");
+        assert!(decide(Priority::Normal, &busy).is_ok());
+        assert_eq!(decide(Priority::Normal, &overloaded).unwrap_err().reason, "db_latency");
+    }
+
+    #[test]
+    fn test_backoff_hint_grows_with_load() {
+        let hint = |inflight| {
+            decide(Priority::Normal, &Load::new(&limits(), inflight, Duration::ZERO))
+                .unwrap_err()
+                .retry_after_ms
+        };
+        assert_eq!(hint(100), MIN_RETRY_MS);
+        assert_eq!(hint(200), 4 * MIN_RETRY_MS);
+        assert_eq!(hint(10_000), MAX_RETRY_MS);
+    }
+
+    #[test]
+    fn test_inflight_guard() {
+        let admission = Arc::new(Admission::new(Limits {
+            max_inflight: 2,
+            db_latency: Duration::from_millis(200),
+        }));
+        let a = admission.admit(Priority::Normal).unwrap();
+        let _b = admission.admit(Priority::Normal).unwrap();
+        assert!(admission.admit(Priority::Normal).is_err());
+        let _c = admission.admit(Priority::Reserved).unwrap();
+        drop(a);
+        assert_eq!(admission.load().inflight, 2);
+        assert!(admission.admit(Priority::Normal).is_err());
+    }
+}
@@ -0,0 +1,260 @@
+//! Alert rules and notifications (tables `alert_rule`, `alert_notification`,
+//! sql/030_alerts.sql) and the ledger projections they are evaluated against
+
+use serde::Serialize;
+use sqlx::PgPool;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AlertRule {
+    pub alert_id: Uuid,
+    pub owner_sid: String,
+    pub container_id: String,
+    pub kind: String,
+    pub threshold: String,
+    pub enabled: bool,
+    pub firing: bool,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub last_fired_at: Option<OffsetDateTime>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AlertNotification {
+    pub id: i64,
+    pub alert_id: Uuid,
+    pub container_id: String,
+    pub kind: String,
+    pub message: String,
+    pub detail: serde_json::Value,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub read_at: Option<OffsetDateTime>,
+}
+
+/// Entry the alert engine evaluates (`physics_delta` as stored, if any)
+#[derive(Debug, Clone)]
+pub struct WatchedEntry {
+    pub container_id: String,
+    pub sequence: i64,
+    pub physics_delta: Option<String>,
+}
+
+pub async fn insert(
+    pool: &PgPool,
+    owner_sid: &str,
+    container_id: &str,
+    kind: &str,
+    threshold: &str,
+) -> sqlx::Result<AlertRule> {
+    sqlx::query_as!(
+        AlertRule,
+        r#"INSERT INTO alert_rule (owner_sid, container_id, kind, threshold)
+           VALUES ($1, $2, $3, $4)
+           RETURNING alert_id, owner_sid, container_id, kind, threshold, enabled, firing,
+                     created_at, updated_at, last_fired_at"#,
+        owner_sid,
+        container_id,
+        kind,
+        threshold
+    )
+    .fetch_one(pool)
+    .await
+}
+
+pub async fn list_for_owner(pool: &PgPool, owner_sid: &str) -> sqlx::Result<Vec<AlertRule>> {
+    sqlx::query_as!(
+        AlertRule,
+        r#"SELECT alert_id, owner_sid, container_id, kind, threshold, enabled, firing,
+                  created_at, updated_at, last_fired_at
+           FROM alert_rule WHERE owner_sid = $1 ORDER BY created_at"#,
+        owner_sid
+    )
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn get(pool: &PgPool, owner_sid: &str, alert_id: Uuid) -> sqlx::Result<Option<AlertRule>> {
+    sqlx::query_as!(
+        AlertRule,
+        r#"SELECT alert_id, owner_sid, container_id, kind, threshold, enabled, firing,
+                  created_at, updated_at, last_fired_at
+           FROM alert_rule WHERE owner_sid = $1 AND alert_id = $2"#,
+        owner_sid,
+        alert_id
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+/// Change threshold and/or enabled; a changed rule starts out not firing
+pub async fn update(
+    pool: &PgPool,
+    owner_sid: &str,
+    alert_id: Uuid,
+    threshold: Option<&str>,
+    enabled: Option<bool>,
+) -> sqlx::Result<Option<AlertRule>> {
+    sqlx::query_as!(
+        AlertRule,
+        r#"UPDATE alert_rule
+           SET threshold = COALESCE($3, threshold), enabled = COALESCE($4, enabled),
+               firing = false, updated_at = now()
+           WHERE owner_sid = $1 AND alert_id = $2
+           RETURNING alert_id, owner_sid, container_id, kind, threshold, enabled, firing,
+                     created_at, updated_at, last_fired_at"#,
+        owner_sid,
+        alert_id,
+        threshold,
+        enabled
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn delete(pool: &PgPool, owner_sid: &str, alert_id: Uuid) -> sqlx::Result<bool> {
+    let r = sqlx::query!(
+        "DELETE FROM alert_rule WHERE owner_sid = $1 AND alert_id = $2",
+        owner_sid,
+        alert_id
+    )
+    .execute(pool)
+    .await?;
+    Ok(r.rows_affected() > 0)
+}
+
+/// Enabled rules watching a container
+pub async fn enabled_for_container(pool: &PgPool, container_id: &str) -> sqlx::Result<Vec<AlertRule>> {
+    sqlx::query_as!(
+        AlertRule,
+        r#"SELECT alert_id, owner_sid, container_id, kind, threshold, enabled, firing,
+                  created_at, updated_at, last_fired_at
+           FROM alert_rule WHERE container_id = $1 AND enabled"#,
+        container_id
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Enabled rules of one kind, across containers
+pub async fn enabled_of_kind(pool: &PgPool, kind: &str) -> sqlx::Result<Vec<AlertRule>> {
+    sqlx::query_as!(
+        AlertRule,
+        r#"SELECT alert_id, owner_sid, container_id, kind, threshold, enabled, firing,
+                  created_at, updated_at, last_fired_at
+           FROM alert_rule WHERE kind = $1 AND enabled"#,
+        kind
+    )
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn set_firing(pool: &PgPool, alert_id: Uuid, firing: bool) -> sqlx::Result<()> {
+    sqlx::query!("UPDATE alert_rule SET firing = $2 WHERE alert_id = $1", alert_id, firing)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Store a notification (delivered by the `alert_notifications` NOTIFY) and mark the rule fired
+pub async fn notify(
+    pool: &PgPool,
+    rule: &AlertRule,
+    message: &str,
+    detail: serde_json::Value,
+) -> sqlx::Result<()> {
+    let mut tx = pool.begin().await?;
+    sqlx::query!(
+        r#"INSERT INTO alert_notification (alert_id, owner_sid, container_id, kind, message, detail)
+           VALUES ($1, $2, $3, $4, $5, $6)"#,
+        rule.alert_id,
+        rule.owner_sid,
+        rule.container_id,
+        rule.kind,
+        message,
+        detail
+    )
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query!(
+        "UPDATE alert_rule SET firing = true, last_fired_at = now() WHERE alert_id = $1",
+        rule.alert_id
+    )
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await
+}
+
+/// Notifications of an owner, newest first
+pub async fn notifications(
+    pool: &PgPool,
+    owner_sid: &str,
+    unread_only: bool,
+    limit: i64,
+) -> sqlx::Result<Vec<AlertNotification>> {
+    sqlx::query_as!(
+        AlertNotification,
+        r#"SELECT id, alert_id, container_id, kind, message, detail, created_at, read_at
+           FROM alert_notification
+           WHERE owner_sid = $1 AND (NOT $2 OR read_at IS NULL)
+           ORDER BY id DESC
+           LIMIT $3"#,
+        owner_sid,
+        unread_only,
+        limit
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Mark an owner's notification read. Returns false if it does not exist.
+pub async fn mark_read(pool: &PgPool, owner_sid: &str, id: i64) -> sqlx::Result<bool> {
+    let r = sqlx::query!(
+        "UPDATE alert_notification SET read_at = COALESCE(read_at, now()) WHERE owner_sid = $1 AND id = $2",
+        owner_sid,
+        id
+    )
+    .execute(pool)
+    .await?;
+    Ok(r.rows_affected() > 0)
+}
+
+/// Ledger entry by feed id
+pub async fn entry(pool: &PgPool, id: i64) -> sqlx::Result<Option<WatchedEntry>> {
+    sqlx::query_as!(
+        WatchedEntry,
+        r#"SELECT container_id, sequence, physics_delta #>> '{}' AS physics_delta
+           FROM ledger_entry WHERE id = $1"#,
+        id
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+/// Sum of recorded physics deltas of a container (entries without one count as 0)
+pub async fn balance(pool: &PgPool, container_id: &str) -> sqlx::Result<String> {
+    let r = sqlx::query!(
+        r#"SELECT COALESCE(SUM((physics_delta #>> '{}')::numeric), 0)::text AS "balance!"
+           FROM ledger_entry WHERE container_id = $1"#,
+        container_id
+    )
+    .fetch_one(pool)
+    .await?;
+    Ok(r.balance)
+}
+
+/// Timestamp (ms) of the container's latest entry
+pub async fn last_activity_ms(pool: &PgPool, container_id: &str) -> sqlx::Result<Option<i64>> {
+    let r = sqlx::query!(
+        "SELECT MAX(ts_unix_ms) AS last FROM ledger_entry WHERE container_id = $1",
+        container_id
+    )
+    .fetch_one(pool)
+    .await?;
+    Ok(r.last)
+}
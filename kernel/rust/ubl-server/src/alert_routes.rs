@@ -0,0 +1,435 @@
+//! # Container alerts (watch-only subscriptions)
+//!
+//! A caller subscribes to containers it can read (admin/operator/auditor, or
+//! named in its ASC container scope) with rules derived from the ledger:
+//!
+//! - `balance_below`: the sum of recorded `physics_delta` drops under `threshold`
+//! - `delta_above`: one entry moves more than `threshold` either way
+//! - `inactive_for`: no entry for `threshold` hours
+//!
+//! Routes (session cookie or Bearer token; rules are private to their owner):
+//!
+//! - GET    /alerts                          own rules
+//! - POST   /alerts                          create a rule
+//! - GET    /alerts/:alert_id
+//! - PUT    /alerts/:alert_id                change threshold / enabled
+//! - DELETE /alerts/:alert_id
+//! - GET    /alerts/notifications?unread=&limit=
+//! - POST   /alerts/notifications/:id/read
+//!
+//! The engine follows the ledger feed on the event bus (`event_bus.rs`) for
+//! balance and delta rules and sweeps inactivity rules every minute.
+//! `balance_below` and `inactive_for` notify once on entering the alerting state and re-arm when it clears.
+//! Notifications are stored in `alert_notification`; each insert raises an
+//! `alert_notifications` NOTIFY for delivery.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    routing::{get, post},
+    Json, Router,
+};
+use serde::Deserialize;
+use std::time::Duration;
+use time::OffsetDateTime;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::alert_db::{self, AlertNotification, AlertRule};
+use crate::auth::{self, rbac};
+use crate::event_bus::{BusEvent, Overflow, RecvError};
+use crate::AppState;
+
+/// What a rule watches
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlertKind {
+    BalanceBelow,
+    DeltaAbove,
+    InactiveFor,
+}
+
+impl AlertKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AlertKind::BalanceBelow => "balance_below",
+            AlertKind::DeltaAbove => "delta_above",
+            AlertKind::InactiveFor => "inactive_for",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "balance_below" => Some(AlertKind::BalanceBelow),
+            "delta_above" => Some(AlertKind::DeltaAbove),
+            "inactive_for" => Some(AlertKind::InactiveFor),
+            _ => None,
+        }
+    }
+
+    /// Validate a threshold for this kind
+    pub fn check_threshold(&self, threshold: &str) -> Result<i128, String> {
+        let t: i128 = threshold
+            .parse()
+            .map_err(|_| format!("threshold must be an integer: {}", threshold))?;
+        match self {
+            AlertKind::BalanceBelow => Ok(t),
+            AlertKind::DeltaAbove if t < 0 => Err("delta_above threshold must not be negative".to_string()),
+            AlertKind::InactiveFor if t <= 0 => Err("inactive_for threshold is in hours and must be positive".to_string()),
+            _ => Ok(t),
+        }
+    }
+}
+
+/// Alert message when `balance` is under `threshold`
+fn balance_alert(threshold: i128, balance: i128) -> Option<String> {
+    (balance < threshold).then(|| format!("balance {} is below {}", balance, threshold))
+}
+
+/// Alert message when `delta` moves more than `threshold`
+fn delta_alert(threshold: i128, delta: i128) -> Option<String> {
+    (delta.unsigned_abs() > threshold.unsigned_abs()).then(|| format!("delta {} exceeds {}", delta, threshold))
+}
+
+/// Alert message when nothing happened for `hours` (since `last_ms`, or since the rule was created)
+fn inactivity_alert(hours: i128, last_ms: i64, now_ms: i64) -> Option<String> {
+    let idle_ms = i128::from(now_ms - last_ms);
+    (idle_ms > hours * 3_600_000).then(|| format!("no activity for {}h (limit {}h)", idle_ms / 3_600_000, hours))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateAlertReq {
+    pub container_id: String,
+    pub kind: String,
+    pub threshold: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateAlertReq {
+    #[serde(default)]
+    pub threshold: Option<String>,
+    #[serde(default)]
+    pub enabled: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct NotificationsQuery {
+    #[serde(default)]
+    pub unread: bool,
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+}
+
+fn default_limit() -> i64 {
+    100
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/alerts", get(route_list).post(route_create))
+        .route("/alerts/notifications", get(route_notifications))
+        .route("/alerts/notifications/:id/read", post(route_mark_read))
+        .route(
+            "/alerts/:alert_id",
+            get(route_get).put(route_update).delete(route_delete),
+        )
+}
+
+fn internal(e: sqlx::Error) -> (StatusCode, String) {
+    (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+}
+
+fn not_found() -> (StatusCode, String) {
+    (StatusCode::NOT_FOUND, "Alert not found".to_string())
+}
+
+/// Whether the caller may read `container_id`
+async fn can_read(state: &AppState, caller: &rbac::RoleContext, container_id: &str) -> bool {
+    if caller.has_any(&[rbac::ADMIN, rbac::OPERATOR, rbac::AUDITOR]) {
+        return true;
+    }
+    match auth::validate_asc(&state.pool, &caller.session.sid).await {
+        Ok(asc) => asc.containers.iter().any(|c| c == container_id),
+        Err(_) => false,
+    }
+}
+
+/// GET /alerts
+async fn route_list(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<AlertRule>>, (StatusCode, String)> {
+    let caller = rbac::authenticate(&state.pool, &headers).await?;
+    let rules = alert_db::list_for_owner(&state.pool, &caller.session.sid)
+        .await
+        .map_err(internal)?;
+    Ok(Json(rules))
+}
+
+/// POST /alerts
+async fn route_create(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<CreateAlertReq>,
+) -> Result<(StatusCode, Json<AlertRule>), (StatusCode, String)> {
+    let caller = rbac::authenticate(&state.pool, &headers).await?;
+    let kind = AlertKind::parse(&req.kind)
+        .ok_or_else(|| (StatusCode::BAD_REQUEST, format!("unknown alert kind: {}", req.kind)))?;
+    let threshold = kind
+        .check_threshold(&req.threshold)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    if !can_read(&state, &caller, &req.container_id).await {
+        warn!(
+            decision = "reject",
+            error_code = "container_not_readable",
+            sid = %caller.session.sid,
+            container_id = %req.container_id
+        );
+        return Err((StatusCode::FORBIDDEN, format!("cannot read container {}", req.container_id)));
+    }
+
+    let rule = alert_db::insert(
+        &state.pool,
+        &caller.session.sid,
+        &req.container_id,
+        kind.as_str(),
+        &threshold.to_string(),
+    )
+    .await
+    .map_err(internal)?;
+    info!(
+        "🔔 ALERT created id={} container={} {} {} by={}",
+        rule.alert_id, rule.container_id, rule.kind, rule.threshold, rule.owner_sid
+    );
+    Ok((StatusCode::CREATED, Json(rule)))
+}
+
+/// GET /alerts/:alert_id
+async fn route_get(
+    State(state): State<AppState>,
+    Path(alert_id): Path<Uuid>,
+    headers: HeaderMap,
+) -> Result<Json<AlertRule>, (StatusCode, String)> {
+    let caller = rbac::authenticate(&state.pool, &headers).await?;
+    alert_db::get(&state.pool, &caller.session.sid, alert_id)
+        .await
+        .map_err(internal)?
+        .map(Json)
+        .ok_or_else(not_found)
+}
+
+/// PUT /alerts/:alert_id
+async fn route_update(
+    State(state): State<AppState>,
+    Path(alert_id): Path<Uuid>,
+    headers: HeaderMap,
+    Json(req): Json<UpdateAlertReq>,
+) -> Result<Json<AlertRule>, (StatusCode, String)> {
+    let caller = rbac::authenticate(&state.pool, &headers).await?;
+    let rule = alert_db::get(&state.pool, &caller.session.sid, alert_id)
+        .await
+        .map_err(internal)?
+        .ok_or_else(not_found)?;
+
+    let threshold = match &req.threshold {
+        Some(t) => {
+            let kind = AlertKind::parse(&rule.kind).ok_or_else(|| {
+                (StatusCode::INTERNAL_SERVER_ERROR, format!("stored alert kind is invalid: {}", rule.kind))
+            })?;
+            Some(kind.check_threshold(t).map_err(|e| (StatusCode::BAD_REQUEST, e))?.to_string())
+        }
+        None => None,
+    };
+    // Re-enabling is a new subscription: the container must still be readable
+    if req.enabled == Some(true) && !can_read(&state, &caller, &rule.container_id).await {
+        return Err((StatusCode::FORBIDDEN, format!("cannot read container {}", rule.container_id)));
+    }
+
+    alert_db::update(&state.pool, &caller.session.sid, alert_id, threshold.as_deref(), req.enabled)
+        .await
+        .map_err(internal)?
+        .map(Json)
+        .ok_or_else(not_found)
+}
+
+/// DELETE /alerts/:alert_id
+async fn route_delete(
+    State(state): State<AppState>,
+    Path(alert_id): Path<Uuid>,
+    headers: HeaderMap,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let caller = rbac::authenticate(&state.pool, &headers).await?;
+    if !alert_db::delete(&state.pool, &caller.session.sid, alert_id)
+        .await
+        .map_err(internal)?
+    {
+        return Err(not_found());
+    }
+    info!("🔕 ALERT deleted id={} by={}", alert_id, caller.session.sid);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// GET /alerts/notifications
+async fn route_notifications(
+    State(state): State<AppState>,
+    Query(q): Query<NotificationsQuery>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<AlertNotification>>, (StatusCode, String)> {
+    let caller = rbac::authenticate(&state.pool, &headers).await?;
+    let items = alert_db::notifications(&state.pool, &caller.session.sid, q.unread, q.limit.clamp(1, 1000))
+        .await
+        .map_err(internal)?;
+    Ok(Json(items))
+}
+
+/// POST /alerts/notifications/:id/read
+async fn route_mark_read(
+    State(state): State<AppState>,
+    Path(id): Path<i64>,
+    headers: HeaderMap,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let caller = rbac::authenticate(&state.pool, &headers).await?;
+    if !alert_db::mark_read(&state.pool, &caller.session.sid, id)
+        .await
+        .map_err(internal)?
+    {
+        return Err((StatusCode::NOT_FOUND, "Notification not found".to_string()));
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ============================================================================
+// ENGINE
+// ============================================================================
+
+/// Notify on entering the alerting state, re-arm on leaving it
+async fn level(state: &AppState, rule: &AlertRule, alert: Option<String>, detail: serde_json::Value) -> sqlx::Result<()> {
+    match alert {
+        Some(message) if !rule.firing => {
+            alert_db::notify(&state.pool, rule, &message, detail).await?;
+            info!("🚨 ALERT fired id={} container={} {}", rule.alert_id, rule.container_id, message);
+        }
+        None if rule.firing => alert_db::set_firing(&state.pool, rule.alert_id, false).await?,
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Evaluate balance and delta rules against one new entry
+async fn on_entry(state: &AppState, entry_id: i64) -> sqlx::Result<()> {
+    let Some(entry) = alert_db::entry(&state.pool, entry_id).await? else {
+        return Ok(());
+    };
+    let rules = alert_db::enabled_for_container(&state.pool, &entry.container_id).await?;
+    if rules.is_empty() {
+        return Ok(());
+    }
+    let delta: i128 = entry.physics_delta.as_deref().and_then(|d| d.parse().ok()).unwrap_or(0);
+    let mut balance: Option<i128> = None;
+
+    for rule in &rules {
+        let Ok(threshold) = rule.threshold.parse::<i128>() else {
+            continue;
+        };
+        match AlertKind::parse(&rule.kind) {
+            Some(AlertKind::DeltaAbove) => {
+                if let Some(message) = delta_alert(threshold, delta) {
+                    let detail = serde_json::json!({ "sequence": entry.sequence, "physics_delta": delta.to_string() });
+                    alert_db::notify(&state.pool, rule, &message, detail).await?;
+                    info!("🚨 ALERT fired id={} container={} {}", rule.alert_id, rule.container_id, message);
+                }
+            }
+            Some(AlertKind::BalanceBelow) => {
+                let b = match balance {
+                    Some(b) => b,
+                    None => {
+                        let b = alert_db::balance(&state.pool, &entry.container_id)
+                            .await?
+                            .parse()
+                            .unwrap_or(0);
+                        *balance.insert(b)
+                    }
+                };
+                let detail = serde_json::json!({ "sequence": entry.sequence, "balance": b.to_string() });
+                level(state, rule, balance_alert(threshold, b), detail).await?;
+            }
+            // Activity re-arms inactivity rules
+            Some(AlertKind::InactiveFor) if rule.firing => alert_db::set_firing(&state.pool, rule.alert_id, false).await?,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Evaluate every inactivity rule
+async fn sweep_inactivity(state: &AppState) -> sqlx::Result<()> {
+    let now_ms = (OffsetDateTime::now_utc().unix_timestamp_nanos() / 1_000_000) as i64;
+    for rule in alert_db::enabled_of_kind(&state.pool, AlertKind::InactiveFor.as_str()).await? {
+        let Ok(hours) = rule.threshold.parse::<i128>() else {
+            continue;
+        };
+        let since_rule = (rule.updated_at.unix_timestamp_nanos() / 1_000_000) as i64;
+        let last = alert_db::last_activity_ms(&state.pool, &rule.container_id)
+            .await?
+            .map_or(since_rule, |l| l.max(since_rule));
+        let detail = serde_json::json!({ "last_activity_ms": last });
+        level(state, &rule, inactivity_alert(hours, last, now_ms), detail).await?;
+    }
+    Ok(())
+}
+
+/// Run the alert engine: follow the event bus feed and sweep inactivity rules
+pub fn spawn_alert_engine(state: AppState) {
+    let feed = state.clone();
+    // Every entry is evaluated: a slow engine holds the feed back
+    let mut sub = state.bus.subscribe("alerts", Overflow::Block(1024));
+    tokio::spawn(async move {
+        loop {
+            match sub.recv().await {
+                Ok(BusEvent::Entry(e)) => {
+                    if let Err(err) = on_entry(&feed, e.head.id).await {
+                        error!("alert engine: entry {} failed: {}", e.head.id, err);
+                    }
+                }
+                Ok(BusEvent::Committed(_)) | Err(RecvError::Lagged(_)) => {}
+                Err(RecvError::Closed) => return,
+            }
+        }
+    });
+
+    tokio::spawn(async move {
+        let mut tick = tokio::time::interval(Duration::from_secs(60));
+        loop {
+            tick.tick().await;
+            if let Err(e) = sweep_inactivity(&state).await {
+                error!("alert engine: inactivity sweep failed: {}", e);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_thresholds() {
+        assert_eq!(AlertKind::BalanceBelow.check_threshold("-5"), Ok(-5));
+        assert!(AlertKind::DeltaAbove.check_threshold("-1").is_err());
+        assert!(AlertKind::InactiveFor.check_threshold("0").is_err());
+        assert!(AlertKind::InactiveFor.check_threshold("1.5").is_err());
+        for k in [AlertKind::BalanceBelow, AlertKind::DeltaAbove, AlertKind::InactiveFor] {
+            assert_eq!(AlertKind::parse(k.as_str()), Some(k));
+        }
+    }
+
+    #[test]
+    fn test_conditions() {
+        assert!(balance_alert(100, 99).is_some());
+        assert!(balance_alert(100, 100).is_none());
+        // Outflows count as much as inflows
+        assert!(delta_alert(50, -51).is_some());
+        assert!(delta_alert(50, 50).is_none());
+        assert!(inactivity_alert(1, 0, 3_600_001).is_some());
+        assert!(inactivity_alert(1, 0, 3_600_000).is_none());
+    }
+}
@@ -0,0 +1,105 @@
+//! Ledger archive segments (table `ledger_archive`, sql/031_ledger_archive.sql)
+
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use time::OffsetDateTime;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ArchiveSegment {
+    pub container_id: String,
+    pub from_seq: i64,
+    pub to_seq: i64,
+    pub entries: i32,
+    pub blob_key: String,
+    pub blake3: String,
+    pub size_bytes: i64,
+    pub parts: i32,
+    pub backend: String,
+    pub last_entry_hash: String,
+    pub created_by: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+/// One ledger row as written to a segment (one JSON line each)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ArchivedEntry {
+    pub container_id: String,
+    pub sequence: i64,
+    pub link_hash: String,
+    pub previous_hash: String,
+    pub entry_hash: String,
+    pub ts_unix_ms: i64,
+    pub intent_class: Option<String>,
+    pub physics_delta: Option<String>,
+}
+
+/// Entries of a container after `after_seq`, oldest first
+pub async fn entries_after(
+    pool: &PgPool,
+    container_id: &str,
+    after_seq: i64,
+    limit: i64,
+) -> sqlx::Result<Vec<ArchivedEntry>> {
+    sqlx::query_as!(
+        ArchivedEntry,
+        r#"SELECT container_id, sequence, link_hash, previous_hash, entry_hash, ts_unix_ms,
+                  intent_class, physics_delta #>> '{}' AS physics_delta
+           FROM ledger_entry
+           WHERE container_id = $1 AND sequence > $2
+           ORDER BY sequence
+           LIMIT $3"#,
+        container_id,
+        after_seq,
+        limit
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Segments of a container, oldest first
+pub async fn list(pool: &PgPool, container_id: &str) -> sqlx::Result<Vec<ArchiveSegment>> {
+    sqlx::query_as!(
+        ArchiveSegment,
+        r#"SELECT container_id, from_seq, to_seq, entries, blob_key, blake3, size_bytes, parts,
+                  backend, last_entry_hash, created_by, created_at
+           FROM ledger_archive WHERE container_id = $1 ORDER BY from_seq"#,
+        container_id
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Highest archived sequence of a container (0 if none)
+pub async fn archived_through(pool: &PgPool, container_id: &str) -> sqlx::Result<i64> {
+    let r = sqlx::query!(
+        r#"SELECT COALESCE(MAX(to_seq), 0) AS "to_seq!" FROM ledger_archive WHERE container_id = $1"#,
+        container_id
+    )
+    .fetch_one(pool)
+    .await?;
+    Ok(r.to_seq)
+}
+
+pub async fn insert(pool: &PgPool, s: &ArchiveSegment) -> sqlx::Result<()> {
+    sqlx::query!(
+        r#"INSERT INTO ledger_archive (container_id, from_seq, to_seq, entries, blob_key, blake3, size_bytes,
+                                       parts, backend, last_entry_hash, created_by, created_at)
+           VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)"#,
+        s.container_id,
+        s.from_seq,
+        s.to_seq,
+        s.entries,
+        s.blob_key,
+        s.blake3,
+        s.size_bytes,
+        s.parts,
+        s.backend,
+        s.last_entry_hash,
+        s.created_by,
+        s.created_at
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,294 @@
+//! # Ledger archive
+//!
+//! Copies a container's ledger into blob storage (`blob::BlobBackend`) as
+//! segments of consecutive entries, one canonical JSON line per entry:
+//!
+//! - POST /admin/archive/:container_id         (admin) archive the next segment
+//! - GET  /admin/archive/:container_id         (admin/operator/auditor) list segments
+//! - POST /admin/archive/:container_id/verify  (admin/operator/auditor) read every segment back
+//!
+//! A segment covers the entries after the last archived one, up to
+//! `UBL_ARCHIVE_SEGMENT_ENTRIES` (default 10000). Verification checks each
+//! segment's BLAKE3, its sequence range, and the hash chain across segments.
+
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    routing::post,
+    Json, Router,
+};
+use serde::Serialize;
+use time::OffsetDateTime;
+use tracing::{info, warn};
+
+use crate::archive_db::{self, ArchiveSegment, ArchivedEntry};
+use crate::auth::rbac;
+use crate::blob::{BlobError, BlobStore};
+use crate::AppState;
+
+const DEFAULT_SEGMENT_ENTRIES: i64 = 10_000;
+
+#[derive(Debug, Serialize)]
+pub struct ArchiveResp {
+    /// `None` when every entry is already archived
+    pub segment: Option<ArchiveSegment>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SegmentFailure {
+    pub from_seq: i64,
+    pub to_seq: i64,
+    pub error: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct VerifyResp {
+    pub container_id: String,
+    pub ok: bool,
+    pub segments: usize,
+    pub entries: i64,
+    pub failures: Vec<SegmentFailure>,
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/admin/archive/:container_id", post(route_archive).get(route_list))
+        .route("/admin/archive/:container_id/verify", post(route_verify))
+}
+
+fn internal(e: impl ToString) -> (StatusCode, String) {
+    (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+}
+
+fn segment_entries() -> i64 {
+    std::env::var("UBL_ARCHIVE_SEGMENT_ENTRIES")
+        .ok()
+        .and_then(|n| n.parse().ok())
+        .filter(|&n| n > 0)
+        .unwrap_or(DEFAULT_SEGMENT_ENTRIES)
+}
+
+/// Blob key of a segment; the container id is percent-encoded into one path segment
+pub fn segment_key(container_id: &str, from_seq: i64, to_seq: i64) -> String {
+    let mut id = String::with_capacity(container_id.len());
+    for b in container_id.bytes() {
+        if b.is_ascii_alphanumeric() || matches!(b, b'.' | b'_' | b'-') {
+            id.push(b as char);
+        } else {
+            id.push_str(&format!("%{:02X}", b));
+        }
+    }
+    format!("ledger/{}/{:020}-{:020}.jsonl", id, from_seq, to_seq)
+}
+
+/// One canonical JSON line per entry
+pub fn encode_segment(entries: &[ArchivedEntry]) -> Vec<u8> {
+    let mut out = Vec::new();
+    for e in entries {
+        let value = serde_json::to_value(e).expect("entry serializes");
+        out.extend(ubl_atom::canonicalize(&value).expect("entry is canonical JSON"));
+        out.push(b'\n');
+    }
+    out
+}
+
+pub fn decode_segment(data: &[u8]) -> Result<Vec<ArchivedEntry>, String> {
+    data.split(|&b| b == b'\n')
+        .filter(|line| !line.is_empty())
+        .enumerate()
+        .map(|(i, line)| serde_json::from_slice(line).map_err(|e| format!("line {}: {}", i + 1, e)))
+        .collect()
+}
+
+/// Check decoded entries against their segment row and the previous segment's last hash
+pub fn check_segment(seg: &ArchiveSegment, entries: &[ArchivedEntry], prev_hash: Option<&str>) -> Result<(), String> {
+    if entries.len() != seg.entries as usize {
+        return Err(format!("{} entries, segment records {}", entries.len(), seg.entries));
+    }
+    let mut expected_seq = seg.from_seq;
+    let mut prev = prev_hash.map(str::to_string);
+    for e in entries {
+        if e.container_id != seg.container_id {
+            return Err(format!("sequence {} belongs to {}", e.sequence, e.container_id));
+        }
+        if e.sequence != expected_seq {
+            return Err(format!("expected sequence {}, found {}", expected_seq, e.sequence));
+        }
+        if let Some(p) = &prev {
+            if &e.previous_hash != p {
+                return Err(format!("chain break at sequence {}", e.sequence));
+            }
+        }
+        prev = Some(e.entry_hash.clone());
+        expected_seq += 1;
+    }
+    if expected_seq - 1 != seg.to_seq || prev.as_deref() != Some(seg.last_entry_hash.as_str()) {
+        return Err("segment does not end where its row says".to_string());
+    }
+    Ok(())
+}
+
+/// POST /admin/archive/:container_id
+async fn route_archive(
+    State(state): State<AppState>,
+    Path(container_id): Path<String>,
+    headers: HeaderMap,
+) -> Result<Json<ArchiveResp>, (StatusCode, String)> {
+    let caller = rbac::require_role(&state.pool, &headers, &[rbac::ADMIN]).await?;
+
+    let after = archive_db::archived_through(&state.pool, &container_id)
+        .await
+        .map_err(internal)?;
+    let entries = archive_db::entries_after(&state.pool, &container_id, after, segment_entries())
+        .await
+        .map_err(internal)?;
+    let (Some(first), Some(last)) = (entries.first(), entries.last()) else {
+        return Ok(Json(ArchiveResp { segment: None }));
+    };
+    let (from_seq, to_seq, last_entry_hash) = (first.sequence, last.sequence, last.entry_hash.clone());
+
+    let key = segment_key(&container_id, from_seq, to_seq);
+    let data = encode_segment(&entries);
+    let BlobStore { backend, options } = state.blobs.clone();
+    let blob_key = key.clone();
+    let blob = tokio::task::spawn_blocking(move || backend.put(&blob_key, &data, &options))
+        .await
+        .map_err(internal)?
+        .map_err(|e| {
+            warn!(container_id = %container_id, key = %key, error = %e, "❌ archive upload failed");
+            (StatusCode::BAD_GATEWAY, e.to_string())
+        })?;
+
+    let segment = ArchiveSegment {
+        container_id: container_id.clone(),
+        from_seq,
+        to_seq,
+        entries: entries.len() as i32,
+        blob_key: blob.key,
+        blake3: blob.blake3,
+        size_bytes: blob.size as i64,
+        parts: blob.parts as i32,
+        backend: state.blobs.backend.name().to_string(),
+        last_entry_hash,
+        created_by: caller.session.sid.clone(),
+        created_at: OffsetDateTime::now_utc(),
+    };
+    archive_db::insert(&state.pool, &segment).await.map_err(|e| match &e {
+        sqlx::Error::Database(d) if d.is_unique_violation() => {
+            (StatusCode::CONFLICT, "segment archived concurrently".to_string())
+        }
+        _ => internal(e),
+    })?;
+    info!(
+        "🗄️  ARCHIVE container={} seq={}..{} size={} parts={} backend={}",
+        segment.container_id, segment.from_seq, segment.to_seq, segment.size_bytes, segment.parts, segment.backend
+    );
+    Ok(Json(ArchiveResp { segment: Some(segment) }))
+}
+
+/// GET /admin/archive/:container_id
+async fn route_list(
+    State(state): State<AppState>,
+    Path(container_id): Path<String>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<ArchiveSegment>>, (StatusCode, String)> {
+    rbac::require_role(&state.pool, &headers, &[rbac::ADMIN, rbac::OPERATOR, rbac::AUDITOR]).await?;
+    let segments = archive_db::list(&state.pool, &container_id).await.map_err(internal)?;
+    Ok(Json(segments))
+}
+
+/// POST /admin/archive/:container_id/verify
+async fn route_verify(
+    State(state): State<AppState>,
+    Path(container_id): Path<String>,
+    headers: HeaderMap,
+) -> Result<Json<VerifyResp>, (StatusCode, String)> {
+    rbac::require_role(&state.pool, &headers, &[rbac::ADMIN, rbac::OPERATOR, rbac::AUDITOR]).await?;
+    let segments = archive_db::list(&state.pool, &container_id).await.map_err(internal)?;
+
+    let mut failures = Vec::new();
+    let mut entries = 0i64;
+    let mut prev_hash: Option<String> = None;
+    for seg in &segments {
+        let backend = state.blobs.backend.clone();
+        let (key, blake3) = (seg.blob_key.clone(), seg.blake3.clone());
+        let read = tokio::task::spawn_blocking(move || backend.get_verified(&key, &blake3))
+            .await
+            .map_err(internal)?;
+        let checked = read
+            .map_err(|e: BlobError| e.to_string())
+            .and_then(|data| decode_segment(&data))
+            .and_then(|decoded| check_segment(seg, &decoded, prev_hash.as_deref()));
+        if let Err(error) = checked {
+            warn!(container_id = %container_id, from_seq = seg.from_seq, error = %error, "❌ archive segment failed verification");
+            failures.push(SegmentFailure {
+                from_seq: seg.from_seq,
+                to_seq: seg.to_seq,
+                error,
+            });
+        }
+        entries += i64::from(seg.entries);
+        prev_hash = Some(seg.last_entry_hash.clone());
+    }
+
+    Ok(Json(VerifyResp {
+        container_id,
+        ok: failures.is_empty(),
+        segments: segments.len(),
+        entries,
+        failures,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(sequence: i64, previous_hash: &str, entry_hash: &str) -> ArchivedEntry {
+        ArchivedEntry {
+            container_id: "repo://t/r".to_string(),
+            sequence,
+            link_hash: format!("l{}", sequence),
+            previous_hash: previous_hash.to_string(),
+            entry_hash: entry_hash.to_string(),
+            ts_unix_ms: sequence,
+            intent_class: Some("Observation".to_string()),
+            physics_delta: Some("0".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_segment_key_is_one_path_segment() {
+        assert_eq!(
+            segment_key("repo://t/r", 1, 2),
+            "ledger/repo%3A%2F%2Ft%2Fr/00000000000000000001-00000000000000000002.jsonl"
+        );
+    }
+
+    #[test]
+    fn test_segment_round_trip_and_chain() {
+        let entries = vec![entry(1, "0x00", "h1"), entry(2, "h1", "h2")];
+        let decoded = decode_segment(&encode_segment(&entries)).unwrap();
+        assert_eq!(decoded, entries);
+
+        let seg = ArchiveSegment {
+            container_id: "repo://t/r".to_string(),
+            from_seq: 1,
+            to_seq: 2,
+            entries: 2,
+            blob_key: String::new(),
+            blake3: String::new(),
+            size_bytes: 0,
+            parts: 1,
+            backend: "fs".to_string(),
+            last_entry_hash: "h2".to_string(),
+            created_by: String::new(),
+            created_at: OffsetDateTime::UNIX_EPOCH,
+        };
+        assert!(check_segment(&seg, &decoded, None).is_ok());
+        assert!(check_segment(&seg, &decoded, Some("other")).is_err());
+
+        let broken = vec![entry(1, "0x00", "h1"), entry(2, "hX", "h2")];
+        assert!(check_segment(&seg, &broken, None).is_err());
+    }
+}
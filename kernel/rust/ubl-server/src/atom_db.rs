@@ -0,0 +1,91 @@
+//! Atom store persistence (`atom_store`, `atom_chunk`; see `crate::atom_store`)
+
+use serde::Serialize;
+use sqlx::PgPool;
+use time::OffsetDateTime;
+
+/// What the store knows of an atom, without its bytes
+#[derive(Debug, Clone, Serialize)]
+pub struct AtomRow {
+    pub atom_hash: String,
+    /// Canonical bytes, uncompressed
+    pub size: i64,
+    /// Bytes held, all chunks
+    pub stored_size: i64,
+    pub codec: String,
+    pub chunk_size: i32,
+    pub chunks: i32,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+pub async fn get(pool: &PgPool, atom_hash: &str) -> sqlx::Result<Option<AtomRow>> {
+    sqlx::query_as!(
+        AtomRow,
+        r#"SELECT atom_hash, size, stored_size, codec, chunk_size, chunks, created_at
+           FROM atom_store WHERE atom_hash = $1"#,
+        atom_hash
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+/// Canonical size of a stored atom
+pub async fn size(pool: &PgPool, atom_hash: &str) -> sqlx::Result<Option<i64>> {
+    sqlx::query_scalar!("SELECT size FROM atom_store WHERE atom_hash = $1", atom_hash)
+        .fetch_optional(pool)
+        .await
+}
+
+/// Store an atom and its chunks; false if it was already stored
+pub async fn insert(
+    pool: &PgPool,
+    atom_hash: &str,
+    size: i64,
+    codec: &str,
+    chunk_size: i32,
+    chunks: &[Vec<u8>],
+) -> sqlx::Result<bool> {
+    let stored_size: i64 = chunks.iter().map(|c| c.len() as i64).sum();
+    let mut tx = pool.begin().await?;
+    let inserted = sqlx::query!(
+        r#"INSERT INTO atom_store (atom_hash, size, stored_size, codec, chunk_size, chunks)
+           VALUES ($1, $2, $3, $4, $5, $6) ON CONFLICT (atom_hash) DO NOTHING"#,
+        atom_hash,
+        size,
+        stored_size,
+        codec,
+        chunk_size,
+        chunks.len() as i32
+    )
+    .execute(&mut *tx)
+    .await?
+    .rows_affected()
+        == 1;
+    if inserted {
+        for (idx, data) in chunks.iter().enumerate() {
+            sqlx::query!(
+                "INSERT INTO atom_chunk (atom_hash, idx, data) VALUES ($1, $2, $3)",
+                atom_hash,
+                idx as i32,
+                data
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+    }
+    tx.commit().await?;
+    Ok(inserted)
+}
+
+/// Stored chunks `first..=last`, in order
+pub async fn chunks(pool: &PgPool, atom_hash: &str, first: i32, last: i32) -> sqlx::Result<Vec<Vec<u8>>> {
+    sqlx::query_scalar!(
+        "SELECT data FROM atom_chunk WHERE atom_hash = $1 AND idx BETWEEN $2 AND $3 ORDER BY idx",
+        atom_hash,
+        first,
+        last
+    )
+    .fetch_all(pool)
+    .await
+}
@@ -0,0 +1,284 @@
+//! # Atom store
+//!
+//! - PUT /atoms (any session; body: the atom as JSON)
+//! - GET /atoms/:atom_hash (`Range: bytes=...` for part of a large atom;
+//!   immutable, 304 on `If-None-Match: "<atom_hash>"`)
+//! - GET /atoms/:atom_hash/meta
+//!
+//! Intents that embed large documents keep them here instead of inline.
+//! An atom is addressed by `atom_hash`, the BLAKE3 of its canonical bytes
+//! (SPEC-UBL-ATOM) before any compression, so the hash a link commits to
+//! never depends on how the store keeps it. Storing is idempotent.
+//!
+//! The canonical bytes are split in [`CHUNK_SIZE`] chunks. Atoms of at least
+//! `UBL_ATOM_COMPRESS_MIN` bytes (default 4096) keep each chunk as an
+//! independent zstd frame, smaller ones keep them raw, so a range
+//! read fetches and decompresses only the chunks it overlaps. A full read is
+//! checked against `atom_hash` before it is answered. `UBL_ATOM_MAX_BYTES`
+//! (default 64 MiB) bounds one atom, body and canonical form both.
+//!
+//! A `max_atom_size` policy constraint bounds the atom a commit carries by
+//! its size here; an atom the store does not hold fails it
+//! (`ubl_policy_vm::constraints`).
+
+use axum::{
+    body::Body,
+    extract::{Path, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, put},
+    Json, Router,
+};
+use serde::Serialize;
+use tracing::{info, warn};
+
+use crate::atom_db::{self, AtomRow};
+use crate::auth::rbac;
+use crate::{http_cache, AppState};
+
+/// Canonical bytes per chunk
+pub const CHUNK_SIZE: usize = 1 << 20;
+
+/// Size limits of the store
+#[derive(Debug, Clone, Copy)]
+pub struct AtomConfig {
+    /// Largest atom accepted, in bytes
+    pub max_bytes: usize,
+    /// Atoms at least this large are compressed
+    pub compress_min: usize,
+}
+
+/// Limits from `UBL_ATOM_MAX_BYTES` and `UBL_ATOM_COMPRESS_MIN`
+pub fn config_from_env() -> AtomConfig {
+    let bytes = |name: &str, default: usize| {
+        std::env::var(name).ok().and_then(|v| v.parse().ok()).unwrap_or(default)
+    };
+    AtomConfig {
+        max_bytes: bytes("UBL_ATOM_MAX_BYTES", 64 << 20),
+        compress_min: bytes("UBL_ATOM_COMPRESS_MIN", 4096),
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct PutAtomResp {
+    /// Already held; nothing was written
+    pub existed: bool,
+    pub atom: AtomRow,
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/atoms", put(route_put))
+        .route("/atoms/:atom_hash", get(route_get))
+        .route("/atoms/:atom_hash/meta", get(route_meta))
+        // Bounded by UBL_ATOM_MAX_BYTES in the handler instead
+        .layer(axum::extract::DefaultBodyLimit::disable())
+}
+
+fn internal(e: impl std::fmt::Display) -> (StatusCode, String) {
+    (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+}
+
+/// Canonical bytes as stored chunks
+fn encode(canonical: &[u8], codec: &str) -> std::io::Result<Vec<Vec<u8>>> {
+    canonical
+        .chunks(CHUNK_SIZE)
+        .map(|chunk| match codec {
+            "zstd" => zstd::bulk::compress(chunk, zstd::DEFAULT_COMPRESSION_LEVEL),
+            _ => Ok(chunk.to_vec()),
+        })
+        .collect()
+}
+
+/// Stored chunks back to canonical bytes; a zstd chunk decompressing past
+/// `chunk_size` is an error
+fn decode(chunks: &[Vec<u8>], codec: &str, chunk_size: usize) -> std::io::Result<Vec<u8>> {
+    let mut out = Vec::new();
+    for chunk in chunks {
+        match codec {
+            "zstd" => out.extend(zstd::bulk::decompress(chunk, chunk_size)?),
+            _ => out.extend_from_slice(chunk),
+        }
+    }
+    Ok(out)
+}
+
+/// PUT /atoms
+async fn route_put(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    body: Body,
+) -> Result<(StatusCode, Json<PutAtomResp>), (StatusCode, String)> {
+    let caller = rbac::authenticate(&state.pool, &headers).await?;
+    let max = state.atoms.max_bytes;
+    let too_large = || (StatusCode::PAYLOAD_TOO_LARGE, format!("atom exceeds {} bytes", max));
+    let body = axum::body::to_bytes(body, max).await.map_err(|_| too_large())?;
+    let value: serde_json::Value = serde_json::from_slice(&body)
+        .map_err(|e| (StatusCode::BAD_REQUEST, format!("atom is not JSON: {}", e)))?;
+    let canonical = ubl_atom::canonicalize(&value).map_err(|e| (StatusCode::UNPROCESSABLE_ENTITY, e.to_string()))?;
+    if canonical.len() > max {
+        return Err(too_large());
+    }
+    let atom_hash = ubl_kernel::hash_atom(&canonical);
+    let size = canonical.len();
+    let codec = if size >= state.atoms.compress_min { "zstd" } else { "raw" };
+
+    if let Some(atom) = atom_db::get(&state.pool, &atom_hash).await.map_err(internal)? {
+        return Ok((StatusCode::OK, Json(PutAtomResp { existed: true, atom })));
+    }
+    let chunks = tokio::task::spawn_blocking(move || encode(&canonical, codec))
+        .await
+        .map_err(internal)?
+        .map_err(internal)?;
+    let inserted = atom_db::insert(&state.pool, &atom_hash, size as i64, codec, CHUNK_SIZE as i32, &chunks)
+        .await
+        .map_err(internal)?;
+    let atom = atom_db::get(&state.pool, &atom_hash)
+        .await
+        .map_err(internal)?
+        .ok_or_else(|| internal("stored atom vanished"))?;
+    info!(
+        "🧱 ATOM stored {} size={} stored={} codec={} by={}",
+        atom_hash, atom.size, atom.stored_size, atom.codec, caller.session.sid
+    );
+    let status = if inserted { StatusCode::CREATED } else { StatusCode::OK };
+    Ok((status, Json(PutAtomResp { existed: !inserted, atom })))
+}
+
+/// GET /atoms/:atom_hash/meta
+async fn route_meta(
+    State(state): State<AppState>,
+    Path(atom_hash): Path<String>,
+) -> Result<Json<AtomRow>, (StatusCode, String)> {
+    atom_db::get(&state.pool, &atom_hash)
+        .await
+        .map_err(internal)?
+        .map(Json)
+        .ok_or((StatusCode::NOT_FOUND, format!("atom {} not stored", atom_hash)))
+}
+
+/// Byte range asked by a `Range` header, `start..=end` within `size`.
+/// `None` serves the whole atom (no header, several ranges, or a form this
+/// does not read); `Some(Err)` is unsatisfiable.
+pub fn parse_range(header: &str, size: u64) -> Option<Result<(u64, u64), ()>> {
+    let spec = header.trim().strip_prefix("bytes=")?;
+    if spec.contains(',') {
+        return None;
+    }
+    let (start, end) = spec.split_once('-')?;
+    let (start, end) = (start.trim(), end.trim());
+    let range = if start.is_empty() {
+        let suffix: u64 = end.parse().ok()?;
+        (suffix > 0 && size > 0).then(|| (size.saturating_sub(suffix), size - 1))
+    } else {
+        let start: u64 = start.parse().ok()?;
+        let end: u64 = if end.is_empty() { u64::MAX } else { end.parse().ok()? };
+        if start > end {
+            return None;
+        }
+        (start < size).then(|| (start, end.min(size - 1)))
+    };
+    Some(range.ok_or(()))
+}
+
+/// GET /atoms/:atom_hash
+async fn route_get(
+    State(state): State<AppState>,
+    Path(atom_hash): Path<String>,
+    headers: HeaderMap,
+) -> Result<Response, (StatusCode, String)> {
+    let atom = atom_db::get(&state.pool, &atom_hash)
+        .await
+        .map_err(internal)?
+        .ok_or((StatusCode::NOT_FOUND, format!("atom {} not stored", atom_hash)))?;
+    let etag = http_cache::etag(&atom_hash);
+    if http_cache::not_modified(&headers, &etag) {
+        return Ok(http_cache::not_modified_response("atoms", &etag, http_cache::IMMUTABLE));
+    }
+    let size = atom.size as u64;
+    let chunk = atom.chunk_size as u64;
+    let range = headers
+        .get(header::RANGE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|h| parse_range(h, size));
+    let (start, end) = match range {
+        None => (0, size.saturating_sub(1)),
+        Some(Ok(r)) => r,
+        Some(Err(())) => {
+            let mut response = StatusCode::RANGE_NOT_SATISFIABLE.into_response();
+            response.headers_mut().insert(
+                header::CONTENT_RANGE,
+                HeaderValue::from_str(&format!("bytes */{}", size)).map_err(internal)?,
+            );
+            return Ok(response);
+        }
+    };
+
+    let bytes = if size == 0 {
+        Vec::new()
+    } else {
+        let (first, last) = (start / chunk, end / chunk);
+        let stored = atom_db::chunks(&state.pool, &atom_hash, first as i32, last as i32)
+            .await
+            .map_err(internal)?;
+        let codec = atom.codec.clone();
+        let decoded = tokio::task::spawn_blocking(move || decode(&stored, &codec, chunk as usize))
+            .await
+            .map_err(internal)?
+            .map_err(internal)?;
+        let offset = (first * chunk) as usize;
+        let wanted = (start as usize - offset)..(end as usize - offset + 1);
+        decoded
+            .get(wanted)
+            .ok_or_else(|| internal(format!("atom {} is missing chunks", atom_hash)))?
+            .to_vec()
+    };
+    if range.is_none() && ubl_kernel::hash_atom(&bytes) != atom_hash {
+        warn!(decision = "reject", error_code = "atom_corrupt", atom_hash = %atom_hash);
+        return Err(internal(format!("atom {} does not match its hash", atom_hash)));
+    }
+
+    let status = if range.is_some() { StatusCode::PARTIAL_CONTENT } else { StatusCode::OK };
+    let mut response = (status, bytes).into_response();
+    let h = response.headers_mut();
+    h.insert(header::CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    h.insert(header::ACCEPT_RANGES, HeaderValue::from_static("bytes"));
+    if range.is_some() {
+        h.insert(
+            header::CONTENT_RANGE,
+            HeaderValue::from_str(&format!("bytes {}-{}/{}", start, end, size)).map_err(internal)?,
+        );
+    }
+    http_cache::set(&mut response, &etag, http_cache::IMMUTABLE);
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_range("bytes=0-99", 1000), Some(Ok((0, 99))));
+        assert_eq!(parse_range("bytes=900-", 1000), Some(Ok((900, 999))));
+        assert_eq!(parse_range("bytes=990-2000", 1000), Some(Ok((990, 999))));
+        assert_eq!(parse_range("bytes=-10", 1000), Some(Ok((990, 999))));
+        assert_eq!(parse_range("bytes=-5000", 1000), Some(Ok((0, 999))));
+        assert_eq!(parse_range("bytes=1000-", 1000), Some(Err(())));
+        assert_eq!(parse_range("bytes=-0", 1000), Some(Err(())));
+        assert_eq!(parse_range("bytes=0-1,5-6", 1000), None);
+        assert_eq!(parse_range("items=0-1", 1000), None);
+        assert_eq!(parse_range("bytes=5-1", 1000), None);
+    }
+
+    #[test]
+    fn test_chunks_round_trip() {
+        let canonical: Vec<u8> = (0..3 * CHUNK_SIZE / 2).map(|i| b"{\"k\":1}"[i % 7]).collect();
+        for codec in ["raw", "zstd"] {
+            let chunks = encode(&canonical, codec).unwrap();
+            assert_eq!(chunks.len(), 2);
+            assert_eq!(decode(&chunks, codec, CHUNK_SIZE).unwrap(), canonical);
+            assert_eq!(decode(&chunks[1..], codec, CHUNK_SIZE).unwrap(), &canonical[CHUNK_SIZE..]);
+        }
+    }
+}
@@ -0,0 +1,236 @@
+//! # Dormancy attestations
+//!
+//! - GET /attestations/key                                  (the attesting public key)
+//! - GET /containers/:id/attestations/dormant?from=&to=     (admin/operator/auditor)
+//!
+//! Auditors ask for proof that nothing happened in a container over a
+//! period. The answer is a `ubl_ledger::DormancyAttestation`
+//! (SPEC-UBL-LEDGER v1.0 §10.1): the container's head sequence and entry
+//! hash, the range, and the two region checkpoints that enclose it, each
+//! bound by the Merkle root of all its heads and the container's path to
+//! it, signed with `UBL_ATTESTATION_KEY` (hex Ed25519 seed). The auditor
+//! verifies it offline against the published key; nothing here has to be
+//! trusted at reading time.
+//!
+//! The statement comes from checkpoints, not from the live ledger: the
+//! opening one is the latest this region wrote at or before `from`, the
+//! closing one the earliest written [`SETTLE_SECS`] or more after `to`
+//! (checkpoint heads are read at the settled cursor, so an entry can reach
+//! them up to that long after it is written). The head must be the same
+//! entry in both, or the request is refused with the two sequences.
+//!
+//! Checkpoints are written by the primary in region mode (`region.rs`); a
+//! single-region deployment gets them by running as a lone primary
+//! (`UBL_REGION` set, no `UBL_PRIMARY_DATABASE_URL`). Only checkpoints this
+//! region wrote itself are used, since those carry its own clock, and only
+//! the primary attests. The primary checkpoints only when the ledger moves,
+//! so on an idle ledger the closing checkpoint waits for the next append.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    routing::get,
+    Json, Router,
+};
+use ed25519_dalek::SigningKey;
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use tracing::info;
+use ubl_ledger::attestation::{self, CheckpointHead, CheckpointRef, ATTESTATION_FORMAT};
+use ubl_ledger::{DormancyAttestation, DormancyStatement};
+
+use crate::auth::rbac;
+use crate::region::Role;
+use crate::region_db::{self, CheckpointRow};
+use crate::AppState;
+
+/// How long after `to` the closing checkpoint must be written
+pub const SETTLE_SECS: i64 = 1;
+
+#[derive(Debug, Deserialize)]
+pub struct DormantQuery {
+    /// Unix seconds
+    pub from: i64,
+    /// Unix seconds
+    pub to: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct KeyResp {
+    pub format: &'static str,
+    pub pubkey: String,
+}
+
+/// The attesting key, from `UBL_ATTESTATION_KEY` (unset: attestations are off)
+pub fn key_from_env() -> anyhow::Result<Option<SigningKey>> {
+    let Some(hex_seed) = std::env::var("UBL_ATTESTATION_KEY").ok().filter(|v| !v.is_empty()) else {
+        return Ok(None);
+    };
+    let seed: [u8; 32] = hex::decode(hex_seed.trim())
+        .ok()
+        .and_then(|b| b.try_into().ok())
+        .ok_or_else(|| anyhow::anyhow!("UBL_ATTESTATION_KEY must be 32 bytes of hex"))?;
+    Ok(Some(SigningKey::from_bytes(&seed)))
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/attestations/key", get(route_key))
+        .route("/containers/:id/attestations/dormant", get(route_dormant))
+}
+
+fn internal(e: impl std::fmt::Display) -> (StatusCode, String) {
+    (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+}
+
+fn key(state: &AppState) -> Result<&SigningKey, (StatusCode, String)> {
+    state.attestation_key.as_deref().ok_or((
+        StatusCode::SERVICE_UNAVAILABLE,
+        "attestations are off (UBL_ATTESTATION_KEY unset)".to_string(),
+    ))
+}
+
+fn unix(t: i64) -> Result<OffsetDateTime, (StatusCode, String)> {
+    OffsetDateTime::from_unix_timestamp(t).map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))
+}
+
+/// The container's head in `checkpoint` and its reference for the statement
+fn locate(checkpoint: &CheckpointRow, container_id: &str) -> Result<Option<(CheckpointHead, CheckpointRef)>, String> {
+    let heads: Vec<CheckpointHead> = checkpoint
+        .heads()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|h| CheckpointHead {
+            container_id: h.container_id,
+            sequence: h.sequence as u64,
+            entry_hash: h.entry_hash,
+        })
+        .collect();
+    let Some(head) = heads.iter().find(|h| h.container_id == container_id).cloned() else {
+        return Ok(None);
+    };
+    let path = attestation::inclusion_path(&heads, container_id).unwrap_or_default();
+    Ok(Some((
+        head,
+        CheckpointRef {
+            epoch: checkpoint.epoch,
+            cursor_id: checkpoint.cursor_id,
+            at: checkpoint.created_at.unix_timestamp(),
+            root: attestation::checkpoint_root(&heads),
+            path,
+        },
+    )))
+}
+
+/// GET /attestations/key
+async fn route_key(State(state): State<AppState>) -> Result<Json<KeyResp>, (StatusCode, String)> {
+    Ok(Json(KeyResp {
+        format: ATTESTATION_FORMAT,
+        pubkey: ubl_kernel::pubkey_from_signing_key(key(&state)?),
+    }))
+}
+
+/// GET /containers/:id/attestations/dormant
+async fn route_dormant(
+    State(state): State<AppState>,
+    Path(container_id): Path<String>,
+    Query(q): Query<DormantQuery>,
+    headers: HeaderMap,
+) -> Result<Json<DormancyAttestation>, (StatusCode, String)> {
+    let caller = rbac::require_role(&state.pool, &headers, &[rbac::ADMIN, rbac::OPERATOR, rbac::AUDITOR]).await?;
+    let key = key(&state)?;
+    let region = state.region.as_deref().ok_or((
+        StatusCode::CONFLICT,
+        "no checkpoints: attestations need region mode (UBL_REGION)".to_string(),
+    ))?;
+    if region.role() != Role::Primary {
+        return Err((
+            StatusCode::CONFLICT,
+            format!("region {} is {}; attestations come from the primary", region.name, region.role().as_str()),
+        ));
+    }
+    if q.from > q.to {
+        return Err((StatusCode::BAD_REQUEST, "from is after to".to_string()));
+    }
+
+    let opening = region_db::checkpoint_before(&state.pool, &region.name, unix(q.from)?)
+        .await
+        .map_err(internal)?
+        .ok_or_else(|| (StatusCode::CONFLICT, format!("no checkpoint at or before {}", q.from)))?;
+    let closing = region_db::checkpoint_after(&state.pool, &region.name, unix(q.to.saturating_add(1 + SETTLE_SECS))?)
+        .await
+        .map_err(internal)?
+        .ok_or_else(|| {
+            (
+                StatusCode::CONFLICT,
+                format!("no checkpoint after {} yet; one is written once the ledger moves", q.to),
+            )
+        })?;
+    let (head, opening) = locate(&opening, &container_id).map_err(internal)?.ok_or_else(|| {
+        (
+            StatusCode::CONFLICT,
+            format!("{} has no entries at the checkpoint opening the range", container_id),
+        )
+    })?;
+    let (closing_head, closing) = locate(&closing, &container_id)
+        .map_err(internal)?
+        .ok_or_else(|| internal(format!("{} missing from a later checkpoint", container_id)))?;
+    if closing_head != head {
+        return Err((
+            StatusCode::CONFLICT,
+            format!(
+                "{} was not dormant: head #{} at {}, #{} at {}",
+                container_id, head.sequence, opening.at, closing_head.sequence, closing.at
+            ),
+        ));
+    }
+
+    let statement = DormancyStatement {
+        format: ATTESTATION_FORMAT.to_string(),
+        container_id: container_id.clone(),
+        from_seq: head.sequence,
+        to_seq: closing_head.sequence,
+        entry_hash: head.entry_hash,
+        from: q.from,
+        to: q.to,
+        opening,
+        closing,
+        issued_at: OffsetDateTime::now_utc().unix_timestamp(),
+    };
+    let attestation = DormancyAttestation::sign(statement, key).map_err(internal)?;
+    info!(
+        "📜 Dormancy attested for {} at #{} over {}..={} by {}",
+        container_id, attestation.statement.from_seq, q.from, q.to, caller.session.sid
+    );
+    Ok(Json(attestation))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::region_db::Head;
+
+    #[test]
+    fn test_locate_binds_head_to_root() {
+        let heads: Vec<Head> = ["C.a", "C.b", "C.c"]
+            .iter()
+            .enumerate()
+            .map(|(i, c)| Head {
+                container_id: c.to_string(),
+                sequence: i as i64 + 1,
+                entry_hash: format!("{:064x}", i),
+            })
+            .collect();
+        let row = CheckpointRow {
+            epoch: 2,
+            cursor_id: 40,
+            region: "eu".to_string(),
+            heads: serde_json::to_value(&heads).unwrap(),
+            created_at: OffsetDateTime::from_unix_timestamp(1000).unwrap(),
+        };
+        let (head, reference) = locate(&row, "C.b").unwrap().unwrap();
+        assert_eq!((head.sequence, reference.epoch, reference.cursor_id, reference.at), (2, 2, 40, 1000));
+        assert!(attestation::verify_inclusion(&head, &reference.path, &reference.root));
+        assert!(locate(&row, "C.z").unwrap().is_none());
+    }
+}
@@ -3,8 +3,12 @@
 //! ASC (Agent Signing Certificate) validation for commits
 //! Enforces scopes: containers, intent_classes, max_delta
 
+pub mod container_grant;
+pub mod container_grant_db;
 pub mod session;
 pub mod session_db;
+pub mod session_policy;
+pub mod rbac;
 pub mod require_stepup;
 
 use axum::{
@@ -15,8 +19,9 @@ use axum::{
 };
 use sqlx::PgPool;
 use time::OffsetDateTime;
+use ubl_errors::ErrorCatalog;
 
-use crate::id_db;
+use crate::{error_routes, id_db};
 
 #[derive(Debug, Clone)]
 pub struct AscContext {
@@ -26,26 +31,29 @@ pub struct AscContext {
     pub max_delta: Option<i128>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, ErrorCatalog)]
+#[catalog(source = "auth", status = 401)]
+#[allow(dead_code)]
 pub enum AuthError {
+    /// No Authorization header
     NoAuth,
+    /// Authorization is not `Bearer ubl:sid:<hash>`
+    #[catalog(status = 400)]
     InvalidFormat,
+    /// The session has no active ASC
     AscNotFound,
+    /// The ASC is outside its validity window
     AscExpired,
+    /// The signing key was revoked
     KeyRevoked,
+    /// The commit is outside the ASC's scopes
+    #[catalog(status = 403)]
     ScopeViolation(String),
 }
 
 impl AuthError {
     pub fn status_code(&self) -> StatusCode {
-        match self {
-            AuthError::NoAuth => StatusCode::UNAUTHORIZED,
-            AuthError::InvalidFormat => StatusCode::BAD_REQUEST,
-            AuthError::AscNotFound => StatusCode::UNAUTHORIZED,
-            AuthError::AscExpired => StatusCode::UNAUTHORIZED,
-            AuthError::KeyRevoked => StatusCode::UNAUTHORIZED,
-            AuthError::ScopeViolation(_) => StatusCode::FORBIDDEN,
-        }
+        error_routes::status(self)
     }
 
     pub fn message(&self) -> String {
@@ -123,16 +131,31 @@ pub fn validate_commit_scopes(
     intent_class: &str,
     physics_delta: &str,
 ) -> Result<(), AuthError> {
+    match scope_violations(asc, container_id, intent_class, physics_delta).into_iter().next() {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
+}
+
+/// Every ASC scope the commit falls outside of (container, intent class, max_delta)
+pub fn scope_violations(
+    asc: &AscContext,
+    container_id: &str,
+    intent_class: &str,
+    physics_delta: &str,
+) -> Vec<AuthError> {
+    let mut violations = Vec::new();
+
     // Check container scope
     if !asc.containers.is_empty() && !asc.containers.contains(&container_id.to_string()) {
-        return Err(AuthError::ScopeViolation(
+        violations.push(AuthError::ScopeViolation(
             format!("Container '{}' not in allowed scopes: {:?}", container_id, asc.containers)
         ));
     }
 
     // Check intent_class scope
     if !asc.intent_classes.is_empty() && !asc.intent_classes.contains(&intent_class.to_string()) {
-        return Err(AuthError::ScopeViolation(
+        violations.push(AuthError::ScopeViolation(
             format!("Intent class '{}' not in allowed scopes: {:?}", intent_class, asc.intent_classes)
         ));
     }
@@ -141,16 +164,17 @@ pub fn validate_commit_scopes(
     if let Some(max_delta) = asc.max_delta {
         let delta: i128 = physics_delta.parse().unwrap_or(0);
         if delta.abs() > max_delta {
-            return Err(AuthError::ScopeViolation(
+            violations.push(AuthError::ScopeViolation(
                 format!("Physics delta {} exceeds max_delta {}", delta, max_delta)
             ));
         }
     }
 
-    Ok(())
+    violations
 }
 
 /// Middleware to validate ASC on protected routes
+#[allow(dead_code)]
 pub async fn asc_middleware(
     req: Request,
     next: Next,
@@ -0,0 +1,193 @@
+//! Container admin grants and their audit trail
+//! (tables `container_admin_grant`, `container_admin_audit`, sql/028_container_admin.sql)
+
+use serde::Serialize;
+use sqlx::PgPool;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AdminGrant {
+    pub grant_id: Uuid,
+    pub container_id: String,
+    pub grantee_sid: String,
+    pub capabilities: Vec<String>,
+    pub granted_by: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub granted_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339")]
+    pub expires_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub revoked_at: Option<OffsetDateTime>,
+    pub revoked_by: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditEntry {
+    pub container_id: String,
+    pub grant_id: Option<Uuid>,
+    pub actor_sid: String,
+    pub action: String,
+    pub capability: Option<String>,
+    pub detail: serde_json::Value,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+/// Store a grant and its `grant` audit entry in one transaction
+pub async fn insert_grant(
+    pool: &PgPool,
+    container_id: &str,
+    grantee_sid: &str,
+    capabilities: &[String],
+    granted_by: &str,
+    expires_at: OffsetDateTime,
+) -> sqlx::Result<AdminGrant> {
+    let mut tx = pool.begin().await?;
+    let grant = sqlx::query_as!(
+        AdminGrant,
+        r#"INSERT INTO container_admin_grant (container_id, grantee_sid, capabilities, granted_by, expires_at)
+           VALUES ($1, $2, $3, $4, $5)
+           RETURNING grant_id, container_id, grantee_sid, capabilities, granted_by,
+                     granted_at, expires_at, revoked_at, revoked_by"#,
+        container_id,
+        grantee_sid,
+        capabilities,
+        granted_by,
+        expires_at
+    )
+    .fetch_one(&mut *tx)
+    .await?;
+
+    sqlx::query!(
+        r#"INSERT INTO container_admin_audit (container_id, grant_id, actor_sid, action, detail)
+           VALUES ($1, $2, $3, 'grant', $4)"#,
+        container_id,
+        grant.grant_id,
+        granted_by,
+        serde_json::json!({
+            "grantee_sid": grantee_sid,
+            "capabilities": capabilities,
+            "expires_at": expires_at.unix_timestamp(),
+        })
+    )
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    Ok(grant)
+}
+
+/// Unrevoked, unexpired grant of `sid` on `container_id` that includes `capability`
+pub async fn active_grant(
+    pool: &PgPool,
+    container_id: &str,
+    sid: &str,
+    capability: &str,
+) -> sqlx::Result<Option<AdminGrant>> {
+    sqlx::query_as!(
+        AdminGrant,
+        r#"SELECT grant_id, container_id, grantee_sid, capabilities, granted_by,
+                  granted_at, expires_at, revoked_at, revoked_by
+           FROM container_admin_grant
+           WHERE container_id = $1 AND grantee_sid = $2 AND $3 = ANY(capabilities)
+             AND revoked_at IS NULL AND expires_at > now()
+           ORDER BY expires_at DESC
+           LIMIT 1"#,
+        container_id,
+        sid,
+        capability
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+/// Unrevoked, unexpired grants on a container
+pub async fn list_active(pool: &PgPool, container_id: &str) -> sqlx::Result<Vec<AdminGrant>> {
+    sqlx::query_as!(
+        AdminGrant,
+        r#"SELECT grant_id, container_id, grantee_sid, capabilities, granted_by,
+                  granted_at, expires_at, revoked_at, revoked_by
+           FROM container_admin_grant
+           WHERE container_id = $1 AND revoked_at IS NULL AND expires_at > now()
+           ORDER BY granted_at"#,
+        container_id
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Revoke a grant and audit it; `None` if it does not exist or is already revoked
+pub async fn revoke(
+    pool: &PgPool,
+    container_id: &str,
+    grant_id: Uuid,
+    revoked_by: &str,
+) -> sqlx::Result<Option<AdminGrant>> {
+    let mut tx = pool.begin().await?;
+    let grant = sqlx::query_as!(
+        AdminGrant,
+        r#"UPDATE container_admin_grant SET revoked_at = now(), revoked_by = $3
+           WHERE container_id = $1 AND grant_id = $2 AND revoked_at IS NULL
+           RETURNING grant_id, container_id, grantee_sid, capabilities, granted_by,
+                     granted_at, expires_at, revoked_at, revoked_by"#,
+        container_id,
+        grant_id,
+        revoked_by
+    )
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    if let Some(g) = &grant {
+        sqlx::query!(
+            r#"INSERT INTO container_admin_audit (container_id, grant_id, actor_sid, action, detail)
+               VALUES ($1, $2, $3, 'revoke', $4)"#,
+            container_id,
+            grant_id,
+            revoked_by,
+            serde_json::json!({ "grantee_sid": g.grantee_sid })
+        )
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+    Ok(grant)
+}
+
+/// Record one use of a capability (`grant_id` is `None` for global admins)
+pub async fn audit_use(
+    pool: &PgPool,
+    container_id: &str,
+    grant_id: Option<Uuid>,
+    actor_sid: &str,
+    capability: &str,
+    detail: serde_json::Value,
+) -> sqlx::Result<()> {
+    sqlx::query!(
+        r#"INSERT INTO container_admin_audit (container_id, grant_id, actor_sid, action, capability, detail)
+           VALUES ($1, $2, $3, 'use', $4, $5)"#,
+        container_id,
+        grant_id,
+        actor_sid,
+        capability,
+        detail
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Audit trail of a container, newest first
+pub async fn audit_log(pool: &PgPool, container_id: &str, limit: i64) -> sqlx::Result<Vec<AuditEntry>> {
+    sqlx::query_as!(
+        AuditEntry,
+        r#"SELECT container_id, grant_id, actor_sid, action, capability, detail, created_at
+           FROM container_admin_audit
+           WHERE container_id = $1
+           ORDER BY created_at DESC, id DESC
+           LIMIT $2"#,
+        container_id,
+        limit
+    )
+    .fetch_all(pool)
+    .await
+}
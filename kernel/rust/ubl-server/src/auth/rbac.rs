@@ -0,0 +1,102 @@
+//! Role checks for operator-facing routes.
+//!
+//! A caller's roles are the union of the session scope (`role` / `roles`)
+//! and the grants stored in `id_role_grant`. Step-up sessions carry `admin`.
+
+use axum::http::{HeaderMap, StatusCode};
+use sqlx::PgPool;
+use tracing::warn;
+
+use crate::auth::require_stepup::{extract_cookie, extract_token};
+use crate::auth::session::Session;
+use crate::auth::session_policy;
+
+pub const ADMIN: &str = "admin";
+pub const OPERATOR: &str = "operator";
+pub const AUDITOR: &str = "auditor";
+pub const DEVELOPER: &str = "developer";
+
+/// Authenticated caller with its effective roles
+#[derive(Debug, Clone)]
+pub struct RoleContext {
+    pub session: Session,
+    pub roles: Vec<String>,
+}
+
+impl RoleContext {
+    pub fn has_any(&self, allowed: &[&str]) -> bool {
+        self.roles.iter().any(|r| allowed.contains(&r.as_str()))
+    }
+}
+
+/// Roles declared directly in a session scope
+pub fn scope_roles(scope: &serde_json::Value) -> Vec<String> {
+    let mut roles: Vec<String> = scope
+        .get("roles")
+        .and_then(|v| v.as_array())
+        .map(|arr| arr.iter().filter_map(|v| v.as_str().map(String::from)).collect())
+        .unwrap_or_default();
+    if let Some(role) = scope.get("role").and_then(|v| v.as_str()) {
+        roles.push(role.to_string());
+    }
+    roles
+}
+
+/// Resolve the session from cookie or Bearer token and require one of `allowed` roles
+pub async fn require_role(
+    pool: &PgPool,
+    headers: &HeaderMap,
+    allowed: &[&str],
+) -> Result<RoleContext, (StatusCode, String)> {
+    let ctx = authenticate(pool, headers).await?;
+    if !ctx.has_any(allowed) {
+        warn!(
+            decision = "reject",
+            error_code = "role_required",
+            sid = %ctx.session.sid,
+            required = ?allowed
+        );
+        return Err((StatusCode::FORBIDDEN, format!("requires role: {}", allowed.join("|"))));
+    }
+    Ok(ctx)
+}
+
+/// Resolve the session from cookie or Bearer token with its effective roles
+pub async fn authenticate(pool: &PgPool, headers: &HeaderMap) -> Result<RoleContext, (StatusCode, String)> {
+    let token = extract_cookie(headers, "session")
+        .or_else(|| extract_token(headers))
+        .ok_or_else(|| {
+            warn!(decision = "reject", error_code = "missing_session");
+            (StatusCode::UNAUTHORIZED, "missing session".to_string())
+        })?;
+
+    let session = session_policy::authenticate(pool, &token).await?;
+
+    let mut roles = scope_roles(&session.scope);
+    let granted = sqlx::query_scalar!(
+        "SELECT role FROM id_role_grant WHERE sid = $1",
+        session.sid
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    roles.extend(granted);
+    roles.sort();
+    roles.dedup();
+
+    Ok(RoleContext { session, roles })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scope_roles() {
+        let scope = serde_json::json!({"role": "admin", "roles": ["auditor"]});
+        let roles = scope_roles(&scope);
+        assert!(roles.contains(&"admin".to_string()));
+        assert!(roles.contains(&"auditor".to_string()));
+        assert!(scope_roles(&serde_json::json!({})).is_empty());
+    }
+}
@@ -8,7 +8,7 @@ use axum::{
 use tracing::warn;
 
 use crate::auth::session::{Session, SessionFlavor};
-use crate::auth::session_db;
+use crate::auth::session_policy;
 use crate::id_routes::IdState;
 
 pub async fn require_stepup(
@@ -19,7 +19,7 @@ pub async fn require_stepup(
     let start = std::time::Instant::now();
 
     let token = extract_token(req.headers())
-        .or_else(|| extract_cookie(&req, "session"))
+        .or_else(|| extract_cookie(req.headers(), "session"))
         .ok_or_else(|| {
             warn!(
                 decision = "reject",
@@ -29,28 +29,7 @@ pub async fn require_stepup(
             (StatusCode::UNAUTHORIZED, "missing session".to_string())
         })?;
 
-    let sess = session_db::get_valid(&state.pool, &token)
-        .await
-        .map_err(|e| {
-            warn!(
-                decision = "reject",
-                error_code = "db_error",
-                error = %e,
-                latency_ms = start.elapsed().as_millis() as u64
-            );
-            (StatusCode::INTERNAL_SERVER_ERROR, "db error".to_string())
-        })?
-        .ok_or_else(|| {
-            warn!(
-                decision = "reject",
-                error_code = "invalid_or_expired_session",
-                latency_ms = start.elapsed().as_millis() as u64
-            );
-            (
-                StatusCode::UNAUTHORIZED,
-                "invalid or expired session".to_string(),
-            )
-        })?;
+    let sess = session_policy::authenticate(&state.pool, &token).await?;
 
     // Precisa ser step-up + admin
     let is_stepup = matches!(sess.flavor, SessionFlavor::StepUp);
@@ -77,13 +56,13 @@ pub async fn require_stepup(
     Ok(next.run(req).await)
 }
 
-fn extract_token(headers: &axum::http::HeaderMap) -> Option<String> {
+pub(crate) fn extract_token(headers: &axum::http::HeaderMap) -> Option<String> {
     let auth = headers.get(axum::http::header::AUTHORIZATION)?.to_str().ok()?;
     auth.strip_prefix("Bearer ").map(|s| s.to_string())
 }
 
-fn extract_cookie(req: &Request<Body>, name: &str) -> Option<String> {
-    req.headers()
+pub(crate) fn extract_cookie(headers: &axum::http::HeaderMap, name: &str) -> Option<String> {
+    headers
         .get("cookie")?
         .to_str()
         .ok()?
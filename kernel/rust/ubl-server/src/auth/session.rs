@@ -5,7 +5,7 @@ use uuid::Uuid;
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Session {
     pub token: String,
-    pub sid: Uuid,
+    pub sid: String,
     pub flavor: SessionFlavor,
     pub scope: serde_json::Value,
     pub exp_unix: i64,
@@ -20,7 +20,7 @@ pub enum SessionFlavor {
 }
 
 impl Session {
-    pub fn new_regular(sid: Uuid) -> Self {
+    pub fn new_regular(sid: String) -> Self {
         let exp = OffsetDateTime::now_utc() + Duration::hours(1);
         Self {
             token: Uuid::new_v4().to_string(),
@@ -31,7 +31,7 @@ impl Session {
         }
     }
 
-    pub fn new_stepup(sid: Uuid) -> Self {
+    pub fn new_stepup(sid: String) -> Self {
         let exp = OffsetDateTime::now_utc() + Duration::minutes(10);
         Self {
             token: Uuid::new_v4().to_string(),
@@ -1,51 +1,135 @@
 use sqlx::PgPool;
-use uuid::Uuid;
 use crate::auth::session::{Session, SessionFlavor};
+use crate::auth::session_policy::{SessionPolicy, SessionTimes};
 
+/// Stored session with the policy that governs it
+pub struct ActiveSession {
+    pub session: Session,
+    pub times: SessionTimes,
+    pub policy: SessionPolicy,
+}
+
+/// Insert a session under its subject's policy: expiry is clamped to the
+/// absolute lifetime and the oldest sessions beyond the concurrent cap are evicted
 pub async fn insert(pool: &PgPool, s: &Session) -> sqlx::Result<()> {
-    let sid_str = s.sid.to_string();
+    let policy = policy_for(pool, &s.sid, s.flavor).await?;
+    let now = time::OffsetDateTime::now_utc().unix_timestamp();
+    let exp_unix = s.exp_unix.min(now + policy.absolute_secs);
+
     sqlx::query!(
         r#"INSERT INTO id_session (token, sid, flavor, scope, exp_unix)
            VALUES ($1, $2, $3, $4, $5)
-           ON CONFLICT (token) DO UPDATE 
-           SET sid=$2, flavor=$3, scope=$4, exp_unix=$5"#,
+           ON CONFLICT (token) DO UPDATE
+           SET sid=$2, flavor=$3, scope=$4, exp_unix=$5, last_seen_at=now(), reauth_required=false"#,
         s.token,
-        sid_str,
+        s.sid,
         flavor_str(s.flavor),
         s.scope,
-        s.exp_unix
+        exp_unix
+    )
+    .execute(pool)
+    .await?;
+
+    sqlx::query!(
+        r#"DELETE FROM id_session WHERE token IN (
+             SELECT token FROM id_session
+             WHERE sid = $1 AND flavor = $2
+             ORDER BY last_seen_at DESC, created_at DESC
+             OFFSET $3)"#,
+        s.sid,
+        flavor_str(s.flavor),
+        policy.max_concurrent
     )
     .execute(pool)
     .await?;
     Ok(())
 }
 
-pub async fn get_valid(pool: &PgPool, token: &str) -> sqlx::Result<Option<Session>> {
+/// Unexpired session with its activity timestamps and policy
+pub async fn load_active(pool: &PgPool, token: &str) -> sqlx::Result<Option<ActiveSession>> {
     let r = sqlx::query!(
-        r#"SELECT token, sid, flavor, scope, exp_unix
-           FROM id_session 
-           WHERE token = $1 
-             AND exp_unix > EXTRACT(EPOCH FROM now())"#,
+        r#"SELECT s.token, s.sid, s.flavor, s.scope, s.exp_unix,
+                  EXTRACT(EPOCH FROM s.created_at)::bigint AS "created_unix!",
+                  EXTRACT(EPOCH FROM s.last_seen_at)::bigint AS "last_seen_unix!",
+                  s.reauth_required, subj.kind,
+                  p.absolute_secs AS "absolute_secs?", p.idle_secs AS "idle_secs?",
+                  p.max_concurrent AS "max_concurrent?", p.reauth_at_risk AS "reauth_at_risk?"
+           FROM id_session s
+           JOIN id_subject subj ON subj.sid = s.sid
+           LEFT JOIN id_session_policy p ON p.subject_kind = subj.kind AND p.tier = s.flavor
+           WHERE s.token = $1
+             AND s.exp_unix > EXTRACT(EPOCH FROM now())"#,
         token
     )
     .fetch_optional(pool)
     .await?;
 
     Ok(r.and_then(|x| {
-        let sid = Uuid::parse_str(&x.sid).ok()?;
-        Some(Session {
-            token: x.token,
-            sid,
-            flavor: match x.flavor.as_str() {
-                "stepup" => SessionFlavor::StepUp,
-                _ => SessionFlavor::Regular,
+        let flavor = parse_flavor(&x.flavor);
+        let policy = stored_policy(
+            &x.kind,
+            flavor,
+            (x.absolute_secs, x.idle_secs, x.max_concurrent, x.reauth_at_risk),
+        );
+        Some(ActiveSession {
+            session: Session {
+                token: x.token,
+                sid: x.sid,
+                flavor,
+                scope: x.scope,
+                exp_unix: x.exp_unix?,
             },
-            scope: x.scope,
-            exp_unix: x.exp_unix?,
+            times: SessionTimes {
+                created_unix: x.created_unix,
+                last_seen_unix: x.last_seen_unix,
+                reauth_required: x.reauth_required,
+            },
+            policy,
         })
     }))
 }
 
+/// Policy for a subject's sessions of one tier
+pub async fn policy_for(pool: &PgPool, sid: &str, flavor: SessionFlavor) -> sqlx::Result<SessionPolicy> {
+    let r = sqlx::query!(
+        r#"SELECT subj.kind,
+                  p.absolute_secs AS "absolute_secs?", p.idle_secs AS "idle_secs?",
+                  p.max_concurrent AS "max_concurrent?", p.reauth_at_risk AS "reauth_at_risk?"
+           FROM id_subject subj
+           LEFT JOIN id_session_policy p ON p.subject_kind = subj.kind AND p.tier = $2
+           WHERE subj.sid = $1"#,
+        sid,
+        flavor_str(flavor)
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(match r {
+        Some(x) => stored_policy(
+            &x.kind,
+            flavor,
+            (x.absolute_secs, x.idle_secs, x.max_concurrent, x.reauth_at_risk),
+        ),
+        None => SessionPolicy::builtin("person", flavor),
+    })
+}
+
+/// Record activity for idle-timeout tracking
+pub async fn touch(pool: &PgPool, token: &str) -> sqlx::Result<()> {
+    sqlx::query!("UPDATE id_session SET last_seen_at = now() WHERE token = $1", token)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Force the session to re-authenticate before its next use
+pub async fn require_reauth(pool: &PgPool, token: &str) -> sqlx::Result<()> {
+    sqlx::query!("UPDATE id_session SET reauth_required = true WHERE token = $1", token)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
 pub async fn delete(pool: &PgPool, token: &str) -> sqlx::Result<()> {
     sqlx::query!("DELETE FROM id_session WHERE token = $1", token)
         .execute(pool)
@@ -53,6 +137,28 @@ pub async fn delete(pool: &PgPool, token: &str) -> sqlx::Result<()> {
     Ok(())
 }
 
+type PolicyRow = (Option<i64>, Option<i64>, Option<i32>, Option<i16>);
+
+/// Policy row from `id_session_policy`, or the built-in default when absent
+fn stored_policy(kind: &str, flavor: SessionFlavor, row: PolicyRow) -> SessionPolicy {
+    match row {
+        (Some(absolute_secs), Some(idle_secs), Some(max_concurrent), Some(reauth_at_risk)) => SessionPolicy {
+            absolute_secs,
+            idle_secs,
+            max_concurrent: max_concurrent as i64,
+            reauth_at_risk: reauth_at_risk as u8,
+        },
+        _ => SessionPolicy::builtin(kind, flavor),
+    }
+}
+
+fn parse_flavor(s: &str) -> SessionFlavor {
+    match s {
+        "stepup" => SessionFlavor::StepUp,
+        _ => SessionFlavor::Regular,
+    }
+}
+
 fn flavor_str(f: SessionFlavor) -> &'static str {
     match f {
         SessionFlavor::StepUp => "stepup",
@@ -0,0 +1,162 @@
+//! Risk-tiered session policies.
+//!
+//! Every authenticated request goes through [`authenticate`], which applies
+//! the policy for the subject's kind (person / llm / app) and the session's
+//! tier (regular / step-up): absolute lifetime from creation, idle timeout
+//! since last activity, and the forced re-auth flag set by [`after_action`]
+//! once an action at or above the policy's risk threshold has run. The
+//! concurrent-session cap is applied when a session is created.
+//!
+//! Policies live in `id_session_policy` (sql/027_session_policy.sql); the
+//! built-in defaults below cover kinds or tiers with no row.
+
+use axum::http::StatusCode;
+use sqlx::PgPool;
+use time::OffsetDateTime;
+use tracing::{info, warn};
+
+use crate::auth::session::{Session, SessionFlavor};
+use crate::auth::session_db;
+
+/// Risk level of admin writes (policy changes, bulk identity import)
+pub const RISK_L4: u8 = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SessionPolicy {
+    /// Maximum age from creation, regardless of activity
+    pub absolute_secs: i64,
+    /// Maximum gap between two authenticated requests
+    pub idle_secs: i64,
+    /// Live sessions per subject and tier; the oldest are evicted beyond it
+    pub max_concurrent: i64,
+    /// Actions at or above this risk level force re-authentication
+    pub reauth_at_risk: u8,
+}
+
+impl SessionPolicy {
+    /// Defaults used when `id_session_policy` has no row
+    pub fn builtin(subject_kind: &str, flavor: SessionFlavor) -> Self {
+        let (absolute_secs, idle_secs, max_concurrent) = match (subject_kind, flavor) {
+            (_, SessionFlavor::StepUp) => (600, 300, 1),
+            ("llm", _) => (3600, 1800, 20),
+            ("app", _) => (86400, 3600, 50),
+            _ => (3600, 900, 5),
+        };
+        Self {
+            absolute_secs,
+            idle_secs,
+            max_concurrent,
+            reauth_at_risk: RISK_L4,
+        }
+    }
+}
+
+/// Why a stored session is no longer usable
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionReject {
+    Expired,
+    Idle,
+    ReauthRequired,
+}
+
+impl SessionReject {
+    pub fn error_code(&self) -> &'static str {
+        match self {
+            SessionReject::Expired => "session_expired",
+            SessionReject::Idle => "session_idle",
+            SessionReject::ReauthRequired => "reauth_required",
+        }
+    }
+}
+
+/// Session timestamps as stored (unix seconds)
+#[derive(Debug, Clone, Copy)]
+pub struct SessionTimes {
+    pub created_unix: i64,
+    pub last_seen_unix: i64,
+    pub reauth_required: bool,
+}
+
+/// Apply a policy to a session at `now`
+pub fn check(policy: &SessionPolicy, times: &SessionTimes, now: i64) -> Result<(), SessionReject> {
+    if times.reauth_required {
+        return Err(SessionReject::ReauthRequired);
+    }
+    if now >= times.created_unix + policy.absolute_secs {
+        return Err(SessionReject::Expired);
+    }
+    if now >= times.last_seen_unix + policy.idle_secs {
+        return Err(SessionReject::Idle);
+    }
+    Ok(())
+}
+
+/// Resolve a session token under its policy and record the activity
+pub async fn authenticate(pool: &PgPool, token: &str) -> Result<Session, (StatusCode, String)> {
+    let active = session_db::load_active(pool, token)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or_else(|| {
+            warn!(decision = "reject", error_code = "invalid_or_expired_session");
+            (StatusCode::UNAUTHORIZED, "invalid or expired session".to_string())
+        })?;
+
+    let now = OffsetDateTime::now_utc().unix_timestamp();
+    if let Err(reject) = check(&active.policy, &active.times, now) {
+        warn!(decision = "reject", error_code = reject.error_code(), sid = %active.session.sid);
+        if reject != SessionReject::ReauthRequired {
+            let _ = session_db::delete(pool, token).await;
+        }
+        let msg = match reject {
+            SessionReject::ReauthRequired => "re-authentication required",
+            SessionReject::Expired => "session expired",
+            SessionReject::Idle => "session idle timeout",
+        };
+        return Err((StatusCode::UNAUTHORIZED, msg.to_string()));
+    }
+
+    session_db::touch(pool, token)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok(active.session)
+}
+
+/// Record that `session` performed an action at `risk` level; at or above
+/// the policy threshold the session must re-authenticate before its next use.
+/// The action has already happened, so a storage error is logged, not returned.
+pub async fn after_action(pool: &PgPool, session: &Session, risk: u8) {
+    let flagged = async {
+        let policy = session_db::policy_for(pool, &session.sid, session.flavor).await?;
+        if risk >= policy.reauth_at_risk {
+            session_db::require_reauth(pool, &session.token).await?;
+            return Ok::<_, sqlx::Error>(true);
+        }
+        Ok(false)
+    };
+    match flagged.await {
+        Ok(true) => info!("🔒 SESSION re-auth required sid={} risk=L{}", session.sid, risk),
+        Ok(false) => {}
+        Err(e) => warn!(sid = %session.sid, error = %e, "session re-auth flag not stored"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_lifetimes() {
+        let policy = SessionPolicy::builtin("person", SessionFlavor::Regular);
+        let times = SessionTimes { created_unix: 0, last_seen_unix: 0, reauth_required: false };
+        assert_eq!(check(&policy, &times, 10), Ok(()));
+        assert_eq!(check(&policy, &times, 900), Err(SessionReject::Idle));
+
+        // Activity keeps it alive until the absolute lifetime
+        let active = SessionTimes { last_seen_unix: 3500, ..times };
+        assert_eq!(check(&policy, &active, 3599), Ok(()));
+        assert_eq!(check(&policy, &active, 3600), Err(SessionReject::Expired));
+
+        let flagged = SessionTimes { reauth_required: true, ..times };
+        assert_eq!(check(&policy, &flagged, 10), Err(SessionReject::ReauthRequired));
+    }
+}
@@ -0,0 +1,97 @@
+//! # Autoscaling signals
+//!
+//! - GET /autoscale/signals  ledger load for Kubernetes HPA (external metrics)
+//!   or KEDA's `metrics-api` scaler, e.g. `valueLocation: db_pool.pressure`
+//!
+//! Every value is a plain number at a fixed path, read when the request
+//! arrives. `queues` holds work waiting on this deployment: Evolution
+//! proposals pending approval. `db_pool` is the primary's connection pool;
+//! `pressure` is connections in use over the pool maximum and nears 1.0 once
+//! commits start to wait for a connection. `replica_pool` is the same for
+//! the read replica, when one is configured. `sse_clients` counts open
+//! streams; the same counts are exported as the `ubl_sse_clients` gauge.
+//! `admission` is the load commits are shed on (`admission.rs`): commits in
+//! flight, probed database latency, and each over its limit.
+//!
+//! Like `/metrics`, the endpoint is unauthenticated so scalers can poll it
+//! without a session.
+
+use axum::{extract::State, http::StatusCode, routing::get, Json, Router};
+use serde::Serialize;
+use sqlx::PgPool;
+
+use crate::admission::Load;
+use crate::evolution_db;
+use crate::metrics::SSE_CLIENTS;
+use crate::AppState;
+
+#[derive(Debug, Serialize)]
+pub struct Signals {
+    pub queues: Queues,
+    pub db_pool: PoolSignals,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replica_pool: Option<PoolSignals>,
+    pub sse_clients: SseClients,
+    pub admission: Load,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Queues {
+    pub evolution_pending: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PoolSignals {
+    pub max: u32,
+    pub open: u32,
+    pub idle: u32,
+    pub in_use: u32,
+    pub pressure: f64,
+}
+
+impl PoolSignals {
+    pub(crate) fn of(pool: &PgPool) -> Self {
+        let max = pool.options().get_max_connections();
+        let open = pool.size();
+        let idle = pool.num_idle() as u32;
+        let in_use = open.saturating_sub(idle);
+        Self {
+            max,
+            open,
+            idle,
+            in_use,
+            pressure: if max == 0 { 0.0 } else { in_use as f64 / max as f64 },
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SseClients {
+    pub tail: i64,
+    pub heads: i64,
+    pub total: i64,
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new().route("/autoscale/signals", get(route_signals))
+}
+
+/// GET /autoscale/signals
+async fn route_signals(State(state): State<AppState>) -> Result<Json<Signals>, (StatusCode, String)> {
+    let evolution_pending = evolution_db::pending_count(&state.pool)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let tail = SSE_CLIENTS.with_label_values(&["tail"]).get();
+    let heads = SSE_CLIENTS.with_label_values(&["heads"]).get();
+    Ok(Json(Signals {
+        queues: Queues { evolution_pending },
+        db_pool: PoolSignals::of(&state.pool),
+        replica_pool: state.replica.as_ref().map(|r| PoolSignals::of(&r.pool)),
+        sse_clients: SseClients {
+            tail,
+            heads,
+            total: tail + heads,
+        },
+        admission: state.admission.load(),
+    }))
+}
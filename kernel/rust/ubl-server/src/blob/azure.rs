@@ -0,0 +1,117 @@
+//! Azure Blob Storage backend over the `az storage blob` CLI
+//!
+//! Objects are block blobs in container `UBL_BLOB_BUCKET` of account
+//! `UBL_BLOB_AZURE_ACCOUNT`, authenticated with `--auth-mode login`. The CLI
+//! uploads large payloads as blocks on its own. Encryption at rest is always
+//! Microsoft-managed unless `kms` names an encryption scope; customer-provided
+//! keys are not supported by the CLI. The BLAKE3 travels as metadata `ubl_blake3`.
+
+use super::cli::{object_name, run_cli, TempFile};
+use super::{digest, BlobBackend, BlobError, BlobInfo, Encryption, PutOptions, Result};
+
+const CLI: &str = "az";
+
+pub struct AzureBackend {
+    account: String,
+    container: String,
+    prefix: String,
+}
+
+fn missing(stderr: &str) -> bool {
+    stderr.contains("BlobNotFound") || stderr.contains("ResourceNotFound") || stderr.contains("does not exist")
+}
+
+impl AzureBackend {
+    pub fn new(account: String, container: String, prefix: String) -> Self {
+        Self { account, container, prefix }
+    }
+
+    fn base(&self, op: &str, key: &str) -> Vec<String> {
+        vec![
+            "storage".into(),
+            "blob".into(),
+            op.into(),
+            "--account-name".into(),
+            self.account.clone(),
+            "--container-name".into(),
+            self.container.clone(),
+            "--name".into(),
+            object_name(&self.prefix, key),
+            "--auth-mode".into(),
+            "login".into(),
+        ]
+    }
+
+    fn enc_args(encryption: &Encryption) -> Result<Vec<String>> {
+        match encryption {
+            Encryption::None | Encryption::Managed => Ok(vec![]),
+            Encryption::Kms(scope) => Ok(vec!["--encryption-scope".into(), scope.clone()]),
+            Encryption::Customer(_) => Err(BlobError::Unsupported(
+                "azure backend does not support customer-provided keys; use an encryption scope".into(),
+            )),
+        }
+    }
+
+    fn run(&self, args: Vec<String>, key: &str) -> Result<Vec<u8>> {
+        run_cli(self.name(), CLI, &args, key, missing)
+    }
+}
+
+impl BlobBackend for AzureBackend {
+    fn name(&self) -> &'static str {
+        "azure"
+    }
+
+    fn put(&self, key: &str, data: &[u8], opts: &PutOptions) -> Result<BlobInfo> {
+        let blake3 = digest(data);
+        let body = TempFile::with(data)?;
+        let mut args = self.base("upload", key);
+        args.extend([
+            "--file".into(),
+            body.arg(),
+            "--content-type".into(),
+            opts.content_type.into(),
+            "--metadata".into(),
+            format!("ubl_blake3={}", blake3),
+            "--overwrite".into(),
+        ]);
+        args.extend(Self::enc_args(&opts.encryption)?);
+        self.run(args, key)?;
+        Ok(BlobInfo {
+            key: key.to_string(),
+            size: data.len() as u64,
+            blake3,
+            parts: 1,
+        })
+    }
+
+    fn get(&self, key: &str) -> Result<Vec<u8>> {
+        let out = TempFile::empty();
+        let mut args = self.base("download", key);
+        args.extend(["--file".into(), out.arg(), "--no-progress".into()]);
+        self.run(args, key)?;
+        std::fs::read(out.path()).map_err(BlobError::from)
+    }
+
+    fn delete(&self, key: &str) -> Result<()> {
+        match self.run(self.base("delete", key), key) {
+            Err(BlobError::NotFound(_)) => Ok(()),
+            other => other.map(|_| ()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encryption_modes() {
+        assert!(AzureBackend::enc_args(&Encryption::Managed).unwrap().is_empty());
+        assert_eq!(
+            AzureBackend::enc_args(&Encryption::Kms("scope1".into())).unwrap(),
+            vec!["--encryption-scope".to_string(), "scope1".to_string()]
+        );
+        assert!(AzureBackend::enc_args(&Encryption::Customer("k".into())).is_err());
+    }
+}
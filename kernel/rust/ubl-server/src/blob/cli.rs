@@ -0,0 +1,122 @@
+//! Helpers shared by the CLI-driven cloud backends
+
+use std::io::Write;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use super::{BlobError, Result};
+
+/// Byte ranges of the parts of a `len`-byte upload (one range if it fits in a part)
+#[allow(clippy::single_range_in_vec_init)]
+pub fn part_ranges(len: usize, part_size: usize) -> Vec<Range<usize>> {
+    let part_size = part_size.max(1);
+    if len <= part_size {
+        return vec![0..len];
+    }
+    (0..len)
+        .step_by(part_size)
+        .map(|start| start..(start + part_size).min(len))
+        .collect()
+}
+
+/// Join an optional prefix and a key
+pub fn object_name(prefix: &str, key: &str) -> String {
+    let prefix = prefix.trim_matches('/');
+    let key = key.trim_start_matches('/');
+    if prefix.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}/{}", prefix, key)
+    }
+}
+
+/// Scratch file removed on drop, for CLIs that read or write paths; also how
+/// customer keys reach a CLI, since arguments are readable by any local user
+/// (`ps`, `/proc/<pid>/cmdline`)
+pub struct TempFile(PathBuf);
+
+impl TempFile {
+    pub fn empty() -> Self {
+        Self(std::env::temp_dir().join(format!("ubl-blob-{}", uuid::Uuid::new_v4())))
+    }
+
+    /// A new file holding `data`, readable by the server's user only
+    pub fn with(data: &[u8]) -> Result<Self> {
+        let f = Self::empty();
+        let mut options = std::fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        options.open(&f.0)?.write_all(data)?;
+        Ok(f)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+
+    pub fn arg(&self) -> String {
+        self.0.display().to_string()
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+/// Run a CLI and return its stdout; `missing` tells whether stderr means "no such object"
+pub fn run_cli(
+    backend: &'static str,
+    program: &str,
+    args: &[String],
+    key: &str,
+    missing: fn(&str) -> bool,
+) -> Result<Vec<u8>> {
+    let out = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| BlobError::Backend {
+            backend,
+            message: format!("{} not available: {}", program, e),
+        })?;
+    if out.status.success() {
+        return Ok(out.stdout);
+    }
+    let stderr = String::from_utf8_lossy(&out.stderr).trim().to_string();
+    if missing(&stderr) {
+        return Err(BlobError::NotFound(key.to_string()));
+    }
+    Err(BlobError::Backend { backend, message: stderr })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_part_ranges() {
+        assert_eq!(part_ranges(0, 4), vec![0..0]);
+        assert_eq!(part_ranges(4, 4), vec![0..4]);
+        assert_eq!(part_ranges(10, 4), vec![0..4, 4..8, 8..10]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_temp_file_owner_only() {
+        use std::os::unix::fs::PermissionsExt;
+        let f = TempFile::with(b"key").unwrap();
+        let mode = std::fs::metadata(f.path()).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        assert_eq!(std::fs::read(f.path()).unwrap(), b"key");
+    }
+
+    #[test]
+    fn test_object_name() {
+        assert_eq!(object_name("", "a/b"), "a/b");
+        assert_eq!(object_name("/ubl/", "/a/b"), "ubl/a/b");
+    }
+}
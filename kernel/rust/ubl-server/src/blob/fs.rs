@@ -0,0 +1,105 @@
+//! Local filesystem backend (default; development and single-node deployments)
+//!
+//! Objects are files under the root; the BLAKE3 recorded at write time sits
+//! next to each one as `<name>.blake3`. Server-side encryption does not apply.
+
+use std::path::{Component, Path, PathBuf};
+
+use super::{digest, BlobBackend, BlobError, BlobInfo, Encryption, PutOptions, Result};
+
+pub struct FsBackend {
+    root: PathBuf,
+}
+
+impl FsBackend {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Path of `key`, refusing keys that would leave the root
+    fn path(&self, key: &str) -> Result<PathBuf> {
+        let rel = Path::new(key.trim_start_matches('/'));
+        if rel.components().any(|c| !matches!(c, Component::Normal(_))) {
+            return Err(BlobError::Config(format!("invalid blob key: {}", key)));
+        }
+        Ok(self.root.join(rel))
+    }
+}
+
+/// `path` with `suffix` appended to its file name
+fn sidecar(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+impl BlobBackend for FsBackend {
+    fn name(&self) -> &'static str {
+        "fs"
+    }
+
+    fn put(&self, key: &str, data: &[u8], opts: &PutOptions) -> Result<BlobInfo> {
+        if opts.encryption != Encryption::None {
+            return Err(BlobError::Unsupported("fs backend has no server-side encryption".into()));
+        }
+        let path = self.path(key)?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        // Write then rename so readers never see a partial object
+        let tmp = sidecar(&path, ".partial");
+        std::fs::write(&tmp, data)?;
+        std::fs::rename(&tmp, &path)?;
+        let blake3 = digest(data);
+        std::fs::write(sidecar(&path, ".blake3"), &blake3)?;
+        Ok(BlobInfo {
+            key: key.to_string(),
+            size: data.len() as u64,
+            blake3,
+            parts: 1,
+        })
+    }
+
+    fn get(&self, key: &str) -> Result<Vec<u8>> {
+        match std::fs::read(self.path(key)?) {
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Err(BlobError::NotFound(key.to_string())),
+            other => Ok(other?),
+        }
+    }
+
+    fn delete(&self, key: &str) -> Result<()> {
+        let path = self.path(key)?;
+        for p in [sidecar(&path, ".blake3"), path] {
+            match std::fs::remove_file(&p) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip_and_integrity() {
+        let root = std::env::temp_dir().join(format!("ubl-blob-test-{}", uuid::Uuid::new_v4()));
+        let fs = FsBackend::new(&root);
+
+        let info = fs.put("ledger/C.Bank/seg.jsonl", b"entries", &PutOptions::default()).unwrap();
+        assert_eq!(fs.get_verified(&info.key, &info.blake3).unwrap(), b"entries");
+
+        std::fs::write(root.join("ledger/C.Bank/seg.jsonl"), b"tampered").unwrap();
+        assert!(matches!(
+            fs.get_verified(&info.key, &info.blake3),
+            Err(BlobError::Integrity { .. })
+        ));
+
+        fs.delete(&info.key).unwrap();
+        assert!(matches!(fs.get(&info.key), Err(BlobError::NotFound(_))));
+        assert!(fs.put("../escape", b"x", &PutOptions::default()).is_err());
+        let _ = std::fs::remove_dir_all(root);
+    }
+}
@@ -0,0 +1,182 @@
+//! Google Cloud Storage backend over the `gcloud storage` CLI
+//!
+//! Payloads over the part size are uploaded as `<object>.part-NNNN` objects
+//! and composed (32 sources per compose call), then the parts are removed.
+//! The BLAKE3 travels as custom metadata `ubl-blake3`. A CSEK reaches the CLI
+//! in a `--flags-file` scratch file, never as an argument.
+
+use super::cli::{object_name, part_ranges, run_cli, TempFile};
+use super::{digest, BlobBackend, BlobError, BlobInfo, Encryption, PutOptions, Result};
+
+const CLI: &str = "gcloud";
+/// Sources per `objects compose` call
+const COMPOSE_MAX: usize = 32;
+
+pub struct GcsBackend {
+    bucket: String,
+    prefix: String,
+    /// CSEK, required on every read of objects written with it
+    customer_key: Option<String>,
+}
+
+fn missing(stderr: &str) -> bool {
+    stderr.contains("No URLs matched") || stderr.contains("NotFound") || stderr.contains("404")
+}
+
+impl GcsBackend {
+    pub fn new(bucket: String, prefix: String, customer_key: Option<String>) -> Self {
+        Self { bucket, prefix, customer_key }
+    }
+
+    fn url(&self, key: &str) -> String {
+        format!("gs://{}/{}", self.bucket, object_name(&self.prefix, key))
+    }
+
+    /// Encryption flags, and the flags file holding a CSEK (keep it until
+    /// the CLI has run)
+    fn enc_args(encryption: &Encryption) -> Result<(Vec<String>, Option<TempFile>)> {
+        match encryption {
+            // Google-managed keys are the default
+            Encryption::None | Encryption::Managed => Ok((vec![], None)),
+            // A KMS key resource name is no secret
+            Encryption::Kms(key) => Ok((vec![format!("--encryption-key={}", key)], None)),
+            // A base64 CSEK is
+            Encryption::Customer(key) => Self::flags_file("--encryption-key", key),
+        }
+    }
+
+    /// `--flags-file` argument naming a scratch file that sets `flag` to `value`
+    fn flags_file(flag: &str, value: &str) -> Result<(Vec<String>, Option<TempFile>)> {
+        let yaml = format!("{}: {}\n", flag, serde_json::Value::from(value));
+        let file = TempFile::with(yaml.as_bytes())?;
+        Ok((vec![format!("--flags-file={}", file.arg())], Some(file)))
+    }
+
+    fn run(&self, args: Vec<String>, key: &str) -> Result<Vec<u8>> {
+        run_cli(self.name(), CLI, &args, key, missing)
+    }
+
+    fn upload(&self, data: &[u8], url: &str, key: &str, opts: &PutOptions) -> Result<()> {
+        let body = TempFile::with(data)?;
+        let mut args = vec![
+            "storage".into(),
+            "cp".into(),
+            body.arg(),
+            url.to_string(),
+            format!("--content-type={}", opts.content_type),
+        ];
+        let (enc, _key_file) = Self::enc_args(&opts.encryption)?;
+        args.extend(enc);
+        self.run(args, key).map(|_| ())
+    }
+
+    fn compose(&self, key: &str, data: &[u8], opts: &PutOptions) -> Result<usize> {
+        let target = self.url(key);
+        let parts: Vec<String> = part_ranges(data.len(), opts.part_size)
+            .into_iter()
+            .enumerate()
+            .map(|(i, range)| {
+                let url = format!("{}.part-{:04}", target, i + 1);
+                self.upload(&data[range], &url, key, opts).map(|_| url)
+            })
+            .collect::<Result<_>>()?;
+
+        // Fold the parts into the target, 32 sources at a time
+        let mut composed = false;
+        let mut rest = parts.as_slice();
+        while !rest.is_empty() {
+            let take = if composed { COMPOSE_MAX - 1 } else { COMPOSE_MAX }.min(rest.len());
+            let mut args: Vec<String> = vec!["storage".into(), "objects".into(), "compose".into()];
+            if composed {
+                args.push(target.clone());
+            }
+            args.extend(rest[..take].iter().cloned());
+            args.push(target.clone());
+            let (enc, _key_file) = Self::enc_args(&opts.encryption)?;
+            args.extend(enc);
+            self.run(args, key)?;
+            composed = true;
+            rest = &rest[take..];
+        }
+
+        let mut rm: Vec<String> = vec!["storage".into(), "rm".into()];
+        rm.extend(parts.iter().cloned());
+        if let Err(e) = self.run(rm, key) {
+            tracing::warn!(key = %key, error = %e, "gcs: leftover upload parts");
+        }
+        Ok(parts.len())
+    }
+}
+
+impl BlobBackend for GcsBackend {
+    fn name(&self) -> &'static str {
+        "gcs"
+    }
+
+    fn put(&self, key: &str, data: &[u8], opts: &PutOptions) -> Result<BlobInfo> {
+        let blake3 = digest(data);
+        let parts = if data.len() > opts.part_size {
+            self.compose(key, data, opts)?
+        } else {
+            self.upload(data, &self.url(key), key, opts)?;
+            1
+        };
+        self.run(
+            vec![
+                "storage".into(),
+                "objects".into(),
+                "update".into(),
+                self.url(key),
+                format!("--update-custom-metadata=ubl-blake3={}", blake3),
+            ],
+            key,
+        )?;
+        Ok(BlobInfo {
+            key: key.to_string(),
+            size: data.len() as u64,
+            blake3,
+            parts,
+        })
+    }
+
+    fn get(&self, key: &str) -> Result<Vec<u8>> {
+        let (dec, _key_file) = match &self.customer_key {
+            Some(k) => Self::flags_file("--decryption-keys", k)?,
+            None => (vec![], None),
+        };
+        let mut args = vec!["storage".into(), "cat".into(), self.url(key)];
+        args.extend(dec);
+        self.run(args, key)
+    }
+
+    fn delete(&self, key: &str) -> Result<()> {
+        match self.run(vec!["storage".into(), "rm".into(), self.url(key)], key) {
+            Err(BlobError::NotFound(_)) => Ok(()),
+            other => other.map(|_| ()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_urls_and_encryption() {
+        let gcs = GcsBackend::new("ubl".into(), "archive".into(), None);
+        assert_eq!(gcs.url("ledger/a"), "gs://ubl/archive/ledger/a");
+        assert!(GcsBackend::enc_args(&Encryption::Managed).unwrap().0.is_empty());
+        assert_eq!(
+            GcsBackend::enc_args(&Encryption::Kms("projects/p/keys/k".into())).unwrap().0,
+            vec!["--encryption-key=projects/p/keys/k".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_customer_key_stays_off_the_command_line() {
+        let (args, key_file) = GcsBackend::enc_args(&Encryption::Customer("c2VjcmV0".into())).unwrap();
+        let key_file = key_file.unwrap();
+        assert_eq!(args, vec![format!("--flags-file={}", key_file.arg())]);
+        assert_eq!(std::fs::read_to_string(key_file.path()).unwrap(), "--encryption-key: \"c2VjcmV0\"\n");
+    }
+}
@@ -0,0 +1,220 @@
+//! # Blob storage for archives and snapshots
+//!
+//! [`BlobBackend`] is the storage seam behind ledger archive segments
+//! (`archive_routes.rs`). Backends are selected with `UBL_BLOB_BACKEND`:
+//!
+//! - `fs` (default): files under `UBL_BLOB_FS_ROOT` (default `./var/blobs`)
+//! - `s3`: the `aws` CLI
+//! - `gcs`: the `gcloud storage` CLI
+//! - `azure`: the `az storage blob` CLI
+//!
+//! Like `repo_routes` with `mc`, cloud backends drive the vendor CLI, so
+//! credentials come from the CLI's own configuration and no SDK is linked;
+//! a backend whose CLI is missing fails on first use. Objects go to
+//! `UBL_BLOB_BUCKET` (container name on Azure) under `UBL_BLOB_PREFIX`.
+//!
+//! Every object is stored with its BLAKE3 and read back through
+//! [`BlobBackend::get_verified`]. Payloads over `UBL_BLOB_PART_SIZE` bytes
+//! (default 64 MiB) are uploaded in parts. `UBL_BLOB_SSE` picks server-side
+//! encryption: `managed`, `kms` (key `UBL_BLOB_KMS_KEY`), or `customer`
+//! (base64 key `UBL_BLOB_CUSTOMER_KEY`); backends reject modes they lack.
+
+mod cli;
+pub mod fs;
+pub mod s3;
+pub mod gcs;
+pub mod azure;
+
+use std::sync::Arc;
+
+use serde::Serialize;
+use thiserror::Error;
+
+/// Default multipart threshold and part size
+pub const DEFAULT_PART_SIZE: usize = 64 * 1024 * 1024;
+
+#[derive(Debug, Error)]
+pub enum BlobError {
+    #[error("blob not found: {0}")]
+    NotFound(String),
+    #[error("blob {key} failed integrity check: expected {expected}, read {got}")]
+    Integrity {
+        key: String,
+        expected: String,
+        got: String,
+    },
+    #[error("{backend} backend: {message}")]
+    Backend {
+        backend: &'static str,
+        message: String,
+    },
+    #[error("{0}")]
+    Unsupported(String),
+    #[error("blob config: {0}")]
+    Config(String),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+pub type Result<T> = std::result::Result<T, BlobError>;
+
+/// Server-side encryption requested for writes
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Encryption {
+    /// Whatever the bucket defaults to
+    #[default]
+    None,
+    /// Provider-managed keys (SSE-S3, Google/Microsoft-managed)
+    Managed,
+    /// Customer-managed KMS key (ARN, KMS resource name, or Azure encryption scope)
+    Kms(String),
+    /// Customer-supplied key, base64 (SSE-C / CSEK); needed again on read
+    Customer(String),
+}
+
+/// Options shared by every write (the fs backend only honours `encryption`)
+#[derive(Debug, Clone)]
+pub struct PutOptions {
+    pub encryption: Encryption,
+    /// Payloads larger than this are uploaded in parts of this size
+    pub part_size: usize,
+    pub content_type: &'static str,
+}
+
+impl Default for PutOptions {
+    fn default() -> Self {
+        Self {
+            encryption: Encryption::None,
+            part_size: DEFAULT_PART_SIZE,
+            content_type: "application/octet-stream",
+        }
+    }
+}
+
+/// What a write stored
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct BlobInfo {
+    pub key: String,
+    pub size: u64,
+    pub blake3: String,
+    /// Parts uploaded (1 for a single-shot upload)
+    pub parts: usize,
+}
+
+/// Object storage used for archives and snapshots
+///
+/// Keys are `/`-separated relative paths; backends prepend their prefix.
+/// Calls block (cloud backends run a CLI), so async callers go through
+/// `tokio::task::spawn_blocking`.
+pub trait BlobBackend: Send + Sync {
+    /// Backend name for logs and errors
+    fn name(&self) -> &'static str;
+
+    /// Store `data` at `key`, replacing any previous object
+    fn put(&self, key: &str, data: &[u8], opts: &PutOptions) -> Result<BlobInfo>;
+
+    /// Read the object at `key`
+    fn get(&self, key: &str) -> Result<Vec<u8>>;
+
+    /// Remove the object at `key`; missing objects are not an error
+    #[allow(dead_code)] // backend contract; nothing prunes archives yet
+    fn delete(&self, key: &str) -> Result<()>;
+
+    /// Read `key` and check it against the BLAKE3 recorded at write time
+    fn get_verified(&self, key: &str, expected_blake3: &str) -> Result<Vec<u8>> {
+        let data = self.get(key)?;
+        let got = digest(&data);
+        if !got.eq_ignore_ascii_case(expected_blake3) {
+            return Err(BlobError::Integrity {
+                key: key.to_string(),
+                expected: expected_blake3.to_string(),
+                got,
+            });
+        }
+        Ok(data)
+    }
+}
+
+/// BLAKE3 hex of a payload
+pub fn digest(data: &[u8]) -> String {
+    hex::encode(blake3::hash(data).as_bytes())
+}
+
+/// Backend plus the write options used with it
+#[derive(Clone)]
+pub struct BlobStore {
+    pub backend: Arc<dyn BlobBackend>,
+    pub options: PutOptions,
+}
+
+impl BlobStore {
+    /// Store configured by the `UBL_BLOB_*` environment
+    pub fn from_env() -> Result<Self> {
+        Ok(Self {
+            backend: backend_from_env()?,
+            options: put_options_from_env()?,
+        })
+    }
+}
+
+/// Write options from the `UBL_BLOB_*` environment
+pub fn put_options_from_env() -> Result<PutOptions> {
+    let var = |k: &str| std::env::var(k).ok().filter(|v| !v.is_empty());
+    let encryption = match var("UBL_BLOB_SSE").as_deref() {
+        None | Some("none") => Encryption::None,
+        Some("managed") => Encryption::Managed,
+        Some("kms") => Encryption::Kms(
+            var("UBL_BLOB_KMS_KEY").ok_or_else(|| BlobError::Config("UBL_BLOB_SSE=kms needs UBL_BLOB_KMS_KEY".into()))?,
+        ),
+        Some("customer") => Encryption::Customer(var("UBL_BLOB_CUSTOMER_KEY").ok_or_else(|| {
+            BlobError::Config("UBL_BLOB_SSE=customer needs UBL_BLOB_CUSTOMER_KEY".into())
+        })?),
+        Some(other) => return Err(BlobError::Config(format!("unknown UBL_BLOB_SSE: {}", other))),
+    };
+    let part_size = match var("UBL_BLOB_PART_SIZE") {
+        Some(n) => n
+            .parse()
+            .ok()
+            .filter(|&n: &usize| n > 0)
+            .ok_or_else(|| BlobError::Config(format!("invalid UBL_BLOB_PART_SIZE: {}", n)))?,
+        None => DEFAULT_PART_SIZE,
+    };
+    Ok(PutOptions {
+        encryption,
+        part_size,
+        ..PutOptions::default()
+    })
+}
+
+/// Backend from the `UBL_BLOB_*` environment
+pub fn backend_from_env() -> Result<Arc<dyn BlobBackend>> {
+    let var = |k: &str| std::env::var(k).ok().filter(|v| !v.is_empty());
+    let bucket = || var("UBL_BLOB_BUCKET").ok_or_else(|| BlobError::Config("UBL_BLOB_BUCKET is required".into()));
+    let prefix = var("UBL_BLOB_PREFIX").unwrap_or_default();
+
+    match var("UBL_BLOB_BACKEND").as_deref().unwrap_or("fs") {
+        "fs" => Ok(Arc::new(fs::FsBackend::new(
+            var("UBL_BLOB_FS_ROOT").unwrap_or_else(|| "./var/blobs".to_string()),
+        ))),
+        "s3" => Ok(Arc::new(s3::S3Backend::new(bucket()?, prefix, customer_key()))),
+        "gcs" => Ok(Arc::new(gcs::GcsBackend::new(bucket()?, prefix, customer_key()))),
+        "azure" => Ok(Arc::new(azure::AzureBackend::new(
+            var("UBL_BLOB_AZURE_ACCOUNT")
+                .ok_or_else(|| BlobError::Config("UBL_BLOB_AZURE_ACCOUNT is required".into()))?,
+            bucket()?,
+            prefix,
+        ))),
+        other => Err(BlobError::Config(format!("unknown UBL_BLOB_BACKEND: {}", other))),
+    }
+}
+
+/// Customer-supplied key, which reads need as well as writes
+fn customer_key() -> Option<String> {
+    match put_options_from_env() {
+        Ok(PutOptions {
+            encryption: Encryption::Customer(k),
+            ..
+        }) => Some(k),
+        _ => None,
+    }
+}
@@ -0,0 +1,216 @@
+//! Amazon S3 (and S3-compatible) backend over the `aws s3api` CLI
+//!
+//! Payloads over the part size use a multipart upload, aborted if any part
+//! fails. The BLAKE3 travels as `x-amz-meta-ubl-blake3`. Set
+//! `AWS_ENDPOINT_URL` for MinIO or other S3-compatible stores. An SSE-C key
+//! is handed to the CLI as `file://` of a scratch file, never as an argument.
+
+use super::cli::{object_name, part_ranges, run_cli, TempFile};
+use super::{digest, BlobBackend, BlobError, BlobInfo, Encryption, PutOptions, Result};
+
+const CLI: &str = "aws";
+
+pub struct S3Backend {
+    bucket: String,
+    prefix: String,
+    /// SSE-C key, required on every read of objects written with it
+    customer_key: Option<String>,
+}
+
+fn missing(stderr: &str) -> bool {
+    stderr.contains("NoSuchKey") || stderr.contains("Not Found") || stderr.contains("(404)")
+}
+
+impl S3Backend {
+    pub fn new(bucket: String, prefix: String, customer_key: Option<String>) -> Self {
+        Self { bucket, prefix, customer_key }
+    }
+
+    fn base(&self, op: &str, key: &str) -> Vec<String> {
+        vec![
+            "s3api".into(),
+            op.into(),
+            "--bucket".into(),
+            self.bucket.clone(),
+            "--key".into(),
+            object_name(&self.prefix, key),
+        ]
+    }
+
+    /// Encryption flags for put-object / create-multipart-upload, and the
+    /// SSE-C key file they name (keep it until the CLI has run)
+    fn sse_args(encryption: &Encryption) -> Result<(Vec<String>, Option<TempFile>)> {
+        Ok(match encryption {
+            Encryption::None => (vec![], None),
+            Encryption::Managed => (vec!["--server-side-encryption".into(), "AES256".into()], None),
+            Encryption::Kms(key) => (
+                vec![
+                    "--server-side-encryption".into(),
+                    "aws:kms".into(),
+                    "--ssekms-key-id".into(),
+                    key.clone(),
+                ],
+                None,
+            ),
+            Encryption::Customer(key) => {
+                let key_file = TempFile::with(key.as_bytes())?;
+                (Self::sse_c_args(&key_file), Some(key_file))
+            }
+        })
+    }
+
+    fn sse_c_args(key_file: &TempFile) -> Vec<String> {
+        vec![
+            "--sse-customer-algorithm".into(),
+            "AES256".into(),
+            "--sse-customer-key".into(),
+            format!("file://{}", key_file.arg()),
+        ]
+    }
+
+    fn put_args(
+        &self,
+        op: &str,
+        key: &str,
+        blake3: &str,
+        opts: &PutOptions,
+    ) -> Result<(Vec<String>, Option<TempFile>)> {
+        let mut args = self.base(op, key);
+        args.extend([
+            "--content-type".into(),
+            opts.content_type.into(),
+            "--metadata".into(),
+            format!("ubl-blake3={}", blake3),
+        ]);
+        let (sse, key_file) = Self::sse_args(&opts.encryption)?;
+        args.extend(sse);
+        Ok((args, key_file))
+    }
+
+    fn run(&self, args: &[String], key: &str) -> Result<String> {
+        let out = run_cli(self.name(), CLI, args, key, missing)?;
+        Ok(String::from_utf8_lossy(&out).trim().to_string())
+    }
+
+    fn multipart(&self, key: &str, data: &[u8], blake3: &str, opts: &PutOptions) -> Result<usize> {
+        let (mut create, key_file) = self.put_args("create-multipart-upload", key, blake3, opts)?;
+        create.extend(["--query".into(), "UploadId".into(), "--output".into(), "text".into()]);
+        let upload_id = self.run(&create, key)?;
+
+        let upload = || -> Result<usize> {
+            let ranges = part_ranges(data.len(), opts.part_size);
+            let mut parts = Vec::with_capacity(ranges.len());
+            for (i, range) in ranges.iter().enumerate() {
+                let body = TempFile::with(&data[range.clone()])?;
+                let mut args = self.base("upload-part", key);
+                args.extend([
+                    "--upload-id".into(),
+                    upload_id.clone(),
+                    "--part-number".into(),
+                    (i + 1).to_string(),
+                    "--body".into(),
+                    body.arg(),
+                    "--query".into(),
+                    "ETag".into(),
+                    "--output".into(),
+                    "text".into(),
+                ]);
+                if let Some(key_file) = &key_file {
+                    args.extend(Self::sse_c_args(key_file));
+                }
+                let etag = self.run(&args, key)?;
+                parts.push(serde_json::json!({ "ETag": etag, "PartNumber": i + 1 }));
+            }
+
+            let manifest = TempFile::with(serde_json::json!({ "Parts": parts }).to_string().as_bytes())?;
+            let mut complete = self.base("complete-multipart-upload", key);
+            complete.extend([
+                "--upload-id".into(),
+                upload_id.clone(),
+                "--multipart-upload".into(),
+                format!("file://{}", manifest.arg()),
+            ]);
+            self.run(&complete, key)?;
+            Ok(parts.len())
+        };
+
+        upload().inspect_err(|_| {
+            let mut abort = self.base("abort-multipart-upload", key);
+            abort.extend(["--upload-id".into(), upload_id.clone()]);
+            let _ = self.run(&abort, key);
+        })
+    }
+}
+
+impl BlobBackend for S3Backend {
+    fn name(&self) -> &'static str {
+        "s3"
+    }
+
+    fn put(&self, key: &str, data: &[u8], opts: &PutOptions) -> Result<BlobInfo> {
+        let blake3 = digest(data);
+        let parts = if data.len() > opts.part_size {
+            self.multipart(key, data, &blake3, opts)?
+        } else {
+            let body = TempFile::with(data)?;
+            let (mut args, _key_file) = self.put_args("put-object", key, &blake3, opts)?;
+            args.extend(["--body".into(), body.arg()]);
+            self.run(&args, key)?;
+            1
+        };
+        Ok(BlobInfo {
+            key: key.to_string(),
+            size: data.len() as u64,
+            blake3,
+            parts,
+        })
+    }
+
+    fn get(&self, key: &str) -> Result<Vec<u8>> {
+        let out = TempFile::empty();
+        let mut args = self.base("get-object", key);
+        let key_file = self.customer_key.as_deref().map(|k| TempFile::with(k.as_bytes())).transpose()?;
+        if let Some(key_file) = &key_file {
+            args.extend(Self::sse_c_args(key_file));
+        }
+        args.push(out.arg());
+        self.run(&args, key)?;
+        std::fs::read(out.path()).map_err(BlobError::from)
+    }
+
+    fn delete(&self, key: &str) -> Result<()> {
+        match self.run(&self.base("delete-object", key), key) {
+            Err(BlobError::NotFound(_)) => Ok(()),
+            other => other.map(|_| ()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_put_args() {
+        let s3 = S3Backend::new("ubl".into(), "archive".into(), None);
+        let opts = PutOptions {
+            encryption: Encryption::Kms("arn:aws:kms:k".into()),
+            ..PutOptions::default()
+        };
+        let (args, key_file) = s3.put_args("put-object", "ledger/a", "ff", &opts).unwrap();
+        let args = args.join(" ");
+        assert!(args.starts_with("s3api put-object --bucket ubl --key archive/ledger/a"));
+        assert!(args.contains("--metadata ubl-blake3=ff"));
+        assert!(args.ends_with("--server-side-encryption aws:kms --ssekms-key-id arn:aws:kms:k"));
+        assert!(key_file.is_none());
+    }
+
+    #[test]
+    fn test_customer_key_stays_off_the_command_line() {
+        let (args, key_file) = S3Backend::sse_args(&Encryption::Customer("c2VjcmV0".into())).unwrap();
+        let key_file = key_file.unwrap();
+        assert!(!args.iter().any(|a| a.contains("c2VjcmV0")));
+        assert_eq!(args.last().unwrap(), &format!("file://{}", key_file.arg()));
+        assert_eq!(std::fs::read(key_file.path()).unwrap(), b"c2VjcmV0");
+    }
+}
@@ -0,0 +1,529 @@
+//! # Break-glass overrides
+//!
+//! When a subsystem fails in a way that blocks commits (a policy timing out
+//! on every evaluation, an approval queue nobody can staff), an operator can
+//! bypass it for a bounded time. The override names the subsystem, carries a
+//! mandatory justification and a TTL, and is not in force until a second SID
+//! approves it:
+//!
+//! - POST /admin/break-glass                 (`{subsystem, justification, ttl_secs}`; admin/operator)
+//! - GET  /admin/break-glass?open=           (admin/operator/auditor)
+//! - GET  /admin/break-glass/:id             (override and the actions under it)
+//! - POST /admin/break-glass/:id/approve     (admin, not the requester)
+//! - POST /admin/break-glass/:id/close       (`{reason?}`; admin/operator)
+//! - GET  /admin/break-glass/:id/report      (available once closed)
+//!
+//! Subsystems that can be bypassed:
+//!
+//! - `policy`: the commit skips policy evaluation;
+//! - `evolution_queue`: an Evolution commit appends without waiting for
+//!   approvals (`evolution_routes.rs`).
+//!
+//! A commit opts in with `X-UBL-Break-Glass: <override_id>` from an
+//! admin/operator session. Everything else in the pipeline still applies.
+//! The entry's `metadata.break_glass` names the override and the actor, and
+//! the action is recorded in the same transaction as the append, so nothing
+//! passes untagged. TTLs run from approval, at most [`MAX_TTL_SECS`].
+//!
+//! An override closes by hand or when it lapses (a sweep every
+//! [`SWEEP_SECS`]; a request never approved lapses after its TTL too).
+//! Closing stores a report: who asked and approved, why, for how long, and
+//! every commit made under the override.
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    routing::{get, post},
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sqlx::{Postgres, Transaction};
+use time::OffsetDateTime;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::auth::rbac;
+use crate::break_glass_db::{self, Action, Override};
+use crate::db::{LedgerEntry, LinkDraft};
+use crate::{metrics, AppState};
+
+/// Header a commit names its override in
+pub const HEADER: &str = "x-ubl-break-glass";
+
+/// Longest an override stays in force
+pub const MAX_TTL_SECS: i32 = 4 * 3600;
+
+/// How often lapsed overrides are closed
+pub const SWEEP_SECS: u64 = 30;
+
+const MAX_LIST: i64 = 200;
+
+/// Subsystem an override bypasses
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Subsystem {
+    Policy,
+    EvolutionQueue,
+}
+
+impl Subsystem {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Subsystem::Policy => "policy",
+            Subsystem::EvolutionQueue => "evolution_queue",
+        }
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "policy" => Some(Subsystem::Policy),
+            "evolution_queue" => Some(Subsystem::EvolutionQueue),
+            _ => None,
+        }
+    }
+}
+
+/// An override a commit is running under
+#[derive(Debug, Clone)]
+pub struct Engaged {
+    pub override_id: Uuid,
+    pub subsystem: Subsystem,
+    /// SID of the session making the commit
+    pub actor: String,
+}
+
+impl Engaged {
+    /// Whether this override bypasses `subsystem`
+    pub fn bypasses(&self, subsystem: Subsystem) -> bool {
+        self.subsystem == subsystem
+    }
+
+    /// Tag a draft's metadata with the override
+    pub fn tag(&self, link: &mut LinkDraft) {
+        let tag = json!({
+            "override_id": self.override_id,
+            "subsystem": self.subsystem.as_str(),
+            "actor": self.actor,
+        });
+        match &mut link.metadata {
+            Some(serde_json::Value::Object(m)) => {
+                m.insert("break_glass".to_string(), tag);
+            }
+            _ => link.metadata = Some(json!({ "break_glass": tag })),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct OpenReq {
+    pub subsystem: Subsystem,
+    pub justification: String,
+    pub ttl_secs: i32,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct CloseReq {
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListQuery {
+    #[serde(default)]
+    pub open: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OverrideView {
+    #[serde(flatten)]
+    pub break_glass: Override,
+    pub actions: Vec<Action>,
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/admin/break-glass", post(route_open).get(route_list))
+        .route("/admin/break-glass/:override_id", get(route_get))
+        .route("/admin/break-glass/:override_id/approve", post(route_approve))
+        .route("/admin/break-glass/:override_id/close", post(route_close))
+        .route("/admin/break-glass/:override_id/report", get(route_report))
+}
+
+fn internal(e: sqlx::Error) -> (StatusCode, String) {
+    (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+}
+
+fn not_found() -> (StatusCode, String) {
+    (StatusCode::NOT_FOUND, "override not found".to_string())
+}
+
+/// The override named by the commit's header, if any; it must be in force
+/// and the caller an admin or operator
+pub async fn engage(state: &AppState, headers: &HeaderMap) -> Result<Option<Engaged>, (StatusCode, String)> {
+    let Some(value) = headers.get(HEADER) else {
+        return Ok(None);
+    };
+    let override_id: Uuid = value
+        .to_str()
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .ok_or_else(|| (StatusCode::BAD_REQUEST, format!("invalid {} header", HEADER)))?;
+    let caller = rbac::require_role(&state.pool, headers, &[rbac::ADMIN, rbac::OPERATOR]).await?;
+    let Some(active) = break_glass_db::active(&state.pool, override_id).await.map_err(internal)? else {
+        warn!(
+            override_id = %override_id,
+            sid = %caller.session.sid,
+            decision = "reject",
+            error_code = "break_glass_inactive"
+        );
+        return Err((StatusCode::FORBIDDEN, format!("override {} is not in force", override_id)));
+    };
+    let subsystem = Subsystem::parse(&active.subsystem).ok_or_else(|| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("override {} names unknown subsystem {}", override_id, active.subsystem),
+        )
+    })?;
+    Ok(Some(Engaged {
+        override_id,
+        subsystem,
+        actor: caller.session.sid,
+    }))
+}
+
+/// Record a commit made under an override, inside the transaction that appends it
+pub async fn record_in(
+    tx: &mut Transaction<'static, Postgres>,
+    link: &LinkDraft,
+    engaged: &Engaged,
+    entry: &LedgerEntry,
+) {
+    let detail = json!({
+        "container_id": entry.container_id,
+        "sequence": entry.sequence,
+        "entry_hash": entry.entry_hash,
+        "intent_class": link.intent_class,
+    });
+    break_glass_db::record_action(&mut **tx, engaged.override_id, &engaged.actor, "commit", &detail)
+        .await
+        .expect("record break-glass action");
+
+    metrics::BREAK_GLASS_ACTIONS.with_label_values(&[engaged.subsystem.as_str()]).inc();
+    warn!(
+        override_id = %engaged.override_id,
+        subsystem = engaged.subsystem.as_str(),
+        actor = %engaged.actor,
+        container_id = %entry.container_id,
+        sequence = entry.sequence,
+        "🧯 Commit under break-glass"
+    );
+}
+
+/// What an override was for and what was done under it
+pub fn report(
+    ov: &Override,
+    actions: &[Action],
+    closed_by: Option<&str>,
+    reason: &str,
+    closed_at: OffsetDateTime,
+) -> serde_json::Value {
+    let mut actors: BTreeMap<&str, usize> = BTreeMap::new();
+    let mut containers: BTreeMap<&str, usize> = BTreeMap::new();
+    for a in actions {
+        *actors.entry(&a.actor).or_default() += 1;
+        if let Some(c) = a.detail.get("container_id").and_then(|c| c.as_str()) {
+            *containers.entry(c).or_default() += 1;
+        }
+    }
+    let in_force_secs = ov.approved_at.map(|from| {
+        let until = ov.expires_at.map_or(closed_at, |e| e.min(closed_at));
+        (until - from).whole_seconds().max(0)
+    });
+    json!({
+        "override_id": ov.override_id,
+        "subsystem": ov.subsystem,
+        "justification": ov.justification,
+        "requested_by": ov.requested_by,
+        "requested_at": ov.requested_at.unix_timestamp(),
+        "approved_by": ov.approved_by,
+        "approved_at": ov.approved_at.map(OffsetDateTime::unix_timestamp),
+        "closed_by": closed_by,
+        "closed_at": closed_at.unix_timestamp(),
+        "close_reason": reason,
+        "in_force_secs": in_force_secs,
+        "action_count": actions.len(),
+        "actions_by_actor": actors,
+        "actions_by_container": containers,
+        "actions": actions.iter().map(|a| json!({
+            "at": a.created_at.unix_timestamp(),
+            "actor": a.actor,
+            "action": a.action,
+            "detail": a.detail,
+        })).collect::<Vec<_>>(),
+    })
+}
+
+/// Close an override and store its report; `None` if it was already closed
+async fn close(
+    state: &AppState,
+    override_id: Uuid,
+    closed_by: Option<&str>,
+    reason: &str,
+) -> sqlx::Result<Option<Override>> {
+    let mut tx = state.pool.begin().await?;
+    let Some(ov) = break_glass_db::lock(&mut *tx, override_id).await? else {
+        return Ok(None);
+    };
+    if ov.closed_at.is_some() {
+        return Ok(None);
+    }
+    let actions = break_glass_db::actions(&mut *tx, override_id).await?;
+    let report = report(&ov, &actions, closed_by, reason, OffsetDateTime::now_utc());
+    let closed = break_glass_db::close(&mut *tx, override_id, closed_by, reason, &report).await?;
+    tx.commit().await?;
+    info!(
+        "🧯 Break-glass {} ({}) closed: {}; {} action(s)",
+        override_id,
+        closed.subsystem,
+        reason,
+        actions.len()
+    );
+    Ok(Some(closed))
+}
+
+/// Close lapsed overrides every [`SWEEP_SECS`]
+pub fn spawn_sweeper(state: AppState) {
+    tokio::spawn(async move {
+        let mut tick = tokio::time::interval(Duration::from_secs(SWEEP_SECS));
+        loop {
+            tick.tick().await;
+            let lapsed = match break_glass_db::lapsed(&state.pool).await {
+                Ok(ids) => ids,
+                Err(e) => {
+                    error!("break-glass sweep failed: {}", e);
+                    continue;
+                }
+            };
+            for id in lapsed {
+                if let Err(e) = close(&state, id, None, "expired").await {
+                    error!("break-glass {}: closing on expiry failed: {}", id, e);
+                }
+            }
+        }
+    });
+}
+
+/// POST /admin/break-glass
+async fn route_open(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<OpenReq>,
+) -> Result<(StatusCode, Json<Override>), (StatusCode, String)> {
+    let caller = rbac::require_role(&state.pool, &headers, &[rbac::ADMIN, rbac::OPERATOR]).await?;
+    let justification = req.justification.trim();
+    if justification.is_empty() {
+        return Err((StatusCode::UNPROCESSABLE_ENTITY, "a justification is required".to_string()));
+    }
+    if !(1..=MAX_TTL_SECS).contains(&req.ttl_secs) {
+        return Err((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            format!("ttl_secs must be between 1 and {}", MAX_TTL_SECS),
+        ));
+    }
+    let ov = break_glass_db::insert(
+        &state.pool,
+        req.subsystem.as_str(),
+        justification,
+        req.ttl_secs,
+        &caller.session.sid,
+    )
+    .await
+    .map_err(internal)?;
+    warn!(
+        override_id = %ov.override_id,
+        subsystem = %ov.subsystem,
+        requested_by = %ov.requested_by,
+        justification = %ov.justification,
+        "🧯 Break-glass requested"
+    );
+    Ok((StatusCode::CREATED, Json(ov)))
+}
+
+/// GET /admin/break-glass
+async fn route_list(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<ListQuery>,
+) -> Result<Json<Vec<Override>>, (StatusCode, String)> {
+    rbac::require_role(&state.pool, &headers, &[rbac::ADMIN, rbac::OPERATOR, rbac::AUDITOR]).await?;
+    break_glass_db::list(&state.pool, query.open, MAX_LIST).await.map(Json).map_err(internal)
+}
+
+/// GET /admin/break-glass/:id
+async fn route_get(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(override_id): Path<Uuid>,
+) -> Result<Json<OverrideView>, (StatusCode, String)> {
+    rbac::require_role(&state.pool, &headers, &[rbac::ADMIN, rbac::OPERATOR, rbac::AUDITOR]).await?;
+    let break_glass = break_glass_db::get(&state.pool, override_id)
+        .await
+        .map_err(internal)?
+        .ok_or_else(not_found)?;
+    let actions = break_glass_db::actions(&state.pool, override_id).await.map_err(internal)?;
+    Ok(Json(OverrideView { break_glass, actions }))
+}
+
+/// POST /admin/break-glass/:id/approve
+async fn route_approve(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(override_id): Path<Uuid>,
+) -> Result<Json<Override>, (StatusCode, String)> {
+    let caller = rbac::require_role(&state.pool, &headers, &[rbac::ADMIN]).await?;
+    let ov = break_glass_db::get(&state.pool, override_id)
+        .await
+        .map_err(internal)?
+        .ok_or_else(not_found)?;
+    if ov.requested_by == caller.session.sid {
+        warn!(override_id = %override_id, sid = %caller.session.sid, decision = "reject", error_code = "self_approval");
+        return Err((StatusCode::FORBIDDEN, "the requester cannot approve their own override".to_string()));
+    }
+    let approved = break_glass_db::approve(&state.pool, override_id, &caller.session.sid)
+        .await
+        .map_err(internal)?
+        .ok_or_else(|| (StatusCode::CONFLICT, "override is no longer pending".to_string()))?;
+    warn!(
+        override_id = %override_id,
+        subsystem = %approved.subsystem,
+        approved_by = %caller.session.sid,
+        ttl_secs = approved.ttl_secs,
+        "🧯 Break-glass in force"
+    );
+    Ok(Json(approved))
+}
+
+/// POST /admin/break-glass/:id/close
+async fn route_close(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(override_id): Path<Uuid>,
+    body: Option<Json<CloseReq>>,
+) -> Result<Json<Override>, (StatusCode, String)> {
+    let caller = rbac::require_role(&state.pool, &headers, &[rbac::ADMIN, rbac::OPERATOR]).await?;
+    let req = body.map(|Json(r)| r).unwrap_or_default();
+    let reason = req.reason.as_deref().map(str::trim).filter(|r| !r.is_empty()).unwrap_or("closed");
+    match close(&state, override_id, Some(&caller.session.sid), reason).await.map_err(internal)? {
+        Some(closed) => Ok(Json(closed)),
+        None => match break_glass_db::get(&state.pool, override_id).await.map_err(internal)? {
+            Some(_) => Err((StatusCode::CONFLICT, "override is already closed".to_string())),
+            None => Err(not_found()),
+        },
+    }
+}
+
+/// GET /admin/break-glass/:id/report
+async fn route_report(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(override_id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    rbac::require_role(&state.pool, &headers, &[rbac::ADMIN, rbac::OPERATOR, rbac::AUDITOR]).await?;
+    let ov = break_glass_db::get(&state.pool, override_id)
+        .await
+        .map_err(internal)?
+        .ok_or_else(not_found)?;
+    ov.report
+        .map(Json)
+        .ok_or_else(|| (StatusCode::CONFLICT, "override is still open; its report is written on close".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(secs: i64) -> OffsetDateTime {
+        OffsetDateTime::from_unix_timestamp(secs).unwrap()
+    }
+
+    fn approved() -> Override {
+        Override {
+            override_id: Uuid::nil(),
+            subsystem: "policy".to_string(),
+            justification: "policy engine times out on every commit".to_string(),
+            ttl_secs: 600,
+            requested_by: "sid:alice".to_string(),
+            requested_at: at(1_000),
+            approved_by: Some("sid:bob".to_string()),
+            approved_at: Some(at(1_100)),
+            expires_at: Some(at(1_700)),
+            closed_at: None,
+            closed_by: None,
+            close_reason: None,
+            report: None,
+        }
+    }
+
+    fn commit(actor: &str, container_id: &str, id: i64) -> Action {
+        Action {
+            id,
+            actor: actor.to_string(),
+            action: "commit".to_string(),
+            detail: json!({ "container_id": container_id, "sequence": id }),
+            created_at: at(1_200 + id),
+        }
+    }
+
+    #[test]
+    fn test_report_counts_actions() {
+        let actions = [commit("sid:alice", "C.A", 1), commit("sid:alice", "C.B", 2), commit("sid:carol", "C.A", 3)];
+        let r = report(&approved(), &actions, Some("sid:alice"), "incident resolved", at(1_400));
+        assert_eq!(r["action_count"], 3);
+        assert_eq!(r["actions_by_actor"], json!({ "sid:alice": 2, "sid:carol": 1 }));
+        assert_eq!(r["actions_by_container"], json!({ "C.A": 2, "C.B": 1 }));
+        assert_eq!(r["in_force_secs"], 300);
+        assert_eq!(r["justification"], "policy engine times out on every commit");
+    }
+
+    #[test]
+    fn test_report_of_lapsed_override() {
+        // Swept after expiry: in force for its TTL only
+        let r = report(&approved(), &[], None, "expired", at(1_730));
+        assert_eq!(r["in_force_secs"], 600);
+        assert_eq!(r["closed_by"], serde_json::Value::Null);
+
+        // Never approved: never in force
+        let pending = Override {
+            approved_by: None,
+            approved_at: None,
+            expires_at: None,
+            ..approved()
+        };
+        assert_eq!(report(&pending, &[], None, "expired", at(1_700))["in_force_secs"], serde_json::Value::Null);
+    }
+
+    #[test]
+    fn test_tag_keeps_policy_metadata() {
+        let engaged = Engaged {
+            override_id: Uuid::nil(),
+            subsystem: Subsystem::EvolutionQueue,
+            actor: "sid:alice".to_string(),
+        };
+        let mut link: LinkDraft = serde_json::from_value(json!({
+            "version": 1, "container_id": "C.A", "expected_sequence": 1, "previous_hash": "0x00",
+            "atom_hash": "abcd", "intent_class": "Evolution", "physics_delta": "0",
+            "author_pubkey": "aa", "signature": "bb"
+        }))
+        .unwrap();
+        link.metadata = Some(json!({ "policy": { "decided_by": "p1" } }));
+        engaged.tag(&mut link);
+        let m = link.metadata.unwrap();
+        assert_eq!(m["policy"]["decided_by"], "p1");
+        assert_eq!(m["break_glass"]["subsystem"], "evolution_queue");
+        assert!(engaged.bypasses(Subsystem::EvolutionQueue) && !engaged.bypasses(Subsystem::Policy));
+    }
+}
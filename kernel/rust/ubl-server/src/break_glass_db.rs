@@ -0,0 +1,198 @@
+//! Break-glass overrides and the actions taken under them
+//! (tables `break_glass`, `break_glass_action`, sql/042_break_glass.sql)
+
+use serde::Serialize;
+use sqlx::{PgExecutor, PgPool};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Override {
+    pub override_id: Uuid,
+    pub subsystem: String,
+    pub justification: String,
+    pub ttl_secs: i32,
+    pub requested_by: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub requested_at: OffsetDateTime,
+    pub approved_by: Option<String>,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub approved_at: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub expires_at: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub closed_at: Option<OffsetDateTime>,
+    pub closed_by: Option<String>,
+    pub close_reason: Option<String>,
+    pub report: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Action {
+    pub id: i64,
+    pub actor: String,
+    pub action: String,
+    pub detail: serde_json::Value,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+pub async fn insert(
+    pool: &PgPool,
+    subsystem: &str,
+    justification: &str,
+    ttl_secs: i32,
+    requested_by: &str,
+) -> sqlx::Result<Override> {
+    sqlx::query_as!(
+        Override,
+        r#"INSERT INTO break_glass (subsystem, justification, ttl_secs, requested_by)
+           VALUES ($1, $2, $3, $4)
+           RETURNING override_id, subsystem, justification, ttl_secs, requested_by, requested_at,
+                     approved_by, approved_at, expires_at, closed_at, closed_by, close_reason, report"#,
+        subsystem,
+        justification,
+        ttl_secs,
+        requested_by
+    )
+    .fetch_one(pool)
+    .await
+}
+
+pub async fn get(db: impl PgExecutor<'_>, override_id: Uuid) -> sqlx::Result<Option<Override>> {
+    sqlx::query_as!(
+        Override,
+        r#"SELECT override_id, subsystem, justification, ttl_secs, requested_by, requested_at,
+                  approved_by, approved_at, expires_at, closed_at, closed_by, close_reason, report
+           FROM break_glass WHERE override_id = $1"#,
+        override_id
+    )
+    .fetch_optional(db)
+    .await
+}
+
+/// Lock an override for the rest of the transaction
+pub async fn lock(db: impl PgExecutor<'_>, override_id: Uuid) -> sqlx::Result<Option<Override>> {
+    sqlx::query_as!(
+        Override,
+        r#"SELECT override_id, subsystem, justification, ttl_secs, requested_by, requested_at,
+                  approved_by, approved_at, expires_at, closed_at, closed_by, close_reason, report
+           FROM break_glass WHERE override_id = $1
+           FOR UPDATE"#,
+        override_id
+    )
+    .fetch_optional(db)
+    .await
+}
+
+/// Overrides, newest first; only unclosed ones with `open`
+pub async fn list(pool: &PgPool, open: bool, limit: i64) -> sqlx::Result<Vec<Override>> {
+    sqlx::query_as!(
+        Override,
+        r#"SELECT override_id, subsystem, justification, ttl_secs, requested_by, requested_at,
+                  approved_by, approved_at, expires_at, closed_at, closed_by, close_reason, report
+           FROM break_glass
+           WHERE NOT $1 OR closed_at IS NULL
+           ORDER BY requested_at DESC LIMIT $2"#,
+        open,
+        limit
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Put a pending override in force for its TTL; `None` unless it is still pending
+pub async fn approve(db: impl PgExecutor<'_>, override_id: Uuid, approved_by: &str) -> sqlx::Result<Option<Override>> {
+    sqlx::query_as!(
+        Override,
+        r#"UPDATE break_glass
+           SET approved_by = $2, approved_at = now(), expires_at = now() + make_interval(secs => ttl_secs)
+           WHERE override_id = $1 AND approved_at IS NULL AND closed_at IS NULL
+           RETURNING override_id, subsystem, justification, ttl_secs, requested_by, requested_at,
+                     approved_by, approved_at, expires_at, closed_at, closed_by, close_reason, report"#,
+        override_id,
+        approved_by
+    )
+    .fetch_optional(db)
+    .await
+}
+
+/// Close an override with its report
+pub async fn close(
+    db: impl PgExecutor<'_>,
+    override_id: Uuid,
+    closed_by: Option<&str>,
+    reason: &str,
+    report: &serde_json::Value,
+) -> sqlx::Result<Override> {
+    sqlx::query_as!(
+        Override,
+        r#"UPDATE break_glass SET closed_at = now(), closed_by = $2, close_reason = $3, report = $4
+           WHERE override_id = $1
+           RETURNING override_id, subsystem, justification, ttl_secs, requested_by, requested_at,
+                     approved_by, approved_at, expires_at, closed_at, closed_by, close_reason, report"#,
+        override_id,
+        closed_by,
+        reason,
+        report
+    )
+    .fetch_one(db)
+    .await
+}
+
+/// Unclosed overrides that lapsed: approved ones past `expires_at`, and
+/// pending ones never approved within their TTL
+pub async fn lapsed(pool: &PgPool) -> sqlx::Result<Vec<Uuid>> {
+    sqlx::query_scalar!(
+        r#"SELECT override_id FROM break_glass
+           WHERE closed_at IS NULL
+             AND COALESCE(expires_at, requested_at + make_interval(secs => ttl_secs)) <= now()"#
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// `override_id` if it is approved, unexpired and not closed
+pub async fn active(pool: &PgPool, override_id: Uuid) -> sqlx::Result<Option<Override>> {
+    sqlx::query_as!(
+        Override,
+        r#"SELECT override_id, subsystem, justification, ttl_secs, requested_by, requested_at,
+                  approved_by, approved_at, expires_at, closed_at, closed_by, close_reason, report
+           FROM break_glass
+           WHERE override_id = $1 AND approved_at IS NOT NULL AND closed_at IS NULL AND expires_at > now()"#,
+        override_id
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn record_action(
+    db: impl PgExecutor<'_>,
+    override_id: Uuid,
+    actor: &str,
+    action: &str,
+    detail: &serde_json::Value,
+) -> sqlx::Result<()> {
+    sqlx::query!(
+        r#"INSERT INTO break_glass_action (override_id, actor, action, detail) VALUES ($1, $2, $3, $4)"#,
+        override_id,
+        actor,
+        action,
+        detail
+    )
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+/// Actions under an override, oldest first
+pub async fn actions(db: impl PgExecutor<'_>, override_id: Uuid) -> sqlx::Result<Vec<Action>> {
+    sqlx::query_as!(
+        Action,
+        r#"SELECT id, actor, action, detail, created_at
+           FROM break_glass_action WHERE override_id = $1 ORDER BY id"#,
+        override_id
+    )
+    .fetch_all(db)
+    .await
+}
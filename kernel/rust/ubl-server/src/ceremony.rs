@@ -0,0 +1,166 @@
+//! # WebAuthn ceremony tracking
+//!
+//! Each register/login/stepup begin and finish records one outcome: the
+//! challenge was issued, the ceremony was accepted, or it was rejected with a
+//! [`FailureClass`]. Outcomes are counted in Prometheus and written to
+//! `id_ceremony_event` off the request path, so tracking never slows or fails
+//! a ceremony.
+//!
+//! A begin takes its correlation id from `X-Correlation-Id` or mints one; the
+//! matching finish inherits it through the challenge id.
+
+use axum::http::HeaderMap;
+use sqlx::PgPool;
+use time::OffsetDateTime;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::ceremony_db::{self, CeremonyEvent};
+use crate::metrics;
+
+pub const CORRELATION_HEADER: &str = "x-correlation-id";
+
+/// Why a ceremony was rejected (the `failure_class` metric label)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailureClass {
+    RateLimited,
+    UsernameExists,
+    ChallengeNotFound,
+    ChallengeUsed,
+    ChallengeExpired,
+    InvalidChallengeKind,
+    OriginMismatch,
+    UnknownCredential,
+    VerificationFailed,
+    SignCountRegression,
+    Internal,
+}
+
+impl FailureClass {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FailureClass::RateLimited => "rate_limited",
+            FailureClass::UsernameExists => "username_exists",
+            FailureClass::ChallengeNotFound => "challenge_not_found",
+            FailureClass::ChallengeUsed => "challenge_used",
+            FailureClass::ChallengeExpired => "challenge_expired",
+            FailureClass::InvalidChallengeKind => "invalid_challenge_kind",
+            FailureClass::OriginMismatch => "origin_mismatch",
+            FailureClass::UnknownCredential => "unknown_credential",
+            FailureClass::VerificationFailed => "verification_failed",
+            FailureClass::SignCountRegression => "sign_count_regression",
+            FailureClass::Internal => "internal",
+        }
+    }
+}
+
+/// Outcome recorder for one ceremony phase
+#[derive(Debug, Clone)]
+pub struct CeremonyTracker {
+    pool: PgPool,
+    ceremony: &'static str,
+    phase: &'static str,
+    pub correlation_id: Uuid,
+    username: Option<String>,
+    sid: Option<String>,
+    challenge_id: Option<Uuid>,
+}
+
+fn header_correlation(headers: &HeaderMap) -> Option<Uuid> {
+    headers
+        .get(CORRELATION_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| Uuid::parse_str(v).ok())
+}
+
+impl CeremonyTracker {
+    /// Tracker for a begin phase
+    pub fn begin(pool: &PgPool, ceremony: &'static str, headers: &HeaderMap) -> Self {
+        Self {
+            pool: pool.clone(),
+            ceremony,
+            phase: "begin",
+            correlation_id: header_correlation(headers).unwrap_or_else(Uuid::new_v4),
+            username: None,
+            sid: None,
+            challenge_id: None,
+        }
+    }
+
+    /// Tracker for a finish phase, correlated with the begin that issued `challenge_id`
+    pub async fn finish(pool: &PgPool, ceremony: &'static str, headers: &HeaderMap, challenge_id: &str) -> Self {
+        let challenge_id = Uuid::parse_str(challenge_id).ok();
+        let issued = match challenge_id {
+            Some(id) => ceremony_db::issued_for_challenge(pool, id).await.ok().flatten(),
+            None => None,
+        };
+        let (correlation_id, username) = match (header_correlation(headers), issued) {
+            (Some(c), issued) => (c, issued.and_then(|(_, u)| u)),
+            (None, Some((c, u))) => (c, u),
+            (None, None) => (Uuid::new_v4(), None),
+        };
+        Self {
+            pool: pool.clone(),
+            ceremony,
+            phase: "finish",
+            correlation_id,
+            username,
+            sid: None,
+            challenge_id,
+        }
+    }
+
+    pub fn set_username(&mut self, username: &str) {
+        self.username = Some(username.to_string());
+    }
+
+    pub fn set_sid(&mut self, sid: &str) {
+        self.sid = Some(sid.to_string());
+    }
+
+    pub fn set_challenge(&mut self, challenge_id: Uuid) {
+        self.challenge_id = Some(challenge_id);
+    }
+
+    /// Challenge handed to the client
+    pub fn issued(&self) {
+        self.record("issued", None, None);
+    }
+
+    /// Ceremony verified
+    pub fn accept(&self) {
+        self.record("accept", None, None);
+    }
+
+    /// Ceremony rejected
+    pub fn fail(&self, class: FailureClass, detail: impl Into<String>) {
+        metrics::WEBAUTHN_CEREMONY_FAILURES
+            .with_label_values(&[self.ceremony, class.as_str()])
+            .inc();
+        self.record("reject", Some(class), Some(detail.into()));
+    }
+
+    fn record(&self, outcome: &'static str, class: Option<FailureClass>, detail: Option<String>) {
+        metrics::WEBAUTHN_CEREMONIES
+            .with_label_values(&[self.ceremony, self.phase, outcome])
+            .inc();
+        let event = CeremonyEvent {
+            correlation_id: self.correlation_id,
+            ceremony: self.ceremony.to_string(),
+            phase: self.phase.to_string(),
+            outcome: outcome.to_string(),
+            failure_class: class.map(|c| c.as_str().to_string()),
+            username: self.username.clone(),
+            sid: self.sid.clone(),
+            challenge_id: self.challenge_id,
+            detail,
+            created_at: OffsetDateTime::now_utc(),
+        };
+        let pool = self.pool.clone();
+        tokio::spawn(async move {
+            if let Err(e) = ceremony_db::insert(&pool, &event).await {
+                warn!(correlation_id = %event.correlation_id, error = %e, "ceremony event not persisted");
+            }
+        });
+    }
+}
@@ -0,0 +1,84 @@
+//! WebAuthn ceremony attempts (table `id_ceremony_event`, sql/026_webauthn_ceremony.sql)
+
+use serde::Serialize;
+use sqlx::PgPool;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+/// One ceremony outcome as stored
+#[derive(Debug, Clone, Serialize)]
+pub struct CeremonyEvent {
+    pub correlation_id: Uuid,
+    pub ceremony: String,
+    pub phase: String,
+    pub outcome: String,
+    pub failure_class: Option<String>,
+    pub username: Option<String>,
+    pub sid: Option<String>,
+    pub challenge_id: Option<Uuid>,
+    pub detail: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+pub async fn insert(pool: &PgPool, e: &CeremonyEvent) -> sqlx::Result<()> {
+    sqlx::query!(
+        r#"INSERT INTO id_ceremony_event
+             (correlation_id, ceremony, phase, outcome, failure_class, username, sid, challenge_id, detail, created_at)
+           VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)"#,
+        e.correlation_id,
+        e.ceremony,
+        e.phase,
+        e.outcome,
+        e.failure_class,
+        e.username,
+        e.sid,
+        e.challenge_id,
+        e.detail,
+        e.created_at
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Correlation id and username recorded when `challenge_id` was issued
+pub async fn issued_for_challenge(
+    pool: &PgPool,
+    challenge_id: Uuid,
+) -> sqlx::Result<Option<(Uuid, Option<String>)>> {
+    let row = sqlx::query!(
+        r#"SELECT correlation_id, username FROM id_ceremony_event
+           WHERE challenge_id = $1 AND outcome = 'issued'
+           ORDER BY id LIMIT 1"#,
+        challenge_id
+    )
+    .fetch_optional(pool)
+    .await?;
+    Ok(row.map(|r| (r.correlation_id, r.username)))
+}
+
+/// Most recent attempts for a username or sid, newest first
+pub async fn recent(
+    pool: &PgPool,
+    username: Option<&str>,
+    sid: Option<&str>,
+    limit: i64,
+) -> sqlx::Result<Vec<CeremonyEvent>> {
+    let rows = sqlx::query_as!(
+        CeremonyEvent,
+        r#"SELECT correlation_id, ceremony, phase, outcome, failure_class, username, sid,
+                  challenge_id, detail, created_at
+           FROM id_ceremony_event
+           WHERE ($1::text IS NULL OR username = $1)
+             AND ($2::text IS NULL OR sid = $2)
+           ORDER BY created_at DESC, id DESC
+           LIMIT $3"#,
+        username,
+        sid,
+        limit
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
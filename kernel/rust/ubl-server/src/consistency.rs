@@ -0,0 +1,152 @@
+//! # Read-your-writes consistency tokens
+//!
+//! With `UBL_READ_DATABASE_URL` set, `GET /state` reads from that replica,
+//! which may lag the primary. Every accepted commit answers a token naming
+//! the entry it wrote (`<container_id>@<sequence>`); a read that sends it
+//! back in `X-UBL-Consistency` is served by the replica only once the replica
+//! has that entry. The server waits up to `UBL_CONSISTENCY_WAIT_MS`
+//! (default 500) for it to catch up, then answers from the primary instead.
+//! `X-UBL-Served-By` says which one answered.
+//!
+//! Without a replica every read goes to the primary and tokens are accepted
+//! but never wait.
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use sqlx::PgPool;
+use tracing::warn;
+
+use crate::db::PgLedger;
+
+/// Request header carrying a token back
+pub const HEADER: &str = "x-ubl-consistency";
+/// Response header naming the database that answered
+pub const SERVED_BY_HEADER: &str = "x-ubl-served-by";
+
+const DEFAULT_WAIT: Duration = Duration::from_millis(500);
+const POLL_INTERVAL: Duration = Duration::from_millis(25);
+
+/// A committed entry a later read must observe
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConsistencyToken {
+    pub container_id: String,
+    pub sequence: i64,
+}
+
+impl fmt::Display for ConsistencyToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}@{}", self.container_id, self.sequence)
+    }
+}
+
+impl FromStr for ConsistencyToken {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid consistency token: {:?}", s);
+        let (container_id, sequence) = s.rsplit_once('@').ok_or_else(invalid)?;
+        let sequence: i64 = sequence.parse().map_err(|_| invalid())?;
+        if container_id.is_empty() || sequence < 1 {
+            return Err(invalid());
+        }
+        Ok(Self {
+            container_id: container_id.to_string(),
+            sequence,
+        })
+    }
+}
+
+/// Which database answered a read
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Served {
+    Replica,
+    Primary,
+}
+
+impl Served {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Served::Replica => "replica",
+            Served::Primary => "primary",
+        }
+    }
+}
+
+/// Read replica and how long a token may wait on it
+#[derive(Clone)]
+pub struct ReadReplica {
+    pub pool: PgPool,
+    pub ledger: PgLedger,
+    pub wait: Duration,
+}
+
+impl ReadReplica {
+    /// Replica from `UBL_READ_DATABASE_URL` (unset: reads stay on the primary)
+    pub async fn from_env() -> anyhow::Result<Option<Self>> {
+        let Some(url) = std::env::var("UBL_READ_DATABASE_URL").ok().filter(|v| !v.is_empty()) else {
+            return Ok(None);
+        };
+        let wait = match std::env::var("UBL_CONSISTENCY_WAIT_MS") {
+            Ok(ms) => Duration::from_millis(
+                ms.parse()
+                    .map_err(|_| anyhow::anyhow!("invalid UBL_CONSISTENCY_WAIT_MS: {}", ms))?,
+            ),
+            Err(_) => DEFAULT_WAIT,
+        };
+        let pool = PgPool::connect(&url).await?;
+        Ok(Some(Self {
+            ledger: PgLedger::new(pool.clone()),
+            pool,
+            wait,
+        }))
+    }
+
+    /// Serve from the replica once it holds `token`'s entry, else from the primary
+    pub async fn route(&self, token: Option<&ConsistencyToken>) -> Served {
+        let Some(token) = token else {
+            return Served::Replica;
+        };
+        let deadline = Instant::now() + self.wait;
+        loop {
+            let head = sqlx::query_scalar!(
+                "SELECT COALESCE(MAX(sequence), 0) FROM ledger_entry WHERE container_id = $1",
+                token.container_id
+            )
+            .fetch_one(&self.pool)
+            .await;
+            match head {
+                Ok(Some(seq)) if seq >= token.sequence => return Served::Replica,
+                Ok(_) => {}
+                Err(e) => {
+                    warn!(error = %e, "replica head lookup failed; reading from primary");
+                    return Served::Primary;
+                }
+            }
+            if Instant::now() >= deadline {
+                return Served::Primary;
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_round_trip() {
+        let token = ConsistencyToken {
+            container_id: "gov://ops@eu".to_string(),
+            sequence: 42,
+        };
+        assert_eq!(token.to_string(), "gov://ops@eu@42");
+        assert_eq!("gov://ops@eu@42".parse::<ConsistencyToken>(), Ok(token));
+
+        for bad in ["C.Test", "C.Test@", "@3", "C.Test@0", "C.Test@x"] {
+            assert!(bad.parse::<ConsistencyToken>().is_err(), "{}", bad);
+        }
+    }
+}
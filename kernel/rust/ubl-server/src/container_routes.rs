@@ -0,0 +1,351 @@
+//! # Container administration
+//!
+//! Container-scoped admin routes, gated by capability (`auth::container_grant`)
+//! rather than the global `admin` role:
+//!
+//! - GET    /containers/:id/policies                (attached policies + composition)
+//! - POST   /containers/:id/policies/:policy_id     (attach; `policies`)
+//! - DELETE /containers/:id/policies/:policy_id     (detach; `policies`)
+//! - PUT    /containers/:id/composition             (composition mode; `policies`)
+//! - POST   /containers/:id/grants                  (issue a grant; `grants`)
+//! - GET    /containers/:id/grants                  (active grants; `grants`)
+//! - DELETE /containers/:id/grants/:grant_id        (revoke; `grants`)
+//! - GET    /containers/:id/admin/audit?limit=      (grants, revocations, uses; `grants`)
+//! - GET    /containers/dependencies                (dependency graph and startup order)
+//! - GET    /containers/:id/dependencies            (what it depends on and what depends on it)
+//!
+//! Dependencies are declared once, by the manifest on a container's first
+//! link (`LinkDraft.manifest.depends_on`), and every one must already exist,
+//! so the graph has no cycles and needs no capability to read.
+//!
+//! A delegated grantor can only hand out capabilities it holds itself, and
+//! never beyond its own grant's expiry.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::Response,
+    routing::{get, post, put},
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use time::{Duration, OffsetDateTime};
+use tracing::info;
+use ubl_policy_vm::CompositionMode;
+use uuid::Uuid;
+
+use crate::auth::container_grant::{self, Capability};
+use crate::auth::container_grant_db::{self, AdminGrant, AuditEntry};
+use crate::auth::session_policy;
+use crate::dependency_db::{self, Graph, Node};
+use crate::{http_cache, id_db, policy_db, AppState};
+
+#[derive(Debug, Serialize)]
+pub struct ContainerPoliciesResp {
+    pub container_id: String,
+    pub policies: Vec<String>,
+    pub composition: CompositionMode,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CompositionReq {
+    pub mode: CompositionMode,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IssueGrantReq {
+    pub grantee_sid: String,
+    pub capabilities: Vec<String>,
+    pub ttl_secs: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AuditQuery {
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+}
+
+fn default_limit() -> i64 {
+    100
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/containers/:container_id/policies", get(route_list_policies))
+        .route(
+            "/containers/:container_id/policies/:policy_id",
+            post(route_attach).delete(route_detach),
+        )
+        .route("/containers/:container_id/composition", put(route_set_composition))
+        .route(
+            "/containers/:container_id/grants",
+            post(route_issue_grant).get(route_list_grants),
+        )
+        .route(
+            "/containers/:container_id/grants/:grant_id",
+            axum::routing::delete(route_revoke_grant),
+        )
+        .route("/containers/:container_id/admin/audit", get(route_audit))
+        .route("/containers/dependencies", get(route_dependency_graph))
+        .route("/containers/:container_id/dependencies", get(route_dependencies))
+}
+
+fn internal(e: sqlx::Error) -> (StatusCode, String) {
+    (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+}
+
+fn policies_view(state: &AppState, container_id: String) -> ContainerPoliciesResp {
+    let vm = state.policies.read().unwrap();
+    ContainerPoliciesResp {
+        policies: vm.attached(&container_id).into_iter().map(String::from).collect(),
+        composition: vm.composition(&container_id),
+        container_id,
+    }
+}
+
+async fn dependency_graph(state: &AppState) -> Result<Graph, (StatusCode, String)> {
+    let edges = dependency_db::edges(&state.pool).await.map_err(internal)?;
+    dependency_db::graph(&edges).map_err(|cycle| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("dependency cycle among {:?}", cycle),
+        )
+    })
+}
+
+/// GET /containers/dependencies
+async fn route_dependency_graph(State(state): State<AppState>) -> Result<Json<Graph>, (StatusCode, String)> {
+    dependency_graph(&state).await.map(Json)
+}
+
+/// GET /containers/:id/dependencies
+async fn route_dependencies(
+    State(state): State<AppState>,
+    Path(container_id): Path<String>,
+) -> Result<Json<Node>, (StatusCode, String)> {
+    let graph = dependency_graph(&state).await?;
+    let node = graph
+        .containers
+        .into_iter()
+        .find(|n| n.container_id == container_id)
+        .unwrap_or(Node {
+            container_id,
+            depends_on: Vec::new(),
+            dependents: Vec::new(),
+        });
+    Ok(Json(node))
+}
+
+/// GET /containers/:id/policies
+async fn route_list_policies(
+    State(state): State<AppState>,
+    Path(container_id): Path<String>,
+    headers: HeaderMap,
+) -> Response {
+    let view = policies_view(&state, container_id);
+    http_cache::respond("container_policies", &headers, http_cache::SHORT, view)
+}
+
+/// POST /containers/:id/policies/:policy_id
+async fn route_attach(
+    State(state): State<AppState>,
+    Path((container_id, policy_id)): Path<(String, String)>,
+    headers: HeaderMap,
+) -> Result<Json<ContainerPoliciesResp>, (StatusCode, String)> {
+    let admin = container_grant::require_capability(
+        &state.pool,
+        &headers,
+        &container_id,
+        Capability::Policies,
+        serde_json::json!({ "action": "attach_policy", "policy_id": policy_id }),
+    )
+    .await?;
+    if state.policies.read().unwrap().get(&policy_id).is_none() {
+        return Err((StatusCode::NOT_FOUND, "Policy not found".to_string()));
+    }
+
+    policy_db::attach(&state.pool, &container_id, &policy_id, &admin.caller.session.sid)
+        .await
+        .map_err(internal)?;
+    state.policies.write().unwrap().attach(&container_id, &policy_id);
+    info!("📎 POLICY attached container={} policy={} by={}", container_id, policy_id, admin.caller.session.sid);
+    session_policy::after_action(&state.pool, &admin.caller.session, session_policy::RISK_L4).await;
+    Ok(Json(policies_view(&state, container_id)))
+}
+
+/// DELETE /containers/:id/policies/:policy_id
+async fn route_detach(
+    State(state): State<AppState>,
+    Path((container_id, policy_id)): Path<(String, String)>,
+    headers: HeaderMap,
+) -> Result<Json<ContainerPoliciesResp>, (StatusCode, String)> {
+    let admin = container_grant::require_capability(
+        &state.pool,
+        &headers,
+        &container_id,
+        Capability::Policies,
+        serde_json::json!({ "action": "detach_policy", "policy_id": policy_id }),
+    )
+    .await?;
+
+    if !policy_db::detach(&state.pool, &container_id, &policy_id)
+        .await
+        .map_err(internal)?
+    {
+        return Err((StatusCode::NOT_FOUND, "Policy not attached".to_string()));
+    }
+    state.policies.write().unwrap().detach(&container_id, &policy_id);
+    info!("📎 POLICY detached container={} policy={} by={}", container_id, policy_id, admin.caller.session.sid);
+    session_policy::after_action(&state.pool, &admin.caller.session, session_policy::RISK_L4).await;
+    Ok(Json(policies_view(&state, container_id)))
+}
+
+/// PUT /containers/:id/composition
+async fn route_set_composition(
+    State(state): State<AppState>,
+    Path(container_id): Path<String>,
+    headers: HeaderMap,
+    Json(req): Json<CompositionReq>,
+) -> Result<Json<ContainerPoliciesResp>, (StatusCode, String)> {
+    let admin = container_grant::require_capability(
+        &state.pool,
+        &headers,
+        &container_id,
+        Capability::Policies,
+        serde_json::json!({ "action": "set_composition", "mode": req.mode }),
+    )
+    .await?;
+
+    policy_db::set_composition(&state.pool, &container_id, req.mode, &admin.caller.session.sid)
+        .await
+        .map_err(internal)?;
+    state.policies.write().unwrap().set_composition(&container_id, req.mode);
+    session_policy::after_action(&state.pool, &admin.caller.session, session_policy::RISK_L4).await;
+    Ok(Json(policies_view(&state, container_id)))
+}
+
+/// POST /containers/:id/grants
+async fn route_issue_grant(
+    State(state): State<AppState>,
+    Path(container_id): Path<String>,
+    headers: HeaderMap,
+    Json(req): Json<IssueGrantReq>,
+) -> Result<Json<AdminGrant>, (StatusCode, String)> {
+    let admin = container_grant::require_capability(
+        &state.pool,
+        &headers,
+        &container_id,
+        Capability::Grants,
+        serde_json::json!({ "action": "issue_grant", "grantee_sid": req.grantee_sid }),
+    )
+    .await?;
+
+    let mut capabilities = Vec::with_capacity(req.capabilities.len());
+    for c in &req.capabilities {
+        let cap = Capability::parse(c)
+            .ok_or_else(|| (StatusCode::BAD_REQUEST, format!("unknown capability: {}", c)))?;
+        capabilities.push(cap.as_str().to_string());
+    }
+    capabilities.sort();
+    capabilities.dedup();
+    if capabilities.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "capabilities must not be empty".to_string()));
+    }
+    if req.ttl_secs <= 0 {
+        return Err((StatusCode::BAD_REQUEST, "ttl_secs must be positive".to_string()));
+    }
+    let mut expires_at = OffsetDateTime::now_utc() + Duration::seconds(req.ttl_secs);
+
+    // Delegation never widens: own capabilities only, own expiry at most
+    if let Some(own) = &admin.grant {
+        if let Some(c) = capabilities.iter().find(|c| !own.capabilities.contains(c)) {
+            return Err((StatusCode::FORBIDDEN, format!("cannot delegate capability not held: {}", c)));
+        }
+        expires_at = expires_at.min(own.expires_at);
+    }
+
+    id_db::get_subject(&state.pool, &req.grantee_sid)
+        .await
+        .map_err(internal)?
+        .ok_or((StatusCode::NOT_FOUND, "Subject not found".to_string()))?;
+
+    let grant = container_grant_db::insert_grant(
+        &state.pool,
+        &container_id,
+        &req.grantee_sid,
+        &capabilities,
+        &admin.caller.session.sid,
+        expires_at,
+    )
+    .await
+    .map_err(internal)?;
+    info!(
+        "🎫 GRANT issued container={} grantee={} caps={:?} by={}",
+        container_id, grant.grantee_sid, grant.capabilities, grant.granted_by
+    );
+    session_policy::after_action(&state.pool, &admin.caller.session, session_policy::RISK_L4).await;
+    Ok(Json(grant))
+}
+
+/// GET /containers/:id/grants
+async fn route_list_grants(
+    State(state): State<AppState>,
+    Path(container_id): Path<String>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<AdminGrant>>, (StatusCode, String)> {
+    container_grant::require_capability(
+        &state.pool,
+        &headers,
+        &container_id,
+        Capability::Grants,
+        serde_json::json!({ "action": "list_grants" }),
+    )
+    .await?;
+    let grants = container_grant_db::list_active(&state.pool, &container_id)
+        .await
+        .map_err(internal)?;
+    Ok(Json(grants))
+}
+
+/// DELETE /containers/:id/grants/:grant_id
+async fn route_revoke_grant(
+    State(state): State<AppState>,
+    Path((container_id, grant_id)): Path<(String, Uuid)>,
+    headers: HeaderMap,
+) -> Result<Json<AdminGrant>, (StatusCode, String)> {
+    let admin = container_grant::require_capability(
+        &state.pool,
+        &headers,
+        &container_id,
+        Capability::Grants,
+        serde_json::json!({ "action": "revoke_grant", "grant_id": grant_id }),
+    )
+    .await?;
+    let grant = container_grant_db::revoke(&state.pool, &container_id, grant_id, &admin.caller.session.sid)
+        .await
+        .map_err(internal)?
+        .ok_or((StatusCode::NOT_FOUND, "Grant not found or already revoked".to_string()))?;
+    info!("🎫 GRANT revoked container={} grant={} by={}", container_id, grant_id, admin.caller.session.sid);
+    Ok(Json(grant))
+}
+
+/// GET /containers/:id/admin/audit
+async fn route_audit(
+    State(state): State<AppState>,
+    Path(container_id): Path<String>,
+    Query(q): Query<AuditQuery>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<AuditEntry>>, (StatusCode, String)> {
+    container_grant::require_capability(
+        &state.pool,
+        &headers,
+        &container_id,
+        Capability::Grants,
+        serde_json::json!({ "action": "read_audit" }),
+    )
+    .await?;
+    let entries = container_grant_db::audit_log(&state.pool, &container_id, q.limit.clamp(1, 1000))
+        .await
+        .map_err(internal)?;
+    Ok(Json(entries))
+}
@@ -1,12 +1,19 @@
 //! Database layer - PostgreSQL ledger with SERIALIZABLE transactions
 //! SPEC-UBL-LEDGER v1.0 compliant
 
-use blake3::Hasher;
 use serde::{Deserialize, Serialize};
-use sqlx::{PgPool, Postgres, Transaction};
+use sqlx::{PgExecutor, PgPool, Postgres, Transaction};
+use std::time::Instant;
 use time::OffsetDateTime;
+use ubl_errors::ErrorCatalog;
+use ubl_link::SpecVersion;
 
-#[derive(Debug, Deserialize)]
+use crate::entry_hash::{self, EntryFields, HashFormat, StoredHash};
+use crate::pipeline::PipelineTrace;
+use crate::rehash_db;
+use crate::region_db;
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct LinkDraft {
     pub version: u8,
     pub container_id: String,
@@ -16,10 +23,62 @@ pub struct LinkDraft {
     pub intent_class: String,     // "Observation"|"Conservation"|"Entropy"|"Evolution"
     pub physics_delta: String,    // i128 string (já validado na Membrane)
     pub author_pubkey: String,    // hex
-    pub signature: String,        // hex
+    pub signature: String,        // hex; verified at the membrane, format-checked in strict mode
+    /// Governance containers only: operational containers this entry affects
+    #[serde(default)]
+    pub affects: Vec<String>,
+    /// Policy to evaluate before append; its constraints bind this commit.
+    /// Containers with bound policies evaluate those, and this must name one
+    #[serde(default)]
+    pub policy_id: Option<String>,
+    /// Intent payload handed to the policy (TDLN input)
+    #[serde(default)]
+    pub intent: Option<serde_json::Value>,
+    /// First link only: the container's manifest
+    #[serde(default)]
+    pub manifest: Option<ContainerManifest>,
+    /// Stored in `ledger_entry.metadata`; set by the server, never by clients
+    #[serde(skip)]
+    pub metadata: Option<serde_json::Value>,
+}
+
+/// What a container declares about itself when it is created
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ContainerManifest {
+    /// Containers that must exist before this one (`dependency_db`); fixed at creation
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    /// Pact the container operates under (`pact_db`); while it is not in
+    /// force the container accepts Observation links only. Fixed at creation
+    #[serde(default)]
+    pub authority_pact: Option<String>,
+    /// Spec version the container's links are validated under, for good
+    /// (`container_spec`); the server's latest when unset. Fixed at creation
+    #[serde(default)]
+    pub spec_version: Option<SpecVersion>,
+}
+
+impl LinkDraft {
+    /// Strict hex formats (`UBL_STRICT_HEX`): 64-hex atom and previous
+    /// hashes and author key, 128-hex signature. A container's first link
+    /// names [`entry_hash::GENESIS`] as its previous hash
+    pub fn check_hex(&self) -> Result<(), ubl_link::HexFieldError> {
+        ubl_link::AtomHash::parse(&self.atom_hash)?;
+        if !(self.expected_sequence == 1 && self.previous_hash == entry_hash::GENESIS) {
+            ubl_link::EntryHash::parse(&self.previous_hash)?;
+        }
+        ubl_link::PubKeyHex::parse(&self.author_pubkey)?;
+        ubl_link::SignatureHex::parse(&self.signature)?;
+        Ok(())
+    }
 }
 
-#[derive(Debug, Serialize)]
+/// `UBL_STRICT_HEX=1` enforces [`LinkDraft::check_hex`] on every draft
+pub fn strict_hex_from_env() -> bool {
+    std::env::var("UBL_STRICT_HEX").is_ok_and(|v| v == "1")
+}
+
+#[derive(Debug, Clone, Serialize)]
 pub struct LedgerEntry {
     pub container_id: String,
     pub sequence: i64,
@@ -29,12 +88,49 @@ pub struct LedgerEntry {
     pub ts_unix_ms: i64,
 }
 
+/// One accepted commit in the deployment-wide head feed
+#[derive(Debug, Clone, Serialize)]
+pub struct HeadEvent {
+    pub id: i64,
+    pub container_id: String,
+    pub sequence: i64,
+    pub entry_hash: String,
+}
+
+/// A feed head with its whole `ledger_entry` row as JSON, as the SSE tail streams it
 #[derive(Debug)]
+pub struct FeedEntry {
+    pub head: HeadEvent,
+    pub row: String,
+}
+
+/// Why an append was refused
+#[derive(Debug, ErrorCatalog)]
+#[catalog(source = "tangency")]
 pub enum TangencyError {
+    /// The link's protocol version is not supported
+    #[catalog(status = 400)]
     InvalidVersion,
+    /// The link targets another container
+    #[catalog(status = 400)]
     InvalidTarget,
+    /// `previous_hash` is not the container's head
+    #[catalog(status = 409)]
     RealityDrift,
+    /// `expected_sequence` is not the container's next sequence
+    #[catalog(status = 409)]
     SequenceMismatch,
+    /// This region is not the primary (`region.rs`)
+    #[catalog(status = 503)]
+    Fenced,
+    /// The transaction did not commit (e.g. a serialization failure); nothing was appended
+    #[catalog(status = 409)]
+    NotCommitted(String),
+}
+
+/// Commit an append transaction; a refused commit surfaces as [`TangencyError::NotCommitted`]
+pub async fn commit(tx: Transaction<'static, Postgres>) -> Result<(), TangencyError> {
+    tx.commit().await.map_err(|e| TangencyError::NotCommitted(e.to_string()))
 }
 
 #[derive(Clone)]
@@ -49,7 +145,18 @@ impl PgLedger {
 
     /// Append transacional com SERIALIZABLE + FOR UPDATE
     /// SPEC-UBL-LEDGER v1.0 §7 - Atomicidade: validate → append → commit
-    pub async fn append(&self, link: &LinkDraft) -> Result<LedgerEntry, TangencyError> {
+    pub async fn append(&self, link: &LinkDraft, trace: &mut PipelineTrace) -> Result<LedgerEntry, TangencyError> {
+        let mut tx = self.begin(trace).await?;
+        let entry = append_in(&mut tx, link, trace).await?;
+
+        // Commit transaction
+        commit(tx).await?;
+        Ok(entry)
+    }
+
+    /// SERIALIZABLE transaction for [`append_in`], refused unless this region appends
+    pub async fn begin(&self, trace: &mut PipelineTrace) -> Result<Transaction<'static, Postgres>, TangencyError> {
+        let t = Instant::now();
         // Begin SERIALIZABLE transaction
         let mut tx: Transaction<Postgres> = self
             .pool
@@ -62,104 +169,393 @@ impl PgLedger {
             .await
             .expect("serializable");
 
-        // Lock and get latest entry (FOR UPDATE)
+        // A standby or fenced region never appends, whatever the caller saw
+        if region_db::role(&mut *tx).await.expect("region role").is_some_and(|r| r != "primary") {
+            trace.fail("region", t, "Fenced");
+            return Err(TangencyError::Fenced);
+        }
+        Ok(tx)
+    }
+
+    /// Get current state of container
+    pub async fn get_state(&self, container_id: &str) -> Result<LedgerEntry, sqlx::Error> {
         let rec = sqlx::query!(
             r#"
-            SELECT sequence, entry_hash
+            SELECT sequence, link_hash, previous_hash, entry_hash, ts_unix_ms
             FROM ledger_entry
             WHERE container_id = $1
             ORDER BY sequence DESC
             LIMIT 1
-            FOR UPDATE
             "#,
-            link.container_id
+            container_id
         )
-        .fetch_optional(&mut *tx)
-        .await
-        .expect("select last");
+        .fetch_one(&self.pool)
+        .await?;
 
-        let (expected_prev, expected_seq) = match rec {
-            Some(r) => (r.entry_hash, r.sequence + 1),
-            None => ("0x00".to_string(), 1),
+        let (read_format, _) = rehash_db::formats(&self.pool).await?;
+        let served = if read_format == HashFormat::ORIGINAL {
+            None
+        } else {
+            rehash_db::entry_hashes(&self.pool, read_format, container_id, rec.sequence).await?
         };
+        let (previous_hash, entry_hash) = match served {
+            Some(h) => (h.previous_hash, h.entry_hash),
+            None => (rec.previous_hash, rec.entry_hash),
+        };
+        Ok(LedgerEntry {
+            container_id: container_id.to_string(),
+            sequence: rec.sequence,
+            link_hash: rec.link_hash,
+            previous_hash,
+            entry_hash,
+            ts_unix_ms: rec.ts_unix_ms,
+        })
+    }
+}
 
-        // Validate causality (SPEC-UBL-MEMBRANE v1.0 §V4)
-        if link.previous_hash != expected_prev {
-            return Err(TangencyError::RealityDrift);
-        }
+/// Validate `link` against its container's head and append it inside `tx`;
+/// nothing is visible until the caller commits. Appends to several
+/// containers in one transaction (`settlement.rs`) lock their heads in call
+/// order.
+pub async fn append_in(
+    tx: &mut Transaction<'static, Postgres>,
+    link: &LinkDraft,
+    trace: &mut PipelineTrace,
+) -> Result<LedgerEntry, TangencyError> {
+    let t = Instant::now();
+    // Lock and get latest entry (FOR UPDATE)
+    let rec = sqlx::query!(
+        r#"
+        SELECT sequence, entry_hash
+        FROM ledger_entry
+        WHERE container_id = $1
+        ORDER BY sequence DESC
+        LIMIT 1
+        FOR UPDATE
+        "#,
+        link.container_id
+    )
+    .fetch_optional(&mut **tx)
+    .await
+    .expect("select last");
 
-        // Validate sequence (SPEC-UBL-MEMBRANE v1.0 §V5)
-        if link.expected_sequence != expected_seq {
-            return Err(TangencyError::SequenceMismatch);
+    // Chains are checked in the format reads are served in; a backfill
+    // target is written alongside so it never falls behind
+    let (read_format, target_format) = rehash_db::formats(&mut **tx).await.expect("hash formats");
+    let mut alt_formats: Vec<HashFormat> = Vec::new();
+    for f in [Some(read_format), target_format].into_iter().flatten() {
+        if f != HashFormat::ORIGINAL && !alt_formats.contains(&f) {
+            alt_formats.push(f);
         }
+    }
+    // Previous hash per alternate format; `None` when the head was never rehashed
+    let mut alt_prev: Vec<(HashFormat, Option<String>)> = Vec::new();
+    for &f in &alt_formats {
+        let prev = match &rec {
+            Some(r) => rehash_db::entry_hashes(&mut **tx, f, &link.container_id, r.sequence)
+                .await
+                .expect("select head hash")
+                .map(|h| h.entry_hash),
+            None => Some(entry_hash::GENESIS.to_string()),
+        };
+        alt_prev.push((f, prev));
+    }
 
-        // Validate version (SPEC-UBL-MEMBRANE v1.0 §V1)
-        if link.version != 1 {
-            return Err(TangencyError::InvalidVersion);
-        }
+    let genesis = rec.is_none();
+    let (original_prev, expected_seq) = match rec {
+        Some(r) => (r.entry_hash, r.sequence + 1),
+        None => (entry_hash::GENESIS.to_string(), 1),
+    };
+    let expected_prev = if read_format == HashFormat::ORIGINAL {
+        Some(original_prev.clone())
+    } else {
+        alt_prev.iter().find(|(f, _)| *f == read_format).and_then(|(_, p)| p.clone())
+    };
+    trace.pass("lock_head", t);
+
+    // Validate causality (SPEC-UBL-MEMBRANE v1.0 §V4)
+    let t = Instant::now();
+    if expected_prev.as_deref() != Some(link.previous_hash.as_str()) {
+        trace.fail("v4_causality", t, "RealityDrift");
+        return Err(TangencyError::RealityDrift);
+    }
+    trace.pass("v4_causality", t);
+
+    // Validate sequence (SPEC-UBL-MEMBRANE v1.0 §V5)
+    let t = Instant::now();
+    if link.expected_sequence != expected_seq {
+        trace.fail("v5_sequence", t, "SequenceMismatch");
+        return Err(TangencyError::SequenceMismatch);
+    }
+    trace.pass("v5_sequence", t);
+
+    // Validate version (SPEC-UBL-MEMBRANE v1.0 §V1) under the container's spec version
+    let t = Instant::now();
+    let spec = if genesis {
+        link.manifest.as_ref().and_then(|m| m.spec_version).unwrap_or(SpecVersion::LATEST)
+    } else {
+        spec_version(&mut **tx, &link.container_id).await.expect("select spec version")
+    };
+    if link.version != ubl_membrane::link_version(spec) {
+        trace.fail("v1_version", t, "InvalidVersion");
+        return Err(TangencyError::InvalidVersion);
+    }
+    trace.pass("v1_version", t);
 
-        // Compute entry_hash = blake3(container_id || sequence || atom_hash || previous_hash || ts)
-        let ts_unix_ms = (OffsetDateTime::now_utc().unix_timestamp_nanos() / 1_000_000) as i64;
-        let mut h = Hasher::new();
-        h.update(link.container_id.as_bytes());
-        h.update(expected_seq.to_string().as_bytes());
-        h.update(link.atom_hash.as_bytes());
-        h.update(expected_prev.as_bytes());
-        h.update(ts_unix_ms.to_string().as_bytes());
-        let entry_hash = hex::encode(h.finalize().as_bytes());
-
-        // Insert new entry (SPEC-UBL-LEDGER v1.0 §7.1 - Append-only)
+    // Compute entry_hash = blake3(container_id || sequence || atom_hash || previous_hash || ts)
+    let t = Instant::now();
+    let ts_unix_ms = (OffsetDateTime::now_utc().unix_timestamp_nanos() / 1_000_000) as i64;
+    let fields = EntryFields {
+        container_id: &link.container_id,
+        sequence: expected_seq,
+        link_hash: &link.atom_hash,
+        ts_unix_ms,
+    };
+    let entry_hash = entry_hash::compute(HashFormat::ORIGINAL, &fields, &original_prev);
+
+    // Insert new entry (SPEC-UBL-LEDGER v1.0 §7.1 - Append-only)
+    sqlx::query!(
+        r#"
+        INSERT INTO ledger_entry (container_id, sequence, link_hash, previous_hash, entry_hash, ts_unix_ms, metadata,
+                                  intent_class, physics_delta)
+        VALUES ($1, $2, $3, $4, $5, $6, COALESCE($9, '{}'::jsonb), $7, $8)
+        "#,
+        link.container_id,
+        expected_seq,
+        link.atom_hash,
+        original_prev,
+        entry_hash,
+        ts_unix_ms,
+        link.intent_class,
+        serde_json::Value::String(link.physics_delta.clone()),
+        link.metadata
+    )
+    .execute(&mut **tx)
+    .await
+    .expect("insert");
+
+    // Governance cross-references land with the entry or not at all
+    for affected in &link.affects {
         sqlx::query!(
             r#"
-            INSERT INTO ledger_entry (container_id, sequence, link_hash, previous_hash, entry_hash, ts_unix_ms, metadata)
-            VALUES ($1, $2, $3, $4, $5, $6, '{}'::jsonb)
+            INSERT INTO governance_ref (governance_container_id, sequence, entry_hash, affected_container_id)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT DO NOTHING
             "#,
             link.container_id,
             expected_seq,
-            link.atom_hash,
-            expected_prev,
             entry_hash,
-            ts_unix_ms
+            affected
         )
-        .execute(&mut *tx)
+        .execute(&mut **tx)
         .await
-        .expect("insert");
-
-        // Commit transaction
-        tx.commit().await.expect("commit");
-
-        Ok(LedgerEntry {
-            container_id: link.container_id.clone(),
-            sequence: expected_seq,
-            link_hash: link.atom_hash.clone(),
-            previous_hash: expected_prev,
-            entry_hash,
-            ts_unix_ms,
-        })
+        .expect("insert governance_ref");
     }
 
-    /// Get current state of container
-    pub async fn get_state(&self, container_id: &str) -> Result<LedgerEntry, sqlx::Error> {
-        let rec = sqlx::query!(
+    // Declared dependencies land with the genesis entry or not at all
+    for dep in link.manifest.iter().flat_map(|m| &m.depends_on) {
+        sqlx::query!(
             r#"
-            SELECT sequence, link_hash, previous_hash, entry_hash, ts_unix_ms
-            FROM ledger_entry
-            WHERE container_id = $1
-            ORDER BY sequence DESC
-            LIMIT 1
+            INSERT INTO container_dependency (container_id, depends_on)
+            VALUES ($1, $2)
+            ON CONFLICT DO NOTHING
             "#,
-            container_id
+            link.container_id,
+            dep
         )
-        .fetch_one(&self.pool)
-        .await?;
+        .execute(&mut **tx)
+        .await
+        .expect("insert container_dependency");
+    }
+    if let Some(pact_id) = link.manifest.as_ref().and_then(|m| m.authority_pact.as_ref()) {
+        sqlx::query!(
+            r#"
+            INSERT INTO container_authority (container_id, pact_id)
+            VALUES ($1, $2)
+            ON CONFLICT DO NOTHING
+            "#,
+            link.container_id,
+            pact_id
+        )
+        .execute(&mut **tx)
+        .await
+        .expect("insert container_authority");
+    }
+    if genesis {
+        sqlx::query!(
+            r#"
+            INSERT INTO container_spec (container_id, spec_version)
+            VALUES ($1, $2)
+            ON CONFLICT DO NOTHING
+            "#,
+            link.container_id,
+            spec.as_str()
+        )
+        .execute(&mut **tx)
+        .await
+        .expect("insert container_spec");
+    }
 
-        Ok(LedgerEntry {
-            container_id: container_id.to_string(),
-            sequence: rec.sequence,
-            link_hash: rec.link_hash,
-            previous_hash: rec.previous_hash,
-            entry_hash: rec.entry_hash,
-            ts_unix_ms: rec.ts_unix_ms,
+    // Alternate formats chain to the head's hash in the same format
+    let mut served = StoredHash {
+        previous_hash: original_prev,
+        entry_hash: entry_hash.clone(),
+    };
+    for (f, prev) in alt_prev {
+        let Some(previous_hash) = prev else { continue };
+        let hashes = StoredHash {
+            entry_hash: entry_hash::compute(f, &fields, &previous_hash),
+            previous_hash,
+        };
+        rehash_db::insert(&mut **tx, f, &link.container_id, expected_seq, &hashes)
+            .await
+            .expect("insert entry hash");
+        if f == read_format {
+            served = hashes;
+        }
+    }
+
+    trace.pass("append", t);
+
+    Ok(LedgerEntry {
+        container_id: link.container_id.clone(),
+        sequence: expected_seq,
+        link_hash: link.atom_hash.clone(),
+        previous_hash: served.previous_hash,
+        entry_hash: served.entry_hash,
+        ts_unix_ms,
+    })
+}
+
+/// Lock `container_id`'s head inside `tx` and return the sequence and
+/// previous hash (in the read format) a link appended next must carry
+pub async fn next_in(tx: &mut Transaction<'static, Postgres>, container_id: &str) -> sqlx::Result<(i64, String)> {
+    let rec = sqlx::query!(
+        "SELECT sequence, entry_hash FROM ledger_entry WHERE container_id = $1 ORDER BY sequence DESC LIMIT 1 FOR UPDATE",
+        container_id
+    )
+    .fetch_optional(&mut **tx)
+    .await?;
+    let Some(rec) = rec else {
+        return Ok((1, entry_hash::GENESIS.to_string()));
+    };
+    let (read_format, _) = rehash_db::formats(&mut **tx).await?;
+    let served = if read_format == HashFormat::ORIGINAL {
+        None
+    } else {
+        rehash_db::entry_hashes(&mut **tx, read_format, container_id, rec.sequence).await?
+    };
+    Ok((rec.sequence + 1, served.map(|h| h.entry_hash).unwrap_or(rec.entry_hash)))
+}
+
+/// Spec version `container_id` is committed under (`container_spec`);
+/// containers created before versions were recorded are v1.0
+pub async fn spec_version(db: impl PgExecutor<'_>, container_id: &str) -> sqlx::Result<SpecVersion> {
+    let declared = sqlx::query_scalar!(
+        "SELECT spec_version FROM container_spec WHERE container_id = $1",
+        container_id
+    )
+    .fetch_optional(db)
+    .await?;
+    match declared {
+        Some(v) => v.parse().map_err(|e: ubl_link::UnknownSpecVersion| sqlx::Error::Decode(Box::new(e))),
+        None => Ok(SpecVersion::V1_0),
+    }
+}
+
+/// Heads committed after feed cursor `after_id`, oldest first.
+/// Rows younger than one second are held back so a slower transaction
+/// holding a lower id can still land before the cursor moves past it.
+pub async fn heads_after(pool: &PgPool, after_id: i64, limit: i64) -> sqlx::Result<Vec<HeadEvent>> {
+    let rows = sqlx::query_as!(
+        HeadEvent,
+        r#"SELECT id, container_id, sequence, entry_hash
+           FROM ledger_entry
+           WHERE id > $1 AND created_at <= now() - interval '1 second'
+           ORDER BY id
+           LIMIT $2"#,
+        after_id,
+        limit
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(rows)
+}
+
+/// Like [`heads_after`], with each entry's row (`row_to_json`)
+pub async fn feed_after(pool: &PgPool, after_id: i64, limit: i64) -> sqlx::Result<Vec<FeedEntry>> {
+    let rows = sqlx::query!(
+        r#"SELECT e.id, e.container_id, e.sequence, e.entry_hash, row_to_json(e)::text AS "row!"
+           FROM ledger_entry e
+           WHERE e.id > $1 AND e.created_at <= now() - interval '1 second'
+           ORDER BY e.id
+           LIMIT $2"#,
+        after_id,
+        limit
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .map(|r| FeedEntry {
+            head: HeadEvent {
+                id: r.id,
+                container_id: r.container_id,
+                sequence: r.sequence,
+                entry_hash: r.entry_hash,
+            },
+            row: r.row,
         })
+        .collect())
+}
+
+/// Highest ledger feed id, where feed followers start
+pub async fn latest_entry_id(pool: &PgPool) -> sqlx::Result<i64> {
+    let r = sqlx::query!(r#"SELECT COALESCE(MAX(id), 0) AS "id!" FROM ledger_entry"#)
+        .fetch_one(pool)
+        .await?;
+    Ok(r.id)
+}
+
+/// Containers with the most recent commits, newest first (from the last 10k entries)
+pub async fn hot_containers(pool: &PgPool, limit: i64) -> sqlx::Result<Vec<String>> {
+    sqlx::query_scalar!(
+        r#"SELECT container_id AS "container_id!" FROM (
+               SELECT container_id, id FROM ledger_entry ORDER BY id DESC LIMIT 10000
+           ) recent
+           GROUP BY container_id
+           ORDER BY max(id) DESC
+           LIMIT $1"#,
+        limit
+    )
+    .fetch_all(pool)
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn draft(expected_sequence: i64, previous_hash: &str) -> LinkDraft {
+        serde_json::from_value(serde_json::json!({
+            "version": 1,
+            "container_id": "C.Test",
+            "expected_sequence": expected_sequence,
+            "previous_hash": previous_hash,
+            "atom_hash": "ab".repeat(32),
+            "intent_class": "Observation",
+            "physics_delta": "0",
+            "author_pubkey": "cd".repeat(32),
+            "signature": "ef".repeat(64),
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_strict_hex_accepts_genesis_on_first_link() {
+        draft(1, entry_hash::GENESIS).check_hex().unwrap();
+        draft(2, &"01".repeat(32)).check_hex().unwrap();
+        assert!(draft(2, entry_hash::GENESIS).check_hex().is_err());
+        assert!(draft(1, "0x01").check_hex().is_err());
     }
 }
@@ -0,0 +1,114 @@
+//! Container dependencies (table `container_dependency`, sql/036_container_dependencies.sql)
+//!
+//! Rows are written by `PgLedger::append` with a container's genesis entry,
+//! from `LinkDraft.manifest`; nothing updates them afterwards.
+
+use serde::Serialize;
+use sqlx::PgPool;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Of `ids`, the containers that have a genesis entry
+pub async fn existing(pool: &PgPool, ids: &[String]) -> sqlx::Result<Vec<String>> {
+    sqlx::query_scalar!(
+        "SELECT container_id FROM ledger_entry WHERE container_id = ANY($1) AND sequence = 1",
+        ids
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Every declared edge as `(container_id, depends_on)`
+pub async fn edges(pool: &PgPool) -> sqlx::Result<Vec<(String, String)>> {
+    let rows = sqlx::query!("SELECT container_id, depends_on FROM container_dependency ORDER BY container_id, depends_on")
+        .fetch_all(pool)
+        .await?;
+    Ok(rows.into_iter().map(|r| (r.container_id, r.depends_on)).collect())
+}
+
+/// One container in the dependency graph
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct Node {
+    pub container_id: String,
+    pub depends_on: Vec<String>,
+    pub dependents: Vec<String>,
+}
+
+/// Dependency graph with a startup order
+#[derive(Debug, Clone, Serialize)]
+pub struct Graph {
+    pub containers: Vec<Node>,
+    /// Every container after all of its dependencies
+    pub startup_order: Vec<String>,
+}
+
+/// Build the graph over every container named by an edge. Containers of
+/// equal rank start in id order, so the same edges always give the same order.
+/// Returns the containers left on a cycle if there is one.
+pub fn graph(edges: &[(String, String)]) -> Result<Graph, Vec<String>> {
+    let mut nodes: BTreeMap<&str, (BTreeSet<&str>, BTreeSet<&str>)> = BTreeMap::new();
+    for (container, dep) in edges {
+        nodes.entry(container).or_default().0.insert(dep);
+        nodes.entry(dep).or_default().1.insert(container);
+    }
+
+    let mut waiting: BTreeMap<&str, usize> = nodes.iter().map(|(&id, (deps, _))| (id, deps.len())).collect();
+    let mut ready: BTreeSet<&str> = waiting.iter().filter(|(_, &n)| n == 0).map(|(&id, _)| id).collect();
+    let mut startup_order = Vec::with_capacity(nodes.len());
+    while let Some(id) = ready.pop_first() {
+        waiting.remove(id);
+        startup_order.push(id.to_string());
+        for &dependent in &nodes[id].1 {
+            let n = waiting.get_mut(dependent).expect("dependent is waiting");
+            *n -= 1;
+            if *n == 0 {
+                ready.insert(dependent);
+            }
+        }
+    }
+    if !waiting.is_empty() {
+        return Err(waiting.into_keys().map(String::from).collect());
+    }
+
+    let containers = nodes
+        .into_iter()
+        .map(|(id, (deps, dependents))| Node {
+            container_id: id.to_string(),
+            depends_on: deps.into_iter().map(String::from).collect(),
+            dependents: dependents.into_iter().map(String::from).collect(),
+        })
+        .collect();
+    Ok(Graph {
+        containers,
+        startup_order,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edge(a: &str, b: &str) -> (String, String) {
+        (a.to_string(), b.to_string())
+    }
+
+    #[test]
+    fn test_startup_order_puts_dependencies_first() {
+        let g = graph(&[
+            edge("C.Escrow", "C.Wallet.alice"),
+            edge("C.Escrow", "C.Wallet.bob"),
+            edge("C.Wallet.bob", "C.Gov"),
+            edge("C.Wallet.alice", "C.Gov"),
+        ])
+        .unwrap();
+        assert_eq!(g.startup_order, ["C.Gov", "C.Wallet.alice", "C.Wallet.bob", "C.Escrow"]);
+        let gov = g.containers.iter().find(|n| n.container_id == "C.Gov").unwrap();
+        assert!(gov.depends_on.is_empty());
+        assert_eq!(gov.dependents, ["C.Wallet.alice", "C.Wallet.bob"]);
+    }
+
+    #[test]
+    fn test_cycle_is_reported() {
+        let err = graph(&[edge("a", "b"), edge("b", "a"), edge("c", "b")]).unwrap_err();
+        assert_eq!(err, ["a", "b", "c"]);
+    }
+}
@@ -0,0 +1,197 @@
+//! # Entry hash formats
+//!
+//! Ledger entries are chained by `entry_hash`. The format an entry was first
+//! hashed with stays in `ledger_entry` forever; when the canonical hashing
+//! rules evolve, entries are rehashed into `ledger_entry_hash` beside the
+//! original (sql/032_entry_hash_formats.sql, `rehash_routes.rs`).
+//!
+//! - `v1`: blake3(container_id ‖ sequence ‖ link_hash ‖ previous_hash ‖ ts_unix_ms),
+//!   numbers as decimal text, no separators
+//! - `v2`: blake3("ubl:entry:v2\n" ‖ canonical JSON of the same five fields)
+
+use std::fmt;
+use std::str::FromStr;
+
+use blake3::Hasher;
+use serde::{Deserialize, Serialize};
+
+/// `previous_hash` of every container's first entry, in every format
+pub const GENESIS: &str = "0x00";
+
+const V2_DOMAIN: &[u8] = b"ubl:entry:v2\n";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HashFormat {
+    V1,
+    V2,
+}
+
+impl HashFormat {
+    /// Format of the hashes stored in `ledger_entry` itself
+    pub const ORIGINAL: HashFormat = HashFormat::V1;
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            HashFormat::V1 => "v1",
+            HashFormat::V2 => "v2",
+        }
+    }
+}
+
+impl fmt::Display for HashFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for HashFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "v1" => Ok(HashFormat::V1),
+            "v2" => Ok(HashFormat::V2),
+            other => Err(format!("unknown hash format: {}", other)),
+        }
+    }
+}
+
+/// The fields an entry hash commits to, apart from the previous hash
+#[derive(Debug, Clone, Copy)]
+pub struct EntryFields<'a> {
+    pub container_id: &'a str,
+    pub sequence: i64,
+    pub link_hash: &'a str,
+    pub ts_unix_ms: i64,
+}
+
+/// Hash of an entry in `format`, chained to `previous_hash` of the same format
+pub fn compute(format: HashFormat, fields: &EntryFields<'_>, previous_hash: &str) -> String {
+    let mut h = Hasher::new();
+    match format {
+        HashFormat::V1 => {
+            h.update(fields.container_id.as_bytes());
+            h.update(fields.sequence.to_string().as_bytes());
+            h.update(fields.link_hash.as_bytes());
+            h.update(previous_hash.as_bytes());
+            h.update(fields.ts_unix_ms.to_string().as_bytes());
+        }
+        HashFormat::V2 => {
+            let body = serde_json::json!({
+                "container_id": fields.container_id,
+                "sequence": fields.sequence,
+                "link_hash": fields.link_hash,
+                "previous_hash": previous_hash,
+                "ts_unix_ms": fields.ts_unix_ms,
+            });
+            h.update(V2_DOMAIN);
+            h.update(&ubl_atom::canonicalize(&body).expect("entry fields are canonical JSON"));
+        }
+    }
+    hex::encode(h.finalize().as_bytes())
+}
+
+/// One entry's stored hashes in a format
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StoredHash {
+    pub previous_hash: String,
+    pub entry_hash: String,
+}
+
+/// First place a container's chain stops verifying
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ChainBreak {
+    pub container_id: String,
+    pub format: HashFormat,
+    pub sequence: i64,
+    pub reason: String,
+}
+
+/// Check one container's chain in `format`, oldest entry first
+///
+/// `stored` holds the entry's hashes in that format (`None` when it has not
+/// been backfilled). Sequences must run from 1 without gaps, each entry must
+/// chain to the previous one, and each hash must recompute.
+pub fn verify_chain<'a>(
+    format: HashFormat,
+    entries: impl IntoIterator<Item = (EntryFields<'a>, Option<StoredHash>)>,
+) -> Result<u64, ChainBreak> {
+    let mut prev = GENESIS.to_string();
+    let mut checked = 0;
+    for (expected_seq, (fields, stored)) in (1..).zip(entries) {
+        let fail = |reason: String| ChainBreak {
+            container_id: fields.container_id.to_string(),
+            format,
+            sequence: fields.sequence,
+            reason,
+        };
+        if fields.sequence != expected_seq {
+            return Err(fail(format!("expected sequence {}", expected_seq)));
+        }
+        let stored = stored.ok_or_else(|| fail("not hashed in this format".to_string()))?;
+        if stored.previous_hash != prev {
+            return Err(fail("previous_hash does not match the prior entry".to_string()));
+        }
+        let computed = compute(format, &fields, &prev);
+        if stored.entry_hash != computed {
+            return Err(fail(format!("entry_hash {} recomputes to {}", stored.entry_hash, computed)));
+        }
+        prev = computed;
+        checked += 1;
+    }
+    Ok(checked)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields(sequence: i64) -> EntryFields<'static> {
+        EntryFields {
+            container_id: "C.Test",
+            sequence,
+            link_hash: "ab",
+            ts_unix_ms: 1_700_000_000_000 + sequence,
+        }
+    }
+
+    fn chain(format: HashFormat, n: i64) -> Vec<(EntryFields<'static>, Option<StoredHash>)> {
+        let mut prev = GENESIS.to_string();
+        (1..=n)
+            .map(|seq| {
+                let entry_hash = compute(format, &fields(seq), &prev);
+                let stored = StoredHash {
+                    previous_hash: std::mem::replace(&mut prev, entry_hash.clone()),
+                    entry_hash,
+                };
+                (fields(seq), Some(stored))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_v1_matches_original_concatenation() {
+        let f = fields(1);
+        let mut h = Hasher::new();
+        h.update(b"C.Test1ab0x001700000000001");
+        assert_eq!(compute(HashFormat::V1, &f, GENESIS), hex::encode(h.finalize().as_bytes()));
+        assert_ne!(compute(HashFormat::V1, &f, GENESIS), compute(HashFormat::V2, &f, GENESIS));
+    }
+
+    #[test]
+    fn test_verify_chain_in_each_format() {
+        for format in [HashFormat::V1, HashFormat::V2] {
+            assert_eq!(verify_chain(format, chain(format, 3)), Ok(3));
+
+            let mut broken = chain(format, 3);
+            broken[1].1.as_mut().unwrap().entry_hash = "00".to_string();
+            assert_eq!(verify_chain(format, broken).unwrap_err().sequence, 2);
+
+            let mut missing = chain(format, 3);
+            missing[2].1 = None;
+            assert_eq!(verify_chain(format, missing).unwrap_err().sequence, 3);
+        }
+        assert!(verify_chain(HashFormat::V2, chain(HashFormat::V1, 2)).is_err());
+    }
+}
@@ -0,0 +1,92 @@
+//! # Error catalog
+//!
+//! - GET /errors    (every error code this deployment can emit; no session needed)
+//!
+//! Each family is generated from the enum that emits it
+//! (`#[derive(ErrorCatalog)]`, see `ubl-errors`): membrane V-codes, pact
+//! errors, policy deny codes, ledger tangency errors and ASC auth errors,
+//! each code with the HTTP status it is answered with, its parameters and
+//! its message template. Handlers answer with the same enums through
+//! [`status`] and `ErrorCatalog::code`, so client SDKs and docs generated
+//! from the catalog follow the code. Rejections that are a bare
+//! `(status, message)` without a code are not listed.
+
+use axum::{
+    http::{HeaderMap, StatusCode},
+    response::Response,
+    routing::get,
+    Router,
+};
+use serde::Serialize;
+use ubl_errors::{ErrorCatalog, ErrorFamily, CATALOG_FORMAT};
+use ubl_membrane::MembraneError;
+use ubl_pact::PactError;
+use ubl_policy_vm::DenyCode;
+
+use crate::auth::AuthError;
+use crate::db::TangencyError;
+use crate::{http_cache, AppState};
+
+#[derive(Debug, Serialize)]
+pub struct Catalog {
+    pub format: &'static str,
+    pub families: Vec<ErrorFamily>,
+}
+
+/// Every family, in the order a commit meets them
+pub fn catalog() -> Catalog {
+    Catalog {
+        format: CATALOG_FORMAT,
+        families: vec![
+            AuthError::family(),
+            MembraneError::family(),
+            PactError::family(),
+            DenyCode::family(),
+            TangencyError::family(),
+        ],
+    }
+}
+
+/// HTTP status a cataloged error is answered with
+pub fn status<E: ErrorCatalog>(e: &E) -> StatusCode {
+    StatusCode::from_u16(e.status()).expect("the derive only accepts HTTP statuses")
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new().route("/errors", get(route_errors))
+}
+
+/// GET /errors
+async fn route_errors(headers: HeaderMap) -> Response {
+    http_cache::respond("errors", &headers, http_cache::SHORT, catalog())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_catalog_matches_responses() {
+        let catalog = catalog();
+        let sources: Vec<_> = catalog.families.iter().map(|f| f.source).collect();
+        assert_eq!(sources, ["auth", "membrane", "pact", "policy", "tangency"]);
+
+        let code = |source: &str, code: &str| {
+            catalog
+                .families
+                .iter()
+                .find(|f| f.source == source)
+                .and_then(|f| f.codes.iter().find(|c| c.code == code))
+                .copied()
+                .unwrap()
+        };
+        let fenced = TangencyError::Fenced;
+        assert_eq!((fenced.code(), status(&fenced)), ("Fenced", StatusCode::SERVICE_UNAVAILABLE));
+        assert_eq!(code("tangency", "Fenced").status, 503);
+        assert_eq!(code("tangency", "NotCommitted").params, ["0"]);
+        assert_eq!(code("membrane", "V4").name, "RealityDrift");
+        assert_eq!(code("pact", "InsufficientSignatures").params, ["got", "need"]);
+        assert_eq!(code("policy", "amount_exceeded").status, 403);
+        assert_eq!(status(&AuthError::ScopeViolation("C.Other".to_string())), StatusCode::FORBIDDEN);
+    }
+}
@@ -0,0 +1,299 @@
+//! # Internal event bus
+//!
+//! Typed in-process fan-out between the append path and the subsystems that
+//! react to commits, so none of them opens its own Postgres listener or is
+//! called from the commit path:
+//!
+//! | Event                   | Published by                          | Carries                                   |
+//! |-------------------------|---------------------------------------|-------------------------------------------|
+//! | [`BusEvent::Committed`] | the append path on this instance      | the entry just committed (advisory)       |
+//! | [`BusEvent::Entry`]     | the feed task ([`spawn_feed`])        | the next entry of the deployment-wide feed |
+//!
+//! `Committed` goes out as soon as a commit (link, settlement, approved
+//! Evolution, policy audit rejection) lands; it never waits. `Entry` is the
+//! gap-free feed: every instance's commits in `ledger_entry.id` order, read
+//! from Postgres with the same one-second hold-back as `/ledger/heads/tail`.
+//! The feed task is the one `LISTEN ledger_heads` per process, and a local
+//! `Committed` wakes it too.
+//!
+//! Subscribers choose what happens when they fall behind ([`Overflow`]):
+//!
+//! - `Skip`: a shared ring of [`RING_CAPACITY`] events. A slow subscriber
+//!   loses the oldest and is told how many ([`RecvError::Lagged`]); it
+//!   resyncs from Postgres by feed cursor. Publishing never waits.
+//! - `Block(n)`: a queue of its own, `n` deep, that sees `Entry` events only.
+//!   The feed task waits for room, so a slow subscriber holds the feed back
+//!   (and `Skip` subscribers lag) rather than missing an entry.
+//!
+//! Subscribers: the SSE tail and heads streams (`sse.rs`, `Skip`) and the
+//! alert engine (`alert_routes.rs`, `Block`). There are no webhooks,
+//! projections, outbox dispatcher or anomaly detection in the server yet;
+//! they would subscribe the same way. Events are counted in
+//! `ubl_bus_events_total{event}` and lag in `ubl_bus_lagged_total{subscriber}`.
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use sqlx::{postgres::PgListener, PgPool};
+use tokio::sync::{broadcast, mpsc};
+use tracing::{debug, error};
+
+use crate::db::{self, FeedEntry, LedgerEntry};
+use crate::metrics;
+
+/// Events the `Skip` ring holds before the slowest subscriber lags
+pub const RING_CAPACITY: usize = 1024;
+
+/// Rows the feed task reads per query
+const FEED_BATCH: i64 = 500;
+
+#[derive(Debug, Clone)]
+pub enum BusEvent {
+    /// Committed on this instance; not yet in the feed
+    Committed(Arc<LedgerEntry>),
+    /// Next entry of the deployment-wide feed
+    Entry(Arc<FeedEntry>),
+}
+
+impl BusEvent {
+    fn kind(&self) -> &'static str {
+        match self {
+            BusEvent::Committed(_) => "committed",
+            BusEvent::Entry(_) => "entry",
+        }
+    }
+}
+
+/// What a subscriber that falls behind gets
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Overflow {
+    /// Miss the oldest events and hear how many
+    Skip,
+    /// Hold the feed back once this many entries are queued
+    Block(usize),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum RecvError {
+    /// This many events were dropped; resync from Postgres
+    Lagged(u64),
+    /// The bus is gone
+    Closed,
+}
+
+enum Receiver {
+    Ring(broadcast::Receiver<BusEvent>),
+    Queue(mpsc::Receiver<BusEvent>),
+}
+
+/// One subscriber's end of the bus
+pub struct Subscription {
+    name: &'static str,
+    rx: Receiver,
+}
+
+impl Subscription {
+    pub async fn recv(&mut self) -> Result<BusEvent, RecvError> {
+        match &mut self.rx {
+            Receiver::Ring(rx) => match rx.recv().await {
+                Ok(event) => Ok(event),
+                Err(broadcast::error::RecvError::Lagged(n)) => {
+                    metrics::BUS_LAGGED.with_label_values(&[self.name]).inc_by(n);
+                    Err(RecvError::Lagged(n))
+                }
+                Err(broadcast::error::RecvError::Closed) => Err(RecvError::Closed),
+            },
+            Receiver::Queue(rx) => rx.recv().await.ok_or(RecvError::Closed),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct EventBus {
+    ring: broadcast::Sender<BusEvent>,
+    queues: Arc<Mutex<Vec<mpsc::Sender<BusEvent>>>>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        let (ring, _) = broadcast::channel(RING_CAPACITY);
+        Self {
+            ring,
+            queues: Arc::default(),
+        }
+    }
+
+    /// Subscribe `name` (a metrics label) with an overflow policy
+    pub fn subscribe(&self, name: &'static str, overflow: Overflow) -> Subscription {
+        let rx = match overflow {
+            Overflow::Skip => Receiver::Ring(self.ring.subscribe()),
+            Overflow::Block(capacity) => {
+                let (tx, rx) = mpsc::channel(capacity);
+                self.queues.lock().expect("bus queues").push(tx);
+                Receiver::Queue(rx)
+            }
+        };
+        Subscription { name, rx }
+    }
+
+    /// The append path: `entry` was just committed here. Never waits.
+    pub fn committed(&self, entry: &LedgerEntry) {
+        self.send_ring(BusEvent::Committed(Arc::new(entry.clone())));
+    }
+
+    /// The feed task: publish the next feed entry, waiting for room in
+    /// every `Block` queue
+    pub async fn publish_entry(&self, entry: FeedEntry) {
+        let event = BusEvent::Entry(Arc::new(entry));
+        let queues = self.queues.lock().expect("bus queues").clone();
+        let mut closed = false;
+        for queue in &queues {
+            closed |= queue.send(event.clone()).await.is_err();
+        }
+        if closed {
+            self.queues.lock().expect("bus queues").retain(|q| !q.is_closed());
+        }
+        self.send_ring(event);
+    }
+
+    fn send_ring(&self, event: BusEvent) {
+        metrics::BUS_EVENTS.with_label_values(&[event.kind()]).inc();
+        // No `Skip` subscriber is not an error
+        let _ = self.ring.send(event);
+    }
+}
+
+/// Wait for a local commit, skipping feed entries
+async fn next_commit(sub: &mut Subscription) {
+    while let Ok(BusEvent::Entry(_)) = sub.recv().await {}
+}
+
+/// Run the feed task: publish every entry committed after startup, from
+/// any instance, as [`BusEvent::Entry`] in feed order
+pub fn spawn_feed(bus: EventBus, pool: PgPool) {
+    tokio::spawn(async move {
+        let mut cursor = match db::latest_entry_id(&pool).await {
+            Ok(c) => c,
+            Err(e) => {
+                error!("event bus: cannot read feed cursor: {}", e);
+                return;
+            }
+        };
+        let mut listener = match PgListener::connect_with(&pool).await {
+            Ok(mut l) => match l.listen("ledger_heads").await {
+                Ok(()) => Some(l),
+                Err(e) => {
+                    error!("event bus: LISTEN ledger_heads failed, polling: {}", e);
+                    None
+                }
+            },
+            Err(e) => {
+                error!("event bus: cannot create PgListener, polling: {}", e);
+                None
+            }
+        };
+        let mut local = bus.subscribe("feed", Overflow::Skip);
+        debug!("🚌 Event bus feed from cursor {}", cursor);
+
+        loop {
+            let batch = match db::feed_after(&pool, cursor, FEED_BATCH).await {
+                Ok(b) => b,
+                Err(e) => {
+                    error!("event bus: feed query failed: {}", e);
+                    tokio::time::sleep(Duration::from_millis(500)).await;
+                    continue;
+                }
+            };
+            let full = batch.len() as i64 == FEED_BATCH;
+            for entry in batch {
+                cursor = entry.head.id;
+                bus.publish_entry(entry).await;
+            }
+            if full {
+                continue;
+            }
+            // Wake on NOTIFY or a local commit, or re-poll once rows age
+            // past the hold-back window
+            let wake = async {
+                match listener.as_mut() {
+                    Some(l) => {
+                        tokio::select! {
+                            _ = l.recv() => {}
+                            _ = next_commit(&mut local) => {}
+                        }
+                    }
+                    None => next_commit(&mut local).await,
+                }
+            };
+            let _ = tokio::time::timeout(Duration::from_secs(1), wake).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(id: i64) -> FeedEntry {
+        FeedEntry {
+            head: db::HeadEvent {
+                id,
+                container_id: "C.Test".to_string(),
+                sequence: id,
+                entry_hash: format!("{:02x}", id),
+            },
+            row: "{}".to_string(),
+        }
+    }
+
+    fn id(event: BusEvent) -> i64 {
+        match event {
+            BusEvent::Entry(e) => e.head.id,
+            BusEvent::Committed(_) => panic!("expected a feed entry"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_skip_subscriber_lags() {
+        let bus = EventBus::new();
+        let mut sub = bus.subscribe("test", Overflow::Skip);
+        for i in 0..RING_CAPACITY as i64 + 3 {
+            bus.publish_entry(entry(i)).await;
+        }
+        assert_eq!(sub.recv().await.unwrap_err(), RecvError::Lagged(3));
+        assert_eq!(id(sub.recv().await.unwrap()), 3);
+    }
+
+    #[tokio::test]
+    async fn test_block_subscriber_holds_feed_back() {
+        let bus = EventBus::new();
+        let mut sub = bus.subscribe("test", Overflow::Block(2));
+        bus.publish_entry(entry(1)).await;
+        bus.publish_entry(entry(2)).await;
+        let third = tokio::time::timeout(Duration::from_millis(50), bus.publish_entry(entry(3)));
+        assert!(third.await.is_err());
+        assert_eq!(id(sub.recv().await.unwrap()), 1);
+        bus.publish_entry(entry(3)).await;
+        assert_eq!(id(sub.recv().await.unwrap()), 2);
+        assert_eq!(id(sub.recv().await.unwrap()), 3);
+
+        // Committed events skip the queues
+        bus.committed(&LedgerEntry {
+            container_id: "C.Test".to_string(),
+            sequence: 4,
+            link_hash: String::new(),
+            previous_hash: String::new(),
+            entry_hash: String::new(),
+            ts_unix_ms: 0,
+        });
+        drop(sub);
+        bus.publish_entry(entry(4)).await;
+        assert!(bus.queues.lock().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,90 @@
+//! Contract tests: the server's own response and stream types, serialized
+//! as they go out, must round-trip exactly through `ubl-events`.
+
+use serde::{de::DeserializeOwned, Serialize};
+use std::time::Instant;
+use time::OffsetDateTime;
+use ubl_policy_vm::DenyCode;
+
+use crate::alert_db::AlertNotification;
+use crate::db::{HeadEvent, LedgerEntry};
+use crate::pipeline::PipelineTrace;
+use crate::{CommitFailure, CommitSuccess};
+
+/// Serialize `sent`, read it as `C`, and require the same JSON back
+fn assert_contract<S: Serialize, C: DeserializeOwned + Serialize>(sent: &S) {
+    let wire = serde_json::to_value(sent).unwrap();
+    let parsed: C = serde_json::from_value(wire.clone())
+        .unwrap_or_else(|e| panic!("{} does not read {}: {}", std::any::type_name::<C>(), wire, e));
+    assert_eq!(serde_json::to_value(&parsed).unwrap(), wire, "{}", std::any::type_name::<C>());
+}
+
+fn entry() -> LedgerEntry {
+    LedgerEntry {
+        container_id: "C.Bank".to_string(),
+        sequence: 7,
+        link_hash: "ab".repeat(32),
+        previous_hash: "cd".repeat(32),
+        entry_hash: "ef".repeat(32),
+        ts_unix_ms: 1_767_225_600_123,
+    }
+}
+
+fn trace() -> PipelineTrace {
+    let mut trace = PipelineTrace::new();
+    trace.pass("v6_profile", Instant::now());
+    trace.skip("manifest", "no manifest");
+    trace.fail("policy", Instant::now(), "policy denied: amount_exceeded");
+    trace
+}
+
+#[test]
+fn test_commit_receipt_contract() {
+    for trace in [None, Some(trace().into_stages())] {
+        assert_contract::<_, ubl_events::CommitReceipt>(&CommitSuccess {
+            ok: true,
+            entry: entry(),
+            consistency_token: "C.Bank@7".to_string(),
+            trace,
+        });
+    }
+}
+
+#[test]
+fn test_commit_rejection_contract() {
+    for deny_code in [None, Some(DenyCode::AmountExceeded), Some(DenyCode::OutsideSchedule)] {
+        assert_contract::<_, ubl_events::CommitRejection>(&CommitFailure {
+            ok: false,
+            error: "policy denied".to_string(),
+            deny_code,
+            trace: Some(trace().into_stages()),
+        });
+    }
+}
+
+#[test]
+fn test_head_event_contract() {
+    assert_contract::<_, ubl_events::HeadEvent>(&HeadEvent {
+        id: 42,
+        container_id: "C.Bank".to_string(),
+        sequence: 7,
+        entry_hash: "ef".repeat(32),
+    });
+}
+
+#[test]
+fn test_alert_notification_contract() {
+    let created_at = OffsetDateTime::from_unix_timestamp(1_767_225_600).unwrap();
+    for read_at in [None, Some(created_at)] {
+        assert_contract::<_, ubl_events::AlertNotification>(&AlertNotification {
+            id: 3,
+            alert_id: uuid::Uuid::nil(),
+            container_id: "C.Bank".to_string(),
+            kind: "delta_above".to_string(),
+            message: "delta 500 above 100".to_string(),
+            detail: serde_json::json!({ "delta": "500" }),
+            created_at,
+            read_at,
+        });
+    }
+}
@@ -0,0 +1,161 @@
+//! Evolution proposals awaiting governance approval (tables
+//! `evolution_proposal`, `evolution_approval`, sql/034_evolution_queue.sql)
+
+use serde::Serialize;
+use sqlx::{PgExecutor, PgPool};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EvolutionProposal {
+    pub proposal_id: Uuid,
+    pub container_id: String,
+    /// The draft as submitted; appended unchanged once approved
+    pub link: serde_json::Value,
+    pub submitted_by: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub submitted_at: OffsetDateTime,
+    pub approvals_required: i32,
+    pub status: String,
+    pub resolved_by: Option<String>,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub resolved_at: Option<OffsetDateTime>,
+    pub reason: Option<String>,
+    pub applied_sequence: Option<i64>,
+    pub applied_entry_hash: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Approval {
+    pub approver_sid: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub approved_at: OffsetDateTime,
+}
+
+pub async fn insert(
+    pool: &PgPool,
+    container_id: &str,
+    link: &serde_json::Value,
+    submitted_by: &str,
+    approvals_required: i32,
+) -> sqlx::Result<EvolutionProposal> {
+    sqlx::query_as!(
+        EvolutionProposal,
+        r#"INSERT INTO evolution_proposal (container_id, link, submitted_by, approvals_required)
+           VALUES ($1, $2, $3, $4)
+           RETURNING proposal_id, container_id, link, submitted_by, submitted_at, approvals_required,
+                     status, resolved_by, resolved_at, reason, applied_sequence, applied_entry_hash"#,
+        container_id,
+        link,
+        submitted_by,
+        approvals_required
+    )
+    .fetch_one(pool)
+    .await
+}
+
+pub async fn get(db: impl PgExecutor<'_>, proposal_id: Uuid) -> sqlx::Result<Option<EvolutionProposal>> {
+    sqlx::query_as!(
+        EvolutionProposal,
+        r#"SELECT proposal_id, container_id, link, submitted_by, submitted_at, approvals_required,
+                  status, resolved_by, resolved_at, reason, applied_sequence, applied_entry_hash
+           FROM evolution_proposal WHERE proposal_id = $1"#,
+        proposal_id
+    )
+    .fetch_optional(db)
+    .await
+}
+
+/// Lock a proposal for the rest of the transaction
+pub async fn lock(db: impl PgExecutor<'_>, proposal_id: Uuid) -> sqlx::Result<Option<EvolutionProposal>> {
+    sqlx::query_as!(
+        EvolutionProposal,
+        r#"SELECT proposal_id, container_id, link, submitted_by, submitted_at, approvals_required,
+                  status, resolved_by, resolved_at, reason, applied_sequence, applied_entry_hash
+           FROM evolution_proposal WHERE proposal_id = $1
+           FOR UPDATE"#,
+        proposal_id
+    )
+    .fetch_optional(db)
+    .await
+}
+
+/// Newest first, optionally narrowed by status and container
+pub async fn list(
+    pool: &PgPool,
+    status: Option<&str>,
+    container_id: Option<&str>,
+    limit: i64,
+) -> sqlx::Result<Vec<EvolutionProposal>> {
+    sqlx::query_as!(
+        EvolutionProposal,
+        r#"SELECT proposal_id, container_id, link, submitted_by, submitted_at, approvals_required,
+                  status, resolved_by, resolved_at, reason, applied_sequence, applied_entry_hash
+           FROM evolution_proposal
+           WHERE ($1::text IS NULL OR status = $1) AND ($2::text IS NULL OR container_id = $2)
+           ORDER BY submitted_at DESC
+           LIMIT $3"#,
+        status,
+        container_id,
+        limit
+    )
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn approvals(db: impl PgExecutor<'_>, proposal_id: Uuid) -> sqlx::Result<Vec<Approval>> {
+    sqlx::query_as!(
+        Approval,
+        r#"SELECT approver_sid, approved_at FROM evolution_approval
+           WHERE proposal_id = $1 ORDER BY approved_at, approver_sid"#,
+        proposal_id
+    )
+    .fetch_all(db)
+    .await
+}
+
+/// Record an approval; false if this SID had already approved
+pub async fn approve(db: impl PgExecutor<'_>, proposal_id: Uuid, approver_sid: &str) -> sqlx::Result<bool> {
+    let r = sqlx::query!(
+        r#"INSERT INTO evolution_approval (proposal_id, approver_sid) VALUES ($1, $2)
+           ON CONFLICT DO NOTHING"#,
+        proposal_id,
+        approver_sid
+    )
+    .execute(db)
+    .await?;
+    Ok(r.rows_affected() == 1)
+}
+
+/// Close a pending proposal as `applied`, `rejected` or `failed`
+pub async fn resolve(
+    db: impl PgExecutor<'_>,
+    proposal_id: Uuid,
+    status: &str,
+    resolved_by: &str,
+    reason: Option<&str>,
+    applied: Option<(i64, &str)>,
+) -> sqlx::Result<()> {
+    sqlx::query!(
+        r#"UPDATE evolution_proposal
+           SET status = $2, resolved_by = $3, resolved_at = now(), reason = $4,
+               applied_sequence = $5, applied_entry_hash = $6
+           WHERE proposal_id = $1 AND status = 'pending'"#,
+        proposal_id,
+        status,
+        resolved_by,
+        reason,
+        applied.map(|(seq, _)| seq),
+        applied.map(|(_, hash)| hash)
+    )
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+/// Proposals still waiting on approvals
+pub async fn pending_count(pool: &PgPool) -> sqlx::Result<i64> {
+    sqlx::query_scalar!(r#"SELECT count(*) AS "n!" FROM evolution_proposal WHERE status = 'pending'"#)
+        .fetch_one(pool)
+        .await
+}
@@ -0,0 +1,316 @@
+//! # Evolution governance queue
+//!
+//! With `UBL_EVOLUTION_APPROVALS` set to N > 0, `POST /link/commit` never
+//! appends an Evolution-class draft. The draft passes the usual checks
+//! (ASC, profile, policy), then waits here as a proposal, and the commit
+//! answers 202 with it. The proposal is appended once N distinct SIDs have
+//! approved it, none of them the submitter. No single key-holder can apply
+//! a rule change alone, admins included.
+//!
+//! - GET  /governance/evolutions?status=&container_id=  (admin/operator/auditor)
+//! - GET  /governance/evolutions/:proposal_id           (admin/operator/auditor)
+//! - POST /governance/evolutions/:proposal_id/approve   (admin/operator)
+//! - POST /governance/evolutions/:proposal_id/reject    (admin/operator, or the submitter)
+//!
+//! The approval that reaches N appends the draft exactly as submitted. The
+//! profile and policy checks run again first. The entry's `metadata.governance`
+//! names the proposal, its submitter and its approvers. If the container has
+//! moved on, or the policy now refuses the draft, the proposal closes as
+//! `failed` with the reason and must be resubmitted. Approvals are append-only.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+use ubl_errors::ErrorCatalog;
+use uuid::Uuid;
+
+use crate::auth::{rbac, session_policy};
+use crate::db::{LedgerEntry, LinkDraft};
+use crate::evolution_db::{self, Approval, EvolutionProposal};
+use crate::pipeline::{PipelineTrace, StageRecord};
+use crate::AppState;
+
+const MAX_LIST: i64 = 200;
+
+/// Approvals required from `UBL_EVOLUTION_APPROVALS` (unset or 0: Evolution commits append directly)
+pub fn approvals_from_env() -> Option<i32> {
+    std::env::var("UBL_EVOLUTION_APPROVALS")
+        .ok()
+        .and_then(|n| n.parse().ok())
+        .filter(|&n| n > 0)
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProposalView {
+    #[serde(flatten)]
+    pub proposal: EvolutionProposal,
+    pub approvals: Vec<Approval>,
+}
+
+#[derive(Debug, Serialize)]
+struct Queued {
+    ok: bool,
+    queued: bool,
+    proposal: ProposalView,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    trace: Option<Vec<StageRecord>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ApproveResp {
+    pub proposal: ProposalView,
+    /// The appended entry, once the last approval lands
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entry: Option<LedgerEntry>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct ListQuery {
+    pub status: Option<String>,
+    pub container_id: Option<String>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct RejectReq {
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/governance/evolutions", get(route_list))
+        .route("/governance/evolutions/:proposal_id", get(route_get))
+        .route("/governance/evolutions/:proposal_id/approve", post(route_approve))
+        .route("/governance/evolutions/:proposal_id/reject", post(route_reject))
+}
+
+fn internal(e: impl ToString) -> (StatusCode, String) {
+    (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+}
+
+async fn view(state: &AppState, proposal: EvolutionProposal) -> Result<ProposalView, (StatusCode, String)> {
+    let approvals = evolution_db::approvals(&state.pool, proposal.proposal_id)
+        .await
+        .map_err(internal)?;
+    Ok(ProposalView { proposal, approvals })
+}
+
+/// Hold a checked Evolution draft for approval (called from `POST /link/commit`)
+pub async fn enqueue(
+    state: &AppState,
+    headers: &HeaderMap,
+    link: &LinkDraft,
+    approvals_required: i32,
+    debug: bool,
+    mut trace: PipelineTrace,
+) -> Result<Response, (StatusCode, String)> {
+    let t = std::time::Instant::now();
+    // Four eyes need to know whose the first pair was
+    let submitter = rbac::authenticate(&state.pool, headers).await?;
+    let draft = serde_json::to_value(link).map_err(internal)?;
+    let proposal = evolution_db::insert(
+        &state.pool,
+        &link.container_id,
+        &draft,
+        &submitter.session.sid,
+        approvals_required,
+    )
+    .await
+    .map_err(internal)?;
+    trace.pass("governance_queue", t);
+
+    info!(
+        "🗳️  EVOLUTION queued proposal={} container={} by={} approvals={}",
+        proposal.proposal_id, proposal.container_id, proposal.submitted_by, approvals_required
+    );
+    let body = Queued {
+        ok: true,
+        queued: true,
+        proposal: ProposalView {
+            proposal,
+            approvals: Vec::new(),
+        },
+        trace: debug.then(|| trace.into_stages()),
+    };
+    Ok((StatusCode::ACCEPTED, Json(body)).into_response())
+}
+
+/// GET /governance/evolutions
+async fn route_list(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<ListQuery>,
+) -> Result<Json<Vec<EvolutionProposal>>, (StatusCode, String)> {
+    rbac::require_role(&state.pool, &headers, &[rbac::ADMIN, rbac::OPERATOR, rbac::AUDITOR]).await?;
+    let limit = query.limit.unwrap_or(50).clamp(1, MAX_LIST);
+    evolution_db::list(&state.pool, query.status.as_deref(), query.container_id.as_deref(), limit)
+        .await
+        .map(Json)
+        .map_err(internal)
+}
+
+/// GET /governance/evolutions/:proposal_id
+async fn route_get(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(proposal_id): Path<Uuid>,
+) -> Result<Json<ProposalView>, (StatusCode, String)> {
+    rbac::require_role(&state.pool, &headers, &[rbac::ADMIN, rbac::OPERATOR, rbac::AUDITOR]).await?;
+    let proposal = evolution_db::get(&state.pool, proposal_id)
+        .await
+        .map_err(internal)?
+        .ok_or((StatusCode::NOT_FOUND, "proposal not found".to_string()))?;
+    Ok(Json(view(&state, proposal).await?))
+}
+
+/// Re-check and append an approved draft; `Err` is the reason it failed
+async fn apply(state: &AppState, proposal: &EvolutionProposal, approvers: &[String]) -> Result<LedgerEntry, String> {
+    let mut link: LinkDraft =
+        serde_json::from_value(proposal.link.clone()).map_err(|e| format!("stored draft unreadable: {}", e))?;
+    crate::check_profile(&link)?;
+    crate::pact_routes::admit(state, &link).await.map_err(|(_, reason)| reason)?;
+    let decided_by = crate::check_policy(state, &link).await.map_err(|(_, reason, _)| reason)?;
+    let mut metadata = serde_json::json!({
+        "governance": {
+            "proposal_id": proposal.proposal_id,
+            "submitted_by": proposal.submitted_by,
+            "approved_by": approvers,
+        }
+    });
+    if let Some(decided_by) = decided_by {
+        metadata["policy"] = serde_json::json!({ "decided_by": decided_by });
+    }
+    link.metadata = Some(metadata);
+    state
+        .ledger
+        .append(&link, &mut PipelineTrace::new())
+        .await
+        .map_err(|e| e.code().to_string())
+}
+
+/// POST /governance/evolutions/:proposal_id/approve
+async fn route_approve(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(proposal_id): Path<Uuid>,
+) -> Result<Json<ApproveResp>, (StatusCode, String)> {
+    let caller = rbac::require_role(&state.pool, &headers, &[rbac::ADMIN, rbac::OPERATOR]).await?;
+    let sid = caller.session.sid.clone();
+
+    let mut tx = state.pool.begin().await.map_err(internal)?;
+    let proposal = evolution_db::lock(&mut *tx, proposal_id)
+        .await
+        .map_err(internal)?
+        .ok_or((StatusCode::NOT_FOUND, "proposal not found".to_string()))?;
+    if proposal.status != "pending" {
+        return Err((StatusCode::CONFLICT, format!("proposal is {}", proposal.status)));
+    }
+    if proposal.submitted_by == sid {
+        warn!(proposal_id = %proposal_id, sid = %sid, decision = "reject", error_code = "self_approval");
+        return Err((StatusCode::FORBIDDEN, "the submitter cannot approve their own proposal".to_string()));
+    }
+    if !evolution_db::approve(&mut *tx, proposal_id, &sid).await.map_err(internal)? {
+        return Err((StatusCode::CONFLICT, "already approved by this SID".to_string()));
+    }
+    let approvers: Vec<String> = evolution_db::approvals(&mut *tx, proposal_id)
+        .await
+        .map_err(internal)?
+        .into_iter()
+        .map(|a| a.approver_sid)
+        .collect();
+    info!(
+        "🗳️  EVOLUTION approval proposal={} by={} ({}/{})",
+        proposal_id,
+        sid,
+        approvers.len(),
+        proposal.approvals_required
+    );
+
+    let mut entry = None;
+    if approvers.len() >= proposal.approvals_required as usize {
+        match apply(&state, &proposal, &approvers).await {
+            Ok(e) => {
+                evolution_db::resolve(
+                    &mut *tx,
+                    proposal_id,
+                    "applied",
+                    &sid,
+                    None,
+                    Some((e.sequence, &e.entry_hash)),
+                )
+                .await
+                .map_err(internal)?;
+                info!("✅ EVOLUTION applied proposal={} seq={} container={}", proposal_id, e.sequence, e.container_id);
+                entry = Some(e);
+            }
+            Err(reason) => {
+                warn!(proposal_id = %proposal_id, reason = %reason, decision = "reject", error_code = "evolution_apply_failed");
+                evolution_db::resolve(&mut *tx, proposal_id, "failed", &sid, Some(&reason), None)
+                    .await
+                    .map_err(internal)?;
+            }
+        }
+    }
+    tx.commit().await.map_err(internal)?;
+    if let Some(e) = &entry {
+        state.bus.committed(e);
+    }
+
+    session_policy::after_action(&state.pool, &caller.session, session_policy::RISK_L4).await;
+    let proposal = evolution_db::get(&state.pool, proposal_id)
+        .await
+        .map_err(internal)?
+        .ok_or((StatusCode::NOT_FOUND, "proposal not found".to_string()))?;
+    Ok(Json(ApproveResp {
+        proposal: view(&state, proposal).await?,
+        entry,
+    }))
+}
+
+/// POST /governance/evolutions/:proposal_id/reject
+async fn route_reject(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(proposal_id): Path<Uuid>,
+    body: Option<Json<RejectReq>>,
+) -> Result<Json<ProposalView>, (StatusCode, String)> {
+    let caller = rbac::authenticate(&state.pool, &headers).await?;
+    let req = body.map(|Json(r)| r).unwrap_or_default();
+
+    let mut tx = state.pool.begin().await.map_err(internal)?;
+    let proposal = evolution_db::lock(&mut *tx, proposal_id)
+        .await
+        .map_err(internal)?
+        .ok_or((StatusCode::NOT_FOUND, "proposal not found".to_string()))?;
+    if proposal.submitted_by != caller.session.sid && !caller.has_any(&[rbac::ADMIN, rbac::OPERATOR]) {
+        return Err((StatusCode::FORBIDDEN, "requires role: admin|operator, or the submitter".to_string()));
+    }
+    if proposal.status != "pending" {
+        return Err((StatusCode::CONFLICT, format!("proposal is {}", proposal.status)));
+    }
+    evolution_db::resolve(
+        &mut *tx,
+        proposal_id,
+        "rejected",
+        &caller.session.sid,
+        req.reason.as_deref(),
+        None,
+    )
+    .await
+    .map_err(internal)?;
+    tx.commit().await.map_err(internal)?;
+
+    info!("🗳️  EVOLUTION rejected proposal={} by={}", proposal_id, caller.session.sid);
+    let proposal = evolution_db::get(&state.pool, proposal_id)
+        .await
+        .map_err(internal)?
+        .ok_or((StatusCode::NOT_FOUND, "proposal not found".to_string()))?;
+    Ok(Json(view(&state, proposal).await?))
+}
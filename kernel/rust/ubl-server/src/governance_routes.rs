@@ -0,0 +1,66 @@
+//! # Governance Routes
+//!
+//! Governance actions are committed to `gov://…` containers (zero-delta,
+//! Observation/Evolution only). Each such entry may name the operational
+//! containers it affects; this module exposes that history per container.
+//!
+//! - GET /governance/:container_id/history
+
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::Response,
+    routing::get,
+    Router,
+};
+use serde::Serialize;
+use sqlx::PgPool;
+
+use crate::{http_cache, AppState};
+
+#[derive(Debug, Serialize)]
+pub struct GovernanceRef {
+    pub governance_container_id: String,
+    pub sequence: i64,
+    pub entry_hash: String,
+    pub created_at_unix: i64,
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new().route("/governance/:container_id/history", get(route_history))
+}
+
+/// Governance entries that reference `container_id`, oldest first
+pub async fn history(pool: &PgPool, container_id: &str) -> sqlx::Result<Vec<GovernanceRef>> {
+    let rows = sqlx::query!(
+        r#"SELECT governance_container_id, sequence, entry_hash, created_at
+           FROM governance_ref
+           WHERE affected_container_id = $1
+           ORDER BY created_at, governance_container_id, sequence"#,
+        container_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|r| GovernanceRef {
+            governance_container_id: r.governance_container_id,
+            sequence: r.sequence,
+            entry_hash: r.entry_hash,
+            created_at_unix: r.created_at.unix_timestamp(),
+        })
+        .collect())
+}
+
+/// GET /governance/:container_id/history
+async fn route_history(
+    State(state): State<AppState>,
+    Path(container_id): Path<String>,
+    headers: HeaderMap,
+) -> Result<Response, (StatusCode, String)> {
+    let refs = history(&state.pool, &container_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok(http_cache::respond("governance_history", &headers, http_cache::SHORT, refs))
+}
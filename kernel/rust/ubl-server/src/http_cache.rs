@@ -0,0 +1,134 @@
+//! # HTTP caching on read endpoints
+//!
+//! Read endpoints answer with an `ETag` and a `Cache-Control` tuned to how
+//! fast their data moves, and with `304 Not Modified` (no body) when the
+//! request's `If-None-Match` names the current tag.
+//!
+//! | Endpoint                                   | ETag                 | Cache-Control        |
+//! |--------------------------------------------|----------------------|----------------------|
+//! | GET /state/:id                             | head `entry_hash`    | [`REVALIDATE`]       |
+//! | GET /atoms/:atom_hash                      | `atom_hash`          | [`IMMUTABLE`]        |
+//! | GET /pacts/:id, /containers/:id/authority  | body hash            | [`REVALIDATE`]       |
+//! | GET /policy/:id, /policy/:id/versions      | body hash            | [`SHORT`]            |
+//! | GET /policy-bindings/resolve/:id           | body hash            | [`SHORT`]            |
+//! | GET /containers/:id/policies               | body hash            | [`SHORT`]            |
+//! | GET /governance/:id/history                | body hash            | [`SHORT`]            |
+//! | GET /errors                                | body hash            | [`SHORT`]            |
+//!
+//! `/state/:id` and `/atoms/:hash` check the tag before the expensive part
+//! of the read (the entry count, the chunk fetch): a dashboard polling an
+//! idle container costs one index lookup. Body-hashed reads still query,
+//! but a match saves the body. Every response is `private`; several
+//! depend on the caller's role. 304s are counted in
+//! `ubl_http_not_modified_total{route}`.
+
+use axum::{
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+
+use crate::metrics;
+
+/// Changes on every commit or can be revoked at any time: always revalidate
+pub const REVALIDATE: &str = "private, no-cache";
+
+/// Changes on admin action only: a few seconds of staleness is fine
+pub const SHORT: &str = "private, max-age=10";
+
+/// Content-addressed
+pub const IMMUTABLE: &str = "private, max-age=31536000, immutable";
+
+/// Strong ETag for a validator (entry hash, atom hash, body hash)
+pub fn etag(validator: &str) -> String {
+    format!("\"{}\"", validator)
+}
+
+/// ETag of a JSON body: BLAKE3 of its serialized bytes
+pub fn body_etag<T: Serialize>(body: &T) -> String {
+    let bytes = serde_json::to_vec(body).expect("response body serializes");
+    etag(blake3::hash(&bytes).to_hex().as_str())
+}
+
+/// Whether `If-None-Match` names `etag` (or is `*`); weak tags compare by
+/// their opaque part, as RFC 9110 asks for `If-None-Match`
+pub fn not_modified(headers: &HeaderMap, etag: &str) -> bool {
+    headers
+        .get_all(header::IF_NONE_MATCH)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .map(str::trim)
+        .any(|tag| tag == "*" || tag.strip_prefix("W/").unwrap_or(tag) == etag)
+}
+
+/// Set the tag and caching policy on a response
+pub fn set(response: &mut Response, etag: &str, cache_control: &'static str) {
+    let h = response.headers_mut();
+    if let Ok(v) = HeaderValue::from_str(etag) {
+        h.insert(header::ETAG, v);
+    }
+    h.insert(header::CACHE_CONTROL, HeaderValue::from_static(cache_control));
+}
+
+/// 304 for `route`, carrying the tag and caching policy
+pub fn not_modified_response(route: &'static str, etag: &str, cache_control: &'static str) -> Response {
+    metrics::HTTP_NOT_MODIFIED.with_label_values(&[route]).inc();
+    let mut response = StatusCode::NOT_MODIFIED.into_response();
+    set(&mut response, etag, cache_control);
+    response
+}
+
+/// Answer `body` under a tag already computed, or 304 if the client has it
+pub fn tagged<T: Serialize>(
+    route: &'static str,
+    headers: &HeaderMap,
+    etag: &str,
+    cache_control: &'static str,
+    body: T,
+) -> Response {
+    if not_modified(headers, etag) {
+        return not_modified_response(route, etag, cache_control);
+    }
+    let mut response = Json(body).into_response();
+    set(&mut response, etag, cache_control);
+    response
+}
+
+/// Answer `body` tagged with its own hash, or 304 if the client has it
+pub fn respond<T: Serialize>(route: &'static str, headers: &HeaderMap, cache_control: &'static str, body: T) -> Response {
+    let etag = body_etag(&body);
+    tagged(route, headers, &etag, cache_control, body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn if_none_match(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::IF_NONE_MATCH, HeaderValue::from_str(value).unwrap());
+        headers
+    }
+
+    #[test]
+    fn test_if_none_match() {
+        let tag = etag("abc");
+        assert!(not_modified(&if_none_match("\"abc\""), &tag));
+        assert!(not_modified(&if_none_match("\"x\", W/\"abc\""), &tag));
+        assert!(not_modified(&if_none_match("*"), &tag));
+        assert!(!not_modified(&if_none_match("\"abd\""), &tag));
+        assert!(!not_modified(&HeaderMap::new(), &tag));
+    }
+
+    #[test]
+    fn test_body_etag_tracks_content() {
+        let a = body_etag(&serde_json::json!({"policies": ["p1"]}));
+        assert_eq!(a, body_etag(&serde_json::json!({"policies": ["p1"]})));
+        assert_ne!(a, body_etag(&serde_json::json!({"policies": ["p1", "p2"]})));
+        let response = respond("test", &if_none_match(&a), SHORT, serde_json::json!({"policies": ["p1"]}));
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(response.headers()[header::CACHE_CONTROL], SHORT);
+    }
+}
@@ -80,6 +80,14 @@ pub struct Asc {
 // SUBJECT OPERATIONS
 // ============================================================================
 
+/// Stable agent SID: "ubl:sid:" + blake3(pubkey_hex | kind)
+pub fn derive_agent_sid(kind: &str, public_key_hex: &str) -> String {
+    let mut h = Hasher::new();
+    h.update(public_key_hex.as_bytes());
+    h.update(kind.as_bytes());
+    format!("ubl:sid:{}", hex::encode(h.finalize().as_bytes()))
+}
+
 /// Create agent (LLM or App) with Ed25519 public key
 /// sid = "ubl:sid:" + blake3(pubkey_hex | kind)
 pub async fn create_agent(
@@ -89,10 +97,7 @@ pub async fn create_agent(
     public_key_hex: &str,
 ) -> sqlx::Result<Subject> {
     // Compute stable SID
-    let mut h = Hasher::new();
-    h.update(public_key_hex.as_bytes());
-    h.update(kind.as_bytes());
-    let sid = format!("ubl:sid:{}", hex::encode(h.finalize().as_bytes()));
+    let sid = derive_agent_sid(kind, public_key_hex);
 
     // Insert subject
     sqlx::query!(
@@ -778,6 +783,23 @@ pub async fn is_key_revoked(
     Ok(row.is_some())
 }
 
+/// SID already holding this Ed25519 public key, at any key version
+pub async fn find_ed25519_key_owner(pool: &PgPool, public_key: &[u8]) -> sqlx::Result<Option<String>> {
+    let sid = sqlx::query_scalar!(
+        r#"
+        SELECT sid
+        FROM id_credential
+        WHERE credential_kind = 'ed25519' AND public_key = $1
+        LIMIT 1
+        "#,
+        public_key
+    )
+    .fetch_optional(pool)
+    .await?;
+
+    Ok(sid)
+}
+
 /// Get subject by SID
 pub async fn get_subject_by_sid(pool: &PgPool, sid: &str) -> sqlx::Result<Option<Subject>> {
     let row = sqlx::query!(
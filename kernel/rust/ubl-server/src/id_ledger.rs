@@ -1,23 +1,154 @@
-use crate::AppState;
-use serde_json::json;
-use time::OffsetDateTime;
+//! # Identity ledger
+//!
+//! Identity events are Observation atoms (delta 0) appended to the
+//! `C.Identity` container, so imports and registrations carry the same
+//! causal chain as any other commit. Each link is signed by the UBL ID
+//! authority key (`UBL_ID_AUTHORITY_KEY`, hex Ed25519 seed); without it
+//! nothing is appended.
 
-/// Emits an Observation atom into the C.Identity container.
-/// TODO: Implement proper ledger append with signatures
+use ed25519_dalek::SigningKey;
+use sqlx::PgPool;
+use ubl_link::{IntentClass, LinkCommit};
+
+use crate::db::{LinkDraft, PgLedger, TangencyError};
+use crate::pipeline::PipelineTrace;
+
+/// Container that receives identity events
+pub const IDENTITY_CONTAINER: &str = "C.Identity";
+
+/// Concurrent appends can move the head between read and lock
+const APPEND_ATTEMPTS: usize = 3;
+
+/// The UBL ID authority key, from `UBL_ID_AUTHORITY_KEY` (unset: identity
+/// events are not recorded)
+pub fn key_from_env() -> anyhow::Result<Option<SigningKey>> {
+    let Some(hex_seed) = std::env::var("UBL_ID_AUTHORITY_KEY").ok().filter(|v| !v.is_empty()) else {
+        return Ok(None);
+    };
+    let seed: [u8; 32] = hex::decode(hex_seed.trim())
+        .ok()
+        .and_then(|b| b.try_into().ok())
+        .ok_or_else(|| anyhow::anyhow!("UBL_ID_AUTHORITY_KEY must be 32 bytes of hex"))?;
+    Ok(Some(SigningKey::from_bytes(&seed)))
+}
+
+/// Sign `draft` as `key`, over the SPEC-UBL-LINK signing bytes
+fn sign_draft(draft: &mut LinkDraft, key: &SigningKey) {
+    let link = LinkCommit {
+        version: draft.version,
+        container_id: draft.container_id.clone(),
+        expected_sequence: draft.expected_sequence as u64,
+        previous_hash: draft.previous_hash.clone(),
+        atom_hash: draft.atom_hash.clone(),
+        intent_class: IntentClass::Observation,
+        physics_delta: 0,
+        asset_deltas: Default::default(),
+        timestamp: None,
+        pact: None,
+        author_pubkey: String::new(),
+        signature: String::new(),
+    };
+    draft.author_pubkey = ubl_kernel::pubkey_from_signing_key(key);
+    draft.signature = ubl_kernel::sign(key, &link.signing_bytes());
+}
+
+/// Emits an Observation atom, signed by `key`, into the C.Identity container.
+/// Returns the entry hash of the appended ledger entry.
 pub async fn emit_identity_event(
-    _state: &AppState,
+    pool: &PgPool,
+    key: &SigningKey,
     event: &str,
     payload: serde_json::Value,
 ) -> Result<String, anyhow::Error> {
-    // TODO: Build proper LinkDraft with signatures and append to ledger
-    // For now, just log the event
-    tracing::info!(
-        event_type = "identity",
-        event = event,
-        payload = %serde_json::to_string(&payload).unwrap_or_default(),
-        "Identity event emitted (ledger append not yet implemented)"
-    );
-    
-    // Return placeholder hash
-    Ok(format!("0x{}", hex::encode(blake3::hash(event.as_bytes()).as_bytes())))
+    let atom = serde_json::json!({
+        "type": "identity_event",
+        "event": event,
+        "payload": payload,
+    });
+    let canonical = ubl_atom::canonicalize(&atom)?;
+    let atom_hash = ubl_kernel::hash_atom(&canonical);
+
+    let ledger = PgLedger::new(pool.clone());
+    for _ in 0..APPEND_ATTEMPTS {
+        let (expected_sequence, previous_hash) = match ledger.get_state(IDENTITY_CONTAINER).await {
+            Ok(head) => (head.sequence + 1, head.entry_hash),
+            Err(sqlx::Error::RowNotFound) => (1, "0x00".to_string()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut draft = LinkDraft {
+            version: 1,
+            container_id: IDENTITY_CONTAINER.to_string(),
+            expected_sequence,
+            previous_hash,
+            atom_hash: atom_hash.clone(),
+            intent_class: "Observation".to_string(),
+            physics_delta: "0".to_string(),
+            author_pubkey: String::new(),
+            signature: String::new(),
+            affects: Vec::new(),
+            policy_id: None,
+            intent: None,
+            manifest: None,
+            metadata: None,
+        };
+        sign_draft(&mut draft, key);
+        match ledger.append(&draft, &mut PipelineTrace::new()).await {
+            Ok(entry) => {
+                tracing::info!(
+                    event_type = "identity",
+                    event = event,
+                    sequence = entry.sequence,
+                    atom_hash = %atom_hash,
+                    "Identity event appended"
+                );
+                return Ok(entry.entry_hash);
+            }
+            Err(TangencyError::RealityDrift | TangencyError::SequenceMismatch) => continue,
+            Err(e) => anyhow::bail!("identity append rejected: {:?}", e),
+        }
+    }
+    anyhow::bail!("identity append lost the head race {} times", APPEND_ATTEMPTS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_draft_verifies_as_link() {
+        let (_, key) = ubl_kernel::generate_keypair();
+        let mut draft = LinkDraft {
+            version: 1,
+            container_id: IDENTITY_CONTAINER.to_string(),
+            expected_sequence: 7,
+            previous_hash: "ab".repeat(32),
+            atom_hash: "cd".repeat(32),
+            intent_class: "Observation".to_string(),
+            physics_delta: "0".to_string(),
+            author_pubkey: String::new(),
+            signature: String::new(),
+            affects: Vec::new(),
+            policy_id: None,
+            intent: None,
+            manifest: None,
+            metadata: None,
+        };
+        sign_draft(&mut draft, &key);
+        let link = LinkCommit {
+            version: 1,
+            container_id: draft.container_id.clone(),
+            expected_sequence: 7,
+            previous_hash: draft.previous_hash.clone(),
+            atom_hash: draft.atom_hash.clone(),
+            intent_class: IntentClass::Observation,
+            physics_delta: 0,
+            asset_deltas: Default::default(),
+            timestamp: None,
+            pact: None,
+            author_pubkey: draft.author_pubkey.clone(),
+            signature: draft.signature.clone(),
+        };
+        assert_eq!(draft.author_pubkey, ubl_kernel::pubkey_from_signing_key(&key));
+        ubl_kernel::verify(&draft.author_pubkey, &link.signing_bytes(), &draft.signature).unwrap();
+    }
 }
@@ -3,7 +3,7 @@
 //! Identity API for People (WebAuthn), LLMs, and Apps
 
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::{StatusCode, HeaderMap, HeaderValue},
     response::IntoResponse,
     routing::{delete, get, post},
@@ -13,10 +13,14 @@ use axum::{
 use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
 use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
+use std::collections::{BTreeSet, HashMap};
 use uuid::Uuid;
 use webauthn_rs::prelude::*;
 
 use crate::id_db;
+use crate::auth::rbac;
+use crate::ceremony::{CeremonyTracker, FailureClass};
+use crate::ceremony_db;
 use crate::auth::session::{Session, SessionFlavor};
 use crate::auth::session_db;
 
@@ -68,6 +72,8 @@ pub struct IdState {
     pub pool: PgPool,
     pub webauthn: Webauthn,
     pub rate_limiter: crate::rate_limit::RateLimiter,
+    /// Signs C.Identity links (see [`crate::id_ledger`]); unset: bulk import is off
+    pub authority_key: Option<std::sync::Arc<ed25519_dalek::SigningKey>>,
 }
 
 // ============================================================================
@@ -89,6 +95,68 @@ pub struct CreateAgentResp {
     pub public_key: String,
 }
 
+/// Upper bound on rows per bulk manifest
+pub const MAX_BULK_ROWS: usize = 10_000;
+
+/// One agent in a bulk import manifest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkAgentRow {
+    pub kind: String, // "llm" | "app"
+    pub display_name: String,
+    pub public_key: String, // hex Ed25519 (64 chars)
+    /// Signature by `public_key` over `pop_message(sid)` (proof of possession)
+    pub pop_signature: String, // hex
+    /// SID the migrating system expects; rejected if derivation disagrees
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected_sid: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkManifest {
+    pub manifest_id: String,
+    pub rows: Vec<BulkAgentRow>,
+}
+
+/// POST /id/agents/bulk body; `signature` covers the canonical JSON of `manifest`
+#[derive(Debug, Deserialize)]
+pub struct BulkAgentsReq {
+    pub manifest: BulkManifest,
+    pub signer_public_key: String, // hex
+    pub signature: String,         // hex
+}
+
+#[derive(Debug, Serialize)]
+pub struct BulkRowResult {
+    pub index: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sid: Option<String>,
+    pub status: &'static str, // "created" | "rejected"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BulkAgentsResp {
+    pub manifest_id: String,
+    pub manifest_hash: String,
+    pub created: usize,
+    pub rejected: usize,
+    pub results: Vec<BulkRowResult>,
+    /// C.Identity entry recording the import summary
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ledger_entry_hash: Option<String>,
+    /// Why the summary was not recorded; the agents above exist regardless
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ledger_error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CeremonyQuery {
+    pub username: Option<String>,
+    pub sid: Option<String>,
+    pub limit: Option<i64>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct IssueAscReq {
     pub containers: Vec<String>,
@@ -266,6 +334,185 @@ pub async fn route_create_agent(
     }
 }
 
+/// Bytes an agent key signs to prove possession during bulk import
+pub fn pop_message(sid: &str) -> Vec<u8> {
+    format!("ubl:id:pop\n{}", sid).into_bytes()
+}
+
+/// Check one manifest row: kind, key shape, SID derivation and PoP signature.
+/// Returns the derived SID and the normalised public key hex.
+fn check_bulk_row(row: &BulkAgentRow) -> Result<(String, String), String> {
+    if row.kind != "llm" && row.kind != "app" {
+        return Err("kind must be 'llm' or 'app'".into());
+    }
+    let public_key = row.public_key.to_ascii_lowercase();
+    if public_key.len() != 64 || hex::decode(&public_key).is_err() {
+        return Err("public_key must be 64 hex characters (Ed25519)".into());
+    }
+
+    let sid = id_db::derive_agent_sid(&row.kind, &public_key);
+    if let Some(expected) = &row.expected_sid {
+        if expected != &sid {
+            return Err(format!("expected_sid {} does not match derived {}", expected, sid));
+        }
+    }
+
+    ubl_kernel::verify(&public_key, &pop_message(&sid), &row.pop_signature)
+        .map_err(|e| format!("proof of possession failed: {}", e))?;
+    Ok((sid, public_key))
+}
+
+/// Keys trusted to sign bulk manifests besides the importer's own, from
+/// `UBL_ID_IMPORT_KEYS` (comma-separated hex Ed25519 public keys)
+fn import_keys_from_env() -> BTreeSet<String> {
+    std::env::var("UBL_ID_IMPORT_KEYS")
+        .unwrap_or_default()
+        .split(',')
+        .map(|k| k.trim().to_ascii_lowercase())
+        .filter(|k| !k.is_empty())
+        .collect()
+}
+
+/// POST /id/agents/bulk - Import a signed manifest of LLM/App agents
+///
+/// The manifest signer must be a key registered to the importing admin or
+/// listed in `UBL_ID_IMPORT_KEYS`. Every row is checked independently and
+/// reported; rows that fail never block the rest. The import summary is
+/// appended to C.Identity; if that fails, the results still come back and
+/// `ledger_error` says why.
+pub async fn route_bulk_create_agents(
+    State(state): State<IdState>,
+    headers: HeaderMap,
+    Json(req): Json<BulkAgentsReq>,
+) -> Result<Json<BulkAgentsResp>, (StatusCode, String)> {
+    let caller = rbac::require_role(&state.pool, &headers, &[rbac::ADMIN]).await?;
+    let authority_key = state.authority_key.clone().ok_or((
+        StatusCode::SERVICE_UNAVAILABLE,
+        "bulk import is off (UBL_ID_AUTHORITY_KEY unset)".to_string(),
+    ))?;
+
+    let rows = req.manifest.rows.len();
+    if rows == 0 || rows > MAX_BULK_ROWS {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("manifest must hold 1..={} rows, got {}", MAX_BULK_ROWS, rows),
+        ));
+    }
+
+    // The manifest signature covers the canonical form, not the wire bytes
+    let manifest_value = serde_json::to_value(&req.manifest)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+    let canonical = ubl_atom::canonicalize(&manifest_value)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+    ubl_kernel::verify(&req.signer_public_key, &canonical, &req.signature).map_err(|e| {
+        tracing::warn!(decision = "reject", error_code = "manifest_signature_invalid");
+        (StatusCode::UNAUTHORIZED, format!("manifest signature: {}", e))
+    })?;
+    let signer = req.signer_public_key.to_ascii_lowercase();
+    if !import_keys_from_env().contains(&signer) {
+        let signer_bytes = hex::decode(&signer).unwrap_or_default();
+        let owner = id_db::find_ed25519_key_owner(&state.pool, &signer_bytes)
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        if owner.as_deref() != Some(caller.session.sid.as_str()) {
+            tracing::warn!(decision = "reject", error_code = "manifest_signer_unbound");
+            return Err((
+                StatusCode::FORBIDDEN,
+                format!(
+                    "manifest signer {} is neither registered to {} nor in UBL_ID_IMPORT_KEYS",
+                    signer, caller.session.sid
+                ),
+            ));
+        }
+    }
+    let manifest_hash = ubl_kernel::hash_atom(&canonical);
+
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    let mut results = Vec::with_capacity(rows);
+    let mut created_sids = Vec::new();
+    for (index, row) in req.manifest.rows.iter().enumerate() {
+        let outcome = match check_bulk_row(row) {
+            Err(e) => Err((None, e)),
+            Ok((sid, public_key)) => {
+                if let Some(first) = seen.get(&public_key) {
+                    Err((Some(sid), format!("public_key duplicates row {}", first)))
+                } else {
+                    seen.insert(public_key.clone(), index);
+                    let key_bytes = hex::decode(&public_key).unwrap_or_default();
+                    match id_db::find_ed25519_key_owner(&state.pool, &key_bytes).await {
+                        Err(e) => Err((Some(sid), format!("database error: {}", e))),
+                        Ok(Some(owner)) => Err((Some(sid), format!("public_key already registered to {}", owner))),
+                        Ok(None) => {
+                            match id_db::create_agent(&state.pool, &row.kind, &row.display_name, &public_key).await {
+                                Ok(subject) => Ok(subject.sid),
+                                Err(e) => Err((Some(sid), format!("database error: {}", e))),
+                            }
+                        }
+                    }
+                }
+            }
+        };
+        results.push(match outcome {
+            Ok(sid) => {
+                created_sids.push(sid.clone());
+                BulkRowResult { index, sid: Some(sid), status: "created", error: None }
+            }
+            Err((sid, error)) => BulkRowResult { index, sid, status: "rejected", error: Some(error) },
+        });
+    }
+
+    let created = created_sids.len();
+    let rejected = rows - created;
+    // The agents exist whatever happens here: report a failed summary, don't fail the import
+    let (ledger_entry_hash, ledger_error) = match crate::id_ledger::emit_identity_event(
+        &state.pool,
+        &authority_key,
+        "agents_bulk_imported",
+        serde_json::json!({
+            "manifest_id": req.manifest.manifest_id,
+            "manifest_hash": manifest_hash,
+            "signer_public_key": signer,
+            "imported_by": caller.session.sid,
+            "created": created,
+            "rejected": rejected,
+            "sids": created_sids,
+        }),
+    )
+    .await
+    {
+        Ok(hash) => (Some(hash), None),
+        Err(e) => {
+            tracing::error!(
+                manifest_id = %req.manifest.manifest_id,
+                created,
+                error = %e,
+                "bulk import summary not recorded in C.Identity"
+            );
+            (None, Some(e.to_string()))
+        }
+    };
+
+    tracing::info!(
+        "📥 AGENTS bulk import manifest={} created={} rejected={}",
+        req.manifest.manifest_id, created, rejected
+    );
+    crate::auth::session_policy::after_action(
+        &state.pool,
+        &caller.session,
+        crate::auth::session_policy::RISK_L4,
+    )
+    .await;
+    Ok(Json(BulkAgentsResp {
+        manifest_id: req.manifest.manifest_id,
+        manifest_hash,
+        created,
+        rejected,
+        results,
+        ledger_entry_hash,
+        ledger_error,
+    }))
+}
+
 /// POST /id/agents/{sid}/asc - Issue Agent Signing Certificate
 pub async fn route_issue_asc(
     State(state): State<IdState>,
@@ -366,15 +613,19 @@ pub async fn route_whoami(
 /// POST /id/register/begin - Begin WebAuthn registration
 pub async fn route_register_begin(
     State(state): State<IdState>,
+    headers: HeaderMap,
     Json(req): Json<RegisterBeginReq>,
 ) -> Result<Json<RegisterBeginResp>, (StatusCode, String)> {
     use tracing::{info, warn};
     let start = std::time::Instant::now();
+    let mut ceremony = CeremonyTracker::begin(&state.pool, "register", &headers);
+    ceremony.set_username(&req.username);
     
     // Rate limit: 5 registrations per username per hour
     let rate_key = format!("register:{}", req.username);
     if let Err(retry_after) = state.rate_limiter.check(&rate_key, 5, 3600) {
         crate::metrics::RATE_LIMIT_REJECTIONS.with_label_values(&["register"]).inc();
+        ceremony.fail(FailureClass::RateLimited, format!("retry after {}s", retry_after));
         warn!(actor_type="person", username=%req.username, decision="reject", error_code="rate_limited", retry_after_secs=%retry_after);
         return Err((StatusCode::TOO_MANY_REQUESTS, format!("Rate limited. Retry after {} seconds", retry_after)));
     }
@@ -384,11 +635,13 @@ pub async fn route_register_begin(
         .await
         .map_err(|e| {
             warn!(actor_type="person", username=%req.username, decision="reject", error_code="db_error", latency_ms=start.elapsed().as_millis());
+            ceremony.fail(FailureClass::Internal, e.to_string());
             (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
         })?;
 
     if existing.is_some() {
         warn!(actor_type="person", username=%req.username, decision="reject", error_code="username_exists", latency_ms=start.elapsed().as_millis());
+        ceremony.fail(FailureClass::UsernameExists, "username already registered");
         return Err((StatusCode::CONFLICT, "Username already registered".to_string()));
     }
 
@@ -418,7 +671,12 @@ pub async fn route_register_begin(
         300, // 5 minutes TTL
     )
     .await
-    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    .map_err(|e| {
+        ceremony.fail(FailureClass::Internal, e.to_string());
+        (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+    })?;
+    ceremony.set_challenge(challenge_id);
+    ceremony.issued();
 
     crate::metrics::WEBAUTHN_OPS.with_label_values(&["register", "begin"]).inc();
     info!(actor_type="person", username=%req.username, challenge_id=%challenge_id, decision="accept", phase="begin", latency_ms=start.elapsed().as_millis());
@@ -432,25 +690,30 @@ pub async fn route_register_begin(
 /// POST /id/register/finish - Finish WebAuthn registration
 pub async fn route_register_finish(
     State(state): State<IdState>,
+    headers: HeaderMap,
     Json(req): Json<RegisterFinishReq>,
 ) -> Result<Json<RegisterFinishResp>, (StatusCode, String)> {
     use tracing::{info, warn};
     let start = std::time::Instant::now();
+    let mut ceremony = CeremonyTracker::finish(&state.pool, "register", &headers, &req.challenge_id).await;
     
     // 1. Get challenge from database
     let challenge = id_db::get_challenge(&state.pool, &req.challenge_id)
         .await
         .map_err(|e| {
             warn!(challenge_id=%req.challenge_id, decision="reject", error_code="challenge_not_found", latency_ms=start.elapsed().as_millis());
+            ceremony.fail(FailureClass::ChallengeNotFound, e.to_string());
             (StatusCode::BAD_REQUEST, "Challenge not found".to_string())
         })?
         .ok_or_else(|| {
             warn!(challenge_id=%req.challenge_id, decision="reject", error_code="challenge_not_found");
+            ceremony.fail(FailureClass::ChallengeNotFound, "no such challenge");
             (StatusCode::BAD_REQUEST, "Challenge not found".to_string())
         })?;
 
     if challenge.used {
         warn!(challenge_id=%req.challenge_id, decision="reject", error_code="challenge_used", latency_ms=start.elapsed().as_millis());
+        ceremony.fail(FailureClass::ChallengeUsed, "challenge already used");
         return Err((StatusCode::BAD_REQUEST, "Challenge already used".to_string()));
     }
 
@@ -459,11 +722,13 @@ pub async fn route_register_finish(
     let clock_skew = time::Duration::seconds(60);
     if now > challenge.expires_at + clock_skew {
         warn!(challenge_id=%req.challenge_id, decision="reject", error_code="challenge_expired", latency_ms=start.elapsed().as_millis());
+        ceremony.fail(FailureClass::ChallengeExpired, format!("expired at {}", challenge.expires_at));
         return Err((StatusCode::BAD_REQUEST, "Challenge expired".to_string()));
     }
 
     if challenge.kind != "register" {
         warn!(challenge_id=%req.challenge_id, decision="reject", error_code="invalid_challenge_kind", latency_ms=start.elapsed().as_millis());
+        ceremony.fail(FailureClass::InvalidChallengeKind, challenge.kind.clone());
         return Err((StatusCode::BAD_REQUEST, "Not a registration challenge".to_string()));
     }
 
@@ -481,10 +746,20 @@ pub async fn route_register_finish(
     let passkey_registration: PasskeyRegistration = serde_json::from_slice(&state_bytes)
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("Invalid registration state: {}", e)))?;
 
+    ceremony.set_username(&username);
+
+    // Validate origin from clientDataJSON
+    if let Ok(cdj) = parse_client_data_json(&req.attestation.response.client_data_json) {
+        assert_origin(&cdj).inspect_err(|_| ceremony.fail(FailureClass::OriginMismatch, cdj.origin.clone()))?;
+    }
+
     // 3. Verify attestation
     let passkey = state.webauthn
         .finish_passkey_registration(&req.attestation, &passkey_registration)
-        .map_err(|e| (StatusCode::BAD_REQUEST, format!("Registration verification failed: {:?}", e)))?;
+        .map_err(|e| {
+            ceremony.fail(FailureClass::VerificationFailed, format!("{:?}", e));
+            (StatusCode::BAD_REQUEST, format!("Registration verification failed: {:?}", e))
+        })?;
 
     // 4. Create person subject with username from challenge
     let sid = id_db::create_person(&state.pool, &username, &username)
@@ -515,6 +790,9 @@ pub async fn route_register_finish(
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
+    ceremony.set_sid(&sid);
+    ceremony.accept();
+
     crate::metrics::ID_DECISIONS.with_label_values(&["register", "accept", ""]).inc();
     crate::metrics::WEBAUTHN_OPS.with_label_values(&["register", "finish"]).inc();
     info!(actor_type="person", username=%username, sid=%sid, challenge_id=%req.challenge_id, decision="accept", phase="finish", latency_ms=start.elapsed().as_millis());
@@ -528,15 +806,19 @@ pub async fn route_register_finish(
 /// POST /id/login/begin - Begin WebAuthn login
 pub async fn route_login_begin(
     State(state): State<IdState>,
+    headers: HeaderMap,
     Json(req): Json<LoginBeginReq>,
 ) -> Result<Json<LoginBeginResp>, (StatusCode, String)> {
     use tracing::{info, warn};
     let start = std::time::Instant::now();
+    let mut ceremony = CeremonyTracker::begin(&state.pool, "login", &headers);
+    ceremony.set_username(&req.username);
     
     // Rate limit: 10 login attempts per username per 5 minutes
     let rate_key = format!("login:{}", req.username);
     if let Err(retry_after) = state.rate_limiter.check(&rate_key, 10, 300) {
         crate::metrics::RATE_LIMIT_REJECTIONS.with_label_values(&["login"]).inc();
+        ceremony.fail(FailureClass::RateLimited, format!("retry after {}s", retry_after));
         warn!(actor_type="person", username=%req.username, decision="reject", error_code="rate_limited", retry_after_secs=%retry_after);
         return Err((StatusCode::TOO_MANY_REQUESTS, format!("Too many login attempts. Retry after {} seconds", retry_after)));
     }
@@ -546,17 +828,21 @@ pub async fn route_login_begin(
         .await
         .map_err(|e| {
             warn!(actor_type="person", username=%req.username, decision="reject", error_code="db_error", latency_ms=start.elapsed().as_millis());
+            ceremony.fail(FailureClass::Internal, e.to_string());
             (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
         })?
         .ok_or_else(|| {
             warn!(actor_type="person", username=%req.username, decision="reject", error_code="unknown_credential", latency_ms=start.elapsed().as_millis());
+            ceremony.fail(FailureClass::UnknownCredential, "user not found");
             (StatusCode::NOT_FOUND, "User not found".to_string())
         })?;
 
     if subject.kind != "person" {
         warn!(actor_type="person", username=%req.username, sid=%subject.sid, decision="reject", error_code="invalid_subject_kind", latency_ms=start.elapsed().as_millis());
+        ceremony.fail(FailureClass::UnknownCredential, "not a person account");
         return Err((StatusCode::BAD_REQUEST, "Not a person account".to_string()));
     }
+    ceremony.set_sid(&subject.sid);
 
     // 2. Get all credentials for user
     let credentials = id_db::get_credentials(&state.pool, &subject.sid)
@@ -564,6 +850,7 @@ pub async fn route_login_begin(
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
     if credentials.is_empty() {
+        ceremony.fail(FailureClass::UnknownCredential, "no credentials registered");
         return Err((StatusCode::BAD_REQUEST, "No credentials registered".to_string()));
     }
 
@@ -578,6 +865,7 @@ pub async fn route_login_begin(
     }
 
     if passkeys.is_empty() {
+        ceremony.fail(FailureClass::UnknownCredential, "no WebAuthn credentials");
         return Err((StatusCode::BAD_REQUEST, "No WebAuthn credentials found".to_string()));
     }
 
@@ -598,7 +886,12 @@ pub async fn route_login_begin(
         300, // 5 minutes TTL
     )
     .await
-    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    .map_err(|e| {
+        ceremony.fail(FailureClass::Internal, e.to_string());
+        (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+    })?;
+    ceremony.set_challenge(challenge_id);
+    ceremony.issued();
 
     crate::metrics::WEBAUTHN_OPS.with_label_values(&["login", "begin"]).inc();
     info!(actor_type="person", username=%req.username, sid=%subject.sid, challenge_id=%challenge_id, decision="accept", phase="login_begin", latency_ms=start.elapsed().as_millis());
@@ -612,25 +905,30 @@ pub async fn route_login_begin(
 /// POST /id/login/finish - Finish WebAuthn login
 pub async fn route_login_finish(
     State(state): State<IdState>,
+    headers: HeaderMap,
     Json(req): Json<LoginFinishReq>,
 ) -> Result<impl IntoResponse, (StatusCode, String)> {
     use tracing::{info, warn};
     let start = std::time::Instant::now();
+    let mut ceremony = CeremonyTracker::finish(&state.pool, "login", &headers, &req.challenge_id).await;
     
     // 1. Get challenge from database
     let challenge = id_db::get_challenge(&state.pool, &req.challenge_id)
         .await
         .map_err(|e| {
             warn!(challenge_id=%req.challenge_id, decision="reject", error_code="db_error", latency_ms=start.elapsed().as_millis());
+            ceremony.fail(FailureClass::Internal, e.to_string());
             (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
         })?
         .ok_or_else(|| {
             warn!(challenge_id=%req.challenge_id, decision="reject", error_code="challenge_not_found", latency_ms=start.elapsed().as_millis());
+            ceremony.fail(FailureClass::ChallengeNotFound, "no such challenge");
             (StatusCode::BAD_REQUEST, "Challenge not found".to_string())
         })?;
 
     if challenge.used {
         warn!(challenge_id=%req.challenge_id, decision="reject", error_code="challenge_used", latency_ms=start.elapsed().as_millis());
+        ceremony.fail(FailureClass::ChallengeUsed, "challenge already used");
         return Err((StatusCode::BAD_REQUEST, "Challenge already used".to_string()));
     }
 
@@ -639,17 +937,23 @@ pub async fn route_login_finish(
     let clock_skew = time::Duration::seconds(60);
     if now > challenge.expires_at + clock_skew {
         warn!(challenge_id=%req.challenge_id, decision="reject", error_code="challenge_expired", latency_ms=start.elapsed().as_millis());
+        ceremony.fail(FailureClass::ChallengeExpired, format!("expired at {}", challenge.expires_at));
         return Err((StatusCode::BAD_REQUEST, "Challenge expired".to_string()));
     }
 
     if challenge.kind != "login" {
         warn!(challenge_id=%req.challenge_id, decision="reject", error_code="invalid_challenge_kind", latency_ms=start.elapsed().as_millis());
+        ceremony.fail(FailureClass::InvalidChallengeKind, challenge.kind.clone());
         return Err((StatusCode::BAD_REQUEST, "Not a login challenge".to_string()));
     }
 
+    if let Some(sid) = &challenge.sid {
+        ceremony.set_sid(sid);
+    }
+
     // Validate origin from clientDataJSON
     if let Ok(cdj) = parse_client_data_json(&req.credential.response.client_data_json) {
-        assert_origin(&cdj)?;
+        assert_origin(&cdj).inspect_err(|_| ceremony.fail(FailureClass::OriginMismatch, cdj.origin.clone()))?;
     }
 
     // 2. Parse authentication state
@@ -671,6 +975,7 @@ pub async fn route_login_finish(
             }
             
             crate::metrics::ID_DECISIONS.with_label_values(&["login", "reject", "auth_failed"]).inc();
+            ceremony.fail(FailureClass::VerificationFailed, format!("{:?}", e));
             warn!(challenge_id=%req.challenge_id, decision="reject", error_code="auth_failed", 
                   consecutive_failures=%fails, latency_ms=start.elapsed().as_millis());
             (StatusCode::UNAUTHORIZED, format!("Authentication failed: {:?}", e))
@@ -685,7 +990,10 @@ pub async fn route_login_finish(
     let cred = id_db::get_credential_by_id(&state.pool, &sid_str, &credential_id)
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
-        .ok_or_else(|| (StatusCode::NOT_FOUND, "Credential not found".to_string()))?;
+        .ok_or_else(|| {
+            ceremony.fail(FailureClass::UnknownCredential, credential_id.clone());
+            (StatusCode::NOT_FOUND, "Credential not found".to_string())
+        })?;
 
     // 5. Validate sign_count (prevent replay attacks)
     let new_counter = auth_result.counter();
@@ -700,6 +1008,10 @@ pub async fn route_login_finish(
         }
         
         crate::metrics::ID_DECISIONS.with_label_values(&["login", "reject", "counter_rollback"]).inc();
+        ceremony.fail(
+            FailureClass::SignCountRegression,
+            format!("stored {} presented {}", cred.sign_count, new_counter),
+        );
         warn!(challenge_id=%req.challenge_id, sid=%sid_str, decision="reject", error_code="counter_rollback", 
               old_count=%cred.sign_count, new_count=%new_counter, consecutive_failures=%fails, latency_ms=start.elapsed().as_millis());
         return Err((
@@ -725,10 +1037,7 @@ pub async fn route_login_finish(
     let final_sid = challenge.sid.ok_or_else(|| 
         (StatusCode::INTERNAL_SERVER_ERROR, "Challenge has no SID".to_string())
     )?;
-    let final_sid_uuid = Uuid::parse_str(&final_sid)
-        .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "Invalid SID format".to_string()))?;
-    
-    let session = Session::new_regular(final_sid_uuid);
+    let session = Session::new_regular(final_sid.clone());
     session_db::insert(&state.pool, &session)
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to create session: {}", e)))?;
@@ -741,6 +1050,8 @@ pub async fn route_login_finish(
     let lockout_key = format!("login_lockout:{}", final_sid);
     state.rate_limiter.on_success(&lockout_key);
 
+    ceremony.accept();
+
     crate::metrics::ID_DECISIONS.with_label_values(&["login", "accept", ""]).inc();
     crate::metrics::WEBAUTHN_OPS.with_label_values(&["login", "finish"]).inc();
     info!(actor_type="person", sid=%final_sid, challenge_id=%req.challenge_id, session_token=%session.token, 
@@ -754,6 +1065,23 @@ pub async fn route_login_finish(
     Ok((headers, resp))
 }
 
+/// GET /id/ceremonies - Recent WebAuthn ceremony attempts for a user (support)
+pub async fn route_list_ceremonies(
+    State(state): State<IdState>,
+    headers: HeaderMap,
+    Query(q): Query<CeremonyQuery>,
+) -> Result<Json<Vec<ceremony_db::CeremonyEvent>>, (StatusCode, String)> {
+    rbac::require_role(&state.pool, &headers, &[rbac::ADMIN, rbac::OPERATOR]).await?;
+    if q.username.is_none() && q.sid.is_none() {
+        return Err((StatusCode::BAD_REQUEST, "username or sid is required".to_string()));
+    }
+    let limit = q.limit.unwrap_or(50).clamp(1, 500);
+    let events = ceremony_db::recent(&state.pool, q.username.as_deref(), q.sid.as_deref(), limit)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok(Json(events))
+}
+
 /// GET /id/agents/:sid - Export agent (backup)
 pub async fn route_export_agent(
     State(state): State<IdState>,
@@ -822,10 +1150,13 @@ pub async fn route_revoke_asc(
 /// POST /id/stepup/begin - Begin step-up authentication for admin operations
 pub async fn route_stepup_begin(
     State(state): State<IdState>,
+    headers: HeaderMap,
     Json(req): Json<StepupBeginReq>,
 ) -> Result<Json<StepupBeginResp>, (StatusCode, String)> {
     use tracing::{info, warn};
     let start = std::time::Instant::now();
+    let mut ceremony = CeremonyTracker::begin(&state.pool, "stepup", &headers);
+    ceremony.set_username(&req.username);
     
     // Validate existing session
     // TODO: Extract and validate session_token properly
@@ -835,9 +1166,14 @@ pub async fn route_stepup_begin(
     let subject = id_db::get_subject_by_username(&state.pool, &username)
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
-        .ok_or_else(|| (StatusCode::UNAUTHORIZED, "Invalid session".to_string()))?;
+        .ok_or_else(|| {
+            ceremony.fail(FailureClass::UnknownCredential, "user not found");
+            (StatusCode::UNAUTHORIZED, "Invalid session".to_string())
+        })?;
+    ceremony.set_sid(&subject.sid);
 
     if subject.kind != "person" {
+        ceremony.fail(FailureClass::UnknownCredential, "not a person account");
         return Err((StatusCode::BAD_REQUEST, "Step-up only for person accounts".to_string()));
     }
 
@@ -856,6 +1192,7 @@ pub async fn route_stepup_begin(
     }
 
     if passkeys.is_empty() {
+        ceremony.fail(FailureClass::UnknownCredential, "no WebAuthn credentials");
         return Err((StatusCode::BAD_REQUEST, "No WebAuthn credentials found".to_string()));
     }
 
@@ -876,7 +1213,12 @@ pub async fn route_stepup_begin(
         120, // 2 minutes for step-up
     )
     .await
-    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    .map_err(|e| {
+        ceremony.fail(FailureClass::Internal, e.to_string());
+        (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+    })?;
+    ceremony.set_challenge(challenge_id);
+    ceremony.issued();
 
     crate::metrics::WEBAUTHN_OPS.with_label_values(&["stepup", "begin"]).inc();
     info!(actor_type="person", username=%username, sid=%subject.sid, challenge_id=%challenge_id, 
@@ -891,28 +1233,36 @@ pub async fn route_stepup_begin(
 /// POST /id/stepup/finish - Finish step-up authentication
 pub async fn route_stepup_finish(
     State(state): State<IdState>,
+    headers: HeaderMap,
     Json(req): Json<StepupFinishReq>,
 ) -> Result<impl IntoResponse, (StatusCode, String)> {
     use tracing::{info, warn};
     let start = std::time::Instant::now();
+    let mut ceremony = CeremonyTracker::finish(&state.pool, "stepup", &headers, &req.challenge_id).await;
     
     // Get challenge
     let challenge = id_db::get_challenge(&state.pool, &req.challenge_id)
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
-        .ok_or_else(|| (StatusCode::BAD_REQUEST, "Challenge not found".to_string()))?;
+        .ok_or_else(|| {
+            ceremony.fail(FailureClass::ChallengeNotFound, "no such challenge");
+            (StatusCode::BAD_REQUEST, "Challenge not found".to_string())
+        })?;
 
     if challenge.used {
+        ceremony.fail(FailureClass::ChallengeUsed, "challenge already used");
         return Err((StatusCode::BAD_REQUEST, "Challenge already used".to_string()));
     }
 
     if challenge.kind != "stepup" {
+        ceremony.fail(FailureClass::InvalidChallengeKind, challenge.kind.clone());
         return Err((StatusCode::BAD_REQUEST, "Not a step-up challenge".to_string()));
     }
 
     // Validate TTL
     let now = time::OffsetDateTime::now_utc();
     if now > challenge.expires_at {
+        ceremony.fail(FailureClass::ChallengeExpired, format!("expired at {}", challenge.expires_at));
         return Err((StatusCode::BAD_REQUEST, "Challenge expired".to_string()));
     }
 
@@ -920,6 +1270,7 @@ pub async fn route_stepup_finish(
     let sid = challenge.sid.clone().ok_or_else(||
         (StatusCode::INTERNAL_SERVER_ERROR, "No SID in challenge".to_string())
     )?;
+    ceremony.set_sid(&sid);
 
     // Parse auth state
     let auth_state: PasskeyAuthentication = serde_json::from_slice(&challenge.challenge)
@@ -928,19 +1279,29 @@ pub async fn route_stepup_finish(
     // Verify assertion
     let auth_result = state.webauthn
         .finish_passkey_authentication(&req.assertion, &auth_state)
-        .map_err(|e| (StatusCode::UNAUTHORIZED, format!("Authentication failed: {:?}", e)))?;
+        .map_err(|e| {
+            ceremony.fail(FailureClass::VerificationFailed, format!("{:?}", e));
+            (StatusCode::UNAUTHORIZED, format!("Authentication failed: {:?}", e))
+        })?;
 
     // Get credential and validate sign_count
     let credential_id = URL_SAFE_NO_PAD.encode(auth_result.cred_id());
     let cred = id_db::get_credential_by_id(&state.pool, &sid, &credential_id)
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
-        .ok_or_else(|| (StatusCode::NOT_FOUND, "Credential not found".to_string()))?;
+        .ok_or_else(|| {
+            ceremony.fail(FailureClass::UnknownCredential, credential_id.clone());
+            (StatusCode::NOT_FOUND, "Credential not found".to_string())
+        })?;
 
     let new_counter = auth_result.counter();
     if new_counter <= cred.sign_count as u32 {
         warn!(challenge_id=%req.challenge_id, sid=%sid, decision="reject", error_code="counter_rollback", 
               old_count=%cred.sign_count, new_count=%new_counter);
+        ceremony.fail(
+            FailureClass::SignCountRegression,
+            format!("stored {} presented {}", cred.sign_count, new_counter),
+        );
         return Err((StatusCode::UNAUTHORIZED, "Counter rollback detected".to_string()));
     }
 
@@ -958,10 +1319,7 @@ pub async fn route_stepup_finish(
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
 
     // Create step-up session (using new Session module)
-    let sid_uuid = Uuid::parse_str(&sid)
-        .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "Invalid SID format".to_string()))?;
-    
-    let session = Session::new_stepup(sid_uuid);
+    let session = Session::new_stepup(sid.clone());
     session_db::insert(&state.pool, &session)
         .await
         .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to create step-up session: {}", e)))?;
@@ -970,6 +1328,8 @@ pub async fn route_stepup_finish(
     let mut headers = HeaderMap::new();
     set_session_cookie(&mut headers, &session.token, session.ttl_secs());
 
+    ceremony.accept();
+
     crate::metrics::ID_DECISIONS.with_label_values(&["stepup", "accept", ""]).inc();
     crate::metrics::WEBAUTHN_OPS.with_label_values(&["stepup", "finish"]).inc();
     info!(actor_type="person", sid=%sid, challenge_id=%req.challenge_id, stepup_token=%session.token,
@@ -1031,6 +1391,7 @@ pub async fn route_ict_finish(
 pub fn id_router() -> Router<IdState> {
     Router::new()
         .route("/id/agents", post(route_create_agent))
+        .route("/id/agents/bulk", post(route_bulk_create_agents))
         .route("/id/agents/:sid", get(route_export_agent))
         .route("/id/agents/:sid/asc", post(route_issue_asc))
         .route("/id/agents/:sid/asc", get(route_list_asc))
@@ -1039,6 +1400,7 @@ pub fn id_router() -> Router<IdState> {
         .route("/id/agents/:sid/rotate", post(route_rotate_key))
         .route("/id/agents/:sid/asc/:asc_id", delete(route_revoke_asc))
         .route("/id/whoami", get(route_whoami))
+        .route("/id/ceremonies", get(route_list_ceremonies))
         .route("/id/register/begin", post(route_register_begin))
         .route("/id/register/finish", post(route_register_finish))
         .route("/id/login/begin", post(route_login_begin))
@@ -1048,3 +1410,37 @@ pub fn id_router() -> Router<IdState> {
         .route("/id/sessions/ict/begin", post(route_ict_begin))
         .route("/id/sessions/ict/finish", post(route_ict_finish))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signed_row(kind: &str) -> BulkAgentRow {
+        let (public_key, key) = ubl_kernel::generate_keypair();
+        let sid = id_db::derive_agent_sid(kind, &public_key);
+        BulkAgentRow {
+            kind: kind.to_string(),
+            display_name: "agent".to_string(),
+            pop_signature: ubl_kernel::sign(&key, &pop_message(&sid)),
+            public_key,
+            expected_sid: Some(sid),
+        }
+    }
+
+    #[test]
+    fn test_check_bulk_row() {
+        let row = signed_row("llm");
+        let (sid, _) = check_bulk_row(&row).unwrap();
+        assert_eq!(Some(sid), row.expected_sid);
+
+        // PoP is bound to the kind through the SID
+        let mut wrong_kind = row.clone();
+        wrong_kind.kind = "app".to_string();
+        wrong_kind.expected_sid = None;
+        assert!(check_bulk_row(&wrong_kind).unwrap_err().contains("proof of possession"));
+
+        let mut wrong_sid = row;
+        wrong_sid.expected_sid = Some("ubl:sid:other".to_string());
+        assert!(check_bulk_row(&wrong_sid).unwrap_err().contains("does not match"));
+    }
+}
@@ -0,0 +1,146 @@
+//! Intent schema registry
+//!
+//! Field shapes per intent `type`, matching the intent types the policy VM
+//! decides on. Used by `/lint/intent` to point out missing or mistyped
+//! fields before a draft reaches the policy.
+
+use serde_json::Value;
+
+/// JSON shape of an intent field
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldType {
+    String,
+    Integer,
+}
+
+impl FieldType {
+    fn matches(&self, v: &Value) -> bool {
+        match self {
+            FieldType::String => v.is_string(),
+            FieldType::Integer => v.is_i64() || v.is_u64(),
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            FieldType::String => "string",
+            FieldType::Integer => "integer",
+        }
+    }
+}
+
+/// Fields of one intent type (besides `type` itself)
+#[derive(Debug, Clone)]
+pub struct IntentSchema {
+    pub types: &'static [&'static str],
+    pub required: &'static [(&'static str, FieldType)],
+    pub optional: &'static [(&'static str, FieldType)],
+}
+
+const SCHEMAS: &[IntentSchema] = &[
+    IntentSchema {
+        types: &["observe", "read"],
+        required: &[],
+        optional: &[("subject", FieldType::String)],
+    },
+    IntentSchema {
+        types: &["transfer", "send"],
+        required: &[("amount", FieldType::Integer), ("counterparty", FieldType::String)],
+        optional: &[("memo", FieldType::String)],
+    },
+    IntentSchema {
+        types: &["create", "mint"],
+        required: &[("amount", FieldType::Integer), ("asset", FieldType::String)],
+        optional: &[("memo", FieldType::String)],
+    },
+    IntentSchema {
+        types: &["evolve", "upgrade"],
+        required: &[("target", FieldType::String)],
+        optional: &[("memo", FieldType::String)],
+    },
+];
+
+/// Schema registered for an intent type
+pub fn lookup(intent_type: &str) -> Option<&'static IntentSchema> {
+    SCHEMAS.iter().find(|s| s.types.contains(&intent_type))
+}
+
+/// Schema problem in an intent
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaIssue {
+    pub code: &'static str,
+    pub field: String,
+    pub message: String,
+}
+
+/// Check an intent against the registry
+pub fn check(intent: &Value) -> Vec<SchemaIssue> {
+    let issue = |code, field: &str, message: String| SchemaIssue {
+        code,
+        field: field.to_string(),
+        message,
+    };
+    let Some(obj) = intent.as_object() else {
+        return vec![issue("not_an_object", "", "intent must be a JSON object".to_string())];
+    };
+    let Some(intent_type) = obj.get("type").and_then(|v| v.as_str()) else {
+        return vec![issue("missing_field", "type", "missing type field".to_string())];
+    };
+    let Some(schema) = lookup(intent_type) else {
+        return vec![issue(
+            "unknown_intent_type",
+            "type",
+            format!("no schema registered for intent type {}", intent_type),
+        )];
+    };
+
+    let mut issues = Vec::new();
+    for (name, ty) in schema.required {
+        match obj.get(*name) {
+            None => issues.push(issue("missing_field", name, format!("missing {} field", name))),
+            Some(v) if !ty.matches(v) => {
+                issues.push(issue("wrong_type", name, format!("{} must be {}", name, ty.as_str())))
+            }
+            Some(_) => {}
+        }
+    }
+    for (name, ty) in schema.optional {
+        if obj.get(*name).is_some_and(|v| !ty.matches(v)) {
+            issues.push(issue("wrong_type", name, format!("{} must be {}", name, ty.as_str())));
+        }
+    }
+    for key in obj.keys() {
+        let known = key == "type"
+            || schema.required.iter().any(|(n, _)| n == key)
+            || schema.optional.iter().any(|(n, _)| n == key);
+        if !known {
+            issues.push(issue(
+                "unexpected_field",
+                key,
+                format!("{} is not part of the {} schema", key, intent_type),
+            ));
+        }
+    }
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_check_intent() {
+        assert!(check(&json!({"type": "transfer", "amount": 5, "counterparty": "bob"})).is_empty());
+
+        let issues = check(&json!({"type": "transfer", "amount": "5", "note": 1}));
+        let codes: Vec<_> = issues.iter().map(|i| (i.code, i.field.as_str())).collect();
+        assert_eq!(
+            codes,
+            vec![("wrong_type", "amount"), ("missing_field", "counterparty"), ("unexpected_field", "note")]
+        );
+
+        assert_eq!(check(&json!({"type": "burn"}))[0].code, "unknown_intent_type");
+        assert_eq!(check(&json!({"amount": 1}))[0].code, "missing_field");
+    }
+}
@@ -0,0 +1,228 @@
+//! # Intent linting (development aid)
+//!
+//! `POST /lint/intent` runs a draft intent through the engines a commit
+//! would meet and reports every problem found. It returns warnings and never
+//! writes state:
+//!
+//! - `schema`: the intent schema registry (`intent_schema`)
+//! - `policy`: the named policy, or the policies bound to the container
+//! - `constraints`: the decision's constraint snapshot (`ubl_policy_vm::violations`)
+//! - `asc`: the caller's ASC scopes (`Authorization: Bearer ubl:sid:…`)
+//!
+//! When the draft gives no `physics_delta`, the intent's `amount` stands in for it.
+
+use axum::{extract::State, http::HeaderMap, routing::post, Json, Router};
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use tracing::info;
+use ubl_link::IntentClass;
+use ubl_policy_vm::{deny, CommitFacts, EvaluationContext, SpecVersion, TranslationDecision};
+
+use crate::auth;
+use crate::db;
+use crate::intent_schema;
+use crate::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct LintIntentReq {
+    pub container_id: String,
+    pub intent: serde_json::Value,
+    /// Policy to lint against; defaults to the container's attached policies
+    #[serde(default)]
+    pub policy_id: Option<String>,
+    /// Intent class the client plans to commit with ("Observation", …)
+    #[serde(default)]
+    pub intent_class: Option<String>,
+    #[serde(default)]
+    pub physics_delta: Option<String>,
+    /// Atom the commit would carry, for `max_atom_size`
+    #[serde(default)]
+    pub atom_hash: Option<String>,
+}
+
+#[derive(Debug, Serialize, PartialEq, Eq)]
+pub struct LintWarning {
+    pub source: &'static str,
+    pub code: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub field: Option<String>,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LintIntentResp {
+    /// No warnings
+    pub ok: bool,
+    /// Policy decision, when a policy could be evaluated
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub decision: Option<TranslationDecision>,
+    pub warnings: Vec<LintWarning>,
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new().route("/lint/intent", post(route_lint_intent))
+}
+
+fn warning(source: &'static str, code: &'static str, message: impl Into<String>) -> LintWarning {
+    LintWarning {
+        source,
+        code,
+        field: None,
+        message: message.into(),
+    }
+}
+
+fn class_from_byte(b: u8) -> Option<IntentClass> {
+    [
+        IntentClass::Observation,
+        IntentClass::Conservation,
+        IntentClass::Entropy,
+        IntentClass::Evolution,
+    ]
+    .into_iter()
+    .find(|c| c.as_byte() == b)
+}
+
+/// POST /lint/intent
+async fn route_lint_intent(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<LintIntentReq>,
+) -> Json<LintIntentResp> {
+    let now = OffsetDateTime::now_utc().unix_timestamp();
+    let mut warnings: Vec<LintWarning> = intent_schema::check(&req.intent)
+        .into_iter()
+        .map(|i| LintWarning {
+            source: "schema",
+            code: i.code,
+            field: Some(i.field).filter(|f| !f.is_empty()),
+            message: i.message,
+        })
+        .collect();
+
+    let declared = match req.intent_class.as_deref().map(str::parse::<IntentClass>) {
+        Some(Ok(class)) => Some(class),
+        Some(Err(e)) => {
+            warnings.push(warning("policy", "invalid_intent_class", e));
+            None
+        }
+        None => None,
+    };
+    let delta: i128 = match &req.physics_delta {
+        Some(d) => d.parse().unwrap_or_else(|_| {
+            warnings.push(warning("constraints", "invalid_physics_delta", format!("invalid physics_delta: {}", d)));
+            0
+        }),
+        None => req.intent.get("amount").and_then(|v| v.as_i64()).unwrap_or(0) as i128,
+    };
+
+    // Policy decision, with the same selection and spec version as production
+    let spec = db::spec_version(&state.pool, &req.container_id).await.unwrap_or_else(|e| {
+        warnings.push(warning("policy", "spec_version_unavailable", e.to_string()));
+        SpecVersion::default()
+    });
+    let context = EvaluationContext {
+        container_id: req.container_id.clone(),
+        actor: String::new(),
+        intent: req.intent.clone(),
+        state: None,
+        timestamp: now,
+        spec,
+    };
+    let decision = {
+        let vm = state.policies.read().unwrap();
+        match &req.policy_id {
+            Some(id) => Some(vm.evaluate(id, &context)),
+            None if vm.resolve(&req.container_id).is_some() => Some(vm.evaluate_all(&req.container_id, &context)),
+            None => None,
+        }
+    };
+    let decision = match decision {
+        None => {
+            warnings.push(warning("policy", "no_policy_bound", "no policy_id given and none bound to the container"));
+            None
+        }
+        Some(Err(e)) => {
+            warnings.push(warning("policy", "policy_error", e.to_string()));
+            None
+        }
+        Some(Ok(d)) => Some(d),
+    };
+
+    let mut class = declared;
+    if let Some(decision) = &decision {
+        match decision {
+            TranslationDecision::Deny { code, detail } => {
+                warnings.push(warning(
+                    "policy",
+                    "policy_denied",
+                    format!("policy denied: {}", deny::describe(*code, detail.as_deref())),
+                ));
+            }
+            TranslationDecision::Allow {
+                intent_class,
+                required_pact,
+                constraints,
+            } => {
+                match declared {
+                    Some(c) if c.as_byte() != *intent_class => warnings.push(warning(
+                        "policy",
+                        "intent_class_mismatch",
+                        format!("policy allows intent class 0x{:02x}, draft is {}", intent_class, c.as_str()),
+                    )),
+                    Some(_) => {}
+                    None => class = class_from_byte(*intent_class),
+                }
+                if let Some(pact) = required_pact {
+                    warnings.push(warning("policy", "pact_required", format!("commit needs a proof for pact {}", pact)));
+                }
+                let atom_size = match &req.atom_hash {
+                    Some(hash) => crate::atom_db::size(&state.pool, hash).await.ok().flatten().map(|s| s as u64),
+                    None => None,
+                };
+                let facts = CommitFacts {
+                    container_id: req.container_id.clone(),
+                    intent_class: *intent_class,
+                    physics_delta: delta,
+                    timestamp: now,
+                    atom_size,
+                };
+                for v in ubl_policy_vm::violations(constraints, &facts) {
+                    warnings.push(warning("constraints", "constraint_violated", v.to_string()));
+                }
+            }
+        }
+    }
+
+    // ASC scopes of the caller, when one is presented
+    if let Some(auth_header) = headers.get("authorization").and_then(|v| v.to_str().ok()) {
+        match auth::extract_sid_from_header(auth_header) {
+            Err(e) => warnings.push(warning("asc", "asc_invalid", e.message())),
+            Ok(sid) => match auth::validate_asc(&state.pool, &sid).await {
+                Err(e) => warnings.push(warning("asc", "asc_invalid", e.message())),
+                Ok(mut asc) => {
+                    // Without a declared or decided class there is nothing to hold the class scope to
+                    if class.is_none() {
+                        asc.intent_classes.clear();
+                    }
+                    let class_name = class.map(|c| c.as_str()).unwrap_or_default();
+                    for v in auth::scope_violations(&asc, &req.container_id, class_name, &delta.to_string()) {
+                        warnings.push(warning("asc", "scope_exceeded", v.message()));
+                    }
+                }
+            },
+        }
+    }
+
+    info!(
+        "🧹 LINT container={} type={} warnings={}",
+        req.container_id,
+        req.intent.get("type").and_then(|v| v.as_str()).unwrap_or("?"),
+        warnings.len()
+    );
+    Json(LintIntentResp {
+        ok: warnings.is_empty(),
+        decision,
+        warnings,
+    })
+}
@@ -6,39 +6,201 @@
 //!
 //! Rotas:
 //! - GET  /health
-//! - GET  /state/:container_id  
+//! - GET  /ready (503 until cold-start warmup has primed pools and hot heads; see warmup.rs)
+//! - GET  /autoscale/signals (queue depths, DB pool pressure, SSE clients for HPA/KEDA)
+//! - GET  /state/:container_id (X-UBL-Consistency: read-your-writes against
+//!   the read replica; see consistency.rs)
+//!   (read endpoints answer ETag and Cache-Control, and 304 on a matching
+//!   If-None-Match; see http_cache.rs)
 //! - POST /link/validate
-//! - POST /link/commit
-//! - GET  /ledger/:container_id/tail (SSE with LISTEN/NOTIFY)
+//!   (`UBL_STRICT_HEX=1`: commit and validate answer 400 unless atom/previous
+//!   hashes and author key are 64 lowercase hex and the signature 128)
+//! - POST /link/commit (?debug=true for pipeline stages, RBAC-gated; policy
+//!   denials answer JSON with `deny_code`; success carries `consistency_token`;
+//!   Evolution drafts answer 202 and queue when `UBL_EVOLUTION_APPROVALS` is set;
+//!   with `UBL_POLICY_AUDIT_CONTAINER`, each policy decision is also appended
+//!   there as an Observation link, atomically with the commit; see policy_audit.rs;
+//!   under overload, commits below reserved priority answer 429 with a backoff
+//!   hint; see admission.rs)
+//! - POST /lint/intent (draft intent warnings; no state change)
+//! - GET  /ledger/:container_id/tail (SSE on the internal event bus)
+//! - GET  /ledger/heads/tail (SSE, every container; operator/auditor)
+//!   (commits are published on an in-process event bus that SSE and the
+//!   alert engine subscribe to, with per-subscriber backpressure; see event_bus.rs)
 //! - POST /id/agents (create LLM/App)
+//! - POST /id/agents/bulk (signed manifest import; admin)
 //! - POST /id/agents/{sid}/asc (issue ASC)
 //! - POST /id/agents/{sid}/rotate (rotate key)
 //! - GET  /id/whoami
+//! - GET  /id/ceremonies?username=|sid= (WebAuthn attempts; admin/operator)
+//! - POST/GET/DELETE /policy/:id (POST takes hex bytecode, WAT or a Cedar policy set)
+//! - POST /policy/evaluate (dry-run decision; no state change)
+//! - POST /policy/import/rego (translate Rego into CEL bytecode; no state change)
+//! - POST /policy/assemble/wat (assemble WAT into WASM bytecode and its hash; no state change)
+//! - POST /admin/policy/reload
+//! - GET /policy/timelock, GET /policy/pending, POST /policy/:id/versions/:version/veto
+//!   (activation delays per risk level; pending versions can be vetoed by pact)
+//! - GET/PUT /policy/:id/rollout, POST /policy/:id/rollout/halt (percentage rollout
+//!   of a version, halted automatically on its deny rate; see policy_rollout.rs)
+//! - GET/POST/DELETE /policy-bindings, GET /policy-bindings/resolve/:container_id
+//!   (namespace-level policy bindings; commits are evaluated against every matching layer)
+//! - GET/POST/DELETE /containers/:id/policies[/:policy_id], PUT /containers/:id/composition
+//! - GET /containers/dependencies, GET /containers/:id/dependencies (declared by the
+//!   `manifest` on a container's first link; its dependencies must already exist;
+//!   its `spec_version` fixes the SPEC-UBL-* rules the container's links are
+//!   validated and evaluated under, the latest by default)
+//! - POST /pacts, GET /pacts/:id, POST /pacts/:id/{revoke,renew}, GET /containers/:id/authority
+//!   (a container whose manifest names an `authority_pact` accepts Observation
+//!   only while that pact is expired or revoked; see pact_routes.rs)
+//! - GET /pacts?scope=&container_id=&namespace=&risk_level=&active= (discover the
+//!   pacts that can authorize an intent on a container instead of hard-coding ids)
+//! - POST /pacts/:id/successor, GET /pacts/:id/lineage (renewal by a successor
+//!   pact that carries the terms forward, and the lineage it forms)
+//! - POST /pacts/drafts, GET /pacts/drafts/:draft_hash,
+//!   POST /pacts/drafts/:draft_hash/{signatures,activate} (pact creation ceremony:
+//!   propose, collect each signer's signature over the draft hash, activate once
+//!   the pact's own quorum is met; see pact_drafts.rs)
+//! - POST /pacts/proofs, GET /pacts/proofs/:link_hash[/proof],
+//!   POST /pacts/proofs/:link_hash/signatures (pact proofs collected on the
+//!   server, one signature at a time, until the threshold is met; each completed
+//!   proof spends one of the pact's `max_uses`, if it has any; see pact_proofs.rs)
+//! - GET /pacts/expiring?within= (pacts nearing `not_after`, also exported as
+//!   `ubl_pact_expires_in_seconds` and logged by a background sweep; see pact_expiry.rs)
+//! - GET /pacts/:pact_id/validations?link_hash= (audit log of pact proof validations,
+//!   accepted or refused, with the signatures counted and rejected; see pact_audit.rs)
+//! - POST /pacts/export, POST /pacts/import (signed pact bundles to move pacts between
+//!   environments; admin; see pact_bundle.rs)
+//! - GET/POST /pacts/freeze, POST /pacts/freeze/lift (emergency freeze: a quorum under a
+//!   Global emergency pact holds every container to Observation links until another lifts
+//!   it; see pact_freeze.rs)
+//! - POST/GET/DELETE /containers/:id/grants[/:grant_id], GET /containers/:id/admin/audit
+//!   (container-scoped; capability grants or admin)
+//! - GET/POST /alerts, GET/PUT/DELETE /alerts/:alert_id, GET /alerts/notifications,
+//!   POST /alerts/notifications/:id/read (watch-only rules on readable containers)
+//! - POST/GET /admin/archive/:container_id, POST /admin/archive/:container_id/verify
+//!   (ledger segments in blob storage; see blob/)
+//! - GET /admin/ledger/hashes, POST /admin/ledger/hashes/{backfill,verify,flip}
+//!   (entry hash format migration; original chain never rewritten)
+//! - GET /admin/usage (request counts, error rates and top callers per route and
+//!   X-UBL-Tenant, rolled up daily in Postgres; see usage.rs)
+//! - PUT /atoms, GET /atoms/:atom_hash[/meta] (content-addressed atoms,
+//!   zstd-compressed in chunks, with range reads; see atom_store/)
+//! - GET /admin/region/status, POST /admin/region/{promote,fence} (active-passive
+//!   regions: standby replication, checkpoint-verified promotion, fencing
+//!   epochs in X-UBL-Fence; see region.rs)
+//! - GET /attestations/key, GET /containers/:id/attestations/dormant?from=&to=
+//!   (signed statement that a container's head did not move over a range, from
+//!   region checkpoints, verifiable offline; see attestation.rs)
+//! - POST /settlements, GET /settlements?merchant=, GET /settlements/:id (buyer
+//!   debits and the balancing merchant credit appended in one transaction,
+//!   with per-item provenance; see settlement.rs)
+//! - POST/GET /admin/break-glass, GET /admin/break-glass/:id[/report],
+//!   POST /admin/break-glass/:id/{approve,close} (time-boxed bypass of policy
+//!   evaluation or the Evolution queue, justified and approved by a second SID;
+//!   commits opt in with X-UBL-Break-Glass and are tagged; see break_glass.rs)
+//! - GET /admin/replay/:container_id?from=&limit= (steps through a container's
+//!   history: ledger state, each validator stage's decision, and the pact and
+//!   policy versions in force at every entry; see replay.rs)
+//! - POST /admin/support-bundle (redacted diagnostic tar for bug reports; see
+//!   support_bundle.rs)
+//! - GET /errors (catalog of every error code the server can emit, with HTTP
+//!   status and parameters, generated from the error enums; see error_routes.rs)
+//! - GET  /governance/:container_id/history
+//! - GET /governance/evolutions[/:id], POST /governance/evolutions/:id/{approve,reject}
+//!   (Evolution commits held for approval by other SIDs; see evolution_routes.rs)
 
+mod admission;
 mod db;
+mod event_bus;
 mod sse;
 mod id_db;
 mod id_routes;
 mod auth;
+mod ceremony;
+mod ceremony_db;
 mod rate_limit;
 mod metrics;
 mod id_ledger;
 mod id_session_token;
 mod repo_routes;
 mod middleware_require_stepup;
+mod pipeline;
+mod governance_routes;
+mod policy_db;
+mod policy_rollout;
+mod policy_routes;
+mod policy_state;
+mod policy_audit;
+mod policy_timelock;
+mod intent_schema;
+mod lint_routes;
+mod container_routes;
+mod alert_db;
+mod alert_routes;
+mod blob;
+mod archive_db;
+mod archive_routes;
+mod entry_hash;
+mod rehash_db;
+mod rehash_routes;
+mod consistency;
+mod http_cache;
+mod dependency_db;
+mod pact_db;
+mod pact_routes;
+mod pact_drafts;
+mod pact_proofs;
+mod pact_expiry;
+mod pact_audit;
+mod pact_cache;
+mod pact_bundle;
+mod pact_freeze;
+mod evolution_db;
+mod evolution_routes;
+mod autoscale;
+mod warmup;
+mod usage;
+mod usage_db;
+mod support_bundle;
+mod support_db;
+mod atom_db;
+mod atom_store;
+mod region;
+mod region_db;
+mod attestation;
+mod settlement;
+mod settlement_db;
+mod break_glass;
+mod break_glass_db;
+mod replay;
+mod replay_db;
+mod error_routes;
+#[cfg(test)]
+mod event_contracts;
 
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::{StatusCode, HeaderMap},
-    response::IntoResponse,
+    response::{IntoResponse, Response},
     routing::{get, post},
     Json, Router,
 };
+use consistency::{ConsistencyToken, Served};
 use db::{LedgerEntry, LinkDraft, PgLedger, TangencyError};
-use serde::Serialize;
+use pipeline::{PipelineTrace, StageRecord};
+use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
+use std::sync::{Arc, RwLock};
+use std::time::Instant;
 use tower_http::cors::{Any, CorsLayer};
-use tracing::{error, info};
+use tracing::{error, info, warn};
+use ubl_errors::ErrorCatalog;
+use ubl_link::IntentClass;
+use ubl_membrane::ContainerProfile;
+use time::OffsetDateTime;
+use ubl_policy_vm::{
+    deny, CommitFacts, DenyCode, EvaluationContext, PolicyError, PolicyVM, SpecVersion, TranslationDecision,
+};
 use webauthn_rs::prelude::*;
 
 // ============================================================================
@@ -49,6 +211,40 @@ use webauthn_rs::prelude::*;
 struct AppState {
     pool: PgPool,
     ledger: PgLedger,
+    policies: Arc<RwLock<PolicyVM>>,
+    blobs: blob::BlobStore,
+    /// `GET /state` reads here when set
+    replica: Option<consistency::ReadReplica>,
+    /// Distinct approvals an Evolution commit needs; `None` appends directly
+    evolution_approvals: Option<i32>,
+    /// Reject drafts whose hex fields are not full width (`UBL_STRICT_HEX`)
+    strict_hex: bool,
+    /// Wall-clock budget of the commit path's policy decision
+    policy_budget: std::time::Duration,
+    /// Activation delays per risk level and the veto pact
+    timelock: Arc<policy_timelock::Timelock>,
+    /// Set once warmup has finished; `/ready` answers 503 until then
+    warmup: Arc<std::sync::OnceLock<warmup::WarmupReport>>,
+    /// Request counts awaiting the next usage flush
+    usage: Arc<usage::Tally>,
+    /// Atom store size limits
+    atoms: atom_store::AtomConfig,
+    /// Latest 5xx answers, scrubbed, for the support bundle
+    errors: Arc<support_bundle::ErrorSamples>,
+    /// This region's role and fencing epoch (`UBL_REGION`)
+    region: Option<Arc<region::Region>>,
+    /// Key dormancy attestations are signed with (`UBL_ATTESTATION_KEY`)
+    attestation_key: Option<Arc<ed25519_dalek::SigningKey>>,
+    /// Container policy decisions are written to (`UBL_POLICY_AUDIT_CONTAINER`)
+    policy_audit: Option<String>,
+    /// Commits and the ledger feed, fanned out to SSE and the alert engine
+    bus: event_bus::EventBus,
+    /// Commits in flight and database latency, for shedding under overload
+    admission: Arc<admission::Admission>,
+    /// Pact validations, written to `pact_validation_event`
+    pact_audit: pact_audit::PgValidationSink,
+    /// Pacts and container authorities read on the commit path
+    pacts: Arc<pact_cache::PactCache>,
 }
 
 // ============================================================================
@@ -70,6 +266,27 @@ struct Decision {
 struct CommitSuccess {
     ok: bool,
     entry: LedgerEntry,
+    /// Send back in `X-UBL-Consistency` to read this entry from the replica
+    consistency_token: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    trace: Option<Vec<StageRecord>>,
+}
+
+#[derive(Serialize)]
+struct CommitFailure {
+    ok: bool,
+    error: String,
+    /// Set when a policy denied the commit
+    #[serde(skip_serializing_if = "Option::is_none")]
+    deny_code: Option<DenyCode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    trace: Option<Vec<StageRecord>>,
+}
+
+#[derive(Deserialize, Default)]
+struct CommitQuery {
+    #[serde(default)]
+    debug: bool,
 }
 
 #[derive(Serialize)]
@@ -96,133 +313,620 @@ async fn route_health() -> Json<HealthResponse> {
 async fn route_state(
     State(state): State<AppState>,
     Path(container_id): Path<String>,
-) -> Result<Json<StateResponse>, (StatusCode, String)> {
-    match state.ledger.get_state(&container_id).await {
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let token = match headers.get(consistency::HEADER) {
+        Some(v) => {
+            let token: ConsistencyToken = v
+                .to_str()
+                .map_err(|_| (StatusCode::BAD_REQUEST, "invalid consistency token".to_string()))?
+                .parse()
+                .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+            if token.container_id != container_id {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    format!("consistency token is for {}, not {}", token.container_id, container_id),
+                ));
+            }
+            Some(token)
+        }
+        None => None,
+    };
+
+    let (served, ledger, pool) = match &state.replica {
+        Some(replica) => match replica.route(token.as_ref()).await {
+            Served::Replica => (Served::Replica, &replica.ledger, &replica.pool),
+            Served::Primary => (Served::Primary, &state.ledger, &state.pool),
+        },
+        None => (Served::Primary, &state.ledger, &state.pool),
+    };
+
+    // The head hash tags the state; a match skips the entry count
+    let served_by = [(consistency::SERVED_BY_HEADER, served.as_str())];
+    let response = match ledger.get_state(&container_id).await {
         Ok(entry) => {
+            let etag = http_cache::etag(&entry.entry_hash);
+            if http_cache::not_modified(&headers, &etag) {
+                return Ok((served_by, http_cache::not_modified_response("state", &etag, http_cache::REVALIDATE)));
+            }
             // Get entry count
             let count = sqlx::query_scalar::<_, i64>(
                 "SELECT COUNT(*) FROM ledger_entry WHERE container_id = $1"
             )
             .bind(&container_id)
-            .fetch_one(&state.pool)
+            .fetch_one(pool)
             .await
             .unwrap_or(0);
 
-            Ok(Json(StateResponse {
+            let body = StateResponse {
                 container_id: entry.container_id,
                 sequence: entry.sequence,
                 last_hash: entry.entry_hash,
                 entry_count: count,
-            }))
+            };
+            http_cache::tagged("state", &headers, &etag, http_cache::REVALIDATE, body)
         }
         Err(_) => {
             // Genesis state
-            Ok(Json(StateResponse {
+            let body = StateResponse {
                 container_id,
                 sequence: 0,
                 last_hash: "0x00".to_string(),
                 entry_count: 0,
-            }))
+            };
+            http_cache::tagged("state", &headers, &http_cache::etag("0x00"), http_cache::REVALIDATE, body)
         }
-    }
+    };
+    Ok((served_by, response))
 }
 
 /// POST /link/validate
 /// Basic validation - in production, inject full Membrane here
 async fn route_validate(
-    State(_state): State<AppState>,
-    Json(_link): Json<LinkDraft>,
-) -> Json<Decision> {
+    State(state): State<AppState>,
+    Json(link): Json<LinkDraft>,
+) -> Result<Json<Decision>, (StatusCode, String)> {
+    if state.strict_hex {
+        link.check_hex().map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+    }
     // TODO: Apply SPEC-UBL-MEMBRANE v1.0 §V1-V9 validations
     // For now, simplified validation
-    Json(Decision {
+    Ok(Json(Decision {
         decision: "Accept",
-    })
+    }))
 }
 
 /// POST /link/commit
 /// Atomic append with SERIALIZABLE transaction + ASC validation
+///
+/// `?debug=true` (admin/operator/developer session) returns the ordered
+/// pipeline stages with outcome and duration, on success and on rejection.
 async fn route_commit(
     State(state): State<AppState>,
+    Query(query): Query<CommitQuery>,
     headers: HeaderMap,
-    Json(link): Json<LinkDraft>,
-) -> Result<Json<CommitSuccess>, (StatusCode, String)> {
+    Json(mut link): Json<LinkDraft>,
+) -> Result<Response, Response> {
     info!(
         "📝 COMMIT seq={} container={} class={}",
         link.expected_sequence, link.container_id, link.intent_class
     );
 
+    if query.debug {
+        auth::rbac::require_role(
+            &state.pool,
+            &headers,
+            &[auth::rbac::ADMIN, auth::rbac::OPERATOR, auth::rbac::DEVELOPER],
+        )
+        .await
+        .map_err(IntoResponse::into_response)?;
+    }
+
+    let mut trace = PipelineTrace::new();
+
+    // Shed below reserved priority under overload, before the pipeline starts
+    let t = Instant::now();
+    let priority = admission::Priority::of(&link, &headers);
+    let _inflight = state.admission.admit(priority).map_err(IntoResponse::into_response)?;
+    trace.pass("admission", t);
+
+    // Only the primary region appends; a newer fence retires this one
+    if state.region.is_some() {
+        let t = Instant::now();
+        if let Err((status, reason)) = region::admit(&state, &headers).await {
+            warn!(container_id = %link.container_id, decision = "reject", error_code = "region", reason = %reason);
+            trace.fail("region", t, reason.clone());
+            return Err(reject(query.debug, status, &reason, trace));
+        }
+        trace.pass("region", t);
+    }
+
+    if state.strict_hex {
+        let t = Instant::now();
+        if let Err(e) = link.check_hex() {
+            warn!(container_id = %link.container_id, decision = "reject", error_code = "strict_hex", reason = %e);
+            trace.fail("strict_hex", t, e.to_string());
+            return Err(reject(query.debug, StatusCode::BAD_REQUEST, &e.to_string(), trace));
+        }
+        trace.pass("strict_hex", t);
+    }
+
     // ASC Validation (PR29)
     if let Some(auth_header) = headers.get("authorization") {
-        let auth_str = auth_header.to_str().map_err(|_| {
-            (StatusCode::BAD_REQUEST, "Invalid authorization header".to_string())
-        })?;
-
-        // Extract SID
-        let sid = auth::extract_sid_from_header(auth_str).map_err(|e| {
-            error!("❌ AUTH ERROR: {}", e.message());
-            (e.status_code(), e.message())
-        })?;
-
-        // Validate ASC
-        let asc_context = auth::validate_asc(&state.pool, &sid).await.map_err(|e| {
-            error!("❌ ASC VALIDATION FAILED: {}", e.message());
-            (e.status_code(), e.message())
-        })?;
+        let t = Instant::now();
+        let auth_str = match auth_header.to_str() {
+            Ok(s) => s,
+            Err(_) => {
+                trace.fail("asc_validation", t, "Invalid authorization header");
+                return Err(reject(query.debug, StatusCode::BAD_REQUEST, "Invalid authorization header", trace));
+            }
+        };
+
+        // Extract SID and validate ASC
+        let sid = match auth::extract_sid_from_header(auth_str) {
+            Ok(sid) => sid,
+            Err(e) => {
+                error!("❌ AUTH ERROR: {}", e.message());
+                trace.fail("asc_validation", t, e.message());
+                return Err(reject(query.debug, e.status_code(), &e.message(), trace));
+            }
+        };
+        let asc_context = match auth::validate_asc(&state.pool, &sid).await {
+            Ok(ctx) => ctx,
+            Err(e) => {
+                error!("❌ ASC VALIDATION FAILED: {}", e.message());
+                trace.fail("asc_validation", t, e.message());
+                return Err(reject(query.debug, e.status_code(), &e.message(), trace));
+            }
+        };
+        trace.pass("asc_validation", t);
 
         // Validate commit scopes
-        auth::validate_commit_scopes(
+        let t = Instant::now();
+        if let Err(e) = auth::validate_commit_scopes(
             &asc_context,
             &link.container_id,
             &link.intent_class,
             &link.physics_delta,
-        ).map_err(|e| {
+        ) {
             error!("❌ SCOPE VIOLATION: {}", e.message());
-            (e.status_code(), e.message())
-        })?;
+            trace.fail("asc_scopes", t, e.message());
+            return Err(reject(query.debug, e.status_code(), &e.message(), trace));
+        }
+        trace.pass("asc_scopes", t);
 
-        info!("✅ ASC VALIDATED sid={} containers={:?}", sid, asc_context.containers);
+        info!("✅ ASC VALIDATED sid={} containers={:?}", asc_context.sid, asc_context.containers);
     } else {
         // No ASC provided - allow for now (TODO: make required in production)
         info!("⚠️  No ASC provided (dev mode - allowing)");
+        trace.skip("asc_validation", "no ASC provided");
+    }
+
+    // Container profile (SPEC-UBL-MEMBRANE v1.0 §V6): governance containers are zero-delta
+    let t = Instant::now();
+    if let Err(reason) = check_profile(&link).and_then(|()| policy_audit::check_target(&state, &link)) {
+        error!("❌ REJECTED: {}", reason);
+        trace.fail("v6_profile", t, reason.clone());
+        return Err(reject(query.debug, StatusCode::UNPROCESSABLE_ENTITY, &reason, trace));
+    }
+    trace.pass("v6_profile", t);
+
+    // Container manifest: declared dependencies must already exist
+    let t = Instant::now();
+    if let Err((status, reason)) = check_manifest(&state, &link).await {
+        error!("❌ REJECTED: {}", reason);
+        trace.fail("manifest", t, reason.clone());
+        return Err(reject(query.debug, status, &reason, trace));
+    }
+    match &link.manifest {
+        Some(_) => trace.pass("manifest", t),
+        None => trace.skip("manifest", "no manifest"),
+    }
+
+    // Emergency freeze, then authority pact: either restricts the container to Observation
+    let t = Instant::now();
+    match pact_routes::admit(&state, &link).await {
+        Ok(Some(_)) => trace.pass("authority", t),
+        Ok(None) => trace.skip("authority", "no authority pact"),
+        Err((status, reason)) => {
+            error!("❌ REJECTED: {}", reason);
+            trace.fail("authority", t, reason.clone());
+            return Err(reject(query.debug, status, &reason, trace));
+        }
     }
 
-    match state.ledger.append(&link).await {
+    // Break-glass: an approved override bypasses one subsystem, and tags the entry
+    let t = Instant::now();
+    let engaged = match break_glass::engage(&state, &headers).await {
+        Ok(engaged) => engaged,
+        Err((status, reason)) => {
+            error!("❌ REJECTED: {}", reason);
+            trace.fail("break_glass", t, reason.clone());
+            return Err(reject(query.debug, status, &reason, trace));
+        }
+    };
+    match &engaged {
+        Some(_) => trace.pass("break_glass", t),
+        None => trace.skip("break_glass", "no override"),
+    }
+    let bypass = |subsystem| engaged.as_ref().is_some_and(|e| e.bypasses(subsystem));
+
+    // Policy decision and its constraint snapshot (SPEC-UBL-POLICY v1.0 §6)
+    let t = Instant::now();
+    let mut audit = None;
+    let decision = if bypass(break_glass::Subsystem::Policy) {
+        Ok(None)
+    } else {
+        check_policy_recorded(&state, &link, &mut audit).await
+    };
+    match decision {
+        Ok(Some(decided_by)) => {
+            trace.pass("policy", t);
+            // The prevailing policy is kept with the entry for audit
+            link.metadata = Some(serde_json::json!({ "policy": { "decided_by": decided_by } }));
+        }
+        Ok(None) if bypass(break_glass::Subsystem::Policy) => trace.skip("policy", "bypassed by break-glass"),
+        Ok(None) => trace.skip("policy", "no policy bound"),
+        Err((status, reason, deny_code)) => {
+            error!("❌ POLICY REJECTED: {}", reason);
+            trace.fail("policy", t, reason.clone());
+            if let Some(record) = &audit {
+                policy_audit::record_rejection(&state, record).await;
+            }
+            return Err(match deny_code {
+                Some(code) => reject_denied(query.debug, status, code, &reason, trace),
+                None => reject(query.debug, status, &reason, trace),
+            });
+        }
+    }
+
+    // Under governance, Evolution waits for other SIDs to approve it
+    if let Some(approvals) = state.evolution_approvals {
+        if matches!(link.intent_class.parse(), Ok(IntentClass::Evolution))
+            && !bypass(break_glass::Subsystem::EvolutionQueue)
+        {
+            return evolution_routes::enqueue(&state, &headers, &link, approvals, query.debug, trace)
+                .await
+                .map_err(IntoResponse::into_response);
+        }
+    }
+
+    if let Some(e) = &engaged {
+        e.tag(&mut link);
+    }
+    match append_commit(&state, &link, engaged.as_ref(), audit.as_ref(), &mut trace).await {
         Ok(entry) => {
             info!("✅ ACCEPTED seq={} hash={}", entry.sequence, &entry.entry_hash[..8]);
-            
-            Ok(Json(CommitSuccess {
+
+            let mut response = Json(CommitSuccess {
                 ok: true,
+                consistency_token: ConsistencyToken {
+                    container_id: entry.container_id.clone(),
+                    sequence: entry.sequence,
+                }
+                .to_string(),
                 entry,
-            }))
+                trace: query.debug.then(|| trace.into_stages()),
+            })
+            .into_response();
+            if let Some(region) = &state.region {
+                response
+                    .headers_mut()
+                    .insert(region::FENCE_HEADER, axum::http::HeaderValue::from(region.epoch()));
+            }
+            Ok(response)
+        }
+        Err(e) => {
+            error!("❌ REJECTED: {}", e.code());
+            Err(reject(query.debug, error_routes::status(&e), e.code(), trace))
+        }
+    }
+}
+
+/// Append a commit with what must land in its transaction: the action
+/// record of a break-glass override, and the policy decision's audit link.
+/// The entry is published on the event bus once committed.
+async fn append_commit(
+    state: &AppState,
+    link: &LinkDraft,
+    engaged: Option<&break_glass::Engaged>,
+    audit: Option<&policy_audit::DecisionRecord>,
+    trace: &mut PipelineTrace,
+) -> Result<LedgerEntry, TangencyError> {
+    let audit = state.policy_audit.as_deref().zip(audit);
+    let entry = if engaged.is_none() && audit.is_none() {
+        state.ledger.append(link, trace).await?
+    } else {
+        let mut tx = state.ledger.begin(trace).await?;
+        let entry = db::append_in(&mut tx, link, trace).await?;
+        if let Some(engaged) = engaged {
+            break_glass::record_in(&mut tx, link, engaged, &entry).await;
+        }
+        if let Some((container, record)) = audit {
+            policy_audit::append_in(&mut tx, container, record, Some(&entry), trace).await?;
+        }
+        db::commit(tx).await?;
+        entry
+    };
+    state.bus.committed(&entry);
+    Ok(entry)
+}
+
+/// Apply the target container's membrane profile to a draft
+fn check_profile(link: &LinkDraft) -> Result<(), String> {
+    let profile = ContainerProfile::for_container(&link.container_id);
+    if profile == ContainerProfile::Standard {
+        if !link.affects.is_empty() {
+            return Err("only governance containers may declare affected containers".to_string());
         }
-        Err(TangencyError::RealityDrift) => {
-            error!("❌ REJECTED: RealityDrift");
-            Err((StatusCode::CONFLICT, "RealityDrift".into()))
+        return Ok(());
+    }
+    let class: IntentClass = link.intent_class.parse()?;
+    let delta: i128 = link
+        .physics_delta
+        .parse()
+        .map_err(|_| format!("invalid physics_delta: {}", link.physics_delta))?;
+    profile.check(class, delta).map_err(|e| e.to_string())
+}
+
+/// A manifest rides only on a container's first link, and names distinct,
+/// existing containers other than itself
+async fn check_manifest(state: &AppState, link: &LinkDraft) -> Result<(), (StatusCode, String)> {
+    let Some(manifest) = &link.manifest else {
+        return Ok(());
+    };
+    let unprocessable = |msg: String| (StatusCode::UNPROCESSABLE_ENTITY, msg);
+    if link.expected_sequence != 1 {
+        return Err(unprocessable("a container manifest is only accepted on its first link".to_string()));
+    }
+    let mut seen = std::collections::HashSet::new();
+    for dep in &manifest.depends_on {
+        if dep.is_empty() || *dep == link.container_id {
+            return Err(unprocessable(format!("container cannot depend on {:?}", dep)));
         }
-        Err(TangencyError::SequenceMismatch) => {
-            error!("❌ REJECTED: SequenceMismatch");
-            Err((StatusCode::CONFLICT, "SequenceMismatch".into()))
+        if !seen.insert(dep) {
+            return Err(unprocessable(format!("dependency {} listed twice", dep)));
         }
-        Err(TangencyError::InvalidVersion) => {
-            error!("❌ REJECTED: InvalidVersion");
-            Err((StatusCode::BAD_REQUEST, "InvalidVersion".into()))
+    }
+    if let Some(pact_id) = &manifest.authority_pact {
+        let registered = state
+            .pacts
+            .pact(&state.pool, pact_id)
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        let Some(registered) = registered else {
+            return Err(unprocessable(format!("authority pact {} is not registered", pact_id)));
+        };
+        if !pact_routes::may_govern(&registered, &link.container_id) {
+            return Err(unprocessable(format!(
+                "authority pact {} is scoped to another container",
+                pact_id
+            )));
         }
-        Err(TangencyError::InvalidTarget) => {
-            error!("❌ REJECTED: InvalidTarget");
-            Err((StatusCode::BAD_REQUEST, "InvalidTarget".into()))
+    }
+    let existing = dependency_db::existing(&state.pool, &manifest.depends_on)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    match manifest.depends_on.iter().find(|d| !existing.contains(d)) {
+        Some(missing) => Err(unprocessable(format!("depends on unknown container {}", missing))),
+        None => Ok(()),
+    }
+}
+
+/// Evaluate the policies governing the draft and hold the commit to the decision:
+/// Deny rejects (with its code), and an Allow binds the intent class and every constraint.
+///
+/// Policies bound to the container (directly or by namespace) always govern;
+/// a draft's `policy_id` must then be one of them. On an unbound container
+/// the draft's `policy_id`, if any, is evaluated alone. Returns the policy
+/// whose decision prevailed (`compose::resolve`), if one was evaluated.
+///
+/// Evaluation runs under `state.policy_budget` and answers 503 when it
+/// overruns; the commit is not applied and may be retried.
+async fn check_policy(state: &AppState, link: &LinkDraft) -> Result<Option<String>, (StatusCode, String, Option<DenyCode>)> {
+    check_policy_recorded(state, link, &mut None).await
+}
+
+/// [`check_policy`], leaving the decision taken, if one was, in `record`
+/// for the audit trail (`policy_audit.rs`)
+async fn check_policy_recorded(
+    state: &AppState,
+    link: &LinkDraft,
+    record: &mut Option<policy_audit::DecisionRecord>,
+) -> Result<Option<String>, (StatusCode, String, Option<DenyCode>)> {
+    let bound = {
+        let vm = state.policies.read().unwrap();
+        let layers = vm.layers(&link.container_id);
+        (!layers.is_empty()).then(|| {
+            layers
+                .into_iter()
+                .flat_map(|(_, ids)| ids.into_iter().map(str::to_string))
+                .collect::<Vec<_>>()
+        })
+    };
+    if let (Some(bound), Some(policy_id)) = (&bound, &link.policy_id) {
+        if !bound.contains(policy_id) {
+            return Err((
+                StatusCode::UNPROCESSABLE_ENTITY,
+                format!("container {} is governed by {:?}, not {}", link.container_id, bound, policy_id),
+                None,
+            ));
         }
     }
+    if bound.is_none() && link.policy_id.is_none() {
+        return Ok(None);
+    }
+    let unprocessable = |msg: String| (StatusCode::UNPROCESSABLE_ENTITY, msg, None);
+    let class: IntentClass = link.intent_class.parse().map_err(unprocessable)?;
+    let delta: i128 = link
+        .physics_delta
+        .parse()
+        .map_err(|_| unprocessable(format!("invalid physics_delta: {}", link.physics_delta)))?;
+    let now = OffsetDateTime::now_utc().unix_timestamp();
+    // A first link is evaluated under the version its manifest declares
+    let spec = if link.expected_sequence == 1 {
+        link.manifest.as_ref().and_then(|m| m.spec_version).unwrap_or(SpecVersion::LATEST)
+    } else {
+        db::spec_version(&state.pool, &link.container_id)
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string(), None))?
+    };
+
+    let context = EvaluationContext {
+        container_id: link.container_id.clone(),
+        actor: link.author_pubkey.clone(),
+        intent: link.intent.clone().unwrap_or(serde_json::Value::Null),
+        state: None,
+        timestamp: now,
+        spec,
+    };
+    let budget = state.policy_budget;
+    let (decision, decided_by) = match (&bound, &link.policy_id) {
+        (None, Some(policy_id)) => PolicyVM::evaluate_async(&state.policies, policy_id, &context, budget)
+            .await
+            .map(|d| (d, Some(policy_id.clone()))),
+        _ => PolicyVM::evaluate_resolved_async(&state.policies, &link.container_id, &context, budget)
+            .await
+            .map(|r| (r.decision, r.decided_by)),
+    }
+    .map_err(|e| match e {
+        PolicyError::PolicyNotFound(_) | PolicyError::NoActiveVersion { .. } => {
+            (StatusCode::NOT_FOUND, e.to_string(), None)
+        }
+        PolicyError::Timeout => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            format!("policy evaluation exceeded its {}µs budget", budget.as_micros()),
+            None,
+        ),
+        _ => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string(), None),
+    })?;
+    let version = decided_by.as_deref().and_then(|id| {
+        let vm = state.policies.read().unwrap();
+        vm.version_for(id, &context).ok().map(|p| p.version.clone())
+    });
+    let audited = record.insert(policy_audit::DecisionRecord {
+        container_id: link.container_id.clone(),
+        actor: link.author_pubkey.clone(),
+        policy_id: decided_by.clone(),
+        version,
+        decision: decision.clone(),
+        rejected: None,
+    });
+
+    match decision {
+        TranslationDecision::Deny { code, detail } => Err((
+            StatusCode::FORBIDDEN,
+            format!("policy denied: {}", deny::describe(code, detail.as_deref())),
+            Some(code),
+        )),
+        TranslationDecision::Allow { intent_class, constraints, .. } => {
+            if intent_class != class.as_byte() {
+                return Err(unprocessable(format!(
+                    "policy allows intent class 0x{:02x}, commit is {}",
+                    intent_class,
+                    class.as_str()
+                )));
+            }
+            // Only a size bound needs the atom store
+            let atom_size = if constraints.iter().any(|c| c.kind == "max_atom_size") {
+                atom_db::size(&state.pool, &link.atom_hash)
+                    .await
+                    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string(), None))?
+                    .map(|s| s as u64)
+            } else {
+                None
+            };
+            let facts = CommitFacts {
+                container_id: link.container_id.clone(),
+                intent_class,
+                physics_delta: delta,
+                timestamp: now,
+                atom_size,
+            };
+            ubl_policy_vm::enforce(&constraints, &facts).map(|()| decided_by).map_err(|e| {
+                if let PolicyError::ConstraintViolated { kind, .. } = &e {
+                    metrics::POLICY_CONSTRAINT_VIOLATIONS.with_label_values(&[kind.as_str()]).inc();
+                }
+                audited.rejected = Some(e.to_string());
+                unprocessable(e.to_string())
+            })
+        }
+    }
+}
+
+/// Rejection body: plain text as before, or JSON with the trace in debug mode
+fn reject(debug: bool, status: StatusCode, message: &str, trace: PipelineTrace) -> Response {
+    if debug {
+        (
+            status,
+            Json(CommitFailure {
+                ok: false,
+                error: message.to_string(),
+                deny_code: None,
+                trace: Some(trace.into_stages()),
+            }),
+        )
+            .into_response()
+    } else {
+        (status, message.to_string()).into_response()
+    }
+}
+
+/// Policy denial body: always JSON, so clients can branch on `deny_code`
+fn reject_denied(debug: bool, status: StatusCode, code: DenyCode, message: &str, trace: PipelineTrace) -> Response {
+    (
+        status,
+        Json(CommitFailure {
+            ok: false,
+            error: message.to_string(),
+            deny_code: Some(code),
+            trace: debug.then(|| trace.into_stages()),
+        }),
+    )
+        .into_response()
 }
 
 /// GET /ledger/:container_id/tail
-/// SSE stream with PostgreSQL LISTEN/NOTIFY (PR10)
+/// SSE stream of the container's entries from the event bus feed (PR10)
 async fn route_tail(
     State(state): State<AppState>,
     Path(container_id): Path<String>,
 ) -> impl IntoResponse {
     info!("📡 SSE tail requested for: {}", container_id);
-    sse::sse_tail(state.pool.clone(), container_id).await
+    sse::sse_tail(state.bus.clone(), container_id).await
+}
+
+#[derive(Deserialize, Default)]
+struct HeadsQuery {
+    after: Option<i64>,
+}
+
+/// GET /ledger/heads/tail
+/// One stream of heads for every container (operator/auditor only)
+async fn route_heads_tail(
+    State(state): State<AppState>,
+    Query(query): Query<HeadsQuery>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    auth::rbac::require_role(&state.pool, &headers, &[auth::rbac::OPERATOR, auth::rbac::AUDITOR]).await?;
+
+    // Resume from ?after=, else Last-Event-ID, else only new commits
+    let after = match query.after.or_else(|| {
+        headers
+            .get("last-event-id")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse().ok())
+    }) {
+        Some(after) => after,
+        None => sqlx::query_scalar!("SELECT COALESCE(MAX(id), 0) FROM ledger_entry")
+            .fetch_one(&state.pool)
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+            .unwrap_or(0),
+    };
+
+    info!("📡 SSE heads tail requested after={}", after);
+    Ok(sse::sse_heads(state.pool.clone(), state.bus.clone(), after).await)
 }
 
 // ============================================================================
@@ -250,10 +954,111 @@ async fn main() -> anyhow::Result<()> {
     let pool = PgPool::connect(&database_url).await?;
     info!("✅ PostgreSQL connected");
 
+    let governance = policy_routes::governance_from_env();
+    if let Some(g) = &governance {
+        info!("🏛️  Policy governance: {} key(s), threshold {}", g.keys().count(), g.threshold());
+    }
+    let (mut policies, rejected) = policy_db::load_vm(&pool, governance).await?;
+    info!("📜 Policies loaded: {} ({} rejected)", policies.len(), rejected.len());
+    if let Some(capacity) = policy_routes::decision_cache_from_env() {
+        policies.enable_decision_cache(capacity);
+        info!("🗃️  Policy decision cache: {} entries", capacity);
+    }
+    policies.set_observer(Some(Arc::new(metrics::observe_policy)));
+    let module_cache = policy_routes::module_cache_from_env();
+    let runtime = Arc::new(ubl_policy_vm::WasmRuntime::new(module_cache)?);
+    metrics::watch_module_cache(runtime.clone());
+    policies.set_wasm_runtime(Some(runtime));
+    policies.set_state_reader(Some(Arc::new(policy_state::PgStateReader::new(pool.clone()))));
+    policies.set_external_engine(Arc::new(ubl_policy_vm::CedarEngine::new(module_cache)));
+    policies.set_rollout_monitor(Some(Arc::new(ubl_policy_vm::RolloutMonitor::new())));
+    info!("🧩 WASM module cache: {} modules", module_cache);
+    let policy_budget = policy_routes::budget_from_env();
+    info!("⏱️  Policy budget: {}µs per commit", policy_budget.as_micros());
+    let policy_audit = policy_audit::container_from_env();
+    if let Some(c) = &policy_audit {
+        info!("🧾 Policy decisions audited to {}", c);
+    }
+
+    let blobs = blob::BlobStore::from_env()?;
+    info!("🗄️  Blob backend: {}", blobs.backend.name());
+
+    let replica = consistency::ReadReplica::from_env().await?;
+    if let Some(r) = &replica {
+        info!("🪞 Read replica connected (consistency wait {}ms)", r.wait.as_millis());
+    }
+
+    let evolution_approvals = evolution_routes::approvals_from_env();
+    if let Some(n) = evolution_approvals {
+        info!("🗳️  Evolution governance: {} approval(s) per commit", n);
+    }
+
+    let strict_hex = db::strict_hex_from_env();
+    if strict_hex {
+        info!("🔒 Strict hex: full-width hashes, keys and signatures required");
+    }
+
+    let timelock = policy_timelock::Timelock::from_env()?;
+    if timelock.is_enabled() || timelock.veto_pact().is_some() {
+        info!(
+            "⏳ Policy timelock: L4 waits {}s, L5 {}s, veto pact {:?}",
+            timelock.delay(ubl_pact::RiskLevel::L4),
+            timelock.delay(ubl_pact::RiskLevel::L5),
+            timelock.veto_pact().map(|p| p.pact_id.as_str())
+        );
+    }
+
+    let region = region::Region::from_env(&pool).await?.map(Arc::new);
+    let attestation_key = attestation::key_from_env()?.map(Arc::new);
+    if let Some(key) = &attestation_key {
+        info!("📜 Dormancy attestations signed by {}", ubl_kernel::pubkey_from_signing_key(key));
+    }
+    let id_authority_key = id_ledger::key_from_env()?.map(Arc::new);
+    match &id_authority_key {
+        Some(key) => info!("🪪 C.Identity links signed by {}", ubl_kernel::pubkey_from_signing_key(key)),
+        None => info!("🪪 UBL_ID_AUTHORITY_KEY unset: bulk agent import is off"),
+    }
+
+    let admission_limits = admission::Limits::from_env();
+    info!(
+        "🚦 Admission: {} commits in flight, {}ms database latency",
+        admission_limits.max_inflight,
+        admission_limits.db_latency.as_millis()
+    );
+    let admission = Arc::new(admission::Admission::new(admission_limits));
+
     let state = AppState {
         ledger: PgLedger::new(pool.clone()),
         pool: pool.clone(),
+        policies: Arc::new(RwLock::new(policies)),
+        blobs,
+        replica,
+        evolution_approvals,
+        strict_hex,
+        policy_budget,
+        timelock: Arc::new(timelock),
+        warmup: Arc::default(),
+        usage: Arc::default(),
+        errors: Arc::default(),
+        atoms: atom_store::config_from_env(),
+        region,
+        attestation_key,
+        policy_audit,
+        bus: event_bus::EventBus::new(),
+        admission,
+        pact_audit: pact_audit::PgValidationSink::spawn(pool.clone()),
+        pacts: Arc::default(),
     };
+    policy_routes::spawn_reload_listener(state.clone());
+    alert_routes::spawn_alert_engine(state.clone());
+    event_bus::spawn_feed(state.bus.clone(), pool.clone());
+    usage::spawn_flusher(state.clone());
+    region::spawn(state.clone());
+    break_glass::spawn_sweeper(state.clone());
+    policy_rollout::spawn_monitor(state.clone());
+    pact_expiry::spawn_monitor(state.clone());
+    pact_cache::spawn_listener(state.clone());
+    admission::spawn_probe(state.clone());
 
     // Initialize WebAuthn
     let rp_id = std::env::var("WEBAUTHN_RP_ID")
@@ -276,6 +1081,7 @@ async fn main() -> anyhow::Result<()> {
         pool,
         webauthn,
         rate_limiter: rate_limit::RateLimiter::new(),
+        authority_key: id_authority_key,
     };
 
     // CORS layer
@@ -291,11 +1097,42 @@ async fn main() -> anyhow::Result<()> {
         .route("/link/validate", post(route_validate))
         .route("/link/commit", post(route_commit))
         .route("/ledger/:container_id/tail", get(route_tail))
+        .route("/ledger/heads/tail", get(route_heads_tail))
         .route("/metrics", get(metrics::metrics_handler))
         .with_state(state.clone())
         .merge(id_routes::id_router().with_state(id_state))
         .merge(id_session_token::router().with_state(state.clone()))
         .merge(repo_routes::router().with_state(state.clone()))
+        .merge(policy_routes::router().with_state(state.clone()))
+        .merge(policy_timelock::router().with_state(state.clone()))
+        .merge(policy_rollout::router().with_state(state.clone()))
+        .merge(lint_routes::router().with_state(state.clone()))
+        .merge(container_routes::router().with_state(state.clone()))
+        .merge(pact_routes::router().with_state(state.clone()))
+        .merge(pact_drafts::router().with_state(state.clone()))
+        .merge(pact_proofs::router().with_state(state.clone()))
+        .merge(pact_expiry::router().with_state(state.clone()))
+        .merge(pact_audit::router().with_state(state.clone()))
+        .merge(pact_bundle::router().with_state(state.clone()))
+        .merge(pact_freeze::router().with_state(state.clone()))
+        .merge(alert_routes::router().with_state(state.clone()))
+        .merge(archive_routes::router().with_state(state.clone()))
+        .merge(rehash_routes::router().with_state(state.clone()))
+        .merge(governance_routes::router().with_state(state.clone()))
+        .merge(evolution_routes::router().with_state(state.clone()))
+        .merge(autoscale::router().with_state(state.clone()))
+        .merge(warmup::router().with_state(state.clone()))
+        .merge(usage::router().with_state(state.clone()))
+        .merge(support_bundle::router().with_state(state.clone()))
+        .merge(atom_store::router().with_state(state.clone()))
+        .merge(region::router().with_state(state.clone()))
+        .merge(attestation::router().with_state(state.clone()))
+        .merge(settlement::router().with_state(state.clone()))
+        .merge(break_glass::router().with_state(state.clone()))
+        .merge(replay::router().with_state(state.clone()))
+        .merge(error_routes::router().with_state(state.clone()))
+        .layer(axum::middleware::from_fn_with_state(state.errors.clone(), support_bundle::capture_errors))
+        .layer(axum::middleware::from_fn_with_state(state.usage.clone(), usage::track))
         .layer(cors);
 
     let port = std::env::var("PORT").unwrap_or_else(|_| "8080".to_string());
@@ -303,13 +1140,14 @@ async fn main() -> anyhow::Result<()> {
 
     info!("🚀 UBL Server v2.0 + PostgreSQL + Identity");
     info!("   Listening: http://{}", addr);
-    info!("   Database: {}", database_url.split('@').last().unwrap_or("postgres"));
+    info!("   Database: {}", database_url.split('@').next_back().unwrap_or("postgres"));
     info!("   Features: SERIALIZABLE transactions + LISTEN/NOTIFY SSE + UBL ID");
     info!("   Chains: Foundation + Persistence + Identity");
     info!("   Features: SERIALIZABLE transactions + LISTEN/NOTIFY SSE + UBL ID");
     info!("   Chains: Foundation + Persistence + Identity");
 
     let listener = tokio::net::TcpListener::bind(&addr).await?;
+    warmup::spawn(state);
     axum::serve(listener, app).await?;
     
     Ok(())
@@ -1,9 +1,14 @@
 //! # Prometheus Metrics
 //!
-//! Exposes identity operation metrics for monitoring
+//! Exposes identity operation, HTTP request and policy evaluation metrics for monitoring
+
+use std::sync::Arc;
 
 use axum::{http::StatusCode, response::IntoResponse};
-use prometheus::{Encoder, IntCounterVec, TextEncoder};
+use prometheus::core::{Collector, Desc};
+use prometheus::proto::MetricFamily;
+use prometheus::{Encoder, HistogramVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec, TextEncoder};
+use ubl_policy_vm::{Evaluation, PolicyError, Rollout, RolloutStats, TranslationDecision, WasmRuntime};
 
 lazy_static::lazy_static! {
     /// Total identity decisions (accept/reject) by operation and error code
@@ -20,6 +25,20 @@ lazy_static::lazy_static! {
         &["operation", "phase"]
     ).unwrap();
     
+    /// WebAuthn ceremony outcomes (issued/accept/reject) by ceremony and phase
+    pub static ref WEBAUTHN_CEREMONIES: IntCounterVec = prometheus::register_int_counter_vec!(
+        "ubl_webauthn_ceremony_total",
+        "WebAuthn ceremony outcomes by ceremony, phase and outcome",
+        &["ceremony", "phase", "outcome"]
+    ).unwrap();
+
+    /// WebAuthn ceremony rejections by failure class
+    pub static ref WEBAUTHN_CEREMONY_FAILURES: IntCounterVec = prometheus::register_int_counter_vec!(
+        "ubl_webauthn_ceremony_failures_total",
+        "WebAuthn ceremony rejections by ceremony and failure class",
+        &["ceremony", "failure_class"]
+    ).unwrap();
+    
     /// Rate limiting rejections
     pub static ref RATE_LIMIT_REJECTIONS: IntCounterVec = prometheus::register_int_counter_vec!(
         "ubl_rate_limit_rejections_total",
@@ -33,6 +52,253 @@ lazy_static::lazy_static! {
         "Progressive lockout activations by failure count",
         &["failure_count"]
     ).unwrap();
+
+    /// SSE clients currently connected, by stream (`tail`, `heads`)
+    pub static ref SSE_CLIENTS: IntGaugeVec = prometheus::register_int_gauge_vec!(
+        "ubl_sse_clients",
+        "Connected SSE clients by stream",
+        &["stream"]
+    ).unwrap();
+
+    /// HTTP requests by method, matched route and status class (`2xx`..`5xx`)
+    pub static ref HTTP_REQUESTS: IntCounterVec = prometheus::register_int_counter_vec!(
+        "ubl_http_requests_total",
+        "HTTP requests by method, route template and status class",
+        &["method", "route", "status"]
+    ).unwrap();
+
+    /// Policy evaluations by policy, outcome (allow/deny/timeout/error) and cache hit
+    pub static ref POLICY_EVALUATIONS: IntCounterVec = prometheus::register_int_counter_vec!(
+        "ubl_policy_evaluations_total",
+        "Policy evaluations by policy, outcome and whether the decision cache answered",
+        &["policy_id", "outcome", "cached"]
+    ).unwrap();
+
+    /// Policy denials by policy and canonical deny code
+    pub static ref POLICY_DENIALS: IntCounterVec = prometheus::register_int_counter_vec!(
+        "ubl_policy_denials_total",
+        "Policy denials by policy and deny code",
+        &["policy_id", "deny_code"]
+    ).unwrap();
+
+    /// Constraints carried by allow decisions, by policy and constraint kind
+    pub static ref POLICY_CONSTRAINT_HITS: IntCounterVec = prometheus::register_int_counter_vec!(
+        "ubl_policy_constraint_hits_total",
+        "Constraints attached by allow decisions, by policy and kind",
+        &["policy_id", "kind"]
+    ).unwrap();
+
+    /// Commits refused at constraint enforcement, by constraint kind
+    pub static ref POLICY_CONSTRAINT_VIOLATIONS: IntCounterVec = prometheus::register_int_counter_vec!(
+        "ubl_policy_constraint_violations_total",
+        "Commits rejected by a decision constraint, by kind",
+        &["kind"]
+    ).unwrap();
+
+    /// Policy evaluation latency by policy (10µs to ~160ms buckets)
+    pub static ref POLICY_EVALUATION_SECONDS: HistogramVec = prometheus::register_histogram_vec!(
+        "ubl_policy_evaluation_seconds",
+        "Policy evaluation time by policy",
+        &["policy_id"],
+        prometheus::exponential_buckets(0.00001, 4.0, 8).unwrap()
+    ).unwrap();
+
+    /// Share of buckets on a rollout's candidate version now
+    pub static ref POLICY_ROLLOUT_PERCENT: IntGaugeVec = prometheus::register_int_gauge_vec!(
+        "ubl_policy_rollout_percent",
+        "Share of contexts a rollout puts on its candidate version now",
+        &["policy_id", "version"]
+    ).unwrap();
+
+    /// Decisions under a rollout on this instance, by arm (`candidate`, `baseline`) and outcome (`allow`, `deny`)
+    pub static ref POLICY_ROLLOUT_DECISIONS: IntGaugeVec = prometheus::register_int_gauge_vec!(
+        "ubl_policy_rollout_decisions",
+        "Decisions under a rollout since start, by arm and outcome",
+        &["policy_id", "version", "arm", "outcome"]
+    ).unwrap();
+
+    /// Candidate decisions also decided under the baseline, by result (`agreed`, `diverged`)
+    pub static ref POLICY_ROLLOUT_COMPARISONS: IntGaugeVec = prometheus::register_int_gauge_vec!(
+        "ubl_policy_rollout_comparisons",
+        "Candidate decisions compared with the baseline since start, by result",
+        &["policy_id", "version", "result"]
+    ).unwrap();
+
+    /// Rollouts halted, by policy and cause (`deny_rate`, `manual`)
+    pub static ref POLICY_ROLLOUT_HALTS: IntCounterVec = prometheus::register_int_counter_vec!(
+        "ubl_policy_rollout_halts_total",
+        "Policy rollouts halted, by policy and cause",
+        &["policy_id", "cause"]
+    ).unwrap();
+
+    /// Commits let through under a break-glass override, by bypassed subsystem
+    pub static ref BREAK_GLASS_ACTIONS: IntCounterVec = prometheus::register_int_counter_vec!(
+        "ubl_break_glass_actions_total",
+        "Commits made under a break-glass override, by subsystem",
+        &["subsystem"]
+    ).unwrap();
+
+    /// Seconds until `not_after` of each pact within the expiry horizon (`pact_expiry.rs`)
+    pub static ref PACT_EXPIRES_IN: IntGaugeVec = prometheus::register_int_gauge_vec!(
+        "ubl_pact_expires_in_seconds",
+        "Seconds until a pact nearing expiry ends, by pact",
+        &["pact_id"]
+    ).unwrap();
+
+    /// Pact cache lookups by kind (`pact`, `authority`) and result (`hit`, `miss`, `bypass`; `pact_cache.rs`)
+    pub static ref PACT_CACHE_LOOKUPS: IntCounterVec = prometheus::register_int_counter_vec!(
+        "ubl_pact_cache_lookups_total",
+        "Pact cache lookups by kind and result",
+        &["kind", "result"]
+    ).unwrap();
+
+    /// Conditional reads answered 304 Not Modified, by route
+    pub static ref HTTP_NOT_MODIFIED: IntCounterVec = prometheus::register_int_counter_vec!(
+        "ubl_http_not_modified_total",
+        "Conditional reads answered 304 Not Modified, by route",
+        &["route"]
+    ).unwrap();
+
+    /// Events published on the internal event bus, by kind (`committed`, `entry`)
+    pub static ref BUS_EVENTS: IntCounterVec = prometheus::register_int_counter_vec!(
+        "ubl_bus_events_total",
+        "Events published on the internal event bus, by kind",
+        &["event"]
+    ).unwrap();
+
+    /// Commits shed by admission control, by priority and signal over its limit
+    pub static ref ADMISSION_REJECTIONS: IntCounterVec = prometheus::register_int_counter_vec!(
+        "ubl_admission_rejections_total",
+        "Commits answered 429 under overload, by priority and reason",
+        &["priority", "reason"]
+    ).unwrap();
+
+    /// Commits in flight on this instance
+    pub static ref ADMISSION_INFLIGHT: IntGauge = prometheus::register_int_gauge!(
+        "ubl_admission_inflight",
+        "Commits admitted and not yet answered"
+    ).unwrap();
+
+    /// Moving average of the probed primary database latency, in microseconds
+    pub static ref ADMISSION_DB_LATENCY_US: IntGauge = prometheus::register_int_gauge!(
+        "ubl_admission_db_latency_us",
+        "Primary database latency seen by the admission probe, in microseconds"
+    ).unwrap();
+
+    /// Events a `Skip` subscriber missed by falling behind the bus ring
+    pub static ref BUS_LAGGED: IntCounterVec = prometheus::register_int_counter_vec!(
+        "ubl_bus_lagged_total",
+        "Event bus events missed by lagging subscribers, by subscriber",
+        &["subscriber"]
+    ).unwrap();
+}
+
+/// `PolicyVM` observer feeding the policy metrics above
+pub fn observe_policy(e: &Evaluation<'_>) {
+    let outcome = match e.result {
+        Ok(TranslationDecision::Allow { constraints, .. }) => {
+            for c in constraints {
+                POLICY_CONSTRAINT_HITS.with_label_values(&[e.policy_id, &c.kind]).inc();
+            }
+            "allow"
+        }
+        Ok(TranslationDecision::Deny { code, .. }) => {
+            POLICY_DENIALS.with_label_values(&[e.policy_id, code.as_str()]).inc();
+            "deny"
+        }
+        Err(PolicyError::Timeout) => "timeout",
+        Err(_) => "error",
+    };
+    let cached = if e.cached { "true" } else { "false" };
+    POLICY_EVALUATIONS.with_label_values(&[e.policy_id, outcome, cached]).inc();
+    POLICY_EVALUATION_SECONDS
+        .with_label_values(&[e.policy_id])
+        .observe(e.elapsed.as_secs_f64());
+}
+
+/// Set the `ubl_policy_rollout_*` gauges of `rollout` from its counts
+pub fn observe_rollout(rollout: &Rollout, stats: &RolloutStats, now: i64) {
+    let (id, version) = (rollout.policy_id.as_str(), rollout.version.as_str());
+    POLICY_ROLLOUT_PERCENT
+        .with_label_values(&[id, version])
+        .set(i64::from(rollout.percent_at(now)));
+    for (arm, total, denied) in [
+        ("candidate", stats.candidate, stats.candidate_denied),
+        ("baseline", stats.baseline, stats.baseline_denied),
+    ] {
+        POLICY_ROLLOUT_DECISIONS
+            .with_label_values(&[id, version, arm, "allow"])
+            .set((total - denied) as i64);
+        POLICY_ROLLOUT_DECISIONS
+            .with_label_values(&[id, version, arm, "deny"])
+            .set(denied as i64);
+    }
+    POLICY_ROLLOUT_COMPARISONS
+        .with_label_values(&[id, version, "agreed"])
+        .set((stats.compared - stats.diverged) as i64);
+    POLICY_ROLLOUT_COMPARISONS
+        .with_label_values(&[id, version, "diverged"])
+        .set(stats.diverged as i64);
+}
+
+/// Compiled WASM module cache counters, read from the runtime at scrape time
+struct ModuleCacheCollector {
+    runtime: Arc<WasmRuntime>,
+    hits: IntCounter,
+    misses: IntCounter,
+    evictions: IntCounter,
+    entries: IntGauge,
+    capacity: IntGauge,
+}
+
+impl Collector for ModuleCacheCollector {
+    fn desc(&self) -> Vec<&Desc> {
+        [
+            self.hits.desc(),
+            self.misses.desc(),
+            self.evictions.desc(),
+            self.entries.desc(),
+            self.capacity.desc(),
+        ]
+        .concat()
+    }
+
+    fn collect(&self) -> Vec<MetricFamily> {
+        let stats = self.runtime.stats();
+        // The runtime's counters only grow; catch ours up to them
+        self.hits.inc_by(stats.hits.saturating_sub(self.hits.get()));
+        self.misses.inc_by(stats.misses.saturating_sub(self.misses.get()));
+        self.evictions.inc_by(stats.evictions.saturating_sub(self.evictions.get()));
+        self.entries.set(stats.entries as i64);
+        self.capacity.set(stats.capacity as i64);
+        [
+            self.hits.collect(),
+            self.misses.collect(),
+            self.evictions.collect(),
+            self.entries.collect(),
+            self.capacity.collect(),
+        ]
+        .concat()
+    }
+}
+
+/// Export `runtime`'s module cache as `ubl_policy_module_cache_*`
+pub fn watch_module_cache(runtime: Arc<WasmRuntime>) {
+    let collector = ModuleCacheCollector {
+        runtime,
+        hits: IntCounter::new("ubl_policy_module_cache_hits_total", "WASM evaluations that found their module compiled")
+            .unwrap(),
+        misses: IntCounter::new("ubl_policy_module_cache_misses_total", "WASM evaluations that compiled their module")
+            .unwrap(),
+        evictions: IntCounter::new(
+            "ubl_policy_module_cache_evictions_total",
+            "Compiled WASM modules evicted, least recently used first",
+        )
+        .unwrap(),
+        entries: IntGauge::new("ubl_policy_module_cache_entries", "Compiled WASM modules held").unwrap(),
+        capacity: IntGauge::new("ubl_policy_module_cache_capacity", "Compiled WASM modules the cache can hold").unwrap(),
+    };
+    prometheus::register(Box::new(collector)).unwrap();
 }
 
 /// GET /metrics - Prometheus metrics endpoint
@@ -0,0 +1,103 @@
+//! # Pact validation audit log
+//!
+//! - GET /pacts/:pact_id/validations?link_hash=&limit=   (newest first; admin/auditor)
+//!
+//! [`PgValidationSink`] is the server's `ubl_pact::ValidationSink`: each
+//! `ValidationEvent` it receives becomes a row of `pact_validation_event`
+//! (`pact_db.rs`), accepted or refused, with the error code, the keys whose
+//! signatures counted and each rejected signature with its reason. A
+//! `PactRegistry` given the sink records every `validate`; the server also
+//! records the checks it makes itself, on each signature added to a proof
+//! collected on the server (`pact_proofs.rs`).
+//!
+//! Recording stays off the request path: `record` hands the event to one
+//! writer task through a queue of [`QUEUE`] events. When the queue is full
+//! or an insert fails the event is logged and dropped; the validation it
+//! describes is not affected either way.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    routing::get,
+    Json, Router,
+};
+use serde::Deserialize;
+use sqlx::PgPool;
+use tokio::sync::mpsc;
+use tracing::{error, warn};
+use ubl_pact::{ValidationEvent, ValidationSink};
+
+use crate::auth::rbac;
+use crate::pact_db::{self, ValidationRow};
+use crate::AppState;
+
+/// Events waiting for the writer before new ones are dropped
+pub const QUEUE: usize = 4096;
+
+/// Writes validation events to `pact_validation_event`
+#[derive(Clone)]
+pub struct PgValidationSink {
+    tx: mpsc::Sender<ValidationEvent>,
+}
+
+impl PgValidationSink {
+    /// The sink, and its writer task on `pool`
+    pub fn spawn(pool: PgPool) -> Self {
+        let (tx, mut rx) = mpsc::channel::<ValidationEvent>(QUEUE);
+        tokio::spawn(async move {
+            while let Some(event) = rx.recv().await {
+                if let Err(e) = pact_db::insert_validation(&pool, &event).await {
+                    error!(
+                        pact_id = %event.pact_id,
+                        link_hash = %event.link_hash,
+                        "pact validation audit insert failed: {}", e
+                    );
+                }
+            }
+        });
+        PgValidationSink { tx }
+    }
+}
+
+impl ValidationSink for PgValidationSink {
+    fn record(&self, event: &ValidationEvent) {
+        if let Err(e) = self.tx.try_send(event.clone()) {
+            warn!(
+                pact_id = %event.pact_id,
+                link_hash = %event.link_hash,
+                "pact validation audit dropped: {}", e
+            );
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ValidationsQuery {
+    #[serde(default)]
+    pub link_hash: Option<String>,
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+}
+
+fn default_limit() -> i64 {
+    100
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new().route("/pacts/:pact_id/validations", get(route_validations))
+}
+
+/// GET /pacts/:pact_id/validations
+async fn route_validations(
+    State(state): State<AppState>,
+    Path(pact_id): Path<String>,
+    headers: HeaderMap,
+    Query(q): Query<ValidationsQuery>,
+) -> Result<Json<Vec<ValidationRow>>, (StatusCode, String)> {
+    rbac::require_role(&state.pool, &headers, &[rbac::ADMIN, rbac::AUDITOR]).await?;
+    let link_hash = q.link_hash.map(|h| h.to_ascii_lowercase());
+    let rows = pact_db::validations(&state.pool, &pact_id, link_hash.as_deref(), q.limit.clamp(1, 1000))
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok(Json(rows))
+}
@@ -0,0 +1,182 @@
+//! # Pact bundles (staging → production)
+//!
+//! - POST /pacts/export   (`{pact_ids}` → signed `ubl_pact::bundle::PactBundle`; admin)
+//! - POST /pacts/import   (a signed bundle → which pacts were registered; admin)
+//!
+//! Export bundles the registered terms of each pact (window as last
+//! renewed) and signs the bundle hash with `UBL_PACT_BUNDLE_KEY` (hex
+//! Ed25519 seed); the bundle's `source` is `UBL_PACT_BUNDLE_SOURCE`, or
+//! `UBL_REGION`. More signatures can be added offline before import.
+//!
+//! Import accepts a bundle only when it is intact and signed by
+//! `UBL_PACT_BUNDLE_THRESHOLD` (default 1) of the keys in
+//! `UBL_PACT_BUNDLE_KEYS` (comma-separated Ed25519 public keys); without
+//! them, imports are off (503). Each pact then goes through the same checks
+//! as `POST /pacts` (`pact_routes.rs`), and all are registered in one
+//! transaction: a pact refused by the checks refuses the bundle (422),
+//! while a pact id already registered here is skipped and reported, not
+//! overwritten.
+
+use std::collections::BTreeSet;
+
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    routing::post,
+    Json, Router,
+};
+use ed25519_dalek::SigningKey;
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use tracing::info;
+use ubl_pact::bundle::PactBundle;
+use ubl_pact::{Pact, PactError};
+
+use crate::auth::rbac;
+use crate::pact_db;
+use crate::pact_routes::{check_pact, normalize_keys};
+use crate::{error_routes, AppState};
+
+/// The exporting key, from `UBL_PACT_BUNDLE_KEY` (unset: exports are off)
+fn key_from_env() -> Result<SigningKey, (StatusCode, String)> {
+    let unavailable = |why: &str| (StatusCode::SERVICE_UNAVAILABLE, format!("pact export is off: {}", why));
+    let hex_seed = std::env::var("UBL_PACT_BUNDLE_KEY")
+        .ok()
+        .filter(|v| !v.is_empty())
+        .ok_or_else(|| unavailable("UBL_PACT_BUNDLE_KEY unset"))?;
+    let seed: [u8; 32] = hex::decode(hex_seed.trim())
+        .ok()
+        .and_then(|b| b.try_into().ok())
+        .ok_or_else(|| unavailable("UBL_PACT_BUNDLE_KEY must be 32 bytes of hex"))?;
+    Ok(SigningKey::from_bytes(&seed))
+}
+
+/// Keys trusted to sign imported bundles and how many must, from
+/// `UBL_PACT_BUNDLE_KEYS` / `UBL_PACT_BUNDLE_THRESHOLD`
+fn trusted_from_env() -> Option<(BTreeSet<String>, usize)> {
+    let keys: BTreeSet<String> = std::env::var("UBL_PACT_BUNDLE_KEYS")
+        .ok()?
+        .split(',')
+        .map(|k| k.trim().to_ascii_lowercase())
+        .filter(|k| !k.is_empty())
+        .collect();
+    if keys.is_empty() {
+        return None;
+    }
+    let threshold = std::env::var("UBL_PACT_BUNDLE_THRESHOLD")
+        .ok()
+        .and_then(|t| t.parse().ok())
+        .unwrap_or(1);
+    Some((keys, threshold))
+}
+
+fn source_from_env() -> String {
+    ["UBL_PACT_BUNDLE_SOURCE", "UBL_REGION"]
+        .iter()
+        .find_map(|var| std::env::var(var).ok().filter(|v| !v.is_empty()))
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+fn internal(e: impl std::fmt::Display) -> (StatusCode, String) {
+    (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+}
+
+fn rejected(e: PactError) -> (StatusCode, String) {
+    (error_routes::status(&e), e.to_string())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExportReq {
+    pub pact_ids: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportedPact {
+    pub pact_id: String,
+    /// `false`: a pact with this id was already registered and was left as is
+    pub registered: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportResult {
+    pub bundle_hash: String,
+    pub source: String,
+    pub pacts: Vec<ImportedPact>,
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/pacts/export", post(route_export))
+        .route("/pacts/import", post(route_import))
+}
+
+/// POST /pacts/export
+async fn route_export(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<ExportReq>,
+) -> Result<Json<PactBundle>, (StatusCode, String)> {
+    let caller = rbac::require_role(&state.pool, &headers, &[rbac::ADMIN]).await?;
+    let key = key_from_env()?;
+    if req.pact_ids.is_empty() {
+        return Err((StatusCode::UNPROCESSABLE_ENTITY, "pact_ids is empty".to_string()));
+    }
+    let mut pacts = Vec::with_capacity(req.pact_ids.len());
+    for pact_id in req.pact_ids.iter().collect::<BTreeSet<_>>() {
+        let row = pact_db::get(&state.pool, pact_id)
+            .await
+            .map_err(internal)?
+            .ok_or_else(|| (StatusCode::NOT_FOUND, format!("pact {} not registered", pact_id)))?;
+        pacts.push(serde_json::from_value::<Pact>(row.pact).map_err(internal)?);
+    }
+    let mut bundle = PactBundle::new(&source_from_env(), OffsetDateTime::now_utc().unix_timestamp(), pacts);
+    bundle.sign(&key).map_err(rejected)?;
+    info!(
+        "📦 Pact bundle {} exported by {} ({} pacts)",
+        bundle.bundle_hash,
+        caller.session.sid,
+        bundle.pacts.len()
+    );
+    Ok(Json(bundle))
+}
+
+/// POST /pacts/import
+async fn route_import(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(bundle): Json<PactBundle>,
+) -> Result<Json<ImportResult>, (StatusCode, String)> {
+    let caller = rbac::require_role(&state.pool, &headers, &[rbac::ADMIN]).await?;
+    let (trusted, threshold) = trusted_from_env().ok_or((
+        StatusCode::SERVICE_UNAVAILABLE,
+        "pact import is off (UBL_PACT_BUNDLE_KEYS unset)".to_string(),
+    ))?;
+    let (bundle_hash, source) = (bundle.bundle_hash.to_ascii_lowercase(), bundle.source.clone());
+    let mut pacts = bundle.into_pacts(&trusted, threshold).map_err(rejected)?;
+    for pact in &mut pacts {
+        check_pact(pact).map_err(|e| (StatusCode::UNPROCESSABLE_ENTITY, format!("{}: {}", pact.pact_id, e)))?;
+        normalize_keys(pact);
+    }
+    let rows = pact_db::insert_many(&state.pool, &pacts, &caller.session.sid).await.map_err(internal)?;
+    let imported: Vec<ImportedPact> = pacts
+        .iter()
+        .zip(&rows)
+        .map(|(pact, row)| ImportedPact {
+            pact_id: pact.pact_id.clone(),
+            registered: row.is_some(),
+        })
+        .collect();
+    info!(
+        "📦 Pact bundle {} from {} imported by {}: {} of {} pacts registered",
+        bundle_hash,
+        source,
+        caller.session.sid,
+        rows.iter().flatten().count(),
+        rows.len()
+    );
+    Ok(Json(ImportResult {
+        bundle_hash,
+        source,
+        pacts: imported,
+    }))
+}
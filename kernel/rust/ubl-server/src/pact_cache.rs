@@ -0,0 +1,266 @@
+//! # Pact cache
+//!
+//! Every commit looks up whether the commit path is frozen
+//! (`pact_freeze.rs`) and its container's authority pact (`pact_routes.rs`),
+//! and genesis links the pact they declare (`main.rs`). These reads go
+//! through this cache instead of Postgres: pacts by id, container
+//! authorities by container, and the freeze in force.
+//!
+//! Entries are dropped on the `pact_changed` NOTIFY, raised by triggers on
+//! `pact` and `container_authority` (sql/051_pact_changed_notify.sql) and
+//! `pact_freeze` (sql/052_pact_freeze.sql) on whichever instance made the
+//! change. A lookup that raced an invalidation
+//! is not cached. While the listener is down, notifications can be missed,
+//! so the cache is bypassed until it has reconnected.
+//!
+//! Each time the listener starts following the channel (on start, and after
+//! every reconnect) the cache is loaded with every pact and container
+//! authority in Postgres, so the first commits after a restart do not each
+//! go to the database. A failed load leaves the cache to fill on demand.
+//!
+//! Lookups are counted in `ubl_pact_cache_lookups_total{kind, result}`
+//! (`kind` `pact`, `authority` or `freeze`; `result` `hit`, `miss` or
+//! `bypass`).
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::RwLock;
+use std::time::Duration;
+
+use sqlx::postgres::PgListener;
+use sqlx::PgPool;
+use tracing::{error, info};
+
+use crate::pact_db::{self, FreezeRow, PactRow};
+use crate::{metrics, AppState};
+
+/// Channel the pact triggers notify on
+pub const CHANNEL: &str = "pact_changed";
+
+/// Key of the one entry in `freeze`
+const COMMIT_PATH: &str = "commit_path";
+
+/// Cached pacts and container authorities; `None` records an absence
+#[derive(Default)]
+pub struct PactCache {
+    pacts: RwLock<HashMap<String, Option<PactRow>>>,
+    authorities: RwLock<HashMap<String, Option<String>>>,
+    freeze: RwLock<HashMap<String, Option<FreezeRow>>>,
+    /// Raised by every invalidation
+    generation: AtomicU64,
+    /// Whether the listener is following `pact_changed`
+    live: AtomicBool,
+}
+
+impl PactCache {
+    /// Pact row by id (`pact_db::get`)
+    pub async fn pact(&self, pool: &PgPool, pact_id: &str) -> sqlx::Result<Option<PactRow>> {
+        let generation = match self.lookup("pact", &self.pacts, pact_id) {
+            Ok(hit) => return Ok(hit),
+            Err(generation) => generation,
+        };
+        let row = pact_db::get(pool, pact_id).await?;
+        self.store(&self.pacts, pact_id, row.clone(), generation);
+        Ok(row)
+    }
+
+    /// Authority pact of a container (`pact_db::authority`)
+    pub async fn authority(&self, pool: &PgPool, container_id: &str) -> sqlx::Result<Option<String>> {
+        let generation = match self.lookup("authority", &self.authorities, container_id) {
+            Ok(hit) => return Ok(hit),
+            Err(generation) => generation,
+        };
+        let pact_id = pact_db::authority(pool, container_id).await?;
+        self.store(&self.authorities, container_id, pact_id.clone(), generation);
+        Ok(pact_id)
+    }
+
+    /// The freeze in force, if the commit path is frozen (`pact_db::freeze`)
+    pub async fn freeze(&self, pool: &PgPool) -> sqlx::Result<Option<FreezeRow>> {
+        let generation = match self.lookup("freeze", &self.freeze, COMMIT_PATH) {
+            Ok(hit) => return Ok(hit),
+            Err(generation) => generation,
+        };
+        let row = pact_db::freeze(pool).await?;
+        self.store(&self.freeze, COMMIT_PATH, row.clone(), generation);
+        Ok(row)
+    }
+
+    /// Load every pact and container authority; returns how many of each.
+    /// Call once live: rows an invalidation overtook are not kept
+    pub async fn load(&self, pool: &PgPool) -> sqlx::Result<(usize, usize)> {
+        let generation = Some(self.generation.load(Ordering::Acquire));
+        let pacts = pact_db::all(pool).await?;
+        let authorities = pact_db::authorities(pool).await?;
+        let loaded = (pacts.len(), authorities.len());
+        for row in pacts {
+            let pact_id = row.pact_id.clone();
+            self.store(&self.pacts, &pact_id, Some(row), generation);
+        }
+        for (container_id, pact_id) in authorities {
+            self.store(&self.authorities, &container_id, Some(pact_id), generation);
+        }
+        Ok(loaded)
+    }
+
+    /// The cached value, or the generation to store the database's under
+    fn lookup<V: Clone>(
+        &self,
+        kind: &str,
+        map: &RwLock<HashMap<String, V>>,
+        key: &str,
+    ) -> Result<V, Option<u64>> {
+        if !self.live.load(Ordering::Acquire) {
+            metrics::PACT_CACHE_LOOKUPS.with_label_values(&[kind, "bypass"]).inc();
+            return Err(None);
+        }
+        let generation = self.generation.load(Ordering::Acquire);
+        match map.read().unwrap().get(key) {
+            Some(value) => {
+                metrics::PACT_CACHE_LOOKUPS.with_label_values(&[kind, "hit"]).inc();
+                Ok(value.clone())
+            }
+            None => {
+                metrics::PACT_CACHE_LOOKUPS.with_label_values(&[kind, "miss"]).inc();
+                Err(Some(generation))
+            }
+        }
+    }
+
+    /// Cache `value`, unless an invalidation came after `generation` was read
+    fn store<V>(&self, map: &RwLock<HashMap<String, V>>, key: &str, value: V, generation: Option<u64>) {
+        let mut map = map.write().unwrap();
+        if generation.is_some_and(|g| g == self.generation.load(Ordering::Acquire)) {
+            map.insert(key.to_string(), value);
+        }
+    }
+
+    /// Drop what a `pact_changed` payload names; everything if unrecognised
+    pub fn invalidate(&self, payload: &str) {
+        self.generation.fetch_add(1, Ordering::AcqRel);
+        match payload.split_once(':') {
+            Some(("pact", pact_id)) => {
+                self.pacts.write().unwrap().remove(pact_id);
+            }
+            Some(("container", container_id)) => {
+                self.authorities.write().unwrap().remove(container_id);
+            }
+            Some(("freeze", _)) => {
+                self.freeze.write().unwrap().clear();
+            }
+            _ => self.clear(),
+        }
+    }
+
+    fn clear(&self) {
+        self.generation.fetch_add(1, Ordering::AcqRel);
+        self.pacts.write().unwrap().clear();
+        self.authorities.write().unwrap().clear();
+        self.freeze.write().unwrap().clear();
+    }
+
+    /// Follow (`true`) or stop following `pact_changed`; either way the
+    /// cache starts over
+    fn set_live(&self, live: bool) {
+        self.live.store(false, Ordering::Release);
+        self.clear();
+        self.live.store(live, Ordering::Release);
+    }
+}
+
+/// Follow `pact_changed` and invalidate on each notification
+pub fn spawn_listener(state: AppState) {
+    tokio::spawn(async move {
+        loop {
+            let mut listener = match PgListener::connect_with(&state.pool).await {
+                Ok(l) => l,
+                Err(e) => {
+                    error!("pact cache: cannot create PgListener: {}", e);
+                    tokio::time::sleep(Duration::from_secs(5)).await;
+                    continue;
+                }
+            };
+            if let Err(e) = listener.listen(CHANNEL).await {
+                error!("pact cache: cannot LISTEN on {}: {}", CHANNEL, e);
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                continue;
+            }
+            state.pacts.set_live(true);
+            // Notifications that arrive meanwhile wait in the listener and
+            // invalidate what was loaded before them
+            match state.pacts.load(&state.pool).await {
+                Ok((pacts, authorities)) => info!(
+                    "📇 Pact cache following {}: loaded {} pacts, {} container authorities",
+                    CHANNEL, pacts, authorities
+                ),
+                Err(e) => error!("pact cache: loading pacts failed, filling on demand: {}", e),
+            }
+            let e = loop {
+                match listener.try_recv().await {
+                    Ok(Some(notification)) => state.pacts.invalidate(notification.payload()),
+                    // The connection dropped; sqlx reconnects, but what was
+                    // sent meanwhile is lost
+                    Ok(None) => state.pacts.clear(),
+                    Err(e) => break e,
+                }
+            };
+            state.pacts.set_live(false);
+            error!("pact cache: {} listener failed, bypassing the cache: {}", CHANNEL, e);
+            tokio::time::sleep(Duration::from_secs(5)).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn live() -> PactCache {
+        let cache = PactCache::default();
+        cache.set_live(true);
+        cache
+    }
+
+    #[test]
+    fn test_invalidation() {
+        let cache = live();
+        let generation = cache.lookup("authority", &cache.authorities, "C.A").unwrap_err();
+        cache.store(&cache.authorities, "C.A", Some("pact_a".to_string()), generation);
+        let generation = cache.lookup("authority", &cache.authorities, "C.B").unwrap_err();
+        cache.store(&cache.authorities, "C.B", None, generation);
+        assert_eq!(cache.lookup("authority", &cache.authorities, "C.A"), Ok(Some("pact_a".to_string())));
+        assert_eq!(cache.lookup("authority", &cache.authorities, "C.B"), Ok(None));
+
+        cache.invalidate("container:C.B");
+        assert!(cache.lookup("authority", &cache.authorities, "C.B").is_err());
+        assert!(cache.lookup("authority", &cache.authorities, "C.A").is_ok());
+        cache.invalidate("pact:pact_a");
+        assert!(cache.lookup("authority", &cache.authorities, "C.A").is_ok());
+
+        let generation = cache.lookup("freeze", &cache.freeze, COMMIT_PATH).unwrap_err();
+        cache.store(&cache.freeze, COMMIT_PATH, None, generation);
+        assert_eq!(cache.lookup("freeze", &cache.freeze, COMMIT_PATH).map(|f| f.is_none()), Ok(true));
+        cache.invalidate("freeze:pact_emergency");
+        assert!(cache.lookup("freeze", &cache.freeze, COMMIT_PATH).is_err());
+        assert!(cache.lookup("authority", &cache.authorities, "C.A").is_ok());
+    }
+
+    #[test]
+    fn test_raced_lookup_is_not_cached() {
+        let cache = live();
+        let generation = cache.lookup("authority", &cache.authorities, "C.A").unwrap_err();
+        // The row changed while it was being read
+        cache.invalidate("container:C.A");
+        cache.store(&cache.authorities, "C.A", None, generation);
+        assert!(cache.lookup("authority", &cache.authorities, "C.A").is_err());
+    }
+
+    #[test]
+    fn test_bypassed_until_live() {
+        let cache = PactCache::default();
+        assert_eq!(cache.lookup("authority", &cache.authorities, "C.A"), Err(None));
+        cache.store(&cache.authorities, "C.A", None, None);
+        cache.set_live(true);
+        assert!(cache.lookup("authority", &cache.authorities, "C.A").is_err());
+    }
+}
@@ -0,0 +1,868 @@
+//! Registered pacts and container authority
+//! (tables `pact`, `pact_event`, `container_authority`, sql/041_pact_authority.sql;
+//! lineage in `pact.predecessor`, sql/048_pact_lineage.sql)
+//! and pact drafts in their creation ceremony
+//! (`pact_draft`, `pact_draft_signature`, sql/045_pact_draft.sql)
+//! and pact proofs being assembled
+//! (`pact_proof_request`, `pact_proof_signature`, sql/046_pact_proof.sql)
+//! and the proofs completed under each pact (`pact_usage`, sql/049_pact_usage.sql)
+//! and the validation audit log (`pact_validation_event`, sql/050_pact_validation_event.sql)
+//! and emergency freezes of the commit path (`pact_freeze`, sql/052_pact_freeze.sql)
+//!
+//! `container_authority` rows are written by `PgLedger::append` with a
+//! container's genesis entry, from `LinkDraft.manifest.authority_pact`.
+
+use serde::Serialize;
+use sqlx::{PgExecutor, PgPool, Postgres, Transaction};
+use time::OffsetDateTime;
+use ubl_errors::ErrorCatalog;
+use ubl_pact::freeze::FreezeAction;
+use ubl_pact::{Pact, PactScope, PactSignature, RiskLevel, ValidationEvent};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PactRow {
+    pub pact_id: String,
+    pub pact: serde_json::Value,
+    pub not_before: i64,
+    pub not_after: i64,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub revoked_at: Option<OffsetDateTime>,
+    pub revoked_by: Option<String>,
+    pub revoke_reason: Option<String>,
+    pub registered_by: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub registered_at: OffsetDateTime,
+}
+
+/// Whether a pact is in force at a given time
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PactStatus {
+    Active,
+    /// Before `not_before`
+    Pending,
+    /// After `not_after`
+    Expired,
+    Revoked,
+    /// Named by a container but not registered here
+    Unknown,
+}
+
+impl PactStatus {
+    pub fn of(row: Option<&PactRow>, now: i64) -> Self {
+        match row {
+            None => PactStatus::Unknown,
+            Some(p) if p.revoked_at.is_some() => PactStatus::Revoked,
+            Some(p) if now < p.not_before => PactStatus::Pending,
+            Some(p) if now > p.not_after => PactStatus::Expired,
+            Some(_) => PactStatus::Active,
+        }
+    }
+
+    /// Status the pact had at `at`, replaying its events up to then: the
+    /// `not_after` last set and whether the last event was a revocation
+    pub fn at(row: Option<&PactRow>, events: &[PactEvent], at: i64) -> Self {
+        let Some(row) = row else {
+            return PactStatus::Unknown;
+        };
+        let mut window = None;
+        for e in events.iter().filter(|e| e.created_at.unix_timestamp() <= at) {
+            window = Some((e.not_after, e.kind == "revoked"));
+        }
+        match window {
+            None => PactStatus::Unknown,
+            Some((_, true)) => PactStatus::Revoked,
+            Some(_) if at < row.not_before => PactStatus::Pending,
+            Some((not_after, _)) if at > not_after => PactStatus::Expired,
+            Some(_) => PactStatus::Active,
+        }
+    }
+
+    pub fn in_force(self) -> bool {
+        self == PactStatus::Active
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PactEvent {
+    pub id: i64,
+    pub kind: String,
+    pub not_after: i64,
+    pub reason: Option<String>,
+    pub actor: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+async fn event(
+    db: impl PgExecutor<'_>,
+    pact_id: &str,
+    kind: &str,
+    not_after: i64,
+    reason: Option<&str>,
+    actor: &str,
+) -> sqlx::Result<()> {
+    sqlx::query!(
+        r#"INSERT INTO pact_event (pact_id, kind, not_after, reason, actor) VALUES ($1, $2, $3, $4, $5)"#,
+        pact_id,
+        kind,
+        not_after,
+        reason,
+        actor
+    )
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+/// Register a pact and its `registered` event; `None` if the id is taken,
+/// or if the pact renews a predecessor that already has a successor
+pub async fn insert(pool: &PgPool, pact: &Pact, actor: &str) -> sqlx::Result<Option<PactRow>> {
+    let mut tx = pool.begin().await?;
+    let row = insert_in(&mut tx, pact, actor).await?;
+    tx.commit().await?;
+    Ok(row)
+}
+
+/// Register `pacts` in one transaction, each as [`insert`] would; `None`
+/// for each id that is taken
+pub async fn insert_many(pool: &PgPool, pacts: &[Pact], actor: &str) -> sqlx::Result<Vec<Option<PactRow>>> {
+    let mut tx = pool.begin().await?;
+    let mut rows = Vec::with_capacity(pacts.len());
+    for pact in pacts {
+        rows.push(insert_in(&mut tx, pact, actor).await?);
+    }
+    tx.commit().await?;
+    Ok(rows)
+}
+
+/// What [`insert_child`] did
+#[derive(Debug, Clone)]
+pub enum ChildRegistration {
+    Registered(PactRow),
+    /// The id is taken; nothing stored
+    Taken,
+    /// The parent's `max_uses` are spent; nothing stored
+    UsesExhausted,
+}
+
+/// Register `child` as [`insert`] would, counting one use of `parent`, the
+/// meta-pact whose proof authorized it
+pub async fn insert_child(pool: &PgPool, child: &Pact, parent: &Pact, actor: &str) -> sqlx::Result<ChildRegistration> {
+    let mut tx = pool.begin().await?;
+    let Some(row) = insert_in(&mut tx, child, actor).await? else {
+        return Ok(ChildRegistration::Taken);
+    };
+    if !spend_use(&mut tx, parent).await? {
+        return Ok(ChildRegistration::UsesExhausted);
+    }
+    tx.commit().await?;
+    Ok(ChildRegistration::Registered(row))
+}
+
+/// [`insert`] inside `tx`
+async fn insert_in(tx: &mut Transaction<'_, Postgres>, pact: &Pact, actor: &str) -> sqlx::Result<Option<PactRow>> {
+    let row = sqlx::query_as!(
+        PactRow,
+        r#"INSERT INTO pact (pact_id, pact, not_before, not_after, registered_by, predecessor)
+           VALUES ($1, $2, $3, $4, $5, $6)
+           ON CONFLICT DO NOTHING
+           RETURNING pact_id, pact, not_before, not_after, revoked_at, revoked_by, revoke_reason,
+                     registered_by, registered_at"#,
+        pact.pact_id,
+        serde_json::to_value(pact).expect("pact serializes"),
+        pact.window.not_before,
+        pact.window.not_after,
+        actor,
+        pact.predecessor
+    )
+    .fetch_optional(&mut **tx)
+    .await?;
+    if let Some(row) = &row {
+        event(&mut **tx, &row.pact_id, "registered", row.not_after, None, actor).await?;
+    }
+    Ok(row)
+}
+
+/// Revoke a pact and record why; `None` if it does not exist or is already revoked
+pub async fn revoke(pool: &PgPool, pact_id: &str, reason: &str, actor: &str) -> sqlx::Result<Option<PactRow>> {
+    let mut tx = pool.begin().await?;
+    let row = sqlx::query_as!(
+        PactRow,
+        r#"UPDATE pact SET revoked_at = now(), revoked_by = $2, revoke_reason = $3
+           WHERE pact_id = $1 AND revoked_at IS NULL
+           RETURNING pact_id, pact, not_before, not_after, revoked_at, revoked_by, revoke_reason,
+                     registered_by, registered_at"#,
+        pact_id,
+        actor,
+        reason
+    )
+    .fetch_optional(&mut *tx)
+    .await?;
+    if let Some(row) = &row {
+        event(&mut *tx, pact_id, "revoked", row.not_after, Some(reason), actor).await?;
+    }
+    tx.commit().await?;
+    Ok(row)
+}
+
+/// Move a pact's `not_after` and lift any revocation; `None` if it does not exist
+pub async fn renew(
+    pool: &PgPool,
+    pact_id: &str,
+    not_after: i64,
+    reason: Option<&str>,
+    actor: &str,
+) -> sqlx::Result<Option<PactRow>> {
+    let mut tx = pool.begin().await?;
+    let row = sqlx::query_as!(
+        PactRow,
+        r#"UPDATE pact
+           SET not_after = $2, pact = jsonb_set(pact, '{window,not_after}', to_jsonb($2::bigint)),
+               revoked_at = NULL, revoked_by = NULL, revoke_reason = NULL
+           WHERE pact_id = $1
+           RETURNING pact_id, pact, not_before, not_after, revoked_at, revoked_by, revoke_reason,
+                     registered_by, registered_at"#,
+        pact_id,
+        not_after
+    )
+    .fetch_optional(&mut *tx)
+    .await?;
+    if row.is_some() {
+        event(&mut *tx, pact_id, "renewed", not_after, reason, actor).await?;
+    }
+    tx.commit().await?;
+    Ok(row)
+}
+
+pub async fn get(db: impl PgExecutor<'_>, pact_id: &str) -> sqlx::Result<Option<PactRow>> {
+    sqlx::query_as!(
+        PactRow,
+        r#"SELECT pact_id, pact, not_before, not_after, revoked_at, revoked_by, revoke_reason,
+                  registered_by, registered_at
+           FROM pact WHERE pact_id = $1"#,
+        pact_id
+    )
+    .fetch_optional(db)
+    .await
+}
+
+/// Every registered pact, to load the pact cache on start
+pub async fn all(pool: &PgPool) -> sqlx::Result<Vec<PactRow>> {
+    sqlx::query_as!(
+        PactRow,
+        r#"SELECT pact_id, pact, not_before, not_after, revoked_at, revoked_by, revoke_reason,
+                  registered_by, registered_at
+           FROM pact ORDER BY pact_id"#
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// The pact registered as `pact_id`'s successor, if it was renewed by one
+pub async fn successor(db: impl PgExecutor<'_>, pact_id: &str) -> sqlx::Result<Option<PactRow>> {
+    sqlx::query_as!(
+        PactRow,
+        r#"SELECT pact_id, pact, not_before, not_after, revoked_at, revoked_by, revoke_reason,
+                  registered_by, registered_at
+           FROM pact WHERE predecessor = $1"#,
+        pact_id
+    )
+    .fetch_optional(db)
+    .await
+}
+
+/// Filters of [`list`]; `None` matches any pact
+#[derive(Debug, Default)]
+pub struct PactFilter {
+    pub scope: Option<PactScope>,
+    /// Scoped to this container, or global
+    pub container_id: Option<String>,
+    pub namespace: Option<String>,
+    /// Authorizing this risk level or a higher one
+    pub risk_level: Option<RiskLevel>,
+    /// In force (or not) at the time of the listing
+    pub active: Option<bool>,
+}
+
+/// A unit variant as it is serialized in a pact's terms (`Container`, `L3`)
+fn as_stored<T: Serialize>(value: &T) -> Option<String> {
+    serde_json::to_value(value).ok()?.as_str().map(str::to_string)
+}
+
+/// Registered pacts matching `filter` at `now`, by pact id
+pub async fn list(pool: &PgPool, filter: &PactFilter, now: i64, limit: i64) -> sqlx::Result<Vec<PactRow>> {
+    // Risk levels `L0`..`L5` order as text like they do as levels
+    sqlx::query_as!(
+        PactRow,
+        r#"SELECT pact_id, pact, not_before, not_after, revoked_at, revoked_by, revoke_reason,
+                  registered_by, registered_at
+           FROM pact
+           WHERE ($1::text IS NULL OR pact->>'scope' = $1)
+             AND ($2::text IS NULL OR pact->>'container_id' = $2 OR pact->>'scope' = 'Global')
+             AND ($3::text IS NULL OR pact->>'namespace' = $3)
+             AND ($4::text IS NULL OR pact->>'risk_level' >= $4)
+             AND ($5::bool IS NULL OR (revoked_at IS NULL AND $6 BETWEEN not_before AND not_after) = $5)
+           ORDER BY pact_id
+           LIMIT $7"#,
+        filter.scope.as_ref().and_then(as_stored),
+        filter.container_id,
+        filter.namespace,
+        filter.risk_level.as_ref().and_then(as_stored),
+        filter.active,
+        now,
+        limit
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Pacts in force whose `not_after` falls in `from..=until`, soonest
+/// first: neither revoked nor renewed by a successor
+pub async fn expiring(pool: &PgPool, from: i64, until: i64) -> sqlx::Result<Vec<PactRow>> {
+    sqlx::query_as!(
+        PactRow,
+        r#"SELECT pact_id, pact, not_before, not_after, revoked_at, revoked_by, revoke_reason,
+                  registered_by, registered_at
+           FROM pact p
+           WHERE revoked_at IS NULL AND not_after BETWEEN $1 AND $2
+             AND NOT EXISTS (SELECT 1 FROM pact s WHERE s.predecessor = p.pact_id)
+           ORDER BY not_after, pact_id"#,
+        from,
+        until
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Most pacts walked each way from a pact by [`lineage`]
+pub const MAX_LINEAGE: usize = 64;
+
+/// `pact_id`'s lineage, oldest first: its predecessors, the pact itself,
+/// then its successors (at most [`MAX_LINEAGE`] each way); empty if the
+/// pact is not registered
+pub async fn lineage(pool: &PgPool, pact_id: &str) -> sqlx::Result<Vec<PactRow>> {
+    let Some(row) = get(pool, pact_id).await? else {
+        return Ok(Vec::new());
+    };
+    let mut lineage = Vec::new();
+    let mut at = predecessor_of(&row);
+    while let Some(id) = at.filter(|_| lineage.len() < MAX_LINEAGE) {
+        let Some(prev) = get(pool, &id).await? else {
+            break;
+        };
+        at = predecessor_of(&prev);
+        lineage.push(prev);
+    }
+    lineage.reverse();
+    let mut next = successor(pool, &row.pact_id).await?;
+    lineage.push(row);
+    for _ in 0..MAX_LINEAGE {
+        let Some(row) = next else {
+            break;
+        };
+        next = successor(pool, &row.pact_id).await?;
+        lineage.push(row);
+    }
+    Ok(lineage)
+}
+
+/// The `predecessor` named in a row's terms
+pub fn predecessor_of(row: &PactRow) -> Option<String> {
+    row.pact.get("predecessor").and_then(|p| p.as_str()).map(str::to_string)
+}
+
+/// A pact's registration, revocations and renewals, oldest first
+pub async fn events(pool: &PgPool, pact_id: &str) -> sqlx::Result<Vec<PactEvent>> {
+    sqlx::query_as!(
+        PactEvent,
+        r#"SELECT id, kind, not_after, reason, actor, created_at
+           FROM pact_event WHERE pact_id = $1 ORDER BY id"#,
+        pact_id
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Authority pact declared by `container_id`'s manifest, if any
+pub async fn authority(db: impl PgExecutor<'_>, container_id: &str) -> sqlx::Result<Option<String>> {
+    sqlx::query_scalar!(
+        "SELECT pact_id FROM container_authority WHERE container_id = $1",
+        container_id
+    )
+    .fetch_optional(db)
+    .await
+}
+
+/// Every container's authority pact, as `(container_id, pact_id)`
+pub async fn authorities(pool: &PgPool) -> sqlx::Result<Vec<(String, String)>> {
+    let rows = sqlx::query!("SELECT container_id, pact_id FROM container_authority ORDER BY container_id")
+        .fetch_all(pool)
+        .await?;
+    Ok(rows.into_iter().map(|r| (r.container_id, r.pact_id)).collect())
+}
+
+/// Containers operating under `pact_id`
+pub async fn containers(pool: &PgPool, pact_id: &str) -> sqlx::Result<Vec<String>> {
+    sqlx::query_scalar!(
+        "SELECT container_id FROM container_authority WHERE pact_id = $1 ORDER BY container_id",
+        pact_id
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Proofs completed under `pact_id`, counted against its `max_uses`
+pub async fn uses(db: impl PgExecutor<'_>, pact_id: &str) -> sqlx::Result<u64> {
+    let uses = sqlx::query_scalar!("SELECT uses FROM pact_usage WHERE pact_id = $1", pact_id)
+        .fetch_optional(db)
+        .await?;
+    Ok(uses.unwrap_or(0) as u64)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DraftRow {
+    pub draft_hash: String,
+    pub pact_id: String,
+    pub pact: serde_json::Value,
+    pub proposed_by: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub proposed_at: OffsetDateTime,
+    pub activated_by: Option<String>,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub activated_at: Option<OffsetDateTime>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DraftSignature {
+    pub pubkey: String,
+    pub signature: String,
+    pub signed_by: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub signed_at: OffsetDateTime,
+}
+
+/// Store a proposed pact under its draft hash; `None` if the same terms were already proposed
+pub async fn insert_draft(pool: &PgPool, draft_hash: &str, pact: &Pact, actor: &str) -> sqlx::Result<Option<DraftRow>> {
+    sqlx::query_as!(
+        DraftRow,
+        r#"INSERT INTO pact_draft (draft_hash, pact_id, pact, proposed_by)
+           VALUES ($1, $2, $3, $4)
+           ON CONFLICT DO NOTHING
+           RETURNING draft_hash, pact_id, pact, proposed_by, proposed_at, activated_by, activated_at"#,
+        draft_hash,
+        pact.pact_id,
+        serde_json::to_value(pact).expect("pact serializes"),
+        actor
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn get_draft(pool: &PgPool, draft_hash: &str) -> sqlx::Result<Option<DraftRow>> {
+    sqlx::query_as!(
+        DraftRow,
+        r#"SELECT draft_hash, pact_id, pact, proposed_by, proposed_at, activated_by, activated_at
+           FROM pact_draft WHERE draft_hash = $1"#,
+        draft_hash
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+/// Adoption signatures collected for a draft, oldest first
+pub async fn draft_signatures(pool: &PgPool, draft_hash: &str) -> sqlx::Result<Vec<DraftSignature>> {
+    sqlx::query_as!(
+        DraftSignature,
+        r#"SELECT pubkey, signature, signed_by, signed_at
+           FROM pact_draft_signature WHERE draft_hash = $1 ORDER BY signed_at, pubkey"#,
+        draft_hash
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Record a verified adoption signature; false if that key already signed
+pub async fn add_draft_signature(
+    pool: &PgPool,
+    draft_hash: &str,
+    signature: &PactSignature,
+    actor: &str,
+) -> sqlx::Result<bool> {
+    let r = sqlx::query!(
+        r#"INSERT INTO pact_draft_signature (draft_hash, pubkey, signature, signed_by)
+           VALUES ($1, $2, $3, $4)
+           ON CONFLICT DO NOTHING"#,
+        draft_hash,
+        signature.pubkey,
+        signature.signature,
+        actor
+    )
+    .execute(pool)
+    .await?;
+    Ok(r.rows_affected() > 0)
+}
+
+/// Mark a draft activated and register its pact in one transaction; `None`
+/// (nothing written) if the draft was already activated or the pact id is taken
+pub async fn activate_draft(
+    pool: &PgPool,
+    draft_hash: &str,
+    pact: &Pact,
+    actor: &str,
+) -> sqlx::Result<Option<PactRow>> {
+    let mut tx = pool.begin().await?;
+    let r = sqlx::query!(
+        r#"UPDATE pact_draft SET activated_by = $2, activated_at = now()
+           WHERE draft_hash = $1 AND activated_at IS NULL"#,
+        draft_hash,
+        actor
+    )
+    .execute(&mut *tx)
+    .await?;
+    if r.rows_affected() == 0 {
+        return Ok(None);
+    }
+    let row = insert_in(&mut tx, pact, actor).await?;
+    if row.is_some() {
+        tx.commit().await?;
+    }
+    Ok(row)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProofRequestRow {
+    pub link_hash: String,
+    pub pact_id: String,
+    pub request: serde_json::Value,
+    pub requested_by: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub requested_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub completed_at: Option<OffsetDateTime>,
+}
+
+/// Store the signing request of a proof for `link_hash`; `None` if one is already pending
+pub async fn insert_proof_request(
+    pool: &PgPool,
+    link_hash: &str,
+    pact_id: &str,
+    request: &serde_json::Value,
+    actor: &str,
+) -> sqlx::Result<Option<ProofRequestRow>> {
+    sqlx::query_as!(
+        ProofRequestRow,
+        r#"INSERT INTO pact_proof_request (link_hash, pact_id, request, requested_by)
+           VALUES ($1, $2, $3, $4)
+           ON CONFLICT DO NOTHING
+           RETURNING link_hash, pact_id, request, requested_by, requested_at, completed_at"#,
+        link_hash,
+        pact_id,
+        request,
+        actor
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn get_proof_request(pool: &PgPool, link_hash: &str) -> sqlx::Result<Option<ProofRequestRow>> {
+    sqlx::query_as!(
+        ProofRequestRow,
+        r#"SELECT link_hash, pact_id, request, requested_by, requested_at, completed_at
+           FROM pact_proof_request WHERE link_hash = $1"#,
+        link_hash
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+/// Signatures collected for the proof of `link_hash`, oldest first
+pub async fn proof_signatures(pool: &PgPool, link_hash: &str) -> sqlx::Result<Vec<DraftSignature>> {
+    sqlx::query_as!(
+        DraftSignature,
+        r#"SELECT pubkey, signature, signed_by, signed_at
+           FROM pact_proof_signature WHERE link_hash = $1 ORDER BY signed_at, pubkey"#,
+        link_hash
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// What [`add_proof_signature`] did
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProofSigning {
+    Added,
+    /// That key already signed; nothing stored
+    AlreadySigned,
+    /// The signature would complete the proof, but the pact's `max_uses`
+    /// are spent (`used`, as [`uses`] counts them); nothing stored
+    UsesExhausted { used: u64 },
+}
+
+/// Record a checked signature. When it meets the quorum of `completes`,
+/// the pact the proof is under, also record the completion time and count
+/// one use of the pact, unless its `max_uses` are spent.
+pub async fn add_proof_signature(
+    pool: &PgPool,
+    link_hash: &str,
+    signature: &PactSignature,
+    actor: &str,
+    completes: Option<&Pact>,
+) -> sqlx::Result<ProofSigning> {
+    let mut tx = pool.begin().await?;
+    let r = sqlx::query!(
+        r#"INSERT INTO pact_proof_signature (link_hash, pubkey, signature, signed_by)
+           VALUES ($1, $2, $3, $4)
+           ON CONFLICT DO NOTHING"#,
+        link_hash,
+        signature.pubkey,
+        signature.signature,
+        actor
+    )
+    .execute(&mut *tx)
+    .await?;
+    if r.rows_affected() == 0 {
+        return Ok(ProofSigning::AlreadySigned);
+    }
+    if let Some(pact) = completes {
+        let completed = sqlx::query!(
+            "UPDATE pact_proof_request SET completed_at = now() WHERE link_hash = $1 AND completed_at IS NULL",
+            link_hash
+        )
+        .execute(&mut *tx)
+        .await?;
+        // Only the signature that first completes the proof spends a use
+        if completed.rows_affected() == 1 && !spend_use(&mut tx, pact).await? {
+            let used = uses(&mut *tx, &pact.pact_id).await?;
+            return Ok(ProofSigning::UsesExhausted { used });
+        }
+    }
+    tx.commit().await?;
+    Ok(ProofSigning::Added)
+}
+
+/// Count one use of `pact` inside `tx`; `false` if its `max_uses` are spent
+async fn spend_use(tx: &mut Transaction<'_, Postgres>, pact: &Pact) -> sqlx::Result<bool> {
+    let counted = sqlx::query_scalar!(
+        r#"INSERT INTO pact_usage (pact_id, uses) VALUES ($1, 1)
+           ON CONFLICT (pact_id) DO UPDATE SET uses = pact_usage.uses + 1, last_used_at = now()
+           WHERE $2::bigint IS NULL OR pact_usage.uses < $2
+           RETURNING uses"#,
+        pact.pact_id,
+        pact.max_uses.map(|m| m as i64)
+    )
+    .fetch_optional(&mut **tx)
+    .await?;
+    Ok(counted.is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(not_before: i64, not_after: i64) -> PactRow {
+        PactRow {
+            pact_id: "pact_fund".to_string(),
+            pact: serde_json::Value::Null,
+            not_before,
+            not_after,
+            revoked_at: None,
+            revoked_by: None,
+            revoke_reason: None,
+            registered_by: None,
+            registered_at: OffsetDateTime::UNIX_EPOCH,
+        }
+    }
+
+    #[test]
+    fn test_status_follows_window_and_revocation() {
+        let pact = row(100, 200);
+        assert_eq!(PactStatus::of(Some(&pact), 99), PactStatus::Pending);
+        assert_eq!(PactStatus::of(Some(&pact), 100), PactStatus::Active);
+        assert_eq!(PactStatus::of(Some(&pact), 200), PactStatus::Active);
+        assert_eq!(PactStatus::of(Some(&pact), 201), PactStatus::Expired);
+        assert_eq!(PactStatus::of(None, 150), PactStatus::Unknown);
+
+        let revoked = PactRow {
+            revoked_at: Some(OffsetDateTime::UNIX_EPOCH),
+            ..row(100, 200)
+        };
+        assert_eq!(PactStatus::of(Some(&revoked), 150), PactStatus::Revoked);
+        assert!(!PactStatus::Revoked.in_force());
+    }
+
+    #[test]
+    fn test_status_at_replays_events() {
+        let event = |kind: &str, not_after, at| PactEvent {
+            id: 0,
+            kind: kind.to_string(),
+            not_after,
+            reason: None,
+            actor: None,
+            created_at: OffsetDateTime::from_unix_timestamp(at).unwrap(),
+        };
+        let pact = row(100, 400);
+        let events = [event("registered", 200, 50), event("revoked", 200, 150), event("renewed", 400, 300)];
+        assert_eq!(PactStatus::at(Some(&pact), &events, 40), PactStatus::Unknown);
+        assert_eq!(PactStatus::at(Some(&pact), &events, 60), PactStatus::Pending);
+        assert_eq!(PactStatus::at(Some(&pact), &events, 120), PactStatus::Active);
+        assert_eq!(PactStatus::at(Some(&pact), &events, 160), PactStatus::Revoked);
+        assert_eq!(PactStatus::at(Some(&pact), &events, 350), PactStatus::Active);
+        assert_eq!(PactStatus::at(Some(&pact), &events[..2], 250), PactStatus::Revoked);
+        assert_eq!(PactStatus::at(Some(&pact), &events[..1], 250), PactStatus::Expired);
+    }
+}
+
+/// A recorded pact validation
+#[derive(Debug, Clone, Serialize)]
+pub struct ValidationRow {
+    pub id: i64,
+    pub pact_id: String,
+    pub container_id: String,
+    pub link_hash: String,
+    pub nonce: i64,
+    pub intent_class: i16,
+    pub validated_at: i64,
+    pub accepted: bool,
+    pub error_code: Option<String>,
+    pub error: Option<String>,
+    pub counted: serde_json::Value,
+    pub rejected: serde_json::Value,
+    #[serde(with = "time::serde::rfc3339")]
+    pub recorded_at: OffsetDateTime,
+}
+
+/// Append a validation to the audit log
+pub async fn insert_validation(pool: &PgPool, event: &ValidationEvent) -> sqlx::Result<()> {
+    let counted = serde_json::to_value(&event.signatures.counted).unwrap_or_default();
+    let rejected = serde_json::to_value(&event.signatures.rejected).unwrap_or_default();
+    sqlx::query!(
+        r#"INSERT INTO pact_validation_event
+             (pact_id, container_id, link_hash, nonce, intent_class, validated_at, accepted,
+              error_code, error, counted, rejected)
+           VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)"#,
+        event.pact_id,
+        event.container_id,
+        event.link_hash,
+        event.nonce as i64,
+        i16::from(event.intent_class),
+        event.at,
+        event.error.is_none(),
+        event.error.as_ref().map(|e| e.code()),
+        event.error.as_ref().map(|e| e.to_string()),
+        counted,
+        rejected
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Validations recorded under `pact_id`, for `link_hash` if given, newest first
+pub async fn validations(
+    pool: &PgPool,
+    pact_id: &str,
+    link_hash: Option<&str>,
+    limit: i64,
+) -> sqlx::Result<Vec<ValidationRow>> {
+    sqlx::query_as!(
+        ValidationRow,
+        r#"SELECT id, pact_id, container_id, link_hash, nonce, intent_class, validated_at, accepted,
+                  error_code, error, counted, rejected, recorded_at
+           FROM pact_validation_event
+           WHERE pact_id = $1 AND ($2::text IS NULL OR link_hash = lower($2))
+           ORDER BY id DESC
+           LIMIT $3"#,
+        pact_id,
+        link_hash,
+        limit
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// A freeze or lift of the commit path
+#[derive(Debug, Clone, Serialize)]
+pub struct FreezeRow {
+    pub id: i64,
+    pub pact_id: String,
+    /// `freeze` or `lift`
+    pub action: String,
+    pub nonce: i64,
+    pub reason: String,
+    pub actor: String,
+    #[serde(with = "time::serde::rfc3339")]
+    pub at: OffsetDateTime,
+}
+
+/// What [`insert_freeze`] did
+#[derive(Debug, Clone)]
+pub enum FreezeChange {
+    Applied(FreezeRow),
+    /// The commit path is already in the state the action leads to; nothing stored
+    Unchanged,
+    /// The proof already acted; nothing stored
+    Replayed,
+}
+
+/// Record a freeze or lift taken on a proof under `pact_id`, unless the
+/// commit path is already frozen (lifted) or the proof already acted
+pub async fn insert_freeze(
+    pool: &PgPool,
+    pact_id: &str,
+    action: FreezeAction,
+    nonce: u64,
+    reason: &str,
+    actor: &str,
+) -> sqlx::Result<FreezeChange> {
+    let mut tx = pool.begin().await?;
+    // Concurrent actions take turns, so each sees the state the last left
+    sqlx::query("LOCK TABLE pact_freeze IN SHARE ROW EXCLUSIVE MODE")
+        .execute(&mut *tx)
+        .await?;
+    let frozen = freeze(&mut *tx).await?.is_some();
+    if frozen == (action == FreezeAction::Freeze) {
+        return Ok(FreezeChange::Unchanged);
+    }
+    let row = sqlx::query_as!(
+        FreezeRow,
+        r#"INSERT INTO pact_freeze (pact_id, action, nonce, reason, actor)
+           VALUES ($1, $2, $3, $4, $5)
+           ON CONFLICT DO NOTHING
+           RETURNING id, pact_id, action, nonce, reason, actor, at"#,
+        pact_id,
+        action.as_str(),
+        nonce as i64,
+        reason,
+        actor
+    )
+    .fetch_optional(&mut *tx)
+    .await?;
+    let Some(row) = row else {
+        return Ok(FreezeChange::Replayed);
+    };
+    tx.commit().await?;
+    Ok(FreezeChange::Applied(row))
+}
+
+/// The freeze in force, if the commit path is frozen
+pub async fn freeze(db: impl PgExecutor<'_>) -> sqlx::Result<Option<FreezeRow>> {
+    let latest = sqlx::query_as!(
+        FreezeRow,
+        "SELECT id, pact_id, action, nonce, reason, actor, at FROM pact_freeze ORDER BY id DESC LIMIT 1"
+    )
+    .fetch_optional(db)
+    .await?;
+    Ok(latest.filter(|row| row.action == FreezeAction::Freeze.as_str()))
+}
+
+/// Freezes and lifts, newest first
+pub async fn freezes(pool: &PgPool, limit: i64) -> sqlx::Result<Vec<FreezeRow>> {
+    sqlx::query_as!(
+        FreezeRow,
+        "SELECT id, pact_id, action, nonce, reason, actor, at FROM pact_freeze ORDER BY id DESC LIMIT $1",
+        limit
+    )
+    .fetch_all(pool)
+    .await
+}
@@ -0,0 +1,289 @@
+//! # Pact creation ceremony
+//!
+//! - POST /pacts/drafts                           (propose a `ubl_pact::Pact`; admin)
+//! - GET  /pacts/drafts/:draft_hash               (terms, signatures so far, quorum progress)
+//! - POST /pacts/drafts/:draft_hash/signatures    (`{pubkey, signature}`; any session)
+//! - POST /pacts/drafts/:draft_hash/activate      (register the pact once adopted; admin)
+//!
+//! `POST /pacts` registers a pact on an admin's word. A pact can instead be
+//! adopted by its own signers (SPEC-UBL-PACT v1.0 §4.4): the proposal is
+//! stored as a draft addressed by `Pact::draft_hash`, each signer adds an
+//! Ed25519 signature over `Pact::adoption_bytes(pact_id, draft_hash)`, and
+//! activation registers the pact only once the collected signatures meet
+//! the pact's own `threshold` and group thresholds (the bootstrap quorum).
+//!
+//! A signature is checked when it is added: the key must be one of the
+//! pact's signers, or the current UBL ID key of a signer named by SID, and
+//! the signature must verify, otherwise it is refused and nothing is
+//! stored. Any session may submit one, since the signature is what carries
+//! the signer's authority; the session is recorded with it. Each key signs
+//! once.
+//!
+//! Drafts and signatures live in Postgres (`pact_draft`,
+//! `pact_draft_signature`; `pact_db.rs`), so a ceremony spans requests,
+//! instances and restarts. Drafts are immutable: amending the terms is a new
+//! proposal with a new hash, and signatures on the old one do not carry
+//! over. Activation marks the draft and inserts the pact in one transaction,
+//! and fails if the pact id was registered meanwhile or its window has
+//! already ended.
+
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    routing::{get, post},
+    Json, Router,
+};
+use serde::Serialize;
+use time::OffsetDateTime;
+use tracing::{info, warn};
+use ubl_pact::{Pact, PactSignature};
+
+use crate::auth::rbac;
+use crate::pact_db::{self, DraftRow, DraftSignature, PactRow};
+use crate::pact_routes::{check_pact, normalize_keys, resolve_signers};
+use crate::AppState;
+
+#[derive(Debug, Serialize)]
+pub struct DraftView {
+    #[serde(flatten)]
+    pub draft: DraftRow,
+    /// `proposed`, or `activated` once registered
+    pub status: &'static str,
+    pub signatures: Vec<DraftSignature>,
+    pub quorum: Quorum,
+}
+
+/// Weight collected towards the pact's thresholds
+#[derive(Debug, Serialize)]
+pub struct Quorum {
+    pub weight: usize,
+    pub threshold: usize,
+    pub groups: Vec<GroupProgress>,
+    pub met: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GroupProgress {
+    pub name: String,
+    pub weight: usize,
+    pub threshold: usize,
+}
+
+impl Quorum {
+    pub fn of(pact: &Pact, signed: &[&str]) -> Self {
+        Quorum {
+            weight: signed.iter().map(|s| pact.weight(s)).sum(),
+            threshold: pact.threshold,
+            groups: pact
+                .groups
+                .iter()
+                .map(|g| GroupProgress {
+                    name: g.name.clone(),
+                    weight: signed
+                        .iter()
+                        .filter(|s| g.members.contains(**s))
+                        .map(|s| pact.weight(s))
+                        .sum(),
+                    threshold: g.threshold,
+                })
+                .collect(),
+            met: pact.check_quorum(signed).is_ok(),
+        }
+    }
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/pacts/drafts", post(route_propose))
+        .route("/pacts/drafts/:draft_hash", get(route_get))
+        .route("/pacts/drafts/:draft_hash/signatures", post(route_sign))
+        .route("/pacts/drafts/:draft_hash/activate", post(route_activate))
+}
+
+fn internal(e: sqlx::Error) -> (StatusCode, String) {
+    (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+}
+
+fn not_found(draft_hash: &str) -> (StatusCode, String) {
+    (StatusCode::NOT_FOUND, format!("no pact draft {}", draft_hash))
+}
+
+fn terms(draft: &DraftRow) -> Result<Pact, (StatusCode, String)> {
+    serde_json::from_value(draft.pact.clone()).map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+}
+
+/// The draft with its signatures and quorum progress
+async fn view(state: &AppState, draft: DraftRow) -> Result<DraftView, (StatusCode, String)> {
+    let pact = resolve_signers(state, &terms(&draft)?).await.map_err(internal)?;
+    let signatures = pact_db::draft_signatures(&state.pool, &draft.draft_hash)
+        .await
+        .map_err(internal)?;
+    let signed: Vec<&str> = signatures.iter().map(|s| s.pubkey.as_str()).collect();
+    Ok(DraftView {
+        status: if draft.activated_at.is_some() { "activated" } else { "proposed" },
+        quorum: Quorum::of(&pact, &signed),
+        signatures,
+        draft,
+    })
+}
+
+async fn open_draft(state: &AppState, draft_hash: &str) -> Result<DraftRow, (StatusCode, String)> {
+    let draft = pact_db::get_draft(&state.pool, draft_hash)
+        .await
+        .map_err(internal)?
+        .ok_or_else(|| not_found(draft_hash))?;
+    if draft.activated_at.is_some() {
+        return Err((StatusCode::CONFLICT, format!("pact draft {} is already activated", draft_hash)));
+    }
+    Ok(draft)
+}
+
+/// POST /pacts/drafts
+async fn route_propose(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(mut pact): Json<Pact>,
+) -> Result<(StatusCode, Json<DraftView>), (StatusCode, String)> {
+    let caller = rbac::require_role(&state.pool, &headers, &[rbac::ADMIN]).await?;
+    check_pact(&pact).map_err(|e| (StatusCode::UNPROCESSABLE_ENTITY, e))?;
+    normalize_keys(&mut pact);
+    if pact_db::get(&state.pool, &pact.pact_id).await.map_err(internal)?.is_some() {
+        return Err((StatusCode::CONFLICT, format!("pact {} already registered", pact.pact_id)));
+    }
+    let draft_hash = pact.draft_hash();
+    let draft = pact_db::insert_draft(&state.pool, &draft_hash, &pact, &caller.session.sid)
+        .await
+        .map_err(internal)?
+        .ok_or_else(|| (StatusCode::CONFLICT, format!("these terms are already proposed as {}", draft_hash)))?;
+    info!("📝 Pact {} proposed as draft {} by {}", pact.pact_id, draft_hash, caller.session.sid);
+    Ok((StatusCode::CREATED, Json(view(&state, draft).await?)))
+}
+
+/// GET /pacts/drafts/:draft_hash
+async fn route_get(
+    State(state): State<AppState>,
+    Path(draft_hash): Path<String>,
+    headers: HeaderMap,
+) -> Result<Json<DraftView>, (StatusCode, String)> {
+    rbac::authenticate(&state.pool, &headers).await?;
+    let draft = pact_db::get_draft(&state.pool, &draft_hash)
+        .await
+        .map_err(internal)?
+        .ok_or_else(|| not_found(&draft_hash))?;
+    Ok(Json(view(&state, draft).await?))
+}
+
+/// POST /pacts/drafts/:draft_hash/signatures
+async fn route_sign(
+    State(state): State<AppState>,
+    Path(draft_hash): Path<String>,
+    headers: HeaderMap,
+    Json(mut signature): Json<PactSignature>,
+) -> Result<Json<DraftView>, (StatusCode, String)> {
+    let caller = rbac::authenticate(&state.pool, &headers).await?;
+    let draft = open_draft(&state, &draft_hash).await?;
+    let pact = terms(&draft)?;
+    let keys = resolve_signers(&state, &pact).await.map_err(internal)?;
+    signature.pubkey = signature.pubkey.to_ascii_lowercase();
+    if !keys.signers.contains(&signature.pubkey) {
+        return Err((
+            StatusCode::FORBIDDEN,
+            format!("{} is not a signer of pact {}", signature.pubkey, pact.pact_id),
+        ));
+    }
+    let message = Pact::adoption_bytes(&pact.pact_id, &draft.draft_hash);
+    if ubl_kernel::verify(&signature.pubkey, &message, &signature.signature).is_err() {
+        warn!(
+            draft_hash = %draft_hash,
+            pubkey = %signature.pubkey,
+            decision = "reject",
+            error_code = "invalid_signature"
+        );
+        return Err((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "signature does not verify over the draft's adoption bytes".to_string(),
+        ));
+    }
+    if !pact_db::add_draft_signature(&state.pool, &draft_hash, &signature, &caller.session.sid)
+        .await
+        .map_err(internal)?
+    {
+        return Err((StatusCode::CONFLICT, format!("{} already signed this draft", signature.pubkey)));
+    }
+    info!("✍️ Pact draft {} signed by {}", draft_hash, signature.pubkey);
+    Ok(Json(view(&state, draft).await?))
+}
+
+/// POST /pacts/drafts/:draft_hash/activate
+async fn route_activate(
+    State(state): State<AppState>,
+    Path(draft_hash): Path<String>,
+    headers: HeaderMap,
+) -> Result<(StatusCode, Json<PactRow>), (StatusCode, String)> {
+    let caller = rbac::require_role(&state.pool, &headers, &[rbac::ADMIN]).await?;
+    let draft = open_draft(&state, &draft_hash).await?;
+    let pact = terms(&draft)?;
+    if pact.window.not_after < OffsetDateTime::now_utc().unix_timestamp() {
+        return Err((StatusCode::UNPROCESSABLE_ENTITY, "the pact's window has already ended".to_string()));
+    }
+    let signatures: Vec<PactSignature> = pact_db::draft_signatures(&state.pool, &draft_hash)
+        .await
+        .map_err(internal)?
+        .into_iter()
+        .map(|s| PactSignature {
+            pubkey: s.pubkey,
+            signature: s.signature,
+        })
+        .collect();
+    let keys = resolve_signers(&state, &pact).await.map_err(internal)?;
+    pact.verify_adoption_by(&keys, &signatures)
+        .map_err(|e| (StatusCode::UNPROCESSABLE_ENTITY, format!("pact not adopted: {}", e)))?;
+    let row = pact_db::activate_draft(&state.pool, &draft_hash, &pact, &caller.session.sid)
+        .await
+        .map_err(internal)?
+        .ok_or_else(|| {
+            (
+                StatusCode::CONFLICT,
+                format!("pact {} is already registered or the draft was activated", pact.pact_id),
+            )
+        })?;
+    info!(
+        "🤝 Pact {} activated from draft {} with {} signature(s) by {}",
+        row.pact_id,
+        draft_hash,
+        signatures.len(),
+        caller.session.sid
+    );
+    Ok((StatusCode::CREATED, Json(row)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ubl_pact::{RiskLevel, SignerGroup, TimeWindow};
+
+    #[test]
+    fn test_quorum_progress() {
+        let pact = Pact {
+            pact_id: "pact_fund".to_string(),
+            threshold: 3,
+            signers: ["aa", "bb", "cc"].iter().map(|s| s.to_string()).collect(),
+            window: TimeWindow {
+                not_before: 0,
+                not_after: 100,
+            },
+            risk_level: RiskLevel::L3,
+            weights: Some([("aa".to_string(), 2)].into_iter().collect()),
+            groups: vec![SignerGroup {
+                name: "auditors".to_string(),
+                members: ["cc".to_string()].into_iter().collect(),
+                threshold: 1,
+            }],
+            ..ubl_fixtures::pact()
+        };
+        let q = Quorum::of(&pact, &["aa", "bb"]);
+        assert_eq!((q.weight, q.threshold, q.groups[0].weight, q.met), (3, 3, 0, false));
+        let q = Quorum::of(&pact, &["aa", "cc"]);
+        assert_eq!((q.weight, q.groups[0].weight, q.met), (3, 1, true));
+    }
+}
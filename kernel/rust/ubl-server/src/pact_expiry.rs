@@ -0,0 +1,171 @@
+//! # Pact expiry warnings
+//!
+//! - GET /pacts/expiring?within=   (pacts ending within `within` seconds, default the horizon; admin/operator/auditor)
+//!
+//! A pact that reaches `not_after` stops authorizing: containers under it
+//! drop to Observation (`pact_routes.rs`) and its proofs are refused. The
+//! monitor sweeps the registered pacts every [`SWEEP_SECS`] for those
+//! ending within `UBL_PACT_EXPIRY_HORIZON_SECS` (default
+//! [`DEFAULT_HORIZON_SECS`]), so operations can renew them first:
+//!
+//! - `ubl_pact_expires_in_seconds{pact_id}` is set for each of them, and
+//!   only them (alert on it in Prometheus);
+//! - a `warn!` names each one once per `not_after`, so a renewal that is
+//!   itself about to end warns again.
+//!
+//! Revoked pacts, and pacts already renewed by a successor, are not listed:
+//! there is nothing left to renew.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+    routing::get,
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use tracing::{error, info, warn};
+
+use crate::auth::rbac;
+use crate::pact_db::{self, PactRow};
+use crate::{metrics, AppState};
+
+/// How often pacts are checked against the horizon
+pub const SWEEP_SECS: u64 = 300;
+
+/// How far ahead of `not_after` a pact is reported, unless set
+pub const DEFAULT_HORIZON_SECS: i64 = 7 * 24 * 3600;
+
+/// `UBL_PACT_EXPIRY_HORIZON_SECS`, or [`DEFAULT_HORIZON_SECS`]
+pub fn horizon_from_env() -> i64 {
+    std::env::var("UBL_PACT_EXPIRY_HORIZON_SECS")
+        .ok()
+        .and_then(|n| n.parse().ok())
+        .filter(|&n: &i64| n > 0)
+        .unwrap_or(DEFAULT_HORIZON_SECS)
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExpiringPact {
+    pub pact_id: String,
+    pub not_after: i64,
+    pub expires_in: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExpiringQuery {
+    #[serde(default)]
+    pub within: Option<i64>,
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new().route("/pacts/expiring", get(route_expiring))
+}
+
+/// Rows ending within `horizon` of `now`, soonest first
+async fn expiring(state: &AppState, now: i64, horizon: i64) -> sqlx::Result<Vec<ExpiringPact>> {
+    let rows = pact_db::expiring(&state.pool, now, now.saturating_add(horizon)).await?;
+    Ok(rows.iter().map(|row| view(row, now)).collect())
+}
+
+fn view(row: &PactRow, now: i64) -> ExpiringPact {
+    ExpiringPact {
+        pact_id: row.pact_id.clone(),
+        not_after: row.not_after,
+        expires_in: row.not_after - now,
+    }
+}
+
+/// Pacts not yet warned about at their current `not_after`; records them in `warned`
+fn newly_expiring<'a>(warned: &mut HashMap<String, i64>, pacts: &'a [ExpiringPact]) -> Vec<&'a ExpiringPact> {
+    // Forget pacts that left the horizon (renewed, revoked or expired)
+    warned.retain(|id, _| pacts.iter().any(|p| &p.pact_id == id));
+    pacts
+        .iter()
+        .filter(|p| warned.insert(p.pact_id.clone(), p.not_after) != Some(p.not_after))
+        .collect()
+}
+
+/// Export and warn about pacts nearing expiry every [`SWEEP_SECS`]
+pub fn spawn_monitor(state: AppState) {
+    let horizon = horizon_from_env();
+    info!("⏳ Pact expiry warnings {}s ahead of not_after", horizon);
+    tokio::spawn(async move {
+        let mut warned = HashMap::new();
+        let mut tick = tokio::time::interval(Duration::from_secs(SWEEP_SECS));
+        loop {
+            tick.tick().await;
+            let now = OffsetDateTime::now_utc().unix_timestamp();
+            let pacts = match expiring(&state, now, horizon).await {
+                Ok(pacts) => pacts,
+                Err(e) => {
+                    error!("pact expiry sweep failed: {}", e);
+                    continue;
+                }
+            };
+            metrics::PACT_EXPIRES_IN.reset();
+            for p in &pacts {
+                metrics::PACT_EXPIRES_IN.with_label_values(&[&p.pact_id]).set(p.expires_in);
+            }
+            for p in newly_expiring(&mut warned, &pacts) {
+                warn!(
+                    pact_id = %p.pact_id,
+                    not_after = p.not_after,
+                    expires_in = p.expires_in,
+                    "pact expires soon; renew it or register a successor"
+                );
+            }
+        }
+    });
+}
+
+/// GET /pacts/expiring
+async fn route_expiring(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(q): Query<ExpiringQuery>,
+) -> Result<Json<Vec<ExpiringPact>>, (StatusCode, String)> {
+    rbac::require_role(&state.pool, &headers, &[rbac::ADMIN, rbac::OPERATOR, rbac::AUDITOR]).await?;
+    let horizon = q.within.unwrap_or_else(horizon_from_env);
+    if horizon <= 0 {
+        return Err((StatusCode::BAD_REQUEST, "within must be positive".to_string()));
+    }
+    let now = OffsetDateTime::now_utc().unix_timestamp();
+    let pacts = expiring(&state, now, horizon)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok(Json(pacts))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pact(pact_id: &str, not_after: i64) -> ExpiringPact {
+        ExpiringPact {
+            pact_id: pact_id.to_string(),
+            not_after,
+            expires_in: not_after - 100,
+        }
+    }
+
+    #[test]
+    fn test_warned_once_per_not_after() {
+        let mut warned = HashMap::new();
+        let sweep = [pact("a", 500), pact("b", 900)];
+        assert_eq!(newly_expiring(&mut warned, &sweep).len(), 2);
+        assert!(newly_expiring(&mut warned, &sweep).is_empty());
+
+        // Renewed in place to a window that also ends soon: warned again
+        let renewed = [pact("a", 800), pact("b", 900)];
+        let again: Vec<_> = newly_expiring(&mut warned, &renewed).iter().map(|p| p.pact_id.as_str()).collect();
+        assert_eq!(again, ["a"]);
+
+        // Out of the horizon, then back at the same not_after
+        assert!(newly_expiring(&mut warned, &[pact("a", 800)]).is_empty());
+        assert_eq!(newly_expiring(&mut warned, &renewed).len(), 1);
+    }
+}
@@ -0,0 +1,193 @@
+//! # Emergency freeze of the commit path
+//!
+//! - GET  /pacts/freeze?limit=     (the freeze in force, if any, then freezes and lifts, newest first;
+//!                                  admin/operator/auditor)
+//! - POST /pacts/freeze            (`{proof, reason}`; any session)
+//! - POST /pacts/freeze/lift       (`{proof, reason}`; any session)
+//!
+//! The kill-switch for incident response (SPEC-UBL-PACT v1.0 §9.7). A
+//! Global pact registered with `emergency` may freeze the whole commit
+//! path: a `PactProof` under it over `FreezeAction::Freeze.subject()`,
+//! with the pact's quorum at its own risk level, and from then on every
+//! link but an Observation is refused on every instance, whatever its
+//! container, authority or proof ([`admit`], 403). Only a second proof,
+//! over `FreezeAction::Lift.subject()`, lifts it; the freeze proof cannot.
+//! As with proofs collected here, any session may submit, since the
+//! signatures carry the authority; the session is recorded as the actor.
+//!
+//! The pact must be in force, and each proof acts once. Freezes and lifts
+//! are appended to `pact_freeze` (`pact_db.rs`) and never rewritten; the
+//! latest row is the state. Freezing a frozen path, or lifting one that is
+//! not, is refused (409) and stores nothing. Commits read the state through
+//! the pact cache (`pact_cache.rs`), which the `pact_changed` NOTIFY from
+//! `pact_freeze` keeps current; like the authority check, a commit already
+//! past [`admit`] when the freeze lands may still land.
+
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+    routing::{get, post},
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use tracing::warn;
+use ubl_link::IntentClass;
+use ubl_membrane::ContainerProfile;
+use ubl_pact::freeze::FreezeAction;
+use ubl_pact::{Pact, PactError, PactProof, PactRegistry};
+
+use crate::auth::rbac;
+use crate::db::LinkDraft;
+use crate::pact_db::{self, FreezeChange, FreezeRow, PactStatus};
+use crate::{error_routes, pact_routes, AppState};
+
+#[derive(Debug, Deserialize)]
+pub struct FreezeReq {
+    /// Proof under an emergency pact over the action's subject
+    pub proof: PactProof,
+    pub reason: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FreezeQuery {
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+}
+
+fn default_limit() -> i64 {
+    50
+}
+
+#[derive(Debug, Serialize)]
+pub struct FreezeView {
+    pub frozen: bool,
+    /// The freeze in force
+    pub freeze: Option<FreezeRow>,
+    /// Freezes and lifts, newest first
+    pub history: Vec<FreezeRow>,
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/pacts/freeze", get(route_get).post(route_freeze))
+        .route("/pacts/freeze/lift", post(route_lift))
+}
+
+fn internal(e: impl std::fmt::Display) -> (StatusCode, String) {
+    (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+}
+
+fn rejected(e: PactError) -> (StatusCode, String) {
+    (error_routes::status(&e), e.to_string())
+}
+
+/// Commit pipeline: while the commit path is frozen, Observation links only
+/// (membrane restricted profile)
+pub async fn admit(state: &AppState, link: &LinkDraft) -> Result<(), (StatusCode, String)> {
+    let Some(freeze) = state.pacts.freeze(&state.pool).await.map_err(internal)? else {
+        return Ok(());
+    };
+    let class: IntentClass = link
+        .intent_class
+        .parse()
+        .map_err(|e| (StatusCode::UNPROCESSABLE_ENTITY, e))?;
+    if let Err(e) = ContainerProfile::Restricted.check(class, 0) {
+        warn!(
+            container_id = %link.container_id,
+            pact_id = %freeze.pact_id,
+            decision = "reject",
+            error_code = "frozen"
+        );
+        return Err((StatusCode::FORBIDDEN, format!("{}: {}", e, PactError::Frozen(freeze.pact_id))));
+    }
+    Ok(())
+}
+
+/// GET /pacts/freeze
+async fn route_get(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(q): Query<FreezeQuery>,
+) -> Result<Json<FreezeView>, (StatusCode, String)> {
+    rbac::require_role(&state.pool, &headers, &[rbac::ADMIN, rbac::OPERATOR, rbac::AUDITOR]).await?;
+    let freeze = pact_db::freeze(&state.pool).await.map_err(internal)?;
+    Ok(Json(FreezeView {
+        frozen: freeze.is_some(),
+        freeze,
+        history: pact_db::freezes(&state.pool, q.limit.clamp(1, 1000)).await.map_err(internal)?,
+    }))
+}
+
+/// POST /pacts/freeze
+async fn route_freeze(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<FreezeReq>,
+) -> Result<Json<FreezeRow>, (StatusCode, String)> {
+    act(&state, &headers, FreezeAction::Freeze, req).await
+}
+
+/// POST /pacts/freeze/lift
+async fn route_lift(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<FreezeReq>,
+) -> Result<Json<FreezeRow>, (StatusCode, String)> {
+    act(&state, &headers, FreezeAction::Lift, req).await
+}
+
+/// Check `req.proof` for `action` under its emergency pact, then record it
+async fn act(
+    state: &AppState,
+    headers: &HeaderMap,
+    action: FreezeAction,
+    req: FreezeReq,
+) -> Result<Json<FreezeRow>, (StatusCode, String)> {
+    let caller = rbac::authenticate(&state.pool, headers).await?;
+    if req.reason.trim().is_empty() {
+        return Err((StatusCode::UNPROCESSABLE_ENTITY, format!("a {} needs a reason", action.as_str())));
+    }
+    let pact_id = &req.proof.pact_id;
+    let now = OffsetDateTime::now_utc().unix_timestamp();
+    let row = pact_db::get(&state.pool, pact_id).await.map_err(internal)?;
+    let status = PactStatus::of(row.as_ref(), now);
+    let Some(row) = row.filter(|_| status.in_force()) else {
+        return Err((StatusCode::CONFLICT, format!("pact {} is {:?}", pact_id, status)));
+    };
+    let pact: Pact = serde_json::from_value(row.pact).map_err(internal)?;
+    let mut registry = PactRegistry::new();
+    registry.register(pact_routes::resolve_signers(state, &pact).await.map_err(internal)?);
+    registry.check_freeze(&req.proof, action, now).map_err(rejected)?;
+    let row = match pact_db::insert_freeze(
+        &state.pool,
+        pact_id,
+        action,
+        req.proof.nonce,
+        &req.reason,
+        &caller.session.sid,
+    )
+    .await
+    .map_err(internal)?
+    {
+        FreezeChange::Applied(row) => row,
+        FreezeChange::Unchanged => {
+            let state = if action == FreezeAction::Freeze { "frozen" } else { "not frozen" };
+            return Err((StatusCode::CONFLICT, format!("the commit path is already {}", state)));
+        }
+        FreezeChange::Replayed => {
+            return Err(rejected(PactError::Replayed {
+                link_hash: action.subject(),
+                nonce: req.proof.nonce,
+            }));
+        }
+    };
+    warn!(
+        pact_id = %pact_id,
+        actor = %caller.session.sid,
+        reason = %req.reason,
+        "🧊 Commit path {}",
+        if action == FreezeAction::Freeze { "frozen" } else { "unfrozen" }
+    );
+    Ok(Json(row))
+}
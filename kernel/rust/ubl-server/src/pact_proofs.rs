@@ -0,0 +1,374 @@
+//! # Pact proofs assembled on the server
+//!
+//! - POST /pacts/proofs                         (open one for a link; any session)
+//!   `{pact_id, container_id, link_hash, intent_class, nonce?}`
+//! - GET  /pacts/proofs/:link_hash              (signing request, signatures so far, quorum progress)
+//! - POST /pacts/proofs/:link_hash/signatures   (`{pubkey, signature}`; any session)
+//! - GET  /pacts/proofs/:link_hash/proof        (the `PactProof` to attach to the link, once complete)
+//!
+//! Gathering a quorum of signatures for a link can take hours, longer than
+//! any one client wants to hold them. Opening a proof exports the signing
+//! request for the link hash under the registered pact
+//! (`ubl_pact::offline::export`, the same file air-gapped signers work
+//! from); each signer then adds their signature over
+//! `PactProof::signing_bytes(link_hash, pact_id, nonce)` whenever they get
+//! to it, and the committer fetches the finished proof once the pact's
+//! `threshold` and group thresholds are met (409 until then). A
+//! container-scoped pact opens proofs for its own container only
+//! (`ScopeMismatch`, 422).
+//!
+//! A signature is checked against the request when it is added, as a
+//! `PendingProof` import: the key must be one of the pact's signers, must
+//! not have signed already, and the signature must verify. Anything else is
+//! refused and nothing is stored. Each check, accepted or not, is recorded
+//! in the validation audit log (`pact_audit.rs`). As with drafts, any session may submit,
+//! since the signature carries the signer's authority; the session is
+//! recorded with it. Signatures are refused once the pact is no longer in
+//! force or the request's `not_after` has passed.
+//!
+//! Each completed proof is one use of its pact. A pact with `max_uses`
+//! (e.g. a single-use emergency authorization) opens no further proofs once
+//! they are spent, and the signature that would complete one more is
+//! refused (`UsesExhausted`, 422). The count is kept in `pact_usage` and
+//! raised in the transaction that completes the proof.
+//!
+//! Signers named by SID are resolved to their current UBL ID key when the
+//! proof is opened, and the request lists that key: a signer who rotates
+//! while signatures are collected signs a new request.
+//!
+//! There is one proof per link hash. Requests and signatures live in
+//! Postgres (`pact_proof_request`, `pact_proof_signature`; `pact_db.rs`) and
+//! are never rewritten, so collection spans requests, instances and
+//! restarts. The fetched proof is checked again at commit like any other:
+//! a pact revoked or vetoed after completion still fails there.
+
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    routing::{get, post},
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use tracing::{info, warn};
+use ubl_pact::offline::{self, PendingProof, SignatureFile, SigningRequestFile, SIGNATURE_FORMAT};
+use ubl_pact::{
+    Pact, PactError, PactProof, PactSignature, RejectedSignature, SignatureTally, ValidationEvent, ValidationSink,
+};
+
+use crate::auth::rbac;
+use crate::pact_db::{self, DraftSignature, PactStatus, ProofRequestRow, ProofSigning};
+use crate::pact_drafts::Quorum;
+use crate::{error_routes, pact_routes, AppState};
+
+#[derive(Debug, Deserialize)]
+pub struct OpenReq {
+    pub pact_id: String,
+    pub container_id: String,
+    pub link_hash: String,
+    pub intent_class: u8,
+    #[serde(default)]
+    pub nonce: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProofView {
+    #[serde(flatten)]
+    pub proof: ProofRequestRow,
+    /// `collecting`, or `complete` once the quorum is met
+    pub status: &'static str,
+    pub signatures: Vec<DraftSignature>,
+    pub quorum: Quorum,
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/pacts/proofs", post(route_open))
+        .route("/pacts/proofs/:link_hash", get(route_get))
+        .route("/pacts/proofs/:link_hash/signatures", post(route_sign))
+        .route("/pacts/proofs/:link_hash/proof", get(route_proof))
+}
+
+fn internal(e: sqlx::Error) -> (StatusCode, String) {
+    (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+}
+
+fn rejected(e: PactError) -> (StatusCode, String) {
+    (error_routes::status(&e), e.to_string())
+}
+
+fn now() -> i64 {
+    OffsetDateTime::now_utc().unix_timestamp()
+}
+
+/// The signature file a signer would have produced offline for `file`
+fn answer(file: &SigningRequestFile, signature: &PactSignature) -> SignatureFile {
+    SignatureFile {
+        format: SIGNATURE_FORMAT.to_string(),
+        request_hash: file.request_hash.clone(),
+        pact_id: file.request.pact_id.clone(),
+        link_hash: file.request.link_hash.clone(),
+        nonce: file.request.nonce,
+        pubkey: signature.pubkey.clone(),
+        signature: signature.signature.clone(),
+    }
+}
+
+/// A stored proof: its request, the pact it is under and the signatures so far
+struct Stored {
+    row: ProofRequestRow,
+    file: SigningRequestFile,
+    pact: Pact,
+    signatures: Vec<DraftSignature>,
+    pending: PendingProof,
+}
+
+async fn load(state: &AppState, link_hash: &str) -> Result<Stored, (StatusCode, String)> {
+    let link_hash = link_hash.to_ascii_lowercase();
+    let row = pact_db::get_proof_request(&state.pool, &link_hash)
+        .await
+        .map_err(internal)?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, format!("no pact proof for link {}", link_hash)))?;
+    let file: SigningRequestFile =
+        serde_json::from_value(row.request.clone()).map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let pact_row = pact_db::get(&state.pool, &row.pact_id)
+        .await
+        .map_err(internal)?
+        .ok_or_else(|| (StatusCode::INTERNAL_SERVER_ERROR, format!("pact {} is gone", row.pact_id)))?;
+    let pact: Pact =
+        serde_json::from_value(pact_row.pact).map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let pact = pact_routes::resolve_signers(state, &pact).await.map_err(internal)?;
+    let signatures = pact_db::proof_signatures(&state.pool, &link_hash).await.map_err(internal)?;
+    let mut pending = PendingProof::new(&file).map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    for s in &signatures {
+        let stored = PactSignature {
+            pubkey: s.pubkey.clone(),
+            signature: s.signature.clone(),
+        };
+        pending
+            .import(&answer(&file, &stored))
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("stored signature rejected: {}", e)))?;
+    }
+    Ok(Stored {
+        row,
+        file,
+        pact,
+        signatures,
+        pending,
+    })
+}
+
+fn view(stored: Stored) -> ProofView {
+    let signed: Vec<&str> = stored.signatures.iter().map(|s| s.pubkey.as_str()).collect();
+    ProofView {
+        status: if stored.pending.is_complete() { "complete" } else { "collecting" },
+        quorum: Quorum::of(&stored.pact, &signed),
+        signatures: stored.signatures,
+        proof: stored.row,
+    }
+}
+
+/// POST /pacts/proofs
+async fn route_open(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<OpenReq>,
+) -> Result<(StatusCode, Json<ProofView>), (StatusCode, String)> {
+    let caller = rbac::authenticate(&state.pool, &headers).await?;
+    let row = pact_db::get(&state.pool, &req.pact_id).await.map_err(internal)?;
+    let now = now();
+    let status = PactStatus::of(row.as_ref(), now);
+    let Some(row) = row.filter(|_| status.in_force()) else {
+        return Err((StatusCode::CONFLICT, format!("pact {} is {:?}", req.pact_id, status)));
+    };
+    let pact: Pact = serde_json::from_value(row.pact).map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let pact = pact_routes::resolve_signers(&state, &pact).await.map_err(internal)?;
+    let used = pact_db::uses(&state.pool, &pact.pact_id).await.map_err(internal)?;
+    pact.check_uses(used).map_err(rejected)?;
+    let file = offline::export(&pact, &req.container_id, &req.link_hash, req.nonce, req.intent_class, now)
+        .map_err(rejected)?;
+    let request = serde_json::to_value(&file).map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let link_hash = file.request.link_hash.clone();
+    pact_db::insert_proof_request(&state.pool, &link_hash, &pact.pact_id, &request, &caller.session.sid)
+        .await
+        .map_err(internal)?
+        .ok_or_else(|| (StatusCode::CONFLICT, format!("a pact proof for link {} is already open", link_hash)))?;
+    info!(
+        "🧾 Pact proof opened for link {} under {} by {}",
+        link_hash, pact.pact_id, caller.session.sid
+    );
+    Ok((StatusCode::CREATED, Json(view(load(&state, &link_hash).await?))))
+}
+
+/// GET /pacts/proofs/:link_hash
+async fn route_get(
+    State(state): State<AppState>,
+    Path(link_hash): Path<String>,
+    headers: HeaderMap,
+) -> Result<Json<ProofView>, (StatusCode, String)> {
+    rbac::authenticate(&state.pool, &headers).await?;
+    Ok(Json(view(load(&state, &link_hash).await?)))
+}
+
+/// POST /pacts/proofs/:link_hash/signatures
+async fn route_sign(
+    State(state): State<AppState>,
+    Path(link_hash): Path<String>,
+    headers: HeaderMap,
+    Json(mut signature): Json<PactSignature>,
+) -> Result<Json<ProofView>, (StatusCode, String)> {
+    let caller = rbac::authenticate(&state.pool, &headers).await?;
+    let mut stored = load(&state, &link_hash).await?;
+    let link_hash = stored.row.link_hash.clone();
+    let now = now();
+    let status = PactStatus::of(pact_db::get(&state.pool, &stored.row.pact_id).await.map_err(internal)?.as_ref(), now);
+    if !status.in_force() || now > stored.file.request.not_after {
+        return Err((
+            StatusCode::CONFLICT,
+            format!("pact {} no longer accepts signatures ({:?})", stored.row.pact_id, status),
+        ));
+    }
+    signature.pubkey = signature.pubkey.to_ascii_lowercase();
+    signature.signature = signature.signature.to_ascii_lowercase();
+    let imported = stored.pending.import(&answer(&stored.file, &signature));
+    state.pact_audit.record(&signature_checked(&stored.file, &signature, now, imported.as_ref().err()));
+    if let Err(e) = imported {
+        warn!(
+            link_hash = %link_hash,
+            pubkey = %signature.pubkey,
+            decision = "reject",
+            error_code = "invalid_signature",
+            "{}", e
+        );
+        return Err(rejected(e));
+    }
+    let completes = stored.pending.is_complete().then_some(&stored.pact);
+    match pact_db::add_proof_signature(&state.pool, &link_hash, &signature, &caller.session.sid, completes)
+        .await
+        .map_err(internal)?
+    {
+        ProofSigning::Added => {}
+        ProofSigning::AlreadySigned => {
+            return Err((StatusCode::CONFLICT, format!("{} already signed this proof", signature.pubkey)));
+        }
+        ProofSigning::UsesExhausted { used } => {
+            let max = stored.pact.max_uses.unwrap_or_default();
+            warn!(
+                link_hash = %link_hash,
+                pact_id = %stored.row.pact_id,
+                used,
+                decision = "reject",
+                error_code = "uses_exhausted"
+            );
+            return Err(rejected(PactError::UsesExhausted { used, max }));
+        }
+    }
+    info!("✍️ Pact proof for link {} signed by {}", link_hash, signature.pubkey);
+    Ok(Json(view(load(&state, &link_hash).await?)))
+}
+
+/// Audit event for one signature checked against `file`'s request
+fn signature_checked(
+    file: &SigningRequestFile,
+    signature: &PactSignature,
+    now: i64,
+    error: Option<&PactError>,
+) -> ValidationEvent {
+    let request = &file.request;
+    let signatures = match error {
+        None => SignatureTally {
+            counted: vec![signature.pubkey.clone()],
+            rejected: Vec::new(),
+        },
+        Some(e) => SignatureTally {
+            counted: Vec::new(),
+            rejected: vec![RejectedSignature {
+                pubkey: signature.pubkey.clone(),
+                reason: e.to_string(),
+            }],
+        },
+    };
+    ValidationEvent {
+        pact_id: request.pact_id.clone(),
+        container_id: request.container_id.clone(),
+        link_hash: request.link_hash.to_ascii_lowercase(),
+        nonce: request.nonce,
+        intent_class: request.intent_class,
+        at: now,
+        signatures,
+        error: error.cloned(),
+    }
+}
+
+/// GET /pacts/proofs/:link_hash/proof
+async fn route_proof(
+    State(state): State<AppState>,
+    Path(link_hash): Path<String>,
+    headers: HeaderMap,
+) -> Result<Json<PactProof>, (StatusCode, String)> {
+    rbac::authenticate(&state.pool, &headers).await?;
+    let stored = load(&state, &link_hash).await?;
+    let collected = stored.pending.collected();
+    stored.pending.into_proof().map(Json).map_err(|e| {
+        (
+            StatusCode::CONFLICT,
+            format!("pact proof not complete ({} collected): {}", collected, e),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::SigningKey;
+    use ubl_pact::{PactRegistry, RiskLevel, TimeWindow};
+
+    #[test]
+    fn test_answer_imports_as_offline_signature() {
+        let keys: Vec<SigningKey> = (1u8..=3).map(|i| SigningKey::from_bytes(&[i; 32])).collect();
+        let pubkeys: Vec<String> = keys.iter().map(ubl_kernel::pubkey_from_signing_key).collect();
+        let pact = Pact {
+            pact_id: "pact_fund".to_string(),
+            threshold: 2,
+            signers: pubkeys.iter().cloned().collect(),
+            window: TimeWindow {
+                not_before: 0,
+                not_after: 10_000,
+            },
+            risk_level: RiskLevel::L3,
+            ..ubl_fixtures::pact()
+        };
+        let link_hash = "ab".repeat(32);
+        let file = offline::export(&pact, "C.Fund", &link_hash, 7, 0x01, 1000).unwrap();
+        let message = PactProof::signing_bytes(&link_hash, &pact.pact_id, 7);
+        let signed = |k: &SigningKey| PactSignature {
+            pubkey: ubl_kernel::pubkey_from_signing_key(k),
+            signature: ubl_kernel::sign(k, &message),
+        };
+
+        let mut pending = PendingProof::new(&file).unwrap();
+        pending.import(&answer(&file, &signed(&keys[0]))).unwrap();
+        assert!(matches!(
+            pending.import(&answer(&file, &signed(&keys[0]))),
+            Err(PactError::DuplicateSigner(_))
+        ));
+        let forged = PactSignature {
+            pubkey: pubkeys[1].clone(),
+            signature: signed(&keys[2]).signature,
+        };
+        let refused = pending.import(&answer(&file, &forged)).unwrap_err();
+        assert!(!pending.is_complete());
+        let event = signature_checked(&file, &forged, 1000, Some(&refused));
+        assert_eq!((event.nonce, event.intent_class, event.at), (7, 0x01, 1000));
+        assert!(event.signatures.counted.is_empty());
+        assert_eq!(event.signatures.rejected[0].pubkey, pubkeys[1]);
+        assert_eq!(event.error, Some(refused));
+
+        pending.import(&answer(&file, &signed(&keys[1]))).unwrap();
+        let proof = pending.into_proof().unwrap();
+        assert_eq!((proof.nonce, proof.signatures.len()), (7, 2));
+        let mut registry = PactRegistry::new();
+        registry.register(pact);
+        registry.validate(&proof, "C.Fund", &link_hash, 0x01, 1000).unwrap();
+    }
+}
@@ -0,0 +1,865 @@
+//! # Pacts and container authority
+//!
+//! - GET  /pacts?scope=&container_id=&namespace=&risk_level=&active=&limit=
+//!                                      (registered pacts matching every filter given)
+//! - POST /pacts                        (register a `ubl_pact::Pact`; admin)
+//!   (or have its signers adopt it first: `pact_drafts.rs`; or, naming a
+//!   meta-pact as `parent`, with a `parent_proof`; any session)
+//! - GET  /pacts/:pact_id               (terms, status, governed containers, uses, history)
+//! - POST /pacts/:pact_id/revoke        (`{reason}`; admin)
+//! - POST /pacts/:pact_id/renew         (`{not_after, reason?}`; lifts a revocation; admin)
+//! - POST /pacts/:pact_id/successor     (register a successor pact renewing it; admin)
+//! - GET  /pacts/:pact_id/lineage       (predecessors and successors, oldest first)
+//! - GET  /containers/:id/authority     (authority pact and the mode it leaves the container in)
+//!
+//! A container operating under a pact names it as `authority_pact` in the
+//! manifest on its first link; the pact must be registered by then. From
+//! that link on, the commit pipeline checks the pact ([`admit`]): while it
+//! is pending, expired or revoked the container is held to the membrane's
+//! restricted profile and only Observation links are accepted. Renewing the
+//! pact resumes full operation with no further action on the container.
+//!
+//! The check runs before the append transaction, like policy evaluation:
+//! a commit already past it when the pact is revoked may still land. Ahead
+//! of it, every commit passes the emergency freeze check
+//! (`pact_freeze.rs`), which holds every container to Observation links.
+//!
+//! Renewing in place keeps the pact's terms. To change them (rotate a
+//! signer, raise the threshold), register a successor instead
+//! (SPEC-UBL-PACT v1.0 §9.3): a new pact naming this one as `predecessor`,
+//! whose terms carry forward except those the request changes. A pact has
+//! at most one successor, and it may be renewed after it expired or was
+//! revoked; its containers keep naming it, so auditors follow the lineage
+//! from there.
+//!
+//! A meta-pact (`issues_pacts`) authorizes child pacts instead of links
+//! (SPEC-UBL-PACT v1.0 §9.6): a child names it as `parent` and is
+//! registered by whoever brings a proof under it, signed over the child's
+//! `draft_hash`, that `PactRegistry::check_child` accepts. The parent must
+//! be in force, and each child spends one of its uses. No containers are
+//! placed in namespaces here, so a namespace-scoped meta-pact issues
+//! namespace-scoped children, not container-scoped ones.
+//!
+//! A signer may be named by its UBL ID SID (`ubl:sid:…`) instead of a raw
+//! key, so that rotating the key (`POST /id/agents/:sid/rotate`) does not
+//! leave the pact naming a revoked one. Wherever signatures are checked
+//! (drafts, proofs), [`resolve_signers`] replaces each SID with the SID's
+//! current Ed25519 key; a SID without one cannot sign.
+//!
+//! Pacts live in Postgres (`pact`, `pact_event`; `pact_db.rs`), not in a
+//! process-local `ubl_pact::PactRegistry`: every route reads the current
+//! row, a restart loses nothing, and all instances see a registration,
+//! revocation or renewal as soon as it commits. Commit checks read through
+//! the pact cache (`pact_cache.rs`), which is loaded from those rows on
+//! start and kept in step with them by the `pact_changed` NOTIFY.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::Response,
+    routing::{get, post},
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use tracing::{info, warn};
+use ubl_link::IntentClass;
+use ubl_membrane::ContainerProfile;
+use ubl_pact::{
+    Pact, PactError, PactProof, PactRegistry, PactScope, RiskLevel, SignatureScheme, SignerGroup, TimeWindow,
+};
+
+use crate::auth::rbac;
+use crate::db::LinkDraft;
+use crate::pact_db::{self, ChildRegistration, PactEvent, PactRow, PactStatus};
+use crate::{error_routes, http_cache, id_db, pact_freeze, AppState};
+
+#[derive(Debug, Serialize)]
+pub struct PactView {
+    #[serde(flatten)]
+    pub pact: PactRow,
+    pub status: PactStatus,
+    pub containers: Vec<String>,
+    /// Proofs completed under the pact, against its `max_uses`
+    pub uses: u64,
+    pub events: Vec<PactEvent>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RegisterReq {
+    #[serde(flatten)]
+    pub pact: Pact,
+    /// For a pact naming a `parent`: a proof under the parent over the
+    /// pact's `draft_hash`
+    #[serde(default)]
+    pub parent_proof: Option<PactProof>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RevokeReq {
+    pub reason: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RenewReq {
+    /// New end of the window, unix seconds
+    pub not_after: i64,
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SuccessorReq {
+    /// Id of the successor pact
+    pub pact_id: String,
+    /// Start of its window, unix seconds (default: now)
+    #[serde(default)]
+    pub not_before: Option<i64>,
+    /// End of its window, unix seconds
+    pub not_after: i64,
+    // Terms to change; the rest carry forward from the predecessor
+    #[serde(default)]
+    pub signers: Option<HashSet<String>>,
+    #[serde(default)]
+    pub threshold: Option<usize>,
+    #[serde(default)]
+    pub weights: Option<HashMap<String, usize>>,
+    #[serde(default)]
+    pub groups: Option<Vec<SignerGroup>>,
+    #[serde(default)]
+    pub veto_signers: Option<BTreeSet<String>>,
+    #[serde(default)]
+    pub max_uses: Option<u64>,
+}
+
+impl SuccessorReq {
+    /// The successor's terms: the predecessor's, with this request's changes
+    pub fn terms(&self, predecessor: &Pact, now: i64) -> Pact {
+        let window = TimeWindow {
+            not_before: self.not_before.unwrap_or(now),
+            not_after: self.not_after,
+        };
+        let mut pact = predecessor.renewal(&self.pact_id, window);
+        if let Some(signers) = &self.signers {
+            pact.signers = signers.clone();
+        }
+        if let Some(threshold) = self.threshold {
+            pact.threshold = threshold;
+        }
+        if let Some(weights) = &self.weights {
+            pact.weights = Some(weights.clone());
+        }
+        if let Some(groups) = &self.groups {
+            pact.groups = groups.clone();
+        }
+        if let Some(veto_signers) = &self.veto_signers {
+            pact.veto_signers = veto_signers.clone();
+        }
+        if let Some(max_uses) = self.max_uses {
+            pact.max_uses = Some(max_uses);
+        }
+        pact
+    }
+}
+
+/// A pact with its status, in a lineage or a listing
+#[derive(Debug, Serialize)]
+pub struct PactEntry {
+    #[serde(flatten)]
+    pub pact: PactRow,
+    pub status: PactStatus,
+}
+
+/// Filters of `GET /pacts`, each optional
+#[derive(Debug, Deserialize)]
+pub struct ListQuery {
+    /// `container`, `namespace` or `global`
+    #[serde(default)]
+    pub scope: Option<String>,
+    /// Pacts covering this container: scoped to it, or global
+    #[serde(default)]
+    pub container_id: Option<String>,
+    /// Pacts scoped to this namespace
+    #[serde(default)]
+    pub namespace: Option<String>,
+    /// Pacts authorizing at least this risk level (`L0`..`L5`), i.e. able
+    /// to authorize an intent that requires it
+    #[serde(default)]
+    pub risk_level: Option<String>,
+    /// Only pacts in force now (`true`), or only those not (`false`)
+    #[serde(default)]
+    pub active: Option<bool>,
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+}
+
+fn default_limit() -> i64 {
+    100
+}
+
+/// `PactScope` as serialized in a pact's terms, from its query form
+fn parse_scope(scope: &str) -> Option<PactScope> {
+    match scope {
+        "container" => Some(PactScope::Container),
+        "namespace" => Some(PactScope::Namespace),
+        "global" => Some(PactScope::Global),
+        _ => None,
+    }
+}
+
+/// How a container may operate under its authority pact
+#[derive(Debug, Clone, Serialize)]
+pub struct Authority {
+    pub container_id: String,
+    pub pact_id: String,
+    pub status: PactStatus,
+    /// `full`, or `observation_only` while the pact is not in force
+    pub mode: &'static str,
+}
+
+impl Authority {
+    fn new(container_id: &str, pact_id: String, row: Option<&PactRow>, now: i64) -> Self {
+        let status = PactStatus::of(row, now);
+        Authority {
+            container_id: container_id.to_string(),
+            pact_id,
+            status,
+            mode: if status.in_force() { "full" } else { "observation_only" },
+        }
+    }
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/pacts", get(route_list).post(route_register))
+        .route("/pacts/:pact_id", get(route_get))
+        .route("/pacts/:pact_id/revoke", post(route_revoke))
+        .route("/pacts/:pact_id/renew", post(route_renew))
+        .route("/pacts/:pact_id/successor", post(route_successor))
+        .route("/pacts/:pact_id/lineage", get(route_lineage))
+        .route("/containers/:container_id/authority", get(route_authority))
+}
+
+fn internal(e: sqlx::Error) -> (StatusCode, String) {
+    (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+}
+
+fn not_found(pact_id: &str) -> (StatusCode, String) {
+    (StatusCode::NOT_FOUND, format!("pact {} not registered", pact_id))
+}
+
+fn now() -> i64 {
+    OffsetDateTime::now_utc().unix_timestamp()
+}
+
+/// Terms a pact must have to be registered
+pub fn check_pact(pact: &Pact) -> Result<(), String> {
+    if pact.pact_id.is_empty() {
+        return Err("pact_id is required".to_string());
+    }
+    for (signer, weight) in pact.weights.iter().flatten() {
+        if !pact.signers.contains(signer) {
+            return Err(format!("weight given to {}, who is not a signer", signer));
+        }
+        if *weight == 0 {
+            return Err(format!("signer {} has weight 0", signer));
+        }
+    }
+    if pact.threshold == 0 || pact.threshold > pact.total_weight() {
+        return Err(format!(
+            "threshold {} is not reachable with {} signers of total weight {}",
+            pact.threshold,
+            pact.signers.len(),
+            pact.total_weight()
+        ));
+    }
+    let mut floor = 0;
+    for (risk, threshold) in pact.risk_thresholds.iter().flatten() {
+        if *risk > pact.risk_level {
+            return Err(format!("risk_thresholds names {:?}, above the pact's {:?}", risk, pact.risk_level));
+        }
+        if *threshold == 0 || *threshold > pact.total_weight() {
+            return Err(format!(
+                "{:?} threshold {} is not reachable with {} signers of total weight {}",
+                risk,
+                threshold,
+                pact.signers.len(),
+                pact.total_weight()
+            ));
+        }
+        // A higher risk never asks fewer signatures than a lower one
+        if *threshold < floor || *threshold > pact.threshold {
+            return Err(format!(
+                "{:?} threshold {} is not between the level below's {} and the pact's {}",
+                risk, threshold, floor, pact.threshold
+            ));
+        }
+        floor = *threshold;
+    }
+    let mut names = std::collections::HashSet::new();
+    for group in &pact.groups {
+        if group.name.is_empty() || !names.insert(&group.name) {
+            return Err(format!("group name {:?} is empty or taken", group.name));
+        }
+        if let Some(m) = group.members.iter().find(|m| !pact.signers.contains(*m)) {
+            return Err(format!("group {} lists {}, who is not a signer", group.name, m));
+        }
+        let weight: usize = group.members.iter().map(|m| pact.weight(m)).sum();
+        if group.threshold == 0 || group.threshold > weight {
+            return Err(format!(
+                "group {} threshold {} is not reachable with {} members of total weight {}",
+                group.name,
+                group.threshold,
+                group.members.len(),
+                weight
+            ));
+        }
+    }
+    if pact.window.not_after < pact.window.not_before {
+        return Err("window ends before it starts".to_string());
+    }
+    if pact.max_uses == Some(0) {
+        return Err("max_uses must be at least 1".to_string());
+    }
+    if let Some(key) = &pact.frost_group_key {
+        if key.len() != 64 || !key.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err("frost_group_key must be a 32-byte hex key".to_string());
+        }
+    }
+    if pact.signature_scheme == SignatureScheme::Bls12381
+        && (pact.frost_group_key.is_some() || pact.sid_signers().next().is_some())
+    {
+        return Err("a BLS12-381 pact lists its signers' BLS keys, without SIDs or a FROST group key".to_string());
+    }
+    if pact.scope == PactScope::Container && pact.container_id.is_none() {
+        return Err("a container-scoped pact names its container_id".to_string());
+    }
+    if pact.scope == PactScope::Namespace && pact.namespace.as_deref().unwrap_or("").is_empty() {
+        return Err("a namespace-scoped pact names its namespace".to_string());
+    }
+    if pact.emergency && pact.scope != PactScope::Global {
+        return Err("an emergency pact is Global-scoped".to_string());
+    }
+    Ok(())
+}
+
+/// Whether a registered pact may govern `container_id`: a container-scoped
+/// pact governs its own container only
+pub fn may_govern(row: &PactRow, container_id: &str) -> bool {
+    match serde_json::from_value::<Pact>(row.pact.clone()) {
+        Ok(pact) => pact.scope != PactScope::Container || pact.container_id.as_deref() == Some(container_id),
+        Err(_) => false,
+    }
+}
+
+/// `pact`'s terms with each SID signer resolved to the SID's current
+/// Ed25519 key: the latest not revoked by a rotation
+pub async fn resolve_signers(state: &AppState, pact: &Pact) -> sqlx::Result<Pact> {
+    let mut keys = HashMap::new();
+    for sid in pact.sid_signers() {
+        let current = id_db::get_credentials(&state.pool, sid)
+            .await?
+            .into_iter()
+            .find(|c| c.credential_kind == "ed25519");
+        if let Some(credential) = current {
+            keys.insert(sid.to_string(), hex::encode(credential.public_key));
+        }
+    }
+    Ok(pact.with_signer_keys(|sid| keys.get(sid).cloned()))
+}
+
+/// Lowercase every public key in the pact's terms, as signatures name them
+pub fn normalize_keys(pact: &mut Pact) {
+    pact.signers = pact.signers.iter().map(|k| k.to_ascii_lowercase()).collect();
+    if let Some(weights) = &mut pact.weights {
+        *weights = weights.drain().map(|(k, w)| (k.to_ascii_lowercase(), w)).collect();
+    }
+    for group in &mut pact.groups {
+        group.members = group.members.iter().map(|k| k.to_ascii_lowercase()).collect();
+    }
+    pact.veto_signers = pact.veto_signers.iter().map(|k| k.to_ascii_lowercase()).collect();
+    if let Some(key) = &mut pact.frost_group_key {
+        key.make_ascii_lowercase();
+    }
+}
+
+/// Authority of the draft's container, if it has one: declared by the
+/// manifest on a genesis link, recorded for every later one
+pub async fn authority(state: &AppState, link: &LinkDraft) -> sqlx::Result<Option<Authority>> {
+    let declared = link.manifest.as_ref().and_then(|m| m.authority_pact.clone());
+    let pact_id = match declared {
+        Some(pact_id) => pact_id,
+        None => match state.pacts.authority(&state.pool, &link.container_id).await? {
+            Some(pact_id) => pact_id,
+            None => return Ok(None),
+        },
+    };
+    let row = state.pacts.pact(&state.pool, &pact_id).await?;
+    Ok(Some(Authority::new(&link.container_id, pact_id, row.as_ref(), now())))
+}
+
+/// Commit pipeline: while the commit path is frozen (`pact_freeze.rs`), or
+/// for a container whose authority pact is not in force, Observation links
+/// only (membrane restricted profile)
+pub async fn admit(state: &AppState, link: &LinkDraft) -> Result<Option<Authority>, (StatusCode, String)> {
+    pact_freeze::admit(state, link).await?;
+    let Some(authority) = authority(state, link).await.map_err(internal)? else {
+        return Ok(None);
+    };
+    if authority.status.in_force() {
+        return Ok(Some(authority));
+    }
+    let class: IntentClass = link
+        .intent_class
+        .parse()
+        .map_err(|e| (StatusCode::UNPROCESSABLE_ENTITY, e))?;
+    if let Err(e) = ContainerProfile::Restricted.check(class, 0) {
+        warn!(
+            container_id = %link.container_id,
+            pact_id = %authority.pact_id,
+            status = ?authority.status,
+            decision = "reject",
+            error_code = "authority_lapsed"
+        );
+        return Err((
+            StatusCode::FORBIDDEN,
+            format!(
+                "{}: authority pact {} is {:?}; container {} accepts Observation only",
+                e, authority.pact_id, authority.status, link.container_id
+            ),
+        ));
+    }
+    Ok(Some(authority))
+}
+
+/// GET /pacts
+async fn route_list(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(q): Query<ListQuery>,
+) -> Result<Response, (StatusCode, String)> {
+    rbac::require_role(&state.pool, &headers, &[rbac::ADMIN, rbac::OPERATOR, rbac::AUDITOR]).await?;
+    let scope = match q.scope.as_deref() {
+        Some(s) => Some(parse_scope(s).ok_or_else(|| {
+            (StatusCode::BAD_REQUEST, format!("scope must be container, namespace or global, not {}", s))
+        })?),
+        None => None,
+    };
+    let risk_level = match q.risk_level.as_deref() {
+        Some(r) => Some(
+            serde_json::from_value::<RiskLevel>(serde_json::Value::String(r.to_string()))
+                .map_err(|_| (StatusCode::BAD_REQUEST, format!("risk_level must be L0 to L5, not {}", r)))?,
+        ),
+        None => None,
+    };
+    let filter = pact_db::PactFilter {
+        scope,
+        container_id: q.container_id,
+        namespace: q.namespace,
+        risk_level,
+        active: q.active,
+    };
+    let now = now();
+    let pacts: Vec<PactEntry> = pact_db::list(&state.pool, &filter, now, q.limit.clamp(1, 1000))
+        .await
+        .map_err(internal)?
+        .into_iter()
+        .map(|pact| PactEntry {
+            status: PactStatus::of(Some(&pact), now),
+            pact,
+        })
+        .collect();
+    Ok(http_cache::respond("pacts", &headers, http_cache::REVALIDATE, pacts))
+}
+
+/// POST /pacts
+async fn route_register(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<RegisterReq>,
+) -> Result<(StatusCode, Json<PactRow>), (StatusCode, String)> {
+    let mut pact = req.pact;
+    if pact.parent.is_some() {
+        let caller = rbac::authenticate(&state.pool, &headers).await?;
+        check_pact(&pact).map_err(|e| (StatusCode::UNPROCESSABLE_ENTITY, e))?;
+        normalize_keys(&mut pact);
+        let proof = req.parent_proof.ok_or_else(|| {
+            (StatusCode::UNPROCESSABLE_ENTITY, "a child pact needs a parent_proof".to_string())
+        })?;
+        return register_child(&state, pact, &proof, &caller.session.sid).await;
+    }
+    let caller = rbac::require_role(&state.pool, &headers, &[rbac::ADMIN]).await?;
+    check_pact(&pact).map_err(|e| (StatusCode::UNPROCESSABLE_ENTITY, e))?;
+    normalize_keys(&mut pact);
+    let row = pact_db::insert(&state.pool, &pact, &caller.session.sid)
+        .await
+        .map_err(internal)?
+        .ok_or_else(|| (StatusCode::CONFLICT, format!("pact {} already registered", pact.pact_id)))?;
+    info!("🤝 Pact {} registered by {}", row.pact_id, caller.session.sid);
+    Ok((StatusCode::CREATED, Json(row)))
+}
+
+/// Register `child` on its parent meta-pact's proof, as
+/// `PactRegistry::check_child` allows; registering spends one use of the parent
+async fn register_child(
+    state: &AppState,
+    child: Pact,
+    proof: &PactProof,
+    actor: &str,
+) -> Result<(StatusCode, Json<PactRow>), (StatusCode, String)> {
+    let now = now();
+    let parent_id = child.parent.clone().unwrap_or_default();
+    let row = pact_db::get(&state.pool, &parent_id).await.map_err(internal)?;
+    let status = PactStatus::of(row.as_ref(), now);
+    let Some(row) = row.filter(|_| status.in_force()) else {
+        return Err((StatusCode::CONFLICT, format!("parent pact {} is {:?}", parent_id, status)));
+    };
+    let parent: Pact =
+        serde_json::from_value(row.pact).map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let parent = resolve_signers(state, &parent).await.map_err(internal)?;
+    let rejected = |e: PactError| (error_routes::status(&e), e.to_string());
+    parent
+        .check_uses(pact_db::uses(&state.pool, &parent_id).await.map_err(internal)?)
+        .map_err(rejected)?;
+    let mut registry = PactRegistry::new();
+    registry.register(parent.clone());
+    registry.check_child(&child, proof, now).map_err(rejected)?;
+    let row = match pact_db::insert_child(&state.pool, &child, &parent, actor).await.map_err(internal)? {
+        ChildRegistration::Registered(row) => row,
+        ChildRegistration::Taken => {
+            return Err((StatusCode::CONFLICT, format!("pact {} already registered", child.pact_id)));
+        }
+        ChildRegistration::UsesExhausted => {
+            let max = parent.max_uses.unwrap_or_default();
+            return Err(rejected(PactError::UsesExhausted { used: max, max }));
+        }
+    };
+    info!("🤝 Pact {} registered under meta-pact {} by {}", row.pact_id, parent_id, actor);
+    Ok((StatusCode::CREATED, Json(row)))
+}
+
+/// GET /pacts/:pact_id
+async fn route_get(
+    State(state): State<AppState>,
+    Path(pact_id): Path<String>,
+    headers: HeaderMap,
+) -> Result<Response, (StatusCode, String)> {
+    rbac::require_role(&state.pool, &headers, &[rbac::ADMIN, rbac::OPERATOR, rbac::AUDITOR]).await?;
+    let pact = pact_db::get(&state.pool, &pact_id)
+        .await
+        .map_err(internal)?
+        .ok_or_else(|| not_found(&pact_id))?;
+    let view = PactView {
+        status: PactStatus::of(Some(&pact), now()),
+        containers: pact_db::containers(&state.pool, &pact_id).await.map_err(internal)?,
+        uses: pact_db::uses(&state.pool, &pact_id).await.map_err(internal)?,
+        events: pact_db::events(&state.pool, &pact_id).await.map_err(internal)?,
+        pact,
+    };
+    Ok(http_cache::respond("pacts", &headers, http_cache::REVALIDATE, view))
+}
+
+/// POST /pacts/:pact_id/revoke
+async fn route_revoke(
+    State(state): State<AppState>,
+    Path(pact_id): Path<String>,
+    headers: HeaderMap,
+    Json(req): Json<RevokeReq>,
+) -> Result<Json<PactRow>, (StatusCode, String)> {
+    let caller = rbac::require_role(&state.pool, &headers, &[rbac::ADMIN]).await?;
+    if req.reason.trim().is_empty() {
+        return Err((StatusCode::UNPROCESSABLE_ENTITY, "a revocation needs a reason".to_string()));
+    }
+    match pact_db::revoke(&state.pool, &pact_id, &req.reason, &caller.session.sid)
+        .await
+        .map_err(internal)?
+    {
+        Some(row) => {
+            warn!(pact_id = %pact_id, actor = %caller.session.sid, reason = %req.reason, "🚫 Pact revoked");
+            Ok(Json(row))
+        }
+        None => match pact_db::get(&state.pool, &pact_id).await.map_err(internal)? {
+            Some(_) => Err((StatusCode::CONFLICT, format!("pact {} is already revoked", pact_id))),
+            None => Err(not_found(&pact_id)),
+        },
+    }
+}
+
+/// POST /pacts/:pact_id/renew
+async fn route_renew(
+    State(state): State<AppState>,
+    Path(pact_id): Path<String>,
+    headers: HeaderMap,
+    Json(req): Json<RenewReq>,
+) -> Result<Json<PactRow>, (StatusCode, String)> {
+    let caller = rbac::require_role(&state.pool, &headers, &[rbac::ADMIN]).await?;
+    let pact = pact_db::get(&state.pool, &pact_id)
+        .await
+        .map_err(internal)?
+        .ok_or_else(|| not_found(&pact_id))?;
+    if req.not_after <= now() || req.not_after < pact.not_before {
+        return Err((
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "not_after must be in the future and after not_before".to_string(),
+        ));
+    }
+    let row = pact_db::renew(&state.pool, &pact_id, req.not_after, req.reason.as_deref(), &caller.session.sid)
+        .await
+        .map_err(internal)?
+        .ok_or_else(|| not_found(&pact_id))?;
+    info!("🤝 Pact {} renewed until {} by {}", pact_id, row.not_after, caller.session.sid);
+    Ok(Json(row))
+}
+
+/// POST /pacts/:pact_id/successor
+async fn route_successor(
+    State(state): State<AppState>,
+    Path(pact_id): Path<String>,
+    headers: HeaderMap,
+    Json(req): Json<SuccessorReq>,
+) -> Result<(StatusCode, Json<PactRow>), (StatusCode, String)> {
+    let caller = rbac::require_role(&state.pool, &headers, &[rbac::ADMIN]).await?;
+    let row = pact_db::get(&state.pool, &pact_id)
+        .await
+        .map_err(internal)?
+        .ok_or_else(|| not_found(&pact_id))?;
+    if req.pact_id == pact_id {
+        return Err((StatusCode::UNPROCESSABLE_ENTITY, "a pact cannot succeed itself".to_string()));
+    }
+    if let Some(existing) = pact_db::successor(&state.pool, &pact_id).await.map_err(internal)? {
+        return Err((
+            StatusCode::CONFLICT,
+            format!("pact {} is already renewed by {}", pact_id, existing.pact_id),
+        ));
+    }
+    let predecessor: Pact =
+        serde_json::from_value(row.pact).map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let mut pact = req.terms(&predecessor, now());
+    check_pact(&pact).map_err(|e| (StatusCode::UNPROCESSABLE_ENTITY, e))?;
+    if pact.window.not_after <= now() {
+        return Err((StatusCode::UNPROCESSABLE_ENTITY, "the successor's window has already ended".to_string()));
+    }
+    normalize_keys(&mut pact);
+    let row = pact_db::insert(&state.pool, &pact, &caller.session.sid)
+        .await
+        .map_err(internal)?
+        .ok_or_else(|| {
+            (
+                StatusCode::CONFLICT,
+                format!("pact {} is already registered, or {} was renewed meanwhile", pact.pact_id, pact_id),
+            )
+        })?;
+    info!("🤝 Pact {} renewed by successor {} by {}", pact_id, row.pact_id, caller.session.sid);
+    Ok((StatusCode::CREATED, Json(row)))
+}
+
+/// GET /pacts/:pact_id/lineage
+async fn route_lineage(
+    State(state): State<AppState>,
+    Path(pact_id): Path<String>,
+    headers: HeaderMap,
+) -> Result<Response, (StatusCode, String)> {
+    rbac::require_role(&state.pool, &headers, &[rbac::ADMIN, rbac::OPERATOR, rbac::AUDITOR]).await?;
+    let now = now();
+    let lineage: Vec<PactEntry> = pact_db::lineage(&state.pool, &pact_id)
+        .await
+        .map_err(internal)?
+        .into_iter()
+        .map(|pact| PactEntry {
+            status: PactStatus::of(Some(&pact), now),
+            pact,
+        })
+        .collect();
+    if lineage.is_empty() {
+        return Err(not_found(&pact_id));
+    }
+    Ok(http_cache::respond("pacts", &headers, http_cache::REVALIDATE, lineage))
+}
+
+/// GET /containers/:id/authority
+async fn route_authority(
+    State(state): State<AppState>,
+    Path(container_id): Path<String>,
+    headers: HeaderMap,
+) -> Result<Response, (StatusCode, String)> {
+    rbac::require_role(&state.pool, &headers, &[rbac::ADMIN, rbac::OPERATOR, rbac::AUDITOR]).await?;
+    let pact_id = pact_db::authority(&state.pool, &container_id)
+        .await
+        .map_err(internal)?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, format!("container {} has no authority pact", container_id)))?;
+    let row = pact_db::get(&state.pool, &pact_id).await.map_err(internal)?;
+    let authority = Authority::new(&container_id, pact_id, row.as_ref(), now());
+    Ok(http_cache::respond("authority", &headers, http_cache::REVALIDATE, authority))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ubl_pact::{RiskLevel, SignerGroup, TimeWindow};
+
+    fn pact(threshold: usize) -> Pact {
+        Pact {
+            pact_id: "pact_fund".to_string(),
+            scope: PactScope::Container,
+            threshold,
+            signers: ["aa", "bb"].iter().map(|s| s.to_string()).collect(),
+            window: TimeWindow {
+                not_before: 0,
+                not_after: 100,
+            },
+            risk_level: RiskLevel::L3,
+            container_id: Some("C.Fund".to_string()),
+            ..ubl_fixtures::pact()
+        }
+    }
+
+    #[test]
+    fn test_check_pact_terms() {
+        assert!(check_pact(&pact(2)).is_ok());
+        assert!(check_pact(&pact(0)).is_err());
+        assert!(check_pact(&pact(3)).is_err());
+        let unscoped = Pact {
+            container_id: None,
+            ..pact(1)
+        };
+        assert!(check_pact(&unscoped).unwrap_err().contains("container_id"));
+        let namespaced = |namespace: Option<&str>| Pact {
+            scope: PactScope::Namespace,
+            namespace: namespace.map(str::to_string),
+            ..pact(1)
+        };
+        assert!(check_pact(&namespaced(Some("acme"))).is_ok());
+        assert!(check_pact(&namespaced(None)).unwrap_err().contains("namespace"));
+        let emergency = |scope| Pact {
+            scope,
+            emergency: true,
+            ..pact(1)
+        };
+        assert!(check_pact(&emergency(PactScope::Global)).is_ok());
+        assert!(check_pact(&emergency(PactScope::Container)).unwrap_err().contains("Global"));
+        let single_use = |max_uses| Pact { max_uses, ..pact(1) };
+        assert!(check_pact(&single_use(Some(1))).is_ok());
+        assert!(check_pact(&single_use(Some(0))).unwrap_err().contains("max_uses"));
+        let threshold_keyed = |key: &str| Pact {
+            frost_group_key: Some(key.to_string()),
+            ..pact(2)
+        };
+        assert!(check_pact(&threshold_keyed(&"AB".repeat(32))).is_ok());
+        assert!(check_pact(&threshold_keyed("abcd")).unwrap_err().contains("frost_group_key"));
+        let bls = Pact {
+            signature_scheme: SignatureScheme::Bls12381,
+            ..pact(2)
+        };
+        assert!(check_pact(&bls).is_ok());
+        let bls_keyed = Pact {
+            frost_group_key: Some("ab".repeat(32)),
+            ..bls
+        };
+        assert!(check_pact(&bls_keyed).unwrap_err().contains("BLS12-381"));
+
+        let weighted = |weights: &[(&str, usize)]| Pact {
+            weights: Some(weights.iter().map(|(s, w)| (s.to_string(), *w)).collect()),
+            ..pact(3)
+        };
+        assert!(check_pact(&weighted(&[("aa", 2)])).is_ok());
+        assert!(check_pact(&weighted(&[("aa", 2), ("bb", 0)])).is_err());
+        assert!(check_pact(&weighted(&[("cc", 5)])).unwrap_err().contains("not a signer"));
+
+        let grouped = |groups: &[(&str, &[&str], usize)]| Pact {
+            groups: groups
+                .iter()
+                .map(|(name, members, threshold)| SignerGroup {
+                    name: name.to_string(),
+                    members: members.iter().map(|m| m.to_string()).collect(),
+                    threshold: *threshold,
+                })
+                .collect(),
+            ..pact(2)
+        };
+        assert!(check_pact(&grouped(&[("admins", &["aa"], 1), ("auditors", &["bb"], 1)])).is_ok());
+        assert!(check_pact(&grouped(&[("admins", &["aa"], 2)])).unwrap_err().contains("not reachable"));
+        assert!(check_pact(&grouped(&[("admins", &["cc"], 1)])).unwrap_err().contains("not a signer"));
+        assert!(check_pact(&grouped(&[("admins", &["aa"], 1), ("admins", &["bb"], 1)])).is_err());
+
+        let by_risk = |thresholds: &[(RiskLevel, usize)]| Pact {
+            risk_thresholds: Some(thresholds.iter().copied().collect()),
+            ..pact(2)
+        };
+        assert!(check_pact(&by_risk(&[(RiskLevel::L1, 1), (RiskLevel::L3, 2)])).is_ok());
+        assert!(check_pact(&by_risk(&[(RiskLevel::L2, 3)])).unwrap_err().contains("not reachable"));
+        assert!(check_pact(&by_risk(&[(RiskLevel::L4, 1)])).unwrap_err().contains("above"));
+        assert!(check_pact(&by_risk(&[(RiskLevel::L1, 2), (RiskLevel::L3, 1)])).unwrap_err().contains("between"));
+        assert!(check_pact(&by_risk(&[(RiskLevel::L1, 0)])).unwrap_err().contains("not reachable"));
+    }
+
+    #[test]
+    fn test_list_filters_match_stored_terms() {
+        // The listing compares against the terms as stored in `pact.pact`
+        let terms = serde_json::to_value(pact(2)).unwrap();
+        assert_eq!(terms["scope"], serde_json::to_value(parse_scope("container").unwrap()).unwrap());
+        assert_eq!(parse_scope("Container"), None);
+        let levels = [RiskLevel::L0, RiskLevel::L1, RiskLevel::L2, RiskLevel::L3, RiskLevel::L4, RiskLevel::L5];
+        let stored: Vec<String> = levels.iter().map(|l| serde_json::to_value(l).unwrap().to_string()).collect();
+        assert!(stored.windows(2).all(|w| w[0] < w[1]), "risk levels must order as text: {:?}", stored);
+    }
+
+    #[test]
+    fn test_successor_carries_terms_forward() {
+        let req: SuccessorReq = serde_json::from_value(serde_json::json!({
+            "pact_id": "pact_fund_2",
+            "not_after": 200,
+            "signers": ["aa", "cc"],
+        }))
+        .unwrap();
+        let successor = req.terms(&pact(2), 150);
+        assert_eq!(successor.predecessor.as_deref(), Some("pact_fund"));
+        assert_eq!((successor.window.not_before, successor.window.not_after), (150, 200));
+        assert_eq!((successor.threshold, successor.container_id), (2, Some("C.Fund".to_string())));
+        assert!(successor.signers.contains("cc") && !successor.signers.contains("bb"));
+        assert!(check_pact(&successor).is_ok());
+    }
+
+    #[test]
+    fn test_container_scoped_pact_governs_its_container() {
+        let row = |pact: Pact| PactRow {
+            pact_id: pact.pact_id.clone(),
+            pact: serde_json::to_value(&pact).unwrap(),
+            not_before: 0,
+            not_after: 100,
+            revoked_at: None,
+            revoked_by: None,
+            revoke_reason: None,
+            registered_by: None,
+            registered_at: OffsetDateTime::UNIX_EPOCH,
+        };
+        assert!(may_govern(&row(pact(2)), "C.Fund"));
+        assert!(!may_govern(&row(pact(2)), "C.Other"));
+        let global = Pact {
+            scope: PactScope::Global,
+            container_id: None,
+            ..pact(2)
+        };
+        assert!(may_govern(&row(global), "C.Other"));
+    }
+
+    #[test]
+    fn test_lapsed_authority_is_observation_only() {
+        let row = PactRow {
+            pact_id: "pact_fund".to_string(),
+            pact: serde_json::Value::Null,
+            not_before: 0,
+            not_after: 100,
+            revoked_at: None,
+            revoked_by: None,
+            revoke_reason: None,
+            registered_by: None,
+            registered_at: OffsetDateTime::UNIX_EPOCH,
+        };
+        let live = Authority::new("C.Fund", "pact_fund".to_string(), Some(&row), 50);
+        assert_eq!((live.status, live.mode), (PactStatus::Active, "full"));
+        let lapsed = Authority::new("C.Fund", "pact_fund".to_string(), Some(&row), 101);
+        assert_eq!((lapsed.status, lapsed.mode), (PactStatus::Expired, "observation_only"));
+    }
+}
@@ -0,0 +1,85 @@
+//! # Commit pipeline breadcrumbs
+//!
+//! Ordered record of the stages a `/link/commit` went through, with outcome
+//! and duration. Always collected (it is a handful of `Instant`s); only
+//! returned to callers that ask for `?debug=true` and hold an operator role.
+
+use serde::Serialize;
+use std::time::Instant;
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum StageOutcome {
+    Pass,
+    Fail,
+    Skip,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StageRecord {
+    pub stage: &'static str,
+    pub outcome: StageOutcome,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+    pub duration_us: u64,
+}
+
+#[derive(Debug, Default)]
+pub struct PipelineTrace {
+    stages: Vec<StageRecord>,
+}
+
+impl PipelineTrace {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn push(&mut self, stage: &'static str, outcome: StageOutcome, started: Option<Instant>, detail: Option<String>) {
+        let duration_us = started.map(|t| t.elapsed().as_micros() as u64).unwrap_or(0);
+        self.stages.push(StageRecord { stage, outcome, detail, duration_us });
+    }
+
+    /// Stage ran and passed
+    pub fn pass(&mut self, stage: &'static str, started: Instant) {
+        self.push(stage, StageOutcome::Pass, Some(started), None);
+    }
+
+    /// Stage ran and rejected the commit
+    pub fn fail(&mut self, stage: &'static str, started: Instant, detail: impl Into<String>) {
+        self.push(stage, StageOutcome::Fail, Some(started), Some(detail.into()));
+    }
+
+    /// Stage was not executed for this commit
+    pub fn skip(&mut self, stage: &'static str, detail: impl Into<String>) {
+        self.push(stage, StageOutcome::Skip, None, Some(detail.into()));
+    }
+
+    pub fn into_stages(self) -> Vec<StageRecord> {
+        self.stages
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trace_keeps_order() {
+        let mut trace = PipelineTrace::new();
+        trace.skip("asc_validation", "no ASC provided");
+        trace.pass("v1_version", Instant::now());
+        trace.fail("v5_sequence", Instant::now(), "SequenceMismatch");
+
+        let records = trace.into_stages();
+        let stages: Vec<_> = records.iter().map(|s| (s.stage, s.outcome)).collect();
+        assert_eq!(
+            stages,
+            vec![
+                ("asc_validation", StageOutcome::Skip),
+                ("v1_version", StageOutcome::Pass),
+                ("v5_sequence", StageOutcome::Fail),
+            ]
+        );
+        assert_eq!(records[2].detail.as_deref(), Some("SequenceMismatch"));
+    }
+}
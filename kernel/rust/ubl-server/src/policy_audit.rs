@@ -0,0 +1,227 @@
+//! # Policy decision audit links
+//!
+//! With `UBL_POLICY_AUDIT_CONTAINER` set, every policy decision taken on
+//! `POST /link/commit` is written to that container as an Observation link
+//! (delta 0, server-authored, empty key and signature). The link's atom is
+//! kept in the entry's `metadata.policy_audit`:
+//!
+//! ```json
+//! {"type": "policy_decision", "container_id": "...", "policy_id": "...",
+//!  "version": "...", "decision": "allow"|"deny", "deny_code": ..., "intent_class": 1,
+//!  "constraints_hash": "...", "outcome": "committed"|"rejected", "reason": ...,
+//!  "sequence": 7, "entry_hash": "..."}
+//! ```
+//!
+//! `constraints_hash` is the atom hash of the canonical constraint list an
+//! Allow bound the commit to. A committed decision's link is appended in the
+//! commit's transaction, after the entry it names (`sequence`,
+//! `entry_hash`), so the two land together or not at all. A rejected one
+//! (a Deny, or an Allow whose constraints the commit broke) is appended on
+//! its own; a failure to write it is logged and the rejection still answered.
+//!
+//! Audited commits all take the audit container's head lock last, so they
+//! serialize on it. Only the server appends to the audit container; drafts
+//! addressed to it are refused. Settlements and approved Evolution
+//! proposals are not audited here.
+
+use serde_json::json;
+use sqlx::{Postgres, Transaction};
+use tracing::error;
+use ubl_policy_vm::{Constraint, DenyCode, TranslationDecision};
+
+use crate::db::{self, LedgerEntry, LinkDraft, TangencyError};
+use crate::pipeline::PipelineTrace;
+use crate::AppState;
+
+/// `UBL_POLICY_AUDIT_CONTAINER`; unset or empty turns auditing off
+pub fn container_from_env() -> Option<String> {
+    std::env::var("UBL_POLICY_AUDIT_CONTAINER")
+        .ok()
+        .map(|c| c.trim().to_string())
+        .filter(|c| !c.is_empty())
+}
+
+/// One policy decision on a draft
+#[derive(Debug, Clone)]
+pub struct DecisionRecord {
+    pub container_id: String,
+    pub actor: String,
+    pub policy_id: Option<String>,
+    pub version: Option<String>,
+    pub decision: TranslationDecision,
+    /// Why the commit was refused despite an Allow
+    pub rejected: Option<String>,
+}
+
+impl DecisionRecord {
+    fn deny_code(&self) -> Option<DenyCode> {
+        match &self.decision {
+            TranslationDecision::Deny { code, .. } => Some(*code),
+            TranslationDecision::Allow { .. } => None,
+        }
+    }
+
+    /// The audit link's atom; `entry` is the commit the decision let through
+    pub fn atom(&self, entry: Option<&LedgerEntry>) -> serde_json::Value {
+        let mut atom = json!({
+            "type": "policy_decision",
+            "container_id": self.container_id,
+            "actor": self.actor,
+            "policy_id": self.policy_id,
+            "version": self.version,
+        });
+        match &self.decision {
+            TranslationDecision::Allow { intent_class, constraints, .. } => {
+                atom["decision"] = json!("allow");
+                atom["intent_class"] = json!(intent_class);
+                atom["constraints_hash"] = json!(constraints_hash(constraints));
+            }
+            TranslationDecision::Deny { .. } => {
+                atom["decision"] = json!("deny");
+                atom["deny_code"] = json!(self.deny_code());
+            }
+        }
+        match entry {
+            Some(e) => {
+                atom["outcome"] = json!("committed");
+                atom["sequence"] = json!(e.sequence);
+                atom["entry_hash"] = json!(e.entry_hash);
+            }
+            None => {
+                atom["outcome"] = json!("rejected");
+                atom["reason"] = json!(self.rejected);
+            }
+        }
+        atom
+    }
+}
+
+/// Atom hash of the canonical constraint list
+pub fn constraints_hash(constraints: &[Constraint]) -> String {
+    let value = serde_json::to_value(constraints).expect("constraints serialize");
+    let canonical = ubl_atom::canonicalize(&value).expect("constraints canonicalize");
+    ubl_kernel::hash_atom(&canonical)
+}
+
+/// Drafts may not address the audit container
+pub fn check_target(state: &AppState, link: &LinkDraft) -> Result<(), String> {
+    match &state.policy_audit {
+        Some(audit) if *audit == link.container_id => Err(format!(
+            "container {} is the policy audit trail; only the server appends to it",
+            audit
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Append the audit link for `record` inside `tx`, after the entry it names
+pub async fn append_in(
+    tx: &mut Transaction<'static, Postgres>,
+    audit_container: &str,
+    record: &DecisionRecord,
+    entry: Option<&LedgerEntry>,
+    trace: &mut PipelineTrace,
+) -> Result<LedgerEntry, TangencyError> {
+    let atom = record.atom(entry);
+    let canonical = ubl_atom::canonicalize(&atom).expect("audit atom canonicalizes");
+    let (expected_sequence, previous_hash) = db::next_in(tx, audit_container).await.expect("audit head");
+    let link = LinkDraft {
+        version: 1,
+        container_id: audit_container.to_string(),
+        expected_sequence,
+        previous_hash,
+        atom_hash: ubl_kernel::hash_atom(&canonical),
+        intent_class: "Observation".to_string(),
+        physics_delta: "0".to_string(),
+        author_pubkey: String::new(),
+        signature: String::new(),
+        affects: Vec::new(),
+        policy_id: None,
+        intent: None,
+        manifest: None,
+        metadata: Some(json!({ "policy_audit": atom })),
+    };
+    db::append_in(tx, &link, trace).await
+}
+
+/// Write a rejected decision's audit link in its own transaction
+pub async fn record_rejection(state: &AppState, record: &DecisionRecord) {
+    let Some(audit) = &state.policy_audit else {
+        return;
+    };
+    let mut trace = PipelineTrace::new();
+    let result = async {
+        let mut tx = state.ledger.begin(&mut trace).await?;
+        let entry = append_in(&mut tx, audit, record, None, &mut trace).await?;
+        db::commit(tx).await?;
+        Ok::<_, TangencyError>(entry)
+    }
+    .await;
+    match result {
+        Ok(entry) => state.bus.committed(&entry),
+        Err(e) => {
+            error!(
+                container_id = %record.container_id,
+                audit_container = %audit,
+                "policy audit link for a rejection not written: {:?}",
+                e
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(decision: TranslationDecision) -> DecisionRecord {
+        DecisionRecord {
+            container_id: "C.Wallet".to_string(),
+            actor: "aa".to_string(),
+            policy_id: Some("limits".to_string()),
+            version: Some("3".to_string()),
+            decision,
+            rejected: None,
+        }
+    }
+
+    fn allow(constraints: Vec<Constraint>) -> TranslationDecision {
+        TranslationDecision::Allow {
+            intent_class: 1,
+            required_pact: None,
+            constraints,
+        }
+    }
+
+    #[test]
+    fn test_committed_allow_names_entry_and_constraints() {
+        let max = Constraint {
+            kind: "max_delta".to_string(),
+            value: "100".to_string(),
+        };
+        let r = record(allow(vec![max.clone()]));
+        let entry = LedgerEntry {
+            container_id: "C.Wallet".to_string(),
+            sequence: 7,
+            link_hash: String::new(),
+            previous_hash: String::new(),
+            entry_hash: "ee".to_string(),
+            ts_unix_ms: 0,
+        };
+        let atom = r.atom(Some(&entry));
+        assert_eq!(atom["decision"], "allow");
+        assert_eq!(atom["outcome"], "committed");
+        assert_eq!(atom["sequence"], 7);
+        assert_eq!(atom["constraints_hash"], constraints_hash(&[max]));
+        assert_ne!(atom["constraints_hash"], constraints_hash(&[]));
+    }
+
+    #[test]
+    fn test_rejected_deny_carries_code() {
+        let atom = record(TranslationDecision::deny(DenyCode::AmountExceeded, "over")).atom(None);
+        assert_eq!(atom["decision"], "deny");
+        assert_eq!(atom["deny_code"], "amount_exceeded");
+        assert_eq!(atom["outcome"], "rejected");
+        assert!(atom.get("entry_hash").is_none());
+    }
+}
@@ -0,0 +1,373 @@
+//! Policy persistence (table `policy`, sql/021_policy.sql, 023_policy_versions.sql,
+//! 029_policy_signatures.sql, 035_policy_timelock.sql) with its vetoes (`policy_veto`)
+//! and container attachments (`container_policy`, `container_composition`, sql/028_container_admin.sql),
+//! namespace bindings (`namespace_policy`, sql/033_namespace_policy.sql)
+//! and rollouts (`policy_rollout`, sql/044_policy_rollout.sql)
+
+use sqlx::PgPool;
+use ubl_policy_vm::{BundleSignature, CompositionMode, GovernanceKeys, Policy, PolicyBundle, PolicyVM, Rollout};
+
+/// Insert a policy version with its governance signatures and activation risk level.
+/// Returns false if (policy_id, version) already exists.
+pub async fn insert(pool: &PgPool, p: &Policy, signatures: &[BundleSignature], risk_level: i16) -> sqlx::Result<bool> {
+    let r = sqlx::query!(
+        r#"INSERT INTO policy (policy_id, version, bytecode_hash, bytecode, description, active_from, signatures, risk_level)
+           VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+           ON CONFLICT (policy_id, version) DO NOTHING"#,
+        p.policy_id,
+        p.version,
+        p.bytecode_hash,
+        p.bytecode,
+        p.description,
+        p.active_from,
+        serde_json::json!(signatures),
+        risk_level
+    )
+    .execute(pool)
+    .await?;
+    Ok(r.rows_affected() > 0)
+}
+
+/// All versions of a policy, oldest activation first
+pub async fn versions(pool: &PgPool, policy_id: &str) -> sqlx::Result<Vec<Policy>> {
+    let rows = sqlx::query!(
+        r#"SELECT policy_id, version, bytecode_hash, bytecode, description, active_from
+           FROM policy WHERE policy_id = $1 ORDER BY active_from"#,
+        policy_id
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|x| Policy {
+            policy_id: x.policy_id,
+            version: x.version,
+            bytecode_hash: x.bytecode_hash,
+            bytecode: x.bytecode,
+            description: x.description,
+            active_from: x.active_from,
+        })
+        .collect())
+}
+
+/// Delete every version of a policy. Returns the number of versions removed.
+pub async fn delete(pool: &PgPool, policy_id: &str) -> sqlx::Result<u64> {
+    let r = sqlx::query!("DELETE FROM policy WHERE policy_id = $1", policy_id)
+        .execute(pool)
+        .await?;
+    Ok(r.rows_affected())
+}
+
+/// Delete one version of a policy
+pub async fn delete_version(pool: &PgPool, policy_id: &str, version: &str) -> sqlx::Result<u64> {
+    let r = sqlx::query!(
+        "DELETE FROM policy WHERE policy_id = $1 AND version = $2",
+        policy_id,
+        version
+    )
+    .execute(pool)
+    .await?;
+    Ok(r.rows_affected())
+}
+
+/// A version with its activation risk and time
+#[derive(Debug, Clone)]
+pub struct PendingActivation {
+    pub policy_id: String,
+    pub version: String,
+    pub bytecode_hash: String,
+    pub risk_level: i16,
+    /// Unix seconds at which the version takes effect; vetoes close then
+    pub active_from: i64,
+}
+
+/// Risk level of the version governing `policy_id` at `now`, if any
+pub async fn active_risk(pool: &PgPool, policy_id: &str, now: i64) -> sqlx::Result<Option<i16>> {
+    sqlx::query_scalar!(
+        r#"SELECT risk_level FROM policy WHERE policy_id = $1 AND active_from <= $2
+           ORDER BY active_from DESC LIMIT 1"#,
+        policy_id,
+        now
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+/// Every version that takes effect after `now`, soonest first
+pub async fn pending(pool: &PgPool, now: i64) -> sqlx::Result<Vec<PendingActivation>> {
+    sqlx::query_as!(
+        PendingActivation,
+        r#"SELECT policy_id, version, bytecode_hash, risk_level, active_from
+           FROM policy WHERE active_from > $1 ORDER BY active_from, policy_id"#,
+        now
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// One version, with its activation risk and time
+pub async fn activation(pool: &PgPool, policy_id: &str, version: &str) -> sqlx::Result<Option<PendingActivation>> {
+    sqlx::query_as!(
+        PendingActivation,
+        r#"SELECT policy_id, version, bytecode_hash, risk_level, active_from
+           FROM policy WHERE policy_id = $1 AND version = $2"#,
+        policy_id,
+        version
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+/// Cancel a pending version: record the veto and delete the row in one transaction.
+/// Returns false if the version is gone or took effect before `now`.
+pub async fn veto(
+    pool: &PgPool,
+    a: &PendingActivation,
+    now: i64,
+    pact_id: &str,
+    signatures: &serde_json::Value,
+    vetoed_by: &str,
+) -> sqlx::Result<bool> {
+    let mut tx = pool.begin().await?;
+    let r = sqlx::query!(
+        "DELETE FROM policy WHERE policy_id = $1 AND version = $2 AND active_from > $3",
+        a.policy_id,
+        a.version,
+        now
+    )
+    .execute(&mut *tx)
+    .await?;
+    if r.rows_affected() == 0 {
+        return Ok(false);
+    }
+    sqlx::query!(
+        r#"INSERT INTO policy_veto (policy_id, version, bytecode_hash, risk_level, active_from, pact_id, signatures, vetoed_by)
+           VALUES ($1, $2, $3, $4, $5, $6, $7, $8)"#,
+        a.policy_id,
+        a.version,
+        a.bytecode_hash,
+        a.risk_level,
+        a.active_from,
+        pact_id,
+        signatures,
+        vetoed_by
+    )
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    Ok(true)
+}
+
+/// Every stored version as a bundle with its signatures
+pub async fn list(pool: &PgPool) -> sqlx::Result<Vec<PolicyBundle>> {
+    let rows = sqlx::query!(
+        r#"SELECT policy_id, version, bytecode_hash, bytecode, description, active_from, signatures
+           FROM policy ORDER BY policy_id, active_from"#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|x| PolicyBundle {
+            // Malformed signature arrays verify as unsigned
+            signatures: serde_json::from_value(x.signatures).unwrap_or_default(),
+            ..PolicyBundle::new(Policy {
+                policy_id: x.policy_id,
+                version: x.version,
+                bytecode_hash: x.bytecode_hash,
+                bytecode: x.bytecode,
+                description: x.description,
+                active_from: x.active_from,
+            })
+        })
+        .collect())
+}
+
+/// Attach a policy to a container. Returns false if it was already attached.
+pub async fn attach(pool: &PgPool, container_id: &str, policy_id: &str, attached_by: &str) -> sqlx::Result<bool> {
+    let r = sqlx::query!(
+        r#"INSERT INTO container_policy (container_id, policy_id, attached_by)
+           VALUES ($1, $2, $3)
+           ON CONFLICT (container_id, policy_id) DO NOTHING"#,
+        container_id,
+        policy_id,
+        attached_by
+    )
+    .execute(pool)
+    .await?;
+    Ok(r.rows_affected() > 0)
+}
+
+/// Detach a policy from a container. Returns false if it was not attached.
+pub async fn detach(pool: &PgPool, container_id: &str, policy_id: &str) -> sqlx::Result<bool> {
+    let r = sqlx::query!(
+        "DELETE FROM container_policy WHERE container_id = $1 AND policy_id = $2",
+        container_id,
+        policy_id
+    )
+    .execute(pool)
+    .await?;
+    Ok(r.rows_affected() > 0)
+}
+
+/// Bind a policy to a namespace. Returns false if it was already bound.
+pub async fn bind_namespace(pool: &PgPool, namespace: &str, policy_id: &str, attached_by: &str) -> sqlx::Result<bool> {
+    let r = sqlx::query!(
+        r#"INSERT INTO namespace_policy (namespace, policy_id, attached_by)
+           VALUES ($1, $2, $3)
+           ON CONFLICT (namespace, policy_id) DO NOTHING"#,
+        namespace,
+        policy_id,
+        attached_by
+    )
+    .execute(pool)
+    .await?;
+    Ok(r.rows_affected() > 0)
+}
+
+/// Remove a namespace binding. Returns false if it was not bound.
+pub async fn unbind_namespace(pool: &PgPool, namespace: &str, policy_id: &str) -> sqlx::Result<bool> {
+    let r = sqlx::query!(
+        "DELETE FROM namespace_policy WHERE namespace = $1 AND policy_id = $2",
+        namespace,
+        policy_id
+    )
+    .execute(pool)
+    .await?;
+    Ok(r.rows_affected() > 0)
+}
+
+pub async fn set_composition(
+    pool: &PgPool,
+    container_id: &str,
+    mode: CompositionMode,
+    updated_by: &str,
+) -> sqlx::Result<()> {
+    sqlx::query!(
+        r#"INSERT INTO container_composition (container_id, mode, updated_by)
+           VALUES ($1, $2, $3)
+           ON CONFLICT (container_id) DO UPDATE SET mode = $2, updated_by = $3, updated_at = now()"#,
+        container_id,
+        composition_str(mode),
+        updated_by
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+fn composition_str(mode: CompositionMode) -> &'static str {
+    match mode {
+        CompositionMode::AllMustAllow => "all_must_allow",
+        CompositionMode::MostRestrictiveWins => "most_restrictive_wins",
+    }
+}
+
+fn parse_composition(s: &str) -> CompositionMode {
+    match s {
+        "most_restrictive_wins" => CompositionMode::MostRestrictiveWins,
+        _ => CompositionMode::AllMustAllow,
+    }
+}
+
+/// Every stored rollout. Rows that no longer parse are skipped.
+pub async fn rollouts(pool: &PgPool) -> sqlx::Result<Vec<Rollout>> {
+    let rows = sqlx::query!("SELECT policy_id, version, rollout FROM policy_rollout ORDER BY policy_id, version")
+        .fetch_all(pool)
+        .await?;
+    Ok(rows
+        .into_iter()
+        .filter_map(|r| match serde_json::from_value(r.rollout) {
+            Ok(rollout) => Some(rollout),
+            Err(e) => {
+                tracing::error!(policy_id = %r.policy_id, version = %r.version, error = %e, "❌ unreadable rollout");
+                None
+            }
+        })
+        .collect())
+}
+
+/// Rollout of one version, if it has one
+pub async fn rollout(pool: &PgPool, policy_id: &str, version: &str) -> sqlx::Result<Option<Rollout>> {
+    let row = sqlx::query_scalar!(
+        "SELECT rollout FROM policy_rollout WHERE policy_id = $1 AND version = $2",
+        policy_id,
+        version
+    )
+    .fetch_optional(pool)
+    .await?;
+    Ok(row.and_then(|v| serde_json::from_value(v).ok()))
+}
+
+/// Create or replace the rollout of a version
+pub async fn upsert_rollout(pool: &PgPool, rollout: &Rollout, updated_by: &str) -> sqlx::Result<()> {
+    let value = serde_json::to_value(rollout).expect("rollout serializes");
+    sqlx::query!(
+        r#"INSERT INTO policy_rollout (policy_id, version, rollout, updated_by)
+           VALUES ($1, $2, $3, $4)
+           ON CONFLICT (policy_id, version) DO UPDATE SET rollout = $3, updated_by = $4, updated_at = now()"#,
+        rollout.policy_id,
+        rollout.version,
+        value,
+        updated_by
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// A stored policy version that could not be registered
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct RejectedPolicy {
+    pub policy_id: String,
+    pub version: String,
+    pub error: String,
+}
+
+/// Build a VM from every stored policy version, container attachment, namespace binding and rollout.
+/// Rows whose hash no longer matches their bytecode, that conflict, or that
+/// lack the governance signatures `governance` requires are left out and reported.
+pub async fn load_vm(
+    pool: &PgPool,
+    governance: Option<GovernanceKeys>,
+) -> sqlx::Result<(PolicyVM, Vec<RejectedPolicy>)> {
+    let mut vm = PolicyVM::new();
+    vm.set_governance_keys(governance);
+    let mut rejected = Vec::new();
+    for bundle in list(pool).await? {
+        let (policy_id, version) = (bundle.policy.policy_id.clone(), bundle.policy.version.clone());
+        if let Err(e) = vm.register_bundle(&bundle) {
+            tracing::error!(policy_id = %policy_id, version = %version, error = %e, "❌ policy rejected at load");
+            rejected.push(RejectedPolicy { policy_id, version, error: e.to_string() });
+        }
+    }
+
+    let attachments = sqlx::query!("SELECT container_id, policy_id FROM container_policy")
+        .fetch_all(pool)
+        .await?;
+    for a in attachments {
+        vm.attach(&a.container_id, &a.policy_id);
+    }
+    let bindings = sqlx::query!("SELECT namespace, policy_id FROM namespace_policy")
+        .fetch_all(pool)
+        .await?;
+    for b in bindings {
+        vm.bind_namespace(&b.namespace, &b.policy_id);
+    }
+    let compositions = sqlx::query!("SELECT container_id, mode FROM container_composition")
+        .fetch_all(pool)
+        .await?;
+    for c in compositions {
+        vm.set_composition(&c.container_id, parse_composition(&c.mode));
+    }
+    // A rollout whose versions are gone leaves every context on the active version
+    for r in rollouts(pool).await? {
+        let (policy_id, version) = (r.policy_id.clone(), r.version.clone());
+        if let Err(e) = vm.set_rollout(r) {
+            tracing::warn!(policy_id = %policy_id, version = %version, error = %e, "⚠️  rollout not applied");
+        }
+    }
+    Ok((vm, rejected))
+}
@@ -0,0 +1,278 @@
+//! # Policy rollouts
+//!
+//! - GET  /policy/:id/rollout       rollouts of the policy's versions, with the
+//!   share on the candidate now and this instance's decision counts
+//! - PUT  /policy/:id/rollout       (admin) start or move the rollout of a
+//!   version: `{version, percent, bucket_by?, max_deny_percent?, min_samples?}`
+//! - POST /policy/:id/rollout/halt  (admin) `{version, reason}`; every context
+//!   goes back to the version before it
+//!
+//! A version registered with a rollout governs only the contexts whose
+//! bucket (BLAKE3 of the container id, or of the actor with
+//! `bucket_by: "actor"`) is under the rollout's percentage; the rest stay on
+//! the version before it (`ubl_policy_vm::rollout`). Each PUT appends a step
+//! taking effect now, so the schedule is history: replaying a link at its
+//! original timestamp selects the version it was committed under. `bucket_by`
+//! is fixed once the rollout has steps, for the same reason. A halted
+//! rollout stays halted; register a new version to try again.
+//!
+//! Every [`MONITOR_SECS`] the monitor exports each rollout's counts as
+//! `ubl_policy_rollout_*` and halts a rollout whose candidate has decided
+//! `min_samples` contexts (default [`DEFAULT_MIN_SAMPLES`]) and denied more
+//! than `max_deny_percent` of them (default [`DEFAULT_MAX_DENY_PERCENT`]).
+//! Counts are per instance and start over on restart; a halt written by
+//! any instance reaches the others through the `policy_changed` NOTIFY.
+//! Candidate contexts are also decided under the baseline, off the record,
+//! and `ubl_policy_rollout_comparisons{result="diverged"}` counts those the
+//! two versions decide differently.
+
+use std::time::Duration;
+
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    routing::{get, post},
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use tracing::{error, info, warn};
+use ubl_policy_vm::{BucketBy, Rollout, RolloutStats, RolloutStep};
+
+use crate::auth::{rbac, session_policy};
+use crate::{metrics, policy_db, AppState};
+
+/// How often rollouts are checked against their deny limit
+pub const MONITOR_SECS: u64 = 5;
+
+/// Candidate deny rate, in percent, that halts a rollout unless set
+pub const DEFAULT_MAX_DENY_PERCENT: u8 = 20;
+
+/// Candidate decisions before the deny rate is judged, unless set
+pub const DEFAULT_MIN_SAMPLES: u64 = 100;
+
+/// Writer recorded for halts the monitor makes
+const MONITOR: &str = "rollout-monitor";
+
+#[derive(Debug, Deserialize)]
+struct RolloutReq {
+    version: String,
+    percent: u8,
+    bucket_by: Option<BucketBy>,
+    max_deny_percent: Option<u8>,
+    min_samples: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct HaltReq {
+    version: String,
+    reason: String,
+}
+
+#[derive(Debug, Serialize)]
+struct RolloutView {
+    #[serde(flatten)]
+    rollout: Rollout,
+    /// Share of buckets on the candidate now
+    percent_now: u8,
+    /// This instance's counts, when the monitor is running
+    stats: Option<RolloutStats>,
+    breached: bool,
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/policy/:id/rollout", get(route_list).put(route_set))
+        .route("/policy/:id/rollout/halt", post(route_halt))
+}
+
+fn views(state: &AppState, policy_id: &str, now: i64) -> Vec<RolloutView> {
+    let vm = state.policies.read().unwrap();
+    vm.rollouts()
+        .into_iter()
+        .filter(|r| r.policy_id == policy_id)
+        .map(|r| {
+            let stats = vm.rollout_monitor().map(|m| m.stats(r));
+            RolloutView {
+                rollout: r.clone(),
+                percent_now: r.percent_at(now),
+                breached: stats.is_some_and(|s| r.breached(&s)),
+                stats,
+            }
+        })
+        .collect()
+}
+
+/// Persist `rollout` and apply it to this instance's VM
+async fn store(state: &AppState, rollout: Rollout, by: &str) -> Result<(), (StatusCode, String)> {
+    policy_db::upsert_rollout(&state.pool, &rollout, by)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    if let Err(e) = state.policies.write().unwrap().set_rollout(rollout) {
+        warn!(error = %e, "⚠️  stored rollout not applied");
+    }
+    Ok(())
+}
+
+/// GET /policy/:id/rollout
+async fn route_list(State(state): State<AppState>, Path(policy_id): Path<String>) -> Json<Vec<RolloutView>> {
+    let now = OffsetDateTime::now_utc().unix_timestamp();
+    Json(views(&state, &policy_id, now))
+}
+
+/// PUT /policy/:id/rollout
+async fn route_set(
+    State(state): State<AppState>,
+    Path(policy_id): Path<String>,
+    headers: HeaderMap,
+    Json(req): Json<RolloutReq>,
+) -> Result<Json<Vec<RolloutView>>, (StatusCode, String)> {
+    let caller = rbac::require_role(&state.pool, &headers, &[rbac::ADMIN]).await?;
+    let position = state
+        .policies
+        .read()
+        .unwrap()
+        .versions(&policy_id)
+        .iter()
+        .position(|p| p.version == req.version);
+    match position {
+        None => return Err((StatusCode::NOT_FOUND, "No matching policy version".to_string())),
+        Some(0) => {
+            return Err((
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "the first version has no version before it to roll out from".to_string(),
+            ))
+        }
+        Some(_) => {}
+    }
+
+    let now = OffsetDateTime::now_utc().unix_timestamp();
+    let existing = policy_db::rollout(&state.pool, &policy_id, &req.version)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let mut rollout = match existing {
+        Some(r) if r.halted_at.is_some() => {
+            return Err((
+                StatusCode::CONFLICT,
+                format!("rollout was halted: {}", r.halt_reason.as_deref().unwrap_or("no reason")),
+            ))
+        }
+        Some(r) if req.bucket_by.is_some_and(|b| b != r.bucket_by) => {
+            return Err((StatusCode::CONFLICT, "bucket_by cannot change once a rollout has started".to_string()))
+        }
+        Some(r) => r,
+        None => Rollout {
+            policy_id: policy_id.clone(),
+            version: req.version.clone(),
+            bucket_by: req.bucket_by.unwrap_or_default(),
+            steps: Vec::new(),
+            halted_at: None,
+            halt_reason: None,
+            max_deny_percent: DEFAULT_MAX_DENY_PERCENT,
+            min_samples: DEFAULT_MIN_SAMPLES,
+        },
+    };
+    rollout.max_deny_percent = req.max_deny_percent.unwrap_or(rollout.max_deny_percent);
+    rollout.min_samples = req.min_samples.unwrap_or(rollout.min_samples);
+    // Two moves within a second: the later one wins that second
+    match rollout.steps.last_mut() {
+        Some(last) if last.from >= now => last.percent = req.percent,
+        _ => rollout.steps.push(RolloutStep { from: now, percent: req.percent }),
+    }
+    rollout.check().map_err(|e| (StatusCode::UNPROCESSABLE_ENTITY, e))?;
+
+    store(&state, rollout, &caller.session.sid).await?;
+    info!(
+        "🎚️ POLICY rollout id={} version={} percent={} by={}",
+        policy_id, req.version, req.percent, caller.session.sid
+    );
+    session_policy::after_action(&state.pool, &caller.session, session_policy::RISK_L4).await;
+    Ok(Json(views(&state, &policy_id, now)))
+}
+
+/// POST /policy/:id/rollout/halt
+async fn route_halt(
+    State(state): State<AppState>,
+    Path(policy_id): Path<String>,
+    headers: HeaderMap,
+    Json(req): Json<HaltReq>,
+) -> Result<Json<Vec<RolloutView>>, (StatusCode, String)> {
+    let caller = rbac::require_role(&state.pool, &headers, &[rbac::ADMIN]).await?;
+    let reason = req.reason.trim();
+    if reason.is_empty() {
+        return Err((StatusCode::UNPROCESSABLE_ENTITY, "a reason is required".to_string()));
+    }
+    let mut rollout = policy_db::rollout(&state.pool, &policy_id, &req.version)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or((StatusCode::NOT_FOUND, "No rollout of that version".to_string()))?;
+    let now = OffsetDateTime::now_utc().unix_timestamp();
+    if !rollout.halt(now, reason) {
+        return Err((StatusCode::CONFLICT, "rollout is already halted".to_string()));
+    }
+
+    store(&state, rollout, &caller.session.sid).await?;
+    metrics::POLICY_ROLLOUT_HALTS.with_label_values(&[&policy_id, "manual"]).inc();
+    warn!(
+        "🛑 POLICY rollout halted id={} version={} by={}: {}",
+        policy_id, req.version, caller.session.sid, reason
+    );
+    session_policy::after_action(&state.pool, &caller.session, session_policy::RISK_L4).await;
+    Ok(Json(views(&state, &policy_id, now)))
+}
+
+/// Export rollout counts and halt breached rollouts every [`MONITOR_SECS`]
+pub fn spawn_monitor(state: AppState) {
+    tokio::spawn(async move {
+        let mut tick = tokio::time::interval(Duration::from_secs(MONITOR_SECS));
+        loop {
+            tick.tick().await;
+            let now = OffsetDateTime::now_utc().unix_timestamp();
+            let breached: Vec<(Rollout, RolloutStats)> = {
+                let vm = state.policies.read().unwrap();
+                let Some(monitor) = vm.rollout_monitor() else {
+                    continue;
+                };
+                vm.rollouts()
+                    .into_iter()
+                    .filter_map(|r| {
+                        let stats = monitor.stats(r);
+                        metrics::observe_rollout(r, &stats, now);
+                        (r.halted_at.is_none() && r.breached(&stats)).then(|| (r.clone(), stats))
+                    })
+                    .collect()
+            };
+            for (r, stats) in breached {
+                if let Err(e) = halt_breached(&state, &r, &stats, now).await {
+                    error!(policy_id = %r.policy_id, version = %r.version, "rollout halt failed: {}", e.1);
+                }
+            }
+        }
+    });
+}
+
+async fn halt_breached(
+    state: &AppState,
+    r: &Rollout,
+    stats: &RolloutStats,
+    now: i64,
+) -> Result<(), (StatusCode, String)> {
+    // Halt the stored rollout: it may have moved on another instance
+    let Some(mut rollout) = policy_db::rollout(&state.pool, &r.policy_id, &r.version)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    else {
+        return Ok(());
+    };
+    let reason = format!(
+        "candidate denied {} of {} decisions, over {}%",
+        stats.candidate_denied, stats.candidate, r.max_deny_percent
+    );
+    if !rollout.halt(now, &reason) {
+        return Ok(());
+    }
+    store(state, rollout, MONITOR).await?;
+    metrics::POLICY_ROLLOUT_HALTS.with_label_values(&[&r.policy_id, "deny_rate"]).inc();
+    warn!("🛑 POLICY rollout halted id={} version={}: {}", r.policy_id, r.version, reason);
+    Ok(())
+}
@@ -0,0 +1,760 @@
+//! # Policy Routes
+//!
+//! CRUD over versioned TDLN policies (SPEC-UBL-POLICY v1.0 §4). Postgres is
+//! the source of truth; the in-memory `PolicyVM` is kept in step on every write.
+//!
+//! - POST   /policy/:id           (admin) register a version, bytecode hash and
+//!   governance signatures verified; CEL sources (`ubl_policy_vm::cel`) are
+//!   compiled here and answer 422 if they do not compile; WASM modules are
+//!   scanned (`ubl_policy_vm::wasm`) and answer 422 if nondeterministic;
+//!   `active_from` waits out the timelock for the activation's risk level
+//!   (`crate::policy_timelock`) and the answer carries `effective_at`
+//! - GET    /policy/:id           version active now, or `?version=`
+//! - GET    /policy/:id/versions  full activation history
+//! - DELETE /policy/:id           (admin) every version, or `?version=`
+//! - POST   /policy/evaluate      dry-run a decision for a caller's context;
+//!   nothing is committed
+//! - POST   /policy/import/rego   translate Rego source (`ubl_policy_vm::rego`)
+//!   into CEL bytecode and its hash, ready to sign and register; 422 names
+//!   the line outside the supported subset
+//! - POST   /policy/assemble/wat  assemble WAT source (the `wat` crate)
+//!   into WASM bytecode and its hash, scanned like a registration; 422 names
+//!   the line it cannot assemble. `POST /policy/:id` also takes `wat` in
+//!   place of `bytecode_hex`, so reviewers diff text while the chain stores
+//!   the binary. Likewise `cedar` takes a Cedar policy set, stored as an
+//!   external policy (`ubl_policy_vm::cedar`) and answering 422 if it does
+//!   not parse or lacks the annotations decisions are read from
+//! - POST   /admin/policy/reload  (admin) rebuild the VM from Postgres
+//! - GET    /policy-bindings      namespace bindings
+//! - POST   /policy-bindings      (admin) bind `policy_id` to `namespace`
+//! - DELETE /policy-bindings?namespace=&policy_id=  (admin) unbind
+//! - GET    /policy-bindings/resolve/:container_id  policies governing a container
+//!
+//! A container is governed by its own attachments (`/containers/:id/policies`)
+//! and by every bound namespace prefixing its id, in that priority order,
+//! longer namespaces first (`ubl_policy_vm::compose`): a Deny at any layer
+//! denies, the highest layer sets the intent class, and each commit records
+//! the policy that prevailed in `metadata.policy.decided_by`. Namespaces
+//! end at a separator (`.`, `/` or `:`) so `C.Bank.` never captures
+//! `C.Banking`; the empty namespace binds every container.
+//!
+//! Reloads also run on the `policy_changed` NOTIFY so every instance follows
+//! writes made elsewhere. A reload swaps the VM only if every stored version
+//! still matches its bytecode hash; otherwise the running set stays in place.
+//!
+//! With `UBL_POLICY_GOVERNANCE_KEYS` set (comma-separated Ed25519 public keys,
+//! threshold `UBL_POLICY_GOVERNANCE_THRESHOLD`, default 1), a version needs
+//! that many governance signatures over its bundle manifest to be registered
+//! or loaded (`ubl_policy_vm::bundle`).
+//!
+//! `UBL_POLICY_DECISION_CACHE=<entries>` enables the VM's decision cache.
+//! A reload builds a fresh VM, so cached decisions never outlive the policy
+//! set they came from.
+//!
+//! WASM policies run on wasmtime, their compiled modules kept by bytecode
+//! hash, up to `UBL_POLICY_MODULE_CACHE` (default 64) modules, least recently
+//! used evicted first (`ubl_policy_vm::runtime`). The runtime is carried
+//! across reloads, so unchanged policies are not compiled again.
+//!
+//! Cedar policy sets run on the embedded Cedar engine, their parsed sets
+//! kept by bytecode hash in a cache the size of the module cache, carried
+//! across reloads like the WASM runtime.
+//!
+//! A version can be rolled out to a share of containers or actors first
+//! (`policy_rollout.rs`); the rollouts are loaded with the versions, and
+//! their decision counts are carried across reloads.
+//!
+//! CEL rules read container state on demand with `balance(container_id)`
+//! and `field(container_id, path)`, answered from Postgres
+//! (`policy_state.rs`) instead of a state blob built for every evaluation.
+//! Those reads count against the evaluation budget below, and their
+//! decisions bypass the decision cache.
+//!
+//! `UBL_POLICY_BUDGET_US` (default 1000) bounds the commit path's policy
+//! decision; an evaluation still running then is interrupted and the commit
+//! answers 503 (`ubl_policy_vm::deadline`).
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::Response,
+    routing::{get, post},
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use time::OffsetDateTime;
+use tracing::{error, info, warn};
+use ubl_policy_vm::{
+    BindingSource, BundleSignature, CelPolicy, CompositionMode, EvaluationContext, GovernanceKeys, Policy, PolicyBundle,
+    PolicyError, TranslationDecision,
+};
+
+use ubl_pact::RiskLevel;
+
+use crate::auth::{rbac, session_policy};
+use crate::{http_cache, policy_db, policy_timelock};
+use crate::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct PutPolicyReq {
+    pub version: String,
+    /// Bytecode, hex-encoded; or give `wat` or `cedar` instead
+    #[serde(default)]
+    pub bytecode_hex: Option<String>,
+    /// WAT source, assembled into the stored WASM (the `wat` crate)
+    #[serde(default)]
+    pub wat: Option<String>,
+    /// Cedar policy set, stored as an external policy (`ubl_policy_vm::cedar`)
+    #[serde(default)]
+    pub cedar: Option<String>,
+    /// BLAKE3 hex of the bytecode; rejected if it does not match. Required
+    /// with `bytecode_hex`; with `wat` or `cedar` it defaults to the hash of
+    /// the stored bytecode
+    #[serde(default)]
+    pub bytecode_hash: Option<String>,
+    #[serde(default)]
+    pub description: String,
+    /// Unix seconds from which this version governs; defaults to now so a
+    /// new version never reaches back over commits already evaluated. The
+    /// timelock for `risk_level` may push it later (`crate::policy_timelock`)
+    pub active_from: Option<i64>,
+    /// Risk level of the activation; defaults to L4
+    #[serde(default)]
+    pub risk_level: Option<RiskLevel>,
+    /// Governance signatures over the bundle manifest
+    #[serde(default)]
+    pub signatures: Vec<BundleSignature>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct VersionQuery {
+    pub version: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PolicyView {
+    pub policy_id: String,
+    pub version: String,
+    pub bytecode_hash: String,
+    pub bytecode_hex: String,
+    pub description: String,
+    pub active_from: i64,
+}
+
+impl From<&Policy> for PolicyView {
+    fn from(p: &Policy) -> Self {
+        Self {
+            policy_id: p.policy_id.clone(),
+            version: p.version.clone(),
+            bytecode_hash: p.bytecode_hash.clone(),
+            bytecode_hex: hex::encode(&p.bytecode),
+            description: p.description.clone(),
+            active_from: p.active_from,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct PutPolicyResp {
+    #[serde(flatten)]
+    pub policy: PolicyView,
+    pub risk_level: RiskLevel,
+    /// Seconds the timelock held the activation back from registration
+    pub timelock_secs: i64,
+    /// When the version governs; vetoes are accepted until then
+    pub effective_at: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeletePolicyResp {
+    pub ok: bool,
+    pub policy_id: String,
+    pub versions_removed: u64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EvaluateReq {
+    /// Policy to evaluate; defaults to the policies bound to `context.container_id`
+    #[serde(default)]
+    pub policy_id: Option<String>,
+    pub context: EvaluationContext,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EvaluateResp {
+    pub allowed: bool,
+    /// Pact the commit would have to carry, if any
+    pub required_pact: Option<String>,
+    pub decision: TranslationDecision,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RegoImportReq {
+    pub source: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WatAssembleReq {
+    pub source: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WatAssembleResp {
+    pub bytecode_hex: String,
+    pub bytecode_hash: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RegoImportResp {
+    pub package: String,
+    pub policy: CelPolicy,
+    pub bytecode_hex: String,
+    pub bytecode_hash: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BindingReq {
+    pub namespace: String,
+    pub policy_id: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NamespaceBinding {
+    pub namespace: String,
+    pub policies: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ResolvedBinding {
+    pub container_id: String,
+    /// Highest-priority layer; `None` when no policy governs the container
+    pub source: Option<BindingSource>,
+    pub policies: Vec<String>,
+    /// Every layer that takes part, highest priority first
+    pub layers: Vec<BindingLayer>,
+    pub composition: CompositionMode,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BindingLayer {
+    pub source: BindingSource,
+    pub policies: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReloadResp {
+    pub swapped: bool,
+    pub policies: usize,
+    pub rejected: Vec<policy_db::RejectedPolicy>,
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/admin/policy/reload", post(route_reload))
+        .route(
+            "/policy/:id",
+            post(route_put_policy).get(route_get_policy).delete(route_delete_policy),
+        )
+        .route("/policy/:id/versions", get(route_policy_versions))
+        .route("/policy/evaluate", post(route_evaluate))
+        .route("/policy/import/rego", post(route_import_rego))
+        .route("/policy/assemble/wat", post(route_assemble_wat))
+        .route(
+            "/policy-bindings",
+            get(route_list_bindings).post(route_bind).delete(route_unbind),
+        )
+        .route("/policy-bindings/resolve/:container_id", get(route_resolve))
+}
+
+fn namespace_bindings(state: &AppState) -> Vec<NamespaceBinding> {
+    state
+        .policies
+        .read()
+        .unwrap()
+        .namespace_bindings()
+        .into_iter()
+        .map(|(ns, policies)| NamespaceBinding {
+            namespace: ns.to_string(),
+            policies: policies.into_iter().map(String::from).collect(),
+        })
+        .collect()
+}
+
+fn check_namespace(namespace: &str) -> Result<(), (StatusCode, String)> {
+    if namespace.is_empty() || namespace.ends_with(['.', '/', ':']) {
+        return Ok(());
+    }
+    Err((
+        StatusCode::BAD_REQUEST,
+        format!("namespace must end with '.', '/' or ':' (got {:?})", namespace),
+    ))
+}
+
+/// POST /policy/:id
+async fn route_put_policy(
+    State(state): State<AppState>,
+    Path(policy_id): Path<String>,
+    headers: HeaderMap,
+    Json(req): Json<PutPolicyReq>,
+) -> Result<Json<PutPolicyResp>, (StatusCode, String)> {
+    let caller = rbac::require_role(&state.pool, &headers, &[rbac::ADMIN]).await?;
+
+    let (bytecode, bytecode_hash) = match (&req.bytecode_hex, &req.wat, &req.cedar) {
+        (Some(hex), None, None) => {
+            let bytecode =
+                hex::decode(hex).map_err(|_| (StatusCode::BAD_REQUEST, "bytecode_hex is not valid hex".to_string()))?;
+            let hash = req
+                .bytecode_hash
+                .ok_or((StatusCode::BAD_REQUEST, "bytecode_hash is required".to_string()))?;
+            (bytecode, hash)
+        }
+        (None, Some(source), None) => {
+            let bytecode = assemble_wat(source)?;
+            let hash = req
+                .bytecode_hash
+                .unwrap_or_else(|| ubl_policy_vm::bytecode_hash(&bytecode));
+            (bytecode, hash)
+        }
+        (None, None, Some(source)) => {
+            let bytecode = ubl_policy_vm::cedar::to_bytecode(source);
+            let hash = req
+                .bytecode_hash
+                .unwrap_or_else(|| ubl_policy_vm::bytecode_hash(&bytecode));
+            (bytecode, hash)
+        }
+        _ => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "give exactly one of bytecode_hex, wat and cedar".to_string(),
+            ))
+        }
+    };
+    let now = OffsetDateTime::now_utc().unix_timestamp();
+    let current_risk = policy_db::active_risk(&state.pool, &policy_id, now)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let risk = policy_timelock::activation_risk(req.risk_level, current_risk);
+    let policy = Policy {
+        policy_id: policy_id.clone(),
+        version: req.version,
+        bytecode_hash,
+        bytecode,
+        description: req.description,
+        active_from: state
+            .timelock
+            .effective_at(risk, req.active_from.unwrap_or(now), now),
+    };
+    if let Err(e) = policy.verify_bytecode() {
+        warn!(policy_id = %policy_id, decision = "reject", error_code = "bytecode_hash_mismatch");
+        return Err((StatusCode::BAD_REQUEST, e.to_string()));
+    }
+    let bundle = PolicyBundle {
+        signatures: req.signatures,
+        ..PolicyBundle::new(policy.clone())
+    };
+
+    // The VM knows every version and the governance keys, so it arbitrates before the write
+    state
+        .policies
+        .write()
+        .unwrap()
+        .register_bundle(&bundle)
+        .map_err(|e| match e {
+            PolicyError::DuplicateVersion { .. } => (StatusCode::CONFLICT, e.to_string()),
+            PolicyError::CompileFailed { .. } => {
+                warn!(policy_id = %policy_id, decision = "reject", error_code = "policy_compile");
+                (StatusCode::UNPROCESSABLE_ENTITY, e.to_string())
+            }
+            PolicyError::NonDeterministic { .. } => {
+                warn!(policy_id = %policy_id, decision = "reject", error_code = "policy_nondeterministic");
+                (StatusCode::UNPROCESSABLE_ENTITY, e.to_string())
+            }
+            PolicyError::InvalidBundleSignature(_) | PolicyError::InsufficientGovernanceSignatures { .. } => {
+                warn!(policy_id = %policy_id, decision = "reject", error_code = "governance_signature");
+                (StatusCode::FORBIDDEN, e.to_string())
+            }
+            _ => (StatusCode::BAD_REQUEST, e.to_string()),
+        })?;
+
+    if let Err(e) = policy_db::insert(&state.pool, &policy, &bundle.signatures, risk as i16).await {
+        state
+            .policies
+            .write()
+            .unwrap()
+            .remove_version(&policy.policy_id, &policy.version);
+        return Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string()));
+    }
+
+    let view = PolicyView::from(&policy);
+    info!(
+        "📜 POLICY registered id={} version={} active_from={} risk={:?} hash={}",
+        view.policy_id, view.version, view.active_from, risk, &view.bytecode_hash[..8]
+    );
+    session_policy::after_action(&state.pool, &caller.session, session_policy::RISK_L4).await;
+    Ok(Json(PutPolicyResp {
+        risk_level: risk,
+        timelock_secs: state.timelock.delay(risk),
+        effective_at: view.active_from,
+        policy: view,
+    }))
+}
+
+/// GET /policy/:id
+async fn route_get_policy(
+    State(state): State<AppState>,
+    Path(policy_id): Path<String>,
+    Query(query): Query<VersionQuery>,
+    headers: HeaderMap,
+) -> Result<Response, (StatusCode, String)> {
+    let versions = policy_db::versions(&state.pool, &policy_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    if versions.is_empty() {
+        return Err((StatusCode::NOT_FOUND, "Policy not found".to_string()));
+    }
+
+    let policy = match query.version {
+        Some(v) => versions.iter().find(|p| p.version == v),
+        None => {
+            let now = OffsetDateTime::now_utc().unix_timestamp();
+            versions.iter().rev().find(|p| p.active_from <= now)
+        }
+    }
+    .ok_or((StatusCode::NOT_FOUND, "No matching policy version".to_string()))?;
+    Ok(http_cache::respond("policy", &headers, http_cache::SHORT, PolicyView::from(policy)))
+}
+
+/// GET /policy/:id/versions
+async fn route_policy_versions(
+    State(state): State<AppState>,
+    Path(policy_id): Path<String>,
+    headers: HeaderMap,
+) -> Result<Response, (StatusCode, String)> {
+    let versions = policy_db::versions(&state.pool, &policy_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    if versions.is_empty() {
+        return Err((StatusCode::NOT_FOUND, "Policy not found".to_string()));
+    }
+    let views: Vec<PolicyView> = versions.iter().map(PolicyView::from).collect();
+    Ok(http_cache::respond("policy_versions", &headers, http_cache::SHORT, views))
+}
+
+/// DELETE /policy/:id
+async fn route_delete_policy(
+    State(state): State<AppState>,
+    Path(policy_id): Path<String>,
+    Query(query): Query<VersionQuery>,
+    headers: HeaderMap,
+) -> Result<Json<DeletePolicyResp>, (StatusCode, String)> {
+    let caller = rbac::require_role(&state.pool, &headers, &[rbac::ADMIN]).await?;
+
+    let removed = match &query.version {
+        Some(v) => policy_db::delete_version(&state.pool, &policy_id, v).await,
+        None => policy_db::delete(&state.pool, &policy_id).await,
+    }
+    .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    if removed == 0 {
+        return Err((StatusCode::NOT_FOUND, "Policy not found".to_string()));
+    }
+
+    {
+        let mut vm = state.policies.write().unwrap();
+        match &query.version {
+            Some(v) => {
+                vm.remove_version(&policy_id, v);
+            }
+            None => {
+                vm.remove(&policy_id);
+            }
+        }
+    }
+    info!("🗑️  POLICY deleted id={} version={:?}", policy_id, query.version);
+    session_policy::after_action(&state.pool, &caller.session, session_policy::RISK_L4).await;
+    Ok(Json(DeletePolicyResp {
+        ok: true,
+        policy_id,
+        versions_removed: removed,
+    }))
+}
+
+/// POST /policy/evaluate
+async fn route_evaluate(
+    State(state): State<AppState>,
+    Json(req): Json<EvaluateReq>,
+) -> Result<Json<EvaluateResp>, (StatusCode, String)> {
+    let decision = {
+        let vm = state.policies.read().unwrap();
+        match &req.policy_id {
+            Some(id) => vm.evaluate(id, &req.context),
+            None => vm.evaluate_all(&req.context.container_id, &req.context),
+        }
+    }
+    .map_err(|e| match e {
+        PolicyError::PolicyNotFound(_) | PolicyError::NoActiveVersion { .. } | PolicyError::NoPolicyAttached(_) => {
+            (StatusCode::NOT_FOUND, e.to_string())
+        }
+        _ => (StatusCode::UNPROCESSABLE_ENTITY, e.to_string()),
+    })?;
+
+    let (allowed, required_pact) = match &decision {
+        TranslationDecision::Allow { required_pact, .. } => (true, required_pact.clone()),
+        TranslationDecision::Deny { .. } => (false, None),
+    };
+    Ok(Json(EvaluateResp {
+        allowed,
+        required_pact,
+        decision,
+    }))
+}
+
+fn assemble_wat(source: &str) -> Result<Vec<u8>, (StatusCode, String)> {
+    wat::parse_str(source).map_err(|e| {
+        warn!(decision = "reject", error_code = "wat_assemble", reason = %e);
+        (StatusCode::UNPROCESSABLE_ENTITY, e.to_string())
+    })
+}
+
+/// POST /policy/assemble/wat
+async fn route_assemble_wat(Json(req): Json<WatAssembleReq>) -> Result<Json<WatAssembleResp>, (StatusCode, String)> {
+    let bytecode = assemble_wat(&req.source)?;
+    ubl_policy_vm::wasm::analyze("wat", &bytecode).map_err(|e| {
+        warn!(decision = "reject", error_code = "policy_nondeterministic", reason = %e);
+        (StatusCode::UNPROCESSABLE_ENTITY, e.to_string())
+    })?;
+    let bytecode_hash = ubl_policy_vm::bytecode_hash(&bytecode);
+    info!(
+        "📜 POLICY wat assembled bytes={} hash={}",
+        bytecode.len(),
+        &bytecode_hash[..8]
+    );
+    Ok(Json(WatAssembleResp {
+        bytecode_hex: hex::encode(&bytecode),
+        bytecode_hash,
+    }))
+}
+
+/// POST /policy/import/rego
+async fn route_import_rego(Json(req): Json<RegoImportReq>) -> Result<Json<RegoImportResp>, (StatusCode, String)> {
+    let imported = ubl_policy_vm::rego::import(&req.source).map_err(|e| {
+        warn!(decision = "reject", error_code = "rego_import", reason = %e);
+        (StatusCode::UNPROCESSABLE_ENTITY, e.to_string())
+    })?;
+    let bytecode = imported.policy.to_bytecode();
+    info!(
+        "📜 POLICY rego import package={} rules={}",
+        imported.package,
+        imported.policy.rules.len()
+    );
+    Ok(Json(RegoImportResp {
+        package: imported.package,
+        policy: imported.policy,
+        bytecode_hash: ubl_policy_vm::bytecode_hash(&bytecode),
+        bytecode_hex: hex::encode(bytecode),
+    }))
+}
+
+/// GET /policy-bindings
+async fn route_list_bindings(State(state): State<AppState>) -> Json<Vec<NamespaceBinding>> {
+    Json(namespace_bindings(&state))
+}
+
+/// POST /policy-bindings
+async fn route_bind(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<BindingReq>,
+) -> Result<Json<Vec<NamespaceBinding>>, (StatusCode, String)> {
+    let caller = rbac::require_role(&state.pool, &headers, &[rbac::ADMIN]).await?;
+    check_namespace(&req.namespace)?;
+    if state.policies.read().unwrap().get(&req.policy_id).is_none() {
+        return Err((StatusCode::NOT_FOUND, "Policy not found".to_string()));
+    }
+
+    policy_db::bind_namespace(&state.pool, &req.namespace, &req.policy_id, &caller.session.sid)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    state
+        .policies
+        .write()
+        .unwrap()
+        .bind_namespace(&req.namespace, &req.policy_id);
+    info!(
+        "📎 POLICY bound namespace={:?} policy={} by={}",
+        req.namespace, req.policy_id, caller.session.sid
+    );
+    session_policy::after_action(&state.pool, &caller.session, session_policy::RISK_L4).await;
+    Ok(Json(namespace_bindings(&state)))
+}
+
+/// DELETE /policy-bindings?namespace=&policy_id=
+async fn route_unbind(
+    State(state): State<AppState>,
+    Query(req): Query<BindingReq>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<NamespaceBinding>>, (StatusCode, String)> {
+    let caller = rbac::require_role(&state.pool, &headers, &[rbac::ADMIN]).await?;
+    if !policy_db::unbind_namespace(&state.pool, &req.namespace, &req.policy_id)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    {
+        return Err((StatusCode::NOT_FOUND, "Policy not bound to namespace".to_string()));
+    }
+    state
+        .policies
+        .write()
+        .unwrap()
+        .unbind_namespace(&req.namespace, &req.policy_id);
+    info!(
+        "📎 POLICY unbound namespace={:?} policy={} by={}",
+        req.namespace, req.policy_id, caller.session.sid
+    );
+    session_policy::after_action(&state.pool, &caller.session, session_policy::RISK_L4).await;
+    Ok(Json(namespace_bindings(&state)))
+}
+
+/// GET /policy-bindings/resolve/:container_id
+async fn route_resolve(
+    State(state): State<AppState>,
+    Path(container_id): Path<String>,
+    headers: HeaderMap,
+) -> Response {
+    let vm = state.policies.read().unwrap();
+    let (source, policies) = match vm.resolve(&container_id) {
+        Some((source, policies)) => (Some(source), policies.into_iter().map(String::from).collect()),
+        None => (None, Vec::new()),
+    };
+    let layers = vm
+        .layers(&container_id)
+        .into_iter()
+        .map(|(source, ids)| BindingLayer {
+            source,
+            policies: ids.into_iter().map(String::from).collect(),
+        })
+        .collect();
+    let resolved = ResolvedBinding {
+        layers,
+        composition: vm.composition(&container_id),
+        container_id,
+        source,
+        policies,
+    };
+    http_cache::respond("policy_bindings_resolve", &headers, http_cache::SHORT, resolved)
+}
+
+/// Rebuild the policy set from Postgres and swap it in atomically
+pub async fn reload(state: &AppState) -> sqlx::Result<ReloadResp> {
+    let governance = state.policies.read().unwrap().governance_keys().cloned();
+    let cache_capacity = state.policies.read().unwrap().cache_stats().map(|c| c.capacity);
+    let observer = state.policies.read().unwrap().observer().cloned();
+    let runtime = state.policies.read().unwrap().wasm_runtime().cloned();
+    let reader = state.policies.read().unwrap().state_reader().cloned();
+    let engines: Vec<_> = state.policies.read().unwrap().external_engines().cloned().collect();
+    let monitor = state.policies.read().unwrap().rollout_monitor().cloned();
+    let (mut vm, rejected) = policy_db::load_vm(&state.pool, governance).await?;
+    if !rejected.is_empty() {
+        warn!(rejected = rejected.len(), "⚠️  policy reload aborted, keeping running set");
+        let policies = state.policies.read().unwrap().len();
+        return Ok(ReloadResp { swapped: false, policies, rejected });
+    }
+    let policies = vm.len();
+    if let Some(capacity) = cache_capacity {
+        vm.enable_decision_cache(capacity);
+    }
+    vm.set_observer(observer);
+    vm.set_wasm_runtime(runtime);
+    vm.set_state_reader(reader);
+    for engine in engines {
+        vm.set_external_engine(engine);
+    }
+    vm.set_rollout_monitor(monitor);
+    *state.policies.write().unwrap() = vm;
+    info!("🔄 POLICIES reloaded: {}", policies);
+    Ok(ReloadResp { swapped: true, policies, rejected })
+}
+
+/// POST /admin/policy/reload
+async fn route_reload(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<(StatusCode, Json<ReloadResp>), (StatusCode, String)> {
+    rbac::require_role(&state.pool, &headers, &[rbac::ADMIN]).await?;
+    let resp = reload(&state)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    let status = if resp.swapped { StatusCode::OK } else { StatusCode::CONFLICT };
+    Ok((status, Json(resp)))
+}
+
+/// Governance keys from `UBL_POLICY_GOVERNANCE_KEYS` / `UBL_POLICY_GOVERNANCE_THRESHOLD`
+pub fn governance_from_env() -> Option<GovernanceKeys> {
+    let keys: Vec<String> = std::env::var("UBL_POLICY_GOVERNANCE_KEYS")
+        .ok()?
+        .split(',')
+        .map(|k| k.trim().to_string())
+        .filter(|k| !k.is_empty())
+        .collect();
+    if keys.is_empty() {
+        return None;
+    }
+    let threshold = std::env::var("UBL_POLICY_GOVERNANCE_THRESHOLD")
+        .ok()
+        .and_then(|t| t.parse().ok())
+        .unwrap_or(1);
+    Some(GovernanceKeys::new(keys, threshold))
+}
+
+/// Decision cache capacity from `UBL_POLICY_DECISION_CACHE` (unset or 0 disables it)
+pub fn decision_cache_from_env() -> Option<usize> {
+    std::env::var("UBL_POLICY_DECISION_CACHE")
+        .ok()
+        .and_then(|n| n.parse().ok())
+        .filter(|&n| n > 0)
+}
+
+/// Compiled WASM module cache capacity from `UBL_POLICY_MODULE_CACHE` (default 64)
+pub fn module_cache_from_env() -> usize {
+    std::env::var("UBL_POLICY_MODULE_CACHE")
+        .ok()
+        .and_then(|n| n.parse().ok())
+        .filter(|&n| n > 0)
+        .unwrap_or(64)
+}
+
+/// Commit-path evaluation budget from `UBL_POLICY_BUDGET_US` (default 1000, i.e. 1ms)
+pub fn budget_from_env() -> std::time::Duration {
+    let micros = std::env::var("UBL_POLICY_BUDGET_US")
+        .ok()
+        .and_then(|n| n.parse().ok())
+        .filter(|&n| n > 0)
+        .unwrap_or(1000);
+    std::time::Duration::from_micros(micros)
+}
+
+/// Follow `policy_changed` notifications and reload on each one
+pub fn spawn_reload_listener(state: AppState) {
+    tokio::spawn(async move {
+        loop {
+            let mut listener = match sqlx::postgres::PgListener::connect_with(&state.pool).await {
+                Ok(l) => l,
+                Err(e) => {
+                    error!("Failed to create PgListener: {}", e);
+                    tokio::time::sleep(Duration::from_secs(5)).await;
+                    continue;
+                }
+            };
+            if let Err(e) = listener.listen("policy_changed").await {
+                error!("Failed to LISTEN on policy_changed: {}", e);
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                continue;
+            }
+            while let Ok(notification) = listener.recv().await {
+                info!("📨 policy_changed: {}", notification.payload());
+                if let Err(e) = reload(&state).await {
+                    error!("policy reload failed: {}", e);
+                }
+            }
+        }
+    });
+}
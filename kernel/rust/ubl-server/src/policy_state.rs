@@ -0,0 +1,71 @@
+//! # Policy state reader
+//!
+//! Postgres-backed [`StateReader`] the policy VM asks when a CEL rule calls
+//! `balance(container_id)` or `field(container_id, path)`:
+//!
+//! - `balance`: sum of the container's recorded `physics_delta`s (as
+//!   `balance_below` alerts compute it), `None` without entries;
+//! - `field`: the dot-separated `path` into the container's head entry as
+//!   JSON (`sequence`, `entry_hash`, `ts_unix_ms`, `intent_class`,
+//!   `metadata.policy.decided_by`, ...).
+//!
+//! Evaluation is synchronous, so each read blocks its thread on the query:
+//! the commit path evaluates on tokio's blocking pool, dry runs on a worker
+//! via `block_in_place`. Reads see committed data; a commit's own
+//! entry is not visible to the policy deciding it.
+
+use sqlx::PgPool;
+use tokio::runtime::Handle;
+use ubl_policy_vm::{PolicyError, StateReader};
+
+pub struct PgStateReader {
+    pool: PgPool,
+    runtime: Handle,
+}
+
+impl PgStateReader {
+    /// Reader on `pool`, blocking on the current tokio runtime
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool,
+            runtime: Handle::current(),
+        }
+    }
+
+    fn block_on<T>(&self, query: impl std::future::Future<Output = sqlx::Result<T>>) -> ubl_policy_vm::Result<T> {
+        tokio::task::block_in_place(|| self.runtime.block_on(query))
+            .map_err(|e| PolicyError::ExecutionFailed(format!("state read failed: {}", e)))
+    }
+}
+
+impl StateReader for PgStateReader {
+    fn get_balance(&self, container_id: &str) -> ubl_policy_vm::Result<Option<i128>> {
+        let balance = self.block_on(
+            sqlx::query_scalar!(
+                r#"SELECT SUM((physics_delta #>> '{}')::numeric)::text FROM ledger_entry WHERE container_id = $1"#,
+                container_id
+            )
+            .fetch_one(&self.pool),
+        )?;
+        balance
+            .map(|b| {
+                b.parse()
+                    .map_err(|_| PolicyError::ExecutionFailed(format!("balance of {} is not an integer: {}", container_id, b)))
+            })
+            .transpose()
+    }
+
+    fn get_field(&self, container_id: &str, path: &str) -> ubl_policy_vm::Result<Option<serde_json::Value>> {
+        let path: Vec<&str> = path.split('.').collect();
+        let field = self.block_on(
+            sqlx::query_scalar!(
+                r#"SELECT to_jsonb(e) #> $2::text[] FROM ledger_entry e
+                   WHERE container_id = $1 ORDER BY sequence DESC LIMIT 1"#,
+                container_id,
+                &path as &[&str]
+            )
+            .fetch_optional(&self.pool),
+        )?;
+        Ok(field.flatten().filter(|v| !v.is_null()))
+    }
+}
@@ -0,0 +1,387 @@
+//! # Policy activation timelock
+//!
+//! - GET  /policy/timelock                      delay per risk level and the veto pact
+//! - GET  /policy/pending                       versions registered but not yet governing
+//! - POST /policy/:id/versions/:version/veto    cancel a pending version with a
+//!   `PactProof` from the veto pact
+//!
+//! `UBL_POLICY_TIMELOCK=L4=86400,L5=604800` sets how long an activation at a
+//! risk level waits after registration. A level without its own entry waits
+//! as long as the nearest listed level below it; levels below every entry
+//! take effect when asked. `POST /policy/:id` moves `active_from` forward to
+//! the end of the delay and answers with that `effective_at`, so clients can
+//! prepare for the switch. The version is stored from the start; until then
+//! the previous one keeps governing.
+//!
+//! An activation runs at the risk level it declares (default L4) or at the
+//! level of the version it replaces, whichever is higher: lowering a
+//! policy's risk waits out the old level's delay.
+//!
+//! While a version is pending, the veto pact (`UBL_POLICY_VETO_PACT`, a pact
+//! JSON file) can cancel it. The proof carries Ed25519 signatures from pact
+//! signers of at least threshold weight over
+//! `PactProof::signing_bytes(veto_subject, pact_id, nonce)`, where `veto_subject`
+//! (listed by `/policy/pending`) binds the policy, version and bytecode hash. The pact
+//! must be inside its window and cover the activation's risk level. A veto
+//! deletes the version and records it in `policy_veto`; once `active_from`
+//! has passed, the version can only be replaced.
+
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    routing::{get, post},
+    Json, Router,
+};
+use serde::Serialize;
+use std::collections::HashSet;
+use time::OffsetDateTime;
+use tracing::{info, warn};
+use ubl_pact::{Pact, PactError, PactProof, RiskLevel};
+
+use crate::auth::{rbac, session_policy};
+use crate::policy_db::{self, PendingActivation};
+use crate::AppState;
+
+/// Risk level of an activation that does not declare one
+pub const DEFAULT_RISK: RiskLevel = RiskLevel::L4;
+
+const LEVELS: [RiskLevel; 6] = [
+    RiskLevel::L0,
+    RiskLevel::L1,
+    RiskLevel::L2,
+    RiskLevel::L3,
+    RiskLevel::L4,
+    RiskLevel::L5,
+];
+
+/// Activation delays and the pact that may veto during them
+#[derive(Debug, Clone)]
+pub struct Timelock {
+    /// Seconds to wait, indexed by risk level
+    delays: [i64; 6],
+    veto_pact: Option<Pact>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TimelockView {
+    /// Seconds per risk level, `L0` to `L5`
+    pub delays: Vec<LevelDelay>,
+    pub veto_pact: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LevelDelay {
+    pub risk_level: RiskLevel,
+    pub delay_secs: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PendingView {
+    pub policy_id: String,
+    pub version: String,
+    pub bytecode_hash: String,
+    pub risk_level: RiskLevel,
+    /// When the version governs; vetoes are accepted until then
+    pub effective_at: i64,
+    /// What veto signers sign, as `link_hash` in the pact signing message
+    pub veto_subject: String,
+}
+
+impl From<PendingActivation> for PendingView {
+    fn from(a: PendingActivation) -> Self {
+        Self {
+            veto_subject: veto_subject(&a.policy_id, &a.version, &a.bytecode_hash),
+            risk_level: risk_from_level(a.risk_level),
+            effective_at: a.active_from,
+            policy_id: a.policy_id,
+            version: a.version,
+            bytecode_hash: a.bytecode_hash,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct VetoResp {
+    pub ok: bool,
+    pub policy_id: String,
+    pub version: String,
+    pub pact_id: String,
+    pub vetoed_by: String,
+}
+
+impl Timelock {
+    /// Parse `L4=86400,L5=604800` into a delay per level
+    pub fn parse_delays(spec: &str) -> Result<[i64; 6], String> {
+        let mut listed = [None; 6];
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let (level, secs) = entry
+                .split_once('=')
+                .ok_or_else(|| format!("expected L<n>=<seconds>, got {:?}", entry))?;
+            let level = level
+                .trim()
+                .strip_prefix('L')
+                .and_then(|n| n.parse::<usize>().ok())
+                .filter(|&n| n < LEVELS.len())
+                .ok_or_else(|| format!("unknown risk level {:?}", level.trim()))?;
+            let secs = secs
+                .trim()
+                .parse::<i64>()
+                .ok()
+                .filter(|&s| s >= 0)
+                .ok_or_else(|| format!("delay for L{} must be whole seconds", level))?;
+            listed[level] = Some(secs);
+        }
+        let mut delays = [0; 6];
+        let mut carried = 0;
+        for (delay, listed) in delays.iter_mut().zip(listed) {
+            carried = listed.unwrap_or(carried);
+            *delay = carried;
+        }
+        Ok(delays)
+    }
+
+    /// `UBL_POLICY_TIMELOCK` and `UBL_POLICY_VETO_PACT`; unset means no delay and no veto
+    pub fn from_env() -> anyhow::Result<Self> {
+        let delays = match std::env::var("UBL_POLICY_TIMELOCK") {
+            Ok(spec) => Self::parse_delays(&spec).map_err(|e| anyhow::anyhow!("UBL_POLICY_TIMELOCK: {}", e))?,
+            Err(_) => [0; 6],
+        };
+        let veto_pact = match std::env::var("UBL_POLICY_VETO_PACT") {
+            Ok(path) => {
+                let mut pact: Pact = serde_json::from_slice(&std::fs::read(&path)?)
+                    .map_err(|e| anyhow::anyhow!("UBL_POLICY_VETO_PACT {}: {}", path, e))?;
+                pact.signers = pact.signers.iter().map(|k| k.to_ascii_lowercase()).collect();
+                Some(pact)
+            }
+            Err(_) => None,
+        };
+        Ok(Self { delays, veto_pact })
+    }
+
+    /// Seconds an activation at `risk` waits
+    pub fn delay(&self, risk: RiskLevel) -> i64 {
+        self.delays[risk as usize]
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.delays.iter().any(|&d| d > 0)
+    }
+
+    pub fn veto_pact(&self) -> Option<&Pact> {
+        self.veto_pact.as_ref()
+    }
+
+    /// When a version registered at `now` asking for `requested` takes effect
+    pub fn effective_at(&self, risk: RiskLevel, requested: i64, now: i64) -> i64 {
+        requested.max(now + self.delay(risk))
+    }
+}
+
+/// Risk level stored as `policy.risk_level`
+pub fn risk_from_level(level: i16) -> RiskLevel {
+    LEVELS[level.clamp(0, 5) as usize]
+}
+
+/// Risk an activation runs at: the declared level, raised to the level of the version it replaces
+pub fn activation_risk(declared: Option<RiskLevel>, current: Option<i16>) -> RiskLevel {
+    let declared = declared.unwrap_or(DEFAULT_RISK);
+    current.map_or(declared, |c| declared.max(risk_from_level(c)))
+}
+
+/// What veto signers sign for one pending version
+pub fn veto_subject(policy_id: &str, version: &str, bytecode_hash: &str) -> String {
+    let msg = format!(
+        "ubl:policy-veto\n{}\n{}\n{}",
+        policy_id,
+        version,
+        bytecode_hash.to_ascii_lowercase()
+    );
+    blake3::hash(msg.as_bytes()).to_hex().to_string()
+}
+
+/// Check a veto proof against the veto pact: same pact, inside its window, covering
+/// `risk`, and distinct signers meeting its quorum whose signatures verify
+pub fn verify_veto(pact: &Pact, proof: &PactProof, subject: &str, risk: RiskLevel, now: i64) -> ubl_pact::Result<()> {
+    if proof.pact_id != pact.pact_id {
+        return Err(PactError::UnknownPact(proof.pact_id.clone()));
+    }
+    if !pact.window.is_valid(now) {
+        return Err(PactError::PactExpired);
+    }
+    if pact.risk_level < risk {
+        return Err(PactError::RiskMismatch {
+            intent: risk,
+            pact: pact.risk_level,
+        });
+    }
+
+    let message = PactProof::signing_bytes(subject, &pact.pact_id, proof.nonce);
+    let mut seen = HashSet::new();
+    for sig in &proof.signatures {
+        let pubkey = sig.pubkey.to_ascii_lowercase();
+        if !pact.signers.contains(&pubkey) {
+            return Err(PactError::UnauthorizedSigner(pubkey));
+        }
+        if !seen.insert(pubkey.clone()) {
+            return Err(PactError::DuplicateSigner(pubkey));
+        }
+        ubl_kernel::verify(&pubkey, &message, &sig.signature).map_err(|_| PactError::InvalidSignature(pubkey))?;
+    }
+    let signed: Vec<&str> = seen.iter().map(String::as_str).collect();
+    pact.check_quorum(&signed)
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/policy/timelock", get(route_timelock))
+        .route("/policy/pending", get(route_pending))
+        .route("/policy/:id/versions/:version/veto", post(route_veto))
+}
+
+/// GET /policy/timelock
+async fn route_timelock(State(state): State<AppState>) -> Json<TimelockView> {
+    Json(TimelockView {
+        delays: LEVELS
+            .iter()
+            .map(|&risk_level| LevelDelay {
+                risk_level,
+                delay_secs: state.timelock.delay(risk_level),
+            })
+            .collect(),
+        veto_pact: state.timelock.veto_pact().map(|p| p.pact_id.clone()),
+    })
+}
+
+/// GET /policy/pending
+async fn route_pending(State(state): State<AppState>) -> Result<Json<Vec<PendingView>>, (StatusCode, String)> {
+    let now = OffsetDateTime::now_utc().unix_timestamp();
+    let pending = policy_db::pending(&state.pool, now)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok(Json(pending.into_iter().map(PendingView::from).collect()))
+}
+
+/// POST /policy/:id/versions/:version/veto
+async fn route_veto(
+    State(state): State<AppState>,
+    Path((policy_id, version)): Path<(String, String)>,
+    headers: HeaderMap,
+    Json(proof): Json<PactProof>,
+) -> Result<Json<VetoResp>, (StatusCode, String)> {
+    let caller = rbac::authenticate(&state.pool, &headers).await?;
+    let pact = state.timelock.veto_pact().ok_or((
+        StatusCode::CONFLICT,
+        "no veto pact configured (UBL_POLICY_VETO_PACT)".to_string(),
+    ))?;
+
+    let activation = policy_db::activation(&state.pool, &policy_id, &version)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or((StatusCode::NOT_FOUND, "No matching policy version".to_string()))?;
+    let now = OffsetDateTime::now_utc().unix_timestamp();
+    if activation.active_from <= now {
+        return Err((
+            StatusCode::CONFLICT,
+            format!("version took effect at {}; the veto window is closed", activation.active_from),
+        ));
+    }
+
+    let subject = veto_subject(&policy_id, &version, &activation.bytecode_hash);
+    let risk = risk_from_level(activation.risk_level);
+    if let Err(e) = verify_veto(pact, &proof, &subject, risk, now) {
+        warn!(policy_id = %policy_id, version = %version, decision = "reject", error_code = "veto_pact", error = %e);
+        return Err((StatusCode::FORBIDDEN, e.to_string()));
+    }
+
+    let signatures = serde_json::to_value(&proof.signatures).unwrap_or_default();
+    if !policy_db::veto(&state.pool, &activation, now, &pact.pact_id, &signatures, &caller.session.sid)
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+    {
+        return Err((StatusCode::CONFLICT, "version is no longer pending".to_string()));
+    }
+    state.policies.write().unwrap().remove_version(&policy_id, &version);
+
+    info!(
+        "🛑 POLICY vetoed id={} version={} pact={} by={}",
+        policy_id, version, pact.pact_id, caller.session.sid
+    );
+    session_policy::after_action(&state.pool, &caller.session, session_policy::RISK_L4).await;
+    Ok(Json(VetoResp {
+        ok: true,
+        policy_id,
+        version,
+        pact_id: pact.pact_id.clone(),
+        vetoed_by: caller.session.sid,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ubl_pact::{PactSignature, TimeWindow};
+
+    #[test]
+    fn test_delays_carry_up_to_higher_levels() {
+        let d = Timelock::parse_delays("L3=60, L5=3600").unwrap();
+        assert_eq!(d, [0, 0, 0, 60, 60, 3600]);
+        assert!(Timelock::parse_delays("L6=1").is_err());
+        assert!(Timelock::parse_delays("L4=-1").is_err());
+        assert_eq!(Timelock::parse_delays("").unwrap(), [0; 6]);
+
+        let t = Timelock { delays: d, veto_pact: None };
+        assert_eq!(t.effective_at(RiskLevel::L4, 100, 100), 160);
+        assert_eq!(t.effective_at(RiskLevel::L4, 500, 100), 500);
+        assert_eq!(activation_risk(Some(RiskLevel::L1), Some(5)), RiskLevel::L5);
+        assert_eq!(activation_risk(None, None), DEFAULT_RISK);
+    }
+
+    #[test]
+    fn test_veto_needs_threshold_of_valid_signatures() {
+        let keys: Vec<_> = (0..2).map(|_| ubl_kernel::generate_keypair()).collect();
+        let pact = Pact {
+            pact_id: "veto".to_string(),
+            threshold: 2,
+            signers: keys.iter().map(|(pk, _)| pk.clone()).collect(),
+            window: TimeWindow {
+                not_before: 0,
+                not_after: 1000,
+            },
+            risk_level: RiskLevel::L4,
+            ..ubl_fixtures::pact()
+        };
+        let subject = veto_subject("p", "v2", &"ab".repeat(32));
+        let sign = |i: usize, subject: &str| PactSignature {
+            pubkey: keys[i].0.clone(),
+            signature: ubl_kernel::sign(&keys[i].1, &PactProof::signing_bytes(subject, "veto", 0)),
+        };
+        let proof = |signatures| PactProof {
+            pact_id: "veto".to_string(),
+            signatures,
+            nonce: 0,
+            delegations: Vec::new(),
+            frost_signature: None,
+            bls_aggregate: None,
+        };
+
+        assert!(verify_veto(&pact, &proof(vec![sign(0, &subject), sign(1, &subject)]), &subject, RiskLevel::L4, 10).is_ok());
+        assert_eq!(
+            verify_veto(&pact, &proof(vec![sign(0, &subject)]), &subject, RiskLevel::L4, 10),
+            Err(PactError::InsufficientSignatures { got: 1, need: 2 })
+        );
+        // Signed for another version
+        let other = veto_subject("p", "v3", &"ab".repeat(32));
+        assert!(matches!(
+            verify_veto(&pact, &proof(vec![sign(0, &subject), sign(1, &other)]), &subject, RiskLevel::L4, 10),
+            Err(PactError::InvalidSignature(_))
+        ));
+        assert!(matches!(
+            verify_veto(&pact, &proof(vec![sign(0, &subject), sign(1, &subject)]), &subject, RiskLevel::L5, 10),
+            Err(PactError::RiskMismatch { .. })
+        ));
+        assert_eq!(
+            verify_veto(&pact, &proof(vec![sign(0, &subject), sign(1, &subject)]), &subject, RiskLevel::L4, 2000),
+            Err(PactError::PactExpired)
+        );
+    }
+}
@@ -0,0 +1,677 @@
+//! # Multi-region active-passive
+//!
+//! - GET  /admin/region/status  (admin/operator/auditor) role, epoch, last
+//!   checkpoint and replication lag per container
+//! - POST /admin/region/promote (admin) standby to primary
+//! - POST /admin/region/fence   (admin) stop appending for a newer primary
+//!
+//! Region mode is on with `UBL_REGION` (this region's name).
+//! `UBL_REGION_ROLE` (`primary` or `standby`, default `primary`) only seeds
+//! the `region_node` row on first start; from then on the database's role
+//! wins. Each region has its own database.
+//!
+//! **Export stream.** A standby reads the primary's database
+//! (`UBL_PRIMARY_DATABASE_URL`) by `ledger_entry.id`, the same gap-free
+//! cursor as `GET /ledger/heads/tail`, and applies each entry with its id,
+//! hashes and metadata unchanged, together with its `ledger_entry_hash`,
+//! `governance_ref`, `container_dependency` and settlement rows. An entry
+//! that does not extend the standby's chain for its container stops
+//! replication; status reports why. The standby polls every `UBL_REGION_POLL_MS` (default 500)
+//! when idle and reports its cursor to the primary's `region_standby`.
+//! Policies, identities and sessions are not streamed.
+//!
+//! **Checkpoints.** The primary writes the head of every container every
+//! `UBL_REGION_CHECKPOINT_SECS` (default 30) when the ledger moved; the
+//! standby replicates them. Promotion refuses unless the standby holds
+//! every head of the last checkpoint it received, with the same entry hash.
+//!
+//! **Fencing.** `epoch` is the fencing token. Promotion bumps it and, if the
+//! old primary's database is reachable, fences it there. Appends read the
+//! role inside their transaction and fail with `Fenced` off a primary, so
+//! a fenced region never writes again, whatever it has in memory. A commit
+//! carrying `X-UBL-Fence` newer than this region's epoch fences it on the
+//! spot; accepted commits answer the epoch in the same header, so clients
+//! and routers that send it back cannot land writes on a stale primary.
+//! A fenced region rejoins by being restored from the new primary.
+
+use std::sync::RwLock;
+use std::time::Duration;
+
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    routing::{get, post},
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use tracing::{error, info, warn};
+
+use crate::auth::{rbac, session_policy};
+use crate::entry_hash;
+use crate::region_db::{self, CheckpointRow, ContainerLag, FeedEntry, Head, NodeRow};
+use crate::AppState;
+
+/// Fencing token header, on commit requests and responses
+pub const FENCE_HEADER: &str = "x-ubl-fence";
+
+const FEED_BATCH: i64 = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    Primary,
+    Standby,
+    Fenced,
+}
+
+impl Role {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Role::Primary => "primary",
+            Role::Standby => "standby",
+            Role::Fenced => "fenced",
+        }
+    }
+}
+
+impl std::str::FromStr for Role {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "primary" => Ok(Role::Primary),
+            "standby" => Ok(Role::Standby),
+            "fenced" => Ok(Role::Fenced),
+            other => Err(format!("unknown region role: {}", other)),
+        }
+    }
+}
+
+/// This region as last read from `region_node`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Node {
+    role: Role,
+    epoch: i64,
+}
+
+pub struct Region {
+    pub name: String,
+    node: RwLock<Node>,
+    /// The primary's database, on a standby
+    primary: Option<PgPool>,
+    /// Held while a batch is applied; promotion takes it to stop the stream
+    apply: tokio::sync::Mutex<()>,
+    /// Why replication stopped, if it did
+    halted: RwLock<Option<String>>,
+    poll: Duration,
+    checkpoint_every: Duration,
+}
+
+impl Region {
+    /// Region from `UBL_REGION` (unset: single region, nothing here applies)
+    pub async fn from_env(pool: &PgPool) -> anyhow::Result<Option<Self>> {
+        let Some(name) = std::env::var("UBL_REGION").ok().filter(|v| !v.is_empty()) else {
+            return Ok(None);
+        };
+        let seed: Role = std::env::var("UBL_REGION_ROLE")
+            .unwrap_or_else(|_| "primary".to_string())
+            .parse()
+            .map_err(|e: String| anyhow::anyhow!(e))?;
+        if seed == Role::Fenced {
+            anyhow::bail!("UBL_REGION_ROLE must be primary or standby");
+        }
+        let ms = |name: &str, default: u64| -> anyhow::Result<u64> {
+            match std::env::var(name) {
+                Ok(v) => v.parse().map_err(|_| anyhow::anyhow!("invalid {}: {}", name, v)),
+                Err(_) => Ok(default),
+            }
+        };
+        let poll = Duration::from_millis(ms("UBL_REGION_POLL_MS", 500)?);
+        let checkpoint_every = Duration::from_secs(ms("UBL_REGION_CHECKPOINT_SECS", 30)?.max(1));
+
+        let row = region_db::init_node(pool, &name, seed.as_str()).await?;
+        if row.region != name {
+            anyhow::bail!("database belongs to region {}, not {}", row.region, name);
+        }
+        let role: Role = row.role.parse().map_err(|e: String| anyhow::anyhow!(e))?;
+        let primary = match std::env::var("UBL_PRIMARY_DATABASE_URL").ok().filter(|v| !v.is_empty()) {
+            Some(url) => Some(PgPool::connect(&url).await?),
+            None if role == Role::Standby => anyhow::bail!("a standby region needs UBL_PRIMARY_DATABASE_URL"),
+            None => None,
+        };
+        Ok(Some(Self {
+            name,
+            node: RwLock::new(Node { role, epoch: row.epoch }),
+            primary,
+            apply: tokio::sync::Mutex::new(()),
+            halted: RwLock::new(None),
+            poll,
+            checkpoint_every,
+        }))
+    }
+
+    pub fn role(&self) -> Role {
+        self.node.read().unwrap().role
+    }
+
+    pub fn epoch(&self) -> i64 {
+        self.node.read().unwrap().epoch
+    }
+
+    fn refresh(&self, row: &NodeRow) {
+        if let Ok(role) = row.role.parse() {
+            *self.node.write().unwrap() = Node { role, epoch: row.epoch };
+        }
+    }
+
+    fn halt(&self, reason: String) {
+        error!(region = %self.name, decision = "reject", error_code = "replication_halted", reason = %reason);
+        *self.halted.write().unwrap() = Some(reason);
+    }
+}
+
+/// The `X-UBL-Fence` epoch a request carries
+pub fn fence_from_headers(headers: &HeaderMap) -> Result<Option<i64>, String> {
+    let Some(v) = headers.get(FENCE_HEADER) else {
+        return Ok(None);
+    };
+    v.to_str()
+        .ok()
+        .and_then(|v| v.trim().parse::<i64>().ok())
+        .filter(|e| *e >= 0)
+        .map(Some)
+        .ok_or_else(|| "invalid X-UBL-Fence: expected a non-negative epoch".to_string())
+}
+
+/// Whether this region may take a commit. A fence newer than the local
+/// epoch means a newer primary exists: this region fences itself.
+pub async fn admit(state: &AppState, headers: &HeaderMap) -> Result<(), (StatusCode, String)> {
+    let Some(region) = &state.region else {
+        return Ok(());
+    };
+    let fence = fence_from_headers(headers).map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    if let Some(epoch) = fence.filter(|e| *e > region.epoch()) {
+        fence_self(&state.pool, region, epoch)
+            .await
+            .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+        return Err((
+            StatusCode::CONFLICT,
+            format!("region {} is fenced: a primary at epoch {} exists", region.name, epoch),
+        ));
+    }
+    match region.role() {
+        Role::Primary => Ok(()),
+        role => Err((
+            StatusCode::SERVICE_UNAVAILABLE,
+            format!("region {} is {}; commits go to the primary", region.name, role.as_str()),
+        )),
+    }
+}
+
+async fn fence_self(pool: &PgPool, region: &Region, epoch: i64) -> sqlx::Result<()> {
+    if region_db::fence(pool, epoch).await? {
+        warn!(region = %region.name, fenced_by = epoch, "region fenced");
+    }
+    if let Some(row) = region_db::node(pool).await? {
+        region.refresh(&row);
+    }
+    Ok(())
+}
+
+/// Why `entry` does not extend a chain whose head is `head`
+pub fn check_extends(head: Option<&(i64, String)>, entry: &FeedEntry) -> Result<(), String> {
+    let (sequence, hash) = match head {
+        Some((seq, hash)) => (seq + 1, hash.as_str()),
+        None => (1, entry_hash::GENESIS),
+    };
+    if entry.sequence != sequence || entry.previous_hash != hash {
+        return Err(format!(
+            "{}#{} (previous {}) does not follow the local head #{} {}",
+            entry.container_id,
+            entry.sequence,
+            entry.previous_hash,
+            sequence - 1,
+            hash
+        ));
+    }
+    Ok(())
+}
+
+/// A checkpoint head the standby does not hold as the primary wrote it
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct HeadMismatch {
+    pub container_id: String,
+    pub sequence: i64,
+    pub expected: String,
+    /// Entry hash held at that sequence, if any
+    pub found: Option<String>,
+}
+
+/// Checkpoint heads missing from, or different in, `held`
+/// (`(container_id, sequence, entry_hash)` at the checkpoint's positions)
+pub fn verify_heads(heads: &[Head], held: &[(String, i64, String)]) -> Vec<HeadMismatch> {
+    let held: std::collections::HashMap<(&str, i64), &str> =
+        held.iter().map(|(c, s, h)| ((c.as_str(), *s), h.as_str())).collect();
+    heads
+        .iter()
+        .filter_map(|head| {
+            let found = held.get(&(head.container_id.as_str(), head.sequence)).copied();
+            (found != Some(head.entry_hash.as_str())).then(|| HeadMismatch {
+                container_id: head.container_id.clone(),
+                sequence: head.sequence,
+                expected: head.entry_hash.clone(),
+                found: found.map(str::to_string),
+            })
+        })
+        .collect()
+}
+
+// ----------------------------------------------------------------------------
+// Background: checkpoints on the primary, the export stream on a standby
+// ----------------------------------------------------------------------------
+
+pub fn spawn(state: AppState) {
+    let Some(region) = state.region.clone() else {
+        return;
+    };
+    info!(
+        "🌍 Region {}: {} at epoch {}",
+        region.name,
+        region.role().as_str(),
+        region.epoch()
+    );
+    tokio::spawn(async move {
+        let mut last_checkpoint = 0;
+        let mut tick = tokio::time::interval(region.checkpoint_every);
+        loop {
+            // The row is the truth: a fence may have been written from elsewhere
+            match region_db::node(&state.pool).await {
+                Ok(Some(row)) => region.refresh(&row),
+                Ok(None) => {}
+                Err(e) => error!("region state read failed: {}", e),
+            }
+            match region.role() {
+                Role::Primary => {
+                    tick.tick().await;
+                    match checkpoint(&state.pool, &region, last_checkpoint).await {
+                        Ok(cursor) => last_checkpoint = cursor,
+                        Err(e) => error!("region checkpoint failed: {}", e),
+                    }
+                }
+                Role::Standby => {
+                    let applied = match replicate(&state.pool, &region).await {
+                        Ok(n) => n,
+                        Err(e) => {
+                            warn!("region replication pass failed: {}", e);
+                            0
+                        }
+                    };
+                    if applied < FEED_BATCH as usize {
+                        tokio::time::sleep(region.poll).await;
+                    }
+                }
+                Role::Fenced => tokio::time::sleep(region.checkpoint_every).await,
+            }
+        }
+    });
+}
+
+/// Write a checkpoint at the settled cursor if the ledger moved since `last`
+async fn checkpoint(pool: &PgPool, region: &Region, last: i64) -> sqlx::Result<i64> {
+    let cursor = region_db::settled_cursor(pool).await?;
+    if cursor == last {
+        return Ok(cursor);
+    }
+    let heads = region_db::heads_at(pool, cursor).await?;
+    region_db::insert_checkpoint(pool, region.epoch(), cursor, &region.name, &heads).await?;
+    Ok(cursor)
+}
+
+/// One pass of the export stream; returns the entries applied
+async fn replicate(pool: &PgPool, region: &Region) -> anyhow::Result<usize> {
+    let Some(primary) = &region.primary else {
+        return Ok(0);
+    };
+    if region.halted.read().unwrap().is_some() {
+        return Ok(0);
+    }
+    let _apply = region.apply.lock().await;
+    // Promotion may have won the lock
+    if region.role() != Role::Standby {
+        return Ok(0);
+    }
+
+    if let Some(row) = region_db::node(primary).await? {
+        region_db::adopt_epoch(pool, row.epoch).await?;
+    }
+    let cursor = region_db::applied_cursor(pool).await?;
+    let entries = region_db::feed_after(primary, cursor, FEED_BATCH).await?;
+    if !entries.is_empty() {
+        let companions = region_db::companions(primary, &entries).await?;
+        let mut tx = pool.begin().await?;
+        let mut heads: std::collections::HashMap<String, Option<(i64, String)>> = Default::default();
+        for entry in &entries {
+            let head = match heads.get(&entry.container_id) {
+                Some(h) => h.clone(),
+                None => region_db::head(&mut *tx, &entry.container_id).await?,
+            };
+            if let Err(reason) = check_extends(head.as_ref(), entry) {
+                drop(tx);
+                region.halt(reason);
+                return Ok(0);
+            }
+            region_db::insert_entry(&mut *tx, &entry.row).await?;
+            heads.insert(entry.container_id.clone(), Some((entry.sequence, entry.entry_hash.clone())));
+        }
+        for (table, rows) in &companions {
+            region_db::insert_companions(&mut *tx, table, rows).await?;
+        }
+        tx.commit().await?;
+    }
+
+    // Checkpoints up to what is now held
+    let last = region_db::last_checkpoint(pool).await?;
+    let (epoch, cursor_id) = last.map(|c| (c.epoch, c.cursor_id)).unwrap_or((-1, 0));
+    let applied = region_db::applied_cursor(pool).await?;
+    for cp in region_db::checkpoints_after(primary, epoch, cursor_id).await? {
+        if cp.cursor_id > applied {
+            break;
+        }
+        let heads = cp.heads().map_err(anyhow::Error::from)?;
+        region_db::insert_checkpoint(pool, cp.epoch, cp.cursor_id, &cp.region, &heads).await?;
+    }
+    region_db::ack(primary, &region.name, region.epoch(), applied).await?;
+    Ok(entries.len())
+}
+
+// ----------------------------------------------------------------------------
+// Routes
+// ----------------------------------------------------------------------------
+
+#[derive(Debug, Serialize)]
+pub struct StandbyStatus {
+    pub region: String,
+    pub epoch: i64,
+    pub applied_id: i64,
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated_at: time::OffsetDateTime,
+    /// Containers with entries the standby has not applied
+    pub lag: Vec<ContainerLag>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StatusResp {
+    pub region: String,
+    pub role: Role,
+    pub epoch: i64,
+    pub fenced_by: Option<i64>,
+    /// Highest feed cursor held
+    pub applied_id: i64,
+    pub last_checkpoint: Option<CheckpointRow>,
+    /// Standby: why the export stream stopped
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub halted: Option<String>,
+    /// Standby: containers behind the primary (only those behind)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lag: Option<Vec<ContainerLag>>,
+    /// Primary: each standby's last report and its lag per container
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub standbys: Option<Vec<StandbyStatus>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PromoteResp {
+    pub promoted: bool,
+    pub region: String,
+    pub epoch: i64,
+    pub checkpoint: Option<CheckpointRow>,
+    /// Checkpoint heads not held as written
+    pub mismatches: Vec<HeadMismatch>,
+    /// Whether the old primary's database took the fence
+    pub old_primary_fenced: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FenceReq {
+    /// Epoch of the primary that supersedes this region
+    pub epoch: i64,
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/admin/region/status", get(route_status))
+        .route("/admin/region/promote", post(route_promote))
+        .route("/admin/region/fence", post(route_fence))
+}
+
+fn internal(e: impl ToString) -> (StatusCode, String) {
+    (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+}
+
+fn region(state: &AppState) -> Result<&Region, (StatusCode, String)> {
+    state
+        .region
+        .as_deref()
+        .ok_or((StatusCode::NOT_FOUND, "region mode is off (UBL_REGION unset)".to_string()))
+}
+
+/// GET /admin/region/status
+async fn route_status(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<StatusResp>, (StatusCode, String)> {
+    rbac::require_role(&state.pool, &headers, &[rbac::ADMIN, rbac::OPERATOR, rbac::AUDITOR]).await?;
+    let region = region(&state)?;
+    let node = region_db::node(&state.pool)
+        .await
+        .map_err(internal)?
+        .ok_or_else(|| internal("region_node row missing"))?;
+    region.refresh(&node);
+    let applied_id = region_db::applied_cursor(&state.pool).await.map_err(internal)?;
+    let mut resp = StatusResp {
+        region: region.name.clone(),
+        role: region.role(),
+        epoch: node.epoch,
+        fenced_by: node.fenced_by,
+        applied_id,
+        last_checkpoint: region_db::last_checkpoint(&state.pool).await.map_err(internal)?,
+        halted: region.halted.read().unwrap().clone(),
+        lag: None,
+        standbys: None,
+    };
+    match (resp.role, &region.primary) {
+        (Role::Standby, Some(primary)) => {
+            resp.lag = Some(region_db::lag_after(primary, applied_id).await.map_err(internal)?);
+        }
+        (Role::Primary, _) => {
+            let mut standbys = Vec::new();
+            for s in region_db::standbys(&state.pool).await.map_err(internal)? {
+                standbys.push(StandbyStatus {
+                    lag: region_db::lag_after(&state.pool, s.applied_id).await.map_err(internal)?,
+                    region: s.region,
+                    epoch: s.epoch,
+                    applied_id: s.applied_id,
+                    updated_at: s.updated_at,
+                });
+            }
+            resp.standbys = Some(standbys);
+        }
+        _ => {}
+    }
+    Ok(Json(resp))
+}
+
+/// POST /admin/region/promote
+async fn route_promote(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<(StatusCode, Json<PromoteResp>), (StatusCode, String)> {
+    let caller = rbac::require_role(&state.pool, &headers, &[rbac::ADMIN]).await?;
+    let region = region(&state)?;
+    // The export stream stays stopped from here on
+    let _apply = region.apply.lock().await;
+    if region.role() != Role::Standby {
+        return Err((
+            StatusCode::CONFLICT,
+            format!("region {} is {}, not a standby", region.name, region.role().as_str()),
+        ));
+    }
+
+    let checkpoint = region_db::last_checkpoint(&state.pool).await.map_err(internal)?;
+    let Some(cp) = checkpoint else {
+        return Err((StatusCode::CONFLICT, "no primary checkpoint replicated yet".to_string()));
+    };
+    let heads = cp.heads().map_err(internal)?;
+    let held = region_db::entry_hashes_at(&state.pool, &heads).await.map_err(internal)?;
+    let mismatches = verify_heads(&heads, &held);
+    if !mismatches.is_empty() {
+        warn!(
+            region = %region.name,
+            checkpoint = cp.cursor_id,
+            mismatches = mismatches.len(),
+            decision = "reject",
+            error_code = "checkpoint_mismatch"
+        );
+        return Ok((
+            StatusCode::CONFLICT,
+            Json(PromoteResp {
+                promoted: false,
+                region: region.name.clone(),
+                epoch: region.epoch(),
+                checkpoint: Some(cp),
+                mismatches,
+                old_primary_fenced: false,
+            }),
+        ));
+    }
+
+    let mut tx = state.pool.begin().await.map_err(internal)?;
+    let epoch = region_db::promote(&mut tx)
+        .await
+        .map_err(internal)?
+        .ok_or_else(|| (StatusCode::CONFLICT, "region is no longer a standby".to_string()))?;
+    tx.commit().await.map_err(internal)?;
+    if let Some(row) = region_db::node(&state.pool).await.map_err(internal)? {
+        region.refresh(&row);
+    }
+
+    // Best effort: the old primary may be the region that was lost
+    let old_primary_fenced = match &region.primary {
+        Some(primary) => {
+            match tokio::time::timeout(Duration::from_secs(5), region_db::fence(primary, epoch)).await {
+                Ok(Ok(fenced)) => fenced,
+                Ok(Err(e)) => {
+                    warn!("old primary not fenced: {}", e);
+                    false
+                }
+                Err(_) => {
+                    warn!("old primary not fenced: timed out");
+                    false
+                }
+            }
+        }
+        None => false,
+    };
+    if let Err(e) = checkpoint(&state.pool, region, -1).await {
+        error!("first checkpoint after promotion failed: {}", e);
+    }
+
+    info!(
+        "👑 REGION {} promoted to primary epoch={} checkpoint={} old_primary_fenced={} by={}",
+        region.name, epoch, cp.cursor_id, old_primary_fenced, caller.session.sid
+    );
+    session_policy::after_action(&state.pool, &caller.session, session_policy::RISK_L4).await;
+    Ok((
+        StatusCode::OK,
+        Json(PromoteResp {
+            promoted: true,
+            region: region.name.clone(),
+            epoch,
+            checkpoint: Some(cp),
+            mismatches: Vec::new(),
+            old_primary_fenced,
+        }),
+    ))
+}
+
+/// POST /admin/region/fence
+async fn route_fence(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<FenceReq>,
+) -> Result<Json<NodeRow>, (StatusCode, String)> {
+    let caller = rbac::require_role(&state.pool, &headers, &[rbac::ADMIN]).await?;
+    let region = region(&state)?;
+    if req.epoch <= region.epoch() {
+        return Err((
+            StatusCode::CONFLICT,
+            format!("epoch {} does not supersede this region's epoch {}", req.epoch, region.epoch()),
+        ));
+    }
+    fence_self(&state.pool, region, req.epoch).await.map_err(internal)?;
+    info!("🚧 REGION {} fenced by epoch {} by={}", region.name, req.epoch, caller.session.sid);
+    session_policy::after_action(&state.pool, &caller.session, session_policy::RISK_L4).await;
+    region_db::node(&state.pool)
+        .await
+        .map_err(internal)?
+        .map(Json)
+        .ok_or_else(|| internal("region_node row missing"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(container_id: &str, sequence: i64, previous_hash: &str) -> FeedEntry {
+        FeedEntry {
+            id: sequence,
+            container_id: container_id.to_string(),
+            sequence,
+            previous_hash: previous_hash.to_string(),
+            entry_hash: format!("h{}", sequence),
+            row: serde_json::Value::Null,
+        }
+    }
+
+    #[test]
+    fn test_check_extends() {
+        assert!(check_extends(None, &entry("C.A", 1, entry_hash::GENESIS)).is_ok());
+        assert!(check_extends(None, &entry("C.A", 2, "h1")).is_err());
+        let head = (1, "h1".to_string());
+        assert!(check_extends(Some(&head), &entry("C.A", 2, "h1")).is_ok());
+        assert!(check_extends(Some(&head), &entry("C.A", 2, "hx")).is_err());
+        assert!(check_extends(Some(&head), &entry("C.A", 3, "h1")).is_err());
+    }
+
+    #[test]
+    fn test_verify_heads() {
+        let head = |c: &str, s, h: &str| Head {
+            container_id: c.to_string(),
+            sequence: s,
+            entry_hash: h.to_string(),
+        };
+        let heads = [head("C.A", 3, "a3"), head("C.B", 1, "b1"), head("C.C", 2, "c2")];
+        let held = vec![
+            ("C.A".to_string(), 3, "a3".to_string()),
+            ("C.B".to_string(), 1, "forked".to_string()),
+        ];
+        let mismatches = verify_heads(&heads, &held);
+        assert_eq!(mismatches.len(), 2);
+        assert_eq!(mismatches[0].found.as_deref(), Some("forked"));
+        assert_eq!((mismatches[1].container_id.as_str(), mismatches[1].found.as_deref()), ("C.C", None));
+        assert!(verify_heads(&heads[..1], &held).is_empty());
+    }
+
+    #[test]
+    fn test_fence_header() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(fence_from_headers(&headers), Ok(None));
+        headers.insert(FENCE_HEADER, "7".parse().unwrap());
+        assert_eq!(fence_from_headers(&headers), Ok(Some(7)));
+        for bad in ["-1", "x", ""] {
+            headers.insert(FENCE_HEADER, bad.parse().unwrap());
+            assert!(fence_from_headers(&headers).is_err(), "{}", bad);
+        }
+    }
+}
@@ -0,0 +1,390 @@
+//! Multi-region state (tables `region_node`, `region_checkpoint`,
+//! `region_standby`, sql/039_region.sql) and the replication feed
+
+use serde::{Deserialize, Serialize};
+use sqlx::{PgExecutor, PgPool, Postgres, Transaction};
+use time::OffsetDateTime;
+
+/// The single `region_node` row
+#[derive(Debug, Clone, Serialize)]
+pub struct NodeRow {
+    pub region: String,
+    pub role: String,
+    pub epoch: i64,
+    pub fenced_by: Option<i64>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated_at: OffsetDateTime,
+}
+
+/// One container's head in a checkpoint
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Head {
+    pub container_id: String,
+    pub sequence: i64,
+    pub entry_hash: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CheckpointRow {
+    pub epoch: i64,
+    pub cursor_id: i64,
+    pub region: String,
+    #[serde(skip)]
+    pub heads: serde_json::Value,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+impl CheckpointRow {
+    pub fn heads(&self) -> Result<Vec<Head>, serde_json::Error> {
+        serde_json::from_value(self.heads.clone())
+    }
+}
+
+/// A ledger entry as the feed ships it: the chain fields, and the whole row
+#[derive(Debug, Clone)]
+pub struct FeedEntry {
+    pub id: i64,
+    pub container_id: String,
+    pub sequence: i64,
+    pub previous_hash: String,
+    pub entry_hash: String,
+    pub row: serde_json::Value,
+}
+
+/// Entries behind on one container
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct ContainerLag {
+    pub container_id: String,
+    pub entries_behind: i64,
+    /// Head sequence on the primary
+    pub primary_sequence: i64,
+    /// Age of the oldest entry not yet applied
+    pub lag_ms: i64,
+}
+
+/// What a standby last reported to the primary
+#[derive(Debug, Clone, Serialize)]
+pub struct StandbyRow {
+    pub region: String,
+    pub epoch: i64,
+    pub applied_id: i64,
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated_at: OffsetDateTime,
+}
+
+/// Rows keyed by a ledger entry that travel with it, as
+/// (table, container column, sequence expression over `t`)
+const COMPANIONS: [(&str, &str, &str); 7] = [
+    ("ledger_entry_hash", "container_id", "t.sequence"),
+    ("governance_ref", "governance_container_id", "t.sequence"),
+    ("container_dependency", "container_id", "1"),
+    ("container_authority", "container_id", "1"),
+    ("container_spec", "container_id", "1"),
+    ("settlement", "merchant_container_id", "t.credit_sequence"),
+    ("settlement_item", "container_id", "t.sequence"),
+];
+
+pub async fn node(conn: impl PgExecutor<'_>) -> sqlx::Result<Option<NodeRow>> {
+    sqlx::query_as!(
+        NodeRow,
+        "SELECT region, role, epoch, fenced_by, updated_at FROM region_node"
+    )
+    .fetch_optional(conn)
+    .await
+}
+
+/// Create the row on first start; an existing row keeps its role
+pub async fn init_node(pool: &PgPool, region: &str, role: &str) -> sqlx::Result<NodeRow> {
+    sqlx::query!(
+        "INSERT INTO region_node (region, role) VALUES ($1, $2) ON CONFLICT DO NOTHING",
+        region,
+        role
+    )
+    .execute(pool)
+    .await?;
+    sqlx::query_as!(
+        NodeRow,
+        "SELECT region, role, epoch, fenced_by, updated_at FROM region_node"
+    )
+    .fetch_one(pool)
+    .await
+}
+
+/// Role that appends must see, read inside the append transaction.
+/// `None` when region mode is off.
+pub async fn role(conn: impl PgExecutor<'_>) -> sqlx::Result<Option<String>> {
+    sqlx::query_scalar!("SELECT role FROM region_node FOR SHARE")
+        .fetch_optional(conn)
+        .await
+}
+
+/// Follow the primary's epoch while replicating
+pub async fn adopt_epoch(pool: &PgPool, epoch: i64) -> sqlx::Result<()> {
+    sqlx::query!(
+        "UPDATE region_node SET epoch = $1, updated_at = now() WHERE role = 'standby' AND epoch < $1",
+        epoch
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Standby to primary at the next epoch; returns it
+pub async fn promote(tx: &mut Transaction<'_, Postgres>) -> sqlx::Result<Option<i64>> {
+    // Replicated rows kept the primary's ids; new appends continue after them
+    sqlx::query("SELECT setval(pg_get_serial_sequence('ledger_entry', 'id'), COALESCE(MAX(id), 0) + 1, false) FROM ledger_entry")
+        .execute(&mut **tx)
+        .await?;
+    sqlx::query_scalar!(
+        r#"UPDATE region_node SET role = 'primary', epoch = epoch + 1, fenced_by = NULL, updated_at = now()
+           WHERE role = 'standby'
+           RETURNING epoch"#
+    )
+    .fetch_optional(&mut **tx)
+    .await
+}
+
+/// Fence this database's region for a primary at `epoch`; false if it was
+/// already at or beyond it
+pub async fn fence(pool: &PgPool, epoch: i64) -> sqlx::Result<bool> {
+    let r = sqlx::query!(
+        r#"UPDATE region_node SET role = 'fenced', fenced_by = $1, updated_at = now()
+           WHERE epoch < $1 AND (fenced_by IS NULL OR fenced_by < $1)"#,
+        epoch
+    )
+    .execute(pool)
+    .await?;
+    Ok(r.rows_affected() == 1)
+}
+
+/// Highest feed cursor whose entries can no longer be overtaken by a slower
+/// transaction (same one-second rule as `db::heads_after`)
+pub async fn settled_cursor(pool: &PgPool) -> sqlx::Result<i64> {
+    let id = sqlx::query_scalar!(
+        "SELECT COALESCE(MAX(id), 0) FROM ledger_entry WHERE created_at <= now() - interval '1 second'"
+    )
+    .fetch_one(pool)
+    .await?;
+    Ok(id.unwrap_or(0))
+}
+
+/// Highest feed cursor held
+pub async fn applied_cursor(conn: impl PgExecutor<'_>) -> sqlx::Result<i64> {
+    let id = sqlx::query_scalar!("SELECT COALESCE(MAX(id), 0) FROM ledger_entry")
+        .fetch_one(conn)
+        .await?;
+    Ok(id.unwrap_or(0))
+}
+
+/// Heads of every container as of `cursor_id`
+pub async fn heads_at(pool: &PgPool, cursor_id: i64) -> sqlx::Result<Vec<Head>> {
+    sqlx::query_as!(
+        Head,
+        r#"SELECT DISTINCT ON (container_id) container_id, sequence, entry_hash
+           FROM ledger_entry
+           WHERE id <= $1
+           ORDER BY container_id, sequence DESC"#,
+        cursor_id
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Original-format entry hashes at the given positions, as
+/// (container_id, sequence, entry_hash)
+pub async fn entry_hashes_at(pool: &PgPool, heads: &[Head]) -> sqlx::Result<Vec<(String, i64, String)>> {
+    let ids: Vec<String> = heads.iter().map(|h| h.container_id.clone()).collect();
+    let seqs: Vec<i64> = heads.iter().map(|h| h.sequence).collect();
+    let rows = sqlx::query!(
+        r#"SELECT e.container_id, e.sequence, e.entry_hash
+           FROM ledger_entry e
+           JOIN UNNEST($1::text[], $2::bigint[]) AS p(container_id, sequence)
+             ON p.container_id = e.container_id AND p.sequence = e.sequence"#,
+        &ids,
+        &seqs
+    )
+    .fetch_all(pool)
+    .await?;
+    Ok(rows.into_iter().map(|r| (r.container_id, r.sequence, r.entry_hash)).collect())
+}
+
+pub async fn insert_checkpoint(
+    conn: impl PgExecutor<'_>,
+    epoch: i64,
+    cursor_id: i64,
+    region: &str,
+    heads: &[Head],
+) -> sqlx::Result<bool> {
+    let heads = serde_json::to_value(heads).unwrap_or_default();
+    let r = sqlx::query!(
+        r#"INSERT INTO region_checkpoint (epoch, cursor_id, region, heads)
+           VALUES ($1, $2, $3, $4) ON CONFLICT DO NOTHING"#,
+        epoch,
+        cursor_id,
+        region,
+        heads
+    )
+    .execute(conn)
+    .await?;
+    Ok(r.rows_affected() == 1)
+}
+
+pub async fn last_checkpoint(conn: impl PgExecutor<'_>) -> sqlx::Result<Option<CheckpointRow>> {
+    sqlx::query_as!(
+        CheckpointRow,
+        r#"SELECT epoch, cursor_id, region, heads, created_at FROM region_checkpoint
+           ORDER BY epoch DESC, cursor_id DESC LIMIT 1"#
+    )
+    .fetch_optional(conn)
+    .await
+}
+
+/// Checkpoints written after (`epoch`, `cursor_id`), oldest first
+pub async fn checkpoints_after(pool: &PgPool, epoch: i64, cursor_id: i64) -> sqlx::Result<Vec<CheckpointRow>> {
+    sqlx::query_as!(
+        CheckpointRow,
+        r#"SELECT epoch, cursor_id, region, heads, created_at FROM region_checkpoint
+           WHERE (epoch, cursor_id) > ($1, $2)
+           ORDER BY epoch, cursor_id"#,
+        epoch,
+        cursor_id
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Latest checkpoint `region` wrote at or before `at`
+pub async fn checkpoint_before(pool: &PgPool, region: &str, at: OffsetDateTime) -> sqlx::Result<Option<CheckpointRow>> {
+    sqlx::query_as!(
+        CheckpointRow,
+        r#"SELECT epoch, cursor_id, region, heads, created_at FROM region_checkpoint
+           WHERE region = $1 AND created_at <= $2
+           ORDER BY created_at DESC LIMIT 1"#,
+        region,
+        at
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+/// Earliest checkpoint `region` wrote at or after `at`
+pub async fn checkpoint_after(pool: &PgPool, region: &str, at: OffsetDateTime) -> sqlx::Result<Option<CheckpointRow>> {
+    sqlx::query_as!(
+        CheckpointRow,
+        r#"SELECT epoch, cursor_id, region, heads, created_at FROM region_checkpoint
+           WHERE region = $1 AND created_at >= $2
+           ORDER BY created_at LIMIT 1"#,
+        region,
+        at
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+/// Settled entries after feed cursor `after_id`, oldest first
+pub async fn feed_after(pool: &PgPool, after_id: i64, limit: i64) -> sqlx::Result<Vec<FeedEntry>> {
+    sqlx::query_as!(
+        FeedEntry,
+        r#"SELECT id, container_id, sequence, previous_hash, entry_hash, to_jsonb(e) AS "row!"
+           FROM ledger_entry e
+           WHERE id > $1 AND created_at <= now() - interval '1 second'
+           ORDER BY id
+           LIMIT $2"#,
+        after_id,
+        limit
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Head of one container: (sequence, entry_hash)
+pub async fn head(conn: impl PgExecutor<'_>, container_id: &str) -> sqlx::Result<Option<(i64, String)>> {
+    let r = sqlx::query!(
+        "SELECT sequence, entry_hash FROM ledger_entry WHERE container_id = $1 ORDER BY sequence DESC LIMIT 1",
+        container_id
+    )
+    .fetch_optional(conn)
+    .await?;
+    Ok(r.map(|r| (r.sequence, r.entry_hash)))
+}
+
+/// Insert a replicated entry as the primary wrote it, id included
+pub async fn insert_entry(conn: impl PgExecutor<'_>, row: &serde_json::Value) -> sqlx::Result<()> {
+    sqlx::query("INSERT INTO ledger_entry SELECT * FROM jsonb_populate_record(NULL::ledger_entry, $1)")
+        .bind(row)
+        .execute(conn)
+        .await?;
+    Ok(())
+}
+
+/// Companion rows of the given entries on the primary, per table
+pub async fn companions(pool: &PgPool, entries: &[FeedEntry]) -> sqlx::Result<Vec<(&'static str, serde_json::Value)>> {
+    let ids: Vec<String> = entries.iter().map(|e| e.container_id.clone()).collect();
+    let seqs: Vec<i64> = entries.iter().map(|e| e.sequence).collect();
+    let mut out = Vec::new();
+    for (table, container_col, sequence_col) in COMPANIONS {
+        let rows: serde_json::Value = sqlx::query_scalar(&format!(
+            r#"SELECT COALESCE(jsonb_agg(to_jsonb(t)), '[]'::jsonb) FROM {table} t
+               JOIN UNNEST($1::text[], $2::bigint[]) AS p(container_id, sequence)
+                 ON p.container_id = t.{container_col} AND p.sequence = {sequence_col}"#
+        ))
+        .bind(&ids)
+        .bind(&seqs)
+        .fetch_one(pool)
+        .await?;
+        out.push((table, rows));
+    }
+    Ok(out)
+}
+
+pub async fn insert_companions(conn: impl PgExecutor<'_>, table: &str, rows: &serde_json::Value) -> sqlx::Result<()> {
+    sqlx::query(&format!(
+        "INSERT INTO {table} SELECT * FROM jsonb_populate_recordset(NULL::{table}, $1) ON CONFLICT DO NOTHING"
+    ))
+    .bind(rows)
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+/// Record on the primary how far a standby has applied
+pub async fn ack(primary: &PgPool, region: &str, epoch: i64, applied_id: i64) -> sqlx::Result<()> {
+    sqlx::query!(
+        r#"INSERT INTO region_standby (region, epoch, applied_id) VALUES ($1, $2, $3)
+           ON CONFLICT (region) DO UPDATE SET epoch = $2, applied_id = $3, updated_at = now()"#,
+        region,
+        epoch,
+        applied_id
+    )
+    .execute(primary)
+    .await?;
+    Ok(())
+}
+
+pub async fn standbys(pool: &PgPool) -> sqlx::Result<Vec<StandbyRow>> {
+    sqlx::query_as!(
+        StandbyRow,
+        "SELECT region, epoch, applied_id, updated_at FROM region_standby ORDER BY region"
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Per container, entries on `primary` past feed cursor `applied_id`
+pub async fn lag_after(primary: &PgPool, applied_id: i64) -> sqlx::Result<Vec<ContainerLag>> {
+    sqlx::query_as!(
+        ContainerLag,
+        r#"SELECT container_id,
+                  COUNT(*) AS "entries_behind!",
+                  MAX(sequence) AS "primary_sequence!",
+                  GREATEST(0, (EXTRACT(EPOCH FROM now()) * 1000)::bigint - MIN(ts_unix_ms)) AS "lag_ms!"
+           FROM ledger_entry
+           WHERE id > $1
+           GROUP BY container_id
+           ORDER BY 4 DESC, container_id"#,
+        applied_id
+    )
+    .fetch_all(primary)
+    .await
+}
@@ -0,0 +1,182 @@
+//! Entry hashes in later formats (tables `ledger_entry_hash` and
+//! `ledger_hash_format`, sql/032_entry_hash_formats.sql)
+
+use serde::Serialize;
+use sqlx::{PgExecutor, PgPool};
+use time::OffsetDateTime;
+
+use crate::entry_hash::{EntryFields, HashFormat, StoredHash};
+
+/// The single `ledger_hash_format` row
+#[derive(Debug, Clone, Serialize)]
+pub struct FormatState {
+    /// Format the ledger read path serves
+    pub read_format: String,
+    /// Format being backfilled; appends write it too
+    pub target_format: Option<String>,
+    pub verified_format: Option<String>,
+    pub verified_entries: Option<i64>,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub verified_at: Option<OffsetDateTime>,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub flipped_at: Option<OffsetDateTime>,
+    pub flipped_by: Option<String>,
+}
+
+/// A ledger entry with its hashes in the original format and, if computed, in another
+#[derive(Debug, Clone)]
+pub struct ChainRow {
+    pub container_id: String,
+    pub sequence: i64,
+    pub link_hash: String,
+    pub previous_hash: String,
+    pub entry_hash: String,
+    pub ts_unix_ms: i64,
+    pub alt_previous_hash: Option<String>,
+    pub alt_entry_hash: Option<String>,
+}
+
+impl ChainRow {
+    pub fn fields(&self) -> EntryFields<'_> {
+        EntryFields {
+            container_id: &self.container_id,
+            sequence: self.sequence,
+            link_hash: &self.link_hash,
+            ts_unix_ms: self.ts_unix_ms,
+        }
+    }
+
+    /// Stored hashes in `format`, where `format` is the one the row was loaded with
+    pub fn stored(&self, format: HashFormat) -> Option<StoredHash> {
+        if format == HashFormat::ORIGINAL {
+            return Some(StoredHash {
+                previous_hash: self.previous_hash.clone(),
+                entry_hash: self.entry_hash.clone(),
+            });
+        }
+        Some(StoredHash {
+            previous_hash: self.alt_previous_hash.clone()?,
+            entry_hash: self.alt_entry_hash.clone()?,
+        })
+    }
+}
+
+pub async fn state(conn: impl PgExecutor<'_>) -> sqlx::Result<FormatState> {
+    sqlx::query_as!(
+        FormatState,
+        r#"SELECT read_format, target_format, verified_format, verified_entries, verified_at,
+                  flipped_at, flipped_by
+           FROM ledger_hash_format"#
+    )
+    .fetch_one(conn)
+    .await
+}
+
+/// Read format and backfill target, falling back to the original format on bad values
+pub async fn formats(conn: impl PgExecutor<'_>) -> sqlx::Result<(HashFormat, Option<HashFormat>)> {
+    let r = sqlx::query!("SELECT read_format, target_format FROM ledger_hash_format")
+        .fetch_optional(conn)
+        .await?;
+    Ok(match r {
+        Some(r) => (
+            r.read_format.parse().unwrap_or(HashFormat::ORIGINAL),
+            r.target_format.and_then(|f| f.parse().ok()),
+        ),
+        None => (HashFormat::ORIGINAL, None),
+    })
+}
+
+pub async fn set_target(pool: &PgPool, format: HashFormat) -> sqlx::Result<()> {
+    sqlx::query!("UPDATE ledger_hash_format SET target_format = $1", format.as_str())
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+pub async fn record_verified(conn: impl PgExecutor<'_>, format: HashFormat, entries: i64) -> sqlx::Result<()> {
+    sqlx::query!(
+        "UPDATE ledger_hash_format SET verified_format = $1, verified_entries = $2, verified_at = now()",
+        format.as_str(),
+        entries
+    )
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+pub async fn flip(conn: impl PgExecutor<'_>, format: HashFormat, by: &str) -> sqlx::Result<()> {
+    sqlx::query!(
+        "UPDATE ledger_hash_format SET read_format = $1, flipped_at = now(), flipped_by = $2",
+        format.as_str(),
+        by
+    )
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+pub async fn containers(conn: impl PgExecutor<'_>) -> sqlx::Result<Vec<String>> {
+    sqlx::query_scalar!("SELECT DISTINCT container_id FROM ledger_entry ORDER BY container_id")
+        .fetch_all(conn)
+        .await
+}
+
+/// A container's entries, oldest first, with their hashes in `format`
+pub async fn chain(conn: impl PgExecutor<'_>, container_id: &str, format: HashFormat) -> sqlx::Result<Vec<ChainRow>> {
+    sqlx::query_as!(
+        ChainRow,
+        r#"SELECT e.container_id, e.sequence, e.link_hash, e.previous_hash, e.entry_hash, e.ts_unix_ms,
+                  h.previous_hash AS "alt_previous_hash?", h.entry_hash AS "alt_entry_hash?"
+           FROM ledger_entry e
+           LEFT JOIN ledger_entry_hash h
+             ON h.format = $2 AND h.container_id = e.container_id AND h.sequence = e.sequence
+           WHERE e.container_id = $1
+           ORDER BY e.sequence"#,
+        container_id,
+        format.as_str()
+    )
+    .fetch_all(conn)
+    .await
+}
+
+/// An entry's hashes in a format other than the original (`None` if not computed)
+pub async fn entry_hashes(
+    conn: impl PgExecutor<'_>,
+    format: HashFormat,
+    container_id: &str,
+    sequence: i64,
+) -> sqlx::Result<Option<StoredHash>> {
+    sqlx::query_as!(
+        StoredHash,
+        r#"SELECT previous_hash, entry_hash FROM ledger_entry_hash
+           WHERE format = $1 AND container_id = $2 AND sequence = $3"#,
+        format.as_str(),
+        container_id,
+        sequence
+    )
+    .fetch_optional(conn)
+    .await
+}
+
+/// Record an entry's hashes in `format`; an existing row is left as it is
+pub async fn insert(
+    conn: impl PgExecutor<'_>,
+    format: HashFormat,
+    container_id: &str,
+    sequence: i64,
+    hashes: &StoredHash,
+) -> sqlx::Result<bool> {
+    let r = sqlx::query!(
+        r#"INSERT INTO ledger_entry_hash (format, container_id, sequence, previous_hash, entry_hash)
+           VALUES ($1, $2, $3, $4, $5)
+           ON CONFLICT DO NOTHING"#,
+        format.as_str(),
+        container_id,
+        sequence,
+        hashes.previous_hash,
+        hashes.entry_hash
+    )
+    .execute(conn)
+    .await?;
+    Ok(r.rows_affected() == 1)
+}
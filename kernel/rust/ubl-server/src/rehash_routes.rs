@@ -0,0 +1,261 @@
+//! # Entry hash migration
+//!
+//! Moves the ledger to a new entry hash format (`entry_hash.rs`) without
+//! touching the original chain:
+//!
+//! - GET  /admin/ledger/hashes           (admin/operator/auditor) migration state
+//! - POST /admin/ledger/hashes/backfill  (admin) compute `format` for every entry
+//! - POST /admin/ledger/hashes/verify    (admin/operator/auditor) check both chains
+//! - POST /admin/ledger/hashes/flip      (admin) serve reads in `format`
+//!
+//! Backfill marks `format` as the target, so commits from then on write it
+//! too, and fills in every entry that lacks it; it is safe to re-run. Existing
+//! rows are never rewritten: a stored hash that does not recompute is reported
+//! as a conflict. Verify walks every container in the original format and in
+//! the target. Flip blocks commits, re-verifies both formats, and switches
+//! `GET /state` and commit causality to the new format in one transaction;
+//! it refuses while any chain breaks. Flipping back to `v1` is always possible.
+
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    routing::{get, post},
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::PgConnection;
+use tracing::{info, warn};
+
+use crate::auth::{rbac, session_policy};
+use crate::entry_hash::{self, ChainBreak, HashFormat, StoredHash};
+use crate::rehash_db::{self, FormatState};
+use crate::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct FormatReq {
+    pub format: HashFormat,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct VerifyReq {
+    /// Format to check beside the original; defaults to the backfill target
+    #[serde(default)]
+    pub format: Option<HashFormat>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BackfillResp {
+    pub format: HashFormat,
+    pub containers: usize,
+    pub entries: u64,
+    pub inserted: u64,
+    /// Stored hashes that do not recompute; left in place
+    pub conflicts: Vec<ChainBreak>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct VerifyResp {
+    pub ok: bool,
+    pub formats: Vec<HashFormat>,
+    pub containers: usize,
+    pub entries: u64,
+    pub breaks: Vec<ChainBreak>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FlipResp {
+    pub read_format: HashFormat,
+    pub verification: VerifyResp,
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/admin/ledger/hashes", get(route_state))
+        .route("/admin/ledger/hashes/backfill", post(route_backfill))
+        .route("/admin/ledger/hashes/verify", post(route_verify))
+        .route("/admin/ledger/hashes/flip", post(route_flip))
+}
+
+fn internal(e: impl ToString) -> (StatusCode, String) {
+    (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+}
+
+/// Verify every container in the original format and in `format`
+async fn verify_all(conn: &mut PgConnection, format: HashFormat) -> sqlx::Result<VerifyResp> {
+    let mut formats = vec![HashFormat::ORIGINAL];
+    if format != HashFormat::ORIGINAL {
+        formats.push(format);
+    }
+    let containers = rehash_db::containers(&mut *conn).await?;
+    let mut resp = VerifyResp {
+        formats: formats.clone(),
+        containers: containers.len(),
+        ..VerifyResp::default()
+    };
+    for container_id in &containers {
+        let rows = rehash_db::chain(&mut *conn, container_id, format).await?;
+        for &f in &formats {
+            match entry_hash::verify_chain(f, rows.iter().map(|r| (r.fields(), r.stored(f)))) {
+                Ok(n) if f == HashFormat::ORIGINAL => resp.entries += n,
+                Ok(_) => {}
+                Err(b) => resp.breaks.push(b),
+            }
+        }
+    }
+    resp.ok = resp.breaks.is_empty();
+    Ok(resp)
+}
+
+/// GET /admin/ledger/hashes
+async fn route_state(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<FormatState>, (StatusCode, String)> {
+    rbac::require_role(&state.pool, &headers, &[rbac::ADMIN, rbac::OPERATOR, rbac::AUDITOR]).await?;
+    Ok(Json(rehash_db::state(&state.pool).await.map_err(internal)?))
+}
+
+/// POST /admin/ledger/hashes/backfill
+async fn route_backfill(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<FormatReq>,
+) -> Result<Json<BackfillResp>, (StatusCode, String)> {
+    rbac::require_role(&state.pool, &headers, &[rbac::ADMIN]).await?;
+    let format = req.format;
+    if format == HashFormat::ORIGINAL {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("{} is the original format; ledger_entry already holds it", format),
+        ));
+    }
+
+    // Target first: commits from here on write the new format themselves
+    rehash_db::set_target(&state.pool, format).await.map_err(internal)?;
+
+    let containers = rehash_db::containers(&state.pool).await.map_err(internal)?;
+    let mut resp = BackfillResp {
+        format,
+        containers: containers.len(),
+        entries: 0,
+        inserted: 0,
+        conflicts: Vec::new(),
+    };
+    for container_id in &containers {
+        let rows = rehash_db::chain(&state.pool, container_id, format).await.map_err(internal)?;
+        let mut tx = state.pool.begin().await.map_err(internal)?;
+        let mut prev = entry_hash::GENESIS.to_string();
+        for row in &rows {
+            let computed = entry_hash::compute(format, &row.fields(), &prev);
+            match row.stored(format) {
+                None => {
+                    let hashes = StoredHash {
+                        previous_hash: prev.clone(),
+                        entry_hash: computed.clone(),
+                    };
+                    if rehash_db::insert(&mut *tx, format, container_id, row.sequence, &hashes)
+                        .await
+                        .map_err(internal)?
+                    {
+                        resp.inserted += 1;
+                    }
+                }
+                Some(stored) if stored.entry_hash != computed || stored.previous_hash != prev => {
+                    resp.conflicts.push(ChainBreak {
+                        container_id: container_id.clone(),
+                        format,
+                        sequence: row.sequence,
+                        reason: format!("stored {} recomputes to {}", stored.entry_hash, computed),
+                    });
+                }
+                Some(_) => {}
+            }
+            prev = computed;
+            resp.entries += 1;
+        }
+        tx.commit().await.map_err(internal)?;
+    }
+
+    info!(
+        "🔁 REHASH backfill format={} containers={} entries={} inserted={} conflicts={}",
+        format,
+        resp.containers,
+        resp.entries,
+        resp.inserted,
+        resp.conflicts.len()
+    );
+    Ok(Json(resp))
+}
+
+/// POST /admin/ledger/hashes/verify
+async fn route_verify(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    body: Option<Json<VerifyReq>>,
+) -> Result<Json<VerifyResp>, (StatusCode, String)> {
+    rbac::require_role(&state.pool, &headers, &[rbac::ADMIN, rbac::OPERATOR, rbac::AUDITOR]).await?;
+    let req = body.map(|Json(r)| r).unwrap_or_default();
+    let format = match req.format {
+        Some(f) => f,
+        None => {
+            let (read, target) = rehash_db::formats(&state.pool).await.map_err(internal)?;
+            target.unwrap_or(read)
+        }
+    };
+
+    let mut conn = state.pool.acquire().await.map_err(internal)?;
+    let resp = verify_all(&mut conn, format).await.map_err(internal)?;
+    if resp.ok {
+        rehash_db::record_verified(&mut *conn, format, resp.entries as i64)
+            .await
+            .map_err(internal)?;
+    } else {
+        warn!(format = %format, breaks = resp.breaks.len(), "⚠️  entry hash verification failed");
+    }
+    Ok(Json(resp))
+}
+
+/// POST /admin/ledger/hashes/flip
+async fn route_flip(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(req): Json<FormatReq>,
+) -> Result<(StatusCode, Json<FlipResp>), (StatusCode, String)> {
+    let caller = rbac::require_role(&state.pool, &headers, &[rbac::ADMIN]).await?;
+    let format = req.format;
+
+    // SHARE conflicts with the appends' ROW EXCLUSIVE: commits wait until the flip lands
+    let mut tx = state.pool.begin().await.map_err(internal)?;
+    sqlx::query("LOCK TABLE ledger_entry IN SHARE MODE")
+        .execute(&mut *tx)
+        .await
+        .map_err(internal)?;
+    let verification = verify_all(&mut tx, format).await.map_err(internal)?;
+    if !verification.ok {
+        warn!(format = %format, breaks = verification.breaks.len(), decision = "reject", error_code = "chain_break");
+        return Ok((
+            StatusCode::CONFLICT,
+            Json(FlipResp {
+                read_format: rehash_db::formats(&mut *tx).await.map_err(internal)?.0,
+                verification,
+            }),
+        ));
+    }
+    rehash_db::record_verified(&mut *tx, format, verification.entries as i64)
+        .await
+        .map_err(internal)?;
+    rehash_db::flip(&mut *tx, format, &caller.session.sid)
+        .await
+        .map_err(internal)?;
+    tx.commit().await.map_err(internal)?;
+
+    info!("🔀 REHASH reads flipped to format={} entries={}", format, verification.entries);
+    session_policy::after_action(&state.pool, &caller.session, session_policy::RISK_L4).await;
+    Ok((
+        StatusCode::OK,
+        Json(FlipResp {
+            read_format: format,
+            verification,
+        }),
+    ))
+}
@@ -0,0 +1,516 @@
+//! # Replay debugger
+//!
+//! - GET /admin/replay/:container_id?from=&limit=  (admin/operator/auditor)
+//!
+//! Steps through a container's history entry by entry to answer "why was
+//! this accepted back then?". Each step carries the ledger state before and
+//! after the entry, the decision every validator stage makes on it, in
+//! commit pipeline order, and the authority pact status and policy versions
+//! in force at the entry's timestamp. A step depends only on the state
+//! before it and the row ([`Replay::step`]), so paging with `from` (the
+//! `next` of the previous page) gives the same answers as one long walk.
+//!
+//! Stages run on what the ledger kept, and say so when it kept nothing:
+//!
+//! | Stage          | Decided from                                                    |
+//! |----------------|-----------------------------------------------------------------|
+//! | `v6_profile`   | the container's membrane profile, class and delta               |
+//! | `authority`    | the pact's status at the entry, replayed from its events        |
+//! | `break_glass`  | the override tag in `metadata`                                  |
+//! | `policy`       | `metadata.policy.decided_by` and its version active at the entry |
+//! | `v4_causality` | `previous_hash` against the head before it                      |
+//! | `v5_sequence`  | `sequence` against the head before it                           |
+//! | `v1_version`   | skipped: the link version is not stored; names what the spec requires |
+//! | `entry_hash`   | the stored hash recomputed in the original format               |
+//!
+//! Policies are those bound to the container now, each at the version that
+//! was active at the entry; bindings themselves are not versioned.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    routing::get,
+    Json, Router,
+};
+use serde::{Deserialize, Serialize, Serializer};
+use ubl_link::IntentClass;
+use ubl_membrane::ContainerProfile;
+use ubl_policy_vm::{PolicyVM, SpecVersion};
+
+use crate::auth::rbac;
+use crate::entry_hash::{self, HashFormat};
+use crate::pact_db::{self, PactEvent, PactRow, PactStatus};
+use crate::pipeline::StageOutcome;
+use crate::replay_db::{self, ReplayEntry};
+use crate::{db, AppState};
+
+#[derive(Debug, Deserialize)]
+pub struct ReplayQuery {
+    /// First sequence to step through
+    #[serde(default = "default_from")]
+    pub from: i64,
+    #[serde(default = "default_limit")]
+    pub limit: i64,
+}
+
+fn default_from() -> i64 {
+    1
+}
+
+fn default_limit() -> i64 {
+    50
+}
+
+/// The ledger as a step finds or leaves it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LedgerState {
+    /// Sequence of the head (0 before genesis)
+    pub sequence: i64,
+    /// Head's entry hash in the original format
+    pub last_hash: String,
+    /// Sum of the `physics_delta`s so far
+    #[serde(serialize_with = "as_string")]
+    pub physical_balance: i128,
+}
+
+fn as_string<S: Serializer>(v: &i128, s: S) -> Result<S::Ok, S::Error> {
+    s.collect_str(v)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StageVerdict {
+    pub stage: &'static str,
+    pub outcome: StageOutcome,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AuthorityAt {
+    pub pact_id: String,
+    pub status: PactStatus,
+    /// `full`, or `observation_only` while the pact was not in force
+    pub mode: &'static str,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ActivePolicy {
+    pub policy_id: String,
+    /// Version active at the entry; `None` if none was yet
+    pub version: Option<String>,
+    /// Whether this policy's decision was recorded with the entry
+    pub decided: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplayStep {
+    pub entry: ReplayEntry,
+    pub before: LedgerState,
+    pub after: LedgerState,
+    pub stages: Vec<StageVerdict>,
+    /// No stage fails on the entry today
+    pub accepted: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub authority: Option<AuthorityAt>,
+    pub policies: Vec<ActivePolicy>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReplayResp {
+    pub container_id: String,
+    pub spec_version: SpecVersion,
+    pub authority_pact: Option<String>,
+    pub steps: Vec<ReplayStep>,
+    /// `from` of the next page, if there are more entries
+    pub next: Option<i64>,
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new().route("/admin/replay/:container_id", get(route_replay))
+}
+
+fn internal(e: impl ToString) -> (StatusCode, String) {
+    (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+}
+
+/// A stage that ran: passed with an optional detail, or failed with why
+fn verdict(stage: &'static str, result: Result<Option<String>, String>) -> StageVerdict {
+    match result {
+        Ok(detail) => StageVerdict {
+            stage,
+            outcome: StageOutcome::Pass,
+            detail,
+        },
+        Err(detail) => StageVerdict {
+            stage,
+            outcome: StageOutcome::Fail,
+            detail: Some(detail),
+        },
+    }
+}
+
+fn skip(stage: &'static str, detail: impl Into<String>) -> StageVerdict {
+    StageVerdict {
+        stage,
+        outcome: StageOutcome::Skip,
+        detail: Some(detail.into()),
+    }
+}
+
+/// What a replay of one container holds fixed across its steps
+pub struct Replay<'a> {
+    pub container_id: &'a str,
+    pub spec: SpecVersion,
+    /// Authority pact id, its row and its events
+    pub pact: Option<(&'a str, Option<&'a PactRow>, &'a [PactEvent])>,
+    /// Policies bound to the container
+    pub bound: Vec<String>,
+}
+
+impl Replay<'_> {
+    /// Run every stage on `entry` against the ledger as it stood before it
+    pub fn step(&self, vm: &PolicyVM, before: &LedgerState, entry: ReplayEntry) -> ReplayStep {
+        let at = entry.ts_unix_ms.div_euclid(1000);
+        let mut stages = Vec::new();
+
+        let class: Result<IntentClass, String> = entry
+            .intent_class
+            .as_deref()
+            .ok_or_else(|| "no intent_class recorded".to_string())
+            .and_then(str::parse);
+        let delta: Result<i128, String> = match entry.physics_delta.as_deref() {
+            None => Ok(0),
+            Some(d) => d.parse().map_err(|_| format!("invalid physics_delta: {}", d)),
+        };
+
+        // Container profile (SPEC-UBL-MEMBRANE §V6)
+        let profile = ContainerProfile::for_container(self.container_id);
+        stages.push(verdict(
+            "v6_profile",
+            class
+                .clone()
+                .and_then(|c| delta.clone().map(|d| (c, d)))
+                .and_then(|(c, d)| profile.check(c, d).map_err(|e| e.to_string()))
+                .map(|()| None),
+        ));
+
+        // Authority pact at the entry's timestamp
+        let authority = self.pact.map(|(pact_id, row, events)| {
+            let status = PactStatus::at(row, events, at);
+            AuthorityAt {
+                pact_id: pact_id.to_string(),
+                status,
+                mode: if status.in_force() { "full" } else { "observation_only" },
+            }
+        });
+        stages.push(match &authority {
+            None => skip("authority", "no authority pact"),
+            Some(a) if a.status.in_force() => verdict("authority", Ok(Some(format!("pact {} active", a.pact_id)))),
+            Some(a) => verdict(
+                "authority",
+                class
+                    .clone()
+                    .and_then(|c| ContainerProfile::Restricted.check(c, 0).map_err(|e| e.to_string()))
+                    .map(|()| Some(format!("pact {} {:?}; Observation only", a.pact_id, a.status)))
+                    .map_err(|e| format!("{}: authority pact {} was {:?}", e, a.pact_id, a.status)),
+            ),
+        });
+
+        // Break-glass override and the policy decision recorded with the entry
+        let override_tag = entry.metadata.get("break_glass");
+        let field = |tag: &serde_json::Value, key: &str| tag[key].as_str().unwrap_or("?").to_string();
+        stages.push(match override_tag {
+            Some(tag) => verdict(
+                "break_glass",
+                Ok(Some(format!(
+                    "override {} bypassed {} for {}",
+                    field(tag, "override_id"),
+                    field(tag, "subsystem"),
+                    field(tag, "actor")
+                ))),
+            ),
+            None => skip("break_glass", "no override"),
+        });
+        let decided_by = entry.metadata["policy"]["decided_by"].as_str().map(str::to_string);
+        let version_at = |policy_id: &str| vm.active_version(policy_id, at).ok().map(|p| p.version.clone());
+        stages.push(match &decided_by {
+            Some(id) => verdict(
+                "policy",
+                Ok(Some(match version_at(id) {
+                    Some(v) => format!("decided by {} version {}", id, v),
+                    None => format!("decided by {}, no version of it known at {}", id, at),
+                })),
+            ),
+            None if override_tag.is_some_and(|t| t["subsystem"] == "policy") => {
+                skip("policy", "bypassed by break-glass")
+            }
+            None => skip("policy", "no policy decision recorded"),
+        });
+
+        // Causality and sequence against the head before (SPEC-UBL-MEMBRANE §V4, §V5)
+        stages.push(verdict(
+            "v4_causality",
+            if entry.previous_hash == before.last_hash {
+                Ok(None)
+            } else {
+                Err(format!(
+                    "RealityDrift: previous_hash {}, head was {}",
+                    entry.previous_hash, before.last_hash
+                ))
+            },
+        ));
+        stages.push(verdict(
+            "v5_sequence",
+            if entry.sequence == before.sequence + 1 {
+                Ok(None)
+            } else {
+                Err(format!(
+                    "SequenceMismatch: sequence {}, expected {}",
+                    entry.sequence,
+                    before.sequence + 1
+                ))
+            },
+        ));
+        stages.push(skip(
+            "v1_version",
+            format!(
+                "link version not stored; spec {} requires version {}",
+                self.spec,
+                ubl_membrane::link_version(self.spec)
+            ),
+        ));
+        let recomputed = entry_hash::compute(
+            HashFormat::ORIGINAL,
+            &entry.fields(self.container_id),
+            &entry.previous_hash,
+        );
+        stages.push(verdict(
+            "entry_hash",
+            if recomputed == entry.entry_hash {
+                Ok(None)
+            } else {
+                Err(format!("stored hash does not recompute (got {})", recomputed))
+            },
+        ));
+
+        let mut policies: Vec<ActivePolicy> = self
+            .bound
+            .iter()
+            .map(|id| ActivePolicy {
+                policy_id: id.clone(),
+                version: version_at(id),
+                decided: decided_by.as_deref() == Some(id.as_str()),
+            })
+            .collect();
+        if let Some(id) = decided_by.filter(|id| !self.bound.contains(id)) {
+            policies.push(ActivePolicy {
+                version: version_at(&id),
+                policy_id: id,
+                decided: true,
+            });
+        }
+
+        // The ledger moves on whatever the stages say today: it is history
+        let after = LedgerState {
+            sequence: entry.sequence,
+            last_hash: entry.entry_hash.clone(),
+            physical_balance: before.physical_balance + delta.unwrap_or(0),
+        };
+        ReplayStep {
+            accepted: stages.iter().all(|s| s.outcome != StageOutcome::Fail),
+            entry,
+            before: before.clone(),
+            after,
+            stages,
+            authority,
+            policies,
+        }
+    }
+}
+
+/// GET /admin/replay/:container_id
+async fn route_replay(
+    State(state): State<AppState>,
+    Path(container_id): Path<String>,
+    Query(q): Query<ReplayQuery>,
+    headers: HeaderMap,
+) -> Result<Json<ReplayResp>, (StatusCode, String)> {
+    rbac::require_role(&state.pool, &headers, &[rbac::ADMIN, rbac::OPERATOR, rbac::AUDITOR]).await?;
+    let from = q.from.max(1);
+    let limit = q.limit.clamp(1, 500);
+
+    let mut entries = replay_db::entries_from(&state.pool, &container_id, from, limit + 1)
+        .await
+        .map_err(internal)?;
+    let before = replay_db::before(&state.pool, &container_id, from)
+        .await
+        .map_err(internal)?;
+    if entries.is_empty() && before.head.is_none() {
+        return Err((
+            StatusCode::NOT_FOUND,
+            format!("container {} has no entries", container_id),
+        ));
+    }
+    let next = (entries.len() as i64 > limit).then(|| entries[limit as usize].sequence);
+    entries.truncate(limit as usize);
+
+    let spec = db::spec_version(&state.pool, &container_id).await.map_err(internal)?;
+    let authority_pact = pact_db::authority(&state.pool, &container_id).await.map_err(internal)?;
+    let (row, events) = match &authority_pact {
+        Some(pact_id) => (
+            pact_db::get(&state.pool, pact_id).await.map_err(internal)?,
+            pact_db::events(&state.pool, pact_id).await.map_err(internal)?,
+        ),
+        None => (None, Vec::new()),
+    };
+    let mut ledger = LedgerState {
+        sequence: before.head.as_ref().map_or(0, |(s, _)| *s),
+        last_hash: before.head.map_or_else(|| entry_hash::GENESIS.to_string(), |(_, h)| h),
+        physical_balance: before.balance.parse().map_err(internal)?,
+    };
+
+    let vm = state.policies.read().unwrap();
+    let replay = Replay {
+        container_id: &container_id,
+        spec,
+        pact: authority_pact
+            .as_deref()
+            .map(|id| (id, row.as_ref(), events.as_slice())),
+        bound: vm
+            .layers(&container_id)
+            .into_iter()
+            .flat_map(|(_, ids)| ids.into_iter().map(str::to_string))
+            .collect(),
+    };
+    let steps = entries
+        .into_iter()
+        .map(|entry| {
+            let step = replay.step(&vm, &ledger, entry);
+            ledger = step.after.clone();
+            step
+        })
+        .collect();
+
+    Ok(Json(ReplayResp {
+        container_id: container_id.clone(),
+        spec_version: spec,
+        authority_pact: authority_pact.clone(),
+        steps,
+        next,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::OffsetDateTime;
+
+    const CONTAINER: &str = "C.Replay";
+
+    fn chain(classes: &[(&str, &str)]) -> Vec<ReplayEntry> {
+        let mut prev = entry_hash::GENESIS.to_string();
+        let mut out = Vec::new();
+        for (i, (class, delta)) in classes.iter().enumerate() {
+            let mut entry = ReplayEntry {
+                sequence: i as i64 + 1,
+                link_hash: format!("{:064x}", i),
+                previous_hash: prev.clone(),
+                entry_hash: String::new(),
+                ts_unix_ms: 1_000_000 + i as i64 * 1000,
+                intent_class: Some(class.to_string()),
+                physics_delta: Some(delta.to_string()),
+                metadata: serde_json::json!({}),
+            };
+            entry.entry_hash = entry_hash::compute(HashFormat::ORIGINAL, &entry.fields(CONTAINER), &prev);
+            prev = entry.entry_hash.clone();
+            out.push(entry);
+        }
+        out
+    }
+
+    fn genesis() -> LedgerState {
+        LedgerState {
+            sequence: 0,
+            last_hash: entry_hash::GENESIS.to_string(),
+            physical_balance: 0,
+        }
+    }
+
+    fn run(replay: &Replay<'_>, entries: Vec<ReplayEntry>) -> Vec<ReplayStep> {
+        let vm = PolicyVM::new();
+        let mut ledger = genesis();
+        entries
+            .into_iter()
+            .map(|e| {
+                let step = replay.step(&vm, &ledger, e);
+                ledger = step.after.clone();
+                step
+            })
+            .collect()
+    }
+
+    fn outcome(step: &ReplayStep, stage: &str) -> StageOutcome {
+        step.stages.iter().find(|s| s.stage == stage).unwrap().outcome
+    }
+
+    #[test]
+    fn test_replay_tracks_state_and_flags_tampering() {
+        let replay = Replay {
+            container_id: CONTAINER,
+            spec: SpecVersion::V1_0,
+            pact: None,
+            bound: Vec::new(),
+        };
+        let mut entries = chain(&[("Entropy", "100"), ("Conservation", "-30"), ("Observation", "0")]);
+        let steps = run(&replay, entries.clone());
+        assert!(steps.iter().all(|s| s.accepted));
+        assert_eq!(steps[2].after.physical_balance, 70);
+        assert_eq!(steps[2].before, steps[1].after);
+        assert_eq!(outcome(&steps[0], "v1_version"), StageOutcome::Skip);
+
+        entries[1].ts_unix_ms += 1;
+        let steps = run(&replay, entries);
+        assert_eq!(outcome(&steps[1], "entry_hash"), StageOutcome::Fail);
+        assert_eq!(outcome(&steps[2], "v4_causality"), StageOutcome::Pass);
+        assert!(!steps[1].accepted);
+    }
+
+    #[test]
+    fn test_replay_uses_pact_status_at_entry() {
+        let row = PactRow {
+            pact_id: "pact_ops".to_string(),
+            pact: serde_json::Value::Null,
+            not_before: 0,
+            not_after: 10_000,
+            revoked_at: None,
+            revoked_by: None,
+            revoke_reason: None,
+            registered_by: None,
+            registered_at: OffsetDateTime::UNIX_EPOCH,
+        };
+        let event = |kind: &str, at| PactEvent {
+            id: 0,
+            kind: kind.to_string(),
+            not_after: 10_000,
+            reason: None,
+            actor: None,
+            created_at: OffsetDateTime::from_unix_timestamp(at).unwrap(),
+        };
+        // Revoked between the first and second entry
+        let events = [event("registered", 0), event("revoked", 1_001)];
+        let replay = Replay {
+            container_id: CONTAINER,
+            spec: SpecVersion::V1_0,
+            pact: Some(("pact_ops", Some(&row), &events)),
+            bound: Vec::new(),
+        };
+        let steps = run(
+            &replay,
+            chain(&[("Entropy", "5"), ("Entropy", "5"), ("Observation", "0")]),
+        );
+        assert_eq!(steps[0].authority.as_ref().unwrap().status, PactStatus::Active);
+        assert_eq!(outcome(&steps[1], "authority"), StageOutcome::Fail);
+        assert_eq!(steps[2].authority.as_ref().unwrap().mode, "observation_only");
+        assert!(steps[2].accepted);
+    }
+}
@@ -0,0 +1,84 @@
+//! Ledger reads for the replay debugger (`replay.rs`)
+
+use serde::Serialize;
+use sqlx::PgPool;
+
+use crate::entry_hash::EntryFields;
+
+/// One `ledger_entry` row as the replay steps through it
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplayEntry {
+    pub sequence: i64,
+    pub link_hash: String,
+    pub previous_hash: String,
+    pub entry_hash: String,
+    pub ts_unix_ms: i64,
+    pub intent_class: Option<String>,
+    pub physics_delta: Option<String>,
+    pub metadata: serde_json::Value,
+}
+
+impl ReplayEntry {
+    pub fn fields<'a>(&'a self, container_id: &'a str) -> EntryFields<'a> {
+        EntryFields {
+            container_id,
+            sequence: self.sequence,
+            link_hash: &self.link_hash,
+            ts_unix_ms: self.ts_unix_ms,
+        }
+    }
+}
+
+/// What the ledger held before `sequence`: the last entry's sequence and
+/// hash (original format), if any, and the sum of the earlier deltas
+pub struct Before {
+    pub head: Option<(i64, String)>,
+    pub balance: String,
+}
+
+/// Entries of a container from `from_seq` on, oldest first
+pub async fn entries_from(
+    pool: &PgPool,
+    container_id: &str,
+    from_seq: i64,
+    limit: i64,
+) -> sqlx::Result<Vec<ReplayEntry>> {
+    sqlx::query_as!(
+        ReplayEntry,
+        r#"SELECT sequence, link_hash, previous_hash, entry_hash, ts_unix_ms,
+                  intent_class, physics_delta #>> '{}' AS physics_delta, metadata
+           FROM ledger_entry
+           WHERE container_id = $1 AND sequence >= $2
+           ORDER BY sequence
+           LIMIT $3"#,
+        container_id,
+        from_seq,
+        limit
+    )
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn before(pool: &PgPool, container_id: &str, sequence: i64) -> sqlx::Result<Before> {
+    let head = sqlx::query!(
+        r#"SELECT sequence, entry_hash FROM ledger_entry
+           WHERE container_id = $1 AND sequence < $2
+           ORDER BY sequence DESC LIMIT 1"#,
+        container_id,
+        sequence
+    )
+    .fetch_optional(pool)
+    .await?;
+    let balance = sqlx::query_scalar!(
+        r#"SELECT COALESCE(SUM((physics_delta #>> '{}')::numeric), 0)::text AS "balance!"
+           FROM ledger_entry WHERE container_id = $1 AND sequence < $2"#,
+        container_id,
+        sequence
+    )
+    .fetch_one(pool)
+    .await?;
+    Ok(Before {
+        head: head.map(|r| (r.sequence, r.entry_hash)),
+        balance,
+    })
+}
@@ -0,0 +1,463 @@
+//! # Marketplace settlements
+//!
+//! A marketplace settles many buyers to one merchant. Instead of N debit
+//! commits and a credit commit orchestrated by the client, `POST /settlements`
+//! takes the buyers' debit links and appends them together with the credit
+//! that balances them, in one SERIALIZABLE transaction: either every entry
+//! lands or none does, and Σ Δ over the settlement is 0.
+//!
+//! - POST /settlements                         (ASC-scoped like `/link/commit`)
+//! - GET  /settlements?merchant=&limit=        (admin/operator/auditor)
+//! - GET  /settlements/:settlement_id          (admin/operator/auditor)
+//!
+//! Each debit is a signed Conservation link with a negative delta on a buyer
+//! container, and passes the commit path's checks: strict hex, ASC scopes,
+//! profile and policy. The credit is authored by the server, like identity
+//! events: a Conservation link on the merchant container chained to its
+//! current head, with delta Σ|debits| and an atom naming the settlement and
+//! every item (buyer container, sequence, atom hash, amount). The merchant's
+//! policies see it as a `{"type": "settlement"}` intent.
+//!
+//! Provenance runs both ways: every debit's `metadata.settlement` names the
+//! settlement and its item number, the credit's names the settlement, and
+//! the `settlement`/`settlement_item` rows (sql/040_settlement.sql) map the
+//! settlement to the entries it appended. `settlement_id` is chosen by the
+//! client and accepted once, so a retried request cannot settle twice.
+//!
+//! Debit heads are locked in container order, then the merchant's, so
+//! concurrent settlements over the same buyers cannot deadlock.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    routing::{get, post},
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tracing::{info, warn};
+use ubl_errors::ErrorCatalog;
+use ubl_link::IntentClass;
+
+use crate::auth::{self, rbac};
+use crate::db::{self, LedgerEntry, LinkDraft, TangencyError};
+use crate::pipeline::PipelineTrace;
+use crate::settlement_db::{self, Item, Settlement};
+use crate::{error_routes, pact_routes, region, AppState};
+
+/// Debits one settlement may carry
+pub const MAX_ITEMS: usize = 1000;
+
+const MAX_LIST: i64 = 200;
+
+#[derive(Debug, Deserialize)]
+pub struct SettleReq {
+    /// Client-chosen, 1-128 characters; accepted once
+    pub settlement_id: String,
+    pub merchant: Merchant,
+    /// Buyer debits, in item order
+    pub debits: Vec<LinkDraft>,
+}
+
+/// Where the credit goes
+#[derive(Debug, Deserialize)]
+pub struct Merchant {
+    pub container_id: String,
+    /// Evaluated for the credit on an unbound merchant container, as a
+    /// draft's `policy_id` is
+    #[serde(default)]
+    pub policy_id: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SettlementView {
+    #[serde(flatten)]
+    pub settlement: Settlement,
+    pub items: Vec<Item>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SettleResp {
+    pub ok: bool,
+    pub settlement: SettlementView,
+    /// Appended debits, in item order
+    pub debits: Vec<LedgerEntry>,
+    pub credit: LedgerEntry,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListQuery {
+    pub merchant: String,
+    pub limit: Option<i64>,
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new()
+        .route("/settlements", post(route_settle).get(route_list))
+        .route("/settlements/:settlement_id", get(route_get))
+}
+
+fn internal(e: impl ToString) -> (StatusCode, String) {
+    (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+}
+
+fn unprocessable(msg: String) -> (StatusCode, String) {
+    (StatusCode::UNPROCESSABLE_ENTITY, msg)
+}
+
+/// Shape checks that need no database; returns the credit's delta
+pub fn check_request(req: &SettleReq) -> Result<i128, String> {
+    if req.settlement_id.is_empty() || req.settlement_id.len() > 128 {
+        return Err("settlement_id must be 1-128 characters".to_string());
+    }
+    if req.debits.is_empty() || req.debits.len() > MAX_ITEMS {
+        return Err(format!("a settlement carries 1-{} debits, got {}", MAX_ITEMS, req.debits.len()));
+    }
+    let mut total: i128 = 0;
+    for (i, link) in req.debits.iter().enumerate() {
+        if link.container_id == req.merchant.container_id {
+            return Err(format!("debit {}: the merchant cannot debit itself", i));
+        }
+        if !matches!(link.intent_class.parse(), Ok(IntentClass::Conservation)) {
+            return Err(format!("debit {}: must be Conservation, is {}", i, link.intent_class));
+        }
+        if !link.affects.is_empty() || link.manifest.is_some() {
+            return Err(format!("debit {}: affects and manifest are not accepted in a settlement", i));
+        }
+        let delta: i128 = link
+            .physics_delta
+            .parse()
+            .map_err(|_| format!("debit {}: invalid physics_delta: {}", i, link.physics_delta))?;
+        if delta >= 0 {
+            return Err(format!("debit {}: physics_delta must be negative, is {}", i, delta));
+        }
+        total = delta
+            .checked_neg()
+            .and_then(|d| total.checked_add(d))
+            .ok_or_else(|| format!("debit {}: settlement total overflows", i))?;
+    }
+    Ok(total)
+}
+
+/// The credit link's atom: everything needed to recompute its hash from the record
+pub fn credit_atom(req: &SettleReq, total: i128) -> serde_json::Value {
+    let items: Vec<_> = req
+        .debits
+        .iter()
+        .map(|d| {
+            json!({
+                "container_id": d.container_id,
+                "sequence": d.expected_sequence,
+                "atom_hash": d.atom_hash,
+                "amount": d.physics_delta,
+            })
+        })
+        .collect();
+    json!({
+        "type": "settlement",
+        "settlement_id": req.settlement_id,
+        "merchant": req.merchant.container_id,
+        "total": total.to_string(),
+        "items": items,
+    })
+}
+
+/// Validate the ASC, if presented, against every debit and the credit;
+/// returns the SID
+async fn authorize(
+    state: &AppState,
+    headers: &HeaderMap,
+    req: &SettleReq,
+    total: i128,
+) -> Result<Option<String>, (StatusCode, String)> {
+    let Some(auth_header) = headers.get("authorization") else {
+        // Same dev-mode allowance as /link/commit
+        info!("⚠️  No ASC provided (dev mode - allowing)");
+        return Ok(None);
+    };
+    let auth_str = auth_header
+        .to_str()
+        .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid authorization header".to_string()))?;
+    let sid = auth::extract_sid_from_header(auth_str).map_err(|e| (e.status_code(), e.message()))?;
+    let asc = auth::validate_asc(&state.pool, &sid)
+        .await
+        .map_err(|e| (e.status_code(), e.message()))?;
+    for link in &req.debits {
+        auth::validate_commit_scopes(&asc, &link.container_id, &link.intent_class, &link.physics_delta)
+            .map_err(|e| (e.status_code(), e.message()))?;
+    }
+    auth::validate_commit_scopes(&asc, &req.merchant.container_id, "Conservation", &total.to_string())
+        .map_err(|e| (e.status_code(), e.message()))?;
+    Ok(Some(asc.sid))
+}
+
+fn rejected(what: &str, e: TangencyError) -> (StatusCode, String) {
+    (error_routes::status(&e), format!("{}: {}", what, e.code()))
+}
+
+/// POST /settlements
+async fn route_settle(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(mut req): Json<SettleReq>,
+) -> Result<Json<SettleResp>, (StatusCode, String)> {
+    info!(
+        "🧾 SETTLE id={} merchant={} debits={}",
+        req.settlement_id,
+        req.merchant.container_id,
+        req.debits.len()
+    );
+    let reject = |(status, reason): (StatusCode, String)| {
+        warn!(settlement_id = %req.settlement_id, decision = "reject", error_code = "settlement", reason = %reason);
+        (status, reason)
+    };
+
+    region::admit(&state, &headers).await.map_err(reject)?;
+    let total = check_request(&req).map_err(unprocessable).map_err(reject)?;
+    if state.strict_hex {
+        for (i, link) in req.debits.iter().enumerate() {
+            link.check_hex()
+                .map_err(|e| (StatusCode::BAD_REQUEST, format!("debit {}: {}", i, e)))
+                .map_err(reject)?;
+        }
+    }
+    let created_by = authorize(&state, &headers, &req, total).await.map_err(reject)?;
+
+    // Each debit is held to its container's profile, authority pact and policies
+    for (i, link) in req.debits.iter_mut().enumerate() {
+        crate::check_profile(link)
+            .map_err(|r| unprocessable(format!("debit {}: {}", i, r)))
+            .map_err(reject)?;
+        pact_routes::admit(&state, link)
+            .await
+            .map_err(|(status, r)| (status, format!("debit {}: {}", i, r)))
+            .map_err(reject)?;
+        let decided_by = crate::check_policy(&state, link)
+            .await
+            .map_err(|(status, r, _)| (status, format!("debit {}: {}", i, r)))
+            .map_err(reject)?;
+        let mut metadata = json!({ "settlement": { "settlement_id": req.settlement_id, "item": i } });
+        if let Some(decided_by) = decided_by {
+            metadata["policy"] = json!({ "decided_by": decided_by });
+        }
+        link.metadata = Some(metadata);
+    }
+
+    // The credit, server-authored; its head is filled in under the lock
+    let atom = credit_atom(&req, total);
+    let canonical = ubl_atom::canonicalize(&atom).map_err(internal)?;
+    let atom_hash = ubl_kernel::hash_atom(&canonical);
+    let mut credit = LinkDraft {
+        version: 1,
+        container_id: req.merchant.container_id.clone(),
+        expected_sequence: 0,
+        previous_hash: String::new(),
+        atom_hash: atom_hash.clone(),
+        intent_class: "Conservation".to_string(),
+        physics_delta: total.to_string(),
+        author_pubkey: String::new(),
+        signature: String::new(),
+        affects: Vec::new(),
+        policy_id: req.merchant.policy_id.clone(),
+        intent: Some(json!({
+            "type": "settlement",
+            "settlement_id": req.settlement_id,
+            "amount": i64::try_from(total).map(serde_json::Value::from).unwrap_or_else(|_| total.to_string().into()),
+            "items": req.debits.len(),
+        })),
+        manifest: None,
+        metadata: None,
+    };
+    crate::check_profile(&credit)
+        .map_err(|r| unprocessable(format!("credit: {}", r)))
+        .map_err(reject)?;
+    pact_routes::admit(&state, &credit)
+        .await
+        .map_err(|(status, r)| (status, format!("credit: {}", r)))
+        .map_err(reject)?;
+    let decided_by = crate::check_policy(&state, &credit)
+        .await
+        .map_err(|(status, r, _)| (status, format!("credit: {}", r)))
+        .map_err(reject)?;
+    let mut metadata = json!({ "settlement": { "settlement_id": req.settlement_id, "items": req.debits.len() } });
+    if let Some(decided_by) = decided_by {
+        metadata["policy"] = json!({ "decided_by": decided_by });
+    }
+    credit.metadata = Some(metadata);
+
+    let mut trace = PipelineTrace::new();
+    let mut tx = state.ledger.begin(&mut trace).await.map_err(|e| rejected("settlement", e)).map_err(reject)?;
+    if settlement_db::exists(&mut *tx, &req.settlement_id).await.map_err(internal)? {
+        return Err(reject((
+            StatusCode::CONFLICT,
+            format!("settlement {} already exists", req.settlement_id),
+        )));
+    }
+    // Heads are locked in container order; a buyer's debits keep their own order
+    let mut order: Vec<usize> = (0..req.debits.len()).collect();
+    order.sort_by(|&a, &b| req.debits[a].container_id.cmp(&req.debits[b].container_id));
+    let mut appended: Vec<Option<LedgerEntry>> = req.debits.iter().map(|_| None).collect();
+    for i in order {
+        let link = &req.debits[i];
+        let entry = db::append_in(&mut tx, link, &mut trace)
+            .await
+            .map_err(|e| rejected(&format!("debit {} ({})", i, link.container_id), e))
+            .map_err(reject)?;
+        appended[i] = Some(entry);
+    }
+    let (sequence, previous_hash) = db::next_in(&mut tx, &credit.container_id).await.map_err(internal)?;
+    credit.expected_sequence = sequence;
+    credit.previous_hash = previous_hash;
+    let credit_entry = db::append_in(&mut tx, &credit, &mut trace)
+        .await
+        .map_err(|e| rejected("credit", e))
+        .map_err(reject)?;
+
+    let debits: Vec<LedgerEntry> = appended.into_iter().flatten().collect();
+    let items: Vec<Item> = debits
+        .iter()
+        .zip(&req.debits)
+        .enumerate()
+        .map(|(i, (entry, link))| Item {
+            item: i as i32,
+            container_id: entry.container_id.clone(),
+            sequence: entry.sequence,
+            entry_hash: entry.entry_hash.clone(),
+            amount: link.physics_delta.clone(),
+        })
+        .collect();
+    settlement_db::insert(
+        &mut *tx,
+        &req.settlement_id,
+        &credit.container_id,
+        credit_entry.sequence,
+        &credit_entry.entry_hash,
+        &atom_hash,
+        &atom,
+        total,
+        items.len() as i32,
+        created_by.as_deref(),
+    )
+    .await
+    .map_err(internal)?;
+    for item in &items {
+        settlement_db::insert_item(&mut *tx, &req.settlement_id, item)
+            .await
+            .map_err(internal)?;
+    }
+    tx.commit().await.map_err(internal)?;
+    for entry in debits.iter().chain([&credit_entry]) {
+        state.bus.committed(entry);
+    }
+
+    info!(
+        "✅ SETTLED id={} merchant={} seq={} total={} debits={}",
+        req.settlement_id,
+        credit_entry.container_id,
+        credit_entry.sequence,
+        total,
+        items.len()
+    );
+    let settlement = settlement_db::get(&state.pool, &req.settlement_id)
+        .await
+        .map_err(internal)?
+        .ok_or_else(|| internal("settlement vanished after commit"))?;
+    Ok(Json(SettleResp {
+        ok: true,
+        settlement: SettlementView { settlement, items },
+        debits,
+        credit: credit_entry,
+    }))
+}
+
+/// GET /settlements?merchant=
+async fn route_list(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<ListQuery>,
+) -> Result<Json<Vec<Settlement>>, (StatusCode, String)> {
+    rbac::require_role(&state.pool, &headers, &[rbac::ADMIN, rbac::OPERATOR, rbac::AUDITOR]).await?;
+    let limit = query.limit.unwrap_or(50).clamp(1, MAX_LIST);
+    settlement_db::list(&state.pool, &query.merchant, limit)
+        .await
+        .map(Json)
+        .map_err(internal)
+}
+
+/// GET /settlements/:settlement_id
+async fn route_get(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(settlement_id): Path<String>,
+) -> Result<Json<SettlementView>, (StatusCode, String)> {
+    rbac::require_role(&state.pool, &headers, &[rbac::ADMIN, rbac::OPERATOR, rbac::AUDITOR]).await?;
+    let settlement = settlement_db::get(&state.pool, &settlement_id)
+        .await
+        .map_err(internal)?
+        .ok_or((StatusCode::NOT_FOUND, "settlement not found".to_string()))?;
+    let items = settlement_db::items(&state.pool, &settlement_id).await.map_err(internal)?;
+    Ok(Json(SettlementView { settlement, items }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn debit(container_id: &str, delta: &str) -> LinkDraft {
+        serde_json::from_value(json!({
+            "version": 1,
+            "container_id": container_id,
+            "expected_sequence": 4,
+            "previous_hash": "ab",
+            "atom_hash": format!("atom-{}", container_id),
+            "intent_class": "Conservation",
+            "physics_delta": delta,
+            "author_pubkey": "",
+            "signature": "",
+        }))
+        .unwrap()
+    }
+
+    fn req(debits: Vec<LinkDraft>) -> SettleReq {
+        SettleReq {
+            settlement_id: "s-1".to_string(),
+            merchant: Merchant {
+                container_id: "C.Shop".to_string(),
+                policy_id: None,
+            },
+            debits,
+        }
+    }
+
+    #[test]
+    fn test_total_balances_debits() {
+        let r = req(vec![debit("C.Ann", "-250"), debit("C.Bob", "-750"), debit("C.Ann", "-1")]);
+        assert_eq!(check_request(&r), Ok(1001));
+    }
+
+    #[test]
+    fn test_rejects_unbalanced_shapes() {
+        assert!(check_request(&req(vec![])).is_err());
+        assert!(check_request(&req(vec![debit("C.Ann", "0")])).unwrap_err().contains("negative"));
+        assert!(check_request(&req(vec![debit("C.Shop", "-5")])).unwrap_err().contains("itself"));
+        let mut minted = debit("C.Ann", "-5");
+        minted.intent_class = "Entropy".to_string();
+        assert!(check_request(&req(vec![minted])).unwrap_err().contains("Conservation"));
+        let overflow = req(vec![debit("C.Ann", &i128::MIN.to_string())]);
+        assert!(check_request(&overflow).unwrap_err().contains("overflows"));
+        let big = (-(i128::MAX / 2 + 1)).to_string();
+        assert!(check_request(&req(vec![debit("C.Ann", &big), debit("C.Bob", &big), debit("C.Cy", "-1")])).is_err());
+    }
+
+    #[test]
+    fn test_credit_atom_names_every_item() {
+        let r = req(vec![debit("C.Ann", "-250"), debit("C.Bob", "-750")]);
+        let atom = credit_atom(&r, 1000);
+        assert_eq!(atom["total"], "1000");
+        assert_eq!(atom["merchant"], "C.Shop");
+        assert_eq!(atom["items"][1]["container_id"], "C.Bob");
+        assert_eq!(atom["items"][1]["amount"], "-750");
+        let hash = |a: &serde_json::Value| ubl_kernel::hash_atom(&ubl_atom::canonicalize(a).unwrap());
+        assert_eq!(hash(&atom), hash(&credit_atom(&r, 1000)));
+    }
+}
@@ -0,0 +1,129 @@
+//! Settlement records (tables `settlement`, `settlement_item`,
+//! sql/040_settlement.sql)
+
+use serde::Serialize;
+use sqlx::{PgExecutor, PgPool};
+use time::OffsetDateTime;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Settlement {
+    pub settlement_id: String,
+    pub merchant_container_id: String,
+    pub credit_sequence: i64,
+    pub credit_entry_hash: String,
+    pub credit_atom_hash: String,
+    /// The credit link's atom: settlement id, merchant, total and items
+    pub atom: serde_json::Value,
+    /// i128 string
+    pub total: String,
+    pub item_count: i32,
+    pub created_by: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+/// One debit of a settlement
+#[derive(Debug, Clone, Serialize)]
+pub struct Item {
+    pub item: i32,
+    pub container_id: String,
+    pub sequence: i64,
+    pub entry_hash: String,
+    /// The debit's physics_delta (negative)
+    pub amount: String,
+}
+
+pub async fn exists(db: impl PgExecutor<'_>, settlement_id: &str) -> sqlx::Result<bool> {
+    sqlx::query_scalar!(
+        r#"SELECT EXISTS (SELECT 1 FROM settlement WHERE settlement_id = $1) AS "exists!""#,
+        settlement_id
+    )
+    .fetch_one(db)
+    .await
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn insert(
+    db: impl PgExecutor<'_>,
+    settlement_id: &str,
+    merchant_container_id: &str,
+    credit_sequence: i64,
+    credit_entry_hash: &str,
+    credit_atom_hash: &str,
+    atom: &serde_json::Value,
+    total: i128,
+    item_count: i32,
+    created_by: Option<&str>,
+) -> sqlx::Result<()> {
+    sqlx::query!(
+        r#"INSERT INTO settlement (settlement_id, merchant_container_id, credit_sequence, credit_entry_hash,
+                                   credit_atom_hash, atom, total, item_count, created_by)
+           VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)"#,
+        settlement_id,
+        merchant_container_id,
+        credit_sequence,
+        credit_entry_hash,
+        credit_atom_hash,
+        atom,
+        total.to_string(),
+        item_count,
+        created_by
+    )
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+pub async fn insert_item(db: impl PgExecutor<'_>, settlement_id: &str, item: &Item) -> sqlx::Result<()> {
+    sqlx::query!(
+        r#"INSERT INTO settlement_item (settlement_id, item, container_id, sequence, entry_hash, amount)
+           VALUES ($1, $2, $3, $4, $5, $6)"#,
+        settlement_id,
+        item.item,
+        item.container_id,
+        item.sequence,
+        item.entry_hash,
+        item.amount
+    )
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+pub async fn get(pool: &PgPool, settlement_id: &str) -> sqlx::Result<Option<Settlement>> {
+    sqlx::query_as!(
+        Settlement,
+        r#"SELECT settlement_id, merchant_container_id, credit_sequence, credit_entry_hash, credit_atom_hash,
+                  atom, total, item_count, created_by, created_at
+           FROM settlement WHERE settlement_id = $1"#,
+        settlement_id
+    )
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn items(pool: &PgPool, settlement_id: &str) -> sqlx::Result<Vec<Item>> {
+    sqlx::query_as!(
+        Item,
+        r#"SELECT item, container_id, sequence, entry_hash, amount
+           FROM settlement_item WHERE settlement_id = $1 ORDER BY item"#,
+        settlement_id
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Settlements crediting `merchant_container_id`, newest first
+pub async fn list(pool: &PgPool, merchant_container_id: &str, limit: i64) -> sqlx::Result<Vec<Settlement>> {
+    sqlx::query_as!(
+        Settlement,
+        r#"SELECT settlement_id, merchant_container_id, credit_sequence, credit_entry_hash, credit_atom_hash,
+                  atom, total, item_count, created_by, created_at
+           FROM settlement WHERE merchant_container_id = $1
+           ORDER BY credit_sequence DESC LIMIT $2"#,
+        merchant_container_id,
+        limit
+    )
+    .fetch_all(pool)
+    .await
+}
@@ -1,9 +1,8 @@
-//! SSE tail endpoint with PostgreSQL LISTEN/NOTIFY
+//! SSE tail endpoints on the internal event bus (`event_bus.rs`)
 //! PR10: Real-time ledger streaming
 
 use axum::response::sse::{Event, Sse};
 use futures_util::Stream;
-use serde_json::Value;
 use sqlx::PgPool;
 use std::{convert::Infallible, time::Duration};
 use tokio::sync::mpsc;
@@ -11,64 +10,141 @@ use tokio_stream::wrappers::ReceiverStream;
 use tokio_stream::StreamExt;
 use tracing::{debug, error};
 
+use crate::db::HeadEvent;
+use crate::event_bus::{BusEvent, EventBus, Overflow, RecvError};
+use crate::metrics::SSE_CLIENTS;
+
+/// Counts a connected client in `ubl_sse_clients` until its stream drops
+struct ClientGauge(&'static str);
+
+impl ClientGauge {
+    fn connect(stream: &'static str) -> Self {
+        SSE_CLIENTS.with_label_values(&[stream]).inc();
+        Self(stream)
+    }
+}
+
+impl Drop for ClientGauge {
+    fn drop(&mut self) {
+        SSE_CLIENTS.with_label_values(&[self.0]).dec();
+    }
+}
+
 /// SSE tail for a specific container
-/// Listens to PostgreSQL NOTIFY and streams only events for the requested container
+/// Follows the event bus feed and streams only entries of the requested container
 pub async fn sse_tail(
-    pool: PgPool,
+    bus: EventBus,
     container_id: String,
 ) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
     let (tx, rx) = mpsc::channel::<String>(128);
+    let mut sub = bus.subscribe("sse_tail", Overflow::Skip);
 
-    // Spawn task to listen to PostgreSQL NOTIFY
     tokio::spawn(async move {
-        match sqlx::postgres::PgListener::connect_with(&pool).await {
-            Ok(mut listener) => {
-                if let Err(e) = listener.listen("ledger_events").await {
-                    error!("Failed to LISTEN on ledger_events: {}", e);
-                    return;
+        debug!("🔊 SSE tail for container: {}", container_id);
+        loop {
+            let event = tokio::select! {
+                _ = tx.closed() => break,
+                event = sub.recv() => event,
+            };
+            match event {
+                Ok(BusEvent::Entry(e)) if e.head.container_id == container_id => {
+                    debug!("📨 SSE event for {}: {}", container_id, e.row.get(..100).unwrap_or(&e.row));
+                    if tx.send(e.row.clone()).await.is_err() {
+                        break;
+                    }
                 }
+                Ok(_) => {}
+                Err(RecvError::Lagged(n)) => debug!("SSE tail for {} skipped {} events", container_id, n),
+                Err(RecvError::Closed) => break,
+            }
+        }
+        debug!("SSE client disconnected");
+    });
 
-                debug!("🔊 LISTEN ledger_events for container: {}", container_id);
+    // Convert mpsc channel to SSE stream
+    let gauge = ClientGauge::connect("tail");
+    let stream = ReceiverStream::new(rx).map(move |json| {
+        let _ = &gauge;
+        Ok(Event::default()
+            .event("ledger_entry")
+            .data(json))
+    });
 
-                // Process notifications
-                loop {
-                    match listener.recv().await {
-                        Ok(notification) => {
-                            let payload = notification.payload().to_string();
-                            
-                            // Parse JSON and filter by container_id
-                            if let Ok(v) = serde_json::from_str::<Value>(&payload) {
-                                if let Some(cid) = v.get("container_id").and_then(|x| x.as_str()) {
-                                    if cid == container_id.as_str() {
-                                        debug!("📨 SSE event for {}: {}", container_id, &payload[..100.min(payload.len())]);
-                                        
-                                        // Send to SSE stream
-                                        if tx.send(payload).await.is_err() {
-                                            debug!("SSE client disconnected");
-                                            break;
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                        Err(e) => {
-                            error!("NOTIFY error: {}", e);
-                            tokio::time::sleep(Duration::from_millis(200)).await;
-                        }
+    Sse::new(stream).keep_alive(axum::response::sse::KeepAlive::default())
+}
+
+/// SSE tail of every accepted commit across the deployment, one `head`
+/// event per entry (`container_id`, `sequence`, `entry_hash`).
+///
+/// Catches up from `ledger_entry` in id order, then follows the event bus
+/// feed, so the stream is gap-free and resumable: the SSE event id is the
+/// feed cursor and `after` (or `Last-Event-ID`) picks up where a client left
+/// off. A client that lags behind the bus goes back to Postgres.
+pub async fn sse_heads(
+    pool: PgPool,
+    bus: EventBus,
+    after: i64,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let (tx, rx) = mpsc::channel::<HeadEvent>(128);
+    // Subscribe before catching up, so nothing lands between the two
+    let mut sub = bus.subscribe("sse_heads", Overflow::Skip);
+
+    tokio::spawn(async move {
+        debug!("🔊 SSE heads from cursor {}", after);
+        let mut cursor = after;
+        'catch_up: loop {
+            loop {
+                let batch = match crate::db::heads_after(&pool, cursor, 500).await {
+                    Ok(b) => b,
+                    Err(e) => {
+                        error!("heads feed query failed: {}", e);
+                        tokio::time::sleep(Duration::from_millis(500)).await;
+                        continue;
+                    }
+                };
+                let full = batch.len() == 500;
+                for head in batch {
+                    cursor = head.id;
+                    if tx.send(head).await.is_err() {
+                        debug!("SSE heads client disconnected");
+                        return;
                     }
                 }
+                if !full {
+                    break;
+                }
             }
-            Err(e) => {
-                error!("Failed to create PgListener: {}", e);
+
+            // Caught up: follow the bus feed, back to Postgres after a lag
+            loop {
+                let event = tokio::select! {
+                    _ = tx.closed() => return,
+                    event = sub.recv() => event,
+                };
+                match event {
+                    Ok(BusEvent::Entry(e)) if e.head.id > cursor => {
+                        cursor = e.head.id;
+                        if tx.send(e.head.clone()).await.is_err() {
+                            debug!("SSE heads client disconnected");
+                            return;
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(RecvError::Lagged(_)) => continue 'catch_up,
+                    Err(RecvError::Closed) => return,
+                }
             }
         }
     });
 
-    // Convert mpsc channel to SSE stream
-    let stream = ReceiverStream::new(rx).map(|json| {
+    let gauge = ClientGauge::connect("heads");
+    let stream = ReceiverStream::new(rx).map(move |head| {
+        let _ = &gauge;
         Ok(Event::default()
-            .event("ledger_entry")
-            .data(json))
+            .event("head")
+            .id(head.id.to_string())
+            .json_data(&head)
+            .unwrap_or_else(|_| Event::default().event("head")))
     });
 
     Sse::new(stream).keep_alive(axum::response::sse::KeepAlive::default())
@@ -0,0 +1,79 @@
+//! Reads behind the support bundle (`crate::support_bundle`). Rows come back
+//! raw; the bundle redacts them before anything is written out.
+
+use sqlx::PgPool;
+use time::OffsetDateTime;
+
+/// Round trip to the database
+pub async fn ping(pool: &PgPool) -> sqlx::Result<()> {
+    sqlx::query_scalar!("SELECT 1 AS \"one!\"").fetch_one(pool).await.map(drop)
+}
+
+/// Of `sentinels`, the ones present in the schema. A sentinel is a relation
+/// (`policy_veto`), a column (`policy.risk_level`) or a function (`notify_ledger_head()`).
+pub async fn present(pool: &PgPool, sentinels: &[String]) -> sqlx::Result<Vec<String>> {
+    sqlx::query_scalar!(
+        r#"SELECT s AS "sentinel!" FROM unnest($1::text[]) s
+           WHERE CASE
+             WHEN s LIKE '%()' THEN to_regproc(left(s, -2)) IS NOT NULL
+             WHEN s LIKE '%.%' THEN EXISTS (
+               SELECT 1 FROM information_schema.columns
+               WHERE table_schema = current_schema()
+                 AND table_name = split_part(s, '.', 1)
+                 AND column_name = split_part(s, '.', 2))
+             ELSE to_regclass(s) IS NOT NULL
+           END"#,
+        sentinels
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// A container admin audit row (`container_admin_audit`)
+#[derive(Debug, Clone)]
+pub struct AuditRow {
+    pub id: i64,
+    pub container_id: String,
+    pub actor_sid: String,
+    pub action: String,
+    pub capability: Option<String>,
+    pub detail: serde_json::Value,
+    pub created_at: OffsetDateTime,
+}
+
+/// Latest container admin audit rows, newest first
+pub async fn recent_audit(pool: &PgPool, limit: i64) -> sqlx::Result<Vec<AuditRow>> {
+    sqlx::query_as!(
+        AuditRow,
+        r#"SELECT id, container_id, actor_sid, action, capability, detail, created_at
+           FROM container_admin_audit ORDER BY id DESC LIMIT $1"#,
+        limit
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// A rejected WebAuthn ceremony (`id_ceremony_event`)
+#[derive(Debug, Clone)]
+pub struct CeremonyRejection {
+    pub id: i64,
+    pub ceremony: String,
+    pub phase: String,
+    pub failure_class: Option<String>,
+    pub username: Option<String>,
+    pub sid: Option<String>,
+    pub detail: Option<String>,
+    pub created_at: OffsetDateTime,
+}
+
+/// Latest rejected ceremonies, newest first
+pub async fn recent_rejections(pool: &PgPool, limit: i64) -> sqlx::Result<Vec<CeremonyRejection>> {
+    sqlx::query_as!(
+        CeremonyRejection,
+        r#"SELECT id, ceremony, phase, failure_class, username, sid, detail, created_at
+           FROM id_ceremony_event WHERE outcome = 'reject' ORDER BY id DESC LIMIT $1"#,
+        limit
+    )
+    .fetch_all(pool)
+    .await
+}
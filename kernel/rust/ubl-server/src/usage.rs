@@ -0,0 +1,352 @@
+//! # API usage analytics
+//!
+//! - GET /admin/usage?days=&tenant=&route=&top= (admin/operator)
+//!
+//! [`track`] wraps every route: it counts each request by UTC day, method,
+//! matched route template, tenant and caller in memory, and bumps
+//! `ubl_http_requests_total`. The flusher adds the counts to
+//! `api_usage_daily` every `UBL_USAGE_FLUSH_SECS` (default 60) and deletes
+//! days older than `UBL_USAGE_RETENTION_DAYS` (default 90); counts a failed
+//! flush could not write are kept for the next one.
+//!
+//! The tenant is the `X-UBL-Tenant` header a gateway sets (`-` without one).
+//! The caller is the SID of the session cookie or Bearer token, resolved at
+//! flush time (`anonymous` without a token, `unknown` if it matches no
+//! session); tokens never reach the table. Requests that match no route are
+//! counted under `<unmatched>`. 4xx and 5xx answers are counted apart, and
+//! `error_rate` is both over `requests`.
+
+use axum::{
+    extract::{MatchedPath, Query, Request, State},
+    http::{HeaderMap, StatusCode},
+    middleware::Next,
+    response::Response,
+    routing::get,
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use time::{Date, OffsetDateTime};
+use tracing::{error, info};
+
+use crate::auth::rbac;
+use crate::auth::require_stepup::{extract_cookie, extract_token};
+use crate::metrics::HTTP_REQUESTS;
+use crate::usage_db::{self, UsageRow};
+use crate::AppState;
+
+const UNMATCHED: &str = "<unmatched>";
+const NO_TENANT: &str = "-";
+const ANONYMOUS: &str = "anonymous";
+const UNKNOWN_CALLER: &str = "unknown";
+/// Longest tenant name kept; longer headers count as no tenant
+const MAX_TENANT_LEN: usize = 64;
+
+fn env_secs(name: &str, default: u64) -> u64 {
+    std::env::var(name).ok().and_then(|n| n.parse().ok()).filter(|&n| n > 0).unwrap_or(default)
+}
+
+/// Seconds between flushes (`UBL_USAGE_FLUSH_SECS`)
+pub fn flush_secs_from_env() -> u64 {
+    env_secs("UBL_USAGE_FLUSH_SECS", 60)
+}
+
+/// Days of rollups kept (`UBL_USAGE_RETENTION_DAYS`)
+pub fn retention_days_from_env() -> i32 {
+    env_secs("UBL_USAGE_RETENTION_DAYS", 90).min(i32::MAX as u64) as i32
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct UsageKey {
+    day: Date,
+    method: String,
+    route: String,
+    tenant: String,
+    /// Session token, replaced by its SID at flush
+    token: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Counts {
+    requests: i64,
+    client_errors: i64,
+    server_errors: i64,
+}
+
+impl Counts {
+    fn of(status: StatusCode) -> Self {
+        Self {
+            requests: 1,
+            client_errors: status.is_client_error() as i64,
+            server_errors: status.is_server_error() as i64,
+        }
+    }
+
+    fn add(&mut self, other: Counts) {
+        self.requests += other.requests;
+        self.client_errors += other.client_errors;
+        self.server_errors += other.server_errors;
+    }
+}
+
+/// Request counts not yet flushed
+#[derive(Debug, Default)]
+pub struct Tally(Mutex<HashMap<UsageKey, Counts>>);
+
+impl Tally {
+    fn record(&self, key: UsageKey, counts: Counts) {
+        self.0.lock().unwrap().entry(key).or_default().add(counts);
+    }
+
+    fn take(&self) -> HashMap<UsageKey, Counts> {
+        std::mem::take(&mut *self.0.lock().unwrap())
+    }
+
+    fn restore(&self, pending: HashMap<UsageKey, Counts>) {
+        let mut map = self.0.lock().unwrap();
+        for (key, counts) in pending {
+            map.entry(key).or_default().add(counts);
+        }
+    }
+}
+
+fn tenant_of(headers: &HeaderMap) -> String {
+    headers
+        .get("x-ubl-tenant")
+        .and_then(|v| v.to_str().ok())
+        .map(str::trim)
+        .filter(|t| !t.is_empty() && t.len() <= MAX_TENANT_LEN)
+        .unwrap_or(NO_TENANT)
+        .to_string()
+}
+
+/// Middleware counting every request into the state's [`Tally`]
+pub async fn track(State(tally): State<Arc<Tally>>, req: Request, next: Next) -> Response {
+    let method = req.method().to_string();
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map_or(UNMATCHED, |p| p.as_str())
+        .to_string();
+    let tenant = tenant_of(req.headers());
+    let token = extract_cookie(req.headers(), "session").or_else(|| extract_token(req.headers()));
+
+    let response = next.run(req).await;
+    let status = response.status();
+    let class = format!("{}xx", status.as_u16() / 100);
+    HTTP_REQUESTS.with_label_values(&[&method, &route, &class]).inc();
+    let key = UsageKey {
+        day: OffsetDateTime::now_utc().date(),
+        method,
+        route,
+        tenant,
+        token,
+    };
+    tally.record(key, Counts::of(status));
+    response
+}
+
+/// Fold `pending` into table rows per day, with tokens replaced by `sids`
+fn rows(pending: &HashMap<UsageKey, Counts>, sids: &HashMap<String, String>) -> HashMap<Date, Vec<UsageRow>> {
+    let mut merged: HashMap<(Date, &str, &str, &str, &str), Counts> = HashMap::new();
+    for (key, &counts) in pending {
+        let caller = match &key.token {
+            None => ANONYMOUS,
+            Some(t) => sids.get(t).map_or(UNKNOWN_CALLER, String::as_str),
+        };
+        merged
+            .entry((key.day, &key.method, &key.route, &key.tenant, caller))
+            .or_default()
+            .add(counts);
+    }
+    let mut by_day: HashMap<Date, Vec<UsageRow>> = HashMap::new();
+    for ((day, method, route, tenant, caller), c) in merged {
+        by_day.entry(day).or_default().push(UsageRow {
+            method: method.to_string(),
+            route: route.to_string(),
+            tenant: tenant.to_string(),
+            caller: caller.to_string(),
+            requests: c.requests,
+            client_errors: c.client_errors,
+            server_errors: c.server_errors,
+        });
+    }
+    by_day
+}
+
+async fn flush(state: &AppState, pending: &HashMap<UsageKey, Counts>) -> sqlx::Result<()> {
+    let mut tokens: Vec<String> = pending.keys().filter_map(|k| k.token.clone()).collect();
+    tokens.sort();
+    tokens.dedup();
+    let sids: HashMap<String, String> = usage_db::session_sids(&state.pool, &tokens).await?.into_iter().collect();
+    for (day, rows) in rows(pending, &sids) {
+        usage_db::add(&state.pool, day, &rows).await?;
+    }
+    Ok(())
+}
+
+/// Flush the tally on an interval and prune old days
+pub fn spawn_flusher(state: AppState) {
+    let every = Duration::from_secs(flush_secs_from_env());
+    let retention_days = retention_days_from_env();
+    info!("📈 API usage: flush every {}s, {} day(s) retained", every.as_secs(), retention_days);
+    tokio::spawn(async move {
+        let mut tick = tokio::time::interval(every);
+        tick.tick().await;
+        loop {
+            tick.tick().await;
+            let pending = state.usage.take();
+            if !pending.is_empty() {
+                if let Err(e) = flush(&state, &pending).await {
+                    error!("usage flush failed, keeping {} key(s): {}", pending.len(), e);
+                    state.usage.restore(pending);
+                    continue;
+                }
+            }
+            if let Err(e) = usage_db::prune(&state.pool, retention_days).await {
+                error!("usage prune failed: {}", e);
+            }
+        }
+    });
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new().route("/admin/usage", get(route_usage))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UsageQuery {
+    /// Days back from today, today included
+    #[serde(default = "default_days")]
+    pub days: i32,
+    pub tenant: Option<String>,
+    /// Route template, e.g. `/link/commit`
+    pub route: Option<String>,
+    /// Callers listed in `top_callers`
+    #[serde(default = "default_top")]
+    pub top: usize,
+}
+
+fn default_days() -> i32 {
+    7
+}
+
+fn default_top() -> usize {
+    10
+}
+
+#[derive(Debug, Serialize)]
+pub struct UsageStat {
+    #[serde(flatten)]
+    pub bucket: usage_db::Bucket,
+    /// 4xx and 5xx answers over requests
+    pub error_rate: f64,
+}
+
+impl From<usage_db::Bucket> for UsageStat {
+    fn from(bucket: usage_db::Bucket) -> Self {
+        let errors = bucket.client_errors + bucket.server_errors;
+        let error_rate = if bucket.requests == 0 { 0.0 } else { errors as f64 / bucket.requests as f64 };
+        Self { bucket, error_rate }
+    }
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct UsageReport {
+    pub days: i32,
+    pub total: Option<UsageStat>,
+    /// Oldest day first
+    pub by_day: Vec<UsageStat>,
+    /// Busiest first, keyed `METHOD route`
+    pub by_route: Vec<UsageStat>,
+    pub by_tenant: Vec<UsageStat>,
+    pub top_callers: Vec<UsageStat>,
+}
+
+/// GET /admin/usage
+async fn route_usage(
+    State(state): State<AppState>,
+    Query(q): Query<UsageQuery>,
+    headers: HeaderMap,
+) -> Result<Json<UsageReport>, (StatusCode, String)> {
+    rbac::require_role(&state.pool, &headers, &[rbac::ADMIN, rbac::OPERATOR]).await?;
+    let days = q.days.clamp(1, retention_days_from_env());
+    let buckets = usage_db::summary(&state.pool, days, q.tenant.as_deref(), q.route.as_deref())
+        .await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+
+    let mut report = UsageReport {
+        days,
+        ..Default::default()
+    };
+    for bucket in buckets {
+        let list = match bucket.dimension.as_str() {
+            "day" => &mut report.by_day,
+            "route" => &mut report.by_route,
+            "tenant" => &mut report.by_tenant,
+            "caller" => &mut report.top_callers,
+            _ => {
+                report.total = Some(bucket.into());
+                continue;
+            }
+        };
+        list.push(bucket.into());
+    }
+    report.by_day.sort_by(|a, b| a.bucket.key.cmp(&b.bucket.key));
+    report.top_callers.truncate(q.top.clamp(1, 100));
+    Ok(Json(report))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(route: &str, token: Option<&str>) -> UsageKey {
+        UsageKey {
+            day: time::macros::date!(2026 - 10 - 14),
+            method: "POST".to_string(),
+            route: route.to_string(),
+            tenant: NO_TENANT.to_string(),
+            token: token.map(String::from),
+        }
+    }
+
+    #[test]
+    fn test_tokens_fold_into_callers() {
+        let tally = Tally::default();
+        tally.record(key("/link/commit", Some("tok-a")), Counts::of(StatusCode::OK));
+        tally.record(key("/link/commit", Some("tok-b")), Counts::of(StatusCode::CONFLICT));
+        tally.record(key("/link/commit", Some("tok-x")), Counts::of(StatusCode::INTERNAL_SERVER_ERROR));
+        tally.record(key("/link/commit", None), Counts::of(StatusCode::UNAUTHORIZED));
+        let pending = tally.take();
+        assert!(tally.take().is_empty());
+
+        // Both tokens belong to one SID; tok-x matches no session
+        let sids = HashMap::from([
+            ("tok-a".to_string(), "ubl:sid:alice".to_string()),
+            ("tok-b".to_string(), "ubl:sid:alice".to_string()),
+        ]);
+        let mut rows: Vec<UsageRow> = rows(&pending, &sids).into_values().flatten().collect();
+        rows.sort_by(|a, b| a.caller.cmp(&b.caller));
+        let callers: Vec<(&str, i64, i64, i64)> = rows
+            .iter()
+            .map(|r| (r.caller.as_str(), r.requests, r.client_errors, r.server_errors))
+            .collect();
+        assert_eq!(callers, [("anonymous", 1, 1, 0), ("ubl:sid:alice", 2, 1, 0), ("unknown", 1, 0, 1)]);
+
+        tally.restore(pending);
+        assert_eq!(tally.take().len(), 4);
+    }
+
+    #[test]
+    fn test_tenant_header() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(tenant_of(&headers), "-");
+        headers.insert("x-ubl-tenant", " acme ".parse().unwrap());
+        assert_eq!(tenant_of(&headers), "acme");
+        headers.insert("x-ubl-tenant", "a".repeat(65).parse().unwrap());
+        assert_eq!(tenant_of(&headers), "-");
+    }
+}
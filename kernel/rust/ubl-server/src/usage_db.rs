@@ -0,0 +1,119 @@
+//! API usage rollups (table `api_usage_daily`, sql/037_api_usage.sql)
+
+use serde::Serialize;
+use sqlx::PgPool;
+use time::Date;
+
+/// Counts added for one `(method, route, tenant, caller)` on a day
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UsageRow {
+    pub method: String,
+    pub route: String,
+    pub tenant: String,
+    pub caller: String,
+    pub requests: i64,
+    pub client_errors: i64,
+    pub server_errors: i64,
+}
+
+/// Add `rows` to the day's counters
+pub async fn add(pool: &PgPool, day: Date, rows: &[UsageRow]) -> sqlx::Result<()> {
+    let mut method = Vec::with_capacity(rows.len());
+    let mut route = Vec::with_capacity(rows.len());
+    let mut tenant = Vec::with_capacity(rows.len());
+    let mut caller = Vec::with_capacity(rows.len());
+    let mut requests = Vec::with_capacity(rows.len());
+    let mut client_errors = Vec::with_capacity(rows.len());
+    let mut server_errors = Vec::with_capacity(rows.len());
+    for r in rows {
+        method.push(r.method.clone());
+        route.push(r.route.clone());
+        tenant.push(r.tenant.clone());
+        caller.push(r.caller.clone());
+        requests.push(r.requests);
+        client_errors.push(r.client_errors);
+        server_errors.push(r.server_errors);
+    }
+    sqlx::query!(
+        r#"INSERT INTO api_usage_daily (day, method, route, tenant, caller, requests, client_errors, server_errors)
+           SELECT $1, * FROM UNNEST($2::text[], $3::text[], $4::text[], $5::text[], $6::bigint[], $7::bigint[], $8::bigint[])
+           ON CONFLICT (day, method, route, tenant, caller) DO UPDATE SET
+             requests = api_usage_daily.requests + EXCLUDED.requests,
+             client_errors = api_usage_daily.client_errors + EXCLUDED.client_errors,
+             server_errors = api_usage_daily.server_errors + EXCLUDED.server_errors"#,
+        day,
+        &method,
+        &route,
+        &tenant,
+        &caller,
+        &requests,
+        &client_errors,
+        &server_errors
+    )
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// SIDs of the sessions behind `tokens`, as `(token, sid)`
+pub async fn session_sids(pool: &PgPool, tokens: &[String]) -> sqlx::Result<Vec<(String, String)>> {
+    let rows = sqlx::query!("SELECT token, sid::text AS \"sid!\" FROM id_session WHERE token = ANY($1)", tokens)
+        .fetch_all(pool)
+        .await?;
+    Ok(rows.into_iter().map(|r| (r.token, r.sid)).collect())
+}
+
+/// Delete days older than `retention_days`
+pub async fn prune(pool: &PgPool, retention_days: i32) -> sqlx::Result<u64> {
+    let done = sqlx::query!("DELETE FROM api_usage_daily WHERE day < current_date - $1::int", retention_days)
+        .execute(pool)
+        .await?;
+    Ok(done.rows_affected())
+}
+
+/// Counts summed over one grouping
+#[derive(Debug, Clone, Serialize)]
+pub struct Bucket {
+    /// `total`, `day`, `route`, `tenant` or `caller`
+    #[serde(skip)]
+    pub dimension: String,
+    /// The day, `METHOD route`, tenant or caller; `total` for the grand total
+    pub key: String,
+    pub requests: i64,
+    pub client_errors: i64,
+    pub server_errors: i64,
+}
+
+/// Requests of the last `days` days (today included), summed per day, route,
+/// tenant and caller, busiest first. `tenant` and `route` narrow every grouping.
+pub async fn summary(
+    pool: &PgPool,
+    days: i32,
+    tenant: Option<&str>,
+    route: Option<&str>,
+) -> sqlx::Result<Vec<Bucket>> {
+    sqlx::query_as!(
+        Bucket,
+        r#"SELECT
+             CASE WHEN GROUPING(day) = 0 THEN 'day'
+                  WHEN GROUPING(method, route) = 0 THEN 'route'
+                  WHEN GROUPING(tenant) = 0 THEN 'tenant'
+                  WHEN GROUPING(caller) = 0 THEN 'caller'
+                  ELSE 'total' END AS "dimension!",
+             COALESCE(day::text, method || ' ' || route, tenant, caller, 'total') AS "key!",
+             sum(requests)::bigint AS "requests!",
+             sum(client_errors)::bigint AS "client_errors!",
+             sum(server_errors)::bigint AS "server_errors!"
+           FROM api_usage_daily
+           WHERE day > current_date - $1::int
+             AND ($2::text IS NULL OR tenant = $2)
+             AND ($3::text IS NULL OR route = $3)
+           GROUP BY GROUPING SETS ((day), (method, route), (tenant), (caller), ())
+           ORDER BY 3 DESC, 2"#,
+        days,
+        tenant,
+        route
+    )
+    .fetch_all(pool)
+    .await
+}
@@ -0,0 +1,150 @@
+//! # Cold-start warmup
+//!
+//! - GET /ready  readiness probe: 503 until warmup has finished, then 200
+//!   with what was primed (`/health` stays the liveness probe)
+//!
+//! Warmup runs once, in the background, as soon as the server listens:
+//!
+//! 1. Opens up to `UBL_WARMUP_CONNECTIONS` pool connections (default: every
+//!    one the pool can still open) at once, so the first commits do not pay
+//!    for TCP, TLS and auth. The read replica's pool is primed the same way.
+//! 2. Reads the head of the `UBL_WARMUP_CONTAINERS` most recently committed
+//!    containers (default 32; 0 skips). This prepares the head query on the
+//!    primed connections and pulls those pages into Postgres' buffers.
+//! 3. Resolves the policies governing those containers. Policy sources are
+//!    already compiled when the VM loads, before the server listens, so this
+//!    step only reports bound policies with no active version. Commits on
+//!    such containers would be refused; they are logged, not blocking.
+//!
+//! If a step fails (the database is not reachable yet), warmup retries from
+//! the start and the probe stays at 503.
+
+use axum::{extract::State, http::StatusCode, response::IntoResponse, routing::get, Json, Router};
+use futures_util::future::join_all;
+use serde::Serialize;
+use sqlx::PgPool;
+use std::time::{Duration, Instant};
+use time::OffsetDateTime;
+use tracing::{info, warn};
+
+use crate::AppState;
+
+const DEFAULT_CONTAINERS: i64 = 32;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WarmupReport {
+    pub connections: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replica_connections: Option<u32>,
+    pub containers: usize,
+    pub policies: usize,
+    /// `container_id: policy_id` pairs bound without an active version
+    pub unresolved: Vec<String>,
+    pub elapsed_ms: u64,
+}
+
+fn env_count(name: &str) -> Option<i64> {
+    std::env::var(name).ok().and_then(|n| n.parse().ok()).filter(|&n| n >= 0)
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new().route("/ready", get(route_ready))
+}
+
+/// Run warmup in the background; `/ready` turns green when it finishes
+pub fn spawn(state: AppState) {
+    tokio::spawn(async move {
+        loop {
+            match warm(&state).await {
+                Ok(report) => {
+                    info!(
+                        "🔥 Warmup done in {}ms: {} connection(s), {} container head(s), {} unresolved binding(s)",
+                        report.elapsed_ms,
+                        report.connections,
+                        report.containers,
+                        report.unresolved.len()
+                    );
+                    let _ = state.warmup.set(report);
+                    return;
+                }
+                Err(e) => {
+                    warn!(error = %e, "warmup failed; retrying");
+                    tokio::time::sleep(Duration::from_secs(2)).await;
+                }
+            }
+        }
+    });
+}
+
+async fn prime(pool: &PgPool) -> sqlx::Result<u32> {
+    // Connections held elsewhere (NOTIFY listeners) stay out of reach
+    let free = pool
+        .options()
+        .get_max_connections()
+        .saturating_sub(pool.size() - pool.num_idle() as u32);
+    let n = env_count("UBL_WARMUP_CONNECTIONS").map_or(free, |n| (n as u32).min(free));
+    // Hold every connection until all are open, or the pool would hand back the same one
+    let conns = join_all((0..n).map(|_| pool.acquire()))
+        .await
+        .into_iter()
+        .collect::<sqlx::Result<Vec<_>>>()?;
+    for mut conn in conns {
+        sqlx::query("SELECT 1").execute(&mut *conn).await?;
+    }
+    Ok(n)
+}
+
+async fn warm(state: &AppState) -> anyhow::Result<WarmupReport> {
+    let t = Instant::now();
+    let connections = prime(&state.pool).await?;
+    let replica_connections = match &state.replica {
+        Some(r) => Some(prime(&r.pool).await?),
+        None => None,
+    };
+
+    let limit = env_count("UBL_WARMUP_CONTAINERS").unwrap_or(DEFAULT_CONTAINERS);
+    let hot = if limit == 0 {
+        Vec::new()
+    } else {
+        crate::db::hot_containers(&state.pool, limit).await?
+    };
+    for head in join_all(hot.iter().map(|cid| state.ledger.get_state(cid))).await {
+        head?;
+    }
+
+    let now = OffsetDateTime::now_utc().unix_timestamp();
+    let (policies, unresolved) = {
+        let vm = state.policies.read().unwrap();
+        let unresolved: Vec<String> = hot
+            .iter()
+            .filter_map(|cid| vm.resolve(cid).map(|(_, ids)| (cid, ids)))
+            .flat_map(|(cid, ids)| {
+                ids.into_iter()
+                    .filter(|id| vm.active_version(id, now).is_err())
+                    .map(move |id| format!("{}: {}", cid, id))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        (vm.len(), unresolved)
+    };
+    for u in &unresolved {
+        warn!(binding = %u, "bound policy has no active version; commits will be refused");
+    }
+
+    Ok(WarmupReport {
+        connections,
+        replica_connections,
+        containers: hot.len(),
+        policies,
+        unresolved,
+        elapsed_ms: t.elapsed().as_millis() as u64,
+    })
+}
+
+/// GET /ready
+async fn route_ready(State(state): State<AppState>) -> impl IntoResponse {
+    match state.warmup.get() {
+        Some(report) => (StatusCode::OK, Json(serde_json::json!({ "status": "ready", "warmup": report }))),
+        None => (StatusCode::SERVICE_UNAVAILABLE, Json(serde_json::json!({ "status": "warming_up" }))),
+    }
+}
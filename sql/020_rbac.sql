@@ -0,0 +1,9 @@
+-- Role grants for operator-facing surfaces (debug traces, head tails, admin routes)
+-- Roles are additive to whatever the session scope carries (step-up sessions carry 'admin').
+CREATE TABLE IF NOT EXISTS id_role_grant (
+  sid         text NOT NULL REFERENCES id_subject(sid) ON DELETE CASCADE,
+  role        text NOT NULL CHECK (role IN ('admin','operator','auditor','developer')),
+  granted_by  text,
+  created_at  timestamptz NOT NULL DEFAULT now(),
+  PRIMARY KEY (sid, role)
+);
@@ -0,0 +1,11 @@
+-- TDLN policies (SPEC-UBL-POLICY v1.0 §4)
+-- bytecode_hash = BLAKE3(bytecode), verified by the server on every write and load.
+CREATE TABLE IF NOT EXISTS policy (
+  policy_id      text PRIMARY KEY,
+  version        text NOT NULL,
+  bytecode_hash  text NOT NULL,
+  bytecode       bytea NOT NULL,
+  description    text NOT NULL DEFAULT '',
+  created_at     timestamptz NOT NULL DEFAULT now(),
+  updated_at     timestamptz NOT NULL DEFAULT now()
+);
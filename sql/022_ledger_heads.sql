@@ -0,0 +1,13 @@
+-- Deployment-wide head feed (GET /ledger/heads/tail)
+-- The notification is only a wake-up; subscribers read ledger_entry by id,
+-- so a dropped NOTIFY never loses a head and clients resume via Last-Event-ID.
+CREATE OR REPLACE FUNCTION notify_ledger_head() RETURNS trigger AS $$
+BEGIN
+  PERFORM pg_notify('ledger_heads', NEW.id::text);
+  RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS ledger_heads_notify ON ledger_entry;
+CREATE TRIGGER ledger_heads_notify AFTER INSERT ON ledger_entry
+FOR EACH ROW EXECUTE FUNCTION notify_ledger_head();
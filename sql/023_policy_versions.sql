@@ -0,0 +1,8 @@
+-- Versioned policies: one row per (policy_id, version), each governing from active_from
+-- (unix seconds, compared against the evaluation timestamp) until the next version.
+ALTER TABLE policy ADD COLUMN IF NOT EXISTS active_from bigint NOT NULL DEFAULT 0;
+
+ALTER TABLE policy DROP CONSTRAINT IF EXISTS policy_pkey;
+ALTER TABLE policy ADD PRIMARY KEY (policy_id, version);
+
+CREATE UNIQUE INDEX IF NOT EXISTS ux_policy_activation ON policy (policy_id, active_from);
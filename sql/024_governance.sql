@@ -0,0 +1,12 @@
+-- Cross-references from governance containers (gov://…) to the operational
+-- containers their entries affect. Written in the same transaction as the
+-- governance entry; append-only like the ledger itself.
+CREATE TABLE IF NOT EXISTS governance_ref (
+  governance_container_id  text   NOT NULL,
+  sequence                 bigint NOT NULL,
+  entry_hash               text   NOT NULL,
+  affected_container_id    text   NOT NULL,
+  created_at               timestamptz NOT NULL DEFAULT now(),
+  PRIMARY KEY (governance_container_id, sequence, affected_container_id)
+);
+CREATE INDEX IF NOT EXISTS ix_governance_ref_affected ON governance_ref (affected_container_id, created_at);
@@ -0,0 +1,12 @@
+-- Wake every ubl-server instance when the policy set changes; each one
+-- reloads from this table and swaps its PolicyVM only if all hashes verify.
+CREATE OR REPLACE FUNCTION notify_policy_change() RETURNS trigger AS $$
+BEGIN
+  PERFORM pg_notify('policy_changed', COALESCE(NEW.policy_id, OLD.policy_id));
+  RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS policy_notify ON policy;
+CREATE TRIGGER policy_notify AFTER INSERT OR UPDATE OR DELETE ON policy
+FOR EACH ROW EXECUTE FUNCTION notify_policy_change();
@@ -0,0 +1,19 @@
+-- WebAuthn ceremony attempts (register / login / stepup), one row per outcome.
+-- correlation_id ties a begin to its finish; support looks attempts up by
+-- username or sid. Diagnostic data only: rows may be pruned freely.
+CREATE TABLE IF NOT EXISTS id_ceremony_event (
+  id              bigserial PRIMARY KEY,
+  correlation_id  uuid NOT NULL,
+  ceremony        text NOT NULL CHECK (ceremony IN ('register','login','stepup')),
+  phase           text NOT NULL CHECK (phase IN ('begin','finish')),
+  outcome         text NOT NULL CHECK (outcome IN ('issued','accept','reject')),
+  failure_class   text,
+  username        text,
+  sid             text,
+  challenge_id    uuid,
+  detail          text,
+  created_at      timestamptz NOT NULL DEFAULT now()
+);
+CREATE INDEX IF NOT EXISTS ix_ceremony_username ON id_ceremony_event (username, created_at DESC);
+CREATE INDEX IF NOT EXISTS ix_ceremony_sid ON id_ceremony_event (sid, created_at DESC);
+CREATE INDEX IF NOT EXISTS ix_ceremony_challenge ON id_ceremony_event (challenge_id);
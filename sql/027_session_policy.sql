@@ -0,0 +1,27 @@
+-- Session policies per subject kind and risk tier (tier = session flavor:
+-- 'regular' or 'stepup'). Missing rows fall back to the server's built-in
+-- defaults (auth/session_policy.rs), which these rows mirror.
+CREATE TABLE IF NOT EXISTS id_session_policy (
+  subject_kind    text     NOT NULL CHECK (subject_kind IN ('person','llm','app')),
+  tier            text     NOT NULL CHECK (tier IN ('regular','stepup')),
+  absolute_secs   bigint   NOT NULL CHECK (absolute_secs > 0),
+  idle_secs       bigint   NOT NULL CHECK (idle_secs > 0),
+  max_concurrent  integer  NOT NULL CHECK (max_concurrent > 0),
+  reauth_at_risk  smallint NOT NULL DEFAULT 4 CHECK (reauth_at_risk BETWEEN 0 AND 6),
+  PRIMARY KEY (subject_kind, tier)
+);
+
+INSERT INTO id_session_policy (subject_kind, tier, absolute_secs, idle_secs, max_concurrent, reauth_at_risk) VALUES
+  ('person', 'regular',  3600,  900,  5, 4),
+  ('person', 'stepup',    600,  300,  1, 4),
+  ('llm',    'regular',  3600, 1800, 20, 4),
+  ('llm',    'stepup',    600,  300,  1, 4),
+  ('app',    'regular', 86400, 3600, 50, 4),
+  ('app',    'stepup',    600,  300,  1, 4)
+ON CONFLICT (subject_kind, tier) DO NOTHING;
+
+-- Activity tracking for idle timeouts, and the forced re-auth flag set
+-- after L4+ actions
+ALTER TABLE id_session ADD COLUMN IF NOT EXISTS last_seen_at timestamptz NOT NULL DEFAULT now();
+ALTER TABLE id_session ADD COLUMN IF NOT EXISTS reauth_required boolean NOT NULL DEFAULT false;
+CREATE INDEX IF NOT EXISTS ix_id_session_sid_flavor ON id_session (sid, flavor, last_seen_at DESC);
@@ -0,0 +1,63 @@
+-- Container-scoped administration: policy attachments, composition mode,
+-- and delegated admin grants (grantee SID + capability list + expiry).
+
+CREATE TABLE IF NOT EXISTS container_policy (
+  container_id  text NOT NULL,
+  policy_id     text NOT NULL,
+  attached_by   text NOT NULL,
+  attached_at   timestamptz NOT NULL DEFAULT now(),
+  PRIMARY KEY (container_id, policy_id)
+);
+
+CREATE TABLE IF NOT EXISTS container_composition (
+  container_id  text PRIMARY KEY,
+  mode          text NOT NULL CHECK (mode IN ('all_must_allow','most_restrictive_wins')),
+  updated_by    text NOT NULL,
+  updated_at    timestamptz NOT NULL DEFAULT now()
+);
+
+-- Attachment and composition changes reload the policy set like policy rows do
+DROP TRIGGER IF EXISTS container_policy_notify ON container_policy;
+CREATE TRIGGER container_policy_notify AFTER INSERT OR UPDATE OR DELETE ON container_policy
+FOR EACH ROW EXECUTE FUNCTION notify_policy_change();
+
+CREATE OR REPLACE FUNCTION notify_composition_change() RETURNS trigger AS $$
+BEGIN
+  PERFORM pg_notify('policy_changed', 'composition:' || COALESCE(NEW.container_id, OLD.container_id));
+  RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS container_composition_notify ON container_composition;
+CREATE TRIGGER container_composition_notify AFTER INSERT OR UPDATE OR DELETE ON container_composition
+FOR EACH ROW EXECUTE FUNCTION notify_composition_change();
+
+CREATE TABLE IF NOT EXISTS container_admin_grant (
+  grant_id      uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+  container_id  text NOT NULL,
+  grantee_sid   text NOT NULL REFERENCES id_subject(sid) ON DELETE CASCADE,
+  capabilities  text[] NOT NULL CHECK (capabilities <@ ARRAY['policies','grants','webhooks']::text[] AND cardinality(capabilities) > 0),
+  granted_by    text NOT NULL,
+  granted_at    timestamptz NOT NULL DEFAULT now(),
+  expires_at    timestamptz NOT NULL,
+  revoked_at    timestamptz,
+  revoked_by    text
+);
+
+CREATE INDEX IF NOT EXISTS ix_container_admin_grant_lookup
+  ON container_admin_grant (container_id, grantee_sid) WHERE revoked_at IS NULL;
+
+-- Every grant, revocation and use of a container capability
+CREATE TABLE IF NOT EXISTS container_admin_audit (
+  id            bigserial PRIMARY KEY,
+  container_id  text NOT NULL,
+  grant_id      uuid,
+  actor_sid     text NOT NULL,
+  action        text NOT NULL CHECK (action IN ('grant','revoke','use')),
+  capability    text,
+  detail        jsonb NOT NULL DEFAULT '{}'::jsonb,
+  created_at    timestamptz NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS ix_container_admin_audit_container
+  ON container_admin_audit (container_id, created_at DESC);
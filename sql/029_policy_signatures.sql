@@ -0,0 +1,5 @@
+-- Governance signatures over each policy version (ubl_policy_vm::PolicyBundle).
+-- Array of {"pubkey": hex, "signature": hex}; checked when the VM is built
+-- if UBL_POLICY_GOVERNANCE_KEYS is set.
+
+ALTER TABLE policy ADD COLUMN IF NOT EXISTS signatures jsonb NOT NULL DEFAULT '[]';
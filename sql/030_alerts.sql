@@ -0,0 +1,52 @@
+-- Watch-only alert rules on containers and their notifications.
+-- A rule belongs to one SID and watches one container the SID can read:
+--   balance_below  sum of physics_delta drops under `threshold`
+--   delta_above    one entry's |physics_delta| exceeds `threshold`
+--   inactive_for   no entry for `threshold` hours
+-- Thresholds are i128 decimal strings, like physics_delta.
+
+-- Entries carry their class and delta (as a JSON string) so projections can
+-- be derived; deployments built from 001_ledger.sql gain the columns here
+ALTER TABLE ledger_entry ADD COLUMN IF NOT EXISTS intent_class text;
+ALTER TABLE ledger_entry ADD COLUMN IF NOT EXISTS physics_delta jsonb;
+
+CREATE TABLE IF NOT EXISTS alert_rule (
+  alert_id      uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+  owner_sid     text NOT NULL,
+  container_id  text NOT NULL,
+  kind          text NOT NULL CHECK (kind IN ('balance_below','delta_above','inactive_for')),
+  threshold     text NOT NULL,
+  enabled       boolean NOT NULL DEFAULT true,
+  -- Level-triggered kinds notify once per transition into the alerting state
+  firing        boolean NOT NULL DEFAULT false,
+  created_at    timestamptz NOT NULL DEFAULT now(),
+  updated_at    timestamptz NOT NULL DEFAULT now(),
+  last_fired_at timestamptz
+);
+CREATE INDEX IF NOT EXISTS ix_alert_rule_container ON alert_rule (container_id) WHERE enabled;
+CREATE INDEX IF NOT EXISTS ix_alert_rule_owner ON alert_rule (owner_sid, created_at);
+
+CREATE TABLE IF NOT EXISTS alert_notification (
+  id            bigserial PRIMARY KEY,
+  alert_id      uuid NOT NULL REFERENCES alert_rule(alert_id) ON DELETE CASCADE,
+  owner_sid     text NOT NULL,
+  container_id  text NOT NULL,
+  kind          text NOT NULL,
+  message       text NOT NULL,
+  detail        jsonb NOT NULL DEFAULT '{}'::jsonb,
+  created_at    timestamptz NOT NULL DEFAULT now(),
+  read_at       timestamptz
+);
+CREATE INDEX IF NOT EXISTS ix_alert_notification_owner ON alert_notification (owner_sid, id DESC);
+
+-- Delivery: subscribers wake on the notification id and read the row
+CREATE OR REPLACE FUNCTION notify_alert() RETURNS trigger AS $$
+BEGIN
+  PERFORM pg_notify('alert_notifications', json_build_object('id', NEW.id, 'owner_sid', NEW.owner_sid)::text);
+  RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS alert_notification_notify ON alert_notification;
+CREATE TRIGGER alert_notification_notify AFTER INSERT ON alert_notification
+FOR EACH ROW EXECUTE FUNCTION notify_alert();
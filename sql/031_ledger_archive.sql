@@ -0,0 +1,21 @@
+-- Ledger archive segments written to blob storage (ubl-server blob/, archive_routes.rs).
+-- Each segment holds a contiguous sequence range of one container as JSON
+-- lines; `blake3` is checked on every read back.
+
+CREATE TABLE IF NOT EXISTS ledger_archive (
+  id              bigserial PRIMARY KEY,
+  container_id    text NOT NULL,
+  from_seq        bigint NOT NULL,
+  to_seq          bigint NOT NULL CHECK (to_seq >= from_seq),
+  entries         integer NOT NULL,
+  blob_key        text NOT NULL,
+  blake3          text NOT NULL,
+  size_bytes      bigint NOT NULL,
+  parts           integer NOT NULL,
+  backend         text NOT NULL,
+  last_entry_hash text NOT NULL,
+  created_by      text NOT NULL,
+  created_at      timestamptz NOT NULL DEFAULT now(),
+  UNIQUE (container_id, from_seq)
+);
+CREATE INDEX IF NOT EXISTS ix_ledger_archive_container ON ledger_archive (container_id, to_seq DESC);
@@ -0,0 +1,45 @@
+-- Entry hashes under later hashing formats (ubl-server entry_hash.rs, rehash_routes.rs).
+-- ledger_entry keeps the hashes each entry was written with (v1) forever;
+-- other formats are computed beside them here, one row per entry and format,
+-- and are never rewritten either.
+
+CREATE TABLE IF NOT EXISTS ledger_entry_hash (
+  format        text   NOT NULL,
+  container_id  text   NOT NULL,
+  sequence      bigint NOT NULL,
+  previous_hash text   NOT NULL,
+  entry_hash    text   NOT NULL,
+  created_at    timestamptz NOT NULL DEFAULT now(),
+  PRIMARY KEY (format, container_id, sequence),
+  FOREIGN KEY (container_id, sequence) REFERENCES ledger_entry (container_id, sequence)
+);
+
+CREATE OR REPLACE FUNCTION forbid_entry_hash_mutation() RETURNS trigger AS $$
+BEGIN
+  RAISE EXCEPTION 'ledger_entry_hash is append-only';
+END $$ LANGUAGE plpgsql;
+
+DO $$ BEGIN
+  IF NOT EXISTS (SELECT 1 FROM pg_trigger WHERE tgname = 'ledger_entry_hash_no_update') THEN
+    CREATE TRIGGER ledger_entry_hash_no_update BEFORE UPDATE ON ledger_entry_hash
+      FOR EACH ROW EXECUTE PROCEDURE forbid_entry_hash_mutation();
+  END IF;
+  IF NOT EXISTS (SELECT 1 FROM pg_trigger WHERE tgname = 'ledger_entry_hash_no_delete') THEN
+    CREATE TRIGGER ledger_entry_hash_no_delete BEFORE DELETE ON ledger_entry_hash
+      FOR EACH ROW EXECUTE PROCEDURE forbid_entry_hash_mutation();
+  END IF;
+END $$;
+
+-- Which format the ledger read path serves, and which one appends also write
+-- while a backfill is under way. Single row.
+CREATE TABLE IF NOT EXISTS ledger_hash_format (
+  singleton        boolean PRIMARY KEY DEFAULT true CHECK (singleton),
+  read_format      text NOT NULL DEFAULT 'v1',
+  target_format    text,
+  verified_format  text,
+  verified_entries bigint,
+  verified_at      timestamptz,
+  flipped_at       timestamptz,
+  flipped_by       text
+);
+INSERT INTO ledger_hash_format DEFAULT VALUES ON CONFLICT DO NOTHING;
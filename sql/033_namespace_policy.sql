@@ -0,0 +1,22 @@
+-- Namespace-level policy bindings: a container with no policy of its own in
+-- container_policy is governed by the policies bound to the longest
+-- namespace that prefixes its id ('' binds every container).
+
+CREATE TABLE IF NOT EXISTS namespace_policy (
+  namespace     text NOT NULL,
+  policy_id     text NOT NULL,
+  attached_by   text NOT NULL,
+  attached_at   timestamptz NOT NULL DEFAULT now(),
+  PRIMARY KEY (namespace, policy_id)
+);
+
+CREATE OR REPLACE FUNCTION notify_namespace_policy_change() RETURNS trigger AS $$
+BEGIN
+  PERFORM pg_notify('policy_changed', 'namespace:' || COALESCE(NEW.namespace, OLD.namespace));
+  RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS namespace_policy_notify ON namespace_policy;
+CREATE TRIGGER namespace_policy_notify AFTER INSERT OR UPDATE OR DELETE ON namespace_policy
+FOR EACH ROW EXECUTE FUNCTION notify_namespace_policy_change();
@@ -0,0 +1,39 @@
+-- Evolution commits held for governance approval (UBL_EVOLUTION_APPROVALS).
+-- The draft link waits here until enough distinct SIDs other than the
+-- submitter approve it; the server then appends it and records where it
+-- landed. Approvals are append-only.
+
+CREATE TABLE IF NOT EXISTS evolution_proposal (
+  proposal_id        uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+  container_id       text NOT NULL,
+  link               jsonb NOT NULL,
+  submitted_by       text NOT NULL,
+  submitted_at       timestamptz NOT NULL DEFAULT now(),
+  approvals_required int NOT NULL CHECK (approvals_required > 0),
+  status             text NOT NULL DEFAULT 'pending'
+                     CHECK (status IN ('pending','applied','rejected','failed')),
+  resolved_by        text,
+  resolved_at        timestamptz,
+  reason             text,
+  applied_sequence   bigint,
+  applied_entry_hash text
+);
+CREATE INDEX IF NOT EXISTS ix_evolution_proposal_status ON evolution_proposal (status, submitted_at);
+CREATE INDEX IF NOT EXISTS ix_evolution_proposal_container ON evolution_proposal (container_id, submitted_at);
+
+CREATE TABLE IF NOT EXISTS evolution_approval (
+  proposal_id  uuid NOT NULL REFERENCES evolution_proposal(proposal_id),
+  approver_sid text NOT NULL,
+  approved_at  timestamptz NOT NULL DEFAULT now(),
+  PRIMARY KEY (proposal_id, approver_sid)
+);
+
+CREATE OR REPLACE FUNCTION forbid_evolution_approval_mutation() RETURNS trigger AS $$
+BEGIN
+  RAISE EXCEPTION 'evolution_approval is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trg_evolution_approval_no_update ON evolution_approval;
+CREATE TRIGGER trg_evolution_approval_no_update BEFORE UPDATE OR DELETE ON evolution_approval
+  FOR EACH ROW EXECUTE FUNCTION forbid_evolution_approval_mutation();
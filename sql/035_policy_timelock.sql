@@ -0,0 +1,34 @@
+-- Governance timelock for policy activations (UBL_POLICY_TIMELOCK).
+-- Each version records the risk level it was activated at; its active_from
+-- is at least the timelock for that level after registration. Versions
+-- registered before the timelock count as L4, the risk every policy write
+-- carries for session step-up.
+
+ALTER TABLE policy ADD COLUMN IF NOT EXISTS risk_level smallint NOT NULL DEFAULT 4
+  CHECK (risk_level BETWEEN 0 AND 5);
+
+-- Activations cancelled during their window by the veto pact
+-- (UBL_POLICY_VETO_PACT). The vetoed row leaves `policy`; this keeps what
+-- it was and the proof that stopped it. Append-only.
+CREATE TABLE IF NOT EXISTS policy_veto (
+  policy_id      text NOT NULL,
+  version        text NOT NULL,
+  bytecode_hash  text NOT NULL,
+  risk_level     smallint NOT NULL,
+  active_from    bigint NOT NULL,
+  pact_id        text NOT NULL,
+  signatures     jsonb NOT NULL,
+  vetoed_by      text NOT NULL,
+  vetoed_at      timestamptz NOT NULL DEFAULT now()
+);
+CREATE INDEX IF NOT EXISTS ix_policy_veto_policy ON policy_veto (policy_id, vetoed_at);
+
+CREATE OR REPLACE FUNCTION forbid_policy_veto_mutation() RETURNS trigger AS $$
+BEGIN
+  RAISE EXCEPTION 'policy_veto is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trg_policy_veto_no_update ON policy_veto;
+CREATE TRIGGER trg_policy_veto_no_update BEFORE UPDATE OR DELETE ON policy_veto
+  FOR EACH ROW EXECUTE FUNCTION forbid_policy_veto_mutation();
@@ -0,0 +1,22 @@
+-- Container dependencies declared by the manifest on a container's first
+-- link (LinkDraft.manifest.depends_on). Every dependency existed when the
+-- container was created, so the graph is acyclic and creation order is a
+-- valid startup order. Rows land with the genesis entry; append-only.
+
+CREATE TABLE IF NOT EXISTS container_dependency (
+  container_id  text NOT NULL,
+  depends_on    text NOT NULL CHECK (depends_on <> container_id),
+  declared_at   timestamptz NOT NULL DEFAULT now(),
+  PRIMARY KEY (container_id, depends_on)
+);
+CREATE INDEX IF NOT EXISTS ix_container_dependency_depends_on ON container_dependency (depends_on);
+
+CREATE OR REPLACE FUNCTION forbid_container_dependency_mutation() RETURNS trigger AS $$
+BEGIN
+  RAISE EXCEPTION 'container_dependency is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trg_container_dependency_no_update ON container_dependency;
+CREATE TRIGGER trg_container_dependency_no_update BEFORE UPDATE OR DELETE ON container_dependency
+  FOR EACH ROW EXECUTE FUNCTION forbid_container_dependency_mutation();
@@ -0,0 +1,19 @@
+-- API usage analytics: request counts per UTC day, route, tenant and caller,
+-- accumulated in memory by the server's usage layer and added here on each
+-- flush (see ubl-server/src/usage.rs). `route` is the matched route template
+-- (`/state/:container_id`), `tenant` the X-UBL-Tenant header ('-' if absent)
+-- and `caller` the session's SID ('anonymous' without a session). Rows older
+-- than UBL_USAGE_RETENTION_DAYS are deleted by the flusher.
+
+CREATE TABLE IF NOT EXISTS api_usage_daily (
+  day            date   NOT NULL,
+  method         text   NOT NULL,
+  route          text   NOT NULL,
+  tenant         text   NOT NULL,
+  caller         text   NOT NULL,
+  requests       bigint NOT NULL DEFAULT 0,
+  client_errors  bigint NOT NULL DEFAULT 0,
+  server_errors  bigint NOT NULL DEFAULT 0,
+  PRIMARY KEY (day, method, route, tenant, caller)
+);
+CREATE INDEX IF NOT EXISTS ix_api_usage_daily_tenant ON api_usage_daily (tenant, day);
@@ -0,0 +1,24 @@
+-- Content-addressed atom store (see ubl-server/src/atom_store). `atom_hash`
+-- is the BLAKE3 of the atom's canonical bytes, uncompressed, so it is the
+-- same hash a link commits to however the atom is kept. The bytes are split
+-- in `chunk_size` chunks; with codec 'zstd' each chunk is an independent
+-- zstd frame, so a range read only fetches and decompresses what it needs.
+
+CREATE TABLE IF NOT EXISTS atom_store (
+  atom_hash    text        PRIMARY KEY,
+  size         bigint      NOT NULL,
+  stored_size  bigint      NOT NULL,
+  codec        text        NOT NULL CHECK (codec IN ('raw', 'zstd')),
+  chunk_size   integer     NOT NULL,
+  chunks       integer     NOT NULL,
+  created_at   timestamptz NOT NULL DEFAULT now()
+);
+
+CREATE TABLE IF NOT EXISTS atom_chunk (
+  atom_hash  text    NOT NULL REFERENCES atom_store (atom_hash),
+  idx        integer NOT NULL,
+  data       bytea   NOT NULL,
+  PRIMARY KEY (atom_hash, idx)
+);
+-- Chunks are compressed already; keep TOAST from compressing them again
+ALTER TABLE atom_chunk ALTER COLUMN data SET STORAGE EXTERNAL;
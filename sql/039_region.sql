@@ -0,0 +1,35 @@
+-- Multi-region active-passive (ubl-server region.rs). Every region keeps its
+-- own database; the standby copies ledger_entry rows from the primary with
+-- their ids, so `id` is the replication cursor on both sides.
+
+-- This region's role. `epoch` is the fencing token: it grows by one on every
+-- promotion, and a region fenced by a higher epoch never appends again.
+-- Single row; absent when region mode is off.
+CREATE TABLE IF NOT EXISTS region_node (
+  singleton  boolean     PRIMARY KEY DEFAULT true CHECK (singleton),
+  region     text        NOT NULL,
+  role       text        NOT NULL CHECK (role IN ('primary', 'standby', 'fenced')),
+  epoch      bigint      NOT NULL DEFAULT 0,
+  fenced_by  bigint,
+  updated_at timestamptz NOT NULL DEFAULT now()
+);
+
+-- Chain heads of every container as of feed cursor `cursor_id`, written by
+-- the primary and replicated to the standby, which is promoted only while
+-- it holds every one of them.
+CREATE TABLE IF NOT EXISTS region_checkpoint (
+  epoch      bigint      NOT NULL,
+  cursor_id  bigint      NOT NULL,
+  region     text        NOT NULL,
+  heads      jsonb       NOT NULL,
+  created_at timestamptz NOT NULL DEFAULT now(),
+  PRIMARY KEY (epoch, cursor_id)
+);
+
+-- Primary side: the feed cursor each standby last reported applying
+CREATE TABLE IF NOT EXISTS region_standby (
+  region     text        PRIMARY KEY,
+  epoch      bigint      NOT NULL,
+  applied_id bigint      NOT NULL,
+  updated_at timestamptz NOT NULL DEFAULT now()
+);
@@ -0,0 +1,48 @@
+-- Marketplace settlements (ubl-server settlement.rs). One request appends a
+-- batch of buyer debits and the merchant credit that balances them in a
+-- single transaction; these rows record which entries belong together.
+-- Both tables are append-only, and replicated to standby regions with the
+-- ledger entries they point at.
+
+-- The settlement and its credit entry. `atom` is the credit link's atom
+-- (canonical JSON of the items), so `credit_atom_hash` can be recomputed.
+CREATE TABLE IF NOT EXISTS settlement (
+  settlement_id          text        PRIMARY KEY,
+  merchant_container_id  text        NOT NULL,
+  credit_sequence        bigint      NOT NULL,
+  credit_entry_hash      text        NOT NULL,
+  credit_atom_hash       text        NOT NULL,
+  atom                   jsonb       NOT NULL,
+  total                  text        NOT NULL,  -- i128 string, > 0
+  item_count             int         NOT NULL CHECK (item_count > 0),
+  created_by             text,
+  created_at             timestamptz NOT NULL DEFAULT now(),
+  UNIQUE (merchant_container_id, credit_sequence)
+);
+
+-- One debit entry per item, in request order. No foreign key: a standby may
+-- receive an item before its settlement row.
+CREATE TABLE IF NOT EXISTS settlement_item (
+  settlement_id  text   NOT NULL,
+  item           int    NOT NULL,
+  container_id   text   NOT NULL,
+  sequence       bigint NOT NULL,
+  entry_hash     text   NOT NULL,
+  amount         text   NOT NULL,  -- i128 string, the debit's physics_delta
+  PRIMARY KEY (settlement_id, item),
+  UNIQUE (container_id, sequence)
+);
+
+CREATE OR REPLACE FUNCTION forbid_settlement_mutation() RETURNS trigger AS $$
+BEGIN
+  RAISE EXCEPTION '% is append-only', TG_TABLE_NAME;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trg_settlement_no_update ON settlement;
+CREATE TRIGGER trg_settlement_no_update BEFORE UPDATE OR DELETE ON settlement
+  FOR EACH ROW EXECUTE FUNCTION forbid_settlement_mutation();
+
+DROP TRIGGER IF EXISTS trg_settlement_item_no_update ON settlement_item;
+CREATE TRIGGER trg_settlement_item_no_update BEFORE UPDATE OR DELETE ON settlement_item
+  FOR EACH ROW EXECUTE FUNCTION forbid_settlement_mutation();
@@ -0,0 +1,56 @@
+-- Pacts registered with the server (ubl-server pact_routes.rs) and the
+-- containers that operate under one. A container names its authority pact
+-- in the manifest on its first link (LinkDraft.manifest.authority_pact);
+-- while that pact is outside its window or revoked, the commit pipeline
+-- admits only Observation links to the container, and full operation
+-- resumes once the pact is renewed.
+
+-- Current terms of each pact. Revocation and renewal update the row;
+-- every change is also recorded in pact_event.
+CREATE TABLE IF NOT EXISTS pact (
+  pact_id        text        PRIMARY KEY,
+  pact           jsonb       NOT NULL,  -- ubl_pact::Pact, window as last renewed
+  not_before     bigint      NOT NULL,  -- unix seconds
+  not_after      bigint      NOT NULL CHECK (not_after >= not_before),
+  revoked_at     timestamptz,
+  revoked_by     text,
+  revoke_reason  text,
+  registered_by  text,
+  registered_at  timestamptz NOT NULL DEFAULT now()
+);
+
+-- Registrations, revocations and renewals, append-only
+CREATE TABLE IF NOT EXISTS pact_event (
+  id          bigserial   PRIMARY KEY,
+  pact_id     text        NOT NULL REFERENCES pact (pact_id),
+  kind        text        NOT NULL CHECK (kind IN ('registered', 'revoked', 'renewed')),
+  not_after   bigint      NOT NULL,
+  reason      text,
+  actor       text,
+  created_at  timestamptz NOT NULL DEFAULT now()
+);
+CREATE INDEX IF NOT EXISTS ix_pact_event_pact ON pact_event (pact_id, id);
+
+-- Authority pact of a container, landed with its genesis entry; append-only
+-- and replicated to standby regions with that entry. No foreign key: a
+-- standby may receive the row before the pact is registered there.
+CREATE TABLE IF NOT EXISTS container_authority (
+  container_id  text        PRIMARY KEY,
+  pact_id       text        NOT NULL,
+  declared_at   timestamptz NOT NULL DEFAULT now()
+);
+CREATE INDEX IF NOT EXISTS ix_container_authority_pact ON container_authority (pact_id);
+
+CREATE OR REPLACE FUNCTION forbid_pact_history_mutation() RETURNS trigger AS $$
+BEGIN
+  RAISE EXCEPTION '% is append-only', TG_TABLE_NAME;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trg_pact_event_no_update ON pact_event;
+CREATE TRIGGER trg_pact_event_no_update BEFORE UPDATE OR DELETE ON pact_event
+  FOR EACH ROW EXECUTE FUNCTION forbid_pact_history_mutation();
+
+DROP TRIGGER IF EXISTS trg_container_authority_no_update ON container_authority;
+CREATE TRIGGER trg_container_authority_no_update BEFORE UPDATE OR DELETE ON container_authority
+  FOR EACH ROW EXECUTE FUNCTION forbid_pact_history_mutation();
@@ -0,0 +1,43 @@
+-- Break-glass overrides (ubl-server break_glass.rs). An operator asks to
+-- bypass one failing subsystem for a bounded time, with a justification;
+-- a second SID approves before the override is in force. Every commit made
+-- under it is recorded in break_glass_action, and closing (by hand or on
+-- expiry) stores a report of what was done.
+
+CREATE TABLE IF NOT EXISTS break_glass (
+  override_id    uuid        PRIMARY KEY DEFAULT gen_random_uuid(),
+  subsystem      text        NOT NULL CHECK (subsystem IN ('policy', 'evolution_queue')),
+  justification  text        NOT NULL CHECK (length(btrim(justification)) > 0),
+  ttl_secs       int         NOT NULL CHECK (ttl_secs > 0),
+  requested_by   text        NOT NULL,
+  requested_at   timestamptz NOT NULL DEFAULT now(),
+  approved_by    text        CHECK (approved_by <> requested_by),
+  approved_at    timestamptz,
+  expires_at     timestamptz,               -- approved_at + ttl_secs
+  closed_at      timestamptz,
+  closed_by      text,                      -- NULL when it lapsed
+  close_reason   text,
+  report         jsonb                      -- set when closed
+);
+CREATE INDEX IF NOT EXISTS ix_break_glass_open ON break_glass (subsystem) WHERE closed_at IS NULL;
+
+-- Actions taken under an override, append-only
+CREATE TABLE IF NOT EXISTS break_glass_action (
+  id           bigserial   PRIMARY KEY,
+  override_id  uuid        NOT NULL REFERENCES break_glass (override_id),
+  actor        text        NOT NULL,
+  action       text        NOT NULL,
+  detail       jsonb       NOT NULL DEFAULT '{}'::jsonb,
+  created_at   timestamptz NOT NULL DEFAULT now()
+);
+CREATE INDEX IF NOT EXISTS ix_break_glass_action_override ON break_glass_action (override_id, id);
+
+CREATE OR REPLACE FUNCTION forbid_break_glass_action_mutation() RETURNS trigger AS $$
+BEGIN
+  RAISE EXCEPTION 'break_glass_action is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trg_break_glass_action_no_update ON break_glass_action;
+CREATE TRIGGER trg_break_glass_action_no_update BEFORE UPDATE OR DELETE ON break_glass_action
+  FOR EACH ROW EXECUTE FUNCTION forbid_break_glass_action_mutation();
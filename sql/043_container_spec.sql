@@ -0,0 +1,22 @@
+-- Spec version each container is committed under (SPEC-UBL-* version,
+-- ubl_kernel::SpecVersion). Declared by the manifest on a container's first
+-- link (LinkDraft.manifest.spec_version), or the server's latest when it
+-- names none; every later link is validated under the rules of that
+-- version. Containers created before this table have no row and are v1.0.
+-- Rows land with the genesis entry; append-only, replicated with it.
+
+CREATE TABLE IF NOT EXISTS container_spec (
+  container_id  text        PRIMARY KEY,
+  spec_version  text        NOT NULL,
+  declared_at   timestamptz NOT NULL DEFAULT now()
+);
+
+CREATE OR REPLACE FUNCTION forbid_container_spec_mutation() RETURNS trigger AS $$
+BEGIN
+  RAISE EXCEPTION 'container_spec is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trg_container_spec_no_update ON container_spec;
+CREATE TRIGGER trg_container_spec_no_update BEFORE UPDATE OR DELETE ON container_spec
+  FOR EACH ROW EXECUTE FUNCTION forbid_container_spec_mutation();
@@ -0,0 +1,26 @@
+-- Percentage rollouts of policy versions (ubl_policy_vm::Rollout): a
+-- version with a row here governs only the share of containers (or actors)
+-- its schedule puts on it; the rest stay on the version before it. The
+-- schedule and halt are times, so replays select the version a link was
+-- committed under. Halts are written by the server's rollout monitor when
+-- the candidate's deny rate breaches the rollout's limit.
+
+CREATE TABLE IF NOT EXISTS policy_rollout (
+  policy_id   text        NOT NULL,
+  version     text        NOT NULL,
+  rollout     jsonb       NOT NULL,
+  updated_by  text        NOT NULL,
+  updated_at  timestamptz NOT NULL DEFAULT now(),
+  PRIMARY KEY (policy_id, version)
+);
+
+CREATE OR REPLACE FUNCTION notify_policy_rollout_change() RETURNS trigger AS $$
+BEGIN
+  PERFORM pg_notify('policy_changed', 'rollout:' || COALESCE(NEW.policy_id, OLD.policy_id));
+  RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS policy_rollout_notify ON policy_rollout;
+CREATE TRIGGER policy_rollout_notify AFTER INSERT OR UPDATE OR DELETE ON policy_rollout
+FOR EACH ROW EXECUTE FUNCTION notify_policy_rollout_change();
@@ -0,0 +1,30 @@
+-- Pact creation ceremony (ubl-server pact_drafts.rs): a pact is proposed as
+-- a draft addressed by its draft_hash (ubl_pact::Pact::draft_hash), each of
+-- its signers adds a signature over the draft, and it is activated into
+-- `pact` once the signatures meet the pact's own quorum. Drafts are never
+-- edited: changed terms are a new draft with a new hash.
+
+CREATE TABLE IF NOT EXISTS pact_draft (
+  draft_hash    text        PRIMARY KEY,
+  pact_id       text        NOT NULL,
+  pact          jsonb       NOT NULL,  -- ubl_pact::Pact as proposed
+  proposed_by   text        NOT NULL,
+  proposed_at   timestamptz NOT NULL DEFAULT now(),
+  activated_by  text,
+  activated_at  timestamptz
+);
+CREATE INDEX IF NOT EXISTS ix_pact_draft_pact ON pact_draft (pact_id);
+
+-- Adoption signatures, one per signer key; append-only
+CREATE TABLE IF NOT EXISTS pact_draft_signature (
+  draft_hash  text        NOT NULL REFERENCES pact_draft (draft_hash),
+  pubkey      text        NOT NULL,
+  signature   text        NOT NULL,  -- Ed25519 over Pact::adoption_bytes
+  signed_by   text        NOT NULL,  -- session that submitted it
+  signed_at   timestamptz NOT NULL DEFAULT now(),
+  PRIMARY KEY (draft_hash, pubkey)
+);
+
+DROP TRIGGER IF EXISTS trg_pact_draft_signature_no_update ON pact_draft_signature;
+CREATE TRIGGER trg_pact_draft_signature_no_update BEFORE UPDATE OR DELETE ON pact_draft_signature
+  FOR EACH ROW EXECUTE FUNCTION forbid_pact_history_mutation();
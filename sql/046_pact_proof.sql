@@ -0,0 +1,28 @@
+-- Pact proofs being assembled on the server (ubl-server pact_proofs.rs),
+-- one per target link hash: the signing request exported for it
+-- (ubl_pact::offline::SigningRequestFile) and the signatures collected so
+-- far, each checked against the request before it is stored. Collecting a
+-- quorum can take hours; the rows let it span requests and restarts.
+
+CREATE TABLE IF NOT EXISTS pact_proof_request (
+  link_hash     text        PRIMARY KEY,  -- lowercase hex
+  pact_id       text        NOT NULL REFERENCES pact (pact_id),
+  request       jsonb       NOT NULL,     -- SigningRequestFile
+  requested_by  text        NOT NULL,
+  requested_at  timestamptz NOT NULL DEFAULT now(),
+  completed_at  timestamptz               -- set when the quorum is first met
+);
+
+-- Signatures collected, one per signer key; append-only
+CREATE TABLE IF NOT EXISTS pact_proof_signature (
+  link_hash  text        NOT NULL REFERENCES pact_proof_request (link_hash),
+  pubkey     text        NOT NULL,
+  signature  text        NOT NULL,  -- Ed25519 over PactProof::signing_bytes
+  signed_by  text        NOT NULL,  -- session that submitted it
+  signed_at  timestamptz NOT NULL DEFAULT now(),
+  PRIMARY KEY (link_hash, pubkey)
+);
+
+DROP TRIGGER IF EXISTS trg_pact_proof_signature_no_update ON pact_proof_signature;
+CREATE TRIGGER trg_pact_proof_signature_no_update BEFORE UPDATE OR DELETE ON pact_proof_signature
+  FOR EACH ROW EXECUTE FUNCTION forbid_pact_history_mutation();
@@ -0,0 +1,4 @@
+-- Dormancy attestations (ubl-server attestation.rs) look up the checkpoints
+-- a region wrote around a time range.
+
+CREATE INDEX IF NOT EXISTS idx_region_checkpoint_time ON region_checkpoint (region, created_at);
@@ -0,0 +1,8 @@
+-- Pact renewal by succession (ubl-server pact_routes.rs, SPEC-UBL-PACT
+-- v1.0 §9.3): a successor pact names the pact it renews, so audit tooling
+-- can walk a lineage from any of its pacts. A pact has at most one
+-- successor.
+
+ALTER TABLE pact ADD COLUMN IF NOT EXISTS predecessor text REFERENCES pact (pact_id);
+
+CREATE UNIQUE INDEX IF NOT EXISTS ux_pact_predecessor ON pact (predecessor) WHERE predecessor IS NOT NULL;
@@ -0,0 +1,11 @@
+-- Pact usage accounting (ubl-server pact_proofs.rs, SPEC-UBL-PACT v1.0
+-- §4.2 `max_uses`): proofs completed under each pact, counted against its
+-- optional limit (1 for a single-use emergency authorization). The count
+-- is raised in the transaction that completes a proof, and only while it
+-- is below the limit, so two instances cannot both spend the last use.
+
+CREATE TABLE IF NOT EXISTS pact_usage (
+  pact_id       text        PRIMARY KEY REFERENCES pact (pact_id),
+  uses          bigint      NOT NULL,
+  last_used_at  timestamptz NOT NULL DEFAULT now()
+);
@@ -0,0 +1,24 @@
+-- Pact validation audit log (ubl-server pact_audit.rs): one row per pact
+-- proof validation the server records through its `ubl_pact::ValidationSink`,
+-- accepted or not, with the signatures that counted and those rejected.
+-- Append-only, for forensic review. `pact_id` is not a foreign key: proofs
+-- naming an unknown pact are recorded too.
+
+CREATE TABLE IF NOT EXISTS pact_validation_event (
+  id            bigserial   PRIMARY KEY,
+  pact_id       text        NOT NULL,
+  container_id  text        NOT NULL,
+  link_hash     text        NOT NULL,   -- lowercase
+  nonce         bigint      NOT NULL,
+  intent_class  smallint    NOT NULL,
+  validated_at  bigint      NOT NULL,   -- unix seconds, the `now` validated at
+  accepted      boolean     NOT NULL,
+  error_code    text,                   -- PactError code (GET /errors) when refused
+  error         text,
+  counted       jsonb       NOT NULL DEFAULT '[]',  -- keys whose signatures counted
+  rejected      jsonb       NOT NULL DEFAULT '[]',  -- [{pubkey, reason}]
+  recorded_at   timestamptz NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS pact_validation_event_pact_idx ON pact_validation_event (pact_id, id);
+CREATE INDEX IF NOT EXISTS pact_validation_event_link_idx ON pact_validation_event (link_hash);
@@ -0,0 +1,26 @@
+-- Pact cache invalidation (ubl-server pact_cache.rs): every change to a
+-- registered pact, and every container authority declared, is announced on
+-- the `pact_changed` channel so each instance drops its cached copy.
+-- Payload: 'pact:<pact_id>' or 'container:<container_id>'.
+
+CREATE OR REPLACE FUNCTION notify_pact_change() RETURNS trigger AS $$
+BEGIN
+  PERFORM pg_notify('pact_changed', 'pact:' || COALESCE(NEW.pact_id, OLD.pact_id));
+  RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS pact_notify ON pact;
+CREATE TRIGGER pact_notify AFTER INSERT OR UPDATE OR DELETE ON pact
+FOR EACH ROW EXECUTE FUNCTION notify_pact_change();
+
+CREATE OR REPLACE FUNCTION notify_container_authority() RETURNS trigger AS $$
+BEGIN
+  PERFORM pg_notify('pact_changed', 'container:' || NEW.container_id);
+  RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS container_authority_notify ON container_authority;
+CREATE TRIGGER container_authority_notify AFTER INSERT ON container_authority
+FOR EACH ROW EXECUTE FUNCTION notify_container_authority();
@@ -0,0 +1,29 @@
+-- Emergency freezes of the commit path (ubl-server pact_freeze.rs,
+-- SPEC-UBL-PACT v1.0 §9.7): one row per freeze or lift, each taken on a
+-- proof under a Global emergency pact. The latest row is the current state;
+-- the commit path is frozen while it is a 'freeze'. A proof acts once:
+-- (pact_id, action, nonce) is unique. Append-only.
+
+CREATE TABLE IF NOT EXISTS pact_freeze (
+  id          bigserial   PRIMARY KEY,
+  pact_id     text        NOT NULL REFERENCES pact (pact_id),
+  action      text        NOT NULL CHECK (action IN ('freeze', 'lift')),
+  nonce       bigint      NOT NULL,
+  reason      text        NOT NULL,
+  actor       text        NOT NULL,   -- session that submitted the proof
+  at          timestamptz NOT NULL DEFAULT now(),
+  UNIQUE (pact_id, action, nonce)
+);
+
+-- Announced on `pact_changed` (sql/051) so each instance's pact cache drops
+-- the freeze state it holds. Payload: 'freeze:<pact_id>'.
+CREATE OR REPLACE FUNCTION notify_pact_freeze() RETURNS trigger AS $$
+BEGIN
+  PERFORM pg_notify('pact_changed', 'freeze:' || NEW.pact_id);
+  RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS pact_freeze_notify ON pact_freeze;
+CREATE TRIGGER pact_freeze_notify AFTER INSERT ON pact_freeze
+FOR EACH ROW EXECUTE FUNCTION notify_pact_freeze();